@@ -0,0 +1,278 @@
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::action::ActionPlan;
+use crate::config::{Config, MailAccountConfig};
+use crate::infra::contacts::{Contact, UserContacts};
+use crate::infra::email::EmailAddress;
+
+/// Words introducing the account in "from my work email", "pelo email
+/// pessoal" and the like; the account's name follows them
+const MENTION_PREFIXES: &[&str] = &[
+    "from my ",
+    "from the ",
+    "from ",
+    "using my ",
+    "using ",
+    "via my ",
+    "via ",
+    "do meu ",
+    "pelo meu ",
+    "pelo ",
+    "com o meu ",
+    "com meu ",
+    "usando o meu ",
+    "usando meu ",
+];
+
+/// What made the selector pick an account
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountReason {
+    /// The request names it, e.g. "from my work email"
+    Mentioned,
+    /// The recipient's contact says so
+    Contact,
+    Domain,
+    Intent,
+    Default,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccountChoice {
+    pub account_id: String,
+    pub reason: AccountReason,
+}
+
+/// Picks the `[[accounts]]` entry an email is sent from. An explicit
+/// mention in the request wins, then the recipient's contact, the
+/// recipient's domain, the intent and finally the default account. `None`
+/// leaves the email to `[smtp]`.
+#[derive(Debug, Clone)]
+pub struct AccountSelector {
+    accounts: Vec<MailAccountConfig>,
+    contacts: Arc<UserContacts>,
+}
+
+impl AccountSelector {
+    pub fn new(accounts: Vec<MailAccountConfig>) -> Self {
+        Self {
+            accounts,
+            contacts: Arc::new(UserContacts::default()),
+        }
+    }
+
+    pub fn from_config(accounts: &[MailAccountConfig]) -> Self {
+        Self::new(accounts.to_vec())
+    }
+
+    pub fn configured() -> Self {
+        Self::from_config(&Config::get().accounts)
+    }
+
+    /// Address book holding each contact's account
+    pub fn with_contacts(mut self, contacts: Arc<UserContacts>) -> Self {
+        self.contacts = contacts;
+        self
+    }
+
+    pub fn accounts(&self) -> &[MailAccountConfig] {
+        &self.accounts
+    }
+
+    /// Account for `plan`, made from `request`
+    pub fn select(&self, request: &str, plan: &ActionPlan) -> Option<AccountChoice> {
+        let choice = |account: &MailAccountConfig, reason| AccountChoice {
+            account_id: account.id.clone(),
+            reason,
+        };
+        if let Some(account) = self.mentioned(request) {
+            return Some(choice(account, AccountReason::Mentioned));
+        }
+        let recipients: Vec<(Option<&Contact>, Option<EmailAddress>)> = plan
+            .recipients
+            .iter()
+            .map(|recipient| self.recipient(recipient))
+            .collect();
+        if let Some(account) = recipients.iter().find_map(|(contact, _)| {
+            let id = contact.and_then(|contact| contact.account.as_deref())?;
+            self.accounts.iter().find(|account| account.id == id)
+        }) {
+            return Some(choice(account, AccountReason::Contact));
+        }
+        if let Some(account) = recipients.iter().find_map(|(_, address)| {
+            let domain = address.as_ref()?.ascii_domain();
+            self.accounts.iter().find(|account| {
+                account
+                    .domains
+                    .iter()
+                    .any(|candidate| candidate.eq_ignore_ascii_case(domain))
+            })
+        }) {
+            return Some(choice(account, AccountReason::Domain));
+        }
+        if let Some(account) = self
+            .accounts
+            .iter()
+            .find(|account| account.intents.contains(&plan.intent))
+        {
+            return Some(choice(account, AccountReason::Intent));
+        }
+        self.accounts
+            .iter()
+            .find(|account| account.default)
+            .map(|account| choice(account, AccountReason::Default))
+    }
+
+    /// `plan` with the selected account, if any
+    pub fn assign(&self, request: &str, plan: ActionPlan) -> ActionPlan {
+        match self.select(request, &plan) {
+            Some(choice) => plan.with_account(&choice.account_id),
+            None => plan,
+        }
+    }
+
+    /// The account whose longest name follows a mention prefix
+    fn mentioned(&self, request: &str) -> Option<&MailAccountConfig> {
+        let request = request.to_lowercase();
+        self.accounts
+            .iter()
+            .flat_map(|account| account.names.iter().map(move |name| (account, name)))
+            .filter(|(_, name)| {
+                let name = name.trim().to_lowercase();
+                !name.is_empty()
+                    && MENTION_PREFIXES
+                        .iter()
+                        .any(|prefix| request.contains(&format!("{}{}", prefix, name)))
+            })
+            .max_by_key(|(_, name)| name.trim().len())
+            .map(|(account, _)| account)
+    }
+
+    /// Recipients are stored as the user wrote them: an address or a name
+    fn recipient(&self, recipient: &str) -> (Option<&Contact>, Option<EmailAddress>) {
+        match EmailAddress::parse(recipient) {
+            Ok(address) => (self.contacts.find_by_email(&address), Some(address)),
+            Err(_) => {
+                let contact = self.contacts.find_by_name(recipient);
+                let address = contact.and_then(|contact| contact.primary_email()).cloned();
+                (contact, address)
+            }
+        }
+    }
+}
+
+impl Default for AccountSelector {
+    fn default() -> Self {
+        Self::configured()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::Params;
+    use crate::agent::{ClassificationResult, Intent};
+
+    fn account(id: &str, names: &[&str], domains: &[&str]) -> MailAccountConfig {
+        MailAccountConfig {
+            id: id.to_string(),
+            names: names.iter().map(|name| name.to_string()).collect(),
+            domains: domains.iter().map(|domain| domain.to_string()).collect(),
+            intents: Vec::new(),
+            default: false,
+            smtp: Default::default(),
+            imap: None,
+        }
+    }
+
+    fn selector() -> AccountSelector {
+        let work = MailAccountConfig {
+            intents: vec![Intent::ScheduleMeeting],
+            ..account(
+                "work",
+                &["work email", "email do trabalho"],
+                &["company.com"],
+            )
+        };
+        let personal = MailAccountConfig {
+            default: true,
+            ..account("personal", &["personal email", "gmail"], &[])
+        };
+        let contacts = UserContacts::from_json_str(
+            r#"{"contacts": [
+                {"id": "c1", "displayName": "Aiko", "account": "personal",
+                 "emails": [{"type": "work", "address": "aiko@company.com"}]},
+                {"id": "c2", "displayName": "Bruno",
+                 "emails": [{"type": "work", "address": "bruno@company.com"}]}
+            ]}"#,
+        )
+        .unwrap();
+        AccountSelector::new(vec![work, personal]).with_contacts(Arc::new(contacts))
+    }
+
+    fn plan(intent: Intent, recipient: &str) -> ActionPlan {
+        let result = ClassificationResult::new(
+            intent,
+            Params::with_values(recipient.to_string(), "Hi".to_string()),
+        );
+        ActionPlan::from_classification(1, &result)
+    }
+
+    fn reason(request: &str, plan: &ActionPlan) -> Option<(String, AccountReason)> {
+        selector()
+            .select(request, plan)
+            .map(|choice| (choice.account_id, choice.reason))
+    }
+
+    #[test]
+    fn test_selection_order() {
+        let to_aiko = plan(Intent::SendEmail, "Aiko");
+
+        assert_eq!(
+            reason("Send it to Aiko from my work email", &to_aiko),
+            Some(("work".to_string(), AccountReason::Mentioned))
+        );
+        assert_eq!(
+            reason("Envie para Bruno pelo email do trabalho", &to_aiko),
+            Some(("work".to_string(), AccountReason::Mentioned))
+        );
+        assert_eq!(
+            reason("Send it to Aiko", &to_aiko),
+            Some(("personal".to_string(), AccountReason::Contact))
+        );
+        assert_eq!(
+            reason("Send it to Bruno", &plan(Intent::SendEmail, "Bruno")),
+            Some(("work".to_string(), AccountReason::Domain))
+        );
+        assert_eq!(
+            reason("Meet Eva", &plan(Intent::ScheduleMeeting, "eva@gmail.com")),
+            Some(("work".to_string(), AccountReason::Intent))
+        );
+        assert_eq!(
+            reason("Hi Eva", &plan(Intent::SendEmail, "eva@gmail.com")),
+            Some(("personal".to_string(), AccountReason::Default))
+        );
+    }
+
+    #[test]
+    fn test_unmentioned_name_and_no_accounts() {
+        let to_eva = plan(Intent::SendEmail, "eva@gmail.com");
+
+        assert_eq!(
+            AccountSelector::new(vec![account("work", &["work"], &[])])
+                .select("Ask Eva about work", &to_eva),
+            None
+        );
+        assert_eq!(
+            AccountSelector::new(Vec::new())
+                .assign("from my work email", to_eva.clone())
+                .account_id,
+            None
+        );
+        assert_eq!(
+            selector().assign("Hi Eva", to_eva).account_id.as_deref(),
+            Some("personal")
+        );
+    }
+}
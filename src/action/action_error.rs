@@ -0,0 +1,61 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::action::PlanStatus;
+use crate::safety::PolicyViolation;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionError {
+    PlanNotFound(u64),
+    InvalidTransition {
+        id: u64,
+        status: PlanStatus,
+    },
+    ElevatedApprovalRequired(u64),
+    /// The action already left the outbox and can no longer be undone
+    UndoWindowClosed(u64),
+    /// The draft breaks a blocking content policy rule and is never queued
+    ContentBlocked(Vec<PolicyViolation>),
+    /// The final draft has findings nobody reviewed; the plan waits for
+    /// confirmation again
+    ContentFlagged {
+        id: u64,
+        violations: Vec<PolicyViolation>,
+    },
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::PlanNotFound(id) => write!(f, "Action plan {} not found", id),
+            ActionError::InvalidTransition { id, status } => {
+                write!(f, "Action plan {} is already {}", id, status)
+            }
+            ActionError::ElevatedApprovalRequired(id) => {
+                write!(f, "Action plan {} needs an approver to confirm it", id)
+            }
+            ActionError::UndoWindowClosed(id) => {
+                write!(
+                    f,
+                    "Action plan {} was already sent and cannot be undone",
+                    id
+                )
+            }
+            ActionError::ContentBlocked(violations) => {
+                let details: Vec<&str> = violations.iter().map(|v| v.detail.as_str()).collect();
+                write!(f, "Draft blocked by content policy: {}", details.join("; "))
+            }
+            ActionError::ContentFlagged { id, violations } => {
+                let details: Vec<&str> = violations.iter().map(|v| v.detail.as_str()).collect();
+                write!(
+                    f,
+                    "Action plan {} was flagged by content policy and needs review: {}",
+                    id,
+                    details.join("; ")
+                )
+            }
+        }
+    }
+}
+
+impl Error for ActionError {}
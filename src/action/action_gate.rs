@@ -0,0 +1,827 @@
+use chrono::{Local, NaiveDateTime};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::action::{
+    AccountSelector, ActionError, ActionPlan, ConfirmationPolicy, ConfirmedPlan, PlanStatus,
+};
+use crate::agent::ClassificationResult;
+use crate::agent::toxicity::ToxicityAgent;
+use crate::infra::contacts::UserContacts;
+use crate::infra::email::EmailAddress;
+use crate::safety::{ContentPolicy, PolicyReport, RecipientAnomalyDetector, RecipientHistory};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Proposal {
+    /// The plan waits for `ActionGate::confirm` or `ActionGate::reject`
+    NeedsConfirmation(ActionPlan),
+    /// The intent is auto-confirmed by policy and may run right away
+    AutoConfirmed(ConfirmedPlan),
+}
+
+/// Holds proposed action plans until the user confirms or rejects them
+#[derive(Debug, Default)]
+pub struct ActionGate {
+    policy: ConfirmationPolicy,
+    content_policy: ContentPolicy,
+    /// Judges final drafts when `toxicity_check` is on; Ollama by default
+    toxicity: Option<Arc<ToxicityAgent>>,
+    recipient_checks: Option<RecipientChecks>,
+    /// Picks the account each email plan is sent from; `[smtp]` when unset
+    accounts: Option<AccountSelector>,
+    plans: Mutex<HashMap<u64, ActionPlan>>,
+    /// Session each pending plan was proposed in, if any
+    sessions: Mutex<HashMap<u64, String>>,
+    /// When each plan that can no longer change state got there
+    settled: Mutex<HashMap<u64, NaiveDateTime>>,
+    next_id: AtomicU64,
+}
+
+impl ActionGate {
+    pub fn new(policy: ConfirmationPolicy) -> Self {
+        Self {
+            policy,
+            content_policy: ContentPolicy::default(),
+            toxicity: None,
+            recipient_checks: None,
+            accounts: None,
+            plans: Mutex::new(HashMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+            settled: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Drafts are checked against `content_policy` before they are proposed
+    pub fn with_content_policy(mut self, content_policy: ContentPolicy) -> Self {
+        self.content_policy = content_policy;
+        self
+    }
+
+    /// Final drafts are judged by `agent` when the content policy asks for
+    /// a toxicity check
+    pub fn with_toxicity_agent(mut self, agent: ToxicityAgent) -> Self {
+        self.toxicity = Some(Arc::new(agent));
+        self
+    }
+
+    /// Recipients are resolved through `contacts` and inspected by
+    /// `detector`; anomalous plans need `confirm_elevated`. Saved contacts
+    /// and the recipients of executed plans count as contacted before.
+    pub fn with_recipient_checks(
+        mut self,
+        detector: RecipientAnomalyDetector,
+        contacts: Arc<UserContacts>,
+    ) -> Self {
+        let mut history = RecipientHistory::new();
+        for contact in contacts.contacts() {
+            for email in &contact.emails {
+                history.know(&email.address);
+            }
+        }
+        self.recipient_checks = Some(RecipientChecks {
+            detector,
+            contacts,
+            history: Mutex::new(history),
+        });
+        self
+    }
+
+    /// Plans are sent from the account `accounts` picks for their message
+    /// and recipients
+    pub fn with_accounts(mut self, accounts: AccountSelector) -> Self {
+        self.accounts = Some(accounts);
+        self
+    }
+
+    /// A gate with the same policies and recipient history and no plans,
+    /// for dry runs
+    pub fn detached(&self) -> Self {
+        Self {
+            recipient_checks: self
+                .recipient_checks
+                .as_ref()
+                .map(|checks| RecipientChecks {
+                    detector: checks.detector.clone(),
+                    contacts: checks.contacts.clone(),
+                    history: Mutex::new(checks.history.lock().unwrap().clone()),
+                }),
+            toxicity: self.toxicity.clone(),
+            accounts: self.accounts.clone(),
+            ..Self::new(self.policy.clone()).with_content_policy(self.content_policy.clone())
+        }
+    }
+
+    pub fn policy(&self) -> &ConfirmationPolicy {
+        &self.policy
+    }
+
+    /// Plans whose draft breaks a blocking content rule are refused; flagged
+    /// drafts wait for the user even if their intent is auto-confirmed.
+    /// A plan with recipient anomalies is never auto-confirmed and can only
+    /// be confirmed with `confirm_elevated`. Plans answering angry mail
+    /// always wait for the user.
+    pub fn propose(&self, result: &ClassificationResult) -> Result<Proposal, ActionError> {
+        self.propose_plan(None, result, None)
+    }
+
+    /// Like `propose` within a session; a plan still waiting for the user
+    /// when the session ends expires with it
+    pub fn propose_in_session(
+        &self,
+        session_id: &str,
+        result: &ClassificationResult,
+    ) -> Result<Proposal, ActionError> {
+        self.propose_plan(Some(session_id), result, None)
+    }
+
+    /// Like `propose_in_session` for a request that repeats plan
+    /// `duplicate_of`; it waits for the user even if its intent is
+    /// auto-confirmed
+    pub fn propose_duplicate(
+        &self,
+        session_id: &str,
+        result: &ClassificationResult,
+        duplicate_of: u64,
+    ) -> Result<Proposal, ActionError> {
+        self.propose_plan(Some(session_id), result, Some(duplicate_of))
+    }
+
+    fn propose_plan(
+        &self,
+        session_id: Option<&str>,
+        result: &ClassificationResult,
+        duplicate_of: Option<u64>,
+    ) -> Result<Proposal, ActionError> {
+        let report = result
+            .params
+            .message()
+            .map(|body| self.content_policy.check(body))
+            .unwrap_or_default();
+        if report.is_blocked() {
+            return Err(ActionError::ContentBlocked(report.violations));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut plan = ActionPlan::from_classification(id, result);
+        if let Some(accounts) = &self.accounts {
+            plan = accounts.assign(result.params.message().unwrap_or_default(), plan);
+        }
+        if let Some(checks) = &self.recipient_checks {
+            plan.anomalies = checks.detector.inspect(
+                &checks.history.lock().unwrap(),
+                &checks.resolve(&plan.all_recipients()),
+            );
+        }
+        plan.policy_flags = report.violations;
+        plan.duplicate_of = duplicate_of;
+
+        if plan.requires_elevated_approval()
+            || !plan.policy_flags.is_empty()
+            || plan.duplicate_of.is_some()
+            || result.needs_human()
+            || self.policy.requires_confirmation_for(result)
+        {
+            self.plans.lock().unwrap().insert(id, plan.clone());
+            if let Some(session_id) = session_id {
+                self.sessions
+                    .lock()
+                    .unwrap()
+                    .insert(id, session_id.to_string());
+            }
+            Ok(Proposal::NeedsConfirmation(plan))
+        } else {
+            plan.status = PlanStatus::Confirmed;
+            self.plans.lock().unwrap().insert(id, plan.clone());
+            Ok(Proposal::AutoConfirmed(ConfirmedPlan::new(plan)))
+        }
+    }
+
+    /// The content policy's findings on `body`, the model's toxicity
+    /// verdict included when `toxicity_check` is on
+    pub async fn check_content(&self, body: &str) -> PolicyReport {
+        match &self.toxicity {
+            Some(agent) => {
+                self.content_policy
+                    .check_with_toxicity(body, agent.as_ref())
+                    .await
+            }
+            None => {
+                self.content_policy
+                    .check_with_toxicity(body, &ToxicityAgent::new())
+                    .await
+            }
+        }
+    }
+
+    /// Checks `body`, what a confirmed plan will send once it is final. A
+    /// blocking finding rejects the plan; a finding of a rule the plan was
+    /// not flagged for puts it back to wait for the user.
+    pub async fn review(
+        &self,
+        confirmed: ConfirmedPlan,
+        body: &str,
+    ) -> Result<ConfirmedPlan, ActionError> {
+        let report = self.check_content(body).await;
+        let mut plans = self.plans.lock().unwrap();
+        let plan = plans
+            .get_mut(&confirmed.id())
+            .ok_or(ActionError::PlanNotFound(confirmed.id()))?;
+        if plan.status != PlanStatus::Confirmed {
+            return Err(ActionError::InvalidTransition {
+                id: plan.id,
+                status: plan.status.clone(),
+            });
+        }
+        plan.content = Some(body.to_string());
+        if report.is_blocked() {
+            plan.status = PlanStatus::Rejected;
+            return Err(ActionError::ContentBlocked(report.violations));
+        }
+        // By rule: an unavailable toxicity check words its finding anew
+        let unreviewed: Vec<_> = report
+            .violations
+            .into_iter()
+            .filter(|found| !plan.policy_flags.iter().any(|flag| flag.rule == found.rule))
+            .collect();
+        if unreviewed.is_empty() {
+            return Ok(ConfirmedPlan::new(plan.clone()));
+        }
+        plan.policy_flags.extend(unreviewed.clone());
+        plan.status = PlanStatus::PendingConfirmation;
+        Err(ActionError::ContentFlagged {
+            id: plan.id,
+            violations: unreviewed,
+        })
+    }
+
+    pub fn get(&self, id: u64) -> Option<ActionPlan> {
+        self.plans.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Every plan in any state, oldest first
+    pub fn plans(&self) -> Vec<ActionPlan> {
+        let mut plans: Vec<ActionPlan> = self.plans.lock().unwrap().values().cloned().collect();
+        plans.sort_by_key(|p| p.id);
+        plans
+    }
+
+    pub fn pending(&self) -> Vec<ActionPlan> {
+        let mut pending: Vec<ActionPlan> = self
+            .plans
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|p| p.status == PlanStatus::PendingConfirmation)
+            .cloned()
+            .collect();
+        pending.sort_by_key(|p| p.id);
+        pending
+    }
+
+    pub fn confirm(&self, id: u64) -> Result<ConfirmedPlan, ActionError> {
+        if self
+            .get(id)
+            .is_some_and(|plan| plan.requires_elevated_approval())
+        {
+            return Err(ActionError::ElevatedApprovalRequired(id));
+        }
+        let plan = self.transition(id, PlanStatus::Confirmed)?;
+        Ok(ConfirmedPlan::new(plan))
+    }
+
+    /// Confirmation by an approver, also accepted for anomalous plans
+    pub fn confirm_elevated(&self, id: u64) -> Result<ConfirmedPlan, ActionError> {
+        let plan = self.transition(id, PlanStatus::Confirmed)?;
+        Ok(ConfirmedPlan::new(plan))
+    }
+
+    pub fn reject(&self, id: u64) -> Result<ActionPlan, ActionError> {
+        self.transition(id, PlanStatus::Rejected)
+    }
+
+    /// Records that the side effect of a confirmed plan has run
+    pub fn mark_executed(&self, confirmed: ConfirmedPlan) -> Result<ActionPlan, ActionError> {
+        let mut plans = self.plans.lock().unwrap();
+        let plan = plans
+            .get_mut(&confirmed.id())
+            .ok_or(ActionError::PlanNotFound(confirmed.id()))?;
+        if plan.status != PlanStatus::Confirmed {
+            return Err(ActionError::InvalidTransition {
+                id: plan.id,
+                status: plan.status.clone(),
+            });
+        }
+        plan.status = PlanStatus::Executed;
+        self.settle(plan.id);
+        if let Some(checks) = &self.recipient_checks {
+            let recipients = checks.resolve(&plan.all_recipients());
+            checks.history.lock().unwrap().record(&recipients);
+        }
+        Ok(plan.clone())
+    }
+
+    /// Takes back a plan confirmed in an earlier run, e.g. one saved with
+    /// the outbox, so it can still be executed or cancelled
+    pub(crate) fn readmit(&self, plan: ActionPlan) -> ConfirmedPlan {
+        self.next_id.fetch_max(plan.id, Ordering::SeqCst);
+        let confirmed = ConfirmedPlan::new(plan);
+        self.plans
+            .lock()
+            .unwrap()
+            .insert(confirmed.id(), confirmed.plan().clone());
+        confirmed
+    }
+
+    /// Records that a confirmed plan was undone before its side effect ran
+    pub fn mark_cancelled(&self, confirmed: ConfirmedPlan) -> Result<ActionPlan, ActionError> {
+        self.finish(confirmed, PlanStatus::Cancelled)
+    }
+
+    /// Records that the side effect of a confirmed plan was refused for good
+    pub fn mark_failed(&self, confirmed: ConfirmedPlan) -> Result<ActionPlan, ActionError> {
+        self.finish(confirmed, PlanStatus::Failed)
+    }
+
+    fn finish(
+        &self,
+        confirmed: ConfirmedPlan,
+        status: PlanStatus,
+    ) -> Result<ActionPlan, ActionError> {
+        let mut plans = self.plans.lock().unwrap();
+        let plan = plans
+            .get_mut(&confirmed.id())
+            .ok_or(ActionError::PlanNotFound(confirmed.id()))?;
+        if plan.status != PlanStatus::Confirmed {
+            return Err(ActionError::InvalidTransition {
+                id: plan.id,
+                status: plan.status.clone(),
+            });
+        }
+        plan.status = status;
+        self.settle(plan.id);
+        Ok(plan.clone())
+    }
+
+    fn settle(&self, id: u64) {
+        self.settled
+            .lock()
+            .unwrap()
+            .insert(id, Local::now().naive_local());
+    }
+
+    /// Forgets the plans settled (executed, rejected, cancelled, failed or
+    /// expired) before `cutoff`; returns how many were dropped
+    pub fn prune_settled_before(&self, cutoff: NaiveDateTime) -> usize {
+        let ids: Vec<u64> = {
+            let mut settled = self.settled.lock().unwrap();
+            let ids = settled
+                .iter()
+                .filter(|(_, at)| **at < cutoff)
+                .map(|(id, _)| *id)
+                .collect();
+            settled.retain(|_, at| *at >= cutoff);
+            ids
+        };
+        let mut plans = self.plans.lock().unwrap();
+        ids.iter().filter(|id| plans.remove(id).is_some()).count()
+    }
+
+    /// Expires the plans of `session_id` still waiting for the user, so
+    /// they cannot be confirmed once the session is gone
+    pub fn expire_session(&self, session_id: &str) -> Vec<ActionPlan> {
+        let ids: Vec<u64> = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let ids = sessions
+                .iter()
+                .filter(|(_, session)| session.as_str() == session_id)
+                .map(|(id, _)| *id)
+                .collect();
+            sessions.retain(|_, session| session.as_str() != session_id);
+            ids
+        };
+        let mut expired: Vec<ActionPlan> = ids
+            .into_iter()
+            .filter_map(|id| self.transition(id, PlanStatus::Expired).ok())
+            .collect();
+        expired.sort_by_key(|plan| plan.id);
+        expired
+    }
+
+    fn transition(&self, id: u64, status: PlanStatus) -> Result<ActionPlan, ActionError> {
+        self.sessions.lock().unwrap().remove(&id);
+        let mut plans = self.plans.lock().unwrap();
+        let plan = plans.get_mut(&id).ok_or(ActionError::PlanNotFound(id))?;
+        if plan.status != PlanStatus::PendingConfirmation {
+            return Err(ActionError::InvalidTransition {
+                id,
+                status: plan.status.clone(),
+            });
+        }
+        if status != PlanStatus::Confirmed {
+            self.settle(id);
+        }
+        plan.status = status;
+        Ok(plan.clone())
+    }
+}
+
+/// What a gate compares recipients against
+#[derive(Debug)]
+struct RecipientChecks {
+    detector: RecipientAnomalyDetector,
+    contacts: Arc<UserContacts>,
+    history: Mutex<RecipientHistory>,
+}
+
+impl RecipientChecks {
+    /// Addresses of recipients given by address or by contact name;
+    /// names that match no contact are left out
+    fn resolve(&self, recipients: &[String]) -> Vec<EmailAddress> {
+        recipients
+            .iter()
+            .filter_map(|recipient| {
+                if EmailAddress::looks_like_address(recipient) {
+                    EmailAddress::parse(recipient).ok()
+                } else {
+                    self.contacts
+                        .find_by_name(recipient)
+                        .and_then(|contact| contact.primary_email())
+                        .cloned()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Intent;
+    use crate::agent::classifier::Params;
+    use crate::agent::sentiment::{Sentiment, SentimentAssessment};
+    use crate::config::ContentPolicyConfig;
+    use crate::safety::{PolicyRule, RecipientAnomaly};
+
+    fn send_email() -> ClassificationResult {
+        ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva@company.com".to_string(), "Hi".to_string()),
+        )
+    }
+
+    #[test]
+    fn test_send_email_needs_confirmation() {
+        let gate = ActionGate::default();
+
+        let proposal = gate.propose(&send_email()).unwrap();
+
+        let Proposal::NeedsConfirmation(plan) = proposal else {
+            panic!("Expected NeedsConfirmation");
+        };
+        assert_eq!(gate.pending(), vec![plan]);
+    }
+
+    #[test]
+    fn test_confirm_then_execute() {
+        let gate = ActionGate::default();
+        let Proposal::NeedsConfirmation(plan) = gate.propose(&send_email()).unwrap() else {
+            panic!("Expected NeedsConfirmation");
+        };
+
+        let confirmed = gate.confirm(plan.id).unwrap();
+        let executed = gate.mark_executed(confirmed).unwrap();
+
+        assert_eq!(executed.status, PlanStatus::Executed);
+        assert!(gate.pending().is_empty());
+    }
+
+    #[test]
+    fn test_rejected_plan_cannot_be_confirmed() {
+        let gate = ActionGate::default();
+        let Proposal::NeedsConfirmation(plan) = gate.propose(&send_email()).unwrap() else {
+            panic!("Expected NeedsConfirmation");
+        };
+
+        gate.reject(plan.id).unwrap();
+
+        assert_eq!(
+            gate.confirm(plan.id),
+            Err(ActionError::InvalidTransition {
+                id: plan.id,
+                status: PlanStatus::Rejected
+            })
+        );
+    }
+
+    #[test]
+    fn test_confirmed_plan_executes_once() {
+        let gate = ActionGate::default();
+        let Proposal::NeedsConfirmation(plan) = gate.propose(&send_email()).unwrap() else {
+            panic!("Expected NeedsConfirmation");
+        };
+        let confirmed = gate.confirm(plan.id).unwrap();
+
+        gate.mark_executed(confirmed.clone()).unwrap();
+
+        assert!(gate.mark_executed(confirmed).is_err());
+    }
+
+    #[test]
+    fn test_cancelled_plan_is_not_executed() {
+        let gate = ActionGate::new(ConfirmationPolicy::new(vec![Intent::SendEmail]));
+        let Proposal::AutoConfirmed(confirmed) = gate.propose(&send_email()).unwrap() else {
+            panic!("Expected AutoConfirmed");
+        };
+
+        let cancelled = gate.mark_cancelled(confirmed.clone()).unwrap();
+
+        assert_eq!(cancelled.status, PlanStatus::Cancelled);
+        assert!(gate.mark_executed(confirmed).is_err());
+    }
+
+    #[test]
+    fn test_auto_confirm_policy() {
+        let gate = ActionGate::new(ConfirmationPolicy::new(vec![Intent::SendEmail]));
+
+        let proposal = gate.propose(&send_email()).unwrap();
+
+        assert!(matches!(proposal, Proposal::AutoConfirmed(_)));
+        assert!(gate.pending().is_empty());
+    }
+
+    #[test]
+    fn test_angry_mail_is_never_auto_confirmed() {
+        let gate = ActionGate::new(ConfirmationPolicy::new(vec![Intent::SendEmail]));
+        let result = send_email().with_sentiment(SentimentAssessment::new(
+            Sentiment::Angry,
+            "threatens to cancel".to_string(),
+        ));
+
+        assert!(matches!(
+            gate.propose(&result).unwrap(),
+            Proposal::NeedsConfirmation(_)
+        ));
+    }
+
+    fn recipient_checks() -> ActionGate {
+        ActionGate::new(ConfirmationPolicy::new(vec![Intent::SendEmail])).with_recipient_checks(
+            RecipientAnomalyDetector::default(),
+            Arc::new(UserContacts::load_from_file("spec/contacts.json").unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_anomalous_plan_needs_elevated_approval() {
+        let gate = recipient_checks();
+
+        let Proposal::NeedsConfirmation(plan) = gate.propose(&send_email()).unwrap() else {
+            panic!("Expected NeedsConfirmation");
+        };
+
+        assert_eq!(
+            plan.anomalies,
+            vec![RecipientAnomaly::FirstContact {
+                recipient: "eva@company.com".to_string(),
+            }]
+        );
+        assert_eq!(
+            gate.confirm(plan.id),
+            Err(ActionError::ElevatedApprovalRequired(plan.id))
+        );
+        assert!(gate.confirm_elevated(plan.id).is_ok());
+    }
+
+    #[test]
+    fn test_bcc_recipients_are_inspected() {
+        let gate = recipient_checks();
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("Tiggy".to_string(), "Running late".to_string())
+                .with_bcc(&["stranger@elsewhere.com"]),
+        );
+
+        let Proposal::NeedsConfirmation(plan) = gate.propose(&result).unwrap() else {
+            panic!("Expected NeedsConfirmation");
+        };
+
+        assert_eq!(
+            plan.anomalies,
+            vec![RecipientAnomaly::FirstContact {
+                recipient: "stranger@elsewhere.com".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_contacts_and_executed_recipients_are_known() {
+        let gate = recipient_checks();
+        let to_contact = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("Tiggy".to_string(), "Hi".to_string()),
+        );
+        let Proposal::NeedsConfirmation(first) = gate.propose(&send_email()).unwrap() else {
+            panic!("Expected NeedsConfirmation");
+        };
+        gate.mark_executed(gate.confirm_elevated(first.id).unwrap())
+            .unwrap();
+
+        assert!(matches!(
+            gate.propose(&to_contact).unwrap(),
+            Proposal::AutoConfirmed(_)
+        ));
+        assert!(matches!(
+            gate.propose(&send_email()).unwrap(),
+            Proposal::AutoConfirmed(_)
+        ));
+    }
+
+    fn content_policy() -> ContentPolicy {
+        ContentPolicy::new(ContentPolicyConfig {
+            banned_phrases: vec!["guaranteed returns".to_string()],
+            max_length: Some(10),
+            flag_only: vec![PolicyRule::MaxLength],
+            ..ContentPolicyConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_blocked_draft_is_never_proposed() {
+        let gate = ActionGate::new(ConfirmationPolicy::new(vec![Intent::SendEmail]))
+            .with_content_policy(content_policy());
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values(
+                "eva@company.com".to_string(),
+                "Guaranteed returns".to_string(),
+            ),
+        );
+
+        let Err(ActionError::ContentBlocked(violations)) = gate.propose(&result) else {
+            panic!("Expected ContentBlocked");
+        };
+
+        assert_eq!(violations[0].rule, PolicyRule::BannedPhrase);
+        assert!(gate.plans().is_empty());
+    }
+
+    #[test]
+    fn test_flagged_draft_is_never_auto_confirmed() {
+        let gate = ActionGate::new(ConfirmationPolicy::new(vec![Intent::SendEmail]))
+            .with_content_policy(content_policy());
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values(
+                "eva@company.com".to_string(),
+                "Running a little late".to_string(),
+            ),
+        );
+
+        let Proposal::NeedsConfirmation(plan) = gate.propose(&result).unwrap() else {
+            panic!("Expected NeedsConfirmation");
+        };
+
+        assert_eq!(plan.policy_flags[0].rule, PolicyRule::MaxLength);
+    }
+
+    fn toxicity_gate(reply: &str) -> ActionGate {
+        ActionGate::new(ConfirmationPolicy::new(vec![Intent::SendEmail]))
+            .with_content_policy(ContentPolicy::new(ContentPolicyConfig {
+                banned_phrases: vec!["guaranteed returns".to_string()],
+                flag_only: vec![PolicyRule::Toxicity],
+                toxicity_check: true,
+                ..ContentPolicyConfig::default()
+            }))
+            .with_toxicity_agent(ToxicityAgent::new().with_chat_model(Arc::new(
+                crate::infra::ollama::MockOllamaClient::new().with_fallback(reply),
+            )))
+    }
+
+    #[tokio::test]
+    async fn test_toxic_final_draft_waits_for_review() {
+        let gate = toxicity_gate(r#"{"toxic": true, "reason": "insults the reader"}"#);
+        let Proposal::AutoConfirmed(confirmed) = gate.propose(&send_email()).unwrap() else {
+            panic!("Expected AutoConfirmed");
+        };
+
+        let flagged = gate.review(confirmed, "You useless clown").await;
+        let plan = gate.get(1).unwrap();
+        let reviewed = gate
+            .review(gate.confirm(1).unwrap(), "You useless clown")
+            .await;
+
+        assert!(matches!(
+            flagged,
+            Err(ActionError::ContentFlagged { id: 1, violations })
+                if violations[0].rule == PolicyRule::Toxicity
+        ));
+        assert_eq!(plan.status, PlanStatus::PendingConfirmation);
+        assert_eq!(plan.content.as_deref(), Some("You useless clown"));
+        assert!(reviewed.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_blocked_final_draft_rejects_the_plan() {
+        let gate = toxicity_gate(r#"{"toxic": false}"#);
+        let Proposal::AutoConfirmed(confirmed) = gate.propose(&send_email()).unwrap() else {
+            panic!("Expected AutoConfirmed");
+        };
+
+        let reviewed = gate
+            .review(confirmed, "Guaranteed returns if you sign today")
+            .await;
+
+        assert!(matches!(reviewed, Err(ActionError::ContentBlocked(_))));
+        assert_eq!(gate.get(1).unwrap().status, PlanStatus::Rejected);
+    }
+
+    #[test]
+    fn test_pending_plans_expire_with_their_session() {
+        let gate = ActionGate::new(ConfirmationPolicy::new(vec![]));
+        let result = send_email();
+
+        let first = gate.propose_in_session("u1", &result).unwrap();
+        let other = gate.propose_in_session("u2", &result).unwrap();
+        let confirmed = gate.propose_in_session("u1", &result).unwrap();
+        let (
+            Proposal::NeedsConfirmation(first),
+            Proposal::NeedsConfirmation(other),
+            Proposal::NeedsConfirmation(confirmed),
+        ) = (first, other, confirmed)
+        else {
+            panic!("Expected NeedsConfirmation");
+        };
+        gate.confirm(confirmed.id).unwrap();
+
+        let expired = gate.expire_session("u1");
+
+        assert_eq!(
+            expired.iter().map(|plan| plan.id).collect::<Vec<_>>(),
+            vec![first.id]
+        );
+        assert_eq!(gate.get(first.id).unwrap().status, PlanStatus::Expired);
+        assert!(matches!(
+            gate.confirm(first.id),
+            Err(ActionError::InvalidTransition { .. })
+        ));
+        assert_eq!(
+            gate.get(other.id).unwrap().status,
+            PlanStatus::PendingConfirmation
+        );
+        assert!(gate.expire_session("u1").is_empty());
+    }
+
+    #[test]
+    fn test_plans_are_sent_from_the_selected_account() {
+        let work = crate::config::MailAccountConfig {
+            id: "work".to_string(),
+            names: Vec::new(),
+            domains: vec!["company.com".to_string()],
+            intents: Vec::new(),
+            default: false,
+            smtp: Default::default(),
+            imap: None,
+        };
+        let gate = ActionGate::default().with_accounts(AccountSelector::new(vec![work]));
+
+        let Proposal::NeedsConfirmation(plan) = gate.propose(&send_email()).unwrap() else {
+            panic!("Expected NeedsConfirmation");
+        };
+
+        assert_eq!(plan.account_id.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn test_settled_plans_are_pruned() {
+        let gate = ActionGate::default();
+        let propose = || match gate.propose(&send_email()).unwrap() {
+            Proposal::NeedsConfirmation(plan) => plan.id,
+            Proposal::AutoConfirmed(_) => panic!("Expected NeedsConfirmation"),
+        };
+        let (rejected, executed, pending, confirmed) = (propose(), propose(), propose(), propose());
+        gate.reject(rejected).unwrap();
+        gate.mark_executed(gate.confirm(executed).unwrap()).unwrap();
+        gate.confirm(confirmed).unwrap();
+        let now = Local::now().naive_local();
+
+        assert_eq!(
+            gate.prune_settled_before(now - chrono::Duration::minutes(1)),
+            0
+        );
+        assert_eq!(
+            gate.prune_settled_before(now + chrono::Duration::minutes(1)),
+            2
+        );
+
+        let ids: Vec<u64> = gate.plans().iter().map(|plan| plan.id).collect();
+        assert_eq!(ids, vec![pending, confirmed]);
+    }
+
+    #[test]
+    fn test_unknown_plan() {
+        let gate = ActionGate::default();
+
+        assert_eq!(gate.confirm(42), Err(ActionError::PlanNotFound(42)));
+    }
+}
@@ -0,0 +1,329 @@
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::agent::classifier::{IntentDetails, Params};
+use crate::agent::sentiment::Sentiment;
+use crate::agent::{ClassificationResult, Intent};
+use crate::i18n::resolve_date_time;
+use crate::safety::{PolicyViolation, RecipientAnomaly};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    PendingConfirmation,
+    Confirmed,
+    Rejected,
+    Executed,
+    /// Retracted from the outbox during the undo window
+    Cancelled,
+    /// Still unconfirmed when the session it was proposed in ended
+    Expired,
+    /// Refused by the mail server; sending it again would fail again
+    Failed,
+}
+
+impl fmt::Display for PlanStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanStatus::PendingConfirmation => write!(f, "pending_confirmation"),
+            PlanStatus::Confirmed => write!(f, "confirmed"),
+            PlanStatus::Rejected => write!(f, "rejected"),
+            PlanStatus::Executed => write!(f, "executed"),
+            PlanStatus::Cancelled => write!(f, "cancelled"),
+            PlanStatus::Expired => write!(f, "expired"),
+            PlanStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionTiming {
+    Immediately,
+    /// Held in the outbox until this local time
+    At(NaiveDateTime),
+}
+
+impl ActionTiming {
+    /// Timing for a requested send time; unrecognized or past times send
+    /// immediately
+    pub fn resolve(send_at: Option<&str>, now: NaiveDateTime) -> Self {
+        match send_at.and_then(|text| resolve_date_time(text, now)) {
+            Some(at) if at > now => ActionTiming::At(at),
+            _ => ActionTiming::Immediately,
+        }
+    }
+
+    pub fn scheduled_for(&self) -> Option<NaiveDateTime> {
+        match self {
+            ActionTiming::Immediately => None,
+            ActionTiming::At(at) => Some(*at),
+        }
+    }
+}
+
+/// What an action will do, shown to the user before any side effect
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ActionPlan {
+    pub id: u64,
+    pub intent: Intent,
+    pub recipients: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cc: Vec<String>,
+    /// Not shown to the other recipients, but counted like them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bcc: Vec<String>,
+    /// Subject the request gave or the model extracted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    pub content: Option<String>,
+    /// Files the request referred to, as the user named them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    pub timing: ActionTiming,
+    pub status: PlanStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anomalies: Vec<RecipientAnomaly>,
+    /// Content policy findings a human has to review before it is sent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_flags: Vec<PolicyViolation>,
+    /// Earlier plan of the same session this one repeats
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<u64>,
+    /// Skips the recipient's quiet hours
+    #[serde(default)]
+    pub urgent: bool,
+    /// `[[accounts]]` entry it is sent from; `[smtp]` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+}
+
+impl ActionPlan {
+    pub fn from_classification(id: u64, result: &ClassificationResult) -> Self {
+        Self::from_classification_at(id, result, Local::now().naive_local())
+    }
+
+    /// Like `from_classification`, resolving "send tomorrow" against `now`
+    pub fn from_classification_at(
+        id: u64,
+        result: &ClassificationResult,
+        now: NaiveDateTime,
+    ) -> Self {
+        Self {
+            id,
+            intent: result.intent.clone(),
+            recipients: result
+                .params
+                .recipient()
+                .map(|r| vec![r.to_string()])
+                .unwrap_or_default(),
+            cc: result.params.cc(),
+            bcc: result.params.bcc(),
+            subject: match &result.details {
+                Some(IntentDetails::SendEmail(details)) if !details.subject.is_empty() => {
+                    Some(details.subject.clone())
+                }
+                _ => result.params.subject().map(str::to_string),
+            },
+            content: result.params.message().map(str::to_string),
+            attachments: result.params.attachments(),
+            timing: ActionTiming::resolve(result.params.send_at(), now),
+            status: PlanStatus::PendingConfirmation,
+            anomalies: Vec::new(),
+            policy_flags: Vec::new(),
+            duplicate_of: None,
+            urgent: result
+                .sentiment
+                .as_ref()
+                .is_some_and(|assessment| assessment.sentiment == Sentiment::Urgent),
+            account_id: None,
+        }
+    }
+
+    /// Everyone the action reaches: recipients, cc and bcc
+    pub fn all_recipients(&self) -> Vec<String> {
+        self.recipients
+            .iter()
+            .chain(&self.cc)
+            .chain(&self.bcc)
+            .cloned()
+            .collect()
+    }
+
+    /// Request the plan carries out, rebuilt from its fields for the
+    /// intent handlers run when it leaves the outbox
+    pub fn classification(&self) -> ClassificationResult {
+        let cc: Vec<&str> = self.cc.iter().map(String::as_str).collect();
+        let bcc: Vec<&str> = self.bcc.iter().map(String::as_str).collect();
+        let attachments: Vec<&str> = self.attachments.iter().map(String::as_str).collect();
+        let params = Params::new(self.recipients.first().cloned(), self.content.clone())
+            .with_cc(&cc)
+            .with_bcc(&bcc)
+            .with_attachments(&attachments);
+        let params = match &self.subject {
+            Some(subject) => params.with_subject(subject),
+            None => params,
+        };
+        ClassificationResult::new(self.intent.clone(), params)
+    }
+
+    pub fn with_account(mut self, account_id: &str) -> Self {
+        self.account_id = Some(account_id.to_string());
+        self
+    }
+
+    /// Plans with anomalous recipients must be confirmed by an approver
+    pub fn requires_elevated_approval(&self) -> bool {
+        !self.anomalies.is_empty()
+    }
+
+    /// One-line description suitable for a confirmation prompt
+    pub fn summary(&self) -> String {
+        let mut recipients = if self.recipients.is_empty() {
+            "nobody".to_string()
+        } else {
+            self.recipients.join(", ")
+        };
+        if !self.cc.is_empty() {
+            recipients = format!("{} (cc {})", recipients, self.cc.join(", "));
+        }
+        if !self.bcc.is_empty() {
+            recipients = format!("{} (bcc {})", recipients, self.bcc.join(", "));
+        }
+        let summary = format!(
+            "{} to {}: {}",
+            self.intent,
+            recipients,
+            self.content.as_deref().unwrap_or("(no content)")
+        );
+        let summary = if self.attachments.is_empty() {
+            summary
+        } else {
+            format!("{} (attaching {})", summary, self.attachments.join(", "))
+        };
+        let summary = match self.timing {
+            ActionTiming::Immediately => summary,
+            ActionTiming::At(at) => format!("{} (at {})", summary, at.format("%Y-%m-%d %H:%M")),
+        };
+        match self.duplicate_of {
+            Some(id) => format!("{} (repeats #{})", summary, id),
+            None => summary,
+        }
+    }
+}
+
+/// Proof that a plan was confirmed. Only the `ActionGate` can create it, so
+/// code performing side effects can require one in its signature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmedPlan {
+    plan: ActionPlan,
+}
+
+impl ConfirmedPlan {
+    pub(crate) fn new(mut plan: ActionPlan) -> Self {
+        plan.status = PlanStatus::Confirmed;
+        Self { plan }
+    }
+
+    pub fn plan(&self) -> &ActionPlan {
+        &self.plan
+    }
+
+    pub fn id(&self) -> u64 {
+        self.plan.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::Params;
+
+    #[test]
+    fn test_from_classification() {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva@company.com".to_string(), "I'll be late".to_string()),
+        );
+
+        let plan = ActionPlan::from_classification(7, &result);
+
+        assert_eq!(plan.id, 7);
+        assert_eq!(plan.recipients, vec!["eva@company.com".to_string()]);
+        assert_eq!(plan.content.as_deref(), Some("I'll be late"));
+        assert_eq!(plan.status, PlanStatus::PendingConfirmation);
+        assert_eq!(
+            plan.summary(),
+            "send_email to eva@company.com: I'll be late"
+        );
+    }
+
+    #[test]
+    fn test_scheduled_send() {
+        let now = chrono::NaiveDate::from_ymd_opt(2025, 5, 14)
+            .unwrap()
+            .and_hms_opt(16, 0, 0)
+            .unwrap();
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva@company.com".to_string(), "Report".to_string())
+                .with_send_at("tomorrow morning"),
+        );
+
+        let plan = ActionPlan::from_classification_at(3, &result, now);
+
+        assert_eq!(
+            plan.timing,
+            ActionTiming::At(now + chrono::Duration::hours(17))
+        );
+        assert_eq!(
+            plan.summary(),
+            "send_email to eva@company.com: Report (at 2025-05-15 09:00)"
+        );
+        assert_eq!(
+            ActionTiming::resolve(Some("yesterday"), now),
+            ActionTiming::Immediately
+        );
+    }
+
+    #[test]
+    fn test_cc_bcc_and_attachments() {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("Eva".to_string(), "The notes".to_string())
+                .with_cc(&["Bruno"])
+                .with_bcc(&["boss@company.com"])
+                .with_attachments(&["the Q3 report"]),
+        );
+
+        let plan = ActionPlan::from_classification(2, &result);
+
+        assert_eq!(
+            plan.all_recipients(),
+            vec!["Eva", "Bruno", "boss@company.com"]
+        );
+        assert_eq!(
+            plan.summary(),
+            "send_email to Eva (cc Bruno) (bcc boss@company.com): The notes (attaching the Q3 report)"
+        );
+    }
+
+    #[test]
+    fn test_summary_without_params() {
+        let result = ClassificationResult::new(Intent::NoAction, Params::new(None, None));
+
+        let plan = ActionPlan::from_classification(1, &result);
+
+        assert_eq!(plan.summary(), "no_action to nobody: (no content)");
+    }
+
+    #[test]
+    fn test_confirmed_plan_status() {
+        let result = ClassificationResult::new(Intent::NoAction, Params::new(None, None));
+        let confirmed = ConfirmedPlan::new(ActionPlan::from_classification(1, &result));
+
+        assert_eq!(confirmed.plan().status, PlanStatus::Confirmed);
+        assert_eq!(confirmed.id(), 1);
+    }
+}
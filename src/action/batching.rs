@@ -0,0 +1,138 @@
+use chrono::Duration;
+
+use crate::action::{ActionPlan, OutboxItem};
+use crate::agent::Intent;
+use crate::config::{BatchingConfig, Config};
+use crate::i18n::Locale;
+
+/// Merges low-priority emails to the same recipient, queued within `window`
+/// of the first one, into a single email so a burst of agent-written notes
+/// lands as one message. The first email of a batch waits for the window
+/// to close.
+#[derive(Debug, Clone)]
+pub struct BatchPolicy {
+    enabled: bool,
+    window: Duration,
+}
+
+impl BatchPolicy {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            enabled: true,
+            window: window.max(Duration::zero()),
+        }
+    }
+
+    /// Sends every email on its own
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            window: Duration::zero(),
+        }
+    }
+
+    pub fn from_config(config: &BatchingConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            ..Self::new(Duration::seconds(config.window_secs as i64))
+        }
+    }
+
+    pub fn configured() -> Self {
+        Self::from_config(&Config::get().actions.batching)
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Recipient, and account, `plan` is batched under. Urgent and
+    /// scheduled sends, and emails to several people, always go out on
+    /// their own.
+    pub fn key(&self, plan: &ActionPlan) -> Option<String> {
+        if !self.enabled
+            || plan.intent != Intent::SendEmail
+            || plan.urgent
+            || plan.timing.scheduled_for().is_some()
+        {
+            return None;
+        }
+        match plan.all_recipients().as_slice() {
+            [recipient] => Some(match &plan.account_id {
+                Some(account_id) => format!("{}/{}", account_id, recipient.trim().to_lowercase()),
+                None => recipient.trim().to_lowercase(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Default for BatchPolicy {
+    fn default() -> Self {
+        Self::configured()
+    }
+}
+
+/// Released outbox items that go out as one email
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch {
+    /// In the order they were queued
+    pub items: Vec<OutboxItem>,
+}
+
+impl Batch {
+    pub fn is_merged(&self) -> bool {
+        self.items.len() > 1
+    }
+
+    pub fn recipients(&self) -> &[String] {
+        self.items
+            .first()
+            .map(|item| item.plan.plan().recipients.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Account all items are sent from
+    pub fn account_id(&self) -> Option<&str> {
+        self.items
+            .first()
+            .and_then(|item| item.plan.plan().account_id.as_deref())
+    }
+
+    /// Subject of a merged email; single emails keep their own
+    pub fn subject(&self, locale: Locale) -> Option<String> {
+        if !self.is_merged() {
+            return None;
+        }
+        Some(match locale {
+            Locale::En => format!("{} messages", self.items.len()),
+            Locale::PtBr => format!("{} mensagens", self.items.len()),
+        })
+    }
+
+    /// Body with one section per original message
+    pub fn content(&self, locale: Locale) -> String {
+        if !self.is_merged() {
+            return self
+                .items
+                .first()
+                .and_then(|item| item.plan.plan().content.clone())
+                .unwrap_or_default();
+        }
+        let total = self.items.len();
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let time = item.queued_at.format("%H:%M");
+                let heading = match locale {
+                    Locale::En => format!("Message {} of {} ({})", index + 1, total, time),
+                    Locale::PtBr => format!("Mensagem {} de {} ({})", index + 1, total, time),
+                };
+                let content = item.plan.plan().content.as_deref().unwrap_or_default();
+                format!("{}\n{}", heading, content.trim())
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
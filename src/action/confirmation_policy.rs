@@ -0,0 +1,91 @@
+use crate::agent::{ClassificationResult, Intent};
+use crate::config::ActionsConfig;
+
+/// Decides which intents may run without an explicit user confirmation
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConfirmationPolicy {
+    auto_confirm: Vec<Intent>,
+    min_confidence: Option<f32>,
+}
+
+impl ConfirmationPolicy {
+    pub fn new(auto_confirm: Vec<Intent>) -> Self {
+        Self {
+            auto_confirm,
+            min_confidence: None,
+        }
+    }
+
+    pub fn from_config(config: &ActionsConfig) -> Self {
+        let policy = Self::new(config.auto_confirm.clone());
+        match config.min_confidence {
+            Some(min_confidence) => policy.with_min_confidence(min_confidence),
+            None => policy,
+        }
+    }
+
+    /// Auto-confirmed intents still wait for the user when the result's
+    /// (calibrated) confidence is below this or unknown
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = Some(min_confidence);
+        self
+    }
+
+    pub fn requires_confirmation(&self, intent: &Intent) -> bool {
+        !self.auto_confirm.contains(intent)
+    }
+
+    /// Like `requires_confirmation`, also applying the confidence threshold
+    pub fn requires_confirmation_for(&self, result: &ClassificationResult) -> bool {
+        self.requires_confirmation(&result.intent)
+            || self.min_confidence.is_some_and(|min| {
+                result
+                    .effective_confidence()
+                    .is_none_or(|confidence| confidence < min)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_requires_confirmation_for_everything() {
+        let policy = ConfirmationPolicy::default();
+
+        assert!(policy.requires_confirmation(&Intent::SendEmail));
+        assert!(policy.requires_confirmation(&Intent::NoAction));
+    }
+
+    #[test]
+    fn test_auto_confirm_intents() {
+        let policy = ConfirmationPolicy::from_config(&ActionsConfig {
+            auto_confirm: vec![Intent::NoAction],
+            ..ActionsConfig::default()
+        });
+
+        assert!(!policy.requires_confirmation(&Intent::NoAction));
+        assert!(policy.requires_confirmation(&Intent::SendEmail));
+    }
+
+    #[test]
+    fn test_low_confidence_waits_for_the_user() {
+        let policy = ConfirmationPolicy::new(vec![Intent::NoAction]).with_min_confidence(0.8);
+        let result = ClassificationResult::new(
+            Intent::NoAction,
+            crate::agent::classifier::Params::new(None, None),
+        );
+
+        assert!(policy.requires_confirmation_for(&result));
+        assert!(
+            policy.requires_confirmation_for(
+                &result
+                    .clone()
+                    .with_confidence(0.95)
+                    .with_calibrated_confidence(0.6)
+            )
+        );
+        assert!(!policy.requires_confirmation_for(&result.with_confidence(0.85)));
+    }
+}
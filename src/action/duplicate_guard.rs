@@ -0,0 +1,389 @@
+use chrono::{Duration, Local, NaiveDateTime};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::action::{ActionError, ActionGate, Proposal};
+use crate::agent::{ClassificationResult, EmbeddingModel, Intent, cosine_similarity};
+use crate::config::{Config, DuplicateConfig};
+use crate::i18n::text::normalize;
+use crate::infra::ollama::OllamaClient;
+
+/// An earlier action of the session that a new request repeats
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate {
+    pub plan_id: u64,
+    pub at: NaiveDateTime,
+    pub similarity: f32,
+}
+
+#[derive(Debug, Clone)]
+struct RecentAction {
+    plan_id: u64,
+    at: NaiveDateTime,
+    intent: Intent,
+    text: String,
+    embedding: Option<Vec<f32>>,
+}
+
+/// Catches "send the email to Turtle" issued twice in a short window by
+/// comparing each request with the recent actions of its session, so the
+/// second one is confirmed instead of sent again
+pub struct DuplicateGuard<E = OllamaClient> {
+    model: E,
+    enabled: bool,
+    window: Duration,
+    threshold: f32,
+    recent: Mutex<HashMap<String, Vec<RecentAction>>>,
+}
+
+impl DuplicateGuard {
+    pub fn from_config(config: &DuplicateConfig) -> Self {
+        DuplicateGuard::new(
+            OllamaClient::new().with_model(&config.embedding_model),
+            Duration::seconds(config.window_secs as i64),
+            config.similarity_threshold,
+        )
+        .with_enabled(config.enabled)
+    }
+
+    pub fn configured() -> Self {
+        Self::from_config(&Config::get().actions.duplicates)
+    }
+}
+
+impl Default for DuplicateGuard {
+    fn default() -> Self {
+        Self::configured()
+    }
+}
+
+impl<E: EmbeddingModel + Sync> DuplicateGuard<E> {
+    pub fn new(model: E, window: Duration, threshold: f32) -> Self {
+        Self {
+            model,
+            enabled: true,
+            window,
+            threshold,
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A disabled guard proposes every request as is
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub async fn propose(
+        &self,
+        gate: &ActionGate,
+        session_id: &str,
+        result: &ClassificationResult,
+    ) -> Result<Proposal, ActionError> {
+        self.propose_at(gate, session_id, result, Local::now().naive_local())
+            .await
+    }
+
+    /// Proposes `result` through `gate`, as a duplicate if it repeats a
+    /// recent action of the session, and remembers it for later requests
+    pub async fn propose_at(
+        &self,
+        gate: &ActionGate,
+        session_id: &str,
+        result: &ClassificationResult,
+        now: NaiveDateTime,
+    ) -> Result<Proposal, ActionError> {
+        if !self.enabled || result.intent == Intent::NoAction {
+            return gate.propose_in_session(session_id, result);
+        }
+        let text = action_text(result);
+        let recent = self.embedded(self.recent_actions(session_id, now)).await;
+        // Nothing to compare with, so the embedding waits for a later
+        // request. Without embeddings only exact repeats are caught.
+        let embedding = if recent.is_empty() {
+            None
+        } else {
+            self.model.embed(&text).await.ok()
+        };
+        let proposal = match self.find(&recent, &result.intent, &text, embedding.as_deref()) {
+            Some(duplicate) => gate.propose_duplicate(session_id, result, duplicate.plan_id)?,
+            None => gate.propose_in_session(session_id, result)?,
+        };
+        let plan_id = match &proposal {
+            Proposal::NeedsConfirmation(plan) => plan.id,
+            Proposal::AutoConfirmed(confirmed) => confirmed.id(),
+        };
+        let mut sessions = self.recent.lock().unwrap();
+        let actions = sessions.entry(session_id.to_string()).or_default();
+        // Keeps the embeddings computed for this request
+        for action in actions
+            .iter_mut()
+            .filter(|action| action.embedding.is_none())
+        {
+            action.embedding = recent
+                .iter()
+                .find(|embedded| embedded.plan_id == action.plan_id)
+                .and_then(|embedded| embedded.embedding.clone());
+        }
+        actions.push(RecentAction {
+            plan_id,
+            at: now,
+            intent: result.intent.clone(),
+            text,
+            embedding,
+        });
+        Ok(proposal)
+    }
+
+    /// Actions of the session within the window. Older actions are
+    /// dropped from every session, and sessions left without any are
+    /// forgotten, so idle sessions do not pile up.
+    fn recent_actions(&self, session_id: &str, now: NaiveDateTime) -> Vec<RecentAction> {
+        let mut sessions = self.recent.lock().unwrap();
+        sessions.retain(|_, actions| {
+            actions.retain(|action| now - action.at <= self.window);
+            !actions.is_empty()
+        });
+        sessions.get(session_id).cloned().unwrap_or_default()
+    }
+
+    /// `actions` with the embeddings they were stored without
+    async fn embedded(&self, mut actions: Vec<RecentAction>) -> Vec<RecentAction> {
+        for action in actions
+            .iter_mut()
+            .filter(|action| action.embedding.is_none())
+        {
+            action.embedding = self.model.embed(&action.text).await.ok();
+        }
+        actions
+    }
+
+    /// Most similar of `recent` above the threshold
+    fn find(
+        &self,
+        recent: &[RecentAction],
+        intent: &Intent,
+        text: &str,
+        embedding: Option<&[f32]>,
+    ) -> Option<Duplicate> {
+        recent
+            .iter()
+            .filter(|action| &action.intent == intent)
+            .filter_map(|action| {
+                let similarity = if action.text == text {
+                    1.0
+                } else {
+                    cosine_similarity(action.embedding.as_deref()?, embedding?)
+                };
+                (similarity >= self.threshold).then_some(Duplicate {
+                    plan_id: action.plan_id,
+                    at: action.at,
+                    similarity,
+                })
+            })
+            .max_by(|a, b| a.similarity.total_cmp(&b.similarity))
+    }
+
+    /// Forgets the actions of a session, e.g. when it ends
+    pub fn forget(&self, session_id: &str) {
+        self.recent.lock().unwrap().remove(session_id);
+    }
+}
+
+/// What is compared: intent, recipient and message, case-folded with
+/// runs of whitespace collapsed
+fn action_text(result: &ClassificationResult) -> String {
+    let text = normalize(&format!(
+        "{} {} {}",
+        result.intent,
+        result.params.recipient().unwrap_or_default(),
+        result.params.message().unwrap_or_default()
+    ));
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::ConfirmationPolicy;
+    use crate::agent::AgentError;
+    use crate::agent::classifier::Params;
+    use chrono::NaiveDate;
+
+    /// Embeds by counting a few keywords, so rewordings land close
+    struct KeywordEmbedder;
+
+    impl EmbeddingModel for KeywordEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>, AgentError> {
+            Ok(["turtle", "email", "report", "meeting"]
+                .iter()
+                .map(|word| text.matches(word).count() as f32)
+                .collect())
+        }
+    }
+
+    /// Counts the texts it is asked to embed
+    #[derive(Default)]
+    struct CountingEmbedder(std::sync::atomic::AtomicUsize);
+
+    impl EmbeddingModel for CountingEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>, AgentError> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            KeywordEmbedder.embed(text).await
+        }
+    }
+
+    struct Offline;
+
+    impl EmbeddingModel for Offline {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>, AgentError> {
+            Err(AgentError::NetworkError("connection refused".to_string()))
+        }
+    }
+
+    fn at(minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, 12)
+            .unwrap()
+            .and_hms_opt(9, minute, 0)
+            .unwrap()
+    }
+
+    fn email(recipient: &str, message: &str) -> ClassificationResult {
+        ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values(recipient.to_string(), message.to_string()),
+        )
+    }
+
+    fn gate() -> ActionGate {
+        ActionGate::new(ConfirmationPolicy::new(vec![Intent::SendEmail]))
+    }
+
+    #[tokio::test]
+    async fn test_reworded_repeat_needs_confirmation() {
+        let guard = DuplicateGuard::new(KeywordEmbedder, Duration::minutes(5), 0.9);
+        let gate = gate();
+
+        let first = guard
+            .propose_at(
+                &gate,
+                "u1",
+                &email("Turtle", "Send the report email"),
+                at(0),
+            )
+            .await
+            .unwrap();
+        let second = guard
+            .propose_at(&gate, "u1", &email("turtle", "the report, by email"), at(2))
+            .await
+            .unwrap();
+
+        assert!(matches!(first, Proposal::AutoConfirmed(_)));
+        let Proposal::NeedsConfirmation(plan) = second else {
+            panic!("Expected NeedsConfirmation");
+        };
+        assert_eq!(plan.duplicate_of, Some(1));
+        assert!(plan.summary().ends_with("(repeats #1)"));
+    }
+
+    #[tokio::test]
+    async fn test_other_sessions_and_old_actions_are_ignored() {
+        let guard = DuplicateGuard::new(KeywordEmbedder, Duration::minutes(5), 0.9);
+        let gate = gate();
+        let request = email("Turtle", "Send the report");
+
+        guard
+            .propose_at(&gate, "u1", &request, at(0))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            guard
+                .propose_at(&gate, "u2", &request, at(1))
+                .await
+                .unwrap(),
+            Proposal::AutoConfirmed(_)
+        ));
+        assert!(matches!(
+            guard
+                .propose_at(&gate, "u1", &request, at(10))
+                .await
+                .unwrap(),
+            Proposal::AutoConfirmed(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_exact_repeat_is_caught_without_embeddings() {
+        let guard = DuplicateGuard::new(Offline, Duration::minutes(5), 0.9);
+        let gate = gate();
+
+        guard
+            .propose_at(&gate, "u1", &email("Turtle", "Send the report"), at(0))
+            .await
+            .unwrap();
+        let repeat = guard
+            .propose_at(&gate, "u1", &email("turtle", "Send the  report"), at(1))
+            .await
+            .unwrap();
+        let different = guard
+            .propose_at(&gate, "u1", &email("Turtle", "Lunch?"), at(1))
+            .await
+            .unwrap();
+
+        assert!(matches!(repeat, Proposal::NeedsConfirmation(_)));
+        assert!(matches!(different, Proposal::AutoConfirmed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_first_action_of_a_session_is_not_embedded() {
+        let guard = DuplicateGuard::new(CountingEmbedder::default(), Duration::minutes(5), 0.9);
+        let gate = gate();
+
+        guard
+            .propose_at(&gate, "u1", &email("Turtle", "Send the report"), at(0))
+            .await
+            .unwrap();
+        let embedded_first = guard.model.0.load(std::sync::atomic::Ordering::SeqCst);
+        guard
+            .propose_at(&gate, "u1", &email("Turtle", "The report email"), at(1))
+            .await
+            .unwrap();
+        guard
+            .propose_at(&gate, "u1", &email("Turtle", "Lunch?"), at(2))
+            .await
+            .unwrap();
+
+        assert_eq!(embedded_first, 0);
+        // Both texts of the second request, then only the third
+        assert_eq!(guard.model.0.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_expired_sessions_are_forgotten() {
+        let guard = DuplicateGuard::new(KeywordEmbedder, Duration::minutes(5), 0.9);
+        let gate = gate();
+
+        for (minute, session) in ["u1", "u2", "u3"].into_iter().enumerate() {
+            guard
+                .propose_at(
+                    &gate,
+                    session,
+                    &email("Turtle", "Send the report"),
+                    at(minute as u32),
+                )
+                .await
+                .unwrap();
+        }
+        guard
+            .propose_at(&gate, "u4", &email("Turtle", "Send the report"), at(30))
+            .await
+            .unwrap();
+
+        let sessions = guard.recent.lock().unwrap();
+        assert_eq!(sessions.keys().collect::<Vec<_>>(), vec!["u4"]);
+    }
+}
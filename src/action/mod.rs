@@ -0,0 +1,21 @@
+pub mod account_selector;
+pub mod action_error;
+pub mod action_gate;
+pub mod action_plan;
+pub mod batching;
+pub mod confirmation_policy;
+pub mod duplicate_guard;
+pub mod outbox;
+pub mod quiet_hours;
+pub mod simulation;
+
+pub use account_selector::{AccountChoice, AccountReason, AccountSelector};
+pub use action_error::ActionError;
+pub use action_gate::{ActionGate, Proposal};
+pub use action_plan::{ActionPlan, ActionTiming, ConfirmedPlan, PlanStatus};
+pub use batching::{Batch, BatchPolicy};
+pub use confirmation_policy::ConfirmationPolicy;
+pub use duplicate_guard::{Duplicate, DuplicateGuard};
+pub use outbox::{DeliveryEvent, Outbox, OutboxItem, OutboxStatus, RecipientDelivery};
+pub use quiet_hours::{DeliveryPolicy, QuietHours};
+pub use simulation::{EffectRecorder, SimulatedEffect, Simulation};
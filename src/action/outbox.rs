@@ -0,0 +1,841 @@
+use chrono::{Duration, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use crate::action::{
+    ActionError, ActionGate, ActionPlan, Batch, BatchPolicy, ConfirmedPlan, DeliveryPolicy,
+    PlanStatus,
+};
+use crate::config::{ActionsConfig, Config};
+use crate::infra::email::{DeliveryReport, DeliveryStatus, EmailAddress};
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    /// Still cancellable: inside the undo window or waiting for its
+    /// scheduled time
+    SendingSoon,
+    Cancelled,
+    /// Handed over to be sent; no longer cancellable
+    Released,
+}
+
+/// A confirmed action waiting out its undo window
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutboxItem {
+    /// Same as the id of the plan
+    pub draft_id: u64,
+    pub plan: ConfirmedPlan,
+    pub queued_at: NaiveDateTime,
+    pub send_at: NaiveDateTime,
+    pub status: OutboxStatus,
+    /// Held past the undo window so it does not land in the recipient's
+    /// night
+    pub deferred: bool,
+    /// When the batch it waits in stops taking more emails to the same
+    /// recipient
+    pub batch_closes_at: Option<NaiveDateTime>,
+    /// Message-ID it went out with, without angle brackets
+    pub message_id: Option<String>,
+    /// Per recipient, what the bounces and read receipts said
+    pub deliveries: Vec<RecipientDelivery>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecipientDelivery {
+    pub recipient: String,
+    pub status: DeliveryStatus,
+    /// Enhanced status code of the last report, e.g. "5.1.1"
+    pub code: Option<String>,
+    pub diagnostic: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// The delivery status of a sent email changed for one recipient
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeliveryEvent {
+    pub draft_id: u64,
+    pub message_id: String,
+    pub recipient: String,
+    pub status: DeliveryStatus,
+    pub code: Option<String>,
+    pub diagnostic: Option<String>,
+    pub at: NaiveDateTime,
+}
+
+/// An item still sending soon, as kept on disk across a restart
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedItem {
+    plan: ActionPlan,
+    queued_at: NaiveDateTime,
+    send_at: NaiveDateTime,
+    deferred: bool,
+    batch_closes_at: Option<NaiveDateTime>,
+}
+
+impl OutboxItem {
+    /// Held for a send time the user asked for
+    pub fn is_scheduled(&self) -> bool {
+        self.plan.plan().timing.scheduled_for().is_some()
+    }
+
+    /// Time left to undo; zero once the window has closed
+    pub fn undo_remaining(&self, now: NaiveDateTime) -> Duration {
+        (self.send_at - now).max(Duration::zero())
+    }
+}
+
+/// Holds confirmed actions in a "sending soon" state for the undo delay,
+/// or until their scheduled time if later, so a misclassified email can
+/// still be retracted with `cancel`
+#[derive(Debug)]
+pub struct Outbox {
+    undo_delay: Duration,
+    delivery: DeliveryPolicy,
+    batching: BatchPolicy,
+    items: Mutex<BTreeMap<u64, OutboxItem>>,
+    events: broadcast::Sender<DeliveryEvent>,
+}
+
+impl Outbox {
+    pub fn new(undo_delay: Duration) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            undo_delay: undo_delay.max(Duration::zero()),
+            delivery: DeliveryPolicy::disabled(),
+            batching: BatchPolicy::disabled(),
+            items: Mutex::new(BTreeMap::new()),
+            events,
+        }
+    }
+
+    pub fn from_config(config: &ActionsConfig) -> Self {
+        Self::new(Duration::seconds(config.undo_delay_secs as i64))
+            .with_delivery(DeliveryPolicy::from_config(&config.quiet_hours))
+            .with_batching(BatchPolicy::from_config(&config.batching))
+    }
+
+    /// Outbox with the undo delay from config.toml
+    pub fn configured() -> Self {
+        Self::from_config(&Config::get().actions)
+    }
+
+    /// Quiet hours applied to sends; none by default with `new`
+    pub fn with_delivery(mut self, delivery: DeliveryPolicy) -> Self {
+        self.delivery = delivery;
+        self
+    }
+
+    /// Merging of low-priority emails to the same recipient; off by
+    /// default with `new`
+    pub fn with_batching(mut self, batching: BatchPolicy) -> Self {
+        self.batching = batching;
+        self
+    }
+
+    pub fn undo_delay(&self) -> Duration {
+        self.undo_delay
+    }
+
+    pub fn enqueue(&self, plan: ConfirmedPlan) -> OutboxItem {
+        self.enqueue_at(plan, Local::now().naive_local())
+    }
+
+    /// Queues `plan` to go out once the undo delay has passed, or at its
+    /// scheduled time, deferred past the recipients' quiet hours and held
+    /// for batching. Queuing a plan twice keeps the first entry.
+    pub fn enqueue_at(&self, plan: ConfirmedPlan, now: NaiveDateTime) -> OutboxItem {
+        let undo_until = now + self.undo_delay;
+        let due = match plan.plan().timing.scheduled_for() {
+            Some(scheduled) => scheduled.max(undo_until),
+            None => undo_until,
+        };
+        let send_at = self.delivery.deliver_at(plan.plan(), due);
+        let mut items = self.items.lock().unwrap();
+        if let Some(item) = items.get(&plan.id()) {
+            return item.clone();
+        }
+        let mut item = OutboxItem {
+            draft_id: plan.id(),
+            plan,
+            queued_at: now,
+            send_at,
+            status: OutboxStatus::SendingSoon,
+            deferred: send_at > due,
+            batch_closes_at: None,
+            message_id: None,
+            deliveries: Vec::new(),
+        };
+        if let Some(key) = self.batching.key(item.plan.plan()) {
+            self.join_batch(&mut items, &key, &mut item, now);
+        }
+        items.insert(item.draft_id, item.clone());
+        item
+    }
+
+    /// Puts `item` in the open batch of its recipient, or opens one. All
+    /// items of a batch are held until it closes and then leave together.
+    fn join_batch(
+        &self,
+        items: &mut BTreeMap<u64, OutboxItem>,
+        key: &str,
+        item: &mut OutboxItem,
+        now: NaiveDateTime,
+    ) {
+        let mut members: Vec<&mut OutboxItem> = items
+            .values_mut()
+            .filter(|other| {
+                other.status == OutboxStatus::SendingSoon
+                    && other.batch_closes_at.is_some_and(|closes| closes > now)
+                    && self.batching.key(other.plan.plan()).as_deref() == Some(key)
+            })
+            .collect();
+        let closes_at = members
+            .iter()
+            .find_map(|member| member.batch_closes_at)
+            .unwrap_or(now + self.batching.window());
+        members.retain(|member| member.batch_closes_at == Some(closes_at));
+        let send_at = members
+            .iter()
+            .map(|member| member.send_at)
+            .fold(item.send_at.max(closes_at), NaiveDateTime::max);
+        for member in members {
+            member.send_at = send_at;
+        }
+        item.batch_closes_at = Some(closes_at);
+        item.send_at = send_at;
+    }
+
+    pub fn get(&self, draft_id: u64) -> Option<OutboxItem> {
+        self.items.lock().unwrap().get(&draft_id).cloned()
+    }
+
+    /// Forgets the items sent, cancelled or given up on whose send time is
+    /// before `cutoff`; returns how many were dropped
+    pub fn prune_before(&self, cutoff: NaiveDateTime) -> usize {
+        let mut items = self.items.lock().unwrap();
+        let before = items.len();
+        items.retain(|_, item| item.status == OutboxStatus::SendingSoon || item.send_at >= cutoff);
+        before - items.len()
+    }
+
+    /// Items that can still be undone, soonest first
+    pub fn sending_soon(&self) -> Vec<OutboxItem> {
+        let mut items: Vec<OutboxItem> = self
+            .items
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|item| item.status == OutboxStatus::SendingSoon)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| (item.send_at, item.draft_id));
+        items
+    }
+
+    /// Pending sends the user scheduled for later, soonest first
+    pub fn scheduled(&self) -> Vec<OutboxItem> {
+        self.sending_soon()
+            .into_iter()
+            .filter(OutboxItem::is_scheduled)
+            .collect()
+    }
+
+    /// Items handed over to be sent, oldest first
+    pub fn released(&self) -> Vec<OutboxItem> {
+        let mut items: Vec<OutboxItem> = self
+            .items
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|item| item.status == OutboxStatus::Released)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| (item.send_at, item.draft_id));
+        items
+    }
+
+    pub fn cancel(&self, draft_id: u64) -> Result<ConfirmedPlan, ActionError> {
+        self.cancel_at(draft_id, Local::now().naive_local())
+    }
+
+    /// Retracts a queued action. Pass the returned plan to
+    /// `ActionGate::mark_cancelled`.
+    pub fn cancel_at(
+        &self,
+        draft_id: u64,
+        now: NaiveDateTime,
+    ) -> Result<ConfirmedPlan, ActionError> {
+        let mut items = self.items.lock().unwrap();
+        let item = items
+            .get_mut(&draft_id)
+            .ok_or(ActionError::PlanNotFound(draft_id))?;
+        match item.status {
+            OutboxStatus::Cancelled => Err(ActionError::InvalidTransition {
+                id: draft_id,
+                status: PlanStatus::Cancelled,
+            }),
+            OutboxStatus::Released => Err(ActionError::UndoWindowClosed(draft_id)),
+            OutboxStatus::SendingSoon if now >= item.send_at => {
+                Err(ActionError::UndoWindowClosed(draft_id))
+            }
+            OutboxStatus::SendingSoon => {
+                item.status = OutboxStatus::Cancelled;
+                Ok(item.plan.clone())
+            }
+        }
+    }
+
+    /// Releases every item whose undo window has closed, in send order
+    pub fn release_due(&self, now: NaiveDateTime) -> Vec<ConfirmedPlan> {
+        self.release_items(now)
+            .into_iter()
+            .map(|item| item.plan)
+            .collect()
+    }
+
+    /// Like `release_due`, with the items of each batch grouped into one
+    /// email
+    pub fn release_batches(&self, now: NaiveDateTime) -> Vec<Batch> {
+        let mut batches: Vec<(Option<(String, NaiveDateTime)>, Batch)> = Vec::new();
+        for item in self.release_items(now) {
+            let key = self
+                .batching
+                .key(item.plan.plan())
+                .zip(item.batch_closes_at);
+            match batches
+                .iter_mut()
+                .find(|(other, _)| key.is_some() && *other == key)
+            {
+                Some((_, batch)) => batch.items.push(item),
+                None => batches.push((key, Batch { items: vec![item] })),
+            }
+        }
+        batches
+            .into_iter()
+            .map(|(_, mut batch)| {
+                batch
+                    .items
+                    .sort_by_key(|item| (item.queued_at, item.draft_id));
+                batch
+            })
+            .collect()
+    }
+
+    fn release_items(&self, now: NaiveDateTime) -> Vec<OutboxItem> {
+        let mut items = self.items.lock().unwrap();
+        let mut due: Vec<&mut OutboxItem> = items
+            .values_mut()
+            .filter(|item| item.status == OutboxStatus::SendingSoon && item.send_at <= now)
+            .collect();
+        due.sort_by_key(|item| (item.send_at, item.draft_id));
+        due.into_iter()
+            .map(|item| {
+                item.status = OutboxStatus::Released;
+                item.clone()
+            })
+            .collect()
+    }
+
+    /// Puts a released item that could not be sent back in the queue, to
+    /// leave again at `send_at`; it can be cancelled until then
+    pub fn retry_at(
+        &self,
+        draft_id: u64,
+        send_at: NaiveDateTime,
+    ) -> Result<OutboxItem, ActionError> {
+        let mut items = self.items.lock().unwrap();
+        let item = items
+            .get_mut(&draft_id)
+            .ok_or(ActionError::PlanNotFound(draft_id))?;
+        if item.status != OutboxStatus::Released || item.message_id.is_some() {
+            return Err(ActionError::InvalidTransition {
+                id: draft_id,
+                status: PlanStatus::Confirmed,
+            });
+        }
+        item.status = OutboxStatus::SendingSoon;
+        item.send_at = send_at;
+        item.batch_closes_at = None;
+        Ok(item.clone())
+    }
+
+    /// Writes the items still sending soon to `path` as JSON, so a restart
+    /// does not lose them; returns how many were saved
+    pub fn save(&self, path: &Path) -> std::io::Result<usize> {
+        let saved: Vec<SavedItem> = self
+            .sending_soon()
+            .into_iter()
+            .map(|item| SavedItem {
+                plan: item.plan.plan().clone(),
+                queued_at: item.queued_at,
+                send_at: item.send_at,
+                deferred: item.deferred,
+                batch_closes_at: item.batch_closes_at,
+            })
+            .collect();
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&saved)?)?;
+        Ok(saved.len())
+    }
+
+    /// Queues again the items an earlier run saved at `path`, keeping their
+    /// send times; their plans are taken back into `gate` as confirmed. The
+    /// file is removed so they are not restored twice.
+    pub fn restore(&self, path: &Path, gate: &ActionGate) -> std::io::Result<Vec<OutboxItem>> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let saved: Vec<SavedItem> = serde_json::from_str(&content)?;
+        let mut items = self.items.lock().unwrap();
+        let mut restored = Vec::new();
+        for saved in saved {
+            if items.contains_key(&saved.plan.id) {
+                continue;
+            }
+            let item = OutboxItem {
+                draft_id: saved.plan.id,
+                plan: gate.readmit(saved.plan),
+                queued_at: saved.queued_at,
+                send_at: saved.send_at,
+                status: OutboxStatus::SendingSoon,
+                deferred: saved.deferred,
+                batch_closes_at: saved.batch_closes_at,
+                message_id: None,
+                deliveries: Vec::new(),
+            };
+            items.insert(item.draft_id, item.clone());
+            restored.push(item);
+        }
+        std::fs::remove_file(path)?;
+        Ok(restored)
+    }
+
+    /// When the next queued item leaves the undo window
+    pub fn next_release(&self) -> Option<NaiveDateTime> {
+        self.sending_soon().first().map(|item| item.send_at)
+    }
+
+    /// Delivery updates of sent items, as reports are applied
+    pub fn subscribe(&self) -> broadcast::Receiver<DeliveryEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn record_sent(
+        &self,
+        draft_id: u64,
+        message_id: &str,
+        recipients: &[EmailAddress],
+    ) -> Result<OutboxItem, ActionError> {
+        self.record_sent_at(draft_id, message_id, recipients, Local::now().naive_local())
+    }
+
+    /// Remembers the Message-ID a released item was sent with, so bounces
+    /// and read receipts can be matched to it
+    pub fn record_sent_at(
+        &self,
+        draft_id: u64,
+        message_id: &str,
+        recipients: &[EmailAddress],
+        now: NaiveDateTime,
+    ) -> Result<OutboxItem, ActionError> {
+        let mut items = self.items.lock().unwrap();
+        let item = items
+            .get_mut(&draft_id)
+            .ok_or(ActionError::PlanNotFound(draft_id))?;
+        item.message_id = Some(message_id.trim_matches(['<', '>']).to_string());
+        item.deliveries = recipients
+            .iter()
+            .map(|recipient| RecipientDelivery {
+                recipient: recipient.to_string(),
+                status: DeliveryStatus::Sent,
+                code: None,
+                diagnostic: None,
+                updated_at: now,
+            })
+            .collect();
+        Ok(item.clone())
+    }
+
+    pub fn find_by_message_id(&self, message_id: &str) -> Option<OutboxItem> {
+        let message_id = message_id.trim_matches(['<', '>']);
+        self.items
+            .lock()
+            .unwrap()
+            .values()
+            .find(|item| item.message_id.as_deref() == Some(message_id))
+            .cloned()
+    }
+
+    pub fn apply_report(&self, report: &DeliveryReport) -> Option<Vec<DeliveryEvent>> {
+        self.apply_report_at(report, Local::now().naive_local())
+    }
+
+    /// Updates the sent item `report` is about; `None` if no item went out
+    /// with its Message-ID. Only recipients whose status moved forward
+    /// produce events.
+    pub fn apply_report_at(
+        &self,
+        report: &DeliveryReport,
+        now: NaiveDateTime,
+    ) -> Option<Vec<DeliveryEvent>> {
+        let mut items = self.items.lock().unwrap();
+        let item = items
+            .values_mut()
+            .find(|item| item.message_id.as_deref() == Some(&report.original_message_id))?;
+        let mut events = Vec::new();
+        for update in &report.recipients {
+            let position = item
+                .deliveries
+                .iter()
+                .position(|delivery| delivery.recipient.eq_ignore_ascii_case(&update.recipient));
+            let position = match position {
+                Some(position) => position,
+                // Reported by an address the email was forwarded to
+                None => {
+                    item.deliveries.push(RecipientDelivery {
+                        recipient: update.recipient.clone(),
+                        status: DeliveryStatus::Sent,
+                        code: None,
+                        diagnostic: None,
+                        updated_at: now,
+                    });
+                    item.deliveries.len() - 1
+                }
+            };
+            let delivery = &mut item.deliveries[position];
+            if !update.status.supersedes(delivery.status) {
+                continue;
+            }
+            delivery.status = update.status;
+            delivery.code = update.code.clone();
+            delivery.diagnostic = update.diagnostic.clone();
+            delivery.updated_at = now;
+            events.push(DeliveryEvent {
+                draft_id: item.draft_id,
+                message_id: report.original_message_id.clone(),
+                recipient: delivery.recipient.clone(),
+                status: update.status,
+                code: update.code.clone(),
+                diagnostic: update.diagnostic.clone(),
+                at: now,
+            });
+        }
+        for event in &events {
+            let _ = self.events.send(event.clone());
+        }
+        Some(events)
+    }
+
+    /// Waits out the undo window of one item and releases it; `None` if it
+    /// was cancelled meanwhile or is not queued
+    pub async fn hold(&self, draft_id: u64) -> Option<ConfirmedPlan> {
+        let send_at = self
+            .get(draft_id)
+            .filter(|item| item.status == OutboxStatus::SendingSoon)?
+            .send_at;
+        let wait = (send_at - Local::now().naive_local())
+            .to_std()
+            .unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        let mut items = self.items.lock().unwrap();
+        let item = items
+            .get_mut(&draft_id)
+            .filter(|item| item.status == OutboxStatus::SendingSoon)?;
+        item.status = OutboxStatus::Released;
+        Some(item.plan.clone())
+    }
+}
+
+impl Default for Outbox {
+    fn default() -> Self {
+        Self::configured()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::{ActionPlan, ActionTiming, QuietHours};
+    use crate::agent::classifier::Params;
+    use crate::agent::{ClassificationResult, Intent};
+    use crate::i18n::Locale;
+    use chrono::NaiveDate;
+
+    fn confirmed(id: u64) -> ConfirmedPlan {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva@company.com".to_string(), "Hi".to_string()),
+        );
+        ConfirmedPlan::new(ActionPlan::from_classification(id, &result))
+    }
+
+    fn at(second: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, 14)
+            .unwrap()
+            .and_hms_opt(10, 0, second)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cancel_inside_undo_window() {
+        let outbox = Outbox::new(Duration::seconds(10));
+        outbox.enqueue_at(confirmed(1), at(0));
+
+        let cancelled = outbox.cancel_at(1, at(9)).unwrap();
+
+        assert_eq!(cancelled.id(), 1);
+        assert!(outbox.sending_soon().is_empty());
+        assert!(outbox.release_due(at(30)).is_empty());
+        assert!(matches!(
+            outbox.cancel_at(1, at(9)),
+            Err(ActionError::InvalidTransition { .. })
+        ));
+    }
+
+    #[test]
+    fn test_undo_window_closes() {
+        let outbox = Outbox::new(Duration::seconds(10));
+        outbox.enqueue_at(confirmed(1), at(0));
+
+        assert_eq!(
+            outbox.cancel_at(1, at(10)),
+            Err(ActionError::UndoWindowClosed(1))
+        );
+        assert_eq!(
+            outbox.cancel_at(2, at(0)),
+            Err(ActionError::PlanNotFound(2))
+        );
+    }
+
+    #[test]
+    fn test_settled_items_are_pruned() {
+        let outbox = Outbox::new(Duration::seconds(10));
+        outbox.enqueue_at(confirmed(1), at(0));
+        outbox.enqueue_at(confirmed(2), at(0));
+        outbox.enqueue_at(confirmed(3), at(20));
+        outbox.cancel_at(2, at(1)).unwrap();
+        outbox.release_due(at(10));
+
+        assert_eq!(outbox.prune_before(at(10)), 0);
+        assert_eq!(outbox.prune_before(at(40)), 2);
+
+        assert!(outbox.get(1).is_none());
+        assert!(outbox.get(2).is_none());
+        assert_eq!(outbox.get(3).unwrap().status, OutboxStatus::SendingSoon);
+    }
+
+    #[test]
+    fn test_release_due_in_send_order() {
+        let outbox = Outbox::new(Duration::seconds(10));
+        outbox.enqueue_at(confirmed(2), at(5));
+        outbox.enqueue_at(confirmed(1), at(0));
+
+        assert!(outbox.release_due(at(9)).is_empty());
+        assert_eq!(outbox.next_release(), Some(at(10)));
+
+        let released: Vec<u64> = outbox.release_due(at(20)).iter().map(|p| p.id()).collect();
+
+        assert_eq!(released, vec![1, 2]);
+        assert_eq!(outbox.get(1).unwrap().status, OutboxStatus::Released);
+        assert_eq!(
+            outbox.cancel_at(1, at(0)),
+            Err(ActionError::UndoWindowClosed(1))
+        );
+    }
+
+    #[test]
+    fn test_failed_send_is_queued_again() {
+        let outbox = Outbox::new(Duration::seconds(10));
+        outbox.enqueue_at(confirmed(1), at(0));
+        outbox.release_due(at(10));
+
+        outbox.retry_at(1, at(40)).unwrap();
+
+        assert!(outbox.release_due(at(39)).is_empty());
+        assert_eq!(outbox.release_due(at(40)).len(), 1);
+        outbox.record_sent(1, "<a@x>", &[]).unwrap();
+        assert!(outbox.retry_at(1, at(50)).is_err());
+    }
+
+    #[test]
+    fn test_saved_items_are_restored_as_confirmed() {
+        let path = std::env::temp_dir().join(format!("outbox-{}.json", std::process::id()));
+        let outbox = Outbox::new(Duration::seconds(10));
+        outbox.enqueue_at(confirmed(4), at(0));
+        outbox.enqueue_at(confirmed(5), at(1));
+        outbox.release_due(at(10));
+
+        assert_eq!(outbox.save(&path).unwrap(), 1);
+
+        let gate = ActionGate::default();
+        let restarted = Outbox::new(Duration::seconds(10));
+        let restored = restarted.restore(&path, &gate).unwrap();
+
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].send_at, at(11));
+        assert_eq!(gate.get(5).unwrap().status, PlanStatus::Confirmed);
+        assert!(gate.mark_executed(restored[0].plan.clone()).is_ok());
+        assert!(!path.exists());
+        assert!(restarted.restore(&path, &gate).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scheduled_send_is_held_until_its_time() {
+        let outbox = Outbox::new(Duration::seconds(10));
+        let mut plan = confirmed(1).plan().clone();
+        plan.timing = ActionTiming::At(at(0) + Duration::hours(17));
+        outbox.enqueue_at(ConfirmedPlan::new(plan), at(0));
+        outbox.enqueue_at(confirmed(2), at(0));
+
+        assert_eq!(
+            outbox
+                .scheduled()
+                .iter()
+                .map(|item| item.draft_id)
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(outbox.release_due(at(30)).len(), 1);
+        assert!(outbox.cancel_at(1, at(0) + Duration::hours(16)).is_ok());
+        assert!(outbox.release_due(at(0) + Duration::days(1)).is_empty());
+    }
+
+    #[test]
+    fn test_quiet_hours_defer_release() {
+        let delivery = DeliveryPolicy::new(QuietHours::new(9, 18))
+            .with_default_timezone(chrono_tz::UTC)
+            .with_sender_timezone(chrono_tz::UTC);
+        let outbox = Outbox::new(Duration::seconds(10)).with_delivery(delivery);
+
+        let item = outbox.enqueue_at(confirmed(1), at(0));
+
+        assert!(item.deferred);
+        assert_eq!(item.send_at, at(0).date().and_hms_opt(18, 0, 0).unwrap());
+        assert!(outbox.release_due(at(30)).is_empty());
+        assert!(outbox.cancel_at(1, at(30)).is_ok());
+    }
+
+    #[test]
+    fn test_undo_remaining() {
+        let outbox = Outbox::new(Duration::seconds(30));
+        let item = outbox.enqueue_at(confirmed(1), at(0));
+
+        assert_eq!(item.undo_remaining(at(20)), Duration::seconds(10));
+        assert_eq!(item.undo_remaining(at(45)), Duration::zero());
+    }
+
+    #[test]
+    fn test_delivery_reports_update_sent_items() {
+        let outbox = Outbox::new(Duration::seconds(10));
+        outbox.enqueue_at(confirmed(1), at(0));
+        outbox.release_due(at(10));
+        let recipients = [
+            EmailAddress::parse("eva@company.com").unwrap(),
+            EmailAddress::parse("bob@company.com").unwrap(),
+        ];
+        outbox
+            .record_sent_at(1, "<18f3a.0001@example.com>", &recipients, at(11))
+            .unwrap();
+        let mut events = outbox.subscribe();
+        let report = |recipient: &str, status| DeliveryReport {
+            kind: crate::infra::email::ReportKind::DeliveryStatus,
+            original_message_id: "18f3a.0001@example.com".to_string(),
+            recipients: vec![crate::infra::email::RecipientReport {
+                recipient: recipient.to_string(),
+                status,
+                code: None,
+                diagnostic: None,
+            }],
+        };
+
+        let read = outbox
+            .apply_report_at(&report("EVA@company.com", DeliveryStatus::Read), at(20))
+            .unwrap();
+        let late = outbox
+            .apply_report_at(&report("eva@company.com", DeliveryStatus::Delayed), at(30))
+            .unwrap();
+        outbox.apply_report_at(&report("bob@company.com", DeliveryStatus::Bounced), at(40));
+
+        let item = outbox
+            .find_by_message_id("<18f3a.0001@example.com>")
+            .unwrap();
+        assert_eq!(read[0].status, DeliveryStatus::Read);
+        assert!(late.is_empty());
+        assert_eq!(item.deliveries[0].status, DeliveryStatus::Read);
+        assert_eq!(item.deliveries[1].status, DeliveryStatus::Bounced);
+        assert_eq!(events.try_recv().unwrap().recipient, "eva@company.com");
+        assert_eq!(events.try_recv().unwrap().status, DeliveryStatus::Bounced);
+        assert!(
+            outbox
+                .apply_report_at(
+                    &DeliveryReport {
+                        original_message_id: "other@example.com".to_string(),
+                        ..report("eva@company.com", DeliveryStatus::Read)
+                    },
+                    at(50)
+                )
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_low_priority_emails_are_batched() {
+        let outbox = Outbox::new(Duration::seconds(10))
+            .with_batching(BatchPolicy::new(Duration::minutes(5)));
+        let mut urgent = confirmed(3).plan().clone();
+        urgent.urgent = true;
+        let mut other = confirmed(4).plan().clone();
+        other.recipients = vec!["bob@company.com".to_string()];
+
+        outbox.enqueue_at(confirmed(1), at(0));
+        outbox.enqueue_at(confirmed(2), at(0) + Duration::minutes(4));
+        outbox.enqueue_at(ConfirmedPlan::new(urgent), at(30));
+        outbox.enqueue_at(ConfirmedPlan::new(other), at(30));
+        let late = outbox.enqueue_at(confirmed(5), at(0) + Duration::minutes(6));
+
+        assert_eq!(outbox.get(1).unwrap().send_at, at(0) + Duration::minutes(5));
+        assert_eq!(late.send_at, at(0) + Duration::minutes(11));
+        let urgent_only = outbox.release_batches(at(40));
+        assert_eq!(urgent_only.len(), 1);
+        assert!(!urgent_only[0].is_merged());
+        let batches = outbox.release_batches(at(30) + Duration::minutes(5));
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].items.len(), 2);
+        assert_eq!(batches[0].recipients(), ["eva@company.com".to_string()]);
+        assert_eq!(
+            batches[0].subject(Locale::En).as_deref(),
+            Some("2 messages")
+        );
+        assert!(
+            batches[0]
+                .content(Locale::En)
+                .starts_with("Message 1 of 2 (10:00)\nHi\n\nMessage 2 of 2 (10:04)")
+        );
+        assert_eq!(batches[1].content(Locale::PtBr), "Hi");
+        assert_eq!(outbox.sending_soon()[0].draft_id, 5);
+    }
+
+    #[tokio::test]
+    async fn test_hold_releases_unless_cancelled() {
+        let outbox = Outbox::new(Duration::milliseconds(20));
+        outbox.enqueue(confirmed(1));
+        outbox.enqueue(confirmed(2));
+
+        outbox.cancel(2).unwrap();
+
+        assert_eq!(outbox.hold(1).await.map(|p| p.id()), Some(1));
+        assert_eq!(outbox.hold(2).await, None);
+        assert!(outbox.cancel(1).is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use chrono::{DateTime, Duration, Local, LocalResult, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::action::ActionPlan;
+use crate::config::{Config, QuietHoursConfig};
+use crate::infra::contacts::{Contact, UserContacts};
+use crate::infra::email::EmailAddress;
+
+/// Night hours, from `start_hour` up to `end_hour`, wrapping past
+/// midnight when `start_hour` is the later one; equal hours mean no quiet
+/// hours at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHours {
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl QuietHours {
+    pub fn new(start_hour: u32, end_hour: u32) -> Self {
+        Self {
+            start_hour: start_hour.min(23),
+            end_hour: end_hour.min(23),
+        }
+    }
+
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        let hour = at.hour();
+        if self.start_hour == self.end_hour {
+            false
+        } else if self.start_hour < self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+
+    /// `at` itself outside quiet hours, else the morning they end
+    pub fn next_allowed(&self, at: NaiveDateTime) -> NaiveDateTime {
+        if !self.contains(at) {
+            return at;
+        }
+        let date = if at.hour() < self.end_hour {
+            at.date()
+        } else {
+            at.date() + Duration::days(1)
+        };
+        date.and_hms_opt(self.end_hour, 0, 0).unwrap_or(at)
+    }
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        let config = QuietHoursConfig::default();
+        Self::new(config.start_hour, config.end_hour)
+    }
+}
+
+/// Defers outbox sends that would land in a recipient's night until their
+/// morning. Recipients whose timezone is unknown are never deferred.
+#[derive(Debug, Clone)]
+pub struct DeliveryPolicy {
+    enabled: bool,
+    quiet_hours: QuietHours,
+    default_timezone: Option<Tz>,
+    urgent_bypass: bool,
+    /// Timezone send times are expressed in; the machine's if unset
+    sender_timezone: Option<Tz>,
+    contacts: Arc<UserContacts>,
+}
+
+impl DeliveryPolicy {
+    pub fn new(quiet_hours: QuietHours) -> Self {
+        Self {
+            enabled: true,
+            quiet_hours,
+            default_timezone: None,
+            urgent_bypass: true,
+            sender_timezone: None,
+            contacts: Arc::new(UserContacts::default()),
+        }
+    }
+
+    /// Never defers anything
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::new(QuietHours::default())
+        }
+    }
+
+    pub fn from_config(config: &QuietHoursConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            default_timezone: config.default_timezone,
+            urgent_bypass: config.urgent_bypass,
+            ..Self::new(QuietHours::new(config.start_hour, config.end_hour))
+        }
+    }
+
+    pub fn configured() -> Self {
+        Self::from_config(&Config::get().actions.quiet_hours)
+    }
+
+    /// Address book holding each contact's timezone and quiet hours
+    pub fn with_contacts(mut self, contacts: Arc<UserContacts>) -> Self {
+        self.contacts = contacts;
+        self
+    }
+
+    /// Timezone of recipients without one of their own
+    pub fn with_default_timezone(mut self, timezone: Tz) -> Self {
+        self.default_timezone = Some(timezone);
+        self
+    }
+
+    pub fn with_sender_timezone(mut self, timezone: Tz) -> Self {
+        self.sender_timezone = Some(timezone);
+        self
+    }
+
+    /// Whether urgent plans skip quiet hours
+    pub fn with_urgent_bypass(mut self, urgent_bypass: bool) -> Self {
+        self.urgent_bypass = urgent_bypass;
+        self
+    }
+
+    /// When `plan`, due at `send_at` in the sender's time, may go out: the
+    /// latest morning among recipients it would wake up. Times the user
+    /// scheduled explicitly are kept.
+    pub fn deliver_at(&self, plan: &ActionPlan, send_at: NaiveDateTime) -> NaiveDateTime {
+        if !self.enabled
+            || (plan.urgent && self.urgent_bypass)
+            || plan.timing.scheduled_for().is_some()
+        {
+            return send_at;
+        }
+        let due = to_utc(send_at, self.sender_timezone);
+        plan.all_recipients()
+            .iter()
+            .filter_map(|recipient| self.morning_of(recipient, due))
+            .max()
+            .map(|morning| from_utc(morning, self.sender_timezone))
+            .map_or(send_at, |morning| morning.max(send_at))
+    }
+
+    /// End of the recipient's quiet hours if `due` falls inside them
+    fn morning_of(&self, recipient: &str, due: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let contact = self.contact(recipient);
+        let timezone = contact
+            .and_then(|contact| contact.timezone)
+            .or(self.default_timezone)?;
+        let quiet_hours = contact
+            .and_then(|contact| contact.quiet_hours)
+            .unwrap_or(self.quiet_hours);
+
+        let local = due.with_timezone(&timezone).naive_local();
+        let morning = quiet_hours.next_allowed(local);
+        (morning != local).then(|| to_utc(morning, Some(timezone)))
+    }
+
+    fn contact(&self, recipient: &str) -> Option<&Contact> {
+        EmailAddress::parse(recipient)
+            .ok()
+            .and_then(|address| self.contacts.find_by_email(&address))
+            .or_else(|| self.contacts.find_by_name(recipient))
+    }
+}
+
+impl Default for DeliveryPolicy {
+    fn default() -> Self {
+        Self::configured()
+    }
+}
+
+fn to_utc(at: NaiveDateTime, timezone: Option<Tz>) -> DateTime<Utc> {
+    match timezone {
+        Some(timezone) => earliest(timezone.from_local_datetime(&at), at),
+        None => earliest(Local.from_local_datetime(&at), at),
+    }
+}
+
+/// Times skipped by a DST change are read as UTC; off by an hour at most
+fn earliest<T: TimeZone>(local: LocalResult<DateTime<T>>, at: NaiveDateTime) -> DateTime<Utc> {
+    local
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&at))
+}
+
+fn from_utc(at: DateTime<Utc>, timezone: Option<Tz>) -> NaiveDateTime {
+    match timezone {
+        Some(timezone) => at.with_timezone(&timezone).naive_local(),
+        None => at.with_timezone(&Local).naive_local(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::Params;
+    use crate::agent::{ClassificationResult, Intent};
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn plan(recipient: &str) -> ActionPlan {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values(recipient.to_string(), "Hi".to_string()),
+        );
+        ActionPlan::from_classification_at(1, &result, at(14, 9))
+    }
+
+    fn contacts() -> Arc<UserContacts> {
+        Arc::new(
+            UserContacts::from_json_str(
+                r#"{"contacts": [
+                    {"id": "c1", "displayName": "Aiko", "timezone": "Asia/Tokyo",
+                     "emails": [{"type": "work", "address": "aiko@example.jp"}]},
+                    {"id": "c2", "displayName": "Bruno", "timezone": "America/Sao_Paulo",
+                     "quietHours": {"startHour": 20, "endHour": 9}}
+                ]}"#,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn policy() -> DeliveryPolicy {
+        DeliveryPolicy::new(QuietHours::new(22, 7))
+            .with_contacts(contacts())
+            .with_sender_timezone(chrono_tz::Europe::Lisbon)
+    }
+
+    #[test]
+    fn test_quiet_hours_wrap_midnight() {
+        let quiet = QuietHours::new(22, 7);
+
+        assert!(quiet.contains(at(14, 23)));
+        assert!(quiet.contains(at(14, 3)));
+        assert!(!quiet.contains(at(14, 7)));
+        assert_eq!(quiet.next_allowed(at(14, 23)), at(15, 7));
+        assert_eq!(quiet.next_allowed(at(14, 3)), at(14, 7));
+        assert_eq!(quiet.next_allowed(at(14, 12)), at(14, 12));
+    }
+
+    #[test]
+    fn test_equal_hours_are_never_quiet() {
+        let quiet = QuietHours::new(7, 7);
+
+        assert!(!quiet.contains(at(14, 7)));
+        assert!(!quiet.contains(at(14, 3)));
+        assert_eq!(quiet.next_allowed(at(14, 3)), at(14, 3));
+    }
+
+    #[test]
+    fn test_defers_to_recipient_morning() {
+        // 15:00 in Lisbon is 23:00 in Tokyo; 07:00 in Tokyo is 23:00 in Lisbon
+        assert_eq!(
+            policy().deliver_at(&plan("aiko@example.jp"), at(14, 15)),
+            at(14, 23)
+        );
+        assert_eq!(
+            policy().deliver_at(&plan("aiko@example.jp"), at(14, 10)),
+            at(14, 10)
+        );
+    }
+
+    #[test]
+    fn test_contact_quiet_hours_override() {
+        // Midnight in Lisbon is 20:00 in São Paulo, outside the global hours
+        // but inside Bruno's
+        assert_eq!(policy().deliver_at(&plan("Bruno"), at(15, 0)), at(15, 13));
+        assert_eq!(policy().deliver_at(&plan("Bruno"), at(14, 1)), at(14, 13));
+    }
+
+    #[test]
+    fn test_urgent_and_unknown_timezones_are_not_deferred() {
+        let mut urgent = plan("aiko@example.jp");
+        urgent.urgent = true;
+
+        assert_eq!(policy().deliver_at(&urgent, at(14, 15)), at(14, 15));
+        assert_eq!(
+            policy()
+                .with_urgent_bypass(false)
+                .deliver_at(&urgent, at(14, 15)),
+            at(14, 23)
+        );
+        assert_eq!(
+            policy().deliver_at(&plan("nobody@example.com"), at(14, 2)),
+            at(14, 2)
+        );
+        assert_eq!(
+            policy()
+                .with_default_timezone(chrono_tz::Europe::Lisbon)
+                .deliver_at(&plan("nobody@example.com"), at(14, 2)),
+            at(14, 7)
+        );
+    }
+}
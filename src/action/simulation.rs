@@ -0,0 +1,214 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+use crate::action::{ActionPlan, ActionTiming, PlanStatus};
+use crate::agent::classifier::{IntentDetails, Params};
+use crate::agent::pipeline::{HandlerRegistry, IntentHandler, StageFuture};
+use crate::agent::{ClassificationResult, Intent};
+use crate::infra::email::MimeMessage;
+
+/// Something a request would send or create, recorded instead of done
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SimulatedEffect {
+    Draft {
+        to: Vec<String>,
+        from: Option<String>,
+        subject: String,
+        body: String,
+        account_id: Option<String>,
+        timing: ActionTiming,
+    },
+    CalendarEvent {
+        title: String,
+        attendees: Vec<String>,
+        start: String,
+        duration_minutes: Option<u32>,
+        location: String,
+    },
+    /// A registered intent handler, e.g. a Slack or webhook post
+    Webhook { intent: Intent, params: Params },
+}
+
+impl SimulatedEffect {
+    /// The email `plan` would queue in the outbox
+    pub fn draft(email: &MimeMessage, plan: &ActionPlan) -> Self {
+        SimulatedEffect::Draft {
+            to: email.to().iter().map(ToString::to_string).collect(),
+            from: email.from().map(ToString::to_string),
+            subject: email.subject().to_string(),
+            body: email.text().to_string(),
+            account_id: plan.account_id.clone(),
+            timing: plan.timing.clone(),
+        }
+    }
+
+    /// The meeting a `schedule_meeting` result would book, from its
+    /// extracted details or else from its params
+    pub fn calendar_event(result: &ClassificationResult) -> Self {
+        match &result.details {
+            Some(IntentDetails::ScheduleMeeting(details)) => SimulatedEffect::CalendarEvent {
+                title: details.title.clone(),
+                attendees: details.attendees.clone(),
+                start: details.start.clone(),
+                duration_minutes: details.duration_minutes,
+                location: details.location.clone(),
+            },
+            _ => SimulatedEffect::CalendarEvent {
+                title: result.params.message().unwrap_or_default().to_string(),
+                attendees: result
+                    .params
+                    .recipient()
+                    .map(|recipient| vec![recipient.to_string()])
+                    .unwrap_or_default(),
+                start: result.params.send_at().unwrap_or_default().to_string(),
+                duration_minutes: None,
+                location: String::new(),
+            },
+        }
+    }
+}
+
+/// Collects effects in place of the transports that would perform them
+#[derive(Debug, Clone, Default)]
+pub struct EffectRecorder {
+    effects: Arc<Mutex<Vec<SimulatedEffect>>>,
+}
+
+impl EffectRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, effect: SimulatedEffect) {
+        self.effects.lock().unwrap().push(effect);
+    }
+
+    /// Recorded effects, in order
+    pub fn effects(&self) -> Vec<SimulatedEffect> {
+        self.effects.lock().unwrap().clone()
+    }
+
+    /// `registry` with every handler replaced by one recording its call
+    pub fn handlers(&self, registry: &HandlerRegistry) -> HandlerRegistry {
+        registry
+            .intents()
+            .into_iter()
+            .fold(HandlerRegistry::new(), |recording, intent| {
+                recording.on(
+                    intent.clone(),
+                    RecordingHandler {
+                        intent,
+                        recorder: self.clone(),
+                    },
+                )
+            })
+    }
+}
+
+struct RecordingHandler {
+    intent: Intent,
+    recorder: EffectRecorder,
+}
+
+impl IntentHandler for RecordingHandler {
+    fn handle(&self, params: Params) -> StageFuture<'_, ()> {
+        self.recorder.record(SimulatedEffect::Webhook {
+            intent: self.intent.clone(),
+            params,
+        });
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// What a request would do, with nothing sent, queued or booked
+#[derive(Debug, Clone, Serialize)]
+pub struct Simulation {
+    pub result: ClassificationResult,
+    /// The plan the user would be asked to confirm, or that would run
+    /// right away
+    pub plan: ActionPlan,
+    pub effects: Vec<SimulatedEffect>,
+}
+
+impl Simulation {
+    pub fn needs_confirmation(&self) -> bool {
+        self.plan.status == PlanStatus::PendingConfirmation
+    }
+
+    pub fn drafts(&self) -> Vec<&SimulatedEffect> {
+        self.effects_where(|effect| matches!(effect, SimulatedEffect::Draft { .. }))
+    }
+
+    pub fn calendar_events(&self) -> Vec<&SimulatedEffect> {
+        self.effects_where(|effect| matches!(effect, SimulatedEffect::CalendarEvent { .. }))
+    }
+
+    pub fn webhooks(&self) -> Vec<&SimulatedEffect> {
+        self.effects_where(|effect| matches!(effect, SimulatedEffect::Webhook { .. }))
+    }
+
+    fn effects_where(&self, keep: impl Fn(&SimulatedEffect) -> bool) -> Vec<&SimulatedEffect> {
+        self.effects.iter().filter(|effect| keep(effect)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::MeetingDetails;
+    use crate::agent::pipeline::handler_fn;
+
+    #[tokio::test]
+    async fn test_recorded_handlers_do_not_run() {
+        let registry = HandlerRegistry::new().on(
+            Intent::ScheduleMeeting,
+            handler_fn(|_| async { panic!("the real handler ran") }),
+        );
+        let recorder = EffectRecorder::new();
+        let result = ClassificationResult::new(
+            Intent::ScheduleMeeting,
+            Params::with_values("eva@company.com".to_string(), "Sync".to_string()),
+        );
+
+        let handled = recorder
+            .handlers(&registry)
+            .dispatch(&result)
+            .await
+            .unwrap();
+
+        assert!(handled);
+        let effects = recorder.effects();
+        assert_eq!(effects.len(), 1);
+        assert!(matches!(
+            &effects[0],
+            SimulatedEffect::Webhook { intent: Intent::ScheduleMeeting, params }
+                if params.recipient() == Some("eva@company.com")
+        ));
+    }
+
+    #[test]
+    fn test_calendar_event_prefers_details() {
+        let mut result = ClassificationResult::new(
+            Intent::ScheduleMeeting,
+            Params::with_values("Eva".to_string(), "Budget review".to_string()),
+        );
+        let fallback = SimulatedEffect::calendar_event(&result);
+        result.details = Some(IntentDetails::ScheduleMeeting(MeetingDetails {
+            attendees: vec!["Eva".to_string(), "Bruno".to_string()],
+            title: "Q3 budget".to_string(),
+            start: "2026-10-20T10:00".to_string(),
+            duration_minutes: Some(30),
+            location: String::new(),
+        }));
+
+        assert_eq!(
+            serde_json::to_value(&fallback).unwrap()["attendees"],
+            serde_json::json!(["Eva"])
+        );
+        let event = serde_json::to_value(SimulatedEffect::calendar_event(&result)).unwrap();
+        assert_eq!(event["kind"], "calendar_event");
+        assert_eq!(event["title"], "Q3 budget");
+        assert_eq!(event["duration_minutes"], 30);
+    }
+}
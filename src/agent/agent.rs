@@ -1,31 +1,66 @@
-use std::error::Error;
-
 use crate::agent::AgentResult;
+use crate::agent::classifier::PartialClassification;
+use crate::infra::resilience::BudgetExceeded;
+use crate::safety::QuarantineReason;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum AgentError {
+    #[error("Processing error: {0}")]
     ProcessingError(String),
+    /// The model server could not be reached or did not answer in time
+    #[error("Network error: {0}")]
     NetworkError(String),
+    #[error("Parse error: {0}")]
     ParseError(String),
+    /// The answer was readable but failed validation; carries what was
+    /// extracted so it can be corrected instead of discarded
+    #[error("Validation error: {0}")]
+    ValidationError(Box<PartialClassification>),
+    /// A stage panicked while handling this item
+    #[error("Stage '{stage}' panicked: {message}")]
+    StagePanicked { stage: String, message: String },
+    /// A run used up its LLM calls or wall time
+    #[error("Budget exhausted: {0}")]
+    BudgetExhausted(String),
+    /// A tenant's token budget is used up until it resets
+    #[error("Budget exceeded: {0}")]
+    BudgetExceeded(BudgetExceeded),
+    /// The model server answered with an error status
+    #[error("HTTP error {status}: {message}")]
+    HttpError { status: u16, message: String },
+    /// The caller's deadline passed before the model answered
+    #[error("Deadline exceeded: {0}")]
+    DeadlineExceeded(String),
+    /// Untrusted input was held for review instead of reaching a prompt
+    #[error("Input quarantined (#{id}): {reason}")]
+    Quarantined { id: u64, reason: QuarantineReason },
+    /// The server does not have the requested model
+    #[error("Model '{0}' is not available")]
+    ModelUnavailable(String),
+    /// The model's answer holds no JSON at all
+    #[error("Malformed model output: {raw}")]
+    MalformedModelOutput { raw: String },
+    /// The answer holds JSON, but not of the expected shape
+    #[error("Deserialization error: {0}")]
+    DeserializationError(String),
 }
 
-impl std::fmt::Display for AgentError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            AgentError::ProcessingError(msg) => write!(f, "Processing error: {}", msg),
-            AgentError::NetworkError(msg) => write!(f, "Network error: {}", msg),
-            AgentError::ParseError(msg) => write!(f, "Parse error: {}", msg),
+impl AgentError {
+    pub(crate) fn malformed(raw: &str) -> Self {
+        AgentError::MalformedModelOutput {
+            raw: raw.to_string(),
         }
     }
 }
 
-impl Error for AgentError {}
+impl From<serde_json::Error> for AgentError {
+    fn from(e: serde_json::Error) -> Self {
+        AgentError::DeserializationError(e.to_string())
+    }
+}
 
 pub trait Agent<P: AgentParam, T: AgentResult> {
-    fn process(
-        &self,
-        input: P,
-    ) -> impl std::future::Future<Output = Result<T, AgentError>> + Send;
+    fn process(&self, input: P) -> impl std::future::Future<Output = Result<T, AgentError>> + Send;
 }
 
 pub trait AgentParam {}
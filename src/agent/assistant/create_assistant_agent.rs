@@ -1,55 +1,109 @@
-use crate::{agent::{agent::AgentParam, assistant::{create_result, CreateResult}, Agent, AgentError}, infra::ollama::OllamaClient};
+use std::sync::Arc;
 
-pub struct CreateAssistantAgent {}
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel, agent::AgentParam, assistant::CreateResult,
+        chat_model::model_or,
+    },
+    infra::ollama::{Modelfile, OllamaClient},
+};
+
+#[derive(Default)]
+pub struct CreateAssistantAgent {
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
 
 impl CreateAssistantAgent {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Sends requests to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
     }
 }
 
 pub struct CreateParam {
-    system: String, 
+    system: String,
     name: String,
 }
 
 impl CreateParam {
     pub fn new(system: String, name: String) -> Self {
-        Self { 
-            system,
-            name
-        }
+        Self { system, name }
+    }
+
+    pub fn system(&self) -> &str {
+        &self.system
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
     }
 }
 
 impl AgentParam for CreateParam {}
 
-impl Agent<CreateParam, CreateResult> for  CreateAssistantAgent {
-    fn process(
-        &self,
-        input: CreateParam, 
-    ) -> impl std::future::Future<Output = Result<CreateResult, AgentError>> + Send {
-        async move {
-            let system_prompt = build_system_prompt(&input.name.clone());
-
-            let result = 
-                OllamaClient::new().create_assistant(&system_prompt.clone(), &input.name.clone()).await;
-
-            match result {
-                Ok(create_result) => {
-                    let success_messages: Vec<bool> = create_result.messages.iter().map(|m| m.status.eq_ignore_ascii_case("success")).collect();
-                    let has_success = success_messages.iter().any(|&success| success);
-                    Ok(CreateResult::new(has_success))
-                }
-                Err(e) => Err(AgentError::ParseError(format!(
-                    "Model creation failed: {}",
-                    e
-                ))),
-            }
-        }
+impl Agent<CreateParam, CreateResult> for CreateAssistantAgent {
+    async fn process(&self, input: CreateParam) -> Result<CreateResult, AgentError> {
+        let system_prompt = build_system_prompt(&input);
+
+        // Built on the configured model
+        let ollama = OllamaClient::new();
+        let modelfile = Modelfile::new(ollama.model()).with_system(&system_prompt);
+        let create_result = model_or(&self.chat_model, || ollama)
+            .create_model(&input.name, &modelfile)
+            .await?;
+
+        let has_success = create_result
+            .messages
+            .iter()
+            .any(|m| m.status.eq_ignore_ascii_case("success"));
+        Ok(CreateResult::new(has_success))
     }
 }
 
-fn build_system_prompt(input: &str) -> String {
-    String::new()
-}
\ No newline at end of file
+/// The assistant's system text; the name only identifies the model
+fn build_system_prompt(input: &CreateParam) -> String {
+    input.system.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_prompt_is_the_system_text() {
+        let param = CreateParam::new(
+            "You answer in one sentence.".to_string(),
+            "terse".to_string(),
+        );
+
+        assert_eq!(build_system_prompt(&param), "You answer in one sentence.");
+    }
+
+    #[tokio::test]
+    async fn test_creates_the_assistant_on_an_injected_model() {
+        use crate::infra::ollama::MockOllamaClient;
+
+        let model = Arc::new(MockOllamaClient::new());
+        let agent = CreateAssistantAgent::new().with_chat_model(model.clone());
+
+        let result = agent
+            .process(CreateParam::new(
+                "You answer in one sentence.".to_string(),
+                "terse".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        let created = model.created_models();
+        assert_eq!(created[0].0, "terse");
+        assert_eq!(created[0].1.system(), Some("You answer in one sentence."));
+    }
+}
@@ -0,0 +1,143 @@
+use futures_core::future::BoxFuture;
+use std::sync::Arc;
+
+use crate::agent::AgentError;
+use crate::infra::ollama::{
+    ChatMessages, ModelCapabilities, Modelfile, OllamaClient, OllamaCreateResponse,
+    OutputConstraint,
+};
+
+/// Anything that answers a list of chat messages with text. Agents hold it
+/// as `Arc<dyn ChatModel>`, so a `MockOllamaClient` can stand in for Ollama.
+pub trait ChatModel: Send + Sync {
+    fn chat(&self, messages: ChatMessages) -> BoxFuture<'_, Result<String, AgentError>>;
+
+    /// Answers a single prompt
+    fn ask<'a>(&'a self, prompt: &'a str) -> BoxFuture<'a, Result<String, AgentError>> {
+        self.chat(ChatMessages::new().user(prompt))
+    }
+
+    /// Answers `messages` with an answer that should satisfy `constraint`.
+    /// Models that cannot enforce it are asked plainly.
+    fn chat_constrained<'a>(
+        &'a self,
+        messages: ChatMessages,
+        _constraint: &'a OutputConstraint,
+    ) -> BoxFuture<'a, Result<String, AgentError>> {
+        self.chat(messages)
+    }
+
+    /// Answers a single prompt whose answer should satisfy `constraint`
+    fn ask_constrained<'a>(
+        &'a self,
+        prompt: &'a str,
+        constraint: &'a OutputConstraint,
+    ) -> BoxFuture<'a, Result<String, AgentError>> {
+        self.chat_constrained(ChatMessages::new().user(prompt), constraint)
+    }
+
+    /// What the model supports; assumed unknown unless it can tell
+    fn capabilities(&self) -> BoxFuture<'_, ModelCapabilities> {
+        Box::pin(std::future::ready(ModelCapabilities::unknown("")))
+    }
+
+    /// Registers `modelfile` as a new model called `name`; only servers
+    /// that manage models can
+    fn create_model<'a>(
+        &'a self,
+        name: &'a str,
+        _modelfile: &'a Modelfile,
+    ) -> BoxFuture<'a, Result<OllamaCreateResponse, AgentError>> {
+        Box::pin(std::future::ready(Err(AgentError::ProcessingError(
+            format!("Cannot create model '{}' here", name),
+        ))))
+    }
+}
+
+impl ChatModel for OllamaClient {
+    fn chat(&self, messages: ChatMessages) -> BoxFuture<'_, Result<String, AgentError>> {
+        Box::pin(async move {
+            self.send_messages(messages)
+                .await
+                .map(|response| response.message.raw_content().to_string())
+        })
+    }
+
+    fn chat_constrained<'a>(
+        &'a self,
+        messages: ChatMessages,
+        constraint: &'a OutputConstraint,
+    ) -> BoxFuture<'a, Result<String, AgentError>> {
+        Box::pin(async move {
+            self.send_messages_constrained(messages, constraint)
+                .await
+                .map(|response| response.message.raw_content().to_string())
+        })
+    }
+
+    fn capabilities(&self) -> BoxFuture<'_, ModelCapabilities> {
+        Box::pin(OllamaClient::capabilities(self))
+    }
+
+    fn create_model<'a>(
+        &'a self,
+        name: &'a str,
+        modelfile: &'a Modelfile,
+    ) -> BoxFuture<'a, Result<OllamaCreateResponse, AgentError>> {
+        Box::pin(OllamaClient::create_model(self, name, modelfile))
+    }
+}
+
+/// Lets a caller lend a model, so a test can inspect it afterwards
+impl<M: ChatModel + ?Sized> ChatModel for &M {
+    fn chat(&self, messages: ChatMessages) -> BoxFuture<'_, Result<String, AgentError>> {
+        (**self).chat(messages)
+    }
+
+    fn chat_constrained<'a>(
+        &'a self,
+        messages: ChatMessages,
+        constraint: &'a OutputConstraint,
+    ) -> BoxFuture<'a, Result<String, AgentError>> {
+        (**self).chat_constrained(messages, constraint)
+    }
+
+    fn ask_constrained<'a>(
+        &'a self,
+        prompt: &'a str,
+        constraint: &'a OutputConstraint,
+    ) -> BoxFuture<'a, Result<String, AgentError>> {
+        (**self).ask_constrained(prompt, constraint)
+    }
+
+    fn capabilities(&self) -> BoxFuture<'_, ModelCapabilities> {
+        (**self).capabilities()
+    }
+
+    fn create_model<'a>(
+        &'a self,
+        name: &'a str,
+        modelfile: &'a Modelfile,
+    ) -> BoxFuture<'a, Result<OllamaCreateResponse, AgentError>> {
+        (**self).create_model(name, modelfile)
+    }
+}
+
+/// The model a request goes to: the injected one, or `ollama` for it
+pub(crate) fn model_or(
+    injected: &Option<Arc<dyn ChatModel>>,
+    ollama: impl FnOnce() -> OllamaClient,
+) -> Arc<dyn ChatModel> {
+    match injected {
+        Some(model) => model.clone(),
+        None => Arc::new(ollama()),
+    }
+}
+
+/// Ollama, on `model` if given and the configured model otherwise
+pub(crate) fn ollama_for(model: Option<&str>) -> OllamaClient {
+    match model {
+        Some(model) => OllamaClient::new().with_model(model),
+        None => OllamaClient::new(),
+    }
+}
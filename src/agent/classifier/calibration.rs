@@ -0,0 +1,325 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::agent::{ClassificationResult, Intent};
+use crate::config::{CalibrationConfig, Config};
+
+/// A classification a human reviewed: what the model said, how sure it
+/// was, and whether the human kept the intent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Outcome {
+    pub intent: Intent,
+    pub confidence: f32,
+    pub correct: bool,
+}
+
+impl Outcome {
+    /// Outcome of `predicted` once the user settled on `corrected`; `None`
+    /// if the model reported no confidence
+    pub fn reviewed(predicted: &ClassificationResult, corrected: &Intent) -> Option<Self> {
+        Some(Self {
+            intent: predicted.intent.clone(),
+            confidence: predicted.confidence?,
+            correct: predicted.intent == *corrected,
+        })
+    }
+}
+
+/// Reviewed outcomes, stored as JSON lines
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutcomeLog {
+    outcomes: Vec<Outcome>,
+}
+
+impl OutcomeLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load_jsonl(path: &Path) -> io::Result<Self> {
+        Self::from_jsonl(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_jsonl(content: &str) -> io::Result<Self> {
+        let outcomes = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Self { outcomes })
+    }
+
+    /// Appends an outcome to a JSONL store, creating the file if needed
+    pub fn append_to(path: &Path, outcome: &Outcome) -> io::Result<()> {
+        let line = serde_json::to_string(outcome)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", line)
+    }
+
+    pub fn push(&mut self, outcome: Outcome) {
+        self.outcomes.push(outcome);
+    }
+
+    pub fn outcomes(&self) -> &[Outcome] {
+        &self.outcomes
+    }
+
+    pub fn len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+}
+
+/// Observed accuracy at a reported confidence
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurvePoint {
+    /// Mean reported confidence of the outcomes pooled into this point
+    pub confidence: f32,
+    pub accuracy: f32,
+    pub samples: usize,
+}
+
+/// Non-decreasing map from reported confidence to observed accuracy,
+/// fitted with pool-adjacent-violators (isotonic regression)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationCurve {
+    points: Vec<CurvePoint>,
+}
+
+impl CalibrationCurve {
+    pub fn fit<'a>(outcomes: impl IntoIterator<Item = &'a Outcome>) -> Self {
+        let mut sorted: Vec<&Outcome> = outcomes.into_iter().collect();
+        sorted.sort_by(|a, b| a.confidence.total_cmp(&b.confidence));
+
+        let mut points: Vec<CurvePoint> = Vec::new();
+        for outcome in sorted {
+            let mut point = CurvePoint {
+                confidence: outcome.confidence,
+                accuracy: if outcome.correct { 1.0 } else { 0.0 },
+                samples: 1,
+            };
+            // Higher confidence must never mean lower accuracy
+            while let Some(previous) = points.last().filter(|p| p.accuracy >= point.accuracy) {
+                point = pool(previous, &point);
+                points.pop();
+            }
+            points.push(point);
+        }
+        Self { points }
+    }
+
+    pub fn points(&self) -> &[CurvePoint] {
+        &self.points
+    }
+
+    pub fn samples(&self) -> usize {
+        self.points.iter().map(|p| p.samples).sum()
+    }
+
+    /// Accuracy expected at `confidence`, interpolated between points; the
+    /// reported value itself when the curve is empty
+    pub fn calibrate(&self, confidence: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return confidence;
+        };
+        if confidence <= first.confidence {
+            return first.accuracy;
+        }
+        if confidence >= last.confidence {
+            return last.accuracy;
+        }
+        self.points
+            .windows(2)
+            .find(|pair| confidence <= pair[1].confidence)
+            .map(|pair| {
+                let (low, high) = (pair[0], pair[1]);
+                let t = (confidence - low.confidence) / (high.confidence - low.confidence);
+                low.accuracy + t * (high.accuracy - low.accuracy)
+            })
+            .unwrap_or(last.accuracy)
+    }
+}
+
+fn pool(a: &CurvePoint, b: &CurvePoint) -> CurvePoint {
+    let samples = a.samples + b.samples;
+    let weighted = |x: f32, y: f32| (x * a.samples as f32 + y * b.samples as f32) / samples as f32;
+    CurvePoint {
+        confidence: weighted(a.confidence, b.confidence),
+        accuracy: weighted(a.accuracy, b.accuracy),
+        samples,
+    }
+}
+
+/// Maps model-reported confidence to the accuracy seen in corrections, per
+/// intent where there is enough history and across intents otherwise
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    overall: CalibrationCurve,
+    #[serde(default)]
+    per_intent: BTreeMap<Intent, CalibrationCurve>,
+}
+
+impl Calibration {
+    /// Curves need at least `min_samples` outcomes; with fewer the
+    /// reported confidence is used as it is
+    pub fn fit(log: &OutcomeLog, min_samples: usize) -> Self {
+        let min_samples = min_samples.max(1);
+        let mut by_intent: BTreeMap<Intent, Vec<&Outcome>> = BTreeMap::new();
+        for outcome in log.outcomes() {
+            by_intent
+                .entry(outcome.intent.clone())
+                .or_default()
+                .push(outcome);
+        }
+        Self {
+            overall: if log.len() >= min_samples {
+                CalibrationCurve::fit(log.outcomes())
+            } else {
+                CalibrationCurve::default()
+            },
+            per_intent: by_intent
+                .into_iter()
+                .filter(|(_, outcomes)| outcomes.len() >= min_samples)
+                .map(|(intent, outcomes)| (intent, CalibrationCurve::fit(outcomes)))
+                .collect(),
+        }
+    }
+
+    pub fn from_config(config: &CalibrationConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let log = OutcomeLog::load_jsonl(Path::new(&config.outcomes_path)).ok()?;
+        Some(Self::fit(&log, config.min_samples)).filter(|c| !c.is_empty())
+    }
+
+    /// Calibration fitted on the outcome log from config.toml; `None` when
+    /// it is disabled, missing or too small
+    pub fn configured() -> Option<Self> {
+        Self::from_config(&Config::get().calibration)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overall.points.is_empty() && self.per_intent.is_empty()
+    }
+
+    pub fn calibrate(&self, intent: &Intent, confidence: f32) -> f32 {
+        self.per_intent
+            .get(intent)
+            .unwrap_or(&self.overall)
+            .calibrate(confidence)
+    }
+
+    /// `result` with its calibrated confidence; unchanged if the model
+    /// reported none
+    pub fn apply(&self, result: ClassificationResult) -> ClassificationResult {
+        match result.confidence {
+            Some(confidence) => {
+                let calibrated = self.calibrate(&result.intent, confidence);
+                result.with_calibrated_confidence(calibrated)
+            }
+            None => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::Params;
+
+    fn outcome(intent: Intent, confidence: f32, correct: bool) -> Outcome {
+        Outcome {
+            intent,
+            confidence,
+            correct,
+        }
+    }
+
+    /// An overconfident model: right half the time at 0.9, always at 0.99
+    fn overconfident() -> OutcomeLog {
+        let mut log = OutcomeLog::new();
+        for i in 0..10 {
+            log.push(outcome(Intent::SendEmail, 0.9, i % 2 == 0));
+            log.push(outcome(Intent::SendEmail, 0.99, true));
+        }
+        log
+    }
+
+    #[test]
+    fn test_curve_maps_confidence_to_observed_accuracy() {
+        let curve = CalibrationCurve::fit(overconfident().outcomes());
+
+        assert_eq!(curve.samples(), 20);
+        assert!((curve.calibrate(0.9) - 0.5).abs() < 1e-6);
+        assert!((curve.calibrate(0.99) - 1.0).abs() < 1e-6);
+        assert!((curve.calibrate(0.5) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_curve_is_monotonic() {
+        let outcomes = [
+            outcome(Intent::NoAction, 0.2, true),
+            outcome(Intent::NoAction, 0.6, false),
+            outcome(Intent::NoAction, 0.8, true),
+        ];
+
+        let curve = CalibrationCurve::fit(&outcomes);
+
+        let accuracies: Vec<f32> = curve.points().iter().map(|p| p.accuracy).collect();
+        assert!(accuracies.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(curve.points().len(), 2);
+    }
+
+    #[test]
+    fn test_intents_without_enough_history_use_the_overall_curve() {
+        let mut log = overconfident();
+        log.push(outcome(Intent::ScheduleMeeting, 0.9, false));
+        let calibration = Calibration::fit(&log, 10);
+
+        let email = calibration.apply(
+            ClassificationResult::new(Intent::SendEmail, Params::new(None, None))
+                .with_confidence(0.9),
+        );
+        let meeting = calibration.calibrate(&Intent::ScheduleMeeting, 0.99);
+
+        assert!((email.calibrated_confidence.unwrap() - 0.5).abs() < 1e-6);
+        assert_eq!(email.effective_confidence(), email.calibrated_confidence);
+        assert!(meeting > 0.9);
+        assert!(Calibration::fit(&log, 100).is_empty());
+    }
+
+    #[test]
+    fn test_reviewed_outcome_and_jsonl_round_trip() {
+        let predicted = ClassificationResult::new(Intent::SendEmail, Params::new(None, None))
+            .with_confidence(1.7);
+
+        let outcome = Outcome::reviewed(&predicted, &Intent::NoAction).unwrap();
+        let path = std::env::temp_dir().join("calibration_outcomes_test.jsonl");
+        let _ = fs::remove_file(&path);
+        OutcomeLog::append_to(&path, &outcome).unwrap();
+
+        assert_eq!(outcome.confidence, 1.0);
+        assert!(!outcome.correct);
+        assert_eq!(
+            OutcomeLog::load_jsonl(&path).unwrap().outcomes(),
+            &[outcome]
+        );
+        assert_eq!(
+            Outcome::reviewed(
+                &ClassificationResult::new(Intent::NoAction, Params::new(None, None)),
+                &Intent::NoAction
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::agent::{ClassificationResult, Intent};
+
+/// The intent before and after
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntentChange {
+    pub before: Intent,
+    pub after: Intent,
+}
+
+/// One param or detail that differs; `None` where the field is absent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Param name, or `details.<name>` for extracted details
+    pub field: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// What changed between two classifications of the same request, e.g.
+/// across models, prompt versions or eval runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClassificationDiff {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intent: Option<IntentChange>,
+    /// In field order: params first, then details
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldChange>,
+}
+
+impl ClassificationDiff {
+    pub fn between(before: &ClassificationResult, after: &ClassificationResult) -> Self {
+        let intent = (before.intent != after.intent).then(|| IntentChange {
+            before: before.intent.clone(),
+            after: after.intent.clone(),
+        });
+        let mut fields = changed_fields("", &fields_of(&before.params), &fields_of(&after.params));
+        fields.extend(changed_fields(
+            "details.",
+            &fields_of(&before.details),
+            &fields_of(&after.details),
+        ));
+        Self { intent, fields }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intent.is_none() && self.fields.is_empty()
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldChange> {
+        self.fields.iter().find(|change| change.field == name)
+    }
+}
+
+impl ClassificationResult {
+    /// Intent and per-field changes from this result to `other`
+    pub fn diff(&self, other: &ClassificationResult) -> ClassificationDiff {
+        ClassificationDiff::between(self, other)
+    }
+}
+
+/// One line per change, e.g. `recipient: "Eva" -> "Bruno"`
+impl fmt::Display for ClassificationDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        if let Some(change) = &self.intent {
+            writeln!(f, "intent: {} -> {}", change.before, change.after)?;
+        }
+        let show = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "(none)".to_string(),
+        };
+        for change in &self.fields {
+            writeln!(
+                f,
+                "{}: {} -> {}",
+                change.field,
+                show(&change.before),
+                show(&change.after)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Top-level fields as serialized, without the `intent` tag of details
+fn fields_of(value: &impl Serialize) -> Map<String, Value> {
+    match serde_json::to_value(value) {
+        Ok(Value::Object(mut fields)) => {
+            fields.remove("intent");
+            fields
+        }
+        _ => Map::new(),
+    }
+}
+
+fn changed_fields(
+    prefix: &str,
+    before: &Map<String, Value>,
+    after: &Map<String, Value>,
+) -> Vec<FieldChange> {
+    let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (old, new) = (present(before.get(name)), present(after.get(name)));
+            (old != new).then(|| FieldChange {
+                field: format!("{}{}", prefix, name),
+                before: old.cloned(),
+                after: new.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// Null, empty strings and empty lists count as absent
+fn present(value: Option<&Value>) -> Option<&Value> {
+    value.filter(|value| match value {
+        Value::Null => false,
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::{IntentDetails, MeetingDetails, Params};
+    use serde_json::json;
+
+    fn result(intent: Intent, recipient: &str, message: &str) -> ClassificationResult {
+        ClassificationResult::new(
+            intent,
+            Params::with_values(recipient.to_string(), message.to_string()),
+        )
+    }
+
+    #[test]
+    fn test_intent_and_param_changes() {
+        let before = result(Intent::SendEmail, "Eva", "Running late");
+        let after = ClassificationResult::new(
+            Intent::ScheduleMeeting,
+            Params::with_values("Bruno".to_string(), "Running late".to_string())
+                .with_cc(&["eva@company.com"]),
+        );
+
+        let diff = before.diff(&after);
+
+        assert_eq!(
+            diff.intent,
+            Some(IntentChange {
+                before: Intent::SendEmail,
+                after: Intent::ScheduleMeeting,
+            })
+        );
+        assert_eq!(diff.fields.len(), 2);
+        assert_eq!(diff.field("cc").unwrap().before, None);
+        assert_eq!(diff.field("recipient").unwrap().after, Some(json!("Bruno")));
+        assert_eq!(
+            diff.to_string(),
+            "intent: send_email -> schedule_meeting\ncc: (none) -> [\"eva@company.com\"]\nrecipient: \"Eva\" -> \"Bruno\"\n"
+        );
+    }
+
+    #[test]
+    fn test_detail_changes_and_equal_results() {
+        let meeting = |start: &str| {
+            result(Intent::ScheduleMeeting, "Eva", "Sync").with_details(
+                IntentDetails::ScheduleMeeting(MeetingDetails {
+                    attendees: vec!["Eva".to_string()],
+                    title: "Sync".to_string(),
+                    start: start.to_string(),
+                    duration_minutes: None,
+                    location: String::new(),
+                }),
+            )
+        };
+        let plain = result(Intent::NoAction, "", "");
+
+        let diff = meeting("Monday 10am").diff(&meeting("Tuesday 10am"));
+
+        assert_eq!(
+            diff.fields,
+            vec![FieldChange {
+                field: "details.start".to_string(),
+                before: Some(json!("Monday 10am")),
+                after: Some(json!("Tuesday 10am")),
+            }]
+        );
+        assert!(plain.diff(&plain.clone()).is_empty());
+        // An empty message and a missing one read the same
+        assert!(
+            plain
+                .diff(&ClassificationResult::new(
+                    Intent::NoAction,
+                    Params::new(None, None)
+                ))
+                .is_empty()
+        );
+    }
+}
@@ -1,20 +1,118 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::agent::{AgentResult, Intent, classifier::Params};
+use crate::agent::{
+    AgentResult, Intent,
+    classifier::{
+        CURRENT_SCHEMA_VERSION, IntentDetails, Params, response_schema,
+        response_schema::legacy_schema_version,
+    },
+    sentiment::SentimentAssessment,
+};
+use crate::infra::ollama::PromptStrategy;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct ClassificationResult {
+    /// Schema the result was recorded with; `from_json_str` upgrades older
+    /// ones
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub intent: Intent,
     pub params: Params,
+    /// Prompt version (`name@version`) that produced this result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+    /// Intent-specific fields from the specialized extraction pass
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<IntentDetails>,
+    /// Tone of the incoming email the result was classified from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<SentimentAssessment>,
+    /// How the model was prompted, chosen from its probed capabilities
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<PromptStrategy>,
+    /// Confidence the model reported, from 0 to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// `confidence` mapped to the accuracy observed for it in past
+    /// corrections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibrated_confidence: Option<f32>,
 }
 
 impl ClassificationResult {
     pub fn new(intent: Intent, params: Params) -> Self {
-        Self { intent, params }
+        Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            intent,
+            params,
+            prompt_version: None,
+            details: None,
+            sentiment: None,
+            strategy: None,
+            confidence: None,
+            calibrated_confidence: None,
+        }
     }
 
+    /// Attaches specialized details. Details for another intent are ignored;
+    /// email details also refine the generic recipient and message.
+    pub fn with_details(mut self, details: IntentDetails) -> Self {
+        if details.intent() != self.intent {
+            return self;
+        }
+        if let IntentDetails::SendEmail(email) = &details {
+            let params = Params::with_values(email.recipient.clone(), email.message.clone());
+            if let Ok(params) = params.normalized() {
+                self.params = params;
+            }
+        }
+        self.details = Some(details);
+        self
+    }
+
+    pub fn with_sentiment(mut self, sentiment: SentimentAssessment) -> Self {
+        self.sentiment = Some(sentiment);
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: PromptStrategy) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// Model-reported confidence, clamped to 0..=1; NaN is dropped
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = (!confidence.is_nan()).then(|| confidence.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn with_calibrated_confidence(mut self, calibrated_confidence: f32) -> Self {
+        self.calibrated_confidence = Some(calibrated_confidence.clamp(0.0, 1.0));
+        self
+    }
+
+    /// The calibrated confidence if there is one, else the reported one
+    pub fn effective_confidence(&self) -> Option<f32> {
+        self.calibrated_confidence.or(self.confidence)
+    }
+
+    /// True when the source email should go to a person rather than be
+    /// answered automatically
+    pub fn needs_human(&self) -> bool {
+        self.sentiment
+            .as_ref()
+            .is_some_and(SentimentAssessment::needs_human)
+    }
+
+    pub fn with_prompt_version(mut self, prompt_version: &str) -> Self {
+        self.prompt_version = Some(prompt_version.to_string());
+        self
+    }
+
+    /// Parses a result recorded with any schema version
     pub fn from_json_str(json_str: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(json_str)
+        serde_json::from_value(response_schema::migrate_str(json_str)?)
     }
 
     pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
@@ -56,6 +154,53 @@ mod tests {
         assert!(json_string.contains("informing her"));
     }
 
+    #[test]
+    fn test_prompt_version_is_serialized_only_when_set() {
+        let result = ClassificationResult::new(Intent::NoAction, Params::new(None, None));
+        assert!(!result.to_json_string().unwrap().contains("prompt_version"));
+
+        let result = result.with_prompt_version("classify_intent@v2");
+        assert!(
+            result
+                .to_json_string()
+                .unwrap()
+                .contains(r#""prompt_version":"classify_intent@v2""#)
+        );
+    }
+
+    #[test]
+    fn test_with_details_refines_email_params() {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("Eva".to_string(), "late".to_string()),
+        );
+        let details = IntentDetails::SendEmail(crate::agent::classifier::SendEmailDetails {
+            recipient: "Eva@Company.com".to_string(),
+            subject: "Delay".to_string(),
+            message: "I'll be 10 minutes late".to_string(),
+        });
+
+        let result = result.with_details(details);
+
+        assert_eq!(result.params.recipient(), Some("Eva@company.com"));
+        assert_eq!(result.params.message(), Some("I'll be 10 minutes late"));
+        assert!(result.details.is_some());
+    }
+
+    #[test]
+    fn test_with_details_ignores_other_intents() {
+        let result = ClassificationResult::new(Intent::NoAction, Params::new(None, None));
+        let details = IntentDetails::ScheduleMeeting(crate::agent::classifier::MeetingDetails {
+            attendees: vec![],
+            title: String::new(),
+            start: String::new(),
+            duration_minutes: None,
+            location: String::new(),
+        });
+
+        assert!(result.with_details(details).details.is_none());
+    }
+
     #[test]
     fn test_deserialization_from_json() {
         let json_str = r#"
@@ -71,6 +216,23 @@ mod tests {
         assert_eq!(result.intent, Intent::SendEmail);
     }
 
+    #[test]
+    fn test_legacy_recorded_result_is_migrated() {
+        let json_str = r#"{"action": "send_message", "params": {"recipient": "Sofia"}}"#;
+
+        let result = ClassificationResult::from_json_str(json_str).unwrap();
+
+        assert_eq!(result.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(result.intent, Intent::SendEmail);
+        assert_eq!(result.params.recipient(), Some("Sofia"));
+        assert!(
+            result
+                .to_json_string()
+                .unwrap()
+                .starts_with(r#"{"schema_version":2,"#)
+        );
+    }
+
     #[test]
     fn test_roundtrip_serialization() {
         let original_params = Params::with_values(
@@ -0,0 +1,255 @@
+use serde::Serialize;
+
+use crate::agent::Intent;
+use crate::i18n::Locale;
+use crate::prompt::{PromptError, PromptTemplate, PromptVersion, ReasoningMode, localized_name};
+
+pub const CLASSIFY_INTENT_PROMPT: &str = "classify_intent";
+const CLASSIFY_INTENT_TEMPLATE: &str = include_str!("../../../prompts/classify_intent@v1.txt");
+const CLASSIFY_INTENT_PT_BR_TEMPLATE: &str =
+    include_str!("../../../prompts/classify_intent.pt-BR@v1.txt");
+/// Stands in for the input while the prompt is rendered for splitting
+const INPUT_SLOT: &str = "@@input@@";
+
+/// Few-shot example shown in the classifier prompt
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PromptExample {
+    pub input: String,
+    pub output: String,
+}
+
+impl PromptExample {
+    pub fn new(input: &str, output: &str) -> Self {
+        Self {
+            input: input.to_string(),
+            output: output.to_string(),
+        }
+    }
+}
+
+/// Variables available to the `classify_intent` template
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ClassifierContext {
+    pub input: String,
+    pub intents: Vec<String>,
+    pub examples: Vec<PromptExample>,
+    pub chain_of_thought: bool,
+    /// Quick reply template names the model may pick from
+    pub quick_replies: Vec<String>,
+    /// Asks for a JSON array with one object per request
+    pub multi_intent: bool,
+}
+
+impl ClassifierContext {
+    pub fn new(input: &str) -> Self {
+        Self::for_locale(input, Locale::En)
+    }
+
+    /// Context with few-shot examples written in `locale`
+    pub fn for_locale(input: &str, locale: Locale) -> Self {
+        let examples = match locale {
+            Locale::En => vec![
+                PromptExample::new(
+                    "Send an email to Carlos about the delay",
+                    r#"{"schema_version":2, "intent":"send_email", "params":{"recipient":"Carlos","message":"About the delay"}}"#,
+                ),
+                PromptExample::new(
+                    "Send message to Sofia: I'll arrive in 10 min",
+                    r#"{"schema_version":2, "intent":"send_email", "params":{"recipient":"Sofia","message":"I'll arrive in 10 min"}}"#,
+                ),
+            ],
+            Locale::PtBr => vec![
+                PromptExample::new(
+                    "Envie um email para o Carlos sobre o atraso",
+                    r#"{"schema_version":2, "intent":"send_email", "params":{"recipient":"Carlos","message":"Sobre o atraso"}}"#,
+                ),
+                PromptExample::new(
+                    "Mande mensagem para a Sofia: chego em 10 min",
+                    r#"{"schema_version":2, "intent":"send_email", "params":{"recipient":"Sofia","message":"Chego em 10 min"}}"#,
+                ),
+            ],
+        };
+        Self {
+            input: input.to_string(),
+            intents: vec![
+                "send_email".to_string(),
+                "schedule_meeting".to_string(),
+                "no_action".to_string(),
+            ],
+            examples,
+            chain_of_thought: false,
+            quick_replies: Vec::new(),
+            multi_intent: false,
+        }
+    }
+
+    /// Offers the `quick_reply` intent with these template names
+    pub fn with_quick_replies(mut self, names: &[String]) -> Self {
+        if names.is_empty() {
+            return self;
+        }
+        self.quick_replies = names.to_vec();
+        let position = self.intents.len().saturating_sub(1);
+        self.intents
+            .insert(position, Intent::QuickReply.to_string());
+        self
+    }
+
+    pub fn with_reasoning(mut self, mode: ReasoningMode) -> Self {
+        self.chain_of_thought = mode.is_chain_of_thought();
+        self
+    }
+
+    pub fn with_multi_intent(mut self, multi_intent: bool) -> Self {
+        self.multi_intent = multi_intent;
+        self
+    }
+}
+
+/// Splits the prompt `template` renders for `context` before the line
+/// holding the input: the instructions, which can be baked into a model's
+/// system prompt, and the rest with the input filled in
+pub fn split_classifier_prompt(
+    template: &PromptTemplate,
+    context: &ClassifierContext,
+) -> Result<(String, String), PromptError> {
+    let slotted = ClassifierContext {
+        input: INPUT_SLOT.to_string(),
+        ..context.clone()
+    };
+    let rendered = template.render(&slotted)?;
+    let Some(slot) = rendered.find(INPUT_SLOT) else {
+        return Err(PromptError::Render(format!(
+            "'{}' does not show the input",
+            template.name()
+        )));
+    };
+    let start = rendered[..slot]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    Ok((
+        rendered[..start].trim_end().to_string(),
+        rendered[start..].replace(INPUT_SLOT, &context.input),
+    ))
+}
+
+/// Built-in template, used unless one is loaded from `prompts/`
+pub fn default_classifier_template() -> PromptTemplate {
+    PromptTemplate::new(CLASSIFY_INTENT_PROMPT, CLASSIFY_INTENT_TEMPLATE)
+}
+
+pub fn default_classifier_version() -> PromptVersion {
+    PromptVersion::new(CLASSIFY_INTENT_PROMPT, "v1", default_classifier_template())
+}
+
+/// Built-in template for `locale`, registered as `classify_intent.<tag>`
+pub fn localized_classifier_version(locale: Locale) -> PromptVersion {
+    let source = match locale {
+        Locale::En => CLASSIFY_INTENT_TEMPLATE,
+        Locale::PtBr => CLASSIFY_INTENT_PT_BR_TEMPLATE,
+    };
+    let name = localized_name(CLASSIFY_INTENT_PROMPT, locale);
+    PromptVersion::new(&name, "v1", PromptTemplate::new(&name, source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_at_input() {
+        let context = ClassifierContext::new("Email Eva");
+        let (instructions, rest) =
+            split_classifier_prompt(&default_classifier_template(), &context).unwrap();
+
+        assert!(instructions.starts_with("Classify intent"));
+        assert!(instructions.contains("Send message to Sofia"));
+        assert!(!instructions.contains("Email Eva"));
+        assert!(rest.starts_with("Input: \"Email Eva\""));
+        assert_eq!(
+            format!("{}\n{}", instructions, rest)
+                .split_whitespace()
+                .collect::<Vec<_>>(),
+            default_classifier_template()
+                .render(&context)
+                .unwrap()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_default_template_renders() {
+        let prompt = default_classifier_template()
+            .render(&ClassifierContext::new("Avise a Eva que vou atrasar"))
+            .unwrap();
+
+        assert!(prompt.starts_with("Classify intent and extract parameters (JSON format):"));
+        assert!(
+            prompt.contains("Example 2:\nInput: \"Send message to Sofia: I'll arrive in 10 min\"")
+        );
+        assert!(prompt.contains("action (send_email, schedule_meeting, no_action)"));
+        assert!(prompt.ends_with("Input: \"Avise a Eva que vou atrasar\"\nOutput: \n"));
+    }
+
+    #[test]
+    fn test_quick_replies_are_offered_when_configured() {
+        let names = vec![
+            "decline_meeting".to_string(),
+            "acknowledge_receipt".to_string(),
+        ];
+
+        let prompt = default_classifier_template()
+            .render(&ClassifierContext::new("Tell Eva I got it").with_quick_replies(&names))
+            .unwrap();
+
+        assert!(prompt.contains("action (send_email, schedule_meeting, quick_reply, no_action)"));
+        assert!(prompt.contains(
+            "set params.template to one of: decline_meeting, acknowledge_receipt.\nInput:"
+        ));
+    }
+
+    #[test]
+    fn test_multi_intent_asks_for_an_array() {
+        let context = ClassifierContext::new("Email Eva and book a call with Bruno");
+
+        let single = default_classifier_template().render(&context).unwrap();
+        let multi = default_classifier_template()
+            .render(&context.with_multi_intent(true))
+            .unwrap();
+
+        assert!(!single.contains("JSON array"));
+        assert!(multi.contains("return a JSON array with one object per request"));
+    }
+
+    #[test]
+    fn test_portuguese_template_renders() {
+        let version = localized_classifier_version(Locale::PtBr);
+        let prompt = version
+            .template
+            .render(&ClassifierContext::for_locale(
+                "Avise a Eva que vou atrasar",
+                Locale::PtBr,
+            ))
+            .unwrap();
+
+        assert_eq!(version.id(), "classify_intent.pt-BR@v1");
+        assert!(prompt.starts_with("Classifique a intenção"));
+        assert!(prompt.contains("Entrada: \"Envie um email para o Carlos sobre o atraso\""));
+        assert!(prompt.ends_with("Entrada: \"Avise a Eva que vou atrasar\"\nSaída: \n"));
+    }
+
+    #[test]
+    fn test_chain_of_thought_asks_for_final_answer() {
+        let prompt = default_classifier_template()
+            .render(
+                &ClassifierContext::new("Avise a Eva")
+                    .with_reasoning(ReasoningMode::ChainOfThought),
+            )
+            .unwrap();
+
+        assert!(prompt.contains("reason step by step"));
+        assert!(prompt.contains(crate::infra::ollama::FINAL_ANSWER_MARKER));
+        assert!(!prompt.contains("Output: \n"));
+    }
+}
@@ -26,6 +26,7 @@ impl fmt::Display for ClassifierPrompt {
     }
 }
 
+#[derive(Default)]
 pub struct ClassifierPromptBuilder {
     content: Option<String>,
 }
@@ -0,0 +1,74 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::Intent;
+
+/// Fields extracted by the `extract_send_email` prompt
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct SendEmailDetails {
+    pub recipient: String,
+    #[serde(default)]
+    pub subject: String,
+    pub message: String,
+}
+
+/// Fields extracted by the `extract_schedule_meeting` prompt
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct MeetingDetails {
+    #[serde(default)]
+    pub attendees: Vec<String>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub start: String,
+    #[serde(default)]
+    pub duration_minutes: Option<u32>,
+    #[serde(default)]
+    pub location: String,
+}
+
+/// Intent-specific parameters from the second, specialized extraction pass
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(tag = "intent", rename_all = "snake_case")]
+pub enum IntentDetails {
+    SendEmail(SendEmailDetails),
+    ScheduleMeeting(MeetingDetails),
+}
+
+impl IntentDetails {
+    pub fn intent(&self) -> Intent {
+        match self {
+            IntentDetails::SendEmail(_) => Intent::SendEmail,
+            IntentDetails::ScheduleMeeting(_) => Intent::ScheduleMeeting,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meeting_details_defaults() {
+        let details: MeetingDetails =
+            serde_json::from_str(r#"{"attendees":["Eva"],"start":"tomorrow 3pm"}"#).unwrap();
+
+        assert_eq!(details.attendees, vec!["Eva".to_string()]);
+        assert_eq!(details.duration_minutes, None);
+        assert_eq!(details.location, "");
+    }
+
+    #[test]
+    fn test_tagged_serialization() {
+        let details = IntentDetails::SendEmail(SendEmailDetails {
+            recipient: "Eva".to_string(),
+            subject: "Delay".to_string(),
+            message: "I'll be late".to_string(),
+        });
+
+        let json = serde_json::to_string(&details).unwrap();
+
+        assert!(json.starts_with(r#"{"intent":"send_email","recipient":"Eva""#));
+        assert_eq!(details.intent(), Intent::SendEmail);
+    }
+}
@@ -0,0 +1,214 @@
+use serde::Serialize;
+
+use crate::{
+    agent::{
+        AgentError, ChatModel, Intent,
+        classifier::{IntentDetails, MeetingDetails, SendEmailDetails},
+    },
+    i18n::Locale,
+    infra::ollama::{OutputConstraint, OutputFormat, parse_json_content},
+    prompt::{PromptRegistry, PromptTemplate, PromptVersion, localized_name},
+};
+
+pub const EXTRACT_SEND_EMAIL_PROMPT: &str = "extract_send_email";
+pub const EXTRACT_SCHEDULE_MEETING_PROMPT: &str = "extract_schedule_meeting";
+
+const EXTRACT_SEND_EMAIL_TEMPLATE: &str =
+    include_str!("../../../prompts/extract_send_email@v1.txt");
+const EXTRACT_SCHEDULE_MEETING_TEMPLATE: &str =
+    include_str!("../../../prompts/extract_schedule_meeting@v1.txt");
+const EXTRACT_SEND_EMAIL_PT_BR_TEMPLATE: &str =
+    include_str!("../../../prompts/extract_send_email.pt-BR@v1.txt");
+const EXTRACT_SCHEDULE_MEETING_PT_BR_TEMPLATE: &str =
+    include_str!("../../../prompts/extract_schedule_meeting.pt-BR@v1.txt");
+
+/// Variables available to the extraction templates
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ExtractionContext {
+    pub input: String,
+}
+
+/// Name of the specialized extraction prompt for an intent, if it has one
+pub fn extraction_prompt(intent: &Intent) -> Option<&'static str> {
+    match intent {
+        Intent::SendEmail => Some(EXTRACT_SEND_EMAIL_PROMPT),
+        Intent::ScheduleMeeting => Some(EXTRACT_SCHEDULE_MEETING_PROMPT),
+        Intent::QuickReply | Intent::NoAction => None,
+    }
+}
+
+/// Built-in extraction prompts, registered alongside the classifier's
+pub fn default_extraction_versions() -> Vec<PromptVersion> {
+    [
+        (
+            EXTRACT_SEND_EMAIL_PROMPT,
+            Locale::En,
+            EXTRACT_SEND_EMAIL_TEMPLATE,
+        ),
+        (
+            EXTRACT_SCHEDULE_MEETING_PROMPT,
+            Locale::En,
+            EXTRACT_SCHEDULE_MEETING_TEMPLATE,
+        ),
+        (
+            EXTRACT_SEND_EMAIL_PROMPT,
+            Locale::PtBr,
+            EXTRACT_SEND_EMAIL_PT_BR_TEMPLATE,
+        ),
+        (
+            EXTRACT_SCHEDULE_MEETING_PROMPT,
+            Locale::PtBr,
+            EXTRACT_SCHEDULE_MEETING_PT_BR_TEMPLATE,
+        ),
+    ]
+    .into_iter()
+    .map(|(prompt, locale, source)| {
+        let name = localized_name(prompt, locale);
+        PromptVersion::new(&name, "v1", PromptTemplate::new(&name, source))
+    })
+    .collect()
+}
+
+/// Schema the extraction output for an intent must match
+pub fn details_constraint(intent: &Intent) -> OutputConstraint {
+    match intent {
+        Intent::SendEmail => OutputConstraint::schema_for::<SendEmailDetails>(),
+        Intent::ScheduleMeeting => OutputConstraint::schema_for::<MeetingDetails>(),
+        Intent::QuickReply | Intent::NoAction => OutputConstraint::Unconstrained,
+    }
+}
+
+/// Parses the model output of an intent's extraction prompt
+pub fn parse_details(intent: &Intent, content: &str) -> Result<IntentDetails, AgentError> {
+    match intent {
+        Intent::SendEmail => {
+            parse_json_content::<SendEmailDetails>(content).map(IntentDetails::SendEmail)
+        }
+        Intent::ScheduleMeeting => {
+            parse_json_content::<MeetingDetails>(content).map(IntentDetails::ScheduleMeeting)
+        }
+        Intent::QuickReply | Intent::NoAction => Err(AgentError::ProcessingError(format!(
+            "{} has no extraction prompt",
+            intent
+        ))),
+    }
+}
+
+/// Runs the second, intent-specific extraction pass with the prompt for
+/// `locale`, constrained as far as `format` allows. Returns `Ok(None)` for
+/// intents without a specialized prompt.
+pub async fn extract_details(
+    client: &dyn ChatModel,
+    prompts: &PromptRegistry,
+    intent: &Intent,
+    input: &str,
+    locale: Locale,
+    assignment_key: &str,
+    format: OutputFormat,
+) -> Result<Option<IntentDetails>, AgentError> {
+    let Some(version) = extraction_prompt(intent)
+        .and_then(|name| prompts.select_localized(name, locale, assignment_key))
+    else {
+        return Ok(None);
+    };
+
+    let prompt = version
+        .template
+        .render(&ExtractionContext {
+            input: input.to_string(),
+        })
+        .map_err(|e| AgentError::ProcessingError(e.to_string()))?;
+
+    let answer = client
+        .ask_constrained(&prompt, &details_constraint(intent).limit(format))
+        .await?;
+
+    parse_details(intent, &answer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extraction_prompt_per_intent() {
+        assert_eq!(
+            extraction_prompt(&Intent::SendEmail),
+            Some(EXTRACT_SEND_EMAIL_PROMPT)
+        );
+        assert_eq!(
+            extraction_prompt(&Intent::ScheduleMeeting),
+            Some(EXTRACT_SCHEDULE_MEETING_PROMPT)
+        );
+        assert_eq!(extraction_prompt(&Intent::NoAction), None);
+    }
+
+    #[test]
+    fn test_default_templates_render_cleanly() {
+        let context = ExtractionContext {
+            input: "Marque uma reunião com a Eva amanhã às 15h".to_string(),
+        };
+
+        for version in default_extraction_versions() {
+            let report = version.template.lint(&context);
+            assert!(report.is_clean(), "{}: {:?}", version.id(), report.issues);
+        }
+    }
+
+    #[test]
+    fn test_details_constraint() {
+        let OutputConstraint::Schema(schema) = details_constraint(&Intent::ScheduleMeeting) else {
+            panic!("Expected a schema");
+        };
+
+        assert!(schema["properties"]["attendees"].is_object());
+        assert_eq!(
+            details_constraint(&Intent::NoAction),
+            OutputConstraint::Unconstrained
+        );
+    }
+
+    #[test]
+    fn test_parse_send_email_details() {
+        let content = r#"```json
+{"recipient":"Eva","subject":"Delay","message":"Vou me atrasar"}
+```"#;
+
+        let details = parse_details(&Intent::SendEmail, content).unwrap();
+
+        assert_eq!(
+            details,
+            IntentDetails::SendEmail(SendEmailDetails {
+                recipient: "Eva".to_string(),
+                subject: "Delay".to_string(),
+                message: "Vou me atrasar".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_meeting_details() {
+        let content = r#"{"attendees":["Eva","john@company.com"],"title":"Sync","start":"amanhã 15h","duration_minutes":30,"location":""}"#;
+
+        let IntentDetails::ScheduleMeeting(details) =
+            parse_details(&Intent::ScheduleMeeting, content).unwrap()
+        else {
+            panic!("Expected meeting details");
+        };
+
+        assert_eq!(details.attendees.len(), 2);
+        assert_eq!(details.duration_minutes, Some(30));
+    }
+
+    #[test]
+    fn test_parse_rejects_wrong_schema() {
+        assert!(matches!(
+            parse_details(&Intent::SendEmail, r#"{"attendees":[]}"#),
+            Err(AgentError::DeserializationError(_))
+        ));
+        assert!(matches!(
+            parse_details(&Intent::SendEmail, "Sure, I'll send it."),
+            Err(AgentError::MalformedModelOutput { raw }) if raw == "Sure, I'll send it."
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeSet;
+
+use crate::agent::Intent;
+use crate::config::LexiconConfig;
+use crate::i18n::Locale;
+use crate::infra::email::transliterate;
+
+/// A keyword that decided the intent
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeywordMatch {
+    pub intent: Intent,
+    pub locale: Locale,
+    pub keyword: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Keyword {
+    intent: Intent,
+    locale: Locale,
+    phrase: String,
+    /// Folded form the input is compared against
+    folded: String,
+}
+
+/// Rule-based pre-classifier: decides the intent from per-language keyword
+/// lexicons when exactly one intent's phrases occur in the input, so
+/// obvious requests skip the classification call
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeywordClassifier {
+    keywords: Vec<Keyword>,
+}
+
+impl KeywordClassifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_config(config: &LexiconConfig) -> Self {
+        let mut classifier = Self::new();
+        for (locale, lexicon) in &config.keywords {
+            for (intent, phrases) in lexicon {
+                for phrase in phrases {
+                    classifier = classifier.with_keyword(*locale, intent.clone(), phrase);
+                }
+            }
+        }
+        classifier
+    }
+
+    pub fn with_keyword(mut self, locale: Locale, intent: Intent, phrase: &str) -> Self {
+        let folded = fold(phrase);
+        if !folded.trim().is_empty() {
+            self.keywords.push(Keyword {
+                intent,
+                locale,
+                phrase: phrase.to_string(),
+                folded,
+            });
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_empty()
+    }
+
+    /// The intent if the input matches keywords of one intent only; `None`
+    /// when nothing or several intents match
+    pub fn classify(&self, input: &str) -> Option<KeywordMatch> {
+        let input = fold(input);
+        let matches: Vec<&Keyword> = self
+            .keywords
+            .iter()
+            .filter(|keyword| input.contains(&keyword.folded))
+            .collect();
+        let intents: BTreeSet<&Intent> = matches.iter().map(|k| &k.intent).collect();
+        if intents.len() != 1 {
+            return None;
+        }
+        matches.first().map(|keyword| KeywordMatch {
+            intent: keyword.intent.clone(),
+            locale: keyword.locale,
+            keyword: keyword.phrase.clone(),
+        })
+    }
+}
+
+/// Lowercase ASCII words padded with spaces, so `Marque uma REUNIÃO!`
+/// becomes ` marque uma reuniao ` and phrases only match whole words
+fn fold(text: &str) -> String {
+    let ascii = transliterate(text).to_lowercase();
+    let words: Vec<&str> = ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classifier() -> KeywordClassifier {
+        KeywordClassifier::from_config(&LexiconConfig::default())
+    }
+
+    #[test]
+    fn test_portuguese_keywords_ignore_case_and_accents() {
+        let found = classifier()
+            .classify("Por favor, MARQUE UMA REUNIAO com a Eva amanhã")
+            .unwrap();
+
+        assert_eq!(found.intent, Intent::ScheduleMeeting);
+        assert_eq!(found.locale, Locale::PtBr);
+        assert_eq!(found.keyword, "marque uma reunião");
+        assert_eq!(
+            classifier()
+                .classify("Quero enviar e-mail para o Carlos")
+                .unwrap()
+                .intent,
+            Intent::SendEmail
+        );
+    }
+
+    #[test]
+    fn test_whole_words_only() {
+        let classifier =
+            KeywordClassifier::new().with_keyword(Locale::En, Intent::SendEmail, "mail");
+
+        assert!(classifier.classify("Check the mailbox").is_none());
+        assert!(classifier.classify("Mail Eva").is_some());
+    }
+
+    #[test]
+    fn test_conflicting_intents_are_left_to_the_model() {
+        assert!(
+            classifier()
+                .classify("Send an email to Eva and schedule a meeting with John")
+                .is_none()
+        );
+        assert!(classifier().classify("What time is it?").is_none());
+    }
+
+    #[test]
+    fn test_config_toml_lexicons_load() {
+        let config = crate::config::Config::load_from_file("config.toml").unwrap();
+
+        let classifier = KeywordClassifier::from_config(&config.lexicons);
+
+        assert_eq!(
+            classifier
+                .classify("agendar reunião com o time")
+                .unwrap()
+                .locale,
+            Locale::PtBr
+        );
+    }
+}
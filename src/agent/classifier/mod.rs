@@ -1,14 +1,36 @@
+pub mod calibration;
+pub mod classification_diff;
 pub mod classification_result;
+pub mod classifier_context;
 pub mod classifier_promp;
 pub mod intent_classifier_agent;
+pub mod intent_details;
+pub mod intent_extractor;
+pub mod keyword_classifier;
 pub mod params;
+pub mod partial_classification;
 pub mod response_mapper;
+pub mod response_schema;
 
+pub use calibration::{Calibration, CalibrationCurve, CurvePoint, Outcome, OutcomeLog};
+pub use classification_diff::{ClassificationDiff, FieldChange, IntentChange};
 pub use classification_result::ClassificationResult;
+pub use classifier_context::{
+    CLASSIFY_INTENT_PROMPT, ClassifierContext, PromptExample, default_classifier_template,
+    default_classifier_version, localized_classifier_version, split_classifier_prompt,
+};
 pub use classifier_promp::ClassifierPrompt;
-pub use intent_classifier_agent::{IntentClassifierAgent, IntentParam};
+pub use intent_classifier_agent::{IntentClassifierAgent, IntentParam, KEYWORD_SHORTCUT_VERSION};
+pub use intent_details::{IntentDetails, MeetingDetails, SendEmailDetails};
+pub use intent_extractor::{
+    EXTRACT_SCHEDULE_MEETING_PROMPT, EXTRACT_SEND_EMAIL_PROMPT, ExtractionContext, extract_details,
+    extraction_prompt,
+};
+pub use keyword_classifier::{KeywordClassifier, KeywordMatch};
 pub use params::Params;
+pub use partial_classification::{FieldError, PartialClassification};
 pub use response_mapper::{
     Mapper, MapperError, OllamaToClassificationMapper, ToClassificationResult,
     map_ollama_to_classification,
 };
+pub use response_schema::{CURRENT_SCHEMA_VERSION, LEGACY_SCHEMA_VERSION, migrate, schema_version};
@@ -1,21 +1,88 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::infra::email::{EmailAddress, EmailAddressError};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct Params {
     recipient: Option<String>,
     message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    subject: Option<String>,
+    /// Copied recipients, names or addresses like `recipient`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    cc: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    bcc: Vec<String>,
+    /// Quick reply template name, for `quick_reply` results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    /// When to send, as the user said it ("tomorrow morning"); resolved
+    /// with `i18n::resolve_date_time`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    send_at: Option<String>,
+    /// Files the user wants attached, as they referred to them ("the Q3
+    /// report"); mapped to paths with `infra::files::FileResolver`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<String>,
 }
 
 impl Params {
     pub fn new(recipient: Option<String>, message: Option<String>) -> Self {
-        Self { recipient, message }
+        Self {
+            recipient,
+            message,
+            subject: None,
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            template: None,
+            send_at: None,
+            attachments: Vec::new(),
+        }
     }
 
     pub fn with_values(recipient: String, message: String) -> Self {
-        Self {
-            recipient: Some(recipient),
-            message: Some(message),
-        }
+        Self::new(Some(recipient), Some(message))
+    }
+
+    pub fn with_recipient(mut self, recipient: &str) -> Self {
+        self.recipient = Some(recipient.to_string());
+        self
+    }
+
+    pub fn with_message(mut self, message: &str) -> Self {
+        self.message = Some(message.to_string());
+        self
+    }
+
+    pub fn with_subject(mut self, subject: &str) -> Self {
+        self.subject = Some(subject.to_string());
+        self
+    }
+
+    pub fn with_cc(mut self, cc: &[&str]) -> Self {
+        self.cc = cc.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    pub fn with_bcc(mut self, bcc: &[&str]) -> Self {
+        self.bcc = bcc.iter().map(|b| b.to_string()).collect();
+        self
+    }
+
+    pub fn with_template(mut self, template: &str) -> Self {
+        self.template = Some(template.to_string());
+        self
+    }
+
+    pub fn with_send_at(mut self, send_at: &str) -> Self {
+        self.send_at = Some(send_at.to_string());
+        self
+    }
+
+    pub fn with_attachments(mut self, attachments: &[&str]) -> Self {
+        self.attachments = attachments.iter().map(|a| a.to_string()).collect();
+        self
     }
 
     pub fn from_json_str(json_str: &str) -> Result<Self, serde_json::Error> {
@@ -33,6 +100,78 @@ impl Params {
     pub fn message(&self) -> Option<&str> {
         self.message.as_deref()
     }
+
+    /// Subject the user asked for; blank answers count as none
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref().filter(|s| !s.trim().is_empty())
+    }
+
+    /// Copied recipients, blank ones left out
+    pub fn cc(&self) -> Vec<String> {
+        non_blank(&self.cc)
+    }
+
+    /// Blind-copied recipients, blank ones left out
+    pub fn bcc(&self) -> Vec<String> {
+        non_blank(&self.bcc)
+    }
+
+    pub fn template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    /// Requested send time; blank answers count as none
+    pub fn send_at(&self) -> Option<&str> {
+        self.send_at.as_deref().filter(|s| !s.trim().is_empty())
+    }
+
+    /// File references, blank ones left out
+    pub fn attachments(&self) -> Vec<String> {
+        non_blank(&self.attachments)
+    }
+
+    /// Recipient as a validated address, if it is one (it may be a name)
+    pub fn recipient_address(&self) -> Option<EmailAddress> {
+        self.recipient()
+            .and_then(|recipient| EmailAddress::parse(recipient).ok())
+    }
+
+    /// Normalizes address-shaped recipients, cc and bcc included,
+    /// rejecting malformed ones. Recipients given by name are left
+    /// untouched.
+    pub fn normalized(mut self) -> Result<Self, EmailAddressError> {
+        if let Some(recipient) = self.recipient.take() {
+            self.recipient = Some(normalize_recipient(recipient)?);
+        }
+        self.cc = self
+            .cc
+            .into_iter()
+            .map(normalize_recipient)
+            .collect::<Result<_, _>>()?;
+        self.bcc = self
+            .bcc
+            .into_iter()
+            .map(normalize_recipient)
+            .collect::<Result<_, _>>()?;
+        Ok(self)
+    }
+}
+
+fn normalize_recipient(recipient: String) -> Result<String, EmailAddressError> {
+    if EmailAddress::looks_like_address(&recipient) {
+        Ok(EmailAddress::parse(&recipient)?.to_string())
+    } else {
+        Ok(recipient)
+    }
+}
+
+fn non_blank(values: &[String]) -> Vec<String> {
+    values
+        .iter()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 #[cfg(test)]
@@ -167,6 +306,28 @@ mod tests {
         assert_eq!(original.message(), deserialized.message());
     }
 
+    #[test]
+    fn test_roundtrip_with_copies_and_subject() {
+        let original = Params::with_values("Eva".to_string(), "Notes".to_string())
+            .with_subject("Q3 review")
+            .with_cc(&["Bruno", " "])
+            .with_bcc(&["boss@company.com"])
+            .with_attachments(&["the Q3 report"]);
+
+        let json_string = original.to_json_string().unwrap();
+        let deserialized = Params::from_json_str(&json_string).unwrap();
+        let bare = Params::with_values("Eva".to_string(), "Notes".to_string());
+
+        assert_eq!(deserialized.subject(), Some("Q3 review"));
+        assert_eq!(deserialized.cc(), vec!["Bruno"]);
+        assert_eq!(deserialized.bcc(), vec!["boss@company.com"]);
+        assert_eq!(deserialized.attachments(), vec!["the Q3 report"]);
+        assert_eq!(
+            bare.to_json_string().unwrap(),
+            r#"{"recipient":"Eva","message":"Notes"}"#
+        );
+    }
+
     #[test]
     fn test_invalid_json_deserialization() {
         let invalid_json = r#"{"invalid": "structure"}"#;
@@ -179,6 +340,38 @@ mod tests {
         assert_eq!(params.message(), None);
     }
 
+    #[test]
+    fn test_send_at() {
+        let params =
+            Params::from_json_str(r#"{"recipient": "Eva", "send_at": "tomorrow morning"}"#)
+                .unwrap();
+        let blank = Params::from_json_str(r#"{"recipient": "Eva", "send_at": " "}"#).unwrap();
+
+        assert_eq!(params.send_at(), Some("tomorrow morning"));
+        assert_eq!(blank.send_at(), None);
+        assert!(
+            !Params::new(None, None)
+                .to_json_string()
+                .unwrap()
+                .contains("send_at")
+        );
+    }
+
+    #[test]
+    fn test_attachments() {
+        let params =
+            Params::from_json_str(r#"{"recipient": "Eva", "attachments": ["the Q3 report", " "]}"#)
+                .unwrap();
+
+        assert_eq!(params.attachments(), vec!["the Q3 report".to_string()]);
+        assert!(
+            !Params::new(None, None)
+                .to_json_string()
+                .unwrap()
+                .contains("attachments")
+        );
+    }
+
     #[test]
     fn test_truly_invalid_json() {
         let invalid_json = r#"{"invalid": json structure"#; // Missing quotes and closing brace
@@ -229,6 +422,54 @@ mod tests {
         assert_eq!(params.message(), deserialized.message());
     }
 
+    #[test]
+    fn test_recipient_address() {
+        let by_address = Params::with_values("Eva@Company.com".to_string(), "hi".to_string());
+        let by_name = Params::with_values("Eva".to_string(), "hi".to_string());
+
+        assert_eq!(
+            by_address.recipient_address().unwrap().to_string(),
+            "Eva@company.com"
+        );
+        assert!(by_name.recipient_address().is_none());
+        assert!(Params::new(None, None).recipient_address().is_none());
+    }
+
+    #[test]
+    fn test_normalized_cleans_address() {
+        let params = Params::with_values(" <eva@Company.COM> ".to_string(), "hi".to_string());
+
+        let normalized = params.normalized().unwrap();
+        assert_eq!(normalized.recipient(), Some("eva@company.com"));
+    }
+
+    #[test]
+    fn test_normalized_keeps_names() {
+        let params = Params::with_values("Eva".to_string(), "hi".to_string());
+
+        assert_eq!(params.normalized().unwrap().recipient(), Some("Eva"));
+    }
+
+    #[test]
+    fn test_normalized_checks_copies() {
+        let params = Params::with_values("Eva".to_string(), "hi".to_string())
+            .with_cc(&["<Bruno@Company.com>", "Carla"]);
+        let malformed = params.clone().with_bcc(&["bruno@@company"]);
+
+        assert_eq!(
+            params.normalized().unwrap().cc(),
+            vec!["Bruno@company.com", "Carla"]
+        );
+        assert!(malformed.normalized().is_err());
+    }
+
+    #[test]
+    fn test_normalized_rejects_malformed_address() {
+        let params = Params::with_values("eva@@company".to_string(), "hi".to_string());
+
+        assert!(params.normalized().is_err());
+    }
+
     #[test]
     fn test_long_content() {
         let long_message = "a".repeat(10000);
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use crate::agent::{
+    Intent,
+    classifier::{Params, response_schema::migrate_str},
+};
+use crate::infra::email::EmailAddress;
+use crate::infra::ollama::extract_json;
+
+/// A field the model got wrong, with a message for the correction form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Whatever could be read from a classification that failed validation,
+/// so a UI can pre-fill a correction form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialClassification {
+    pub intent: Option<Intent>,
+    /// Values as the model gave them, before normalization
+    pub params: Params,
+    pub errors: Vec<FieldError>,
+}
+
+impl PartialClassification {
+    /// Leniently reads a model response, collecting a `FieldError` for each
+    /// field that would make strict mapping fail
+    pub fn from_content(content: &str) -> Self {
+        let Some(value) = extract_json(content)
+            .ok()
+            .and_then(|json| migrate_str(&json).ok())
+        else {
+            return Self {
+                intent: None,
+                params: Params::new(None, None),
+                errors: vec![FieldError::new(
+                    "response",
+                    "No JSON object in the model response",
+                )],
+            };
+        };
+
+        let mut errors = Vec::new();
+        let intent = match value.get("intent") {
+            Some(intent) => serde_json::from_value::<Intent>(intent.clone())
+                .map_err(|_| {
+                    errors.push(FieldError::new(
+                        "intent",
+                        &format!("Unknown intent {}", intent),
+                    ))
+                })
+                .ok(),
+            None => {
+                errors.push(FieldError::new("intent", "Missing intent"));
+                None
+            }
+        };
+
+        let field = |name: &str| {
+            value
+                .get("params")
+                .and_then(|params| params.get(name))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        let recipient = field("recipient");
+        if let Some(recipient) = recipient.as_deref()
+            && EmailAddress::looks_like_address(recipient)
+            && let Err(e) = EmailAddress::parse(recipient)
+        {
+            errors.push(FieldError::new("recipient", &e.to_string()));
+        }
+
+        Self {
+            intent,
+            params: Params::new(recipient, field("message")),
+            errors,
+        }
+    }
+
+    /// True if anything at all could be salvaged
+    pub fn has_data(&self) -> bool {
+        self.intent.is_some()
+            || self.params.recipient().is_some()
+            || self.params.message().is_some()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for PartialClassification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_recipient_keeps_other_fields() {
+        let partial = PartialClassification::from_content(
+            r#"{"intent": "send_email", "params": {"recipient": "eva@@company", "message": "Running late"}}"#,
+        );
+
+        assert_eq!(partial.intent, Some(Intent::SendEmail));
+        assert_eq!(partial.params.recipient(), Some("eva@@company"));
+        assert_eq!(partial.params.message(), Some("Running late"));
+        assert_eq!(partial.errors.len(), 1);
+        assert_eq!(partial.errors[0].field, "recipient");
+        assert!(partial.has_data());
+    }
+
+    #[test]
+    fn test_unknown_intent() {
+        let partial = PartialClassification::from_content(
+            r#"{"intent": "book_flight", "params": {"recipient": "Eva"}}"#,
+        );
+
+        assert_eq!(partial.intent, None);
+        assert_eq!(partial.params.recipient(), Some("Eva"));
+        assert_eq!(
+            partial.to_string(),
+            r#"intent: Unknown intent "book_flight""#
+        );
+    }
+
+    #[test]
+    fn test_no_json() {
+        let partial = PartialClassification::from_content("I could not decide.");
+
+        assert!(!partial.has_data());
+        assert_eq!(partial.errors[0].field, "response");
+    }
+
+    #[test]
+    fn test_valid_content() {
+        let partial = PartialClassification::from_content(
+            r#"{"intent": "no_action", "params": {"recipient": null, "message": null}}"#,
+        );
+
+        assert!(partial.is_valid());
+    }
+}
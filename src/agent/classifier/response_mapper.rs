@@ -1,5 +1,5 @@
 use crate::agent::classifier::ClassificationResult;
-use crate::infra::ollama::OllamaResponseMessage;
+use crate::infra::ollama::{OllamaIntentResponseContent, OllamaResponseMessage};
 use std::error::Error;
 use std::fmt;
 
@@ -41,14 +41,28 @@ impl Mapper<&OllamaResponseMessage, ClassificationResult> for OllamaToClassifica
         let parsed_content = source
             .parsed_content()
             .map_err(|e| MapperError::ParseError(e.to_string()))?;
-
-        // Create ClassificationResult from the parsed content
-        let result = ClassificationResult::new(parsed_content.intent, parsed_content.params);
-
-        Ok(result)
+        from_content(parsed_content)
     }
 }
 
+fn from_content(
+    parsed_content: OllamaIntentResponseContent,
+) -> Result<ClassificationResult, MapperError> {
+    // Reject malformed addresses and normalize valid ones
+    let params = parsed_content
+        .params
+        .normalized()
+        .map_err(|e| MapperError::InvalidContent(e.to_string()))?;
+
+    // Create ClassificationResult from the parsed content
+    let result = ClassificationResult::new(parsed_content.intent, params);
+
+    Ok(match parsed_content.confidence {
+        Some(confidence) => result.with_confidence(confidence),
+        None => result,
+    })
+}
+
 /// Convenience function for mapping
 pub fn map_ollama_to_classification(
     response: &OllamaResponseMessage,
@@ -59,12 +73,23 @@ pub fn map_ollama_to_classification(
 /// Extension trait for OllamaResponseMessage to provide convenient mapping
 pub trait ToClassificationResult {
     fn to_classification_result(&self) -> Result<ClassificationResult, MapperError>;
+
+    /// One result per request of a multi-intent answer
+    fn to_classification_results(&self) -> Result<Vec<ClassificationResult>, MapperError>;
 }
 
 impl ToClassificationResult for OllamaResponseMessage {
     fn to_classification_result(&self) -> Result<ClassificationResult, MapperError> {
         OllamaToClassificationMapper::map(self)
     }
+
+    fn to_classification_results(&self) -> Result<Vec<ClassificationResult>, MapperError> {
+        self.parsed_contents()
+            .map_err(|e| MapperError::ParseError(e.to_string()))?
+            .into_iter()
+            .map(from_content)
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -103,6 +128,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_reported_confidence_is_kept() {
+        let content = r#"{"schema_version":2,"intent":"no_action","params":{"recipient":null,"message":null},"confidence":0.72}"#;
+
+        let response = create_test_response_message(content);
+        let result = OllamaToClassificationMapper::map(&response).unwrap();
+
+        assert_eq!(result.confidence, Some(0.72));
+        assert_eq!(result.calibrated_confidence, None);
+    }
+
     #[test]
     fn test_map_valid_schedule_meeting_response() {
         let content = r#"```json
@@ -274,6 +310,38 @@ mod tests {
         assert_eq!(result.params.message(), Some("Hello 世界! 🌍"));
     }
 
+    #[test]
+    fn test_map_rejects_malformed_recipient_address() {
+        let content = r#"{
+  "intent": "send_email",
+  "params": {
+    "recipient": "eva@",
+    "message": "Hello"
+  }
+}"#;
+
+        let response = create_test_response_message(content);
+        let result = OllamaToClassificationMapper::map(&response);
+
+        assert!(matches!(result, Err(MapperError::InvalidContent(_))));
+    }
+
+    #[test]
+    fn test_map_normalizes_recipient_address() {
+        let content = r#"{
+  "intent": "send_email",
+  "params": {
+    "recipient": "Eva <eva@Company.COM>",
+    "message": "Hello"
+  }
+}"#;
+
+        let response = create_test_response_message(content);
+        let result = OllamaToClassificationMapper::map(&response).unwrap();
+
+        assert_eq!(result.params.recipient(), Some("eva@company.com"));
+    }
+
     #[test]
     fn test_partial_params() {
         let content = r#"```json
@@ -0,0 +1,111 @@
+use serde_json::{Map, Value};
+
+/// Version of the JSON contract between the classifier prompts and the
+/// parsers. Bump it when the expected output changes and add a step to
+/// `migrate`.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// Version assumed for responses and recorded results without a
+/// `schema_version`, which all predate it
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+pub(crate) fn legacy_schema_version() -> u32 {
+    LEGACY_SCHEMA_VERSION
+}
+
+/// `schema_version` of a classification JSON
+pub fn schema_version(value: &Value) -> u32 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .and_then(|version| u32::try_from(version).ok())
+        .unwrap_or(LEGACY_SCHEMA_VERSION)
+        .max(LEGACY_SCHEMA_VERSION)
+}
+
+/// Upgrades a classification JSON to the current schema one version at a
+/// time. Versions newer than this build are left as they are and parsed
+/// best-effort.
+pub fn migrate(mut value: Value) -> Value {
+    let version = schema_version(&value);
+    let Some(object) = value.as_object_mut() else {
+        return value;
+    };
+    if version >= CURRENT_SCHEMA_VERSION {
+        return value;
+    }
+    for from in version..CURRENT_SCHEMA_VERSION {
+        if from == 1 {
+            v1_to_v2(object);
+        }
+    }
+    object.insert("schema_version".to_string(), CURRENT_SCHEMA_VERSION.into());
+    value
+}
+
+/// Parses `json` and upgrades it to the current schema
+pub fn migrate_str(json: &str) -> Result<Value, serde_json::Error> {
+    serde_json::from_str(json).map(migrate)
+}
+
+/// v1 prompts asked for an `action`, their examples used `send_message`
+/// and answers without a recipient sometimes left out `params`
+fn v1_to_v2(object: &mut Map<String, Value>) {
+    if !object.contains_key("intent")
+        && let Some(action) = object.remove("action")
+    {
+        object.insert("intent".to_string(), action);
+    }
+    if object.get("intent").and_then(Value::as_str) == Some("send_message") {
+        object.insert("intent".to_string(), "send_email".into());
+    }
+    object
+        .entry("params")
+        .or_insert_with(|| Value::Object(Map::new()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_missing_version_is_legacy() {
+        assert_eq!(schema_version(&json!({"intent": "no_action"})), 1);
+        assert_eq!(schema_version(&json!({"schema_version": 0})), 1);
+        assert_eq!(schema_version(&json!({"schema_version": 2})), 2);
+    }
+
+    #[test]
+    fn test_v1_response_is_upgraded() {
+        let migrated = migrate(json!({
+            "action": "send_message",
+            "params": {"recipient": "Sofia", "message": "I'll arrive in 10 min"}
+        }));
+
+        assert_eq!(
+            migrated,
+            json!({
+                "schema_version": 2,
+                "intent": "send_email",
+                "params": {"recipient": "Sofia", "message": "I'll arrive in 10 min"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_params_are_added() {
+        let migrated = migrate(json!({"intent": "no_action"}));
+
+        assert_eq!(migrated["params"], json!({}));
+    }
+
+    #[test]
+    fn test_current_and_newer_versions_are_untouched() {
+        let current = json!({"schema_version": 2, "intent": "send_message", "params": {}});
+        let newer = json!({"schema_version": 3, "action": "no_action"});
+
+        assert_eq!(migrate(current.clone()), current);
+        assert_eq!(migrate(newer.clone()), newer);
+    }
+}
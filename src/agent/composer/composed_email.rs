@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::agent::AgentResult;
+use crate::infra::files::{FileResolution, UnresolvedFile};
+
+/// A complete email written from a `send_email` request, ready for review
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ComposedEmail {
+    /// As the user gave it: an address or a contact name
+    pub recipient: String,
+    /// Addresses or contact names, like `recipient`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cc: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bcc: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    /// Files the request referred to, inside the configured directories
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<PathBuf>,
+    /// References no file was found for; shown to the user to fix
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unresolved_attachments: Vec<UnresolvedFile>,
+    /// `[[accounts]]` entry it is sent from; `[smtp]` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+}
+
+impl ComposedEmail {
+    pub fn new(recipient: &str, subject: &str, body: &str) -> Self {
+        Self {
+            recipient: recipient.to_string(),
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+            attachments: Vec::new(),
+            unresolved_attachments: Vec::new(),
+            account_id: None,
+        }
+    }
+
+    pub fn with_copies(mut self, cc: Vec<String>, bcc: Vec<String>) -> Self {
+        self.cc = cc;
+        self.bcc = bcc;
+        self
+    }
+
+    pub fn with_account(mut self, account_id: Option<String>) -> Self {
+        self.account_id = account_id;
+        self
+    }
+
+    pub fn with_attachments(mut self, files: FileResolution) -> Self {
+        self.attachments = files.resolved.into_iter().map(|file| file.path).collect();
+        self.unresolved_attachments = files.unresolved;
+        self
+    }
+
+    /// Whether some referenced file still has to be picked by hand
+    pub fn has_unresolved_attachments(&self) -> bool {
+        !self.unresolved_attachments.is_empty()
+    }
+}
+
+impl AgentResult for ComposedEmail {}
@@ -0,0 +1,407 @@
+use chrono::Local;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel, ClassificationResult, Intent,
+        agent::AgentParam,
+        chat_model::{model_or, ollama_for},
+        classifier::IntentDetails,
+        composer::{ComposedEmail, TemplateVariables, VariableContext, VariableProvider},
+    },
+    config::Config,
+    i18n::{Locale, Tone, detect_locale, text::preview},
+    infra::{
+        files::FileResolver,
+        ollama::{OutputFormat, parse_json_content},
+        resilience::Deadline,
+    },
+};
+
+/// Characters of the request used as subject when the model gives none
+const SUBJECT_PREVIEW_LENGTH: usize = 60;
+
+/// Turns a `send_email` classification into a complete subject and body
+pub struct EmailComposerAgent {
+    model: Option<String>,
+    file_resolver: FileResolver,
+    providers: Vec<Arc<dyn VariableProvider>>,
+    output_format: OutputFormat,
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
+
+impl Default for EmailComposerAgent {
+    fn default() -> Self {
+        Self {
+            model: None,
+            file_resolver: FileResolver::default(),
+            providers: Vec::new(),
+            output_format: Config::get().structured_output.format_for("composer"),
+            chat_model: None,
+        }
+    }
+}
+
+impl EmailComposerAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_model(model: &str) -> Self {
+        Self {
+            model: Some(model.to_string()),
+            ..Self::default()
+        }
+    }
+}
+
+impl EmailComposerAgent {
+    /// Sends the request to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
+    }
+
+    /// Looks up the files the request refers to; the `[files]`
+    /// directories by default
+    pub fn with_file_resolver(mut self, file_resolver: FileResolver) -> Self {
+        self.file_resolver = file_resolver;
+        self
+    }
+
+    /// Offers the provider's value to drafts, e.g. the next free slot for
+    /// "suggest a time next week"
+    pub fn with_variable_provider(mut self, provider: impl VariableProvider + 'static) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    /// How strictly the model's subject and body JSON is constrained
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+pub struct ComposerParam {
+    result: ClassificationResult,
+    locale: Option<Locale>,
+    tone: Tone,
+    relationship: Option<String>,
+    sender_name: Option<String>,
+    account_id: Option<String>,
+    deadline: Option<Deadline>,
+}
+
+impl ComposerParam {
+    pub fn new(result: ClassificationResult) -> Self {
+        Self {
+            result,
+            locale: None,
+            tone: Tone::default(),
+            relationship: None,
+            sender_name: None,
+            account_id: None,
+            deadline: None,
+        }
+    }
+
+    /// Language of the email; the request's own if unset
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    pub fn with_tone(mut self, tone: Tone) -> Self {
+        self.tone = tone;
+        self
+    }
+
+    /// Earlier interactions with the recipient, from
+    /// `RelationshipHistory::summary`
+    pub fn with_relationship(mut self, summary: Option<String>) -> Self {
+        self.relationship = summary;
+        self
+    }
+
+    /// Name to sign the email with
+    pub fn with_sender_name(mut self, sender_name: &str) -> Self {
+        self.sender_name = Some(sender_name.to_string());
+        self
+    }
+
+    /// Account the email is sent from, as picked by the `AccountSelector`
+    pub fn with_account(mut self, account_id: Option<String>) -> Self {
+        self.account_id = account_id;
+        self
+    }
+
+    /// Caller's deadline; the Ollama call never outlives it
+    pub fn with_deadline(mut self, deadline: Option<Deadline>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    fn recipient(&self) -> &str {
+        self.result.params.recipient().unwrap_or_default()
+    }
+
+    fn request(&self) -> &str {
+        self.result.params.message().unwrap_or_default()
+    }
+
+    /// Subject the extraction pass already found, else the one the user
+    /// asked for, if any
+    fn extracted_subject(&self) -> Option<&str> {
+        match &self.result.details {
+            Some(IntentDetails::SendEmail(details)) if !details.subject.trim().is_empty() => {
+                Some(&details.subject)
+            }
+            _ => self.result.params.subject(),
+        }
+    }
+
+    fn locale(&self) -> Locale {
+        self.locale
+            .or_else(|| detect_locale(self.request()))
+            .unwrap_or(Config::get().i18n.locale)
+    }
+}
+
+impl From<ClassificationResult> for ComposerParam {
+    fn from(result: ClassificationResult) -> Self {
+        Self::new(result)
+    }
+}
+
+impl AgentParam for ComposerParam {}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ModelEmail {
+    #[serde(default)]
+    subject: String,
+    body: String,
+}
+
+impl Agent<ComposerParam, ComposedEmail> for EmailComposerAgent {
+    async fn process(&self, input: ComposerParam) -> Result<ComposedEmail, AgentError> {
+        if input.result.intent != Intent::SendEmail {
+            return Err(AgentError::ProcessingError(format!(
+                "Composing needs a send_email result, got {}",
+                input.result.intent
+            )));
+        }
+        if input.recipient().trim().is_empty() || input.request().trim().is_empty() {
+            return Err(AgentError::ProcessingError(
+                "Composing needs a recipient and a message".to_string(),
+            ));
+        }
+
+        let client = model_or(&self.chat_model, || {
+            ollama_for(self.model.as_deref()).with_deadline(input.deadline)
+        });
+        let variables = TemplateVariables::resolve(
+            &self.providers,
+            &VariableContext {
+                request: input.request().to_string(),
+                locale: input.locale(),
+                now: Local::now().naive_local(),
+            },
+        )
+        .await;
+        let answer = client
+            .ask_constrained(
+                &build_prompt(&input, &variables),
+                &self.output_format.constraint_for::<ModelEmail>(),
+            )
+            .await?;
+        let email = parse_json_content::<ModelEmail>(&answer)?;
+        let email = ModelEmail {
+            subject: variables.interpolate(&email.subject),
+            body: variables.interpolate(&email.body),
+        };
+        if email.body.trim().is_empty() {
+            return Err(AgentError::ParseError(
+                "Composing failed: empty body".to_string(),
+            ));
+        }
+
+        let files = self
+            .file_resolver
+            .resolve_all(&input.result.params.attachments());
+        Ok(ComposedEmail::new(
+            input.recipient(),
+            &subject_for(&input, &email.subject),
+            email.body.trim(),
+        )
+        .with_copies(input.result.params.cc(), input.result.params.bcc())
+        .with_attachments(files)
+        .with_account(input.account_id))
+    }
+}
+
+/// The model's subject, else the extracted one, else a preview of the
+/// request
+fn subject_for(input: &ComposerParam, model_subject: &str) -> String {
+    let model_subject = model_subject.trim();
+    if !model_subject.is_empty() {
+        return model_subject.to_string();
+    }
+    input
+        .extracted_subject()
+        .map(str::to_string)
+        .unwrap_or_else(|| preview(input.request(), SUBJECT_PREVIEW_LENGTH))
+}
+
+fn build_prompt(input: &ComposerParam, variables: &TemplateVariables) -> String {
+    let locale = input.locale();
+    let subject = input
+        .extracted_subject()
+        .map(|subject| format!("\nSubject: {}", subject))
+        .unwrap_or_default();
+    let relationship = input
+        .relationship
+        .as_deref()
+        .map(|summary| format!("\n{}", summary))
+        .unwrap_or_default();
+    let signature = input
+        .sender_name
+        .as_deref()
+        .map(|name| format!(" Sign it as {}.", name))
+        .unwrap_or_default();
+    let variables = variables
+        .prompt_section()
+        .map(|section| format!("\n{}", section))
+        .unwrap_or_default();
+    format!(
+        "{} Write in {}. {}{}{}{}{}\nRecipient: {}{}\nWhat to say:\n{}",
+        INSTRUCTION,
+        locale.language_name(),
+        input.tone.guidance(locale),
+        signature,
+        OUTPUT_FORMAT,
+        variables,
+        relationship,
+        input.recipient(),
+        subject,
+        input.request()
+    )
+}
+
+const INSTRUCTION: &str = "Write the complete email the user asked for: a greeting, a body that says everything below in well-formed sentences, and a sign-off. Do not invent facts, dates or promises that are not in the request.";
+const OUTPUT_FORMAT: &str =
+    " Answer only with JSON: {\"subject\": \"a short subject line\", \"body\": \"the email\"}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::{Params, SendEmailDetails};
+    use crate::infra::ollama::MockOllamaClient;
+
+    fn send_email(message: &str) -> ClassificationResult {
+        ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("Eva".to_string(), message.to_string()),
+        )
+    }
+
+    #[test]
+    fn test_prompt_carries_request_tone_and_history() {
+        let prompt = build_prompt(
+            &ComposerParam::new(send_email(
+                "I can't make the meeting, sorry for the short notice",
+            ))
+            .with_locale(Locale::En)
+            .with_tone(Tone::Informal)
+            .with_sender_name("Ana")
+            .with_relationship(Some("History with Eva: 2 earlier emails.".to_string())),
+            &TemplateVariables::default(),
+        );
+
+        assert!(prompt.starts_with(INSTRUCTION));
+        assert!(prompt.contains("Write in English."));
+        assert!(prompt.contains(Tone::Informal.guidance(Locale::En)));
+        assert!(prompt.contains("Sign it as Ana."));
+        assert!(prompt.contains("History with Eva: 2 earlier emails.\nRecipient: Eva"));
+        assert!(
+            prompt.ends_with("What to say:\nI can't make the meeting, sorry for the short notice")
+        );
+    }
+
+    #[test]
+    fn test_language_follows_the_request() {
+        let prompt = build_prompt(
+            &ComposerParam::new(send_email(
+                "Não vou poder comparecer à reunião, peço desculpas pelo aviso em cima da hora",
+            )),
+            &TemplateVariables::default(),
+        );
+
+        assert!(prompt.contains("Write in Brazilian Portuguese."));
+    }
+
+    #[test]
+    fn test_subject_falls_back_to_extraction_then_preview() {
+        let param = ComposerParam::new(send_email("Running late"));
+        let extracted = ComposerParam::new(send_email("Running late").with_details(
+            IntentDetails::SendEmail(SendEmailDetails {
+                recipient: "Eva".to_string(),
+                subject: "Delay".to_string(),
+                message: "Running late".to_string(),
+            }),
+        ));
+
+        assert_eq!(subject_for(&param, " Quick update "), "Quick update");
+        assert_eq!(subject_for(&param, ""), "Running late");
+        assert_eq!(subject_for(&extracted, ""), "Delay");
+        assert!(
+            build_prompt(&extracted, &TemplateVariables::default()).contains("\nSubject: Delay\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_composes_with_an_injected_model() {
+        let model = Arc::new(
+            MockOllamaClient::new()
+                .with_reply(r#"{"subject": "Running late", "body": "Hi Eva,\nI'm running late."}"#),
+        );
+        let agent = EmailComposerAgent::new().with_chat_model(model.clone());
+
+        let email = agent
+            .process(ComposerParam::new(send_email("Tell her I'm running late")))
+            .await
+            .unwrap();
+
+        assert_eq!(email.recipient, "Eva");
+        assert_eq!(email.subject, "Running late");
+        assert_eq!(email.body, "Hi Eva,\nI'm running late.");
+        assert!(
+            model
+                .last_prompt()
+                .unwrap()
+                .ends_with("What to say:\nTell her I'm running late")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_other_intents_are_rejected_without_calling_ollama() {
+        let agent = EmailComposerAgent::new();
+
+        let meeting = agent
+            .process(ComposerParam::from(ClassificationResult::new(
+                Intent::ScheduleMeeting,
+                Params::new(None, None),
+            )))
+            .await;
+        let empty = agent.process(ComposerParam::new(send_email(" "))).await;
+
+        assert!(
+            matches!(meeting, Err(AgentError::ProcessingError(msg)) if msg.contains("schedule_meeting"))
+        );
+        assert!(matches!(empty, Err(AgentError::ProcessingError(_))));
+    }
+}
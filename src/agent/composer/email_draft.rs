@@ -0,0 +1,336 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::agent::{AgentError, chat_model::ChatModel, composer::ComposedEmail};
+use crate::infra::ollama::{ChatMessages, OllamaClient, parse_json_content};
+
+/// One line of a revision's diff against the revision before it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", content = "line", rename_all = "snake_case")]
+pub enum DiffLine {
+    Kept(String),
+    Removed(String),
+    Added(String),
+}
+
+impl DiffLine {
+    pub fn is_change(&self) -> bool {
+        !matches!(self, DiffLine::Kept(_))
+    }
+}
+
+/// A version of the draft and the edit that produced it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DraftRevision {
+    /// 0 for the composed email, then one per edit
+    pub number: usize,
+    /// Edit request that produced this revision; `None` for the first one
+    pub instruction: Option<String>,
+    pub email: ComposedEmail,
+    /// Line diff of subject and body against the previous revision
+    pub diff: Vec<DiffLine>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DraftRevision {
+    /// Diff in unified style, changed lines only
+    pub fn changes(&self) -> String {
+        self.diff
+            .iter()
+            .filter_map(|line| match line {
+                DiffLine::Kept(_) => None,
+                DiffLine::Removed(text) => Some(format!("- {}", text)),
+                DiffLine::Added(text) => Some(format!("+ {}", text)),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// An email being refined through natural-language edits, keeping every
+/// revision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailDraft {
+    revisions: Vec<DraftRevision>,
+}
+
+impl EmailDraft {
+    pub fn new(email: ComposedEmail) -> Self {
+        Self {
+            revisions: vec![DraftRevision {
+                number: 0,
+                instruction: None,
+                email,
+                diff: Vec::new(),
+                created_at: Utc::now(),
+            }],
+        }
+    }
+
+    /// The latest revision's email
+    pub fn current(&self) -> &ComposedEmail {
+        &self.latest().email
+    }
+
+    pub fn latest(&self) -> &DraftRevision {
+        self.revisions
+            .last()
+            .expect("a draft has at least one revision")
+    }
+
+    pub fn revisions(&self) -> &[DraftRevision] {
+        &self.revisions
+    }
+
+    pub fn revision(&self, number: usize) -> Option<&DraftRevision> {
+        self.revisions.get(number)
+    }
+
+    /// Rewrites the current email following `instruction` ("make it
+    /// shorter and more formal") with the configured model
+    pub async fn apply_edit(&mut self, instruction: &str) -> Result<&DraftRevision, AgentError> {
+        self.apply_edit_with(&OllamaClient::new(), instruction)
+            .await
+    }
+
+    /// Like `apply_edit`, asking `model` instead, e.g. an agent's injected
+    /// `ChatModel`
+    pub async fn apply_edit_with(
+        &mut self,
+        model: &dyn ChatModel,
+        instruction: &str,
+    ) -> Result<&DraftRevision, AgentError> {
+        let instruction = instruction.trim();
+        if instruction.is_empty() {
+            return Err(AgentError::ProcessingError(
+                "Editing needs an instruction".to_string(),
+            ));
+        }
+
+        let current = self.current().clone();
+        let messages = ChatMessages::new()
+            .system(&format!("{}{}", INSTRUCTION, OUTPUT_FORMAT))
+            .user(&format!(
+                "Subject: {}\n\n{}\n\nEdit request: {}",
+                current.subject, current.body, instruction
+            ));
+        let content = model.chat(messages).await?;
+        let edited = parse_json_content::<ModelEdit>(&content)?;
+        if edited.body.trim().is_empty() {
+            return Err(AgentError::ParseError(
+                "Editing failed: empty body".to_string(),
+            ));
+        }
+
+        let subject = match edited.subject.trim() {
+            "" => current.subject.as_str(),
+            subject => subject,
+        };
+        let email = ComposedEmail {
+            subject: subject.to_string(),
+            body: edited.body.trim().to_string(),
+            ..current.clone()
+        };
+        self.revisions.push(DraftRevision {
+            number: self.revisions.len(),
+            instruction: Some(instruction.to_string()),
+            diff: line_diff(&text_of(&current), &text_of(&email)),
+            email,
+            created_at: Utc::now(),
+        });
+        Ok(self.latest())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AgentError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AgentError::ProcessingError(format!("Draft not saved: {}", e)))?;
+        // Write then rename so a crash mid-write keeps the previous history
+        let partial = path.with_extension("json.tmp");
+        fs::write(&partial, json).map_err(io_error)?;
+        fs::rename(&partial, path).map_err(io_error)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, AgentError> {
+        let content = fs::read_to_string(path).map_err(io_error)?;
+        let draft: Self = serde_json::from_str(&content)
+            .map_err(|e| AgentError::ParseError(format!("Draft is corrupt: {}", e)))?;
+        if draft.revisions.is_empty() {
+            return Err(AgentError::ParseError(
+                "Draft is corrupt: no revisions".to_string(),
+            ));
+        }
+        Ok(draft)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelEdit {
+    #[serde(default)]
+    subject: String,
+    body: String,
+}
+
+fn io_error(e: std::io::Error) -> AgentError {
+    AgentError::ProcessingError(format!("Draft store: {}", e))
+}
+
+fn text_of(email: &ComposedEmail) -> String {
+    format!("Subject: {}\n\n{}", email.subject, email.body)
+}
+
+/// Line diff from the longest common subsequence of lines
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Kept(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(
+        old[i..]
+            .iter()
+            .map(|line| DiffLine::Removed(line.to_string())),
+    );
+    diff.extend(
+        new[j..]
+            .iter()
+            .map(|line| DiffLine::Added(line.to_string())),
+    );
+    diff
+}
+
+const INSTRUCTION: &str = "You edit email drafts. Apply the user's edit request to the draft and keep everything the request does not ask to change. Do not invent facts, dates or promises that are not in the draft.";
+const OUTPUT_FORMAT: &str =
+    " Answer only with JSON: {\"subject\": \"the subject line\", \"body\": \"the edited email\"}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::ollama::MockOllamaClient;
+
+    fn lunch_draft() -> EmailDraft {
+        EmailDraft::new(ComposedEmail::new(
+            "eva@example.com",
+            "Lunch",
+            "Hey Eva,\nwanna grab lunch tomorrow?\nCheers",
+        ))
+    }
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc", "a\nx\nc\nd");
+
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Kept("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Added("x".to_string()),
+                DiffLine::Kept("c".to_string()),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_edit_adds_revision_with_diff() {
+        let model = MockOllamaClient::new().with_reply(
+            r#"{"subject": "", "body": "Dear Eva,\nwould you be available for lunch tomorrow?\nCheers"}"#,
+        );
+        let mut draft = lunch_draft();
+
+        let revision = draft
+            .apply_edit_with(&model, "make it more formal")
+            .await
+            .unwrap();
+
+        assert_eq!(revision.number, 1);
+        assert_eq!(revision.instruction.as_deref(), Some("make it more formal"));
+        assert_eq!(revision.email.subject, "Lunch");
+        assert_eq!(
+            revision.changes(),
+            "- Hey Eva,\n- wanna grab lunch tomorrow?\n+ Dear Eva,\n+ would you be available for lunch tomorrow?"
+        );
+        assert_eq!(draft.revisions().len(), 2);
+        assert_eq!(
+            draft.revision(0).unwrap().email.body,
+            lunch_draft().current().body
+        );
+
+        let seen = model.requests();
+        let prompt = &seen[0].clone().into_vec()[1].content;
+        assert!(prompt.contains("wanna grab lunch"));
+        assert!(prompt.ends_with("Edit request: make it more formal"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_edit_keeps_history() {
+        let mut draft = lunch_draft();
+
+        let error = draft
+            .apply_edit_with(
+                &MockOllamaClient::new().with_reply(r#"{"body": " "}"#),
+                "shorter",
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, AgentError::ParseError(_)));
+        assert_eq!(draft.revisions().len(), 1);
+        assert!(matches!(
+            draft.apply_edit_with(&MockOllamaClient::new(), "  ").await,
+            Err(AgentError::ProcessingError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("draft-{}.json", std::process::id()));
+        let mut draft = lunch_draft();
+        draft
+            .apply_edit_with(
+                &MockOllamaClient::new()
+                    .with_reply(r#"{"subject": "Lunch tomorrow?", "body": "Eva, lunch?"}"#),
+                "shorter",
+            )
+            .await
+            .unwrap();
+
+        draft.save(&path).unwrap();
+        let loaded = EmailDraft::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, draft);
+        assert_eq!(loaded.current().subject, "Lunch tomorrow?");
+        assert_eq!(
+            loaded.latest().diff[0],
+            DiffLine::Removed("Subject: Lunch".to_string())
+        );
+    }
+}
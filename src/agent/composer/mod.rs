@@ -0,0 +1,12 @@
+pub mod composed_email;
+pub mod email_composer_agent;
+pub mod email_draft;
+pub mod template_variables;
+
+pub use composed_email::ComposedEmail;
+pub use email_composer_agent::{ComposerParam, EmailComposerAgent};
+pub use email_draft::{DiffLine, DraftRevision, EmailDraft, line_diff};
+pub use template_variables::{
+    NextFreeSlotProvider, TemplateVariable, TemplateVariables, VariableContext, VariableFuture,
+    VariableProvider, WeatherProvider,
+};
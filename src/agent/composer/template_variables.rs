@@ -0,0 +1,332 @@
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::agent::tools::CheckCalendarTool;
+use crate::config::{Config, VariablesConfig};
+use crate::i18n::Locale;
+
+pub type VariableFuture<'a> = Pin<Box<dyn Future<Output = Option<String>> + Send + 'a>>;
+
+/// What a provider gets to work out its value
+#[derive(Debug, Clone)]
+pub struct VariableContext {
+    /// The user's request, e.g. "suggest a time next week"
+    pub request: String,
+    pub locale: Locale,
+    pub now: NaiveDateTime,
+}
+
+/// Supplies one value drafts can hold, like the next free meeting slot.
+/// The model writes `{{ name }}` where it goes and the composer fills it
+/// in, so the detail is exact rather than made up.
+pub trait VariableProvider: Send + Sync {
+    /// Placeholder name, e.g. `next_free_slot`
+    fn name(&self) -> &str;
+
+    /// Tells the model what the value is
+    fn description(&self) -> &str;
+
+    /// `None` when there is nothing to offer, e.g. the source is down
+    fn value<'a>(&'a self, context: &'a VariableContext) -> VariableFuture<'a>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub description: String,
+    pub value: String,
+}
+
+/// Values resolved for one draft
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateVariables {
+    variables: Vec<TemplateVariable>,
+}
+
+impl TemplateVariables {
+    /// Asks every provider in turn; those without a value are left out
+    pub async fn resolve(
+        providers: &[Arc<dyn VariableProvider>],
+        context: &VariableContext,
+    ) -> Self {
+        let mut variables = Vec::new();
+        for provider in providers {
+            if let Some(value) = provider.value(context).await {
+                variables.push(TemplateVariable {
+                    name: provider.name().to_string(),
+                    description: provider.description().to_string(),
+                    value,
+                });
+            }
+        }
+        Self { variables }
+    }
+
+    pub fn variables(&self) -> &[TemplateVariable] {
+        &self.variables
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+
+    /// Lines telling the model which placeholders it may use
+    pub fn prompt_section(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let lines: Vec<String> = self
+            .variables
+            .iter()
+            .map(|variable| format!("{{{{ {} }}}}: {}", variable.name, variable.description))
+            .collect();
+        Some(format!(
+            "Where the email needs one of these details, write its placeholder exactly as shown instead of a value:\n{}",
+            lines.join("\n")
+        ))
+    }
+
+    /// Replaces `{{ name }}` and `{{name}}` with the values; unknown
+    /// placeholders are kept
+    pub fn interpolate(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            result.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find("}}") else {
+                rest = &rest[start..];
+                break;
+            };
+            let placeholder = &rest[start..start + end + 2];
+            let name = placeholder[2..placeholder.len() - 2].trim();
+            match self.variables.iter().find(|variable| variable.name == name) {
+                Some(variable) => result.push_str(&variable.value),
+                None => result.push_str(placeholder),
+            }
+            rest = &rest[start + end + 2..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Words asking for a time in the following week
+const NEXT_WEEK: &[&str] = &[
+    "next week",
+    "próxima semana",
+    "proxima semana",
+    "semana que vem",
+];
+
+/// `next_free_slot`: the earliest free slot in the user's calendar, from
+/// next Monday when the request says "next week"
+pub struct NextFreeSlotProvider {
+    calendar: CheckCalendarTool,
+    duration: Duration,
+}
+
+impl NextFreeSlotProvider {
+    pub fn new(calendar: CheckCalendarTool) -> Self {
+        Self::from_config(calendar, &Config::get().variables)
+    }
+
+    pub fn from_config(calendar: CheckCalendarTool, config: &VariablesConfig) -> Self {
+        Self {
+            calendar,
+            duration: Duration::minutes(config.slot_minutes as i64),
+        }
+    }
+
+    pub fn slot(&self, context: &VariableContext) -> Option<NaiveDateTime> {
+        let request = context.request.to_lowercase();
+        let from = if NEXT_WEEK.iter().any(|words| request.contains(words)) {
+            let days = 7 - context.now.weekday().num_days_from_monday() as i64;
+            (context.now.date() + Duration::days(days)).and_time(NaiveTime::MIN)
+        } else {
+            context.now
+        };
+        self.calendar.suggest_start(from, self.duration)
+    }
+}
+
+impl VariableProvider for NextFreeSlotProvider {
+    fn name(&self) -> &str {
+        "next_free_slot"
+    }
+
+    fn description(&self) -> &str {
+        "the next time the user is free to meet"
+    }
+
+    fn value<'a>(&'a self, context: &'a VariableContext) -> VariableFuture<'a> {
+        Box::pin(async move {
+            self.slot(context)
+                .map(|slot| context.locale.format_date_time(slot))
+        })
+    }
+}
+
+/// `current_weather`: a one-line weather report for the user's city from
+/// `[variables] weather_url`, e.g. wttr.in
+pub struct WeatherProvider {
+    url: String,
+    client: reqwest::Client,
+    timeout: std::time::Duration,
+}
+
+impl WeatherProvider {
+    /// `url` may hold `{city}`
+    pub fn new(url: &str, city: &str) -> Self {
+        Self {
+            url: url.replace("{city}", city),
+            client: reqwest::Client::new(),
+            timeout: std::time::Duration::from_secs(5),
+        }
+    }
+
+    /// `None` unless both the URL and the city are configured
+    pub fn from_config(config: &VariablesConfig) -> Option<Self> {
+        match (&config.weather_url, &config.city) {
+            (Some(url), Some(city)) => Some(Self::new(url, city)),
+            _ => None,
+        }
+    }
+}
+
+impl VariableProvider for WeatherProvider {
+    fn name(&self) -> &str {
+        "current_weather"
+    }
+
+    fn description(&self) -> &str {
+        "the weather in the user's city right now"
+    }
+
+    fn value<'a>(&'a self, _: &'a VariableContext) -> VariableFuture<'a> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get(&self.url)
+                .timeout(self.timeout)
+                .send()
+                .await
+                .ok()?
+                .error_for_status()
+                .ok()?;
+            let text = response.text().await.ok()?;
+            Some(text.trim().to_string()).filter(|text| !text.is_empty())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::tools::BusySlot;
+    use crate::i18n::{BusinessCalendar, Region};
+    use chrono::NaiveDate;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Wednesday morning
+    fn context(request: &str) -> VariableContext {
+        VariableContext {
+            request: request.to_string(),
+            locale: Locale::En,
+            now: NaiveDate::from_ymd_opt(2025, 6, 4)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap(),
+        }
+    }
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 6, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn slot_provider() -> NextFreeSlotProvider {
+        let calendar = CheckCalendarTool::new(vec![BusySlot {
+            start: at(9, 9),
+            end: at(9, 11),
+            title: "Planning".to_string(),
+        }])
+        .with_business_calendar(BusinessCalendar::new(Some(Region::Us)));
+        NextFreeSlotProvider::from_config(calendar, &VariablesConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_next_week_slot_skips_busy_time() {
+        let provider = slot_provider();
+
+        let variables = TemplateVariables::resolve(
+            &[Arc::new(provider) as Arc<dyn VariableProvider>],
+            &context("Suggest a time next week to Eva"),
+        )
+        .await;
+
+        assert_eq!(variables.variables()[0].value, "06/09/2025 at 11:00 AM");
+        assert_eq!(
+            slot_provider().slot(&context("Meet Eva soon")),
+            Some(at(4, 9))
+        );
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let variables = TemplateVariables {
+            variables: vec![TemplateVariable {
+                name: "next_free_slot".to_string(),
+                description: "the next time the user is free to meet".to_string(),
+                value: "06/09/2025 at 11:00 AM".to_string(),
+            }],
+        };
+
+        assert_eq!(
+            variables.interpolate(
+                "How about {{ next_free_slot }}? Or {{next_free_slot}}. {{ other }} {{"
+            ),
+            "How about 06/09/2025 at 11:00 AM? Or 06/09/2025 at 11:00 AM. {{ other }} {{"
+        );
+        assert!(
+            variables
+                .prompt_section()
+                .unwrap()
+                .ends_with("{{ next_free_slot }}: the next time the user is free to meet")
+        );
+        assert_eq!(TemplateVariables::default().prompt_section(), None);
+    }
+
+    #[tokio::test]
+    async fn test_weather_from_configured_url() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/{{city}}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let read = stream.read(&mut request).await.unwrap();
+            assert!(String::from_utf8_lossy(&request[..read]).starts_with("GET /Lisbon "));
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 15\r\n\r\nLisbon: +21C \n\n")
+                .await
+                .unwrap();
+        });
+        let config = VariablesConfig {
+            weather_url: Some(url),
+            city: Some("Lisbon".to_string()),
+            ..VariablesConfig::default()
+        };
+
+        let provider = WeatherProvider::from_config(&config).unwrap();
+
+        assert_eq!(
+            provider.value(&context("")).await.as_deref(),
+            Some("Lisbon: +21C")
+        );
+        assert!(WeatherProvider::from_config(&VariablesConfig::default()).is_none());
+    }
+}
@@ -1,5 +1,6 @@
-use crate::agent::{agent::AgentParam, contact::contact_result::ContactResult, Agent, AgentError};
+use crate::agent::{Agent, AgentError, agent::AgentParam, contact::contact_result::ContactResult};
 
+#[derive(Default)]
 pub struct ContactAgent {}
 
 impl ContactAgent {
@@ -8,30 +9,29 @@ impl ContactAgent {
     }
 }
 pub struct ContactParam {
-    input: String
+    input: String,
 }
 
 impl ContactParam {
-    pub fn new(input: &String) -> Self {
-        Self { 
-            input: input.clone()
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: input.to_string(),
         }
     }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
 }
 
-impl AgentParam for  ContactParam {}
+impl AgentParam for ContactParam {}
 
 impl Agent<ContactParam, ContactResult> for ContactAgent {
-    fn process(
-        &self,
-        _input: ContactParam,
-    ) -> impl std::future::Future<Output = Result<ContactResult, AgentError>> + Send {
-        async move {
-            // TODO: Implement contact data discovery logic
+    async fn process(&self, _input: ContactParam) -> Result<ContactResult, AgentError> {
+        // TODO: Implement contact data discovery logic
 
-            Err(AgentError::ProcessingError(
-                "Contact finding not implemented yet".to_string(),
-            ))
-        }
+        Err(AgentError::ProcessingError(
+            "Contact finding not implemented yet".to_string(),
+        ))
     }
 }
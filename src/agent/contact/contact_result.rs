@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::agent::AgentResult;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct ContactResult {}
 
 impl ContactResult {
@@ -1,2 +1,5 @@
 pub mod contact_agent;
 pub mod contact_result;
+pub mod relationship;
+
+pub use relationship::{Interaction, RelationshipHistory, RelationshipSources};
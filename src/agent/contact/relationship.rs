@@ -0,0 +1,342 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::action::{Outbox, OutboxItem, OutboxStatus};
+use crate::agent::Intent;
+use crate::agent::follow_up::FollowUpTracker;
+use crate::i18n::Tone;
+use crate::i18n::text::preview;
+use crate::infra::contacts::{Contact, UserContacts};
+use crate::infra::email::EmailAddress;
+
+/// Sent emails listed in a history unless `with_max_emails` says otherwise
+const DEFAULT_MAX_EMAILS: usize = 3;
+
+/// Characters of an email or meeting kept to say what it was about
+const TOPIC_LENGTH: usize = 40;
+
+/// One email or meeting with the contact
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Interaction {
+    pub at: NaiveDateTime,
+    /// What it was about, shortened
+    pub topic: String,
+    /// Account it was sent from, if not `[smtp]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+}
+
+/// What has happened with one contact so far, newest first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RelationshipHistory {
+    pub address: EmailAddress,
+    pub name: Option<String>,
+    /// Register the contact is written to in
+    pub tone: Option<Tone>,
+    pub emails_sent: usize,
+    /// The latest sent emails, up to the source's maximum
+    pub last_emails: Vec<Interaction>,
+    /// Latest meeting, past or booked
+    pub last_meeting: Option<Interaction>,
+    /// Subjects of questions the contact has not answered yet
+    pub awaiting_reply: Vec<String>,
+}
+
+impl RelationshipHistory {
+    pub fn is_empty(&self) -> bool {
+        self.emails_sent == 0 && self.last_meeting.is_none() && self.awaiting_reply.is_empty()
+    }
+
+    /// Short summary for a drafting prompt; `None` for a first contact
+    /// without a known tone
+    pub fn summary(&self) -> Option<String> {
+        if self.is_empty() && self.tone.is_none() {
+            return None;
+        }
+        let who = match &self.name {
+            Some(name) => format!("{} <{}>", name, self.address),
+            None => self.address.to_string(),
+        };
+        let mut facts = Vec::new();
+        match self.last_emails.first() {
+            Some(last) => facts.push(format!(
+                "{} earlier email(s), the last on {} about \"{}\"",
+                self.emails_sent,
+                last.at.format("%Y-%m-%d"),
+                last.topic
+            )),
+            None => facts.push("no earlier emails".to_string()),
+        }
+        if let Some(meeting) = &self.last_meeting {
+            facts.push(format!(
+                "last meeting \"{}\" on {}",
+                meeting.topic,
+                meeting.at.format("%Y-%m-%d")
+            ));
+        }
+        match self.tone {
+            Some(Tone::Formal) => facts.push("usually addressed formally".to_string()),
+            Some(Tone::Informal) => facts.push("usually addressed informally".to_string()),
+            None => {}
+        }
+        if !self.awaiting_reply.is_empty() {
+            facts.push(format!(
+                "still waiting for a reply about \"{}\"",
+                self.awaiting_reply.join("\", \"")
+            ));
+        }
+        Some(format!("History with {}: {}.", who, facts.join("; ")))
+    }
+}
+
+/// Assembles a `RelationshipHistory` from whichever stores are available
+pub struct RelationshipSources<'a> {
+    contacts: Option<&'a UserContacts>,
+    outbox: Option<&'a Outbox>,
+    follow_ups: Option<&'a FollowUpTracker>,
+    max_emails: usize,
+}
+
+impl<'a> RelationshipSources<'a> {
+    pub fn new() -> Self {
+        Self {
+            contacts: None,
+            outbox: None,
+            follow_ups: None,
+            max_emails: DEFAULT_MAX_EMAILS,
+        }
+    }
+
+    /// Names, tone preference and name matching for plans addressed by name
+    pub fn with_contacts(mut self, contacts: &'a UserContacts) -> Self {
+        self.contacts = Some(contacts);
+        self
+    }
+
+    /// Sent emails and booked meetings
+    pub fn with_outbox(mut self, outbox: &'a Outbox) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    /// Questions still waiting for a reply
+    pub fn with_follow_ups(mut self, follow_ups: &'a FollowUpTracker) -> Self {
+        self.follow_ups = Some(follow_ups);
+        self
+    }
+
+    pub fn with_max_emails(mut self, max_emails: usize) -> Self {
+        self.max_emails = max_emails;
+        self
+    }
+
+    pub fn history(&self, address: &EmailAddress) -> RelationshipHistory {
+        let known = self.contacts.and_then(|contacts| {
+            contacts.contacts().iter().find_map(|c| {
+                c.emails
+                    .iter()
+                    .find(|e| same_address(&e.address, address))
+                    .map(|e| (c, &e.address))
+            })
+        });
+        let contact = known.map(|(contact, _)| contact);
+        // Spelled the way the address book has it
+        let address = known.map_or(address, |(_, address)| address);
+        let items: Vec<OutboxItem> = self
+            .outbox
+            .map(|outbox| {
+                let mut items = outbox.released();
+                items.extend(outbox.sending_soon());
+                items
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|item| {
+                item.plan
+                    .plan()
+                    .recipients
+                    .iter()
+                    .any(|r| refers_to(r, address, contact))
+            })
+            .collect();
+
+        let mut sent: Vec<Interaction> = items
+            .iter()
+            .filter(|item| {
+                item.status == OutboxStatus::Released
+                    && item.plan.plan().intent == Intent::SendEmail
+            })
+            .map(interaction)
+            .collect();
+        sent.sort_by_key(|email| std::cmp::Reverse(email.at));
+        let last_meeting = items
+            .iter()
+            .filter(|item| item.plan.plan().intent == Intent::ScheduleMeeting)
+            .map(interaction)
+            .max_by_key(|meeting| meeting.at);
+        let awaiting_reply = self
+            .follow_ups
+            .map(FollowUpTracker::waiting)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|w| refers_to(&w.recipient, address, contact))
+            .map(|w| w.subject)
+            .collect();
+
+        RelationshipHistory {
+            address: address.clone(),
+            name: contact.map(|c| c.display_name.clone()),
+            tone: contact.and_then(|c| c.tone),
+            emails_sent: sent.len(),
+            last_emails: sent.into_iter().take(self.max_emails).collect(),
+            last_meeting,
+            awaiting_reply,
+        }
+    }
+}
+
+impl Default for RelationshipSources<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn interaction(item: &OutboxItem) -> Interaction {
+    let plan = item.plan.plan();
+    Interaction {
+        at: plan.timing.scheduled_for().unwrap_or(item.send_at),
+        topic: preview(plan.content.as_deref().unwrap_or_default(), TOPIC_LENGTH),
+        account_id: plan.account_id.clone(),
+    }
+}
+
+/// Recipients are stored as the user wrote them: an address or a name
+fn refers_to(recipient: &str, address: &EmailAddress, contact: Option<&Contact>) -> bool {
+    match EmailAddress::parse(recipient) {
+        Ok(parsed) => same_address(&parsed, address),
+        Err(_) => contact.is_some_and(|c| c.matches_name(recipient)),
+    }
+}
+
+/// Local parts compared case-insensitively, like virtually every mail
+/// server does
+fn same_address(a: &EmailAddress, b: &EmailAddress) -> bool {
+    a.to_string().eq_ignore_ascii_case(&b.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::{ActionGate, ConfirmationPolicy, Proposal};
+    use crate::agent::ClassificationResult;
+    use crate::agent::classifier::Params;
+    use chrono::{Duration, NaiveDate};
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn contacts() -> UserContacts {
+        UserContacts::from_json_str(
+            r#"{"contacts": [{
+                "id": "c001",
+                "displayName": "Eva Martins",
+                "firstName": "Eva",
+                "emails": [{"type": "work", "address": "eva@company.com", "primary": true}],
+                "tone": "informal"
+            }]}"#,
+        )
+        .unwrap()
+    }
+
+    fn queue(
+        outbox: &Outbox,
+        gate: &ActionGate,
+        intent: Intent,
+        to: &str,
+        what: &str,
+        now: NaiveDateTime,
+    ) {
+        let result = ClassificationResult::new(
+            intent,
+            Params::with_values(to.to_string(), what.to_string()),
+        );
+        let Proposal::AutoConfirmed(confirmed) = gate.propose(&result).unwrap() else {
+            panic!("expected auto-confirmation");
+        };
+        outbox.enqueue_at(confirmed, now);
+    }
+
+    #[test]
+    fn test_history_from_outbox_and_follow_ups() {
+        let contacts = contacts();
+        let gate = ActionGate::new(ConfirmationPolicy::new(vec![
+            Intent::SendEmail,
+            Intent::ScheduleMeeting,
+        ]));
+        let outbox = Outbox::new(Duration::zero());
+        queue(
+            &outbox,
+            &gate,
+            Intent::SendEmail,
+            "Eva",
+            "Budget numbers for Q3",
+            at(5, 9),
+        );
+        queue(
+            &outbox,
+            &gate,
+            Intent::SendEmail,
+            "eva@company.com",
+            "Thanks for the review",
+            at(8, 9),
+        );
+        queue(
+            &outbox,
+            &gate,
+            Intent::SendEmail,
+            "john@company.com",
+            "Unrelated",
+            at(8, 10),
+        );
+        queue(
+            &outbox,
+            &gate,
+            Intent::ScheduleMeeting,
+            "Eva",
+            "Q3 review",
+            at(7, 15),
+        );
+        outbox.release_due(at(9, 0));
+        let follow_ups = FollowUpTracker::default();
+        follow_ups.record_sent("eva@company.com", "Budget", "Numbers?", at(5, 9));
+
+        let history = RelationshipSources::new()
+            .with_contacts(&contacts)
+            .with_outbox(&outbox)
+            .with_follow_ups(&follow_ups)
+            .with_max_emails(1)
+            .history(&EmailAddress::parse("Eva@company.com").unwrap());
+
+        assert_eq!(history.name.as_deref(), Some("Eva Martins"));
+        assert_eq!(history.emails_sent, 2);
+        assert_eq!(history.last_emails[0].topic, "Thanks for the review");
+        assert_eq!(history.last_meeting.as_ref().unwrap().topic, "Q3 review");
+        assert_eq!(
+            history.summary().unwrap(),
+            "History with Eva Martins <eva@company.com>: 2 earlier email(s), the last on 2025-05-08 about \"Thanks for the review\"; last meeting \"Q3 review\" on 2025-05-07; usually addressed informally; still waiting for a reply about \"Budget\"."
+        );
+    }
+
+    #[test]
+    fn test_first_contact_has_no_summary() {
+        let history =
+            RelationshipSources::new().history(&EmailAddress::parse("new@client.com").unwrap());
+
+        assert!(history.is_empty());
+        assert_eq!(history.summary(), None);
+    }
+}
@@ -0,0 +1,237 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::agent::AgentError;
+use crate::config::{Config, DelegationConfig};
+
+/// Limits for one run; `None` means unlimited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Budget {
+    pub max_llm_calls: Option<u32>,
+    pub max_wall_time: Option<Duration>,
+}
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn from_config(config: &DelegationConfig) -> Self {
+        Self {
+            max_llm_calls: Some(config.max_llm_calls),
+            max_wall_time: Some(Duration::from_secs(config.max_wall_time_secs)),
+        }
+    }
+
+    pub fn with_max_llm_calls(mut self, calls: u32) -> Self {
+        self.max_llm_calls = Some(calls);
+        self
+    }
+
+    pub fn with_max_wall_time(mut self, wall_time: Duration) -> Self {
+        self.max_wall_time = Some(wall_time);
+        self
+    }
+}
+
+struct Usage {
+    limits: Budget,
+    llm_calls: AtomicU32,
+    started: Instant,
+    parent: Option<RunBudget>,
+}
+
+/// Budget being spent by a run. Clones share the same usage; a child
+/// budget also charges its parent, so a sub-agent can never spend more
+/// than the run that delegated to it has left.
+#[derive(Clone)]
+pub struct RunBudget {
+    usage: Arc<Usage>,
+}
+
+tokio::task_local! {
+    static CURRENT_BUDGET: RunBudget;
+}
+
+impl RunBudget {
+    pub fn new(limits: Budget) -> Self {
+        Self::with_parent(limits, None)
+    }
+
+    fn with_parent(limits: Budget, parent: Option<RunBudget>) -> Self {
+        Self {
+            usage: Arc::new(Usage {
+                limits,
+                llm_calls: AtomicU32::new(0),
+                started: Instant::now(),
+                parent,
+            }),
+        }
+    }
+
+    /// Budget for a sub-agent, limited by both `limits` and this budget
+    pub fn child(&self, limits: Budget) -> Self {
+        Self::with_parent(limits, Some(self.clone()))
+    }
+
+    pub fn limits(&self) -> Budget {
+        self.usage.limits
+    }
+
+    pub fn llm_calls(&self) -> u32 {
+        self.usage.llm_calls.load(Ordering::SeqCst)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.usage.started.elapsed()
+    }
+
+    /// Wall time left, taking every ancestor into account
+    pub fn remaining_time(&self) -> Option<Duration> {
+        let own = self
+            .usage
+            .limits
+            .max_wall_time
+            .map(|max| max.saturating_sub(self.elapsed()));
+        let parent = self
+            .usage
+            .parent
+            .as_ref()
+            .and_then(RunBudget::remaining_time);
+        match (own, parent) {
+            (Some(own), Some(parent)) => Some(own.min(parent)),
+            (own, parent) => own.or(parent),
+        }
+    }
+
+    /// Fails once this budget or an ancestor is out of calls or time
+    pub fn check(&self) -> Result<(), AgentError> {
+        if let Some(max) = self.usage.limits.max_llm_calls
+            && self.llm_calls() > max
+        {
+            return Err(calls_exhausted(max));
+        }
+        if let Some(max) = self.usage.limits.max_wall_time
+            && self.elapsed() >= max
+        {
+            return Err(time_exhausted(max));
+        }
+        match &self.usage.parent {
+            Some(parent) => parent.check(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.check().is_err()
+    }
+
+    /// Records one LLM call against this budget and its ancestors
+    pub fn charge_llm_call(&self) -> Result<(), AgentError> {
+        let calls = self.usage.llm_calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(max) = self.usage.limits.max_llm_calls
+            && calls > max
+        {
+            return Err(calls_exhausted(max));
+        }
+        if let Some(max) = self.usage.limits.max_wall_time
+            && self.elapsed() >= max
+        {
+            return Err(time_exhausted(max));
+        }
+        match &self.usage.parent {
+            Some(parent) => parent.charge_llm_call(),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs `future` with this as the current budget, so every Ollama
+    /// call made inside it is charged here
+    pub async fn scope<F: Future>(&self, future: F) -> F::Output {
+        CURRENT_BUDGET.scope(self.clone(), future).await
+    }
+}
+
+impl Default for RunBudget {
+    fn default() -> Self {
+        Self::new(Budget::from_config(&Config::get().delegation))
+    }
+}
+
+/// Budget of the run executing on this task, if any
+pub fn current_budget() -> Option<RunBudget> {
+    CURRENT_BUDGET.try_with(RunBudget::clone).ok()
+}
+
+/// Charges one LLM call to the current budget; free outside a budgeted run
+pub fn charge_llm_call() -> Result<(), AgentError> {
+    match current_budget() {
+        Some(budget) => budget.charge_llm_call(),
+        None => Ok(()),
+    }
+}
+
+fn calls_exhausted(max: u32) -> AgentError {
+    AgentError::BudgetExhausted(format!("more than {} LLM calls", max))
+}
+
+fn time_exhausted(max: Duration) -> AgentError {
+    AgentError::BudgetExhausted(format!("wall time of {:?} used up", max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_llm_call_limit() {
+        let budget = RunBudget::new(Budget::unlimited().with_max_llm_calls(2));
+
+        budget.charge_llm_call().unwrap();
+        budget.charge_llm_call().unwrap();
+        let error = budget.charge_llm_call().unwrap_err();
+
+        assert_eq!(error.to_string(), "Budget exhausted: more than 2 LLM calls");
+        assert!(budget.is_exhausted());
+    }
+
+    #[test]
+    fn test_child_charges_parent() {
+        let run = RunBudget::new(Budget::unlimited().with_max_llm_calls(3));
+        let sub_agent = run.child(Budget::unlimited().with_max_llm_calls(10));
+
+        for _ in 0..3 {
+            sub_agent.charge_llm_call().unwrap();
+        }
+
+        assert_eq!(run.llm_calls(), 3);
+        assert!(sub_agent.charge_llm_call().is_err());
+    }
+
+    #[test]
+    fn test_remaining_time_is_bounded_by_parent() {
+        let run = RunBudget::new(Budget::unlimited().with_max_wall_time(Duration::from_secs(5)));
+        let sub_agent = run.child(Budget::unlimited().with_max_wall_time(Duration::from_secs(60)));
+
+        assert!(sub_agent.remaining_time().unwrap() <= Duration::from_secs(5));
+        assert_eq!(RunBudget::new(Budget::unlimited()).remaining_time(), None);
+    }
+
+    #[tokio::test]
+    async fn test_scope_sets_current_budget() {
+        let budget = RunBudget::new(Budget::unlimited().with_max_llm_calls(1));
+
+        assert!(current_budget().is_none());
+        let result = budget
+            .scope(async {
+                charge_llm_call()?;
+                charge_llm_call()
+            })
+            .await;
+
+        assert!(matches!(result, Err(AgentError::BudgetExhausted(_))));
+        assert!(charge_llm_call().is_ok());
+    }
+}
@@ -0,0 +1,232 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::agent::{
+    Agent, AgentError, AgentResult,
+    agent::AgentParam,
+    delegation::{Budget, RunBudget},
+};
+
+type Value = Box<dyn Any + Send>;
+type DelegateFuture = Pin<Box<dyn Future<Output = Result<Value, AgentError>> + Send>>;
+type Invoke = Box<dyn Fn(Value) -> DelegateFuture + Send + Sync>;
+
+/// Registered agents other agents may delegate to by name, each call
+/// running under an explicit budget
+#[derive(Default)]
+pub struct Delegator {
+    agents: HashMap<String, Invoke>,
+}
+
+impl Delegator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<P, T, A>(&mut self, name: &str, agent: A)
+    where
+        P: AgentParam + Send + 'static,
+        T: AgentResult + Send + 'static,
+        A: Agent<P, T> + Send + Sync + 'static,
+    {
+        let agent = Arc::new(agent);
+        let name_for_errors = name.to_string();
+        let invoke: Invoke = Box::new(move |param: Value| {
+            let agent = agent.clone();
+            let name = name_for_errors.clone();
+            Box::pin(async move {
+                let param = param.downcast::<P>().map_err(|_| {
+                    AgentError::ProcessingError(format!(
+                        "Agent '{}' received an unexpected param type",
+                        name
+                    ))
+                })?;
+                let result = agent.process(*param).await?;
+                Ok(Box::new(result) as Value)
+            })
+        });
+        self.agents.insert(name.to_string(), invoke);
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.agents.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Runs agent `name` under a child of `budget` limited by `limits`.
+    /// Fails with `BudgetExhausted` when the budget runs out before or
+    /// during the call, including calls the agent itself delegates.
+    pub async fn delegate<P, T>(
+        &self,
+        name: &str,
+        param: P,
+        budget: &RunBudget,
+        limits: Budget,
+    ) -> Result<T, AgentError>
+    where
+        P: AgentParam + Send + 'static,
+        T: AgentResult + Send + 'static,
+    {
+        let invoke = self.agents.get(name).ok_or_else(|| {
+            AgentError::ProcessingError(format!("No agent registered as '{}'", name))
+        })?;
+        budget.check()?;
+        let sub_budget = budget.child(limits);
+
+        let call = sub_budget.scope(invoke(Box::new(param)));
+        let result = match sub_budget.remaining_time() {
+            Some(remaining) => tokio::time::timeout(remaining, call)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(AgentError::BudgetExhausted("wall time used up".to_string()))
+                }),
+            None => call.await,
+        };
+
+        let value = match result {
+            Ok(value) => value,
+            // Agents report a refused Ollama call as their own error type
+            Err(e) => return Err(sub_budget.check().err().unwrap_or(e)),
+        };
+        value.downcast::<T>().map(|result| *result).map_err(|_| {
+            AgentError::ProcessingError(format!(
+                "Agent '{}' returned an unexpected result type",
+                name
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::delegation::charge_llm_call;
+    use std::time::Duration;
+
+    struct Question(String);
+    impl AgentParam for Question {}
+
+    #[derive(Debug)]
+    struct Answer(String);
+    impl AgentResult for Answer {}
+
+    /// Keeps asking the model until it is satisfied, which it never is;
+    /// reports failures the way real agents do
+    struct SpiralingAgent;
+
+    impl Agent<Question, Answer> for SpiralingAgent {
+        async fn process(&self, _input: Question) -> Result<Answer, AgentError> {
+            loop {
+                charge_llm_call().map_err(|e| AgentError::NetworkError(e.to_string()))?;
+                tokio::task::yield_now().await;
+            }
+        }
+    }
+
+    struct EchoAgent;
+
+    impl Agent<Question, Answer> for EchoAgent {
+        async fn process(&self, input: Question) -> Result<Answer, AgentError> {
+            charge_llm_call()?;
+            Ok(Answer(input.0))
+        }
+    }
+
+    struct SlowAgent;
+
+    impl Agent<Question, Answer> for SlowAgent {
+        async fn process(&self, input: Question) -> Result<Answer, AgentError> {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(Answer(input.0))
+        }
+    }
+
+    fn delegator() -> Delegator {
+        let mut delegator = Delegator::new();
+        delegator.register("spiral", SpiralingAgent);
+        delegator.register("echo", EchoAgent);
+        delegator.register("slow", SlowAgent);
+        delegator
+    }
+
+    #[tokio::test]
+    async fn test_spiraling_agent_is_stopped() {
+        let run = RunBudget::new(Budget::unlimited().with_max_llm_calls(100));
+
+        let result: Result<Answer, _> = delegator()
+            .delegate(
+                "spiral",
+                Question("?".to_string()),
+                &run,
+                Budget::unlimited().with_max_llm_calls(5),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AgentError::BudgetExhausted(_))));
+        assert_eq!(run.llm_calls(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_delegation_is_charged_to_the_run() {
+        let run = RunBudget::new(Budget::unlimited().with_max_llm_calls(1));
+        let delegator = delegator();
+
+        let answer: Answer = delegator
+            .delegate(
+                "echo",
+                Question("hi".to_string()),
+                &run,
+                Budget::unlimited(),
+            )
+            .await
+            .unwrap();
+        let second: Result<Answer, _> = delegator
+            .delegate(
+                "echo",
+                Question("hi".to_string()),
+                &run,
+                Budget::unlimited(),
+            )
+            .await;
+
+        assert_eq!(answer.0, "hi");
+        assert!(matches!(second, Err(AgentError::BudgetExhausted(_))));
+    }
+
+    #[tokio::test]
+    async fn test_wall_time_limit() {
+        let run = RunBudget::new(Budget::unlimited());
+
+        let result: Result<Answer, _> = delegator()
+            .delegate(
+                "slow",
+                Question("hi".to_string()),
+                &run,
+                Budget::unlimited().with_max_wall_time(Duration::from_millis(20)),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AgentError::BudgetExhausted(_))));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_agent() {
+        let run = RunBudget::new(Budget::unlimited());
+
+        let result: Result<Answer, _> = delegator()
+            .delegate(
+                "nope",
+                Question("hi".to_string()),
+                &run,
+                Budget::unlimited(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AgentError::ProcessingError(_))));
+        assert_eq!(delegator().names(), vec!["echo", "slow", "spiral"]);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod budget;
+pub mod delegator;
+
+pub use budget::{Budget, RunBudget, charge_llm_call, current_budget};
+pub use delegator::Delegator;
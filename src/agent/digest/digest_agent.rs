@@ -0,0 +1,247 @@
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+use crate::{
+    action::{ActionPlan, PlanStatus},
+    agent::{
+        Agent, AgentError, ClassificationResult,
+        agent::AgentParam,
+        digest::Digest,
+        follow_up::AwaitingReply,
+        summarizer::{SummarizerAgent, SummarizerParam, SummaryLength},
+        tools::BusySlot,
+    },
+    i18n::Locale,
+};
+
+/// Compiles a user's classifications, sent emails, meetings and pending
+/// approvals into one digest, with a short overview from the model
+pub struct DigestAgent {
+    summarizer: Option<SummarizerAgent>,
+}
+
+impl Default for DigestAgent {
+    fn default() -> Self {
+        Self {
+            summarizer: Some(SummarizerAgent::new()),
+        }
+    }
+}
+
+impl DigestAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_model(model: &str) -> Self {
+        Self {
+            summarizer: Some(SummarizerAgent::with_model(model)),
+        }
+    }
+
+    /// Builds the digest from the stores alone, without calling the model
+    pub fn without_overview() -> Self {
+        Self { summarizer: None }
+    }
+}
+
+pub struct DigestParam {
+    user: String,
+    date: NaiveDate,
+    locale: Locale,
+    classifications: Vec<ClassificationResult>,
+    plans: Vec<ActionPlan>,
+    meetings: Vec<BusySlot>,
+    awaiting_replies: Vec<AwaitingReply>,
+}
+
+impl DigestParam {
+    pub fn new(user: &str, date: NaiveDate, locale: Locale) -> Self {
+        Self {
+            user: user.to_string(),
+            date,
+            locale,
+            classifications: Vec::new(),
+            plans: Vec::new(),
+            meetings: Vec::new(),
+            awaiting_replies: Vec::new(),
+        }
+    }
+
+    pub fn with_classifications(mut self, classifications: Vec<ClassificationResult>) -> Self {
+        self.classifications = classifications;
+        self
+    }
+
+    /// Plans from the `ActionGate`; executed ones count as sent, pending
+    /// ones as waiting for approval
+    pub fn with_plans(mut self, plans: Vec<ActionPlan>) -> Self {
+        self.plans = plans;
+        self
+    }
+
+    /// Calendar entries; only those starting on the digest date are listed
+    pub fn with_meetings(mut self, meetings: Vec<BusySlot>) -> Self {
+        self.meetings = meetings;
+        self
+    }
+
+    pub fn with_awaiting_replies(mut self, awaiting_replies: Vec<AwaitingReply>) -> Self {
+        self.awaiting_replies = awaiting_replies;
+        self
+    }
+
+    fn compile(self) -> Digest {
+        let mut classified = BTreeMap::new();
+        for result in &self.classifications {
+            *classified.entry(result.intent.clone()).or_insert(0) += 1;
+        }
+        let with_status = |status: PlanStatus| {
+            self.plans
+                .iter()
+                .filter(|plan| plan.status == status)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let mut meetings: Vec<BusySlot> = self
+            .meetings
+            .iter()
+            .filter(|slot| slot.start.date() == self.date)
+            .cloned()
+            .collect();
+        meetings.sort_by_key(|slot| slot.start);
+
+        Digest {
+            sent: with_status(PlanStatus::Executed),
+            pending_approvals: with_status(PlanStatus::PendingConfirmation),
+            user: self.user,
+            date: self.date,
+            locale: self.locale,
+            overview: String::new(),
+            classified,
+            meetings,
+            awaiting_replies: self.awaiting_replies,
+        }
+    }
+}
+
+impl AgentParam for DigestParam {}
+
+impl Agent<DigestParam, Digest> for DigestAgent {
+    async fn process(&self, input: DigestParam) -> Result<Digest, AgentError> {
+        let mut digest = input.compile();
+        let Some(summarizer) = &self.summarizer else {
+            return Ok(digest);
+        };
+        if digest.is_empty() {
+            return Ok(digest);
+        }
+
+        let summary = summarizer
+            .process(
+                SummarizerParam::new(digest.render_sections())
+                    .with_length(SummaryLength::Short)
+                    .with_language(digest.locale),
+            )
+            .await?;
+        digest.overview = summary.summary;
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{Intent, classifier::Params};
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 5, 14).unwrap()
+    }
+
+    fn plan(id: u64, status: PlanStatus) -> ActionPlan {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva@company.com".to_string(), "Running late".to_string()),
+        );
+        let mut plan = ActionPlan::from_classification(id, &result);
+        plan.status = status;
+        plan
+    }
+
+    fn param() -> DigestParam {
+        let at = |day: u32, hour: u32| {
+            NaiveDate::from_ymd_opt(2025, 5, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        DigestParam::new("ana", date(), Locale::En)
+            .with_classifications(vec![
+                ClassificationResult::new(Intent::SendEmail, Params::new(None, None)),
+                ClassificationResult::new(Intent::SendEmail, Params::new(None, None)),
+                ClassificationResult::new(Intent::NoAction, Params::new(None, None)),
+            ])
+            .with_plans(vec![
+                plan(1, PlanStatus::Executed),
+                plan(2, PlanStatus::PendingConfirmation),
+                plan(3, PlanStatus::Rejected),
+            ])
+            .with_meetings(vec![
+                BusySlot {
+                    start: at(15, 10),
+                    end: at(15, 11),
+                    title: "Tomorrow".to_string(),
+                },
+                BusySlot {
+                    start: at(14, 15),
+                    end: at(14, 16),
+                    title: "1:1".to_string(),
+                },
+            ])
+    }
+
+    #[tokio::test]
+    async fn test_compiles_sections() {
+        let digest = DigestAgent::without_overview()
+            .process(param())
+            .await
+            .unwrap();
+
+        assert_eq!(digest.classified[&Intent::SendEmail], 2);
+        assert_eq!(digest.sent[0].id, 1);
+        assert_eq!(digest.pending_approvals[0].id, 2);
+        assert_eq!(digest.meetings.len(), 1);
+        assert_eq!(
+            digest.render_text(),
+            "Requests handled: 3 (send_email: 2, no_action: 1)\n\n\
+             Emails sent (1):\n- #1 eva@company.com: Running late\n\n\
+             Waiting for your approval (1):\n- #2 eva@company.com: Running late\n\n\
+             Meetings (1):\n- 3:00 PM–4:00 PM 1:1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_day_skips_the_model() {
+        let digest = DigestAgent::new()
+            .process(DigestParam::new("ana", date(), Locale::PtBr))
+            .await
+            .unwrap();
+
+        assert!(digest.is_empty());
+        assert_eq!(digest.subject(), "Resumo diário – 14/05/2025");
+    }
+
+    #[tokio::test]
+    async fn test_json_report() {
+        let digest = DigestAgent::without_overview()
+            .process(param())
+            .await
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&digest.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["user"], "ana");
+        assert_eq!(json["classified"]["send_email"], 2);
+        assert_eq!(json["meetings"][0]["title"], "1:1");
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::action::ActionPlan;
+use crate::agent::{AgentResult, Intent, follow_up::AwaitingReply, tools::BusySlot};
+use crate::i18n::{Locale, text::preview};
+use crate::infra::email::{EmailAddress, MimeMessage};
+
+/// Longest email body shown per line of the digest
+const PREVIEW_GRAPHEMES: usize = 60;
+
+/// How a digest is delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFormat {
+    #[default]
+    Email,
+    Json,
+}
+
+/// One user's day at a glance
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Digest {
+    pub user: String,
+    pub date: NaiveDate,
+    pub locale: Locale,
+    /// A few sentences written by the model; empty when not requested
+    pub overview: String,
+    /// Requests classified since the last digest, per intent
+    pub classified: BTreeMap<Intent, usize>,
+    pub sent: Vec<ActionPlan>,
+    pub pending_approvals: Vec<ActionPlan>,
+    pub meetings: Vec<BusySlot>,
+    pub awaiting_replies: Vec<AwaitingReply>,
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.classified.is_empty()
+            && self.sent.is_empty()
+            && self.pending_approvals.is_empty()
+            && self.meetings.is_empty()
+            && self.awaiting_replies.is_empty()
+    }
+
+    pub fn subject(&self) -> String {
+        format!(
+            "{} {}",
+            self.locale.pick("Daily digest –", "Resumo diário –"),
+            self.locale.format_date(self.date)
+        )
+    }
+
+    /// Plain-text body: the overview followed by one section per
+    /// non-empty source
+    pub fn render_text(&self) -> String {
+        let mut text = String::new();
+        if !self.overview.is_empty() {
+            let _ = writeln!(text, "{}\n", self.overview);
+        }
+        text.push_str(&self.render_sections());
+        text.trim_end().to_string()
+    }
+
+    /// Sections without the overview, also what the overview is written from
+    pub fn render_sections(&self) -> String {
+        let locale = self.locale;
+        let mut text = String::new();
+        if !self.classified.is_empty() {
+            let counts: Vec<String> = self
+                .classified
+                .iter()
+                .map(|(intent, count)| format!("{}: {}", intent, count))
+                .collect();
+            let _ = writeln!(
+                text,
+                "{} {} ({})\n",
+                locale.pick("Requests handled:", "Pedidos tratados:"),
+                self.classified.values().sum::<usize>(),
+                counts.join(", ")
+            );
+        }
+        let plan_line = |plan: &ActionPlan| {
+            format!(
+                "#{} {}: {}",
+                plan.id,
+                plan.recipients.join(", "),
+                preview(
+                    plan.content.as_deref().unwrap_or_default(),
+                    PREVIEW_GRAPHEMES
+                )
+            )
+        };
+        section(
+            &mut text,
+            locale.pick("Emails sent", "E-mails enviados"),
+            self.sent.iter().map(plan_line),
+        );
+        section(
+            &mut text,
+            locale.pick("Waiting for your approval", "Aguardando sua aprovação"),
+            self.pending_approvals.iter().map(plan_line),
+        );
+        section(
+            &mut text,
+            locale.pick("Meetings", "Reuniões"),
+            self.meetings.iter().map(|slot| {
+                format!(
+                    "{}–{} {}",
+                    locale.format_time(slot.start.time()),
+                    locale.format_time(slot.end.time()),
+                    slot.title
+                )
+            }),
+        );
+        section(
+            &mut text,
+            locale.pick("Waiting for replies", "Aguardando respostas"),
+            self.awaiting_replies.iter().map(|awaiting| {
+                format!(
+                    "{}: \"{}\" ({} {})",
+                    awaiting.recipient,
+                    awaiting.subject,
+                    locale.pick("since", "desde"),
+                    locale.format_date(awaiting.sent_at.date())
+                )
+            }),
+        );
+        text
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn to_email(&self, to: EmailAddress) -> MimeMessage {
+        MimeMessage::new(vec![to], &self.subject(), &self.render_text())
+    }
+}
+
+fn section(text: &mut String, title: &str, lines: impl Iterator<Item = String>) {
+    let lines: Vec<String> = lines.collect();
+    if lines.is_empty() {
+        return;
+    }
+    let _ = writeln!(text, "{} ({}):", title, lines.len());
+    for line in lines {
+        let _ = writeln!(text, "- {}", line);
+    }
+    text.push('\n');
+}
+
+impl AgentResult for Digest {}
@@ -0,0 +1,136 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::agent::digest::DigestFormat;
+use crate::config::DigestConfig;
+use crate::i18n::Locale;
+
+/// When and how one user gets their digest
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestDelivery {
+    pub send_hour: u32,
+    pub locale: Locale,
+    pub format: DigestFormat,
+    /// Where the email goes; the caller's default address if unset
+    pub email: Option<String>,
+}
+
+impl DigestDelivery {
+    fn send_time(&self) -> NaiveTime {
+        NaiveTime::from_hms_opt(self.send_hour.min(23), 0, 0).unwrap_or(NaiveTime::MIN)
+    }
+
+    /// First delivery time after `now`, today's if it has not passed
+    pub fn next_delivery(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let today = now.date().and_time(self.send_time());
+        if today > now {
+            today
+        } else {
+            today + Duration::days(1)
+        }
+    }
+
+    /// True once today's send time has passed and no digest went out today
+    pub fn is_due(&self, last_sent: Option<NaiveDate>, now: NaiveDateTime) -> bool {
+        now.time() >= self.send_time() && last_sent.is_none_or(|date| date < now.date())
+    }
+}
+
+/// Per-user digest settings from `[digest]`, falling back to the
+/// section's defaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestSchedule {
+    config: DigestConfig,
+    default_locale: Locale,
+}
+
+impl DigestSchedule {
+    pub fn new(config: DigestConfig, default_locale: Locale) -> Self {
+        Self {
+            config,
+            default_locale,
+        }
+    }
+
+    /// Delivery settings for `user`; `None` when they get no digest
+    pub fn delivery(&self, user: &str) -> Option<DigestDelivery> {
+        let preferences = self.config.users.get(user);
+        let enabled = preferences
+            .and_then(|p| p.enabled)
+            .unwrap_or(self.config.enabled);
+        if !enabled {
+            return None;
+        }
+        Some(DigestDelivery {
+            send_hour: preferences
+                .and_then(|p| p.send_hour)
+                .unwrap_or(self.config.send_hour),
+            locale: preferences
+                .and_then(|p| p.locale)
+                .unwrap_or(self.default_locale),
+            format: preferences
+                .and_then(|p| p.format)
+                .unwrap_or(self.config.format),
+            email: preferences.and_then(|p| p.email.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DigestUserConfig;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn schedule() -> DigestSchedule {
+        let mut config = DigestConfig {
+            enabled: true,
+            ..DigestConfig::default()
+        };
+        config.users.insert(
+            "ana".to_string(),
+            DigestUserConfig {
+                send_hour: Some(8),
+                locale: Some(Locale::PtBr),
+                format: Some(DigestFormat::Json),
+                ..DigestUserConfig::default()
+            },
+        );
+        config.users.insert(
+            "bob".to_string(),
+            DigestUserConfig {
+                enabled: Some(false),
+                ..DigestUserConfig::default()
+            },
+        );
+        DigestSchedule::new(config, Locale::En)
+    }
+
+    #[test]
+    fn test_per_user_settings() {
+        let schedule = schedule();
+
+        let ana = schedule.delivery("ana").unwrap();
+        assert_eq!(ana.send_hour, 8);
+        assert_eq!(ana.locale, Locale::PtBr);
+        assert_eq!(ana.format, DigestFormat::Json);
+        assert_eq!(schedule.delivery("carol").unwrap().send_hour, 7);
+        assert_eq!(schedule.delivery("bob"), None);
+    }
+
+    #[test]
+    fn test_delivered_once_each_morning() {
+        let delivery = schedule().delivery("carol").unwrap();
+
+        assert!(!delivery.is_due(None, at(14, 6)));
+        assert!(delivery.is_due(Some(at(13, 7).date()), at(14, 7)));
+        assert!(!delivery.is_due(Some(at(14, 7).date()), at(14, 9)));
+        assert_eq!(delivery.next_delivery(at(14, 9)), at(15, 7));
+        assert_eq!(delivery.next_delivery(at(14, 6)), at(14, 7));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod digest_agent;
+pub mod digest_report;
+pub mod digest_schedule;
+
+pub use digest_agent::{DigestAgent, DigestParam};
+pub use digest_report::{Digest, DigestFormat};
+pub use digest_schedule::{DigestDelivery, DigestSchedule};
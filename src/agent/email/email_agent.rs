@@ -1,5 +1,7 @@
-use crate::agent::{agent::AgentParam, email::EmailResult, Agent, AgentError};
+use crate::agent::{Agent, AgentError, agent::AgentParam, email::EmailResult};
+use crate::i18n::text::normalize;
 
+#[derive(Default)]
 pub struct EmailAgent {}
 
 impl EmailAgent {
@@ -9,34 +11,33 @@ impl EmailAgent {
 }
 
 pub struct EmailParam {
-    input: String
+    input: String,
 }
 
 impl EmailParam {
-    pub fn new(input: &String) -> Self {
-        Self { 
-            input: input.clone()
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: normalize(input),
         }
     }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
 }
 
-impl AgentParam for  EmailParam {}
+impl AgentParam for EmailParam {}
 
 impl Agent<EmailParam, EmailResult> for EmailAgent {
-    fn process(
-        &self,
-        _input: EmailParam,
-    ) -> impl std::future::Future<Output = Result<EmailResult, AgentError>> + Send {
-        async move {
-            // TODO: Implement email sending logic
-            // - Parse input parameters (recipient, message)
-            // - Validate email address
-            // - Send email using email_sender
-            // - Return sending confirmation
-
-            Err(AgentError::ProcessingError(
-                "Email sending not implemented yet".to_string(),
-            ))
-        }
+    async fn process(&self, _input: EmailParam) -> Result<EmailResult, AgentError> {
+        // TODO: Implement email sending logic
+        // - Parse input parameters (recipient, message)
+        // - Validate email address
+        // - Send email using email_sender
+        // - Return sending confirmation
+
+        Err(AgentError::ProcessingError(
+            "Email sending not implemented yet".to_string(),
+        ))
     }
 }
@@ -0,0 +1,42 @@
+use crate::agent::AgentError;
+use crate::infra::ollama::OllamaClient;
+
+/// Anything that turns text into a vector whose direction captures its
+/// meaning
+pub trait EmbeddingModel {
+    fn embed(
+        &self,
+        text: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<f32>, AgentError>> + Send;
+}
+
+impl EmbeddingModel for OllamaClient {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, AgentError> {
+        self.send_embedding(text).await
+    }
+}
+
+/// Cosine of the angle between two embeddings; 0 when either is empty or
+/// their sizes differ
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+}
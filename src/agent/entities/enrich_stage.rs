@@ -0,0 +1,129 @@
+use crate::agent::{
+    Agent, AgentError, ClassificationResult,
+    classifier::IntentDetails,
+    entities::{Entities, EntityExtractorAgent, EntityExtractorParam, EntityKind},
+    pipeline::{PipelineContext, Stage, StageFuture},
+};
+
+/// Stage that extracts entities from the message and fills in meeting
+/// fields the classifier left empty. The entities are also stored in the
+/// `PipelineContext`, so later stages can pick up e.g. files to attach.
+pub struct EnrichWithEntities {
+    agent: EntityExtractorAgent,
+}
+
+pub fn enrich_with_entities() -> EnrichWithEntities {
+    EnrichWithEntities {
+        agent: EntityExtractorAgent::new(),
+    }
+}
+
+impl EnrichWithEntities {
+    pub fn with_agent(mut self, agent: EntityExtractorAgent) -> Self {
+        self.agent = agent;
+        self
+    }
+
+    async fn enrich_result(
+        &self,
+        result: ClassificationResult,
+        context: &mut PipelineContext,
+    ) -> Result<ClassificationResult, AgentError> {
+        let Some(message) = result.params.message() else {
+            return Ok(result);
+        };
+        let entities = self
+            .agent
+            .process(EntityExtractorParam::new(message.to_string()))
+            .await?;
+        let result = enrich(result, &entities);
+        context.insert(entities);
+        Ok(result)
+    }
+}
+
+/// Fills empty meeting attendees, start and location from `entities`;
+/// fields the classifier already set are kept
+pub fn enrich(mut result: ClassificationResult, entities: &Entities) -> ClassificationResult {
+    if let Some(IntentDetails::ScheduleMeeting(meeting)) = &mut result.details {
+        if meeting.attendees.is_empty() {
+            meeting.attendees = entities
+                .of_kind(EntityKind::Person)
+                .map(str::to_string)
+                .collect();
+        }
+        if meeting.start.trim().is_empty()
+            && let Some(date) = entities.first(EntityKind::Date)
+        {
+            meeting.start = date.to_string();
+        }
+        if meeting.location.trim().is_empty()
+            && let Some(place) = entities.first(EntityKind::Place)
+        {
+            meeting.location = place.to_string();
+        }
+    }
+    result
+}
+
+impl Stage<ClassificationResult, ClassificationResult> for EnrichWithEntities {
+    fn name(&self) -> &str {
+        "extract_entities"
+    }
+
+    fn run<'a>(
+        &'a self,
+        input: ClassificationResult,
+        context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ClassificationResult> {
+        Box::pin(self.enrich_result(input, context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Intent;
+    use crate::agent::classifier::{MeetingDetails, Params};
+    use crate::agent::entities::Entity;
+
+    #[test]
+    fn test_fills_empty_meeting_fields() {
+        let result = ClassificationResult::new(Intent::ScheduleMeeting, Params::new(None, None))
+            .with_details(IntentDetails::ScheduleMeeting(MeetingDetails {
+                attendees: Vec::new(),
+                title: "Budget".to_string(),
+                start: "Friday 10am".to_string(),
+                duration_minutes: None,
+                location: String::new(),
+            }));
+        let entities = Entities::new(vec![
+            Entity::new(EntityKind::Person, "Eva"),
+            Entity::new(EntityKind::Date, "Thursday"),
+            Entity::new(EntityKind::Place, "room 4B"),
+        ]);
+
+        let Some(IntentDetails::ScheduleMeeting(meeting)) = enrich(result, &entities).details
+        else {
+            panic!("expected meeting details");
+        };
+
+        assert_eq!(meeting.attendees, vec!["Eva".to_string()]);
+        assert_eq!(meeting.start, "Friday 10am");
+        assert_eq!(meeting.location, "room 4B");
+    }
+
+    #[tokio::test]
+    async fn test_results_without_message_pass_through() {
+        let mut context = PipelineContext::new();
+        let result = ClassificationResult::new(Intent::NoAction, Params::new(None, None));
+
+        let result = enrich_with_entities()
+            .run(result, &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(result.intent, Intent::NoAction);
+        assert!(context.get::<Entities>().is_none());
+    }
+}
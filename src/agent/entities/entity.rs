@@ -0,0 +1,111 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::agent::AgentResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Person,
+    /// A date or time, relative ("next Friday") or absolute
+    Date,
+    Place,
+    /// A file the text refers to, e.g. "the Q3 report.pdf"
+    Attachment,
+    Organization,
+}
+
+impl EntityKind {
+    pub const ALL: [EntityKind; 5] = [
+        EntityKind::Person,
+        EntityKind::Date,
+        EntityKind::Place,
+        EntityKind::Attachment,
+        EntityKind::Organization,
+    ];
+}
+
+impl fmt::Display for EntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EntityKind::Person => "person",
+            EntityKind::Date => "date",
+            EntityKind::Place => "place",
+            EntityKind::Attachment => "attachment",
+            EntityKind::Organization => "organization",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A mention found in the text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Entity {
+    pub kind: EntityKind,
+    /// The mention as written
+    pub text: String,
+}
+
+impl Entity {
+    pub fn new(kind: EntityKind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Entities {
+    #[serde(default)]
+    pub entities: Vec<Entity>,
+}
+
+impl Entities {
+    pub fn new(entities: Vec<Entity>) -> Self {
+        Self { entities }
+    }
+
+    /// Mentions of `kind`, in order of appearance
+    pub fn of_kind(&self, kind: EntityKind) -> impl Iterator<Item = &str> {
+        self.entities
+            .iter()
+            .filter(move |entity| entity.kind == kind)
+            .map(|entity| entity.text.as_str())
+    }
+
+    pub fn first(&self, kind: EntityKind) -> Option<&str> {
+        self.of_kind(kind).next()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+impl AgentResult for Entities {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_of_kind() {
+        let entities: Entities = serde_json::from_str(
+            r#"{"entities": [
+                {"kind": "person", "text": "Eva"},
+                {"kind": "attachment", "text": "budget.xlsx"},
+                {"kind": "person", "text": "Tiago"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            entities.of_kind(EntityKind::Person).collect::<Vec<_>>(),
+            vec!["Eva", "Tiago"]
+        );
+        assert_eq!(entities.first(EntityKind::Attachment), Some("budget.xlsx"));
+        assert_eq!(entities.first(EntityKind::Place), None);
+    }
+}
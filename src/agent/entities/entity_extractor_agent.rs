@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel,
+        agent::AgentParam,
+        chat_model::{model_or, ollama_for},
+        entities::{Entities, EntityKind},
+    },
+    config::Config,
+    infra::ollama::{OutputFormat, parse_json_content},
+};
+
+/// Finds people, dates, places, organizations and mentioned files in text
+pub struct EntityExtractorAgent {
+    model: Option<String>,
+    output_format: OutputFormat,
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
+
+impl Default for EntityExtractorAgent {
+    fn default() -> Self {
+        Self {
+            model: None,
+            output_format: Config::get()
+                .structured_output
+                .format_for("entity_extractor"),
+            chat_model: None,
+        }
+    }
+}
+
+impl EntityExtractorAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_model(model: &str) -> Self {
+        Self {
+            model: Some(model.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Sends requests to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
+    }
+
+    /// How strictly the model's JSON answer is constrained
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+pub struct EntityExtractorParam {
+    text: String,
+    kinds: Vec<EntityKind>,
+}
+
+impl EntityExtractorParam {
+    /// Looks for every kind of entity
+    pub fn new(text: String) -> Self {
+        Self {
+            text,
+            kinds: EntityKind::ALL.to_vec(),
+        }
+    }
+
+    /// Restricts the search to `kinds`
+    pub fn with_kinds(mut self, kinds: &[EntityKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+}
+
+impl AgentParam for EntityExtractorParam {}
+
+impl Agent<EntityExtractorParam, Entities> for EntityExtractorAgent {
+    async fn process(&self, input: EntityExtractorParam) -> Result<Entities, AgentError> {
+        if input.text.trim().is_empty() || input.kinds.is_empty() {
+            return Ok(Entities::default());
+        }
+
+        let client = model_or(&self.chat_model, || ollama_for(self.model.as_deref()));
+        let answer = client
+            .ask_constrained(
+                &build_prompt(&input),
+                &self.output_format.constraint_for::<Entities>(),
+            )
+            .await?;
+
+        let mut entities = parse_json_content::<Entities>(&answer)?;
+        // Models sometimes volunteer kinds that were not asked for
+        entities
+            .entities
+            .retain(|entity| input.kinds.contains(&entity.kind) && !entity.text.trim().is_empty());
+        Ok(entities)
+    }
+}
+
+fn build_prompt(input: &EntityExtractorParam) -> String {
+    let kinds: Vec<String> = input.kinds.iter().map(|kind| kind.to_string()).collect();
+    format!(
+        "{} Kinds: {}.{}{}{}",
+        INSTRUCTION,
+        kinds.join(", "),
+        OUTPUT_FORMAT,
+        TEXT_LABEL,
+        input.text
+    )
+}
+
+const INSTRUCTION: &str = "List the entities mentioned in the following text, copying each mention exactly as written and in order of appearance. An attachment is a file the text refers to.";
+const OUTPUT_FORMAT: &str =
+    " Answer only with JSON: {\"entities\": [{\"kind\": \"person\", \"text\": \"Eva\"}]}";
+const TEXT_LABEL: &str = "\nText:\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_lists_requested_kinds() {
+        let prompt = build_prompt(
+            &EntityExtractorParam::new("Send Eva the deck".to_string())
+                .with_kinds(&[EntityKind::Person, EntityKind::Attachment]),
+        );
+
+        assert!(prompt.contains("Kinds: person, attachment."));
+        assert!(prompt.ends_with("Text:\nSend Eva the deck"));
+    }
+
+    #[tokio::test]
+    async fn test_empty_text_skips_the_model() {
+        let entities = EntityExtractorAgent::new()
+            .process(EntityExtractorParam::new(String::new()))
+            .await
+            .unwrap();
+
+        assert!(entities.is_empty());
+    }
+}
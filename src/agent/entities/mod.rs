@@ -0,0 +1,7 @@
+pub mod enrich_stage;
+pub mod entity;
+pub mod entity_extractor_agent;
+
+pub use enrich_stage::{EnrichWithEntities, enrich_with_entities};
+pub use entity::{Entities, Entity, EntityKind};
+pub use entity_extractor_agent::{EntityExtractorAgent, EntityExtractorParam};
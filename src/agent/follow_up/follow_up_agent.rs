@@ -0,0 +1,183 @@
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel,
+        agent::AgentParam,
+        chat_model::{model_or, ollama_for},
+        follow_up::{AwaitingReply, FollowUpDraft},
+    },
+    i18n::{Locale, Tone},
+    infra::ollama::parse_json_content,
+};
+
+/// Writes a short, polite follow-up for a question nobody answered
+#[derive(Default)]
+pub struct FollowUpAgent {
+    model: Option<String>,
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
+
+impl FollowUpAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_model(model: &str) -> Self {
+        Self {
+            model: Some(model.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Sends requests to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
+    }
+}
+
+pub struct FollowUpParam {
+    awaiting: AwaitingReply,
+    locale: Locale,
+    tone: Tone,
+    relationship: Option<String>,
+}
+
+impl FollowUpParam {
+    pub fn new(awaiting: AwaitingReply, locale: Locale) -> Self {
+        Self {
+            awaiting,
+            locale,
+            tone: Tone::default(),
+            relationship: None,
+        }
+    }
+
+    /// Earlier interactions with the recipient, from
+    /// `RelationshipHistory::summary`, so the draft reads naturally
+    pub fn with_relationship(mut self, summary: Option<String>) -> Self {
+        self.relationship = summary;
+        self
+    }
+
+    pub fn with_tone(mut self, tone: Tone) -> Self {
+        self.tone = tone;
+        self
+    }
+}
+
+impl AgentParam for FollowUpParam {}
+
+#[derive(Debug, Deserialize)]
+struct ModelDraft {
+    body: String,
+}
+
+impl Agent<FollowUpParam, FollowUpDraft> for FollowUpAgent {
+    async fn process(&self, input: FollowUpParam) -> Result<FollowUpDraft, AgentError> {
+        let client = model_or(&self.chat_model, || ollama_for(self.model.as_deref()));
+        let answer = client.ask(&build_prompt(&input)).await?;
+        let draft = parse_json_content::<ModelDraft>(&answer)?;
+
+        Ok(FollowUpDraft {
+            recipient: input.awaiting.recipient.clone(),
+            subject: reply_subject(&input.awaiting.subject),
+            body: draft.body,
+        })
+    }
+}
+
+fn reply_subject(subject: &str) -> String {
+    if subject.trim_start().to_lowercase().starts_with("re:") {
+        subject.to_string()
+    } else {
+        format!("Re: {}", subject)
+    }
+}
+
+fn build_prompt(input: &FollowUpParam) -> String {
+    let nudge = match input.awaiting.follow_ups {
+        0 => "This is the first follow-up.",
+        _ => "Earlier follow-ups were not answered either; stay polite and keep it shorter.",
+    };
+    let relationship = input
+        .relationship
+        .as_deref()
+        .map(|summary| format!("\n{}", summary))
+        .unwrap_or_default();
+    format!(
+        "{} Write in {}. {} {}{}{}\nSubject: {}\nOriginal email:\n{}",
+        INSTRUCTION,
+        input.locale.language_name(),
+        input.tone.guidance(input.locale),
+        nudge,
+        OUTPUT_FORMAT,
+        relationship,
+        input.awaiting.subject,
+        input.awaiting.question
+    )
+}
+
+const INSTRUCTION: &str = "The user sent the email below and got no reply. Write a brief, polite follow-up that reminds the recipient of the question without repeating the whole email and without sounding impatient.";
+const OUTPUT_FORMAT: &str = " Answer only with JSON: {\"body\": \"the follow-up email\"}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn awaiting(follow_ups: u32) -> AwaitingReply {
+        let sent_at = NaiveDate::from_ymd_opt(2025, 5, 12)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        AwaitingReply {
+            id: 1,
+            recipient: "eva@company.com".to_string(),
+            subject: "Budget".to_string(),
+            question: "Can you send the numbers?".to_string(),
+            sent_at,
+            follow_ups,
+            last_sent_at: sent_at,
+        }
+    }
+
+    #[test]
+    fn test_prompt_quotes_the_question() {
+        let prompt = build_prompt(&FollowUpParam::new(awaiting(0), Locale::PtBr));
+
+        assert!(prompt.starts_with(INSTRUCTION));
+        assert!(prompt.contains("Write in Brazilian Portuguese."));
+        assert!(prompt.contains("first follow-up"));
+        assert!(prompt.ends_with("Original email:\nCan you send the numbers?"));
+    }
+
+    #[test]
+    fn test_later_follow_ups_are_shorter() {
+        let prompt = build_prompt(&FollowUpParam::new(awaiting(1), Locale::En));
+
+        assert!(prompt.contains("keep it shorter"));
+    }
+
+    #[test]
+    fn test_relationship_summary_precedes_the_email() {
+        let summary =
+            "History with eva@company.com: no earlier emails; usually addressed informally.";
+        let prompt = build_prompt(
+            &FollowUpParam::new(awaiting(0), Locale::En)
+                .with_relationship(Some(summary.to_string())),
+        );
+
+        assert!(prompt.contains(&format!("{}\nSubject: Budget", summary)));
+    }
+
+    #[test]
+    fn test_reply_subject() {
+        assert_eq!(reply_subject("Budget"), "Re: Budget");
+        assert_eq!(reply_subject("RE: Budget"), "RE: Budget");
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentResult;
+
+/// A polite nudge for an unanswered email
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FollowUpDraft {
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl AgentResult for FollowUpDraft {}
@@ -0,0 +1,245 @@
+use chrono::{Duration, NaiveDateTime};
+use serde::Serialize;
+use std::sync::Mutex;
+
+use crate::config::FollowUpConfig;
+use crate::i18n::Locale;
+
+/// A sent question still waiting for an answer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AwaitingReply {
+    pub id: u64,
+    pub recipient: String,
+    pub subject: String,
+    /// Body of the email that asked the question
+    pub question: String,
+    pub sent_at: NaiveDateTime,
+    /// Follow-ups sent so far
+    pub follow_ups: u32,
+    /// When the last email (question or follow-up) went out
+    pub last_sent_at: NaiveDateTime,
+}
+
+impl AwaitingReply {
+    /// Note for the user when no draft is wanted
+    pub fn reminder(&self, locale: Locale, now: NaiveDateTime) -> String {
+        let days = (now - self.sent_at).num_days().max(1);
+        match locale {
+            Locale::En => format!(
+                "No reply from {} to \"{}\" for {} day(s).",
+                self.recipient, self.subject, days
+            ),
+            Locale::PtBr => format!(
+                "Sem resposta de {} para \"{}\" há {} dia(s).",
+                self.recipient, self.subject, days
+            ),
+        }
+    }
+
+    fn is_answered_by(&self, message: &InboxMessage) -> bool {
+        let from_recipient = message
+            .from
+            .trim()
+            .eq_ignore_ascii_case(self.recipient.trim());
+        let subject = thread_subject(&self.subject);
+        from_recipient && (subject.is_empty() || thread_subject(&message.subject) == subject)
+    }
+}
+
+/// An email found in the inbox
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboxMessage {
+    pub from: String,
+    pub subject: String,
+}
+
+impl InboxMessage {
+    pub fn new(from: &str, subject: &str) -> Self {
+        Self {
+            from: from.to_string(),
+            subject: subject.to_string(),
+        }
+    }
+}
+
+/// Subject without reply and forward prefixes, lowercased
+fn thread_subject(subject: &str) -> String {
+    const PREFIXES: &[&str] = &["re:", "res:", "fw:", "fwd:", "enc:"];
+    let mut rest = subject.trim();
+    while let Some(prefix) = PREFIXES.iter().find(|prefix| {
+        rest.get(..prefix.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+    }) {
+        rest = rest[prefix.len()..].trim_start();
+    }
+    rest.to_lowercase()
+}
+
+/// Remembers questions the user sent and tells when a follow-up is due
+#[derive(Debug)]
+pub struct FollowUpTracker {
+    wait: Duration,
+    max_follow_ups: u32,
+    waiting: Mutex<Vec<AwaitingReply>>,
+}
+
+impl FollowUpTracker {
+    pub fn new(wait: Duration, max_follow_ups: u32) -> Self {
+        Self {
+            wait,
+            max_follow_ups,
+            waiting: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn from_config(config: &FollowUpConfig) -> Self {
+        Self::new(
+            Duration::hours(i64::from(config.wait_hours)),
+            config.max_follow_ups,
+        )
+    }
+
+    /// Starts waiting for a reply to a sent question; returns its id
+    pub fn record_sent(
+        &self,
+        recipient: &str,
+        subject: &str,
+        question: &str,
+        sent_at: NaiveDateTime,
+    ) -> u64 {
+        let mut waiting = self.waiting.lock().unwrap();
+        let id = waiting.iter().map(|w| w.id).max().unwrap_or(0) + 1;
+        waiting.push(AwaitingReply {
+            id,
+            recipient: recipient.to_string(),
+            subject: subject.to_string(),
+            question: question.to_string(),
+            sent_at,
+            follow_ups: 0,
+            last_sent_at: sent_at,
+        });
+        id
+    }
+
+    /// Stops waiting for every question `inbox` answers; returns them
+    pub fn watch(&self, inbox: &[InboxMessage]) -> Vec<AwaitingReply> {
+        let mut waiting = self.waiting.lock().unwrap();
+        let (answered, still_waiting): (Vec<_>, Vec<_>) = waiting
+            .drain(..)
+            .partition(|w| inbox.iter().any(|message| w.is_answered_by(message)));
+        *waiting = still_waiting;
+        answered
+    }
+
+    /// Questions unanswered for the wait period since the last email,
+    /// that have not used up their follow-ups
+    pub fn due(&self, now: NaiveDateTime) -> Vec<AwaitingReply> {
+        self.waiting
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|w| w.follow_ups < self.max_follow_ups && now - w.last_sent_at >= self.wait)
+            .cloned()
+            .collect()
+    }
+
+    /// Records that a follow-up for `id` went out, restarting the wait
+    pub fn mark_followed_up(&self, id: u64, now: NaiveDateTime) -> Option<AwaitingReply> {
+        let mut waiting = self.waiting.lock().unwrap();
+        let entry = waiting.iter_mut().find(|w| w.id == id)?;
+        entry.follow_ups += 1;
+        entry.last_sent_at = now;
+        Some(entry.clone())
+    }
+
+    /// Stops waiting without a reply, e.g. when the user gives up
+    pub fn cancel(&self, id: u64) -> Option<AwaitingReply> {
+        let mut waiting = self.waiting.lock().unwrap();
+        let index = waiting.iter().position(|w| w.id == id)?;
+        Some(waiting.remove(index))
+    }
+
+    pub fn waiting(&self) -> Vec<AwaitingReply> {
+        self.waiting.lock().unwrap().clone()
+    }
+}
+
+impl Default for FollowUpTracker {
+    fn default() -> Self {
+        Self::from_config(&FollowUpConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn day(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn tracker() -> FollowUpTracker {
+        let tracker = FollowUpTracker::new(Duration::hours(48), 2);
+        tracker.record_sent(
+            "eva@company.com",
+            "Budget",
+            "Can you send the numbers?",
+            day(12, 9),
+        );
+        tracker
+    }
+
+    #[test]
+    fn test_due_after_wait_period() {
+        let tracker = tracker();
+
+        assert!(tracker.due(day(13, 9)).is_empty());
+        let due = tracker.due(day(14, 9));
+        assert_eq!(due.len(), 1);
+        assert_eq!(
+            due[0].reminder(Locale::En, day(14, 9)),
+            "No reply from eva@company.com to \"Budget\" for 2 day(s)."
+        );
+    }
+
+    #[test]
+    fn test_reply_stops_waiting() {
+        let tracker = tracker();
+
+        let answered = tracker.watch(&[
+            InboxMessage::new("tiger@company.com", "Re: Budget"),
+            InboxMessage::new("Eva@Company.com", "RES: Re: budget"),
+        ]);
+
+        assert_eq!(answered.len(), 1);
+        assert!(tracker.waiting().is_empty());
+    }
+
+    #[test]
+    fn test_other_threads_do_not_count_as_reply() {
+        let tracker = tracker();
+
+        assert!(
+            tracker
+                .watch(&[InboxMessage::new("eva@company.com", "Lunch?")])
+                .is_empty()
+        );
+        assert_eq!(tracker.waiting().len(), 1);
+    }
+
+    #[test]
+    fn test_follow_ups_restart_wait_and_run_out() {
+        let tracker = tracker();
+
+        tracker.mark_followed_up(1, day(14, 9));
+        assert!(tracker.due(day(15, 9)).is_empty());
+        assert_eq!(tracker.due(day(16, 9)).len(), 1);
+
+        tracker.mark_followed_up(1, day(16, 9));
+        assert!(tracker.due(day(30, 9)).is_empty());
+    }
+}
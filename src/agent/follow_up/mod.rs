@@ -0,0 +1,7 @@
+pub mod follow_up_agent;
+pub mod follow_up_draft;
+pub mod follow_up_tracker;
+
+pub use follow_up_agent::{FollowUpAgent, FollowUpParam};
+pub use follow_up_draft::FollowUpDraft;
+pub use follow_up_tracker::{AwaitingReply, FollowUpTracker, InboxMessage};
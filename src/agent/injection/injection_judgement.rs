@@ -0,0 +1,33 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentResult;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct InjectionJudgement {
+    pub injection: bool,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl InjectionJudgement {
+    pub fn new(injection: bool, reason: String) -> Self {
+        Self { injection, reason }
+    }
+}
+
+impl AgentResult for InjectionJudgement {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialization_without_reason() {
+        let judgement: InjectionJudgement =
+            serde_json::from_str(r#"{"injection": false}"#).unwrap();
+
+        assert!(!judgement.injection);
+        assert_eq!(judgement.reason, "");
+    }
+}
@@ -0,0 +1,5 @@
+pub mod injection_judge_agent;
+pub mod injection_judgement;
+
+pub use injection_judge_agent::{InjectionJudgeAgent, InjectionJudgeParam};
+pub use injection_judgement::InjectionJudgement;
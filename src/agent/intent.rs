@@ -1,19 +1,26 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, JsonSchema,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum Intent {
     SendEmail,
     ScheduleMeeting,
+    // Answered from the quick reply library instead of the model
+    QuickReply,
     NoAction,
 }
 
 impl Intent {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &str) -> Self {
         match input.trim().to_lowercase().as_str() {
             SEND_EMAIL => Intent::SendEmail,
             SCHEDULE_MEETING => Intent::ScheduleMeeting,
+            QUICK_REPLY => Intent::QuickReply,
             _ => Intent::NoAction,
         }
     }
@@ -22,6 +29,7 @@ impl Intent {
         match self {
             Self::SendEmail => SEND_EMAIL,
             Self::ScheduleMeeting => SCHEDULE_MEETING,
+            Self::QuickReply => QUICK_REPLY,
             Self::NoAction => NO_ACTION,
         }
     }
@@ -32,6 +40,7 @@ impl fmt::Display for Intent {
         match self {
             Intent::SendEmail => write!(f, "{}", SEND_EMAIL),
             Intent::ScheduleMeeting => write!(f, "{}", SCHEDULE_MEETING),
+            Intent::QuickReply => write!(f, "{}", QUICK_REPLY),
             Intent::NoAction => write!(f, "{}", NO_ACTION),
         }
     }
@@ -39,4 +48,5 @@ impl fmt::Display for Intent {
 
 const SEND_EMAIL: &str = "send_email";
 const SCHEDULE_MEETING: &str = "schedule_meeting";
+const QUICK_REPLY: &str = "quick_reply";
 const NO_ACTION: &str = "no_action";
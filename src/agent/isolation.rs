@@ -0,0 +1,145 @@
+use std::any::Any;
+use std::future::Future;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::agent::{Agent, AgentError, AgentResult, agent::AgentParam};
+
+/// Runs one stage, turning a panic inside it into
+/// `AgentError::StagePanicked` so only the current item fails
+pub async fn isolate<T, F>(stage: &str, stage_future: F) -> Result<T, AgentError>
+where
+    F: Future<Output = Result<T, AgentError>>,
+{
+    CatchUnwind {
+        inner: Box::pin(stage_future),
+    }
+    .await
+    .unwrap_or_else(|payload| {
+        Err(AgentError::StagePanicked {
+            stage: stage.to_string(),
+            message: panic_message(payload.as_ref()),
+        })
+    })
+}
+
+/// Wraps an agent so every `process` call is isolated
+pub struct Isolated<A> {
+    stage: String,
+    agent: A,
+}
+
+impl<A> Isolated<A> {
+    pub fn new(stage: &str, agent: A) -> Self {
+        Self {
+            stage: stage.to_string(),
+            agent,
+        }
+    }
+
+    pub fn inner(&self) -> &A {
+        &self.agent
+    }
+}
+
+impl<P, T, A> Agent<P, T> for Isolated<A>
+where
+    P: AgentParam + Send,
+    T: AgentResult + Send,
+    A: Agent<P, T> + Sync,
+{
+    async fn process(&self, input: P) -> Result<T, AgentError> {
+        isolate(&self.stage, self.agent.process(input)).await
+    }
+}
+
+struct CatchUnwind<F> {
+    inner: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = self.inner.as_mut();
+        match catch_unwind(AssertUnwindSafe(|| inner.poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Text(String);
+    impl AgentParam for Text {}
+
+    #[derive(Debug)]
+    struct Length(usize);
+    impl AgentResult for Length {}
+
+    struct FragileAgent;
+
+    impl Agent<Text, Length> for FragileAgent {
+        async fn process(&self, input: Text) -> Result<Length, AgentError> {
+            tokio::task::yield_now().await;
+            if input.0.is_empty() {
+                panic!("empty input reached the parser");
+            }
+            Ok(Length(input.0.len()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panic_becomes_stage_error() {
+        let agent = Isolated::new("parser", FragileAgent);
+
+        let result = agent.process(Text(String::new())).await;
+
+        match result {
+            Err(AgentError::StagePanicked { stage, message }) => {
+                assert_eq!(stage, "parser");
+                assert_eq!(message, "empty input reached the parser");
+            }
+            other => panic!("expected StagePanicked, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_other_items_keep_working() {
+        let agent = Isolated::new("parser", FragileAgent);
+
+        let _ = agent.process(Text(String::new())).await;
+        let result = agent.process(Text("hello".to_string())).await.unwrap();
+
+        assert_eq!(result.0, 5);
+    }
+
+    #[tokio::test]
+    async fn test_formatted_panic_message() {
+        let result: Result<(), AgentError> = isolate("stage", async {
+            let index = 3;
+            panic!("index {} out of range", index)
+        })
+        .await;
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Stage 'stage' panicked: index 3 out of range"
+        );
+    }
+}
@@ -1,12 +1,39 @@
+#[allow(clippy::module_inception)]
 pub mod agent;
 pub mod agent_result;
 pub mod assistant;
+pub mod chat_model;
 pub mod classifier;
+pub mod composer;
 pub mod contact;
+pub mod delegation;
+pub mod digest;
 pub mod email;
+pub mod embedding_model;
+pub mod entities;
+pub mod follow_up;
+pub mod injection;
 pub mod intent;
+pub mod isolation;
+pub mod pipeline;
+pub mod planner;
+pub mod quick_reply;
+pub mod sentiment;
+pub mod summarizer;
+pub mod tone;
+pub mod tools;
+pub mod toxicity;
+pub mod translator;
+pub mod verifier;
 
 pub use agent::{Agent, AgentError};
 pub use agent_result::AgentResult;
+pub use chat_model::ChatModel;
 pub use classifier::ClassificationResult;
+pub use composer::{ComposedEmail, ComposerParam, EmailComposerAgent, EmailDraft};
+pub use embedding_model::{EmbeddingModel, cosine_similarity};
+pub use entities::{Entities, EntityExtractorAgent, EntityExtractorParam};
 pub use intent::Intent;
+pub use isolation::{Isolated, isolate};
+pub use sentiment::{SentimentAgent, SentimentAssessment};
+pub use summarizer::{SummarizerAgent, SummarizerParam, Summary};
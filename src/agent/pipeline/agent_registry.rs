@@ -0,0 +1,335 @@
+use std::any::{TypeId, type_name};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::agent::{
+    Agent, AgentResult, ClassificationResult, Intent,
+    agent::AgentParam,
+    pipeline::{
+        BranchSpec, ErrorPolicy, Pipeline, PipelineSpec, PipelineSpecError, Routed, Stage,
+        StageSpec, agent,
+        pipeline::{Node, StageNode, TypedNode},
+        route_by_intent,
+    },
+};
+
+/// Branch stages, keyed by intent; `None` for a terminator
+type RouteParts = Vec<(Intent, String, Option<Vec<StageNode>>)>;
+type MakeRouter =
+    Arc<dyn Fn(RouteParts, Option<(String, Vec<StageNode>)>) -> Box<dyn Node> + Send + Sync>;
+
+#[derive(Clone, Copy)]
+struct TypeInfo {
+    id: TypeId,
+    name: &'static str,
+}
+
+impl TypeInfo {
+    fn of<T: 'static>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+}
+
+struct Entry {
+    input: TypeInfo,
+    output: TypeInfo,
+    make_node: Arc<dyn Fn() -> Box<dyn Node> + Send + Sync>,
+    /// Builds a router whose branches end with this stage
+    make_router: MakeRouter,
+    routed_output: TypeInfo,
+}
+
+/// Named stages that pipelines declared in configuration can reference.
+/// Stage types are checked when a pipeline is built, so a misconfigured
+/// flow fails at startup rather than mid-run.
+#[derive(Default)]
+pub struct AgentRegistry {
+    entries: HashMap<String, Entry>,
+}
+
+impl AgentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_stage<I, O, S>(&mut self, name: &str, stage: S)
+    where
+        I: Clone + Send + 'static,
+        O: Clone + Send + 'static,
+        S: Stage<I, O> + 'static,
+    {
+        let stage = Arc::new(stage);
+        let make_router: MakeRouter = Arc::new(|routes, fallback| {
+            let mut router = route_by_intent::<O>();
+            for (intent, name, nodes) in routes {
+                router = match nodes {
+                    Some(nodes) => router.on(intent, &name, Pipeline::from_nodes(nodes)),
+                    None => router.terminate(intent),
+                };
+            }
+            if let Some((name, nodes)) = fallback {
+                router = router.otherwise(&name, Pipeline::from_nodes(nodes));
+            }
+            TypedNode::<_, ClassificationResult, Routed<O>>::boxed(router)
+        });
+        self.entries.insert(
+            name.to_string(),
+            Entry {
+                input: TypeInfo::of::<I>(),
+                output: TypeInfo::of::<O>(),
+                make_node: Arc::new(move || TypedNode::<_, I, O>::boxed(stage.clone())),
+                make_router,
+                routed_output: TypeInfo::of::<Routed<O>>(),
+            },
+        );
+    }
+
+    /// Registers an agent as a stage named `name`
+    pub fn register_agent<P, T, A>(&mut self, name: &str, agent_impl: A)
+    where
+        P: AgentParam + Clone + Send + 'static,
+        T: AgentResult + Clone + Send + 'static,
+        A: Agent<P, T> + Send + Sync + 'static,
+    {
+        self.register_stage(name, agent(name, agent_impl));
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Instantiates `spec`, checking that it takes `I` and produces `O`
+    pub fn build<I, O>(&self, spec: &PipelineSpec) -> Result<Pipeline<I, O>, PipelineSpecError>
+    where
+        I: 'static,
+        O: 'static,
+    {
+        let pipeline_name = spec.name.as_deref().unwrap_or("pipeline");
+        let (nodes, output) = self.build_stages(&spec.stages, TypeInfo::of::<I>())?;
+        expect_type(pipeline_name, TypeInfo::of::<O>(), output)?;
+        Ok(Pipeline::from_nodes(nodes))
+    }
+
+    fn build_stages(
+        &self,
+        stages: &[StageSpec],
+        mut current: TypeInfo,
+    ) -> Result<(Vec<StageNode>, TypeInfo), PipelineSpecError> {
+        let mut nodes = Vec::new();
+        for spec in stages {
+            let (node, output) = match spec {
+                StageSpec::Stage { stage, retry } => {
+                    let entry = self.entry(stage)?;
+                    expect_type(stage, entry.input, current)?;
+                    let policy = match retry {
+                        Some(attempts) => ErrorPolicy::Retry {
+                            attempts: *attempts,
+                        },
+                        None => ErrorPolicy::Fail,
+                    };
+                    (StageNode::new((entry.make_node)(), policy), entry.output)
+                }
+                StageSpec::Route { route_by_intent } => {
+                    expect_type(
+                        "route_by_intent",
+                        TypeInfo::of::<ClassificationResult>(),
+                        current,
+                    )?;
+                    self.build_router(route_by_intent)?
+                }
+            };
+            nodes.push(node);
+            current = output;
+        }
+        Ok((nodes, current))
+    }
+
+    fn build_router(
+        &self,
+        branches: &[BranchSpec],
+    ) -> Result<(StageNode, TypeInfo), PipelineSpecError> {
+        let mut routes = Vec::new();
+        let mut fallback = None;
+        // Every branch must end with the same type; the last stage of the
+        // first one decides which router to build
+        let mut last: Option<&Entry> = None;
+
+        for branch in branches {
+            let name = branch.name();
+            if branch.terminate {
+                let intent = branch.intent.clone().ok_or_else(|| {
+                    PipelineSpecError::InvalidRoute(
+                        "only intent branches can terminate".to_string(),
+                    )
+                })?;
+                routes.push((intent, name, None));
+                continue;
+            }
+            let Some(StageSpec::Stage { stage, .. }) = branch.stages.last() else {
+                return Err(PipelineSpecError::InvalidRoute(format!(
+                    "branch '{}' must end with a stage or terminate",
+                    name
+                )));
+            };
+            let (nodes, output) =
+                self.build_stages(&branch.stages, TypeInfo::of::<ClassificationResult>())?;
+            let entry = self.entry(stage)?;
+            match last {
+                Some(first) => expect_type(&name, first.output, output)?,
+                None => last = Some(entry),
+            }
+            match &branch.intent {
+                Some(intent) => routes.push((intent.clone(), name, Some(nodes))),
+                None => fallback = Some((name, nodes)),
+            }
+        }
+
+        let entry = last.ok_or_else(|| {
+            PipelineSpecError::InvalidRoute("at least one branch needs stages".to_string())
+        })?;
+        let node = (entry.make_router)(routes, fallback);
+        Ok((StageNode::new(node, ErrorPolicy::Fail), entry.routed_output))
+    }
+
+    fn entry(&self, name: &str) -> Result<&Entry, PipelineSpecError> {
+        self.entries
+            .get(name)
+            .ok_or_else(|| PipelineSpecError::UnknownStage(name.to_string()))
+    }
+}
+
+fn expect_type(stage: &str, expected: TypeInfo, found: TypeInfo) -> Result<(), PipelineSpecError> {
+    if expected.id == found.id {
+        return Ok(());
+    }
+    Err(PipelineSpecError::TypeMismatch {
+        stage: stage.to_string(),
+        expected: expected.name.to_string(),
+        found: found.name.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::Params;
+    use crate::agent::pipeline::from_fn;
+
+    fn registry() -> AgentRegistry {
+        let mut registry = AgentRegistry::new();
+        registry.register_stage(
+            "classify",
+            from_fn("classify", |text: String, _: &mut _| {
+                let intent = if text.contains("meet") {
+                    Intent::ScheduleMeeting
+                } else if text.contains("email") {
+                    Intent::SendEmail
+                } else {
+                    Intent::NoAction
+                };
+                Ok(ClassificationResult::new(intent, Params::new(None, None)))
+            }),
+        );
+        registry.register_stage(
+            "compose",
+            from_fn("compose", |_: ClassificationResult, _: &mut _| {
+                Ok("draft".to_string())
+            }),
+        );
+        registry.register_stage(
+            "invite",
+            from_fn("invite", |_: ClassificationResult, _: &mut _| {
+                Ok("invite".to_string())
+            }),
+        );
+        registry.register_stage(
+            "count",
+            from_fn("count", |text: String, _: &mut _| Ok(text.len())),
+        );
+        registry
+    }
+
+    fn spec(toml: &str) -> PipelineSpec {
+        PipelineSpec::from_toml(toml).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_builds_routed_pipeline() {
+        let spec = spec(
+            r#"
+[[stages]]
+stage = "classify"
+retry = 2
+
+[[stages]]
+[[stages.route_by_intent]]
+intent = "send_email"
+stages = [{ stage = "compose" }]
+
+[[stages.route_by_intent]]
+intent = "no_action"
+terminate = true
+
+[[stages.route_by_intent]]
+branch = "calendar"
+stages = [{ stage = "invite" }]
+"#,
+        );
+
+        let pipeline: Pipeline<String, Routed<String>> = registry().build(&spec).unwrap();
+
+        let routed = pipeline.run("please email Eva".to_string()).await.unwrap();
+        assert_eq!(routed.output().map(String::as_str), Some("draft"));
+        let routed = pipeline.run("let's meet".to_string()).await.unwrap();
+        assert_eq!(routed.branch(), Some("calendar"));
+        assert!(
+            pipeline
+                .run("hello".to_string())
+                .await
+                .unwrap()
+                .is_terminated()
+        );
+        assert!(pipeline.to_mermaid().contains("retry x2"));
+    }
+
+    #[test]
+    fn test_unknown_stage() {
+        let result = registry().build::<String, String>(&spec("[[stages]]\nstage = \"send\""));
+
+        assert_eq!(
+            result.err(),
+            Some(PipelineSpecError::UnknownStage("send".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        let result = registry().build::<String, usize>(&spec(
+            "[[stages]]\nstage = \"classify\"\n[[stages]]\nstage = \"count\"",
+        ));
+
+        match result.err() {
+            Some(PipelineSpecError::TypeMismatch { stage, .. }) => assert_eq!(stage, "count"),
+            other => panic!("expected a type mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_wrong_pipeline_output() {
+        let result = registry().build::<String, String>(&spec("[[stages]]\nstage = \"count\""));
+
+        assert!(matches!(
+            result.err(),
+            Some(PipelineSpecError::TypeMismatch { .. })
+        ));
+    }
+}
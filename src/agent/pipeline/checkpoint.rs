@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::any::Any;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::agent::AgentError;
+
+/// Output of the last checkpointed stage of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub run_id: String,
+    pub stage: String,
+    pub stage_index: usize,
+    pub output: serde_json::Value,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Checkpoints saved as `<run_id>.json` in a directory; one per run,
+/// overwritten as the run progresses and removed once it completes
+pub struct CheckpointStore {
+    directory: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(directory: &Path) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+        }
+    }
+
+    pub fn save(&self, checkpoint: &Checkpoint) -> Result<(), AgentError> {
+        fs::create_dir_all(&self.directory).map_err(io_error)?;
+        let json = serde_json::to_string(checkpoint)
+            .map_err(|e| AgentError::ProcessingError(format!("Checkpoint not saved: {}", e)))?;
+        // Write then rename so a crash mid-write leaves the previous checkpoint
+        let path = self.path(&checkpoint.run_id);
+        let partial = path.with_extension("json.tmp");
+        fs::write(&partial, json).map_err(io_error)?;
+        fs::rename(&partial, &path).map_err(io_error)
+    }
+
+    pub fn load(&self, run_id: &str) -> Result<Option<Checkpoint>, AgentError> {
+        let path = self.path(run_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path).map_err(io_error)?;
+        serde_json::from_str(&content).map(Some).map_err(|e| {
+            AgentError::ParseError(format!("Checkpoint '{}' is corrupt: {}", run_id, e))
+        })
+    }
+
+    /// Ids of runs that stopped before completing
+    pub fn run_ids(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.directory) else {
+            return Vec::new();
+        };
+        let mut ids: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| path.file_stem()?.to_str().map(str::to_string))
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    pub fn remove(&self, run_id: &str) -> Result<(), AgentError> {
+        match fs::remove_file(self.path(run_id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(e)),
+            _ => Ok(()),
+        }
+    }
+
+    fn path(&self, run_id: &str) -> PathBuf {
+        let file_name: String = run_id
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.directory.join(format!("{}.json", file_name))
+    }
+}
+
+fn io_error(e: std::io::Error) -> AgentError {
+    AgentError::ProcessingError(format!("Checkpoint store: {}", e))
+}
+
+/// Type-erased (de)serializer for a checkpointed stage's output
+pub(crate) trait ErasedCodec: Send + Sync {
+    fn encode(&self, output: &(dyn Any + Send)) -> Result<serde_json::Value, AgentError>;
+
+    fn decode(&self, output: serde_json::Value) -> Result<Box<dyn Any + Send>, AgentError>;
+}
+
+pub(crate) struct TypedCodec<O>(pub(crate) PhantomData<fn(O) -> O>);
+
+impl<O> ErasedCodec for TypedCodec<O>
+where
+    O: Serialize + DeserializeOwned + Send + 'static,
+{
+    fn encode(&self, output: &(dyn Any + Send)) -> Result<serde_json::Value, AgentError> {
+        let output = output.downcast_ref::<O>().ok_or_else(|| {
+            AgentError::ProcessingError("Checkpoint received an unexpected output type".to_string())
+        })?;
+        serde_json::to_value(output)
+            .map_err(|e| AgentError::ProcessingError(format!("Checkpoint not saved: {}", e)))
+    }
+
+    fn decode(&self, output: serde_json::Value) -> Result<Box<dyn Any + Send>, AgentError> {
+        serde_json::from_value::<O>(output)
+            .map(|output| Box::new(output) as Box<dyn Any + Send>)
+            .map_err(|e| AgentError::ParseError(format!("Checkpoint not restored: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_remove() {
+        let directory = std::env::temp_dir().join(format!("checkpoints-{}", std::process::id()));
+        let store = CheckpointStore::new(&directory);
+        let checkpoint = Checkpoint {
+            run_id: "run-1".to_string(),
+            stage: "classify".to_string(),
+            stage_index: 0,
+            output: serde_json::json!({"intent": "send_email"}),
+            saved_at: Utc::now(),
+        };
+
+        store.save(&checkpoint).unwrap();
+
+        assert_eq!(store.load("run-1").unwrap(), Some(checkpoint));
+        assert_eq!(store.run_ids(), vec!["run-1".to_string()]);
+        store.remove("run-1").unwrap();
+        assert_eq!(store.load("run-1").unwrap(), None);
+        store.remove("run-1").unwrap();
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+use std::any::Any;
+use std::marker::PhantomData;
+
+use crate::agent::{
+    AgentError,
+    pipeline::{PipelineContext, StageFuture},
+};
+
+/// Undoes the side effect of a completed stage, given the stage's output
+/// (e.g. deletes the calendar event a stage created)
+pub trait Compensation<O>: Send + Sync {
+    fn compensate<'a>(&'a self, output: O, context: &'a mut PipelineContext)
+    -> StageFuture<'a, ()>;
+}
+
+pub struct FnCompensation<F> {
+    function: F,
+}
+
+/// Compensation from a synchronous closure
+pub fn compensate_fn<O, F>(function: F) -> FnCompensation<F>
+where
+    F: Fn(O, &mut PipelineContext) -> Result<(), AgentError> + Send + Sync,
+{
+    FnCompensation { function }
+}
+
+impl<O, F> Compensation<O> for FnCompensation<F>
+where
+    O: Send + 'static,
+    F: Fn(O, &mut PipelineContext) -> Result<(), AgentError> + Send + Sync,
+{
+    fn compensate<'a>(
+        &'a self,
+        output: O,
+        context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ()> {
+        Box::pin(async move { (self.function)(output, context) })
+    }
+}
+
+/// Type-erased compensation stored next to its stage
+pub(crate) trait ErasedCompensation: Send + Sync {
+    /// Copy of the stage output kept until the run succeeds
+    fn snapshot(&self, output: &(dyn Any + Send)) -> Option<Box<dyn Any + Send>>;
+
+    fn compensate<'a>(
+        &'a self,
+        output: Box<dyn Any + Send>,
+        context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ()>;
+}
+
+pub(crate) struct TypedCompensation<C, O> {
+    pub(crate) compensation: C,
+    pub(crate) _output: PhantomData<fn(O)>,
+}
+
+impl<C, O> ErasedCompensation for TypedCompensation<C, O>
+where
+    C: Compensation<O>,
+    O: Clone + Send + 'static,
+{
+    fn snapshot(&self, output: &(dyn Any + Send)) -> Option<Box<dyn Any + Send>> {
+        output
+            .downcast_ref::<O>()
+            .map(|output| Box::new(output.clone()) as Box<dyn Any + Send>)
+    }
+
+    fn compensate<'a>(
+        &'a self,
+        output: Box<dyn Any + Send>,
+        context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ()> {
+        match output.downcast::<O>() {
+            Ok(output) => self.compensation.compensate(*output, context),
+            Err(_) => Box::pin(async {
+                Err(AgentError::ProcessingError(
+                    "Compensation received an unexpected output type".to_string(),
+                ))
+            }),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use tokio::task::JoinSet;
+
+use crate::agent::{
+    AgentError,
+    pipeline::{Pipeline, PipelineContext, Stage, StageFuture, StageKind, StageRecord},
+};
+
+/// Per-item results of a fan-out, in input order
+#[derive(Debug, Clone)]
+pub struct FanIn<R> {
+    results: Vec<Result<R, AgentError>>,
+}
+
+impl<R> FanIn<R> {
+    pub fn results(&self) -> &[Result<R, AgentError>] {
+        &self.results
+    }
+
+    pub fn successes(&self) -> Vec<&R> {
+        self.results
+            .iter()
+            .filter_map(|r| r.as_ref().ok())
+            .collect()
+    }
+
+    /// Failed items with their index in the input
+    pub fn failures(&self) -> Vec<(usize, &AgentError)> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, r)| r.as_ref().err().map(|e| (index, e)))
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.results.iter().all(Result::is_ok)
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// All outputs, or the first item error
+    pub fn into_all(self) -> Result<Vec<R>, AgentError> {
+        self.results.into_iter().collect()
+    }
+}
+
+/// Runs a pipeline for every item of a collection, e.g. one personalized
+/// email per group member, with at most `max_concurrency` items in flight
+pub struct FanOut<T, R> {
+    name: String,
+    pipeline: Arc<Pipeline<T, R>>,
+    max_concurrency: usize,
+}
+
+pub fn fan_out<T, R>(name: &str, pipeline: Pipeline<T, R>, max_concurrency: usize) -> FanOut<T, R> {
+    FanOut {
+        name: name.to_string(),
+        pipeline: Arc::new(pipeline),
+        max_concurrency: max_concurrency.max(1),
+    }
+}
+
+impl<T, R> Stage<Vec<T>, FanIn<R>> for FanOut<T, R>
+where
+    T: Send + 'static,
+    R: Clone + Send + 'static,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::FanOut {
+            max_concurrency: self.max_concurrency,
+            body: self.pipeline.graph(),
+        }
+    }
+
+    /// Items run with their own context, under the same middleware; their traces are merged into the
+    /// run's trace as `name[index].stage`
+    fn run<'a>(
+        &'a self,
+        input: Vec<T>,
+        context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, FanIn<R>> {
+        Box::pin(async move {
+            let total = input.len();
+            let mut results: Vec<Option<Result<R, AgentError>>> = vec![None; total];
+            let mut traces: Vec<Vec<StageRecord>> = vec![Vec::new(); total];
+            let mut items = input.into_iter().enumerate();
+            let mut running = JoinSet::new();
+
+            loop {
+                while running.len() < self.max_concurrency {
+                    let Some((index, item)) = items.next() else {
+                        break;
+                    };
+                    let pipeline = self.pipeline.clone();
+                    let mut item_context = context.child();
+                    running.spawn(async move {
+                        let result = pipeline.run_with(item, &mut item_context).await;
+                        (index, result, item_context)
+                    });
+                }
+                let Some(joined) = running.join_next().await else {
+                    break;
+                };
+                let (index, result, item_context) = joined.map_err(|e| {
+                    AgentError::ProcessingError(format!("Fan-out task failed: {}", e))
+                })?;
+                results[index] = Some(result);
+                traces[index] = item_context.trace().to_vec();
+            }
+
+            for (index, trace) in traces.into_iter().enumerate() {
+                for mut record in trace {
+                    record.stage = format!("{}[{}].{}", self.name, index, record.stage);
+                    context.record(record);
+                }
+            }
+            Ok(FanIn {
+                results: results.into_iter().flatten().collect(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::pipeline::from_fn;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    struct Compose {
+        in_flight: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    impl Stage<String, String> for Compose {
+        fn name(&self) -> &str {
+            "compose"
+        }
+
+        fn run<'a>(
+            &'a self,
+            member: String,
+            _context: &'a mut PipelineContext,
+        ) -> StageFuture<'a, String> {
+            Box::pin(async move {
+                let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                if member.is_empty() {
+                    return Err(AgentError::ProcessingError("no recipient".to_string()));
+                }
+                Ok(format!("Hi {}", member))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_is_bounded_and_collects_per_item() {
+        let peak = Arc::new(AtomicUsize::new(0));
+        let compose = Compose {
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            peak: peak.clone(),
+        };
+        let pipeline = Pipeline::new()
+            .then(from_fn("members", |team: String, _: &mut _| {
+                Ok(team.split(',').map(str::to_string).collect::<Vec<_>>())
+            }))
+            .then(fan_out("personalize", Pipeline::new().then(compose), 2));
+        let mut context = PipelineContext::new();
+
+        let fan_in = pipeline
+            .run_with("Eva,Carlos,,Sofia".to_string(), &mut context)
+            .await
+            .unwrap();
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        assert_eq!(fan_in.len(), 4);
+        assert_eq!(fan_in.successes(), vec!["Hi Eva", "Hi Carlos", "Hi Sofia"]);
+        assert_eq!(fan_in.failures()[0].0, 2);
+        assert!(fan_in.clone().into_all().is_err());
+        assert!(
+            context
+                .trace()
+                .iter()
+                .any(|record| record.stage == "personalize[3].compose")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_collection() {
+        let pipeline = Pipeline::new().then(fan_out(
+            "none",
+            Pipeline::new().then(from_fn("id", |n: u32, _: &mut _| Ok(n))),
+            4,
+        ));
+
+        let fan_in = pipeline.run(Vec::new()).await.unwrap();
+
+        assert!(fan_in.is_empty());
+        assert!(fan_in.is_complete());
+    }
+}
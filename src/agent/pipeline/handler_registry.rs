@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::agent::{
+    AgentError, ClassificationResult, Intent,
+    classifier::{FieldError, Params, PartialClassification},
+    pipeline::{PipelineContext, Stage, StageFuture},
+};
+
+/// Side effect run for one intent with the validated params of a result
+/// (e.g. post to Slack when a meeting is scheduled)
+pub trait IntentHandler: Send + Sync {
+    fn handle(&self, params: Params) -> StageFuture<'_, ()>;
+}
+
+pub struct FnHandler<F> {
+    function: F,
+}
+
+/// Handler from an async closure
+pub fn handler_fn<F, Fut>(function: F) -> FnHandler<F>
+where
+    F: Fn(Params) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), AgentError>> + Send + 'static,
+{
+    FnHandler { function }
+}
+
+impl<F, Fut> IntentHandler for FnHandler<F>
+where
+    F: Fn(Params) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), AgentError>> + Send + 'static,
+{
+    fn handle(&self, params: Params) -> StageFuture<'_, ()> {
+        Box::pin((self.function)(params))
+    }
+}
+
+/// User-supplied handlers keyed by intent, so library users can plug in
+/// their own side effects without changing the pipeline
+#[derive(Default, Clone)]
+pub struct HandlerRegistry {
+    handlers: HashMap<Intent, Arc<dyn IntentHandler>>,
+}
+
+impl HandlerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the handler of `intent`
+    pub fn register(&mut self, intent: Intent, handler: impl IntentHandler + 'static) {
+        self.handlers.insert(intent, Arc::new(handler));
+    }
+
+    pub fn on(mut self, intent: Intent, handler: impl IntentHandler + 'static) -> Self {
+        self.register(intent, handler);
+        self
+    }
+
+    pub fn handles(&self, intent: &Intent) -> bool {
+        self.handlers.contains_key(intent)
+    }
+
+    /// Intents with a handler, sorted
+    pub fn intents(&self) -> Vec<Intent> {
+        let mut intents: Vec<Intent> = self.handlers.keys().cloned().collect();
+        intents.sort();
+        intents
+    }
+
+    /// Runs the handler of the result's intent. Returns `false` if the
+    /// intent has none; params that fail validation are never handed over.
+    pub async fn dispatch(&self, result: &ClassificationResult) -> Result<bool, AgentError> {
+        let Some(handler) = self.handlers.get(&result.intent) else {
+            return Ok(false);
+        };
+        let params = result.params.clone().normalized().map_err(|e| {
+            AgentError::ValidationError(Box::new(PartialClassification {
+                intent: Some(result.intent.clone()),
+                params: result.params.clone(),
+                errors: vec![FieldError::new("recipient", &e.to_string())],
+            }))
+        })?;
+        handler.handle(params).await?;
+        Ok(true)
+    }
+}
+
+/// Stage that runs the registered handler for each result and passes the
+/// result on unchanged
+pub struct DispatchHandlers {
+    registry: Arc<HandlerRegistry>,
+}
+
+pub fn dispatch_handlers(registry: Arc<HandlerRegistry>) -> DispatchHandlers {
+    DispatchHandlers { registry }
+}
+
+impl Stage<ClassificationResult, ClassificationResult> for DispatchHandlers {
+    fn name(&self) -> &str {
+        "dispatch_handlers"
+    }
+
+    fn run<'a>(
+        &'a self,
+        input: ClassificationResult,
+        _context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ClassificationResult> {
+        Box::pin(async move {
+            self.registry.dispatch(&input).await?;
+            Ok(input)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::pipeline::Pipeline;
+    use std::sync::Mutex;
+
+    fn meeting(recipient: &str) -> ClassificationResult {
+        ClassificationResult::new(
+            Intent::ScheduleMeeting,
+            Params::with_values(recipient.to_string(), "Sync tomorrow".to_string()),
+        )
+    }
+
+    fn recording(posted: &Arc<Mutex<Vec<String>>>) -> impl IntentHandler + 'static {
+        let posted = Arc::clone(posted);
+        handler_fn(move |params: Params| {
+            let posted = Arc::clone(&posted);
+            async move {
+                posted
+                    .lock()
+                    .unwrap()
+                    .push(params.recipient().unwrap_or_default().to_string());
+                Ok(())
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_to_registered_handler() {
+        let posted = Arc::new(Mutex::new(Vec::new()));
+        let registry = HandlerRegistry::new().on(Intent::ScheduleMeeting, recording(&posted));
+
+        assert!(
+            registry
+                .dispatch(&meeting(" <eva@Company.COM> "))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !registry
+                .dispatch(&ClassificationResult::new(
+                    Intent::NoAction,
+                    Params::new(None, None)
+                ))
+                .await
+                .unwrap()
+        );
+        // Handlers receive normalized params
+        assert_eq!(*posted.lock().unwrap(), vec!["eva@company.com".to_string()]);
+        assert_eq!(registry.intents(), vec![Intent::ScheduleMeeting]);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_params_are_not_handed_over() {
+        let posted = Arc::new(Mutex::new(Vec::new()));
+        let registry = HandlerRegistry::new().on(Intent::ScheduleMeeting, recording(&posted));
+
+        let result = registry.dispatch(&meeting("eva@@company")).await;
+
+        assert!(matches!(result, Err(AgentError::ValidationError(_))));
+        assert!(posted.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handler_errors_fail_the_stage() {
+        let registry = HandlerRegistry::new().on(
+            Intent::ScheduleMeeting,
+            handler_fn(|_| async {
+                Err(AgentError::NetworkError("Slack unavailable".to_string()))
+            }),
+        );
+        let pipeline = Pipeline::new().then(dispatch_handlers(Arc::new(registry)));
+
+        let result = pipeline.run(meeting("eva@company.com")).await;
+
+        assert!(matches!(result, Err(AgentError::NetworkError(_))));
+    }
+}
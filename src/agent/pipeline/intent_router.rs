@@ -0,0 +1,330 @@
+use crate::agent::{
+    AgentError, ClassificationResult, Intent,
+    pipeline::{GraphBranch, Pipeline, PipelineContext, Stage, StageFuture, StageKind},
+    sentiment::Sentiment,
+};
+
+/// Output of a routed stage: which branch handled the result, or the
+/// intent that ended the run without further work
+#[derive(Debug, Clone, PartialEq)]
+pub enum Routed<N> {
+    Handled { branch: String, output: N },
+    Terminated(Intent),
+}
+
+impl<N> Routed<N> {
+    pub fn branch(&self) -> Option<&str> {
+        match self {
+            Routed::Handled { branch, .. } => Some(branch),
+            Routed::Terminated(_) => None,
+        }
+    }
+
+    pub fn output(&self) -> Option<&N> {
+        match self {
+            Routed::Handled { output, .. } => Some(output),
+            Routed::Terminated(_) => None,
+        }
+    }
+
+    pub fn into_output(self) -> Option<N> {
+        match self {
+            Routed::Handled { output, .. } => Some(output),
+            Routed::Terminated(_) => None,
+        }
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        matches!(self, Routed::Terminated(_))
+    }
+}
+
+enum Route<N> {
+    Branch {
+        name: String,
+        pipeline: Pipeline<ClassificationResult, N>,
+    },
+    Terminate,
+}
+
+/// Branch node sending each classification down the pipeline registered
+/// for its intent
+pub struct IntentRouter<N> {
+    sentiment_routes: Vec<(Sentiment, String, Pipeline<ClassificationResult, N>)>,
+    routes: Vec<(Intent, Route<N>)>,
+    fallback: Option<(String, Pipeline<ClassificationResult, N>)>,
+}
+
+/// Starts a router; add branches with `on`, `terminate` and `otherwise`
+pub fn route_by_intent<N>() -> IntentRouter<N> {
+    IntentRouter {
+        sentiment_routes: Vec::new(),
+        routes: Vec::new(),
+        fallback: None,
+    }
+}
+
+impl<N> IntentRouter<N> {
+    /// Runs `pipeline` for results with `intent`
+    pub fn on(
+        mut self,
+        intent: Intent,
+        name: &str,
+        pipeline: Pipeline<ClassificationResult, N>,
+    ) -> Self {
+        self.set(
+            intent,
+            Route::Branch {
+                name: name.to_string(),
+                pipeline,
+            },
+        );
+        self
+    }
+
+    /// Runs `pipeline` for results from mail with `sentiment`, whatever
+    /// their intent, e.g. to alert a person about angry mail instead of
+    /// replying to it
+    pub fn on_sentiment(
+        mut self,
+        sentiment: Sentiment,
+        name: &str,
+        pipeline: Pipeline<ClassificationResult, N>,
+    ) -> Self {
+        self.sentiment_routes
+            .retain(|(existing, _, _)| *existing != sentiment);
+        self.sentiment_routes
+            .push((sentiment, name.to_string(), pipeline));
+        self
+    }
+
+    /// Ends the run for results with `intent`
+    pub fn terminate(mut self, intent: Intent) -> Self {
+        self.set(intent, Route::Terminate);
+        self
+    }
+
+    /// Runs `pipeline` for intents without a branch of their own
+    pub fn otherwise(mut self, name: &str, pipeline: Pipeline<ClassificationResult, N>) -> Self {
+        self.fallback = Some((name.to_string(), pipeline));
+        self
+    }
+
+    /// Branch names in registration order, sentiment branches first and
+    /// fallback last
+    pub fn branch_names(&self) -> Vec<&str> {
+        self.sentiment_routes
+            .iter()
+            .map(|(_, name, _)| name.as_str())
+            .chain(self.routes.iter().filter_map(|(_, route)| match route {
+                Route::Branch { name, .. } => Some(name.as_str()),
+                Route::Terminate => None,
+            }))
+            .chain(self.fallback.iter().map(|(name, _)| name.as_str()))
+            .collect()
+    }
+
+    fn set(&mut self, intent: Intent, route: Route<N>) {
+        self.routes.retain(|(existing, _)| *existing != intent);
+        self.routes.push((intent, route));
+    }
+
+    fn route(
+        &self,
+        result: &ClassificationResult,
+    ) -> Option<(&str, Option<&Pipeline<ClassificationResult, N>>)> {
+        if let Some(sentiment) = &result.sentiment
+            && let Some((_, name, pipeline)) = self
+                .sentiment_routes
+                .iter()
+                .find(|(s, _, _)| *s == sentiment.sentiment)
+        {
+            return Some((name, Some(pipeline)));
+        }
+        let intent = &result.intent;
+        let found = self.routes.iter().find(|(i, _)| i == intent);
+        match found {
+            Some((_, Route::Branch { name, pipeline })) => Some((name, Some(pipeline))),
+            Some((_, Route::Terminate)) => Some(("", None)),
+            None => self
+                .fallback
+                .as_ref()
+                .map(|(name, pipeline)| (name.as_str(), Some(pipeline))),
+        }
+    }
+}
+
+impl<N> Stage<ClassificationResult, Routed<N>> for IntentRouter<N>
+where
+    N: Clone + Send + 'static,
+{
+    fn name(&self) -> &str {
+        "route_by_intent"
+    }
+
+    fn kind(&self) -> StageKind {
+        let mut branches: Vec<GraphBranch> = self
+            .sentiment_routes
+            .iter()
+            .map(|(sentiment, name, pipeline)| GraphBranch {
+                label: format!("sentiment={}", sentiment),
+                name: Some(name.clone()),
+                nodes: pipeline.graph(),
+            })
+            .collect();
+        branches.extend(self.routes.iter().map(|(intent, route)| match route {
+            Route::Branch { name, pipeline } => GraphBranch {
+                label: intent.to_string(),
+                name: Some(name.clone()),
+                nodes: pipeline.graph(),
+            },
+            Route::Terminate => GraphBranch {
+                label: intent.to_string(),
+                name: None,
+                nodes: Vec::new(),
+            },
+        }));
+        if let Some((name, pipeline)) = &self.fallback {
+            branches.push(GraphBranch {
+                label: "else".to_string(),
+                name: Some(name.clone()),
+                nodes: pipeline.graph(),
+            });
+        }
+        StageKind::Router { branches }
+    }
+
+    fn run<'a>(
+        &'a self,
+        input: ClassificationResult,
+        context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, Routed<N>> {
+        Box::pin(async move {
+            match self.route(&input) {
+                Some((name, Some(pipeline))) => {
+                    let output = pipeline.run_with(input, context).await?;
+                    Ok(Routed::Handled {
+                        branch: name.to_string(),
+                        output,
+                    })
+                }
+                Some((_, None)) => Ok(Routed::Terminated(input.intent)),
+                None => Err(AgentError::ProcessingError(format!(
+                    "No pipeline branch for intent '{}'",
+                    input.intent
+                ))),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::Params;
+    use crate::agent::pipeline::from_fn;
+    use crate::agent::sentiment::SentimentAssessment;
+
+    fn result(intent: Intent) -> ClassificationResult {
+        ClassificationResult::new(
+            intent,
+            Params::with_values("eva@company.com".to_string(), "Hi".to_string()),
+        )
+    }
+
+    fn label(text: &'static str) -> Pipeline<ClassificationResult, String> {
+        Pipeline::new().then(from_fn(text, move |r: ClassificationResult, _: &mut _| {
+            Ok(format!("{}:{}", text, r.params.recipient().unwrap_or("")))
+        }))
+    }
+
+    fn router() -> IntentRouter<String> {
+        route_by_intent()
+            .on(Intent::SendEmail, "email", label("compose"))
+            .on(Intent::ScheduleMeeting, "calendar", label("invite"))
+            .terminate(Intent::NoAction)
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_intent() {
+        let pipeline = Pipeline::new().then(router());
+        let mut context = PipelineContext::new();
+
+        let routed = pipeline
+            .run_with(result(Intent::ScheduleMeeting), &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(routed.branch(), Some("calendar"));
+        assert_eq!(routed.output().unwrap(), "invite:eva@company.com");
+        let stages: Vec<&str> = context.trace().iter().map(|r| r.stage.as_str()).collect();
+        assert_eq!(stages, vec!["invite", "route_by_intent"]);
+    }
+
+    #[tokio::test]
+    async fn test_terminator() {
+        let pipeline = Pipeline::new().then(router());
+
+        let routed = pipeline.run(result(Intent::NoAction)).await.unwrap();
+
+        assert_eq!(routed, Routed::Terminated(Intent::NoAction));
+    }
+
+    #[tokio::test]
+    async fn test_fallback_branch() {
+        let pipeline = Pipeline::new().then(
+            route_by_intent()
+                .on(Intent::SendEmail, "email", label("compose"))
+                .otherwise("review", label("review")),
+        );
+
+        let routed = pipeline.run(result(Intent::NoAction)).await.unwrap();
+
+        assert_eq!(routed.branch(), Some("review"));
+        assert_eq!(pipeline.stage_names(), vec!["route_by_intent"]);
+    }
+
+    #[tokio::test]
+    async fn test_unrouted_intent_fails() {
+        let pipeline = Pipeline::new().then(route_by_intent().on(
+            Intent::SendEmail,
+            "email",
+            label("compose"),
+        ));
+
+        let error = pipeline
+            .run(result(Intent::ScheduleMeeting))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Processing error: No pipeline branch for intent 'schedule_meeting'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_angry_mail_goes_to_a_person() {
+        let pipeline =
+            Pipeline::new().then(router().on_sentiment(Sentiment::Angry, "alert", label("alert")));
+        let angry = result(Intent::SendEmail).with_sentiment(SentimentAssessment::new(
+            Sentiment::Angry,
+            "complaint".to_string(),
+        ));
+        let friendly = result(Intent::SendEmail)
+            .with_sentiment(SentimentAssessment::new(Sentiment::Friendly, String::new()));
+
+        assert_eq!(pipeline.run(angry).await.unwrap().branch(), Some("alert"));
+        assert_eq!(
+            pipeline.run(friendly).await.unwrap().branch(),
+            Some("email")
+        );
+    }
+
+    #[test]
+    fn test_branch_names() {
+        let router = router().otherwise("review", label("review"));
+
+        assert_eq!(router.branch_names(), vec!["email", "calendar", "review"]);
+    }
+}
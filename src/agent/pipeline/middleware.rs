@@ -0,0 +1,299 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::agent::pipeline::{PipelineContext, StageFuture, StageOutcome, StageRecord};
+use crate::config::{Config, RedactionConfig};
+use crate::i18n::text::preview;
+use crate::safety::{RedactionSink, Redactor};
+
+/// Longest error shown on a trace line; model output can be pages long
+const MAX_TRACE_ERROR: usize = 200;
+
+/// Cross-cutting behaviour attached once to a pipeline and applied to
+/// every stage it runs, including stages of branches and fan-outs.
+/// `before_stage` hooks run in the order middleware was added,
+/// `after_stage` hooks in reverse.
+pub trait Middleware: Send + Sync {
+    /// Runs before each stage; an error fails the run without running it
+    fn before_stage<'a>(
+        &'a self,
+        _stage: &'a str,
+        _context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Sees, and may rewrite, each stage's record before it is added to
+    /// the trace
+    fn after_stage(&self, _record: &mut StageRecord, _context: &mut PipelineContext) {}
+}
+
+/// Reports each stage as it starts and finishes
+pub struct Tracing {
+    sink: Box<dyn Fn(&str) + Send + Sync>,
+}
+
+impl Tracing {
+    /// Emits each line as a `tracing` event
+    pub fn new() -> Self {
+        Self::with_sink(|line| tracing::info!(target: "pipeline", "{}", line))
+    }
+
+    pub fn with_sink(sink: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self {
+            sink: Box::new(sink),
+        }
+    }
+}
+
+impl Default for Tracing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Middleware for Tracing {
+    fn before_stage<'a>(
+        &'a self,
+        stage: &'a str,
+        _context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ()> {
+        (self.sink)(&format!("[pipeline] {} started", stage));
+        Box::pin(async { Ok(()) })
+    }
+
+    fn after_stage(&self, record: &mut StageRecord, _context: &mut PipelineContext) {
+        let outcome = match &record.outcome {
+            StageOutcome::Failed(e) => format!("failed: {}", preview(e, MAX_TRACE_ERROR)),
+            _ => "succeeded".to_string(),
+        };
+        (self.sink)(&format!(
+            "[pipeline] {} {} after {} attempt(s) in {} ms",
+            record.stage,
+            outcome,
+            record.attempts,
+            record.elapsed.as_millis()
+        ));
+    }
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub stage: String,
+    pub attempts: u32,
+    pub elapsed_ms: u64,
+    pub succeeded: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Appends a JSON line per stage to a file. Add it before `Redaction` so
+/// errors are scrubbed before they are written.
+pub struct Audit {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl Audit {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+impl Middleware for Audit {
+    fn after_stage(&self, record: &mut StageRecord, _context: &mut PipelineContext) {
+        let entry = AuditEntry {
+            at: Utc::now(),
+            stage: record.stage.clone(),
+            attempts: record.attempts,
+            elapsed_ms: record.elapsed.as_millis() as u64,
+            succeeded: record.outcome == StageOutcome::Succeeded,
+            error: match &record.outcome {
+                StageOutcome::Failed(e) => Some(e.clone()),
+                _ => None,
+            },
+        };
+        let Ok(line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        let _guard = self.lock.lock().unwrap();
+        // Auditing must never fail the run it observes
+        if let Ok(mut file) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+        {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+/// Masks PII in stage errors before they reach the trace or outer
+/// middleware
+pub struct Redaction {
+    redactor: Redactor,
+}
+
+impl Redaction {
+    pub fn new(redactor: Redactor) -> Self {
+        Self { redactor }
+    }
+
+    /// Masks what `[safety.redaction] logs` lists
+    pub fn from_config(config: &RedactionConfig) -> Self {
+        Self::new(Redactor::for_sink(config, RedactionSink::Logs))
+    }
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self::from_config(&Config::get().safety.redaction)
+    }
+}
+
+impl Middleware for Redaction {
+    fn after_stage(&self, record: &mut StageRecord, _context: &mut PipelineContext) {
+        if let StageOutcome::Failed(e) = &mut record.outcome {
+            *e = self.redactor.redact(e);
+        }
+    }
+}
+
+/// Lets at most `max_stages` stages start per `interval`, waiting for a
+/// slot otherwise; shared by every run of the pipeline
+pub struct RateLimit {
+    max_stages: usize,
+    interval: Duration,
+    started: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimit {
+    pub fn new(max_stages: usize, interval: Duration) -> Self {
+        Self {
+            max_stages: max_stages.max(1),
+            interval,
+            started: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Takes a slot, or returns how long until the oldest one frees up
+    fn try_acquire(&self, now: Instant) -> Result<(), Duration> {
+        let mut started = self.started.lock().unwrap();
+        while started
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= self.interval)
+        {
+            started.pop_front();
+        }
+        if started.len() < self.max_stages {
+            started.push_back(now);
+            return Ok(());
+        }
+        let oldest = started.front().copied().unwrap_or(now);
+        Err(self.interval.saturating_sub(now.duration_since(oldest)))
+    }
+}
+
+impl Middleware for RateLimit {
+    fn before_stage<'a>(
+        &'a self,
+        _stage: &'a str,
+        _context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ()> {
+        Box::pin(async move {
+            while let Err(wait) = self.try_acquire(Instant::now()) {
+                tokio::time::sleep(wait).await;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::safety::PiiKind;
+
+    fn failed(error: &str) -> StageRecord {
+        StageRecord {
+            stage: "send".to_string(),
+            attempts: 2,
+            elapsed: Duration::from_millis(12),
+            outcome: StageOutcome::Failed(error.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_redaction_masks_errors() {
+        let mut record = failed("Rejected recipient eva@company.com");
+
+        Redaction::default().after_stage(&mut record, &mut PipelineContext::new());
+
+        assert_eq!(
+            record.outcome,
+            StageOutcome::Failed("Rejected recipient [REDACTED_EMAIL]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_redaction_follows_the_logs_configuration() {
+        let mut record = failed("Rejected recipient eva@company.com, call +1-555-123-4567");
+        let config = RedactionConfig {
+            logs: vec![PiiKind::Phone],
+            ..RedactionConfig::default()
+        };
+
+        Redaction::from_config(&config).after_stage(&mut record, &mut PipelineContext::new());
+
+        assert_eq!(
+            record.outcome,
+            StageOutcome::Failed(
+                "Rejected recipient eva@company.com, call [REDACTED_PHONE]".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_audit_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("audit-{}.jsonl", std::process::id()));
+        let audit = Audit::new(&path);
+
+        audit.after_stage(&mut failed("smtp down"), &mut PipelineContext::new());
+        audit.after_stage(&mut failed("smtp down"), &mut PipelineContext::new());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let entries: Vec<AuditEntry> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].error.as_deref(), Some("smtp down"));
+        assert_eq!(entries[0].elapsed_ms, 12);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rate_limit_window() {
+        let limit = RateLimit::new(2, Duration::from_secs(1));
+        let now = Instant::now();
+
+        assert!(limit.try_acquire(now).is_ok());
+        assert!(limit.try_acquire(now).is_ok());
+        assert_eq!(
+            limit.try_acquire(now + Duration::from_millis(400)),
+            Err(Duration::from_millis(600))
+        );
+        assert!(limit.try_acquire(now + Duration::from_secs(1)).is_ok());
+    }
+}
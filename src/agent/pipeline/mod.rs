@@ -0,0 +1,28 @@
+pub mod agent_registry;
+pub mod checkpoint;
+pub mod compensation;
+pub mod fan_out;
+pub mod handler_registry;
+pub mod intent_router;
+pub mod middleware;
+#[allow(clippy::module_inception)]
+pub mod pipeline;
+pub mod pipeline_context;
+pub mod pipeline_graph;
+pub mod pipeline_spec;
+pub mod stage;
+
+pub use agent_registry::AgentRegistry;
+pub use checkpoint::{Checkpoint, CheckpointStore};
+pub use compensation::{Compensation, FnCompensation, compensate_fn};
+pub use fan_out::{FanIn, FanOut, fan_out};
+pub use handler_registry::{
+    DispatchHandlers, FnHandler, HandlerRegistry, IntentHandler, dispatch_handlers, handler_fn,
+};
+pub use intent_router::{IntentRouter, Routed, route_by_intent};
+pub use middleware::{Audit, AuditEntry, Middleware, RateLimit, Redaction, Tracing};
+pub use pipeline::{ErrorPolicy, Pipeline};
+pub use pipeline_context::{PipelineContext, StageOutcome, StageRecord};
+pub use pipeline_graph::{GraphBranch, GraphNode, StageKind};
+pub use pipeline_spec::{BranchSpec, PipelineSpec, PipelineSpecError, StageSpec};
+pub use stage::{AgentStage, FnStage, Stage, StageFuture, agent, from_fn, gate};
@@ -0,0 +1,757 @@
+use std::any::Any;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::agent::{
+    AgentError, isolate,
+    pipeline::{
+        Checkpoint, CheckpointStore, Compensation, GraphNode, Middleware, PipelineContext, Stage,
+        StageKind, StageOutcome, StageRecord,
+        checkpoint::{ErasedCodec, TypedCodec},
+        compensation::{ErasedCompensation, TypedCompensation},
+        pipeline_graph,
+    },
+};
+
+pub(crate) type Value = Box<dyn Any + Send>;
+type NodeFuture<'a> = Pin<Box<dyn Future<Output = Result<Value, AgentError>> + Send + 'a>>;
+
+/// What to do when a stage fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Stop the run with the stage's error
+    #[default]
+    Fail,
+    /// Run the stage up to `attempts` times while its error is retryable
+    Retry { attempts: u32 },
+}
+
+impl ErrorPolicy {
+    fn should_retry(&self, error: &AgentError, attempt: u32) -> bool {
+        match self {
+            ErrorPolicy::Fail => false,
+            ErrorPolicy::Retry { attempts } => attempt < *attempts && error.is_retryable(),
+        }
+    }
+}
+
+/// Type-erased stage so stages with different types can share a list
+pub(crate) trait Node: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn kind(&self) -> StageKind;
+
+    fn run<'a>(
+        &'a self,
+        input: Value,
+        policy: ErrorPolicy,
+        context: &'a mut PipelineContext,
+    ) -> NodeFuture<'a>;
+}
+
+pub(crate) struct TypedNode<S, I, O> {
+    stage: S,
+    _types: PhantomData<fn(I) -> O>,
+}
+
+impl<S, I, O> TypedNode<S, I, O>
+where
+    S: Stage<I, O> + 'static,
+    I: Clone + Send + 'static,
+    O: Send + 'static,
+{
+    pub(crate) fn boxed(stage: S) -> Box<dyn Node> {
+        Box::new(Self {
+            stage,
+            _types: PhantomData,
+        })
+    }
+}
+
+impl<S, I, O> Node for TypedNode<S, I, O>
+where
+    S: Stage<I, O>,
+    I: Clone + Send + 'static,
+    O: Send + 'static,
+{
+    fn name(&self) -> &str {
+        self.stage.name()
+    }
+
+    fn kind(&self) -> StageKind {
+        self.stage.kind()
+    }
+
+    fn run<'a>(
+        &'a self,
+        input: Value,
+        policy: ErrorPolicy,
+        context: &'a mut PipelineContext,
+    ) -> NodeFuture<'a> {
+        Box::pin(async move {
+            let input = *input.downcast::<I>().map_err(|_| {
+                AgentError::ProcessingError(format!(
+                    "Stage '{}' received an unexpected input type",
+                    self.name()
+                ))
+            })?;
+
+            let middleware = context.middleware();
+            for layer in &middleware {
+                if let Err(e) = layer.before_stage(self.name(), context).await {
+                    context.record(StageRecord {
+                        stage: self.name().to_string(),
+                        attempts: 0,
+                        elapsed: Duration::ZERO,
+                        outcome: StageOutcome::Failed(e.to_string()),
+                    });
+                    return Err(e);
+                }
+            }
+
+            let started = Instant::now();
+            let mut attempts = 0;
+            let result = loop {
+                attempts += 1;
+                let result = isolate(self.name(), self.stage.run(input.clone(), context)).await;
+                match result {
+                    Err(e) if policy.should_retry(&e, attempts) => continue,
+                    result => break result,
+                }
+            };
+
+            let mut record = StageRecord {
+                stage: self.name().to_string(),
+                attempts,
+                elapsed: started.elapsed(),
+                outcome: match &result {
+                    Ok(_) => StageOutcome::Succeeded,
+                    Err(e) => StageOutcome::Failed(e.to_string()),
+                },
+            };
+            for layer in middleware.iter().rev() {
+                layer.after_stage(&mut record, context);
+            }
+            context.record(record);
+            result.map(|output| Box::new(output) as Value)
+        })
+    }
+}
+
+pub(crate) struct StageNode {
+    node: Box<dyn Node>,
+    policy: ErrorPolicy,
+    compensation: Option<Box<dyn ErasedCompensation>>,
+    checkpoint: Option<Box<dyn ErasedCodec>>,
+}
+
+impl StageNode {
+    pub(crate) fn new(node: Box<dyn Node>, policy: ErrorPolicy) -> Self {
+        Self {
+            node,
+            policy,
+            compensation: None,
+            checkpoint: None,
+        }
+    }
+}
+
+/// Where a resumable run saves its progress
+struct Checkpoints<'a> {
+    store: &'a CheckpointStore,
+    run_id: &'a str,
+}
+
+/// Chain of typed stages run in order, each one's output feeding the next:
+/// `Pipeline::new().then(classify).then(resolve).then(approve)`
+pub struct Pipeline<I, O> {
+    nodes: Vec<StageNode>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    _types: PhantomData<fn(I) -> O>,
+}
+
+impl<I: Clone + Send + 'static> Pipeline<I, I> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            middleware: Vec::new(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<I, O> Pipeline<I, O> {
+    /// Pipeline over stages whose types were checked at runtime, e.g. by
+    /// the `AgentRegistry`
+    pub(crate) fn from_nodes(nodes: Vec<StageNode>) -> Self {
+        Self {
+            nodes,
+            middleware: Vec::new(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<I: Clone + Send + 'static> Default for Pipeline<I, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, O> Pipeline<I, O>
+where
+    I: Send + 'static,
+    O: Clone + Send + 'static,
+{
+    /// Appends a stage taking this pipeline's output
+    pub fn then<N, S>(mut self, stage: S) -> Pipeline<I, N>
+    where
+        S: Stage<O, N> + 'static,
+        N: Clone + Send + 'static,
+    {
+        self.nodes.push(StageNode::new(
+            TypedNode::boxed(stage),
+            ErrorPolicy::default(),
+        ));
+        Pipeline {
+            nodes: self.nodes,
+            middleware: self.middleware,
+            _types: PhantomData,
+        }
+    }
+
+    /// Sets the error policy of the last stage added
+    pub fn on_error(mut self, policy: ErrorPolicy) -> Self {
+        if let Some(last) = self.nodes.last_mut() {
+            last.policy = policy;
+        }
+        self
+    }
+
+    /// Registers how to undo the last stage added. If a later stage fails,
+    /// the compensations of all completed stages run in reverse order
+    /// before the error is returned.
+    pub fn compensate_with<C>(mut self, compensation: C) -> Self
+    where
+        C: Compensation<O> + 'static,
+    {
+        if let Some(last) = self.nodes.last_mut() {
+            last.compensation = Some(Box::new(TypedCompensation {
+                compensation,
+                _output: PhantomData,
+            }));
+        }
+        self
+    }
+
+    /// Applies `middleware` to every stage of every run, including the
+    /// stages of branches and fan-outs
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Saves the output of the last stage added during resumable runs, so
+    /// a restarted run continues after it instead of repeating it
+    pub fn checkpoint(mut self) -> Self
+    where
+        O: Serialize + DeserializeOwned,
+    {
+        if let Some(last) = self.nodes.last_mut() {
+            last.checkpoint = Some(Box::new(TypedCodec::<O>(PhantomData)));
+        }
+        self
+    }
+
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.nodes.iter().map(|n| n.node.name()).collect()
+    }
+
+    /// Stages with their branches and policies, for diagrams
+    pub fn graph(&self) -> Vec<GraphNode> {
+        self.nodes
+            .iter()
+            .map(|stage| GraphNode {
+                name: stage.node.name().to_string(),
+                kind: stage.node.kind(),
+                policy: stage.policy,
+                compensated: stage.compensation.is_some(),
+            })
+            .collect()
+    }
+
+    /// Graphviz DOT source of the flow
+    pub fn to_dot(&self) -> String {
+        pipeline_graph::to_dot(&self.graph())
+    }
+
+    /// Mermaid flowchart source of the flow
+    pub fn to_mermaid(&self) -> String {
+        pipeline_graph::to_mermaid(&self.graph())
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub async fn run(&self, input: I) -> Result<O, AgentError> {
+        self.run_with(input, &mut PipelineContext::new()).await
+    }
+
+    /// Runs with a caller-provided context, e.g. pre-filled with shared
+    /// values or inspected afterwards for the trace
+    pub async fn run_with(&self, input: I, context: &mut PipelineContext) -> Result<O, AgentError> {
+        self.run_from(0, Box::new(input), context, None).await
+    }
+
+    /// Runs under `run_id`, saving the output of every checkpointed stage.
+    /// If the store holds a checkpoint for `run_id`, e.g. after a crash,
+    /// the run continues after that stage and `input` is ignored. The
+    /// checkpoint is removed once the run succeeds and kept if it fails.
+    /// Compensations only cover stages run since the last restart.
+    pub async fn run_resumable(
+        &self,
+        run_id: &str,
+        input: I,
+        store: &CheckpointStore,
+        context: &mut PipelineContext,
+    ) -> Result<O, AgentError> {
+        let (start, value) = match self.restore(store.load(run_id)?) {
+            Some((index, value)) => {
+                context.record(StageRecord {
+                    stage: self.nodes[index].node.name().to_string(),
+                    attempts: 0,
+                    elapsed: Duration::ZERO,
+                    outcome: StageOutcome::Restored,
+                });
+                (index + 1, value)
+            }
+            None => (0, Box::new(input) as Value),
+        };
+
+        let checkpoints = Checkpoints { store, run_id };
+        let output = self
+            .run_from(start, value, context, Some(&checkpoints))
+            .await?;
+        store.remove(run_id)?;
+        Ok(output)
+    }
+
+    /// Stage index and output to continue from; `None` if the checkpoint
+    /// is missing or was taken by a pipeline with different stages
+    fn restore(&self, checkpoint: Option<Checkpoint>) -> Option<(usize, Value)> {
+        let checkpoint = checkpoint?;
+        let stage = self.nodes.get(checkpoint.stage_index)?;
+        if stage.node.name() != checkpoint.stage {
+            return None;
+        }
+        let value = stage.checkpoint.as_ref()?.decode(checkpoint.output).ok()?;
+        Some((checkpoint.stage_index, value))
+    }
+
+    async fn run_from(
+        &self,
+        start: usize,
+        value: Value,
+        context: &mut PipelineContext,
+        checkpoints: Option<&Checkpoints<'_>>,
+    ) -> Result<O, AgentError> {
+        let previous = context.push_middleware(&self.middleware);
+        let result = self.run_stages(start, value, context, checkpoints).await;
+        context.restore_middleware(previous);
+        result
+    }
+
+    async fn run_stages(
+        &self,
+        start: usize,
+        mut value: Value,
+        context: &mut PipelineContext,
+        checkpoints: Option<&Checkpoints<'_>>,
+    ) -> Result<O, AgentError> {
+        let mut completed = Vec::new();
+        for (index, stage) in self.nodes.iter().enumerate().skip(start) {
+            value = match stage.node.run(value, stage.policy, context).await {
+                Ok(value) => value,
+                Err(e) => {
+                    compensate(completed, context).await;
+                    return Err(e);
+                }
+            };
+            if let Some(compensation) = &stage.compensation
+                && let Some(snapshot) = compensation.snapshot(value.as_ref())
+            {
+                completed.push((stage, compensation.as_ref(), snapshot));
+            }
+            if let Some(checkpoints) = checkpoints
+                && let Some(codec) = &stage.checkpoint
+            {
+                checkpoints.store.save(&Checkpoint {
+                    run_id: checkpoints.run_id.to_string(),
+                    stage: stage.node.name().to_string(),
+                    stage_index: index,
+                    output: codec.encode(value.as_ref())?,
+                    saved_at: Utc::now(),
+                })?;
+            }
+        }
+        value.downcast::<O>().map(|output| *output).map_err(|_| {
+            AgentError::ProcessingError("Pipeline produced an unexpected output type".to_string())
+        })
+    }
+}
+
+/// Undoes completed stages, latest first. A failing compensation is
+/// recorded in the trace and does not stop the others.
+async fn compensate(
+    completed: Vec<(&StageNode, &dyn ErasedCompensation, Value)>,
+    context: &mut PipelineContext,
+) {
+    for (stage, compensation, output) in completed.into_iter().rev() {
+        let name = stage.node.name();
+        let started = Instant::now();
+        let result = isolate(name, compensation.compensate(output, context)).await;
+        context.record(StageRecord {
+            stage: name.to_string(),
+            attempts: 1,
+            elapsed: started.elapsed(),
+            outcome: match result {
+                Ok(()) => StageOutcome::Compensated,
+                Err(e) => StageOutcome::CompensationFailed(e.to_string()),
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::{ActionGate, ConfirmationPolicy, Proposal};
+    use crate::agent::classifier::Params;
+    use crate::agent::pipeline::{
+        StageFuture, Tracing, compensate_fn, from_fn, gate, route_by_intent,
+    };
+    use crate::agent::{ClassificationResult, Intent};
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn parse() -> impl Stage<String, u32> {
+        from_fn("parse", |input: String, _: &mut PipelineContext| {
+            input
+                .trim()
+                .parse()
+                .map_err(|_| AgentError::ParseError(format!("not a number: {}", input)))
+        })
+    }
+
+    #[tokio::test]
+    async fn test_stages_run_in_order() {
+        let pipeline = Pipeline::new()
+            .then(parse())
+            .then(from_fn("double", |n: u32, _: &mut _| Ok(n * 2)))
+            .then(from_fn("format", |n: u32, _: &mut _| Ok(format!("={}", n))));
+
+        assert_eq!(pipeline.stage_names(), vec!["parse", "double", "format"]);
+        assert_eq!(pipeline.run(" 21 ".to_string()).await.unwrap(), "=42");
+    }
+
+    #[tokio::test]
+    async fn test_context_is_shared_between_stages() {
+        let pipeline = Pipeline::new()
+            .then(from_fn(
+                "remember",
+                |n: u32, context: &mut PipelineContext| {
+                    context.insert(n);
+                    Ok(n + 1)
+                },
+            ))
+            .then(from_fn(
+                "recall",
+                |n: u32, context: &mut PipelineContext| {
+                    Ok(n + context.get::<u32>().copied().unwrap_or(0))
+                },
+            ));
+
+        let mut context = PipelineContext::new();
+        let output = pipeline.run_with(5, &mut context).await.unwrap();
+
+        assert_eq!(output, 11);
+        assert_eq!(context.trace().len(), 2);
+        assert!(context.failed_stage().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let flaky = from_fn("flaky", move |n: u32, _: &mut _| {
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(AgentError::NetworkError("timeout".to_string()))
+            } else {
+                Ok(n)
+            }
+        });
+        let pipeline = Pipeline::new()
+            .then(flaky)
+            .on_error(ErrorPolicy::Retry { attempts: 3 });
+
+        let mut context = PipelineContext::new();
+        assert_eq!(pipeline.run_with(7, &mut context).await.unwrap(), 7);
+        assert_eq!(context.trace()[0].attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retries_exhausted_stop_the_run() {
+        let pipeline = Pipeline::new()
+            .then(parse())
+            .on_error(ErrorPolicy::Retry { attempts: 3 })
+            .then(from_fn("double", |n: u32, _: &mut _| Ok(n * 2)));
+        let mut context = PipelineContext::new();
+
+        let result = pipeline.run_with("abc".to_string(), &mut context).await;
+
+        assert!(matches!(result, Err(AgentError::ParseError(_))));
+        assert_eq!(context.trace().len(), 1);
+        assert_eq!(context.trace()[0].attempts, 3);
+        assert_eq!(context.failed_stage(), Some("parse"));
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_errors_fail_fast() {
+        let pipeline = Pipeline::new()
+            .then(from_fn("reject", |_: u32, _: &mut _| -> Result<u32, _> {
+                Err(AgentError::ProcessingError("unsupported".to_string()))
+            }))
+            .on_error(ErrorPolicy::Retry { attempts: 3 });
+        let mut context = PipelineContext::new();
+
+        pipeline.run_with(1, &mut context).await.unwrap_err();
+
+        assert_eq!(context.trace()[0].attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_stage_is_isolated() {
+        let pipeline = Pipeline::new()
+            .then(from_fn("boom", |_: u32, _: &mut _| -> Result<u32, _> {
+                panic!("bad input")
+            }));
+
+        let result = pipeline.run(1).await;
+
+        assert!(matches!(result, Err(AgentError::StagePanicked { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_gate_stage() {
+        let action_gate = Arc::new(ActionGate::new(ConfirmationPolicy::default()));
+        let pipeline = Pipeline::new().then(gate(action_gate.clone()));
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva@company.com".to_string(), "Hi".to_string()),
+        );
+
+        let proposal = pipeline.run(result).await.unwrap();
+
+        assert!(matches!(proposal, Proposal::NeedsConfirmation(_)));
+        assert_eq!(action_gate.pending().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failure_compensates_completed_stages_in_reverse() {
+        let undone = Arc::new(Mutex::new(Vec::new()));
+        let (event_log, notice_log) = (undone.clone(), undone.clone());
+        let pipeline = Pipeline::new()
+            .then(from_fn("create_event", |n: u32, _: &mut _| Ok(n + 100)))
+            .compensate_with(compensate_fn(move |event: u32, _: &mut _| {
+                event_log
+                    .lock()
+                    .unwrap()
+                    .push(format!("delete event {}", event));
+                Ok(())
+            }))
+            .then(from_fn("notify", |n: u32, _: &mut _| Ok(n)))
+            .compensate_with(compensate_fn(move |_: u32, _: &mut _| {
+                notice_log
+                    .lock()
+                    .unwrap()
+                    .push("retract notice".to_string());
+                Err(AgentError::NetworkError("mail down".to_string()))
+            }))
+            .then(from_fn(
+                "send_invite",
+                |_: u32, _: &mut _| -> Result<u32, _> {
+                    Err(AgentError::NetworkError("smtp refused".to_string()))
+                },
+            ));
+        let mut context = PipelineContext::new();
+
+        let result = pipeline.run_with(1, &mut context).await;
+
+        assert!(matches!(result, Err(AgentError::NetworkError(e)) if e == "smtp refused"));
+        assert_eq!(
+            *undone.lock().unwrap(),
+            vec!["retract notice".to_string(), "delete event 101".to_string()]
+        );
+        let outcomes: Vec<&StageOutcome> = context.trace().iter().map(|r| &r.outcome).collect();
+        assert!(matches!(outcomes[3], StageOutcome::CompensationFailed(_)));
+        assert_eq!(outcomes[4], &StageOutcome::Compensated);
+    }
+
+    #[tokio::test]
+    async fn test_success_does_not_compensate() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let pipeline = Pipeline::new()
+            .then(from_fn("create_event", |n: u32, _: &mut _| Ok(n)))
+            .compensate_with(compensate_fn(move |_: u32, _: &mut _| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }));
+
+        pipeline.run(1).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_resumes_after_last_checkpoint() {
+        let directory = std::env::temp_dir().join(format!("resume-{}", std::process::id()));
+        let store = CheckpointStore::new(&directory);
+        let classified = Arc::new(AtomicU32::new(0));
+        let sends = Arc::new(AtomicU32::new(0));
+        let (classify_count, send_count) = (classified.clone(), sends.clone());
+        let pipeline = Pipeline::new()
+            .then(from_fn("classify", move |text: String, _: &mut _| {
+                classify_count.fetch_add(1, Ordering::SeqCst);
+                Ok(text.len() as u32)
+            }))
+            .checkpoint()
+            .then(from_fn("send", move |n: u32, _: &mut _| {
+                if send_count.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(AgentError::NetworkError("smtp down".to_string()))
+                } else {
+                    Ok(n * 10)
+                }
+            }));
+
+        let mut context = PipelineContext::new();
+        pipeline
+            .run_resumable("run-1", "hello".to_string(), &store, &mut context)
+            .await
+            .unwrap_err();
+        assert_eq!(store.load("run-1").unwrap().unwrap().stage, "classify");
+
+        let mut context = PipelineContext::new();
+        let output = pipeline
+            .run_resumable("run-1", "ignored".to_string(), &store, &mut context)
+            .await
+            .unwrap();
+
+        assert_eq!(output, 50);
+        assert_eq!(classified.load(Ordering::SeqCst), 1);
+        assert_eq!(context.trace()[0].outcome, StageOutcome::Restored);
+        assert_eq!(store.load("run-1").unwrap(), None);
+        std::fs::remove_dir_all(directory).ok();
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_from_other_pipeline_is_ignored() {
+        let directory = std::env::temp_dir().join(format!("stale-{}", std::process::id()));
+        let store = CheckpointStore::new(&directory);
+        store
+            .save(&Checkpoint {
+                run_id: "run-2".to_string(),
+                stage: "renamed".to_string(),
+                stage_index: 0,
+                output: serde_json::json!(99),
+                saved_at: Utc::now(),
+            })
+            .unwrap();
+        let pipeline = Pipeline::new()
+            .then(parse())
+            .checkpoint()
+            .then(from_fn("double", |n: u32, _: &mut _| Ok(n * 2)));
+
+        let output = pipeline
+            .run_resumable(
+                "run-2",
+                "4".to_string(),
+                &store,
+                &mut PipelineContext::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(output, 8);
+        std::fs::remove_dir_all(directory).ok();
+    }
+
+    struct DenyStage(&'static str);
+
+    impl Middleware for DenyStage {
+        fn before_stage<'a>(
+            &'a self,
+            stage: &'a str,
+            _context: &'a mut PipelineContext,
+        ) -> StageFuture<'a, ()> {
+            Box::pin(async move {
+                if stage == self.0 {
+                    Err(AgentError::ProcessingError(format!(
+                        "{} is disabled",
+                        stage
+                    )))
+                } else {
+                    Ok(())
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_covers_nested_stages() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = lines.clone();
+        let branch = Pipeline::new()
+            .then(from_fn("compose", |r: ClassificationResult, _: &mut _| {
+                Ok(r.intent.to_string())
+            }));
+        let pipeline = Pipeline::new()
+            .then(route_by_intent().on(Intent::SendEmail, "email", branch))
+            .with_middleware(Tracing::with_sink(move |line| {
+                sink.lock().unwrap().push(line.to_string())
+            }));
+        let result = ClassificationResult::new(Intent::SendEmail, Params::new(None, None));
+
+        pipeline.run(result).await.unwrap();
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], "[pipeline] compose started");
+    }
+
+    #[tokio::test]
+    async fn test_middleware_can_stop_a_run() {
+        let pipeline = Pipeline::new()
+            .then(parse())
+            .then(from_fn("send", |n: u32, _: &mut _| Ok(n)))
+            .with_middleware(DenyStage("send"));
+        let mut context = PipelineContext::new();
+
+        let result = pipeline.run_with("1".to_string(), &mut context).await;
+
+        assert!(matches!(result, Err(AgentError::ProcessingError(_))));
+        assert_eq!(context.trace()[1].attempts, 0);
+    }
+}
@@ -0,0 +1,126 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::agent::pipeline::Middleware;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StageOutcome {
+    Succeeded,
+    Failed(String),
+    /// Undone after a later stage failed
+    Compensated,
+    CompensationFailed(String),
+    /// Not run; its output was restored from a checkpoint
+    Restored,
+}
+
+/// What happened in one stage of a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageRecord {
+    pub stage: String,
+    pub attempts: u32,
+    pub elapsed: Duration,
+    pub outcome: StageOutcome,
+}
+
+/// State shared by all stages of one pipeline run: typed values any stage
+/// may read or write, plus a trace of the stages run so far
+#[derive(Default)]
+pub struct PipelineContext {
+    values: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    trace: Vec<StageRecord>,
+    middleware: Vec<Arc<dyn Middleware>>,
+}
+
+impl PipelineContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fresh context for a nested run that keeps the active middleware,
+    /// e.g. for one item of a fan-out
+    pub fn child(&self) -> Self {
+        Self {
+            middleware: self.middleware.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Stores a value, replacing any previous value of the same type
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) {
+        self.values.insert(TypeId::of::<T>(), Box::new(value));
+    }
+
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    pub fn get_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+        self.values
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut())
+    }
+
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        self.values
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    pub fn trace(&self) -> &[StageRecord] {
+        &self.trace
+    }
+
+    /// Stage that failed the run, if any
+    pub fn failed_stage(&self) -> Option<&str> {
+        self.trace
+            .iter()
+            .find(|record| matches!(record.outcome, StageOutcome::Failed(_)))
+            .map(|record| record.stage.as_str())
+    }
+
+    pub(crate) fn record(&mut self, record: StageRecord) {
+        self.trace.push(record);
+    }
+
+    pub(crate) fn middleware(&self) -> Vec<Arc<dyn Middleware>> {
+        self.middleware.clone()
+    }
+
+    /// Adds a pipeline's middleware for the length of its run; returns
+    /// the count to restore afterwards
+    pub(crate) fn push_middleware(&mut self, middleware: &[Arc<dyn Middleware>]) -> usize {
+        let previous = self.middleware.len();
+        self.middleware.extend(middleware.iter().cloned());
+        previous
+    }
+
+    pub(crate) fn restore_middleware(&mut self, count: usize) {
+        self.middleware.truncate(count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct UserId(String);
+
+    #[test]
+    fn test_typed_values() {
+        let mut context = PipelineContext::new();
+        context.insert(UserId("u1".to_string()));
+        context.insert(3_u32);
+
+        assert_eq!(context.get::<UserId>(), Some(&UserId("u1".to_string())));
+        *context.get_mut::<u32>().unwrap() += 1;
+        assert_eq!(context.remove::<u32>(), Some(4));
+        assert_eq!(context.get::<u32>(), None);
+    }
+}
@@ -0,0 +1,283 @@
+use std::fmt::Write;
+
+use crate::agent::pipeline::ErrorPolicy;
+
+/// Shape of a stage in a pipeline diagram
+#[derive(Debug, Clone, PartialEq)]
+pub enum StageKind {
+    Step,
+    Router {
+        branches: Vec<GraphBranch>,
+    },
+    FanOut {
+        max_concurrency: usize,
+        body: Vec<GraphNode>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub name: String,
+    pub kind: StageKind,
+    pub policy: ErrorPolicy,
+    pub compensated: bool,
+}
+
+/// One way out of a router; `name` is `None` for a terminator
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphBranch {
+    pub label: String,
+    pub name: Option<String>,
+    pub nodes: Vec<GraphNode>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Shape {
+    Terminal,
+    Step,
+    Decision,
+    Collect,
+}
+
+struct Vertex {
+    label: String,
+    shape: Shape,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+}
+
+/// Flattened diagram shared by the DOT and Mermaid writers
+#[derive(Default)]
+struct Diagram {
+    vertices: Vec<Vertex>,
+    edges: Vec<Edge>,
+}
+
+type Exit = (usize, Option<String>);
+
+impl Diagram {
+    fn build(nodes: &[GraphNode]) -> Self {
+        let mut diagram = Self::default();
+        let start = diagram.vertex("input", Shape::Terminal);
+        let exits = diagram.walk(nodes, vec![(start, None)]);
+        let end = diagram.vertex("output", Shape::Terminal);
+        diagram.connect(exits, end);
+        diagram
+    }
+
+    fn vertex(&mut self, label: &str, shape: Shape) -> usize {
+        self.vertices.push(Vertex {
+            label: label.to_string(),
+            shape,
+        });
+        self.vertices.len() - 1
+    }
+
+    fn connect(&mut self, exits: Vec<Exit>, to: usize) {
+        for (from, label) in exits {
+            self.edges.push(Edge { from, to, label });
+        }
+    }
+
+    fn walk(&mut self, nodes: &[GraphNode], mut exits: Vec<Exit>) -> Vec<Exit> {
+        for node in nodes {
+            let mut label = node.name.clone();
+            if let ErrorPolicy::Retry { attempts } = node.policy {
+                label.push_str(&format!("\nretry x{}", attempts));
+            }
+            if node.compensated {
+                label.push_str("\ncompensated");
+            }
+
+            exits = match &node.kind {
+                StageKind::Step => {
+                    let id = self.vertex(&label, Shape::Step);
+                    self.connect(exits, id);
+                    vec![(id, None)]
+                }
+                StageKind::Router { branches } => {
+                    let id = self.vertex(&label, Shape::Decision);
+                    self.connect(exits, id);
+                    let mut branch_exits = Vec::new();
+                    for branch in branches {
+                        let entry = vec![(id, Some(branch.label.clone()))];
+                        match &branch.name {
+                            Some(_) => branch_exits.extend(self.walk(&branch.nodes, entry)),
+                            None => {
+                                let end = self.vertex("end", Shape::Terminal);
+                                self.connect(entry, end);
+                            }
+                        }
+                    }
+                    branch_exits
+                }
+                StageKind::FanOut {
+                    max_concurrency,
+                    body,
+                } => {
+                    label.push_str(&format!("\nfan-out, max {}", max_concurrency));
+                    let id = self.vertex(&label, Shape::Step);
+                    self.connect(exits, id);
+                    let body_exits = self.walk(body, vec![(id, Some("each".to_string()))]);
+                    let collect = self.vertex(&format!("{} fan-in", node.name), Shape::Collect);
+                    self.connect(body_exits, collect);
+                    vec![(collect, None)]
+                }
+            };
+        }
+        exits
+    }
+}
+
+/// Graphviz DOT source for the stages
+pub fn to_dot(nodes: &[GraphNode]) -> String {
+    let diagram = Diagram::build(nodes);
+    let mut dot = String::from("digraph pipeline {\n    rankdir=LR;\n");
+    for (id, vertex) in diagram.vertices.iter().enumerate() {
+        let shape = match vertex.shape {
+            Shape::Terminal => "oval",
+            Shape::Step => "box",
+            Shape::Decision => "diamond",
+            Shape::Collect => "invtrapezium",
+        };
+        let _ = writeln!(
+            dot,
+            "    n{} [label=\"{}\", shape={}];",
+            id,
+            escape(&vertex.label).replace('\n', "\\n"),
+            shape
+        );
+    }
+    for edge in &diagram.edges {
+        let _ = match &edge.label {
+            Some(label) => writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\"];",
+                edge.from,
+                edge.to,
+                escape(label)
+            ),
+            None => writeln!(dot, "    n{} -> n{};", edge.from, edge.to),
+        };
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Mermaid flowchart source for the stages, e.g. for Markdown docs
+pub fn to_mermaid(nodes: &[GraphNode]) -> String {
+    let diagram = Diagram::build(nodes);
+    let mut mermaid = String::from("flowchart LR\n");
+    for (id, vertex) in diagram.vertices.iter().enumerate() {
+        let label = escape(&vertex.label).replace('\n', "<br/>");
+        let _ = match vertex.shape {
+            Shape::Terminal => writeln!(mermaid, "    n{}([\"{}\"])", id, label),
+            Shape::Step => writeln!(mermaid, "    n{}[\"{}\"]", id, label),
+            Shape::Decision => writeln!(mermaid, "    n{}{{\"{}\"}}", id, label),
+            Shape::Collect => writeln!(mermaid, "    n{}[\\\"{}\"/]", id, label),
+        };
+    }
+    for edge in &diagram.edges {
+        let _ = match &edge.label {
+            Some(label) => writeln!(
+                mermaid,
+                "    n{} -->|\"{}\"| n{}",
+                edge.from,
+                escape(label),
+                edge.to
+            ),
+            None => writeln!(mermaid, "    n{} --> n{}", edge.from, edge.to),
+        };
+    }
+    mermaid
+}
+
+fn escape(label: &str) -> String {
+    label.replace('"', "'")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::agent::pipeline::{
+        ErrorPolicy, FanIn, Pipeline, PipelineContext, Routed, compensate_fn, fan_out, from_fn,
+        route_by_intent,
+    };
+    use crate::agent::{ClassificationResult, Intent, classifier::Params};
+
+    fn pipeline() -> Pipeline<String, ClassificationResult> {
+        Pipeline::new()
+            .then(from_fn("classify", |_: String, _: &mut PipelineContext| {
+                Ok(ClassificationResult::new(
+                    Intent::NoAction,
+                    Params::new(None, None),
+                ))
+            }))
+            .on_error(ErrorPolicy::Retry { attempts: 3 })
+    }
+
+    fn routed() -> Pipeline<String, Routed<Vec<String>>> {
+        let email = Pipeline::new()
+            .then(from_fn("members", |_: ClassificationResult, _: &mut _| {
+                Ok(vec!["Eva".to_string()])
+            }))
+            .then(fan_out(
+                "compose",
+                Pipeline::new().then(from_fn("personalize", |m: String, _: &mut _| Ok(m))),
+                4,
+            ))
+            .then(from_fn("collect", |fan_in: FanIn<String>, _: &mut _| {
+                Ok(fan_in.successes().into_iter().cloned().collect::<Vec<_>>())
+            }));
+        let calendar = Pipeline::new()
+            .then(from_fn(
+                "create_event",
+                |_: ClassificationResult, _: &mut _| Ok(vec!["event".to_string()]),
+            ))
+            .compensate_with(compensate_fn(|_: Vec<String>, _: &mut _| Ok(())));
+        pipeline().then(
+            route_by_intent()
+                .on(Intent::SendEmail, "email", email)
+                .on(Intent::ScheduleMeeting, "calendar", calendar)
+                .terminate(Intent::NoAction),
+        )
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = pipeline().to_dot();
+
+        assert_eq!(
+            dot,
+            "digraph pipeline {\n    rankdir=LR;\n    \
+             n0 [label=\"input\", shape=oval];\n    \
+             n1 [label=\"classify\\nretry x3\", shape=box];\n    \
+             n2 [label=\"output\", shape=oval];\n    \
+             n0 -> n1;\n    n1 -> n2;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_branches_and_fan_out() {
+        let dot = routed().to_dot();
+
+        assert!(dot.contains("shape=diamond"));
+        assert!(dot.contains("[label=\"send_email\"]"));
+        assert!(dot.contains("[label=\"no_action\"]"));
+        assert!(dot.contains("compose\\nfan-out, max 4"));
+        assert!(dot.contains("create_event\\ncompensated"));
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let mermaid = routed().to_mermaid();
+
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("{\"route_by_intent\"}"));
+        assert!(mermaid.contains("-->|\"schedule_meeting\"|"));
+        assert!(mermaid.contains("[\"classify<br/>retry x3\"]"));
+    }
+}
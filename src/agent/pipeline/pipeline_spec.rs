@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::agent::Intent;
+
+/// Pipeline declared in a TOML (or, with the `yaml` feature, YAML) file
+/// and built at runtime from the stages of an `AgentRegistry`:
+///
+/// ```toml
+/// name = "assistant"
+///
+/// [[stages]]
+/// stage = "classify"
+/// retry = 3
+///
+/// [[stages]]
+/// [[stages.route_by_intent]]
+/// intent = "send_email"
+/// branch = "email"
+/// stages = [{ stage = "compose" }, { stage = "approve" }]
+///
+/// [[stages.route_by_intent]]
+/// intent = "no_action"
+/// terminate = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineSpec {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub stages: Vec<StageSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StageSpec {
+    /// Stage registered under `stage`, retried up to `retry` attempts
+    Stage {
+        stage: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry: Option<u32>,
+    },
+    Route {
+        route_by_intent: Vec<BranchSpec>,
+    },
+}
+
+/// One branch of a router; a branch without `intent` is the fallback
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intent: Option<Intent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub terminate: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<StageSpec>,
+}
+
+impl BranchSpec {
+    pub fn name(&self) -> String {
+        match (&self.branch, &self.intent) {
+            (Some(branch), _) => branch.clone(),
+            (None, Some(intent)) => intent.to_string(),
+            (None, None) => "else".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineSpecError {
+    Io(String),
+    Syntax(String),
+    UnknownStage(String),
+    /// A stage does not accept what the previous stage produces
+    TypeMismatch {
+        stage: String,
+        expected: String,
+        found: String,
+    },
+    InvalidRoute(String),
+}
+
+impl fmt::Display for PipelineSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineSpecError::Io(msg) => write!(f, "Failed to read pipeline: {}", msg),
+            PipelineSpecError::Syntax(msg) => write!(f, "Pipeline syntax error: {}", msg),
+            PipelineSpecError::UnknownStage(name) => write!(f, "Unknown stage '{}'", name),
+            PipelineSpecError::TypeMismatch {
+                stage,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Stage '{}' takes {} but receives {}",
+                stage, expected, found
+            ),
+            PipelineSpecError::InvalidRoute(msg) => write!(f, "Invalid route: {}", msg),
+        }
+    }
+}
+
+impl Error for PipelineSpecError {}
+
+impl PipelineSpec {
+    pub fn from_toml(content: &str) -> Result<Self, PipelineSpecError> {
+        toml::from_str(content).map_err(|e| PipelineSpecError::Syntax(e.to_string()))
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(content: &str) -> Result<Self, PipelineSpecError> {
+        serde_yaml::from_str(content).map_err(|e| PipelineSpecError::Syntax(e.to_string()))
+    }
+
+    /// Reads a `.toml` file, or a `.yaml`/`.yml` file with the `yaml` feature
+    pub fn load(path: &Path) -> Result<Self, PipelineSpecError> {
+        let content = fs::read_to_string(path).map_err(|e| PipelineSpecError::Io(e.to_string()))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => Self::from_yaml(&content),
+            _ => Self::from_toml(&content),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+name = "assistant"
+
+[[stages]]
+stage = "classify"
+retry = 3
+
+[[stages]]
+[[stages.route_by_intent]]
+intent = "send_email"
+branch = "email"
+stages = [{ stage = "compose" }]
+
+[[stages.route_by_intent]]
+intent = "no_action"
+terminate = true
+
+[[stages.route_by_intent]]
+stages = [{ stage = "review" }]
+"#;
+
+    #[test]
+    fn test_toml() {
+        let spec = PipelineSpec::from_toml(SPEC).unwrap();
+
+        assert_eq!(
+            spec.stages[0],
+            StageSpec::Stage {
+                stage: "classify".to_string(),
+                retry: Some(3)
+            }
+        );
+        let StageSpec::Route { route_by_intent } = &spec.stages[1] else {
+            panic!("expected a route");
+        };
+        assert_eq!(route_by_intent[0].name(), "email");
+        assert!(route_by_intent[1].terminate);
+        assert_eq!(route_by_intent[2].name(), "else");
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        assert!(matches!(
+            PipelineSpec::from_toml("stages = 3"),
+            Err(PipelineSpecError::Syntax(_))
+        ));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml() {
+        let yaml = "
+stages:
+  - stage: classify
+  - route_by_intent:
+      - intent: schedule_meeting
+        stages:
+          - stage: invite
+";
+        let spec = PipelineSpec::from_yaml(yaml).unwrap();
+
+        assert_eq!(spec.stages.len(), 2);
+        assert_eq!(
+            spec,
+            PipelineSpec::from_toml(&toml::to_string(&spec).unwrap()).unwrap()
+        );
+    }
+}
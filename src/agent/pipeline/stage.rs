@@ -0,0 +1,120 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::action::{ActionGate, Proposal};
+use crate::agent::{
+    Agent, AgentError, AgentResult, ClassificationResult,
+    agent::AgentParam,
+    pipeline::{PipelineContext, StageKind},
+};
+
+pub type StageFuture<'a, O> = Pin<Box<dyn Future<Output = Result<O, AgentError>> + Send + 'a>>;
+
+/// One typed step of a pipeline
+pub trait Stage<I, O>: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn run<'a>(&'a self, input: I, context: &'a mut PipelineContext) -> StageFuture<'a, O>;
+
+    /// Shape of the stage in pipeline diagrams
+    fn kind(&self) -> StageKind {
+        StageKind::Step
+    }
+}
+
+/// Lets one stage instance be shared by several pipelines
+impl<I, O, S> Stage<I, O> for Arc<S>
+where
+    S: Stage<I, O> + ?Sized,
+{
+    fn name(&self) -> &str {
+        self.as_ref().name()
+    }
+
+    fn run<'a>(&'a self, input: I, context: &'a mut PipelineContext) -> StageFuture<'a, O> {
+        self.as_ref().run(input, context)
+    }
+
+    fn kind(&self) -> StageKind {
+        self.as_ref().kind()
+    }
+}
+
+/// Runs an agent as a stage
+pub struct AgentStage<A, P, T> {
+    name: String,
+    agent: A,
+    _types: PhantomData<fn(P) -> T>,
+}
+
+pub fn agent<A, P, T>(name: &str, agent: A) -> AgentStage<A, P, T>
+where
+    A: Agent<P, T> + Send + Sync,
+    P: AgentParam,
+    T: AgentResult,
+{
+    AgentStage {
+        name: name.to_string(),
+        agent,
+        _types: PhantomData,
+    }
+}
+
+impl<A, P, T> Stage<P, T> for AgentStage<A, P, T>
+where
+    A: Agent<P, T> + Send + Sync,
+    P: AgentParam + Send + 'static,
+    T: AgentResult + Send + 'static,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run<'a>(&'a self, input: P, _context: &'a mut PipelineContext) -> StageFuture<'a, T> {
+        Box::pin(self.agent.process(input))
+    }
+}
+
+/// Synchronous glue between agents, e.g. mapping one result into the next
+/// agent's param
+pub struct FnStage<F> {
+    name: String,
+    function: F,
+}
+
+pub fn from_fn<I, O, F>(name: &str, function: F) -> FnStage<F>
+where
+    F: Fn(I, &mut PipelineContext) -> Result<O, AgentError> + Send + Sync,
+{
+    FnStage {
+        name: name.to_string(),
+        function,
+    }
+}
+
+impl<I, O, F> Stage<I, O> for FnStage<F>
+where
+    I: Send + 'static,
+    O: Send + 'static,
+    F: Fn(I, &mut PipelineContext) -> Result<O, AgentError> + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run<'a>(&'a self, input: I, context: &'a mut PipelineContext) -> StageFuture<'a, O> {
+        Box::pin(async move { (self.function)(input, context) })
+    }
+}
+
+/// Proposes the classified action to the gate for approval
+pub fn gate(
+    gate: Arc<ActionGate>,
+) -> FnStage<impl Fn(ClassificationResult, &mut PipelineContext) -> Result<Proposal, AgentError>> {
+    from_fn("approve", move |result: ClassificationResult, _: &mut _| {
+        gate.propose(&result)
+            .map_err(|e| AgentError::ProcessingError(e.to_string()))
+    })
+}
@@ -0,0 +1,7 @@
+pub mod plan;
+pub mod plan_store;
+pub mod planner_agent;
+
+pub use plan::{Plan, PlanStep, StepStatus};
+pub use plan_store::PlanStore;
+pub use planner_agent::{PlannerAgent, PlannerParam};
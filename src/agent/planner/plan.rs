@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::agent::{
+    AgentError, AgentResult, Intent, classifier::IntentParam, pipeline::Pipeline,
+    planner::PlanStore,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+impl fmt::Display for StepStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepStatus::Pending => write!(f, "pending"),
+            StepStatus::Running => write!(f, "running"),
+            StepStatus::Done => write!(f, "done"),
+            StepStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// One sub-task, phrased as a standalone request for the pipeline
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PlanStep {
+    pub intent: Intent,
+    pub instruction: String,
+    pub status: StepStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PlanStep {
+    pub fn new(intent: Intent, instruction: &str) -> Self {
+        Self {
+            intent,
+            instruction: instruction.to_string(),
+            status: StepStatus::Pending,
+            error: None,
+        }
+    }
+}
+
+/// Ordered sub-tasks decomposed from one complex utterance
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Plan {
+    pub id: String,
+    pub utterance: String,
+    pub steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    pub fn new(utterance: &str, steps: Vec<PlanStep>) -> Self {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        Self {
+            id: format!("plan-{:x}", millis),
+            utterance: utterance.to_string(),
+            steps,
+        }
+    }
+
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    /// Index of the first step that still has to run
+    pub fn next_step(&self) -> Option<usize> {
+        self.steps
+            .iter()
+            .position(|step| step.status != StepStatus::Done)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| step.status == StepStatus::Done)
+    }
+
+    pub fn has_failed(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|step| step.status == StepStatus::Failed)
+    }
+
+    pub fn mark(&mut self, index: usize, status: StepStatus) {
+        if let Some(step) = self.steps.get_mut(index) {
+            step.status = status;
+            if status != StepStatus::Failed {
+                step.error = None;
+            }
+        }
+    }
+
+    pub fn mark_failed(&mut self, index: usize, error: &str) {
+        if let Some(step) = self.steps.get_mut(index) {
+            step.status = StepStatus::Failed;
+            step.error = Some(error.to_string());
+        }
+    }
+
+    /// Runs the remaining steps in order through `pipeline`, stopping at the
+    /// first failure. Steps already done are skipped, so a loaded plan
+    /// resumes where it stopped. The plan is saved after every change of
+    /// step status when a store is given.
+    pub async fn execute<O>(
+        &mut self,
+        pipeline: &Pipeline<IntentParam, O>,
+        store: Option<&PlanStore>,
+    ) -> Result<Vec<O>, AgentError>
+    where
+        O: Clone + Send + 'static,
+    {
+        let mut outputs = Vec::new();
+        while let Some(index) = self.next_step() {
+            self.mark(index, StepStatus::Running);
+            self.save(store)?;
+
+            let input = IntentParam::new(self.steps[index].instruction.clone());
+            match pipeline.run(input).await {
+                Ok(output) => {
+                    self.mark(index, StepStatus::Done);
+                    self.save(store)?;
+                    outputs.push(output);
+                }
+                Err(e) => {
+                    self.mark_failed(index, &e.to_string());
+                    self.save(store)?;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(outputs)
+    }
+
+    fn save(&self, store: Option<&PlanStore>) -> Result<(), AgentError> {
+        match store {
+            Some(store) => store.save(self),
+            None => Ok(()),
+        }
+    }
+}
+
+impl AgentResult for Plan {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::pipeline::{PipelineContext, from_fn};
+
+    fn plan() -> Plan {
+        Plan::new(
+            "email the team the summary and book a follow-up Friday",
+            vec![
+                PlanStep::new(Intent::SendEmail, "Email the team the summary"),
+                PlanStep::new(Intent::ScheduleMeeting, "Book a follow-up on Friday"),
+            ],
+        )
+    }
+
+    fn echo() -> Pipeline<IntentParam, String> {
+        Pipeline::new().then(from_fn(
+            "echo",
+            |input: IntentParam, _: &mut PipelineContext| {
+                if input.input().contains("Friday") {
+                    Err(AgentError::ProcessingError("calendar offline".to_string()))
+                } else {
+                    Ok(input.input().to_string())
+                }
+            },
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_execute_stops_at_failed_step() {
+        let mut plan = plan();
+
+        let result = plan.execute(&echo(), None).await;
+
+        assert!(result.is_err());
+        assert_eq!(plan.steps[0].status, StepStatus::Done);
+        assert_eq!(plan.steps[1].status, StepStatus::Failed);
+        assert_eq!(
+            plan.steps[1].error.as_deref(),
+            Some("Processing error: calendar offline")
+        );
+        assert_eq!(plan.next_step(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_execute_resumes_after_done_steps() {
+        let mut plan = plan();
+        plan.mark(0, StepStatus::Done);
+        plan.steps[1].instruction = "Book a follow-up".to_string();
+
+        let outputs = plan.execute(&echo(), None).await.unwrap();
+
+        assert_eq!(outputs, vec!["Book a follow-up".to_string()]);
+        assert!(plan.is_complete());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let plan = plan().with_id("plan-1");
+
+        let json = serde_json::to_string(&plan).unwrap();
+
+        assert!(json.contains(r#""intent":"schedule_meeting""#));
+        assert!(json.contains(r#""status":"pending""#));
+        assert_eq!(serde_json::from_str::<Plan>(&json).unwrap(), plan);
+    }
+}
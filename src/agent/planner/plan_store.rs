@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agent::{AgentError, planner::Plan};
+
+/// Plans saved as `<id>.json` in a directory, so a run interrupted by a
+/// restart can be resumed
+pub struct PlanStore {
+    directory: PathBuf,
+}
+
+impl PlanStore {
+    pub fn new(directory: &Path) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+        }
+    }
+
+    pub fn save(&self, plan: &Plan) -> Result<(), AgentError> {
+        fs::create_dir_all(&self.directory).map_err(io_error)?;
+        let json = serde_json::to_string_pretty(plan)
+            .map_err(|e| AgentError::ProcessingError(format!("Plan not saved: {}", e)))?;
+        fs::write(self.path(&plan.id), json).map_err(io_error)
+    }
+
+    pub fn load(&self, id: &str) -> Result<Plan, AgentError> {
+        let content = fs::read_to_string(self.path(id)).map_err(io_error)?;
+        serde_json::from_str(&content)
+            .map_err(|e| AgentError::ParseError(format!("Plan '{}' is corrupt: {}", id, e)))
+    }
+
+    /// Plans that still have steps to run
+    pub fn unfinished(&self) -> Result<Vec<Plan>, AgentError> {
+        let Ok(entries) = fs::read_dir(&self.directory) else {
+            return Ok(Vec::new());
+        };
+        let mut plans = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(id) = path.file_stem().and_then(|stem| stem.to_str())
+            {
+                let plan = self.load(id)?;
+                if !plan.is_complete() {
+                    plans.push(plan);
+                }
+            }
+        }
+        plans.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(plans)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), AgentError> {
+        fs::remove_file(self.path(id)).map_err(io_error)
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        let file_name: String = id
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.directory.join(format!("{}.json", file_name))
+    }
+}
+
+fn io_error(e: std::io::Error) -> AgentError {
+    AgentError::ProcessingError(format!("Plan store: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Intent;
+    use crate::agent::planner::{PlanStep, StepStatus};
+
+    #[test]
+    fn test_save_load_and_unfinished() {
+        let directory = std::env::temp_dir().join(format!("plans-{}", std::process::id()));
+        let store = PlanStore::new(&directory);
+        let mut done = Plan::new("a", vec![PlanStep::new(Intent::SendEmail, "a")]).with_id("p1");
+        done.mark(0, StepStatus::Done);
+        let open = Plan::new("b", vec![PlanStep::new(Intent::SendEmail, "b")]).with_id("p2");
+
+        store.save(&done).unwrap();
+        store.save(&open).unwrap();
+
+        assert_eq!(store.load("p1").unwrap(), done);
+        assert_eq!(store.unfinished().unwrap(), vec![open]);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel, Intent,
+        agent::AgentParam,
+        chat_model::{model_or, ollama_for},
+        planner::{Plan, PlanStep},
+    },
+    config::Config,
+    infra::ollama::{OutputFormat, parse_json_content},
+};
+
+/// Decomposes an utterance asking for several things into an ordered plan
+/// of single-intent steps the pipeline can run one by one
+pub struct PlannerAgent {
+    model: Option<String>,
+    output_format: OutputFormat,
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
+
+impl Default for PlannerAgent {
+    fn default() -> Self {
+        Self {
+            model: None,
+            output_format: Config::get().structured_output.format_for("planner"),
+            chat_model: None,
+        }
+    }
+}
+
+impl PlannerAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_model(model: &str) -> Self {
+        Self {
+            model: Some(model.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Sends requests to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
+    }
+
+    /// How strictly the model's JSON answer is constrained
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+pub struct PlannerParam {
+    utterance: String,
+}
+
+impl PlannerParam {
+    pub fn new(utterance: String) -> Self {
+        Self { utterance }
+    }
+}
+
+impl AgentParam for PlannerParam {}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ModelPlan {
+    steps: Vec<ModelStep>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ModelStep {
+    intent: String,
+    instruction: String,
+}
+
+impl Agent<PlannerParam, Plan> for PlannerAgent {
+    async fn process(&self, input: PlannerParam) -> Result<Plan, AgentError> {
+        let client = model_or(&self.chat_model, || ollama_for(self.model.as_deref()));
+        let answer = client
+            .ask_constrained(
+                &build_prompt(&input.utterance),
+                &self.output_format.constraint_for::<ModelPlan>(),
+            )
+            .await?;
+        let model_plan = parse_json_content::<ModelPlan>(&answer)?;
+
+        to_plan(&input.utterance, model_plan)
+    }
+}
+
+fn to_plan(utterance: &str, model_plan: ModelPlan) -> Result<Plan, AgentError> {
+    let steps: Vec<PlanStep> = model_plan
+        .steps
+        .into_iter()
+        .filter(|step| !step.instruction.trim().is_empty())
+        .map(|step| PlanStep::new(Intent::from_str(&step.intent), step.instruction.trim()))
+        .collect();
+    if steps.is_empty() {
+        return Err(AgentError::ParseError(
+            "Planning failed: the plan has no steps".to_string(),
+        ));
+    }
+    Ok(Plan::new(utterance, steps))
+}
+
+fn build_prompt(utterance: &str) -> String {
+    format!(
+        "{}{}{}{}",
+        INSTRUCTION, OUTPUT_FORMAT, REQUEST_LABEL, utterance
+    )
+}
+
+const INSTRUCTION: &str = "Split the user's request into the smallest ordered list of tasks that each do exactly one thing. Each task is one of: send_email, schedule_meeting, no_action. Rewrite each task as a standalone request in the user's language, keeping every name, date and detail it needs.";
+const OUTPUT_FORMAT: &str =
+    " Answer only with JSON: {\"steps\": [{\"intent\": \"send_email\", \"instruction\": \"...\"}]}";
+const REQUEST_LABEL: &str = "\nRequest: ";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::planner::StepStatus;
+
+    #[test]
+    fn test_model_plan_becomes_pending_steps() {
+        let model_plan: ModelPlan = parse_json_content(
+            r#"```json
+{"steps": [
+  {"intent": "send_email", "instruction": "Email the team the summary of yesterday's thread"},
+  {"intent": "schedule_meeting", "instruction": " Book a follow-up on Friday "},
+  {"intent": "no_action", "instruction": ""}
+]}
+```"#,
+        )
+        .unwrap();
+
+        let plan = to_plan("email the team ... and book a follow-up Friday", model_plan).unwrap();
+
+        assert_eq!(plan.steps.len(), 2);
+        assert_eq!(plan.steps[1].intent, Intent::ScheduleMeeting);
+        assert_eq!(plan.steps[1].instruction, "Book a follow-up on Friday");
+        assert!(plan.steps.iter().all(|s| s.status == StepStatus::Pending));
+    }
+
+    #[test]
+    fn test_empty_plan_is_a_parse_error() {
+        let result = to_plan("hi", ModelPlan { steps: vec![] });
+
+        assert!(matches!(result, Err(AgentError::ParseError(_))));
+    }
+
+    #[test]
+    fn test_prompt_contains_request() {
+        assert!(build_prompt("do two things").ends_with("Request: do two things"));
+    }
+}
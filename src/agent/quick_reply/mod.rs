@@ -0,0 +1,5 @@
+pub mod quick_reply_library;
+pub mod quick_reply_stage;
+
+pub use quick_reply_library::{QuickReply, QuickReplyLibrary, QuickReplyTemplate};
+pub use quick_reply_stage::{QuickReplyStage, quick_replies};
@@ -0,0 +1,195 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::i18n::Locale;
+use crate::prompt::{PromptError, PromptTemplate};
+
+pub const DECLINE_MEETING: &str = "decline_meeting";
+pub const ACKNOWLEDGE_RECEIPT: &str = "acknowledge_receipt";
+pub const REQUEST_MORE_INFO: &str = "request_more_info";
+
+/// A canned reply in one language. Subject and body are templates with
+/// `{{ recipient }}` and `{{ note }}` (the user's own words, may be empty).
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickReplyTemplate {
+    pub name: String,
+    pub locale: Locale,
+    subject: PromptTemplate,
+    body: PromptTemplate,
+}
+
+impl QuickReplyTemplate {
+    pub fn new(name: &str, locale: Locale, subject: &str, body: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            locale,
+            subject: PromptTemplate::new(name, subject),
+            body: PromptTemplate::new(name, body),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ReplyContext<'a> {
+    recipient: &'a str,
+    note: &'a str,
+}
+
+/// A rendered quick reply, ready to send without calling the model
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickReply {
+    pub template: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Named reply templates for frequent answers
+#[derive(Debug, Clone, Default)]
+pub struct QuickReplyLibrary {
+    templates: BTreeMap<(String, Locale), QuickReplyTemplate>,
+}
+
+impl QuickReplyLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decline a meeting, acknowledge receipt and ask for more
+    /// information, in English and Portuguese
+    pub fn builtin() -> Self {
+        let mut library = Self::new();
+        for template in builtin_templates() {
+            library.register(template);
+        }
+        library
+    }
+
+    /// Adds a template, replacing one with the same name and locale
+    pub fn register(&mut self, template: QuickReplyTemplate) {
+        self.templates
+            .insert((template.name.clone(), template.locale), template);
+    }
+
+    /// Template names, each listed once
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .templates
+            .keys()
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.dedup();
+        names
+    }
+
+    /// Template `name` in `locale`, falling back to English
+    pub fn get(&self, name: &str, locale: Locale) -> Option<&QuickReplyTemplate> {
+        self.templates
+            .get(&(name.to_string(), locale))
+            .or_else(|| self.templates.get(&(name.to_string(), Locale::En)))
+    }
+
+    /// Renders `name`; `None` if the library has no such template
+    pub fn render(
+        &self,
+        name: &str,
+        locale: Locale,
+        recipient: &str,
+        note: &str,
+    ) -> Option<Result<QuickReply, PromptError>> {
+        let template = self.get(name, locale)?;
+        let context = ReplyContext { recipient, note };
+        let rendered = template.subject.render(&context).and_then(|subject| {
+            Ok(QuickReply {
+                template: name.to_string(),
+                subject: subject.trim().to_string(),
+                body: template.body.render(&context)?.trim().to_string(),
+            })
+        });
+        Some(rendered)
+    }
+}
+
+fn builtin_templates() -> Vec<QuickReplyTemplate> {
+    vec![
+        QuickReplyTemplate::new(
+            DECLINE_MEETING,
+            Locale::En,
+            "Unable to attend",
+            "Hi {{ recipient }},\n\nThank you for the invitation. Unfortunately I won't be able to attend.{% if note %} {{ note }}{% endif %}\n\nBest regards",
+        ),
+        QuickReplyTemplate::new(
+            DECLINE_MEETING,
+            Locale::PtBr,
+            "Não poderei participar",
+            "Olá, {{ recipient }},\n\nObrigado pelo convite. Infelizmente não poderei participar.{% if note %} {{ note }}{% endif %}\n\nAtenciosamente",
+        ),
+        QuickReplyTemplate::new(
+            ACKNOWLEDGE_RECEIPT,
+            Locale::En,
+            "Received",
+            "Hi {{ recipient }},\n\nThanks, I received your message and will get back to you soon.{% if note %} {{ note }}{% endif %}\n\nBest regards",
+        ),
+        QuickReplyTemplate::new(
+            ACKNOWLEDGE_RECEIPT,
+            Locale::PtBr,
+            "Recebido",
+            "Olá, {{ recipient }},\n\nObrigado, recebi sua mensagem e retorno em breve.{% if note %} {{ note }}{% endif %}\n\nAtenciosamente",
+        ),
+        QuickReplyTemplate::new(
+            REQUEST_MORE_INFO,
+            Locale::En,
+            "More information needed",
+            "Hi {{ recipient }},\n\nThanks for reaching out. Could you send me a few more details so I can help?{% if note %} {{ note }}{% endif %}\n\nBest regards",
+        ),
+        QuickReplyTemplate::new(
+            REQUEST_MORE_INFO,
+            Locale::PtBr,
+            "Preciso de mais informações",
+            "Olá, {{ recipient }},\n\nObrigado pelo contato. Poderia me enviar mais alguns detalhes para que eu possa ajudar?{% if note %} {{ note }}{% endif %}\n\nAtenciosamente",
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_names() {
+        assert_eq!(
+            QuickReplyLibrary::builtin().names(),
+            vec![ACKNOWLEDGE_RECEIPT, DECLINE_MEETING, REQUEST_MORE_INFO]
+        );
+    }
+
+    #[test]
+    fn test_render_with_note() {
+        let reply = QuickReplyLibrary::builtin()
+            .render(DECLINE_MEETING, Locale::PtBr, "Eva", "Podemos remarcar?")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(reply.subject, "Não poderei participar");
+        assert!(reply.body.starts_with("Olá, Eva,"));
+        assert!(reply.body.contains("participar. Podemos remarcar?"));
+    }
+
+    #[test]
+    fn test_falls_back_to_english() {
+        let mut library = QuickReplyLibrary::new();
+        library.register(QuickReplyTemplate::new(
+            "thanks",
+            Locale::En,
+            "Thanks",
+            "Thanks, {{ recipient }}!",
+        ));
+
+        let reply = library
+            .render("thanks", Locale::PtBr, "Eva", "")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(reply.body, "Thanks, Eva!");
+        assert!(library.render("missing", Locale::En, "Eva", "").is_none());
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        AgentError, ClassificationResult, Intent,
+        classifier::{IntentDetails, Params, SendEmailDetails},
+        pipeline::{PipelineContext, Stage, StageFuture},
+        quick_reply::QuickReplyLibrary,
+    },
+    i18n::Locale,
+};
+
+/// Stage that turns a `quick_reply` result into a ready `send_email` one
+/// from the library, without calling the model. Other intents pass
+/// through untouched.
+pub struct QuickReplyStage {
+    library: Arc<QuickReplyLibrary>,
+    locale: Locale,
+}
+
+pub fn quick_replies(library: Arc<QuickReplyLibrary>) -> QuickReplyStage {
+    QuickReplyStage {
+        library,
+        locale: Locale::configured(),
+    }
+}
+
+impl QuickReplyStage {
+    /// Language of the replies; config.toml's by default
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    fn apply(&self, result: ClassificationResult) -> Result<ClassificationResult, AgentError> {
+        if result.intent != Intent::QuickReply {
+            return Ok(result);
+        }
+        let name = result.params.template().unwrap_or_default();
+        let recipient = result.params.recipient().unwrap_or_default();
+        let reply = self
+            .library
+            .render(
+                name,
+                self.locale,
+                recipient,
+                result.params.message().unwrap_or_default(),
+            )
+            .ok_or_else(|| {
+                AgentError::ProcessingError(format!("Unknown quick reply template '{}'", name))
+            })?
+            .map_err(|e| AgentError::ProcessingError(e.to_string()))?;
+
+        let params = Params::with_values(recipient.to_string(), reply.body.clone())
+            .with_template(&reply.template);
+        let mut email = ClassificationResult::new(Intent::SendEmail, params).with_details(
+            IntentDetails::SendEmail(SendEmailDetails {
+                recipient: recipient.to_string(),
+                subject: reply.subject,
+                message: reply.body,
+            }),
+        );
+        // with_details normalizes the params and drops the template name
+        email.params = email.params.with_template(&reply.template);
+        email.prompt_version = result.prompt_version;
+        email.sentiment = result.sentiment;
+        Ok(email)
+    }
+}
+
+impl Stage<ClassificationResult, ClassificationResult> for QuickReplyStage {
+    fn name(&self) -> &str {
+        "quick_reply"
+    }
+
+    fn run<'a>(
+        &'a self,
+        input: ClassificationResult,
+        _context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ClassificationResult> {
+        Box::pin(async move { self.apply(input) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::pipeline::Pipeline;
+
+    fn pipeline() -> Pipeline<ClassificationResult, ClassificationResult> {
+        Pipeline::new()
+            .then(quick_replies(Arc::new(QuickReplyLibrary::builtin())).with_locale(Locale::En))
+    }
+
+    #[tokio::test]
+    async fn test_quick_reply_becomes_email() {
+        let result = ClassificationResult::new(
+            Intent::QuickReply,
+            Params::new(Some("eva@company.com".to_string()), None)
+                .with_template("acknowledge_receipt"),
+        );
+
+        let email = pipeline().run(result).await.unwrap();
+
+        assert_eq!(email.intent, Intent::SendEmail);
+        assert_eq!(email.params.template(), Some("acknowledge_receipt"));
+        assert!(
+            email
+                .params
+                .message()
+                .unwrap()
+                .contains("I received your message")
+        );
+        let Some(IntentDetails::SendEmail(details)) = email.details else {
+            panic!("expected email details");
+        };
+        assert_eq!(details.subject, "Received");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_template_fails() {
+        let result = ClassificationResult::new(
+            Intent::QuickReply,
+            Params::new(Some("Eva".to_string()), None).with_template("haiku"),
+        );
+
+        let error = pipeline().run(result).await.unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Processing error: Unknown quick reply template 'haiku'"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod sentiment_agent;
+pub mod sentiment_assessment;
+
+pub use sentiment_agent::{SentimentAgent, SentimentParam};
+pub use sentiment_assessment::{Sentiment, SentimentAssessment};
@@ -0,0 +1,65 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::agent::AgentResult;
+
+/// Overall tone of an incoming email
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Sentiment {
+    /// Complaint, frustration or hostility
+    Angry,
+    /// Asks for something right away
+    Urgent,
+    Friendly,
+    #[default]
+    Neutral,
+}
+
+impl fmt::Display for Sentiment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Sentiment::Angry => "angry",
+            Sentiment::Urgent => "urgent",
+            Sentiment::Friendly => "friendly",
+            Sentiment::Neutral => "neutral",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct SentimentAssessment {
+    pub sentiment: Sentiment,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl SentimentAssessment {
+    pub fn new(sentiment: Sentiment, reason: String) -> Self {
+        Self { sentiment, reason }
+    }
+
+    /// Angry mail is never answered automatically
+    pub fn needs_human(&self) -> bool {
+        self.sentiment == Sentiment::Angry
+    }
+}
+
+impl AgentResult for SentimentAssessment {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialization() {
+        let assessment: SentimentAssessment =
+            serde_json::from_str(r#"{"sentiment": "angry", "reason": "third complaint"}"#).unwrap();
+
+        assert_eq!(assessment.sentiment, Sentiment::Angry);
+        assert!(assessment.needs_human());
+        assert!(!SentimentAssessment::new(Sentiment::Urgent, String::new()).needs_human());
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use crate::{
+    agent::{
+        Agent,
+        summarizer::{SummarizerAgent, SummarizerParam, Summary, SummaryLength},
+    },
+    error::Result,
+    infra::input::DocumentConverter,
+    safety::attachment_guard::mime_type_for,
+};
+
+const CONTEXT_LABEL: &str = "Attachments:";
+
+/// What one attached document says
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachmentSummary {
+    pub name: String,
+    pub mime_type: String,
+    pub summary: Summary,
+}
+
+impl AttachmentSummary {
+    /// One line of the prompt context, e.g. `- report.pdf: Q3 results —
+    /// Revenue grew 8%. Key points: ...`
+    pub fn line(&self) -> String {
+        let mut line = format!("- {}: ", self.name);
+        if !self.summary.headline.is_empty() {
+            line.push_str(&format!("{} — ", self.summary.headline));
+        }
+        line.push_str(self.summary.summary.trim());
+        if !self.summary.key_points.is_empty() {
+            line.push_str(&format!(
+                " Key points: {}.",
+                self.summary.key_points.join("; ")
+            ));
+        }
+        line
+    }
+}
+
+/// Block describing the attachments, appended to a classification or reply
+/// prompt; `None` when there is nothing to add
+pub fn attachment_context(summaries: &[AttachmentSummary]) -> Option<String> {
+    if summaries.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = summaries.iter().map(AttachmentSummary::line).collect();
+    Some(format!("{}\n{}", CONTEXT_LABEL, lines.join("\n")))
+}
+
+/// Extracts the text of document attachments and summarizes it, so replies
+/// and classifications can take their content into account
+#[derive(Default)]
+pub struct AttachmentSummarizer {
+    converter: DocumentConverter,
+    summarizer: SummarizerAgent,
+}
+
+impl AttachmentSummarizer {
+    pub fn new(converter: DocumentConverter) -> Self {
+        Self {
+            converter,
+            summarizer: SummarizerAgent::new(),
+        }
+    }
+
+    pub fn with_summarizer(mut self, summarizer: SummarizerAgent) -> Self {
+        self.summarizer = summarizer;
+        self
+    }
+
+    pub fn converter(&self) -> &DocumentConverter {
+        &self.converter
+    }
+
+    pub async fn summarize(&self, path: &Path) -> Result<AttachmentSummary> {
+        let text = self.converter.extract_text(path).await?;
+        let summary = self
+            .summarizer
+            .process(SummarizerParam::new(text).with_length(SummaryLength::Short))
+            .await?;
+        Ok(AttachmentSummary {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string()),
+            mime_type: mime_type_for(path).to_string(),
+            summary,
+        })
+    }
+
+    /// Summaries of the attachments whose text could be read. Images,
+    /// unsupported types and failed conversions are left out: the context
+    /// is a help, not a requirement.
+    pub async fn summarize_all<P: AsRef<Path>>(&self, paths: &[P]) -> Vec<AttachmentSummary> {
+        let mut summaries = Vec::new();
+        for path in paths.iter().map(AsRef::as_ref) {
+            if !self.converter.supports(path) {
+                continue;
+            }
+            if let Ok(summary) = self.summarize(path).await {
+                summaries.push(summary);
+            }
+        }
+        summaries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::infra::input::InputError;
+
+    fn summary(name: &str, headline: &str, key_points: &[&str]) -> AttachmentSummary {
+        AttachmentSummary {
+            name: name.to_string(),
+            mime_type: mime_type_for(Path::new(name)).to_string(),
+            summary: Summary::new(
+                headline.to_string(),
+                "Revenue grew 8% in Q3.".to_string(),
+                key_points.iter().map(|p| p.to_string()).collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_context_lists_every_attachment() {
+        let context = attachment_context(&[
+            summary("report.pdf", "Q3 results", &["Approve budget by Friday"]),
+            summary("notes.txt", "", &[]),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            context,
+            "Attachments:\n\
+             - report.pdf: Q3 results — Revenue grew 8% in Q3. Key points: Approve budget by Friday.\n\
+             - notes.txt: Revenue grew 8% in Q3."
+        );
+        assert_eq!(attachment_context(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_attachments_are_skipped() {
+        let summarizer = AttachmentSummarizer::new(DocumentConverter::new());
+
+        let result = summarizer.summarize(Path::new("missing.txt")).await;
+
+        assert!(matches!(result, Err(Error::Input(InputError::NotFound(_)))));
+        assert!(
+            summarizer
+                .summarize_all(&["photo.png", "missing.txt", "report.pdf"])
+                .await
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod attachment_summary;
+pub mod summarizer_agent;
+pub mod summary;
+
+pub use attachment_summary::{AttachmentSummarizer, AttachmentSummary, attachment_context};
+pub use summarizer_agent::{SummarizerAgent, SummarizerParam};
+pub use summary::{Summary, SummaryLength, SummaryStyle};
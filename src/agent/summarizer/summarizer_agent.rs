@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel,
+        agent::AgentParam,
+        chat_model::{model_or, ollama_for},
+        summarizer::{Summary, SummaryLength, SummaryStyle},
+    },
+    i18n::Locale,
+    infra::ollama::parse_json_content,
+    prompt::{Tokenizer, tokenizer_for},
+};
+
+/// Longest input sent to the model; the rest of the text is dropped
+const MAX_INPUT_TOKENS: usize = 3000;
+
+/// Summarizes any text — threads, documents, notes — into a headline, a
+/// summary and key points
+#[derive(Default)]
+pub struct SummarizerAgent {
+    model: Option<String>,
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
+
+impl SummarizerAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_model(model: &str) -> Self {
+        Self {
+            model: Some(model.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Sends requests to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
+    }
+}
+
+pub struct SummarizerParam {
+    text: String,
+    length: SummaryLength,
+    style: SummaryStyle,
+    language: Option<Locale>,
+}
+
+impl SummarizerParam {
+    pub fn new(text: String) -> Self {
+        Self {
+            text,
+            length: SummaryLength::default(),
+            style: SummaryStyle::default(),
+            language: None,
+        }
+    }
+
+    pub fn with_length(mut self, length: SummaryLength) -> Self {
+        self.length = length;
+        self
+    }
+
+    pub fn with_style(mut self, style: SummaryStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Language to write the summary in; the text's own if unset
+    pub fn with_language(mut self, language: Locale) -> Self {
+        self.language = Some(language);
+        self
+    }
+}
+
+impl AgentParam for SummarizerParam {}
+
+impl Agent<SummarizerParam, Summary> for SummarizerAgent {
+    async fn process(&self, input: SummarizerParam) -> Result<Summary, AgentError> {
+        if input.text.trim().is_empty() {
+            return Ok(Summary::default());
+        }
+
+        let ollama = ollama_for(self.model.as_deref());
+        let tokenizer = tokenizer_for(ollama.model());
+        let answer = model_or(&self.chat_model, || ollama)
+            .ask(&build_prompt(&input, tokenizer.as_ref()))
+            .await?;
+
+        let summary = parse_json_content::<Summary>(&answer)?;
+        if summary.is_empty() {
+            return Err(AgentError::ParseError(
+                "Summarization failed: empty summary".to_string(),
+            ));
+        }
+        Ok(summary)
+    }
+}
+
+fn build_prompt(input: &SummarizerParam, tokenizer: &dyn Tokenizer) -> String {
+    let language = match input.language {
+        Some(locale) => format!("Write in {}.", locale.language_name()),
+        None => "Write in the language of the text.".to_string(),
+    };
+    format!(
+        "{} {} {} {} List at most {} key points.{}{}{}",
+        INSTRUCTION,
+        input.length.instruction(),
+        input.style.instruction(),
+        language,
+        input.length.max_key_points(),
+        OUTPUT_FORMAT,
+        TEXT_LABEL,
+        tokenizer.truncate(&input.text, MAX_INPUT_TOKENS)
+    )
+}
+
+const INSTRUCTION: &str = "Summarize the following text. Keep names, dates and numbers exactly as they are and do not add anything that is not in the text.";
+const OUTPUT_FORMAT: &str = " Answer only with JSON: {\"headline\": \"a few words\", \"summary\": \"the summary\", \"key_points\": [\"decision, request or deadline\"]}";
+const TEXT_LABEL: &str = "\nText:\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt::CharEstimate;
+
+    #[test]
+    fn test_prompt_carries_length_and_style() {
+        let prompt = build_prompt(
+            &SummarizerParam::new("Eva moved the review to Friday.".to_string())
+                .with_length(SummaryLength::Short)
+                .with_style(SummaryStyle::Bullets)
+                .with_language(Locale::PtBr),
+            &CharEstimate,
+        );
+
+        assert!(prompt.starts_with(INSTRUCTION));
+        assert!(prompt.contains("one or two sentences"));
+        assert!(prompt.contains("bulleted list"));
+        assert!(prompt.contains("Write in Brazilian Portuguese."));
+        assert!(prompt.contains("at most 3 key points"));
+        assert!(prompt.ends_with("Text:\nEva moved the review to Friday."));
+    }
+
+    #[test]
+    fn test_long_input_is_truncated() {
+        let prompt = build_prompt(&SummarizerParam::new("word ".repeat(10_000)), &CharEstimate);
+
+        assert!(prompt.len() < 10_000 * 5);
+    }
+
+    #[tokio::test]
+    async fn test_empty_text_skips_the_model() {
+        let summary = SummarizerAgent::new()
+            .process(SummarizerParam::new("  ".to_string()))
+            .await
+            .unwrap();
+
+        assert!(summary.is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentResult;
+
+/// How much of the text a summary keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryLength {
+    /// One or two sentences
+    Short,
+    #[default]
+    Medium,
+    Long,
+}
+
+impl SummaryLength {
+    pub fn instruction(&self) -> &'static str {
+        match self {
+            SummaryLength::Short => "Keep the summary to one or two sentences.",
+            SummaryLength::Medium => "Keep the summary to one short paragraph.",
+            SummaryLength::Long => {
+                "Write a detailed summary of up to three paragraphs that leaves out no decision or request."
+            }
+        }
+    }
+
+    /// Most key points asked for
+    pub fn max_key_points(&self) -> usize {
+        match self {
+            SummaryLength::Short => 3,
+            SummaryLength::Medium => 5,
+            SummaryLength::Long => 10,
+        }
+    }
+}
+
+/// How the summary text is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryStyle {
+    #[default]
+    Prose,
+    /// One `- ` bullet per line
+    Bullets,
+}
+
+impl SummaryStyle {
+    pub fn instruction(&self) -> &'static str {
+        match self {
+            SummaryStyle::Prose => "Write the summary as plain prose.",
+            SummaryStyle::Bullets => {
+                "Write the summary as a bulleted list, one \"- \" bullet per line."
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Summary {
+    /// Subject-line sized gist
+    #[serde(default)]
+    pub headline: String,
+    pub summary: String,
+    /// Decisions, requests and deadlines worth acting on
+    #[serde(default)]
+    pub key_points: Vec<String>,
+}
+
+impl Summary {
+    pub fn new(headline: String, summary: String, key_points: Vec<String>) -> Self {
+        Self {
+            headline,
+            summary,
+            key_points,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.summary.trim().is_empty()
+    }
+}
+
+impl AgentResult for Summary {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialization_defaults() {
+        let summary: Summary =
+            serde_json::from_str(r#"{"summary": "Eva moved the review to Friday."}"#).unwrap();
+
+        assert_eq!(summary.headline, "");
+        assert!(summary.key_points.is_empty());
+        assert!(!summary.is_empty());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod tone_assessment;
+pub mod tone_check_agent;
+
+pub use tone_assessment::ToneAssessment;
+pub use tone_check_agent::{ToneCheckAgent, ToneCheckParam};
@@ -0,0 +1,35 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentResult;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct ToneAssessment {
+    pub matches: bool,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl ToneAssessment {
+    pub fn new(matches: bool, reason: String) -> Self {
+        Self { matches, reason }
+    }
+}
+
+impl AgentResult for ToneAssessment {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialization() {
+        let assessment: ToneAssessment =
+            serde_json::from_str(r#"{"matches": false, "reason": "uses tu"}"#).unwrap();
+
+        assert_eq!(
+            assessment,
+            ToneAssessment::new(false, "uses tu".to_string())
+        );
+    }
+}
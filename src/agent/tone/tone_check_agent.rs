@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel, agent::AgentParam, chat_model::model_or, tone::ToneAssessment,
+    },
+    config::Config,
+    i18n::{Locale, Tone},
+    infra::ollama::{OllamaClient, OutputFormat, parse_json_content},
+};
+
+/// Checks after generation that a draft is written in the requested tone
+pub struct ToneCheckAgent {
+    output_format: OutputFormat,
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
+
+impl Default for ToneCheckAgent {
+    fn default() -> Self {
+        Self {
+            output_format: Config::get().structured_output.format_for("tone_check"),
+            chat_model: None,
+        }
+    }
+}
+
+impl ToneCheckAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends requests to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
+    }
+
+    /// How strictly the model's JSON answer is constrained
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+pub struct ToneCheckParam {
+    body: String,
+    tone: Tone,
+    locale: Locale,
+}
+
+impl ToneCheckParam {
+    pub fn new(body: String, tone: Tone, locale: Locale) -> Self {
+        Self { body, tone, locale }
+    }
+}
+
+impl AgentParam for ToneCheckParam {}
+
+impl Agent<ToneCheckParam, ToneAssessment> for ToneCheckAgent {
+    async fn process(&self, input: ToneCheckParam) -> Result<ToneAssessment, AgentError> {
+        let prompt = build_prompt(&input);
+
+        let answer = model_or(&self.chat_model, OllamaClient::new)
+            .ask_constrained(
+                &prompt,
+                &self.output_format.constraint_for::<ToneAssessment>(),
+            )
+            .await?;
+
+        parse_json_content::<ToneAssessment>(&answer)
+    }
+}
+
+fn build_prompt(input: &ToneCheckParam) -> String {
+    format!(
+        "{} The expected tone is {}: {}{}{}{}",
+        INSTRUCTION,
+        input.tone,
+        input.tone.guidance(input.locale),
+        OUTPUT_FORMAT,
+        EMAIL_LABEL,
+        input.body
+    )
+}
+
+const INSTRUCTION: &str = "Review the following email draft and decide whether it is written in the expected tone, including how it addresses the recipient.";
+const OUTPUT_FORMAT: &str =
+    " Answer only with JSON: {\"matches\": true|false, \"reason\": \"short explanation\"}";
+const EMAIL_LABEL: &str = "\nEmail:\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_carries_tone_guidance() {
+        let prompt = build_prompt(&ToneCheckParam::new(
+            "Oi Eva, tudo bem?".to_string(),
+            Tone::Formal,
+            Locale::PtBr,
+        ));
+
+        assert!(prompt.starts_with(INSTRUCTION));
+        assert!(prompt.contains("The expected tone is formal: Use a formal register"));
+        assert!(prompt.ends_with("Email:\nOi Eva, tudo bem?"));
+    }
+}
@@ -0,0 +1,217 @@
+use chrono::{Duration, NaiveDateTime};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::agent::tools::{Tool, ToolError, ToolFuture};
+use crate::i18n::BusinessCalendar;
+
+const DATE_TIME_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"];
+
+/// Candidate starts tried before giving up on suggesting a slot
+const MAX_SLOT_ATTEMPTS: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BusySlot {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub title: String,
+}
+
+/// Answers whether a time range is free in the user's calendar
+pub struct CheckCalendarTool {
+    busy: Vec<BusySlot>,
+    business_calendar: BusinessCalendar,
+}
+
+impl CheckCalendarTool {
+    /// Uses the business calendar from config.toml
+    pub fn new(busy: Vec<BusySlot>) -> Self {
+        Self {
+            busy,
+            business_calendar: BusinessCalendar::configured(),
+        }
+    }
+
+    pub fn with_business_calendar(mut self, calendar: BusinessCalendar) -> Self {
+        self.business_calendar = calendar;
+        self
+    }
+
+    /// Why nobody would attend a meeting in this range, if it falls on a
+    /// day off or outside working hours
+    pub fn closed(&self, start: NaiveDateTime, end: NaiveDateTime) -> Option<String> {
+        let calendar = &self.business_calendar;
+        if let Some(day_off) = calendar.day_off(start.date()) {
+            return Some(day_off.to_string());
+        }
+        if !calendar.is_working_time(start) || end > calendar.end_of_working_day(start) {
+            return Some("outside working hours".to_string());
+        }
+        None
+    }
+
+    /// Earliest start at or after `from` that is free, on a business day
+    /// and within working hours
+    pub fn suggest_start(&self, from: NaiveDateTime, duration: Duration) -> Option<NaiveDateTime> {
+        let calendar = &self.business_calendar;
+        let mut start = from;
+        for _ in 0..MAX_SLOT_ATTEMPTS {
+            start = calendar.next_working_time(start);
+            let day_end = calendar.end_of_working_day(start);
+            if start + duration > day_end {
+                start = day_end;
+                continue;
+            }
+            match self
+                .conflicts(start, start + duration)
+                .iter()
+                .map(|slot| slot.end)
+                .max()
+            {
+                Some(busy_until) => start = busy_until,
+                None => return Some(start),
+            }
+        }
+        None
+    }
+
+    pub fn conflicts(&self, start: NaiveDateTime, end: NaiveDateTime) -> Vec<&BusySlot> {
+        self.busy
+            .iter()
+            .filter(|slot| slot.start < end && start < slot.end)
+            .collect()
+    }
+}
+
+fn parse_date_time(value: &str) -> Option<NaiveDateTime> {
+    DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+}
+
+impl Tool for CheckCalendarTool {
+    fn name(&self) -> &str {
+        "check_calendar"
+    }
+
+    fn description(&self) -> &str {
+        "Checks whether the user is free for a meeting."
+    }
+
+    fn arguments_hint(&self) -> &str {
+        r#"{"start": "2024-06-03T15:00", "duration_minutes": 30}"#
+    }
+
+    fn call<'a>(&'a self, arguments: &'a Value) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let start = arguments
+                .get("start")
+                .and_then(Value::as_str)
+                .and_then(parse_date_time)
+                .ok_or_else(|| {
+                    ToolError::InvalidArguments("'start' must be like 2024-06-03T15:00".to_string())
+                })?;
+            let minutes = arguments
+                .get("duration_minutes")
+                .and_then(Value::as_i64)
+                .unwrap_or(30);
+            let end = start + Duration::minutes(minutes);
+
+            let conflicts: Vec<Value> = self
+                .conflicts(start, end)
+                .iter()
+                .map(|slot| {
+                    json!({
+                        "title": slot.title,
+                        "start": slot.start.format("%Y-%m-%dT%H:%M").to_string(),
+                        "end": slot.end.format("%Y-%m-%dT%H:%M").to_string(),
+                    })
+                })
+                .collect();
+
+            let closed = self.closed(start, end);
+            let available = conflicts.is_empty() && closed.is_none();
+            let mut result = json!({"available": available, "conflicts": conflicts});
+            if let Some(reason) = closed {
+                result["closed"] = json!(reason);
+            }
+            if !available && let Some(suggested) = self.suggest_start(start, end - start) {
+                result["suggested_start"] = json!(suggested.format("%Y-%m-%dT%H:%M").to_string());
+            }
+            Ok(result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Region;
+
+    fn tool() -> CheckCalendarTool {
+        CheckCalendarTool::new(vec![BusySlot {
+            start: parse_date_time("2024-06-03T14:00").unwrap(),
+            end: parse_date_time("2024-06-03T15:00").unwrap(),
+            title: "1:1".to_string(),
+        }])
+        .with_business_calendar(BusinessCalendar::new(Some(Region::Us)))
+    }
+
+    #[tokio::test]
+    async fn test_free_slot() {
+        let result = tool()
+            .call(&json!({"start": "2024-06-03T15:00", "duration_minutes": 30}))
+            .await
+            .unwrap();
+
+        assert_eq!(result["available"], true);
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_slot() {
+        let result = tool()
+            .call(&json!({"start": "2024-06-03 14:30"}))
+            .await
+            .unwrap();
+
+        assert_eq!(result["available"], false);
+        assert_eq!(result["conflicts"][0]["title"], "1:1");
+        assert_eq!(result["suggested_start"], "2024-06-03T15:00");
+    }
+
+    #[tokio::test]
+    async fn test_holiday_is_never_free() {
+        // Independence Day 2024 is a Thursday
+        let result = tool()
+            .call(&json!({"start": "2024-07-04T10:00", "duration_minutes": 60}))
+            .await
+            .unwrap();
+
+        assert_eq!(result["available"], false);
+        assert_eq!(result["closed"], "Independence Day");
+        assert_eq!(result["suggested_start"], "2024-07-05T09:00");
+    }
+
+    #[test]
+    fn test_suggestion_moves_past_end_of_day() {
+        let tool = tool();
+
+        let suggested = tool
+            .suggest_start(
+                parse_date_time("2024-05-31T17:30").unwrap(),
+                Duration::minutes(60),
+            )
+            .unwrap();
+
+        // Friday evening → Monday morning
+        assert_eq!(suggested, parse_date_time("2024-06-03T09:00").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_start() {
+        assert!(matches!(
+            tool().call(&json!({"start": "tomorrow"})).await,
+            Err(ToolError::InvalidArguments(_))
+        ));
+    }
+}
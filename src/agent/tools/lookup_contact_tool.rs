@@ -0,0 +1,153 @@
+use serde_json::{Value, json};
+
+use crate::agent::tools::{Tool, ToolError, ToolFuture};
+use crate::infra::contacts::{Contact, UserContacts};
+use crate::infra::email::{EmailAddress, suggest_addresses};
+
+/// Resolves a name or address against the user's address book
+pub struct LookupContactTool {
+    contacts: UserContacts,
+    suggestion_domain: Option<String>,
+}
+
+impl LookupContactTool {
+    pub fn new(contacts: UserContacts) -> Self {
+        Self {
+            contacts,
+            suggestion_domain: None,
+        }
+    }
+
+    /// Suggests addresses at `domain` for contacts that have none stored
+    pub fn with_suggestion_domain(mut self, domain: &str) -> Self {
+        self.suggestion_domain = Some(domain.to_string());
+        self
+    }
+
+    fn suggestions(&self, contact: &Contact) -> Vec<String> {
+        match (&self.suggestion_domain, contact.primary_email()) {
+            (Some(domain), None) => suggest_addresses(&contact.display_name, domain)
+                .iter()
+                .map(EmailAddress::to_string)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn lookup(&self, query: &str) -> Option<&Contact> {
+        if EmailAddress::looks_like_address(query) {
+            EmailAddress::parse(query)
+                .ok()
+                .and_then(|address| self.contacts.find_by_email(&address))
+        } else {
+            self.contacts.find_by_name(query)
+        }
+    }
+}
+
+impl Tool for LookupContactTool {
+    fn name(&self) -> &str {
+        "lookup_contact"
+    }
+
+    fn description(&self) -> &str {
+        "Finds a contact by name, nickname or email address."
+    }
+
+    fn arguments_hint(&self) -> &str {
+        r#"{"query": "Eva"}"#
+    }
+
+    fn call<'a>(&'a self, arguments: &'a Value) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let query = arguments
+                .get("query")
+                .and_then(Value::as_str)
+                .ok_or_else(|| ToolError::InvalidArguments("'query' is required".to_string()))?;
+
+            Ok(match self.lookup(query) {
+                Some(contact) => {
+                    let mut result = json!({
+                        "found": true,
+                        "name": contact.display_name,
+                        "email": contact.primary_email().map(|e| e.to_string()),
+                        "company": contact.company,
+                    });
+                    let suggestions = self.suggestions(contact);
+                    if !suggestions.is_empty() {
+                        // Guesses for the user to confirm, not addresses
+                        result["suggested_emails"] = json!(suggestions);
+                    }
+                    result
+                }
+                None => json!({"found": false}),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool() -> LookupContactTool {
+        LookupContactTool::new(UserContacts::load_from_file("spec/contacts.json").unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_lookup_by_name() {
+        let result = tool().call(&json!({"query": "Turtle"})).await.unwrap();
+
+        assert_eq!(result["found"], true);
+        assert!(result["email"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_suggestions_for_contact_without_email() {
+        let contacts = UserContacts::from_json_str(
+            r#"{"contacts": [{"id": "c1", "displayName": "Conceição Araújo"}]}"#,
+        )
+        .unwrap();
+        let tool = LookupContactTool::new(contacts).with_suggestion_domain("empresa.com.br");
+
+        let result = tool
+            .call(&json!({"query": "Conceição Araújo"}))
+            .await
+            .unwrap();
+
+        assert_eq!(result["email"], Value::Null);
+        assert_eq!(
+            result["suggested_emails"][0],
+            "conceicao.araujo@empresa.com.br"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_suggestions_when_email_is_stored() {
+        let result = tool()
+            .with_suggestion_domain("company.com")
+            .call(&json!({"query": "Turtle"}))
+            .await
+            .unwrap();
+
+        assert!(result.get("suggested_emails").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_unknown() {
+        let result = tool()
+            .call(&json!({"query": "Nobody Known"}))
+            .await
+            .unwrap();
+
+        assert_eq!(result, json!({"found": false}));
+    }
+
+    #[tokio::test]
+    async fn test_missing_query() {
+        assert!(matches!(
+            tool().call(&json!({})).await,
+            Err(ToolError::InvalidArguments(_))
+        ));
+    }
+}
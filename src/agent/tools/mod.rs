@@ -0,0 +1,11 @@
+pub mod check_calendar_tool;
+pub mod lookup_contact_tool;
+pub mod tool;
+pub mod tool_loop;
+pub mod tool_registry;
+
+pub use check_calendar_tool::{BusySlot, CheckCalendarTool};
+pub use lookup_contact_tool::LookupContactTool;
+pub use tool::{Tool, ToolError, ToolFuture};
+pub use tool_loop::{ToolLoop, ToolLoopOutcome, ToolStep};
+pub use tool_registry::ToolRegistry;
@@ -0,0 +1,38 @@
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send + 'a>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolError {
+    UnknownTool(String),
+    InvalidArguments(String),
+    Failed(String),
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolError::UnknownTool(name) => write!(f, "Unknown tool '{}'", name),
+            ToolError::InvalidArguments(msg) => write!(f, "Invalid arguments: {}", msg),
+            ToolError::Failed(msg) => write!(f, "Tool failed: {}", msg),
+        }
+    }
+}
+
+impl Error for ToolError {}
+
+/// A capability the model may call while working on an answer
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// Example arguments object shown to the model
+    fn arguments_hint(&self) -> &str;
+
+    fn call<'a>(&'a self, arguments: &'a Value) -> ToolFuture<'a>;
+}
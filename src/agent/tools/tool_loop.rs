@@ -0,0 +1,207 @@
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::agent::{AgentError, chat_model::ChatModel, tools::ToolRegistry};
+use crate::infra::ollama::{ChatMessages, parse_json_content};
+
+const DEFAULT_MAX_STEPS: usize = 5;
+
+const TOOL_PROTOCOL: &str = "To call a tool answer only with JSON: {\"tool\": \"<name>\", \"arguments\": {...}}. The result comes back in a tool message. When you have everything you need answer only with JSON: {\"final\": <answer>}.";
+
+/// A tool call made during the loop
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolStep {
+    pub tool: String,
+    pub arguments: Value,
+    pub result: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolLoopOutcome<T> {
+    pub answer: T,
+    pub steps: Vec<ToolStep>,
+}
+
+/// ReAct-style loop: the model either calls a tool, whose result is fed
+/// back as a tool message, or gives its final structured answer
+pub struct ToolLoop<M: ChatModel> {
+    model: M,
+    tools: ToolRegistry,
+    max_steps: usize,
+}
+
+impl<M: ChatModel + Sync> ToolLoop<M> {
+    pub fn new(model: M, tools: ToolRegistry) -> Self {
+        Self {
+            model,
+            tools,
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+
+    /// Maximum number of model turns before giving up
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn system_prompt(&self, instructions: &str) -> String {
+        format!(
+            "{}\nYou can use these tools:\n{}\n{}",
+            instructions,
+            self.tools.describe(),
+            TOOL_PROTOCOL
+        )
+    }
+
+    pub async fn run<T: DeserializeOwned>(
+        &self,
+        instructions: &str,
+        input: &str,
+    ) -> Result<ToolLoopOutcome<T>, AgentError> {
+        let mut messages = ChatMessages::new()
+            .system(&self.system_prompt(instructions))
+            .user(input);
+        let mut steps = Vec::new();
+
+        for _ in 0..self.max_steps {
+            let reply = self.model.chat(messages.clone()).await?;
+            let step = parse_json_content::<Value>(&reply)?;
+
+            if let Some(answer) = step.get("final") {
+                let answer = serde_json::from_value(answer.clone())?;
+                return Ok(ToolLoopOutcome { answer, steps });
+            }
+
+            let tool = step
+                .get("tool")
+                .and_then(Value::as_str)
+                .ok_or_else(|| {
+                    AgentError::ParseError(
+                        "Tool loop: reply is neither a tool call nor a final answer".to_string(),
+                    )
+                })?
+                .to_string();
+            let arguments = step.get("arguments").cloned().unwrap_or(Value::Null);
+
+            // Errors go back to the model so it can correct itself
+            let result = self
+                .tools
+                .call(&tool, &arguments)
+                .await
+                .unwrap_or_else(|e| json!({"error": e.to_string()}));
+
+            messages = messages.assistant(&reply).tool(&tool, &result.to_string());
+            steps.push(ToolStep {
+                tool,
+                arguments,
+                result,
+            });
+        }
+
+        Err(AgentError::ProcessingError(format!(
+            "Tool loop gave no final answer after {} steps",
+            self.max_steps
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::tools::LookupContactTool;
+    use crate::infra::contacts::UserContacts;
+    use crate::infra::ollama::MockOllamaClient;
+    use serde::Deserialize;
+
+    fn scripted(replies: &[&str]) -> MockOllamaClient {
+        replies
+            .iter()
+            .fold(MockOllamaClient::new(), |model, reply| {
+                model.with_reply(reply)
+            })
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Answer {
+        recipient: String,
+    }
+
+    fn tools() -> ToolRegistry {
+        ToolRegistry::new().register(LookupContactTool::new(
+            UserContacts::load_from_file("spec/contacts.json").unwrap(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_then_final_answer() {
+        let model = scripted(&[
+            "I should look Turtle up.\n```json\n{\"tool\": \"lookup_contact\", \"arguments\": {\"query\": \"Turtle\"}}\n```",
+            r#"{"final": {"recipient": "turtle"}}"#,
+        ]);
+        let tool_loop = ToolLoop::new(model, tools());
+
+        let outcome: ToolLoopOutcome<Answer> = tool_loop
+            .run("Resolve the recipient.", "Email Turtle")
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.answer.recipient, "turtle");
+        assert_eq!(outcome.steps.len(), 1);
+        assert_eq!(outcome.steps[0].result["found"], true);
+
+        let seen = tool_loop.model.requests();
+        let second_turn = seen[1].clone().into_vec();
+        let roles: Vec<&str> = second_turn.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "tool"]);
+        assert_eq!(second_turn[3].tool_name.as_deref(), Some("lookup_contact"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_errors_are_fed_back() {
+        let model = scripted(&[
+            r#"{"tool": "send_money", "arguments": {}}"#,
+            r#"{"final": {"recipient": "nobody"}}"#,
+        ]);
+        let tool_loop = ToolLoop::new(model, tools());
+
+        let outcome: ToolLoopOutcome<Answer> = tool_loop.run("", "x").await.unwrap();
+
+        assert_eq!(
+            outcome.steps[0].result,
+            json!({"error": "Unknown tool 'send_money'"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_max_steps() {
+        let call = r#"{"tool": "lookup_contact", "arguments": {"query": "Turtle"}}"#;
+        let model = scripted(&[call, call, call]);
+        let tool_loop = ToolLoop::new(model, tools()).with_max_steps(2);
+
+        let result: Result<ToolLoopOutcome<Answer>, _> = tool_loop.run("", "x").await;
+
+        assert!(matches!(result, Err(AgentError::ProcessingError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reply_without_protocol_is_a_parse_error() {
+        let model = scripted(&[r#"{"answer": 42}"#]);
+        let tool_loop = ToolLoop::new(model, tools());
+
+        let result: Result<ToolLoopOutcome<Answer>, _> = tool_loop.run("", "x").await;
+
+        assert!(matches!(result, Err(AgentError::ParseError(_))));
+    }
+
+    #[test]
+    fn test_system_prompt_lists_tools() {
+        let tool_loop = ToolLoop::new(scripted(&[]), tools());
+
+        let prompt = tool_loop.system_prompt("Help the user.");
+
+        assert!(prompt.starts_with("Help the user.\n"));
+        assert!(prompt.contains("- lookup_contact: Finds a contact"));
+        assert!(prompt.contains("{\"final\": <answer>}"));
+    }
+}
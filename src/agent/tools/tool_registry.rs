@@ -0,0 +1,59 @@
+use serde_json::Value;
+
+use crate::agent::tools::{Tool, ToolError};
+
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a tool, replacing any tool with the same name
+    pub fn register<T: Tool + 'static>(mut self, tool: T) -> Self {
+        self.tools.retain(|t| t.name() != tool.name());
+        self.tools.push(Box::new(tool));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Tool> {
+        self.tools
+            .iter()
+            .find(|t| t.name() == name)
+            .map(|t| t.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.tools.iter().map(|t| t.name()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    pub async fn call(&self, name: &str, arguments: &Value) -> Result<Value, ToolError> {
+        let tool = self
+            .get(name)
+            .ok_or_else(|| ToolError::UnknownTool(name.to_string()))?;
+        tool.call(arguments).await
+    }
+
+    /// Tool list for the system prompt
+    pub fn describe(&self) -> String {
+        self.tools
+            .iter()
+            .map(|t| {
+                format!(
+                    "- {}: {} Arguments: {}",
+                    t.name(),
+                    t.description(),
+                    t.arguments_hint()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
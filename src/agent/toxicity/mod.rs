@@ -0,0 +1,5 @@
+pub mod toxicity_agent;
+pub mod toxicity_assessment;
+
+pub use toxicity_agent::{ToxicityAgent, ToxicityParam};
+pub use toxicity_assessment::ToxicityAssessment;
@@ -0,0 +1,103 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel, agent::AgentParam, chat_model::model_or,
+        toxicity::ToxicityAssessment,
+    },
+    config::Config,
+    infra::ollama::{OllamaClient, OutputFormat, parse_json_content},
+};
+
+/// Asks the model whether a draft is abusive, harassing or hateful
+pub struct ToxicityAgent {
+    output_format: OutputFormat,
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
+
+impl Default for ToxicityAgent {
+    fn default() -> Self {
+        Self {
+            output_format: Config::get().structured_output.format_for("toxicity"),
+            chat_model: None,
+        }
+    }
+}
+
+impl fmt::Debug for ToxicityAgent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToxicityAgent")
+            .field("output_format", &self.output_format)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ToxicityAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends requests to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
+    }
+
+    /// How strictly the model's JSON answer is constrained
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+pub struct ToxicityParam {
+    body: String,
+}
+
+impl ToxicityParam {
+    pub fn new(body: String) -> Self {
+        Self { body }
+    }
+}
+
+impl AgentParam for ToxicityParam {}
+
+impl Agent<ToxicityParam, ToxicityAssessment> for ToxicityAgent {
+    async fn process(&self, input: ToxicityParam) -> Result<ToxicityAssessment, AgentError> {
+        let prompt = build_prompt(&input.body);
+
+        let answer = model_or(&self.chat_model, OllamaClient::new)
+            .ask_constrained(
+                &prompt,
+                &self.output_format.constraint_for::<ToxicityAssessment>(),
+            )
+            .await?;
+
+        parse_json_content::<ToxicityAssessment>(&answer)
+    }
+}
+
+fn build_prompt(body: &str) -> String {
+    format!("{}{}{}{}", INSTRUCTION, OUTPUT_FORMAT, EMAIL_LABEL, body)
+}
+
+const INSTRUCTION: &str = "Review the following email draft before it is sent. Decide whether it is insulting, harassing, threatening, hateful or otherwise inappropriate for professional correspondence.";
+const OUTPUT_FORMAT: &str =
+    " Answer only with JSON: {\"toxic\": true|false, \"reason\": \"short explanation\"}";
+const EMAIL_LABEL: &str = "\nEmail:\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_contains_body() {
+        let prompt = build_prompt("Hello Eva");
+
+        assert!(prompt.starts_with(INSTRUCTION));
+        assert!(prompt.ends_with("Email:\nHello Eva"));
+    }
+}
@@ -0,0 +1,35 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentResult;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct ToxicityAssessment {
+    pub toxic: bool,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl ToxicityAssessment {
+    pub fn new(toxic: bool, reason: String) -> Self {
+        Self { toxic, reason }
+    }
+}
+
+impl AgentResult for ToxicityAssessment {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialization() {
+        let assessment: ToxicityAssessment =
+            serde_json::from_str(r#"{"toxic": true, "reason": "insult"}"#).unwrap();
+
+        assert_eq!(
+            assessment,
+            ToxicityAssessment::new(true, "insult".to_string())
+        );
+    }
+}
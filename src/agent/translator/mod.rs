@@ -0,0 +1,7 @@
+pub mod translate_stage;
+pub mod translation;
+pub mod translator_agent;
+
+pub use translate_stage::{TranslateForRecipient, translate_for_recipient};
+pub use translation::Translation;
+pub use translator_agent::{TranslatorAgent, TranslatorParam};
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ClassificationResult, Intent,
+        classifier::{IntentDetails, Params},
+        pipeline::{PipelineContext, Stage, StageFuture},
+        tone::{ToneCheckAgent, ToneCheckParam},
+        translator::{TranslatorAgent, TranslatorParam},
+    },
+    i18n::{Locale, Tone, detect_locale},
+    infra::contacts::{Contact, UserContacts},
+};
+
+/// Stage that rewrites a `send_email` message in the recipient's preferred
+/// language and tone. Results for other intents, for recipients without a
+/// language preference or already in the right language pass through
+/// untouched.
+pub struct TranslateForRecipient {
+    agent: TranslatorAgent,
+    contacts: Arc<UserContacts>,
+    tone_check: bool,
+}
+
+pub fn translate_for_recipient(contacts: Arc<UserContacts>) -> TranslateForRecipient {
+    TranslateForRecipient {
+        agent: TranslatorAgent::new(),
+        contacts,
+        tone_check: true,
+    }
+}
+
+impl TranslateForRecipient {
+    pub fn with_agent(mut self, agent: TranslatorAgent) -> Self {
+        self.agent = agent;
+        self
+    }
+
+    /// Turns the post-translation tone check on or off
+    pub fn with_tone_check(mut self, enabled: bool) -> Self {
+        self.tone_check = enabled;
+        self
+    }
+
+    fn recipient(&self, params: &Params) -> Option<&Contact> {
+        match params.recipient_address() {
+            Some(address) => self.contacts.find_by_email(&address),
+            None => self.contacts.find_by_name(params.recipient()?),
+        }
+    }
+
+    /// Preferred language of the contact the result is addressed to
+    pub fn recipient_language(&self, params: &Params) -> Option<Locale> {
+        self.recipient(params)?.language
+    }
+
+    /// Tone the contact prefers, formal unless they say otherwise
+    pub fn recipient_tone(&self, params: &Params) -> Tone {
+        self.recipient(params)
+            .and_then(|contact| contact.tone)
+            .unwrap_or_default()
+    }
+
+    async fn translate(
+        &self,
+        text: &str,
+        source: Locale,
+        target: Locale,
+        tone: Tone,
+    ) -> Result<String, AgentError> {
+        let param = TranslatorParam::new(text.to_string(), target)
+            .with_source(source)
+            .with_tone(tone);
+        Ok(self.agent.process(param).await?.text)
+    }
+
+    /// Fails with a retryable error when the draft misses the tone, so a
+    /// `Retry` policy on the stage regenerates it
+    async fn check_tone(&self, text: &str, tone: Tone, locale: Locale) -> Result<(), AgentError> {
+        let assessment = ToneCheckAgent::new()
+            .process(ToneCheckParam::new(text.to_string(), tone, locale))
+            .await?;
+        if assessment.matches {
+            return Ok(());
+        }
+        Err(AgentError::ParseError(format!(
+            "Translation is not {}: {}",
+            tone, assessment.reason
+        )))
+    }
+
+    async fn translate_result(
+        &self,
+        mut result: ClassificationResult,
+    ) -> Result<ClassificationResult, AgentError> {
+        if result.intent != Intent::SendEmail {
+            return Ok(result);
+        }
+        let (Some(target), Some(message)) = (
+            self.recipient_language(&result.params),
+            result.params.message(),
+        ) else {
+            return Ok(result);
+        };
+        // Dictated text too short to tell is taken as the user's language
+        let source = detect_locale(message).unwrap_or_else(Locale::configured);
+        if source == target {
+            return Ok(result);
+        }
+
+        let tone = self.recipient_tone(&result.params);
+        let message = self.translate(message, source, target, tone).await?;
+        if self.tone_check {
+            self.check_tone(&message, tone, target).await?;
+        }
+        if let Some(IntentDetails::SendEmail(details)) = &mut result.details {
+            if !details.subject.trim().is_empty() {
+                details.subject = self
+                    .translate(&details.subject, source, target, tone)
+                    .await?;
+            }
+            details.message = message.clone();
+        }
+        result.params = Params::new(result.params.recipient().map(str::to_string), Some(message));
+        Ok(result)
+    }
+}
+
+impl Stage<ClassificationResult, ClassificationResult> for TranslateForRecipient {
+    fn name(&self) -> &str {
+        "translate"
+    }
+
+    fn run<'a>(
+        &'a self,
+        input: ClassificationResult,
+        _context: &'a mut PipelineContext,
+    ) -> StageFuture<'a, ClassificationResult> {
+        Box::pin(self.translate_result(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::pipeline::Pipeline;
+
+    fn stage() -> TranslateForRecipient {
+        translate_for_recipient(Arc::new(
+            UserContacts::load_from_file("spec/contacts.json").unwrap(),
+        ))
+    }
+
+    fn email(recipient: &str, message: &str) -> ClassificationResult {
+        ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values(recipient.to_string(), message.to_string()),
+        )
+    }
+
+    #[test]
+    fn test_recipient_language_by_name_or_address() {
+        let stage = stage();
+
+        assert_eq!(
+            stage.recipient_language(&Params::with_values("Elephant".to_string(), String::new())),
+            Some(Locale::PtBr)
+        );
+        assert_eq!(
+            stage.recipient_language(&Params::with_values(
+                "tiger.brilliant@gmail.com".to_string(),
+                String::new()
+            )),
+            Some(Locale::En)
+        );
+        assert_eq!(
+            stage.recipient_language(&Params::with_values("Nobody".to_string(), String::new())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_recipient_tone_defaults_to_formal() {
+        let stage = stage();
+
+        assert_eq!(
+            stage.recipient_tone(&Params::with_values("Elephant".to_string(), String::new())),
+            Tone::Informal
+        );
+        assert_eq!(
+            stage.recipient_tone(&Params::with_values("Tiger".to_string(), String::new())),
+            Tone::Formal
+        );
+    }
+
+    #[tokio::test]
+    async fn test_matching_language_passes_through() {
+        let pipeline = Pipeline::new().then(stage());
+
+        let result = pipeline
+            .run(email("Elephant", "Avise que vou me atrasar para a reunião"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.params.message(),
+            Some("Avise que vou me atrasar para a reunião")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_other_intents_and_unknown_recipients_pass_through() {
+        let stage = stage();
+        let mut context = PipelineContext::new();
+
+        let result = stage
+            .run(email("Nobody", "Vou me atrasar"), &mut context)
+            .await
+            .unwrap();
+        assert_eq!(result.params.message(), Some("Vou me atrasar"));
+
+        let no_action = ClassificationResult::new(Intent::NoAction, Params::new(None, None));
+        let result = stage.run(no_action, &mut context).await.unwrap();
+        assert_eq!(result.intent, Intent::NoAction);
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentResult;
+use crate::i18n::Locale;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Translation {
+    pub text: String,
+    /// Language of the original text, when known
+    pub source: Option<Locale>,
+    pub target: Locale,
+}
+
+impl Translation {
+    pub fn new(text: String, source: Option<Locale>, target: Locale) -> Self {
+        Self {
+            text,
+            source,
+            target,
+        }
+    }
+
+    /// True if the text was passed through because it already was in the
+    /// target language
+    pub fn is_unchanged(&self) -> bool {
+        self.source == Some(self.target)
+    }
+}
+
+impl AgentResult for Translation {}
@@ -0,0 +1,170 @@
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel,
+        agent::AgentParam,
+        chat_model::{model_or, ollama_for},
+        translator::Translation,
+    },
+    i18n::{Locale, Tone},
+    infra::ollama::parse_json_content,
+};
+
+/// Translates email text between the bundled languages, keeping names,
+/// addresses and formatting intact
+#[derive(Default)]
+pub struct TranslatorAgent {
+    model: Option<String>,
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
+
+impl TranslatorAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_model(model: &str) -> Self {
+        Self {
+            model: Some(model.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Sends requests to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
+    }
+}
+
+pub struct TranslatorParam {
+    text: String,
+    source: Option<Locale>,
+    target: Locale,
+    tone: Option<Tone>,
+}
+
+impl TranslatorParam {
+    pub fn new(text: String, target: Locale) -> Self {
+        Self {
+            text,
+            source: None,
+            target,
+            tone: None,
+        }
+    }
+
+    /// Register to write the translation in; the original's is kept if unset
+    pub fn with_tone(mut self, tone: Tone) -> Self {
+        self.tone = Some(tone);
+        self
+    }
+
+    /// Language the text is written in; the model guesses when not given
+    pub fn with_source(mut self, source: Locale) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
+
+impl AgentParam for TranslatorParam {}
+
+#[derive(Debug, Deserialize)]
+struct ModelTranslation {
+    text: String,
+}
+
+impl Agent<TranslatorParam, Translation> for TranslatorAgent {
+    async fn process(&self, input: TranslatorParam) -> Result<Translation, AgentError> {
+        if input.source == Some(input.target) || input.text.trim().is_empty() {
+            return Ok(Translation::new(input.text, input.source, input.target));
+        }
+
+        let client = model_or(&self.chat_model, || ollama_for(self.model.as_deref()));
+        let answer = client.ask(&build_prompt(&input)).await?;
+        let translation = parse_json_content::<ModelTranslation>(&answer)?;
+
+        Ok(Translation::new(
+            translation.text,
+            input.source,
+            input.target,
+        ))
+    }
+}
+
+fn build_prompt(input: &TranslatorParam) -> String {
+    let direction = match input.source {
+        Some(source) => format!(
+            "from {} into {}",
+            source.language_name(),
+            input.target.language_name()
+        ),
+        None => format!("into {}", input.target.language_name()),
+    };
+    let tone = match input.tone {
+        Some(tone) => tone.guidance(input.target),
+        None => "Keep the tone of the original.",
+    };
+    format!(
+        "Translate the following email text {}. {} {}{}{}{}",
+        direction, INSTRUCTION, tone, OUTPUT_FORMAT, TEXT_LABEL, input.text
+    )
+}
+
+const INSTRUCTION: &str = "Keep names, email addresses, numbers, dates, links and line breaks exactly as they are. Do not add anything.";
+const OUTPUT_FORMAT: &str = " Answer only with JSON: {\"text\": \"the translation\"}";
+const TEXT_LABEL: &str = "\nText:\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_names_both_languages() {
+        let prompt = build_prompt(
+            &TranslatorParam::new("Vou me atrasar".to_string(), Locale::En)
+                .with_source(Locale::PtBr),
+        );
+
+        assert!(prompt.starts_with(
+            "Translate the following email text from Brazilian Portuguese into English."
+        ));
+        assert!(prompt.contains("Keep the tone of the original."));
+        assert!(prompt.ends_with("Text:\nVou me atrasar"));
+    }
+
+    #[test]
+    fn test_prompt_with_tone() {
+        let prompt = build_prompt(
+            &TranslatorParam::new("I'm running late".to_string(), Locale::PtBr)
+                .with_tone(Tone::Informal),
+        );
+
+        assert!(prompt.contains("\"você\" or \"tu\""));
+        assert!(!prompt.contains("Keep the tone of the original."));
+    }
+
+    #[test]
+    fn test_prompt_without_source() {
+        let prompt = build_prompt(&TranslatorParam::new("Hi".to_string(), Locale::PtBr));
+
+        assert!(
+            prompt.starts_with("Translate the following email text into Brazilian Portuguese.")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_same_language_skips_the_model() {
+        let translation = TranslatorAgent::new()
+            .process(TranslatorParam::new("Hello".to_string(), Locale::En).with_source(Locale::En))
+            .await
+            .unwrap();
+
+        assert_eq!(translation.text, "Hello");
+        assert!(translation.is_unchanged());
+    }
+}
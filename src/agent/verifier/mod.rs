@@ -0,0 +1,5 @@
+pub mod verification_result;
+pub mod verifier_agent;
+
+pub use verification_result::{Verdict, VerificationResult};
+pub use verifier_agent::{VerifierAgent, VerifierParam};
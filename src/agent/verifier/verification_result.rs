@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentResult;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// The classification matches the utterance
+    Accepted,
+    /// Possible mismatch; a human should look before acting
+    Flagged,
+    /// The classification misrepresents the utterance and must not be used
+    Rejected,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VerificationResult {
+    pub verdict: Verdict,
+    pub issues: Vec<String>,
+}
+
+impl VerificationResult {
+    pub fn new(verdict: Verdict, issues: Vec<String>) -> Self {
+        Self { verdict, issues }
+    }
+
+    pub fn accepted() -> Self {
+        Self::new(Verdict::Accepted, Vec::new())
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.verdict == Verdict::Accepted
+    }
+}
+
+impl AgentResult for VerificationResult {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepted() {
+        let result = VerificationResult::accepted();
+
+        assert!(result.is_accepted());
+        assert!(result.issues.is_empty());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let result = VerificationResult::new(Verdict::Flagged, vec!["recipient".to_string()]);
+
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"verdict":"flagged","issues":["recipient"]}"#
+        );
+    }
+}
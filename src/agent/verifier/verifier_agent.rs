@@ -0,0 +1,267 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        Agent, AgentError, ChatModel, ClassificationResult,
+        agent::AgentParam,
+        chat_model::{model_or, ollama_for},
+        verifier::{Verdict, VerificationResult},
+    },
+    config::Config,
+    infra::ollama::{OutputFormat, parse_json_content},
+};
+
+/// Second-pass check that a classification faithfully represents the
+/// user's request, catching hallucinated recipients before anything is sent
+pub struct VerifierAgent {
+    model: Option<String>,
+    output_format: OutputFormat,
+    /// Answers in place of Ollama
+    chat_model: Option<Arc<dyn ChatModel>>,
+}
+
+impl Default for VerifierAgent {
+    fn default() -> Self {
+        Self {
+            model: None,
+            output_format: Config::get().structured_output.format_for("verifier"),
+            chat_model: None,
+        }
+    }
+}
+
+impl VerifierAgent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verifies with a different model than the one that classified
+    pub fn with_model(model: &str) -> Self {
+        Self {
+            model: Some(model.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Sends requests to `model` instead of Ollama, e.g. a
+    /// `MockOllamaClient` in tests
+    pub fn with_chat_model(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.chat_model = Some(model);
+        self
+    }
+
+    /// How strictly the model's JSON answer is constrained
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+pub struct VerifierParam {
+    utterance: String,
+    classification: ClassificationResult,
+}
+
+impl VerifierParam {
+    pub fn new(utterance: String, classification: ClassificationResult) -> Self {
+        Self {
+            utterance,
+            classification,
+        }
+    }
+}
+
+impl AgentParam for VerifierParam {}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ModelVerdict {
+    faithful: bool,
+    #[serde(default)]
+    issues: Vec<String>,
+}
+
+impl Agent<VerifierParam, VerificationResult> for VerifierAgent {
+    async fn process(&self, input: VerifierParam) -> Result<VerificationResult, AgentError> {
+        let issues = check_recipient_grounding(&input.utterance, &input.classification);
+
+        let classification_json = input
+            .classification
+            .to_json_string()
+            .map_err(|e| AgentError::ParseError(format!("Verification failed: {}", e)))?;
+        let prompt = build_prompt(&input.utterance, &classification_json);
+
+        let client = model_or(&self.chat_model, || ollama_for(self.model.as_deref()));
+        let answer = client
+            .ask_constrained(
+                &prompt,
+                &self.output_format.constraint_for::<ModelVerdict>(),
+            )
+            .await?;
+        let model_verdict = parse_json_content::<ModelVerdict>(&answer)?;
+
+        Ok(combine(model_verdict, issues))
+    }
+}
+
+fn combine(model_verdict: ModelVerdict, mut issues: Vec<String>) -> VerificationResult {
+    let grounded = issues.is_empty();
+    issues.extend(model_verdict.issues);
+    let verdict = match (model_verdict.faithful, grounded) {
+        (true, true) => Verdict::Accepted,
+        (true, false) => Verdict::Flagged,
+        (false, _) => Verdict::Rejected,
+    };
+    VerificationResult::new(verdict, issues)
+}
+
+/// Shorter parts of a recipient's name ground it only as the whole name
+const MIN_NAME_TOKEN_CHARS: usize = 3;
+
+/// Deterministic check: every recipient must be traceable to the utterance
+pub fn check_recipient_grounding(
+    utterance: &str,
+    classification: &ClassificationResult,
+) -> Vec<String> {
+    let Some(recipient) = classification.params.recipient() else {
+        return Vec::new();
+    };
+    let utterance = words(utterance);
+    let recipient_words = words(recipient);
+    let name_part = recipient.split('@').next().unwrap_or(recipient);
+
+    // Whole words only: "email" must not ground "ai" or "Al"
+    let grounded = (!recipient_words.is_empty()
+        && utterance
+            .windows(recipient_words.len())
+            .any(|window| window == recipient_words))
+        || words(name_part)
+            .iter()
+            .filter(|token| token.chars().count() >= MIN_NAME_TOKEN_CHARS)
+            .any(|token| utterance.contains(token));
+
+    if grounded {
+        Vec::new()
+    } else {
+        vec![format!(
+            "Recipient '{}' does not appear in the request",
+            recipient
+        )]
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn build_prompt(utterance: &str, classification_json: &str) -> String {
+    format!(
+        "{}{}{}{}{}{}",
+        INSTRUCTION, OUTPUT_FORMAT, REQUEST_LABEL, utterance, JSON_LABEL, classification_json
+    )
+}
+
+const INSTRUCTION: &str = "You verify the output of an intent classifier. Does the JSON below faithfully represent the user's request? Check the intent, that every recipient is actually mentioned by the user, and that the message does not add facts the user did not state.";
+const OUTPUT_FORMAT: &str =
+    " Answer only with JSON: {\"faithful\": true|false, \"issues\": [\"...\"]}";
+const REQUEST_LABEL: &str = "\nRequest: ";
+const JSON_LABEL: &str = "\nJSON: ";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Intent;
+    use crate::agent::classifier::Params;
+
+    fn classification(recipient: &str) -> ClassificationResult {
+        ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values(recipient.to_string(), "late".to_string()),
+        )
+    }
+
+    #[test]
+    fn test_grounded_recipient_by_name() {
+        let issues = check_recipient_grounding(
+            "Envie um e-mail para Eva informando que vou me atrasar",
+            &classification("Eva"),
+        );
+
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_grounded_recipient_by_address_local_part() {
+        let issues = check_recipient_grounding(
+            "Email Carlos about the delay",
+            &classification("carlos@company.com"),
+        );
+
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_hallucinated_recipient() {
+        let issues =
+            check_recipient_grounding("Email Carlos about the delay", &classification("Sofia"));
+
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("Sofia"));
+    }
+
+    #[test]
+    fn test_hallucinated_short_recipient() {
+        let request = "Send an email to the team saying I'm late";
+
+        assert_eq!(
+            check_recipient_grounding(request, &classification("Al")).len(),
+            1
+        );
+        assert_eq!(
+            check_recipient_grounding(request, &classification("ai@x.com")).len(),
+            1
+        );
+        assert!(check_recipient_grounding("Email Al about it", &classification("Al")).is_empty());
+    }
+
+    #[test]
+    fn test_no_recipient_is_not_an_issue() {
+        let result = ClassificationResult::new(Intent::NoAction, Params::new(None, None));
+
+        assert!(check_recipient_grounding("hello", &result).is_empty());
+    }
+
+    #[test]
+    fn test_combine_verdicts() {
+        let faithful = || ModelVerdict {
+            faithful: true,
+            issues: vec![],
+        };
+        let unfaithful = || ModelVerdict {
+            faithful: false,
+            issues: vec!["wrong intent".to_string()],
+        };
+
+        assert_eq!(combine(faithful(), vec![]).verdict, Verdict::Accepted);
+        assert_eq!(
+            combine(faithful(), vec!["ungrounded".to_string()]).verdict,
+            Verdict::Flagged
+        );
+        let rejected = combine(unfaithful(), vec![]);
+        assert_eq!(rejected.verdict, Verdict::Rejected);
+        assert_eq!(rejected.issues, vec!["wrong intent".to_string()]);
+    }
+
+    #[test]
+    fn test_prompt_contains_request_and_json() {
+        let prompt = build_prompt("Email Carlos", r#"{"intent":"send_email"}"#);
+
+        assert!(prompt.contains("Request: Email Carlos"));
+        assert!(prompt.contains(r#"JSON: {"intent":"send_email"}"#));
+    }
+}
@@ -0,0 +1,37 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::auth::Role;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthError {
+    MissingCredentials,
+    InvalidApiKey,
+    InvalidToken(String),
+    Forbidden { subject: String, required: Role },
+}
+
+impl AuthError {
+    /// HTTP status the server should answer with
+    pub fn status_code(&self) -> u16 {
+        match self {
+            AuthError::Forbidden { .. } => 403,
+            _ => 401,
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingCredentials => write!(f, "Missing credentials"),
+            AuthError::InvalidApiKey => write!(f, "Invalid API key"),
+            AuthError::InvalidToken(msg) => write!(f, "Invalid token: {}", msg),
+            AuthError::Forbidden { subject, required } => {
+                write!(f, "{} lacks the {:?} role", subject, required)
+            }
+        }
+    }
+}
+
+impl Error for AuthError {}
@@ -0,0 +1,266 @@
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+use crate::auth::{AuthError, EndpointPolicy, Role};
+use crate::config::AuthConfig;
+
+/// Authenticated caller
+#[derive(Debug, Clone, PartialEq)]
+pub struct Principal {
+    pub subject: String,
+    pub roles: Vec<Role>,
+}
+
+impl Principal {
+    pub fn has_role(&self, required: Role) -> bool {
+        self.roles.iter().any(|role| role.grants(required))
+    }
+}
+
+/// Credentials extracted from request headers
+#[derive(Debug, Clone, PartialEq)]
+pub enum Credentials {
+    ApiKey(String),
+    Bearer(String),
+}
+
+impl Credentials {
+    /// Reads `X-Api-Key` first, then `Authorization: Bearer <token>`
+    pub fn from_headers(authorization: Option<&str>, api_key: Option<&str>) -> Option<Self> {
+        if let Some(key) = api_key.map(str::trim).filter(|k| !k.is_empty()) {
+            return Some(Credentials::ApiKey(key.to_string()));
+        }
+        let value = authorization?.trim();
+        let (scheme, token) = value.split_once(' ')?;
+        scheme
+            .eq_ignore_ascii_case("bearer")
+            .then(|| Credentials::Bearer(token.trim().to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    roles: Vec<Role>,
+}
+
+pub struct Authenticator {
+    api_keys: Vec<(String, Principal)>,
+    jwt: Option<(DecodingKey, Validation)>,
+    policy: EndpointPolicy,
+}
+
+impl Authenticator {
+    pub fn new(policy: EndpointPolicy) -> Self {
+        Self {
+            api_keys: Vec::new(),
+            jwt: None,
+            policy,
+        }
+    }
+
+    pub fn from_config(config: &AuthConfig) -> Self {
+        let mut authenticator = Self::new(EndpointPolicy::default());
+        for entry in &config.api_keys {
+            authenticator = authenticator.with_api_key(&entry.key, &entry.subject, &entry.roles);
+        }
+        if let Some(secret) = &config.jwt_secret {
+            authenticator = authenticator.with_jwt_secret(secret, config.jwt_issuer.as_deref());
+        }
+        authenticator
+    }
+
+    pub fn with_api_key(mut self, key: &str, subject: &str, roles: &[Role]) -> Self {
+        self.api_keys.push((
+            key.to_string(),
+            Principal {
+                subject: subject.to_string(),
+                roles: roles.to_vec(),
+            },
+        ));
+        self
+    }
+
+    /// Accepts HS256 tokens signed with `secret` carrying `sub` and `roles` claims
+    pub fn with_jwt_secret(mut self, secret: &str, issuer: Option<&str>) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        if let Some(issuer) = issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        self.jwt = Some((DecodingKey::from_secret(secret.as_bytes()), validation));
+        self
+    }
+
+    pub fn authenticate(&self, credentials: Option<&Credentials>) -> Result<Principal, AuthError> {
+        match credentials.ok_or(AuthError::MissingCredentials)? {
+            Credentials::ApiKey(key) => self
+                .api_keys
+                .iter()
+                .find(|(known, _)| constant_time_eq(known.as_bytes(), key.as_bytes()))
+                .map(|(_, principal)| principal.clone())
+                .ok_or(AuthError::InvalidApiKey),
+            Credentials::Bearer(token) => {
+                let (key, validation) = self
+                    .jwt
+                    .as_ref()
+                    .ok_or_else(|| AuthError::InvalidToken("JWT auth is disabled".to_string()))?;
+                let data = jsonwebtoken::decode::<Claims>(token, key, validation)
+                    .map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+                Ok(Principal {
+                    subject: data.claims.sub,
+                    roles: data.claims.roles,
+                })
+            }
+        }
+    }
+
+    pub fn authorize(
+        &self,
+        principal: &Principal,
+        method: &str,
+        path: &str,
+    ) -> Result<(), AuthError> {
+        let required = self.policy.required_role(method, path);
+        if principal.has_role(required) {
+            Ok(())
+        } else {
+            Err(AuthError::Forbidden {
+                subject: principal.subject.clone(),
+                required,
+            })
+        }
+    }
+
+    /// Authenticates the caller and checks the endpoint in one step
+    pub fn check(
+        &self,
+        credentials: Option<&Credentials>,
+        method: &str,
+        path: &str,
+    ) -> Result<Principal, AuthError> {
+        let principal = self.authenticate(credentials)?;
+        self.authorize(&principal, method, path)?;
+        Ok(principal)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct TestClaims<'a> {
+        sub: &'a str,
+        roles: Vec<Role>,
+        exp: u64,
+        iss: &'a str,
+    }
+
+    fn token(secret: &str, roles: Vec<Role>, iss: &str) -> String {
+        let claims = TestClaims {
+            sub: "eva",
+            roles,
+            exp: 4_000_000_000,
+            iss,
+        };
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn authenticator() -> Authenticator {
+        Authenticator::new(EndpointPolicy::default())
+            .with_api_key("reader-key", "dashboard", &[Role::Reader])
+            .with_api_key("approver-key", "ops", &[Role::Approver])
+            .with_jwt_secret("s3cret", Some("playground"))
+    }
+
+    #[test]
+    fn test_credentials_from_headers() {
+        assert_eq!(
+            Credentials::from_headers(Some("Bearer abc"), None),
+            Some(Credentials::Bearer("abc".to_string()))
+        );
+        assert_eq!(
+            Credentials::from_headers(Some("Bearer abc"), Some("key")),
+            Some(Credentials::ApiKey("key".to_string()))
+        );
+        assert_eq!(Credentials::from_headers(Some("Basic abc"), None), None);
+        assert_eq!(Credentials::from_headers(None, None), None);
+    }
+
+    #[test]
+    fn test_api_key_roles_are_enforced_per_endpoint() {
+        let auth = authenticator();
+        let approver = Credentials::ApiKey("approver-key".to_string());
+        let reader = Credentials::ApiKey("reader-key".to_string());
+
+        assert!(
+            auth.check(Some(&approver), "POST", "/api/plans/1/confirm")
+                .is_ok()
+        );
+        assert!(auth.check(Some(&reader), "POST", "/classify").is_ok());
+
+        let denied = auth.check(Some(&reader), "POST", "/api/plans/1/confirm");
+        assert!(matches!(
+            denied,
+            Err(AuthError::Forbidden {
+                required: Role::Approver,
+                ..
+            })
+        ));
+        assert_eq!(denied.unwrap_err().status_code(), 403);
+    }
+
+    #[test]
+    fn test_unknown_api_key_and_missing_credentials() {
+        let auth = authenticator();
+
+        assert_eq!(
+            auth.authenticate(Some(&Credentials::ApiKey("nope".to_string()))),
+            Err(AuthError::InvalidApiKey)
+        );
+        assert_eq!(auth.authenticate(None), Err(AuthError::MissingCredentials));
+        assert_eq!(AuthError::MissingCredentials.status_code(), 401);
+    }
+
+    #[test]
+    fn test_jwt_roles() {
+        let auth = authenticator();
+        let sender = Credentials::Bearer(token("s3cret", vec![Role::Sender], "playground"));
+
+        let principal = auth.check(Some(&sender), "POST", "/api/send").unwrap();
+
+        assert_eq!(principal.subject, "eva");
+        assert!(
+            auth.authorize(&principal, "POST", "/api/plans/7/confirm")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_jwt_with_wrong_secret_or_issuer_is_rejected() {
+        let auth = authenticator();
+        let forged = Credentials::Bearer(token("guess", vec![Role::Admin], "playground"));
+        let foreign = Credentials::Bearer(token("s3cret", vec![Role::Admin], "elsewhere"));
+
+        assert!(matches!(
+            auth.authenticate(Some(&forged)),
+            Err(AuthError::InvalidToken(_))
+        ));
+        assert!(matches!(
+            auth.authenticate(Some(&foreign)),
+            Err(AuthError::InvalidToken(_))
+        ));
+    }
+}
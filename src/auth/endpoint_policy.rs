@@ -0,0 +1,116 @@
+use crate::auth::Role;
+
+/// Role required per `METHOD /path` route. Path segments written as
+/// `{name}` match any single segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointPolicy {
+    routes: Vec<(String, Vec<String>, Role)>,
+    default_role: Role,
+}
+
+impl EndpointPolicy {
+    pub fn new(default_role: Role) -> Self {
+        Self {
+            routes: Vec::new(),
+            default_role,
+        }
+    }
+
+    pub fn route(mut self, method: &str, path: &str, role: Role) -> Self {
+        self.routes
+            .push((method.to_ascii_uppercase(), segments(path), role));
+        self
+    }
+
+    /// Role needed for the request; unknown routes fall back to the default
+    pub fn required_role(&self, method: &str, path: &str) -> Role {
+        let requested = segments(path);
+        self.routes
+            .iter()
+            .find(|(route_method, pattern, _)| {
+                route_method.eq_ignore_ascii_case(method) && matches_pattern(pattern, &requested)
+            })
+            .map(|(_, _, role)| *role)
+            .unwrap_or(self.default_role)
+    }
+}
+
+impl Default for EndpointPolicy {
+    fn default() -> Self {
+        EndpointPolicy::new(Role::Admin)
+            .route("POST", "/classify", Role::Reader)
+            .route("POST", "/compose", Role::Reader)
+            .route("POST", "/api/classify", Role::Reader)
+            .route("GET", "/api/outbox", Role::Reader)
+            .route("GET", "/api/outbox/{id}", Role::Reader)
+            .route("POST", "/api/send", Role::Sender)
+            .route("POST", "/api/plans/{id}/confirm", Role::Approver)
+            .route("POST", "/api/plans/{id}/confirm-elevated", Role::Approver)
+            .route("POST", "/api/plans/{id}/reject", Role::Approver)
+            .route("POST", "/api/plans/{id}/cancel", Role::Sender)
+    }
+}
+
+fn segments(path: &str) -> Vec<String> {
+    path.split('?')
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn matches_pattern(pattern: &[String], requested: &[String]) -> bool {
+    pattern.len() == requested.len()
+        && pattern
+            .iter()
+            .zip(requested)
+            .all(|(p, r)| (p.starts_with('{') && p.ends_with('}')) || p == r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_routes() {
+        let policy = EndpointPolicy::default();
+
+        assert_eq!(policy.required_role("POST", "/classify"), Role::Reader);
+        assert_eq!(
+            policy.required_role("post", "/api/plans/42/confirm"),
+            Role::Approver
+        );
+        assert_eq!(
+            policy.required_role("POST", "/api/plans/42/confirm-elevated"),
+            Role::Approver
+        );
+        assert_eq!(policy.required_role("POST", "/api/send"), Role::Sender);
+        assert_eq!(
+            policy.required_role("POST", "/api/delivery-reports"),
+            Role::Admin
+        );
+    }
+
+    #[test]
+    fn test_unknown_route_uses_default_role() {
+        let policy = EndpointPolicy::default();
+
+        assert_eq!(
+            policy.required_role("DELETE", "/api/outbox/42"),
+            Role::Admin
+        );
+        assert_eq!(policy.required_role("GET", "/quarantine"), Role::Admin);
+    }
+
+    #[test]
+    fn test_query_string_and_trailing_slash_are_ignored() {
+        let policy = EndpointPolicy::default();
+
+        assert_eq!(
+            policy.required_role("GET", "/api/outbox/?page=2"),
+            Role::Reader
+        );
+    }
+}
@@ -0,0 +1,9 @@
+pub mod auth_error;
+pub mod authenticator;
+pub mod endpoint_policy;
+pub mod role;
+
+pub use auth_error::AuthError;
+pub use authenticator::{Authenticator, Credentials, Principal};
+pub use endpoint_policy::EndpointPolicy;
+pub use role::Role;
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Reader,
+    Approver,
+    Sender,
+    Admin,
+}
+
+impl Role {
+    /// Admins can do everything and every role can read; otherwise the
+    /// role must match exactly (approving does not imply sending)
+    pub fn grants(&self, required: Role) -> bool {
+        *self == Role::Admin || *self == required || required == Role::Reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_grants() {
+        assert!(Role::Admin.grants(Role::Sender));
+        assert!(Role::Approver.grants(Role::Reader));
+        assert!(Role::Approver.grants(Role::Approver));
+        assert!(!Role::Approver.grants(Role::Sender));
+        assert!(!Role::Reader.grants(Role::Approver));
+    }
+}
@@ -0,0 +1,106 @@
+//! Runs the intent classifier over a labelled dataset and reports accuracy
+//! per language.
+//!
+//! Usage: eval <samples.jsonl> [min_accuracy] [--save <report.json>] [--baseline <report.json>]
+//!
+//! `--save` writes the report with every answer; `--baseline` lists the
+//! answers that changed since a saved report.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use ollama_ai_agents_playground::agent::classifier::IntentClassifierAgent;
+use ollama_ai_agents_playground::prompt::{EvalReport, EvalSet};
+
+const USAGE: &str =
+    "Usage: eval <samples.jsonl> [min_accuracy] [--save <report.json>] [--baseline <report.json>]";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let (Some(save_path), Some(baseline_path)) = (
+        take_option(&mut args, "--save"),
+        take_option(&mut args, "--baseline"),
+    ) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    let Some(samples_path) = args.first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+
+    let set = match EvalSet::load_jsonl(Path::new(samples_path)) {
+        Ok(set) => set,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let baseline = match baseline_path.map(|path| load_report(&path)).transpose() {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let report = set.run(&IntentClassifierAgent::default()).await;
+
+    print!("{}", report);
+    for miss in &report.misses {
+        println!(
+            "miss [{}] expected {}, got {}: {}",
+            miss.language, miss.expected, miss.actual, miss.input
+        );
+    }
+    if let Some(baseline) = &baseline {
+        for change in report.changes_since(baseline) {
+            println!("changed: {}", change.input);
+            for line in change.diff.to_string().lines() {
+                println!("  {}", line);
+            }
+        }
+    }
+    if let Some(path) = save_path {
+        let saved = serde_json::to_string_pretty(&report)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = saved {
+            eprintln!("Failed to save {}: {}", path, e);
+            return ExitCode::from(2);
+        }
+    }
+
+    // Every language has to clear the bar, not just the average
+    let min_accuracy: f64 = args.get(1).and_then(|m| m.parse().ok()).unwrap_or(0.0);
+    if report
+        .by_language
+        .values()
+        .all(|stats| stats.accuracy() >= min_accuracy)
+    {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Removes `name <value>` from `args`; `None` when the value is missing,
+/// `Some(None)` when the option is not given
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<Option<String>> {
+    let Some(index) = args.iter().position(|arg| arg == name) else {
+        return Some(None);
+    };
+    if index + 1 >= args.len() {
+        return None;
+    }
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Some(Some(value))
+}
+
+fn load_report(path: &str) -> Result<EvalReport, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read baseline {}: {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid baseline {}: {}", path, e))
+}
@@ -0,0 +1,55 @@
+//! Dry-renders a prompt template with sample data and reports lint issues.
+//!
+//! Usage: prompt_lint <template> <sample.json> [max_tokens]
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use ollama_ai_agents_playground::prompt::{PromptLinter, PromptTemplate};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (Some(template_path), Some(sample_path)) = (args.first(), args.get(1)) else {
+        eprintln!("Usage: prompt_lint <template> <sample.json> [max_tokens]");
+        return ExitCode::from(2);
+    };
+
+    let template = match PromptTemplate::load_from_file(Path::new(template_path)) {
+        Ok(template) => template,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let sample: serde_json::Value = match std::fs::read_to_string(sample_path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+    {
+        Ok(sample) => sample,
+        Err(e) => {
+            eprintln!("Invalid sample {}: {}", sample_path, e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut linter = PromptLinter::default();
+    if let Some(max_tokens) = args.get(2).and_then(|m| m.parse().ok()) {
+        linter = linter.with_max_tokens(max_tokens);
+    }
+    let report = linter.lint(&template, &sample);
+
+    if let Some(rendered) = &report.rendered {
+        println!("{}", rendered);
+        println!("---");
+    }
+    println!("~{} tokens", report.token_estimate);
+    for issue in &report.issues {
+        println!("warning: {}", issue);
+    }
+
+    if report.is_clean() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
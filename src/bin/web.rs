@@ -0,0 +1,46 @@
+//! Serves the playground web UI and the `/classify` and `/compose` API.
+//!
+//! Usage: web [bind_address] [contacts.json]
+
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use ollama_ai_agents_playground::config::Config;
+use ollama_ai_agents_playground::infra::contacts::UserContacts;
+use ollama_ai_agents_playground::playground::Playground;
+use ollama_ai_agents_playground::server::{Server, serve};
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let bind = args
+        .first()
+        .cloned()
+        .unwrap_or_else(|| Config::get().server.bind.clone());
+
+    let mut builder = Playground::builder();
+    if let Some(contacts_path) = args.get(1) {
+        match UserContacts::load_from_file(contacts_path) {
+            Ok(contacts) => builder = builder.contacts(Arc::new(contacts)),
+            Err(e) => {
+                eprintln!("Invalid contacts {}: {}", contacts_path, e);
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    println!("🌐 Playground UI on http://{}", bind);
+    let server = Arc::new(Server::new(Arc::new(builder.build())));
+    let shutdown = server.shutdown().clone();
+    let stopping = tokio::spawn(async move { shutdown.on_ctrl_c().await });
+    if let Err(e) = serve(server, &bind).await {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+    // Serving stopped on Ctrl+C; wait for the shutdown to finish its hooks
+    let _ = stopping.await;
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,236 @@
+//! Serialization of results, drafts and events for other tools. JSON is
+//! always available; YAML needs the `yaml` feature and MessagePack the
+//! `msgpack` feature.
+
+use serde::{Serialize, de::DeserializeOwned};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Json,
+    Yaml,
+    MessagePack,
+}
+
+impl Format {
+    /// Every format, including those not compiled in
+    pub const ALL: [Format; 3] = [Format::Json, Format::Yaml, Format::MessagePack];
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Yaml => "application/yaml",
+            Format::MessagePack => "application/msgpack",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::MessagePack => "msgpack",
+        }
+    }
+
+    /// Whether the encoded bytes are not text
+    pub fn is_binary(&self) -> bool {
+        *self == Format::MessagePack
+    }
+
+    /// Whether this build can encode and decode the format
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Format::Json => true,
+            Format::Yaml => cfg!(feature = "yaml"),
+            Format::MessagePack => cfg!(feature = "msgpack"),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl FromStr for Format {
+    type Err = CodecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "msgpack" | "messagepack" | "mpk" => Ok(Format::MessagePack),
+            other => Err(CodecError::UnknownFormat(other.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodecError {
+    UnknownFormat(String),
+    /// The format's cargo feature is not enabled in this build
+    Disabled(Format),
+    Encode(String),
+    Decode(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::UnknownFormat(name) => {
+                write!(
+                    f,
+                    "Unknown format '{}' (expected json, yaml or msgpack)",
+                    name
+                )
+            }
+            CodecError::Disabled(format) => write!(
+                f,
+                "Format {} is not enabled; rebuild with the '{}' feature",
+                format,
+                feature_name(*format)
+            ),
+            CodecError::Encode(msg) => write!(f, "Encoding failed: {}", msg),
+            CodecError::Decode(msg) => write!(f, "Decoding failed: {}", msg),
+        }
+    }
+}
+
+impl Error for CodecError {}
+
+fn feature_name(format: Format) -> &'static str {
+    match format {
+        Format::Json => "default",
+        Format::Yaml => "yaml",
+        Format::MessagePack => "msgpack",
+    }
+}
+
+pub fn encode<T: Serialize + ?Sized>(value: &T, format: Format) -> Result<Vec<u8>, CodecError> {
+    let encoded = match format {
+        Format::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+        #[cfg(feature = "yaml")]
+        Format::Yaml => serde_yaml::to_string(value)
+            .map(String::into_bytes)
+            .map_err(|e| e.to_string()),
+        // Field names are kept so optional fields survive the round trip
+        #[cfg(feature = "msgpack")]
+        Format::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+        #[allow(unreachable_patterns)]
+        disabled => return Err(CodecError::Disabled(disabled)),
+    };
+    encoded.map_err(CodecError::Encode)
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8], format: Format) -> Result<T, CodecError> {
+    let decoded = match format {
+        Format::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+        #[cfg(feature = "yaml")]
+        Format::Yaml => serde_yaml::from_slice(bytes).map_err(|e| e.to_string()),
+        #[cfg(feature = "msgpack")]
+        Format::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+        #[allow(unreachable_patterns)]
+        disabled => return Err(CodecError::Disabled(disabled)),
+    };
+    decoded.map_err(CodecError::Decode)
+}
+
+/// Encodes a text format as a string; binary formats are refused
+pub fn encode_to_string<T: Serialize + ?Sized>(
+    value: &T,
+    format: Format,
+) -> Result<String, CodecError> {
+    if format.is_binary() {
+        return Err(CodecError::Encode(format!("{} is a binary format", format)));
+    }
+    let bytes = encode(value, format)?;
+    String::from_utf8(bytes).map_err(|e| CodecError::Encode(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Intent;
+    use crate::agent::classifier::{ClassificationResult, Params};
+    use crate::agent::follow_up::FollowUpDraft;
+    use crate::session::{EvictionReason, SessionEvent};
+
+    fn result() -> ClassificationResult {
+        ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva@company.com".to_string(), "Running late".to_string()),
+        )
+        .with_prompt_version("classify_intent@v2")
+    }
+
+    fn round_trip(format: Format) {
+        let encoded = encode(&result(), format).unwrap();
+        let decoded: ClassificationResult = decode(&encoded, format).unwrap();
+        assert_eq!(decoded.intent, Intent::SendEmail);
+        assert_eq!(decoded.params.recipient(), Some("eva@company.com"));
+        assert_eq!(
+            decoded.prompt_version.as_deref(),
+            Some("classify_intent@v2")
+        );
+
+        let event = SessionEvent::Expired {
+            user_id: "ana".to_string(),
+            reason: EvictionReason::IdleTimeout,
+            turns: 4,
+        };
+        let encoded = encode(&event, format).unwrap();
+        assert_eq!(decode::<SessionEvent>(&encoded, format).unwrap(), event);
+
+        let draft = FollowUpDraft {
+            recipient: "eva@company.com".to_string(),
+            subject: "Checking in".to_string(),
+            body: "Any news on the budget?".to_string(),
+        };
+        let encoded = encode(&draft, format).unwrap();
+        assert_eq!(decode::<FollowUpDraft>(&encoded, format).unwrap(), draft);
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!("YML".parse::<Format>().unwrap(), Format::Yaml);
+        assert_eq!("msgpack".parse::<Format>().unwrap(), Format::MessagePack);
+        assert_eq!(
+            "xml".parse::<Format>(),
+            Err(CodecError::UnknownFormat("xml".to_string()))
+        );
+        assert!(Format::ALL.iter().all(|f| f.to_string().parse() == Ok(*f)));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        round_trip(Format::Json);
+        assert_eq!(
+            encode_to_string(&EvictionReason::MaxTurns, Format::Json).unwrap(),
+            "\"max_turns\""
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_round_trip() {
+        round_trip(Format::Yaml);
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_round_trip() {
+        round_trip(Format::MessagePack);
+        assert!(encode_to_string(&result(), Format::MessagePack).is_err());
+    }
+
+    #[test]
+    fn test_disabled_formats_are_reported() {
+        for format in Format::ALL.into_iter().filter(|f| !f.is_enabled()) {
+            assert_eq!(encode(&result(), format), Err(CodecError::Disabled(format)));
+        }
+    }
+}
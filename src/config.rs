@@ -1,31 +1,766 @@
+use chrono::NaiveDate;
+use chrono_tz::Tz;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::PathBuf;
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+use crate::agent::Intent;
+use crate::agent::digest::DigestFormat;
+use crate::auth::Role;
+use crate::i18n::{Locale, Region};
+use crate::infra::email::{AutoSubmitted, EmailAddress};
+use crate::infra::ollama::OutputFormat;
+use crate::safety::content_policy::PolicyRule;
+use crate::safety::redaction::PiiKind;
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
 pub struct Config {
     pub database: DatabaseConfig,
     pub ollama: OllamaConfig,
+    #[serde(default)]
+    pub session: SessionConfig,
+    #[serde(default)]
+    pub safety: SafetyConfig,
+    #[serde(default)]
+    pub actions: ActionsConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub prompts: PromptsConfig,
+    #[serde(default)]
+    pub resilience: ResilienceConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub i18n: I18nConfig,
+    #[serde(default)]
+    pub delegation: DelegationConfig,
+    #[serde(default)]
+    pub lexicons: LexiconConfig,
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    #[serde(default)]
+    pub variables: VariablesConfig,
+    #[serde(default)]
+    pub follow_ups: FollowUpConfig,
+    #[serde(default)]
+    pub digest: DigestConfig,
+    #[serde(default)]
+    pub transcription: TranscriptionConfig,
+    #[serde(default)]
+    pub prompt_tracing: PromptTraceConfig,
+    #[serde(default)]
+    pub documents: DocumentConfig,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub calibration: CalibrationConfig,
+    #[serde(default)]
+    pub smtp: SmtpConfig,
+    #[serde(default)]
+    pub imap: ImapConfig,
+    #[serde(default)]
+    pub accounts: Vec<MailAccountConfig>,
+    #[serde(default)]
+    pub structured_output: StructuredOutputConfig,
+    #[serde(default)]
+    pub tokenizer: TokenizerConfig,
+    #[serde(default)]
+    pub cost: CostConfig,
+    #[serde(default)]
+    pub files: FilesConfig,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
 pub struct DatabaseConfig {
     pub path: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
 pub struct OllamaConfig {
     pub api: ApiConfig,
+    #[serde(default)]
+    pub probe: ProbeConfig,
+    #[serde(default)]
+    pub cache: ResponseCacheConfig,
+}
+
+/// On-disk cache of model answers, for development runs
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ResponseCacheConfig {
+    pub enabled: bool,
+    pub directory: String,
+    pub backend: CacheBackend,
+    /// Answers older than this are asked for again; kept forever if unset
+    pub ttl_secs: Option<u64>,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: ".cache/ollama".to_string(),
+            backend: CacheBackend::Files,
+            ttl_secs: None,
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+/// Where the response cache keeps its answers
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheBackend {
+    /// One JSON file per answer
+    #[default]
+    Files,
+    /// A single `responses.sqlite3` database; needs the `sqlite` feature
+    Sqlite,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
 pub struct ApiConfig {
     pub url: String,
     pub model: String,
+    /// Sampling temperature sent with every chat request; the model's own
+    /// default when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Upper bound on a single request; a caller's deadline can shorten it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct SessionConfig {
+    pub idle_ttl_secs: u64,
+    pub max_sessions: usize,
+    pub max_turns: usize,
+    pub eviction_interval_secs: u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            idle_ttl_secs: 1800,
+            max_sessions: 1000,
+            max_turns: 50,
+            eviction_interval_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct SafetyConfig {
+    pub injection: InjectionConfig,
+    pub redaction: RedactionConfig,
+    pub content_policy: ContentPolicyConfig,
+    pub blast_radius: BlastRadiusConfig,
+    pub attachments: AttachmentConfig,
+    pub recipient_anomaly: RecipientAnomalyConfig,
+    pub poison: PoisonConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct InjectionConfig {
+    pub suspicious_threshold: f32,
+    pub malicious_threshold: f32,
+    pub use_model_judge: bool,
+}
+
+impl Default for InjectionConfig {
+    fn default() -> Self {
+        Self {
+            suspicious_threshold: 0.4,
+            malicious_threshold: 0.8,
+            use_model_judge: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub logs: Vec<PiiKind>,
+    pub prompt_trace: Vec<PiiKind>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            logs: PiiKind::all(),
+            prompt_trace: PiiKind::all(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ContentPolicyConfig {
+    pub banned_phrases: Vec<String>,
+    pub max_length: Option<usize>,
+    pub required_disclaimer: Option<String>,
+    pub allowed_url_domains: Vec<String>,
+    pub flag_only: Vec<PolicyRule>,
+    pub toxicity_check: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ActionsConfig {
+    pub auto_confirm: Vec<Intent>,
+    /// Seconds a confirmed action can still be cancelled before it runs
+    pub undo_delay_secs: u64,
+    pub duplicates: DuplicateConfig,
+    /// Auto-confirmed intents still wait for the user below this
+    /// (calibrated) confidence
+    pub min_confidence: Option<f32>,
+    pub quiet_hours: QuietHoursConfig,
+    pub batching: BatchingConfig,
+    /// Emails still sending soon are saved here on shutdown and queued
+    /// again on the next start; kept in memory only when unset
+    pub outbox_path: Option<String>,
+    /// Seconds settled plans and sent or cancelled outbox items are kept
+    /// before they are forgotten
+    pub retention_secs: u64,
+}
+
+impl Default for ActionsConfig {
+    fn default() -> Self {
+        Self {
+            auto_confirm: Vec::new(),
+            undo_delay_secs: 0,
+            duplicates: DuplicateConfig::default(),
+            min_confidence: None,
+            quiet_hours: QuietHoursConfig::default(),
+            batching: BatchingConfig::default(),
+            outbox_path: None,
+            retention_secs: 604800,
+        }
+    }
+}
+
+/// Low-priority emails to one recipient within `window_secs` of the first
+/// are sent as a single email
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct BatchingConfig {
+    pub enabled: bool,
+    pub window_secs: u64,
 }
 
-static CONFIG: Lazy<Config> =
-    Lazy::new(|| Config::load_from_file("config.toml").expect("Failed to load config.toml"));
+impl Default for BatchingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: 900,
+        }
+    }
+}
+
+/// Repeated requests within a session are confirmed instead of run twice
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct DuplicateConfig {
+    pub enabled: bool,
+    /// How far back earlier actions of the session are compared
+    pub window_secs: u64,
+    /// Cosine similarity from which two requests count as the same
+    pub similarity_threshold: f32,
+    pub embedding_model: String,
+}
+
+impl Default for DuplicateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 300,
+            similarity_threshold: 0.92,
+            embedding_model: "nomic-embed-text".to_string(),
+        }
+    }
+}
+
+/// Sends landing in the recipient's night wait for their morning
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct QuietHoursConfig {
+    pub enabled: bool,
+    /// Hour the recipient's night starts, in their timezone
+    pub start_hour: u32,
+    /// Hour deferred sends go out; exclusive end of the night
+    pub end_hour: u32,
+    /// IANA name used for recipients without one; the sender's local time
+    /// if unset
+    pub default_timezone: Option<Tz>,
+    /// Messages flagged urgent go out right away
+    pub urgent_bypass: bool,
+}
+
+impl Default for QuietHoursConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            start_hour: 22,
+            end_hour: 7,
+            default_timezone: None,
+            urgent_bypass: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct BlastRadiusConfig {
+    pub max_recipients_per_email: usize,
+    pub max_emails_per_utterance: usize,
+    pub sending_start_hour: Option<u32>,
+    pub sending_end_hour: Option<u32>,
+    /// Hold emails on weekends and holidays of `[calendar]`
+    pub business_days_only: bool,
+}
+
+impl Default for BlastRadiusConfig {
+    fn default() -> Self {
+        Self {
+            max_recipients_per_email: 10,
+            max_emails_per_utterance: 3,
+            sending_start_hour: None,
+            sending_end_hour: None,
+            business_days_only: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct AttachmentConfig {
+    pub max_size_bytes: u64,
+    pub allowed_mime_types: Vec<String>,
+    pub scan_command: Option<String>,
+    pub scan_url: Option<String>,
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: 10 * 1024 * 1024,
+            allowed_mime_types: vec![
+                "application/pdf".to_string(),
+                "text/plain".to_string(),
+                "text/csv".to_string(),
+                "image/*".to_string(),
+            ],
+            scan_command: None,
+            scan_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct RecipientAnomalyConfig {
+    pub free_mail_domains: Vec<String>,
+    pub min_history: usize,
+}
+
+impl Default for RecipientAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            free_mail_domains: [
+                "gmail.com",
+                "googlemail.com",
+                "outlook.com",
+                "hotmail.com",
+                "live.com",
+                "yahoo.com",
+                "icloud.com",
+                "proton.me",
+                "protonmail.com",
+                "gmx.com",
+                "uol.com.br",
+                "bol.com.br",
+                "terra.com.br",
+            ]
+            .iter()
+            .map(|d| d.to_string())
+            .collect(),
+            min_history: 5,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct PoisonConfig {
+    /// Failed attempts before an input is quarantined
+    pub max_attempts: u32,
+    pub attempt_timeout_secs: u64,
+}
+
+impl Default for PoisonConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            attempt_timeout_secs: 120,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub api_keys: Vec<ApiKeyConfig>,
+    pub jwt_secret: Option<String>,
+    pub jwt_issuer: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct ApiKeyConfig {
+    pub key: String,
+    pub subject: String,
+    pub roles: Vec<Role>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct PromptsConfig {
+    /// Directory holding `<name>@<version>.txt` templates
+    pub directory: Option<String>,
+    /// Per prompt, the versions in play and their relative weights
+    pub experiments: BTreeMap<String, BTreeMap<String, u32>>,
+    pub compression: CompressionConfig,
+}
+
+/// Rules for stripping email boilerplate before prompting. Markers are
+/// case-insensitive whole-line patterns where `*` matches anything.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct CompressionConfig {
+    pub strip_quoted: bool,
+    /// Lines that start an older message in the thread
+    pub reply_headers: Vec<String>,
+    /// Lines that start a signature block
+    pub signature_markers: Vec<String>,
+    /// Lines that start a legal or confidentiality footer
+    pub footer_markers: Vec<String>,
+    /// Single lines to drop, e.g. mobile client taglines
+    pub drop_lines: Vec<String>,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        Self {
+            strip_quoted: true,
+            reply_headers: strings(&[
+                "On * wrote:",
+                "Em * escreveu:",
+                "*-----original message-----*",
+                "*-----mensagem original-----*",
+                "From: *",
+                "De: *",
+                "________________________________",
+            ]),
+            signature_markers: strings(&["Best regards,", "Kind regards,", "Atenciosamente,"]),
+            footer_markers: strings(&[
+                "CONFIDENTIALITY NOTICE*",
+                "This e-mail * confidential*",
+                "This email * confidential*",
+                "Esta mensagem * confidencia*",
+                "AVISO DE CONFIDENCIALIDADE*",
+            ]),
+            drop_lines: strings(&["Sent from my *", "Enviado do meu *", "Get Outlook for *"]),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ResilienceConfig {
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub hedging: HedgingConfig,
+    /// Times a classification that fails to parse is sent back to the
+    /// model to be fixed
+    pub repair_attempts: u32,
+}
+
+impl Default for ResilienceConfig {
+    fn default() -> Self {
+        Self {
+            circuit_breaker: CircuitBreakerConfig::default(),
+            hedging: HedgingConfig::default(),
+            repair_attempts: 1,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before probing again
+    pub open_secs: u64,
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_secs: 30,
+            half_open_probes: 1,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    /// Longest wait for in-flight work before exiting anyway
+    pub drain_timeout_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_timeout_secs: 30,
+        }
+    }
+}
+
+/// Duplicate slow Ollama requests to a second host or model
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct HedgingConfig {
+    pub enabled: bool,
+    /// Latency after which the duplicate request is sent
+    pub delay_ms: u64,
+    /// Second endpoint; defaults to the primary url
+    pub url: Option<String>,
+    /// Second model; defaults to the primary model
+    pub model: Option<String>,
+    /// Hedges allowed per regular request, e.g. 0.1 for at most 10%
+    pub budget_ratio: f64,
+    /// Hedges available before any requests have been made
+    pub budget_reserve: u32,
+}
+
+impl Default for HedgingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_ms: 2000,
+            url: None,
+            model: None,
+            budget_ratio: 0.1,
+            budget_reserve: 10,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct I18nConfig {
+    /// Language of user-facing messages: "en" or "pt-BR"
+    pub locale: Locale,
+    /// Pick classifier prompts in the language of the input; `locale` is
+    /// used when off or when the language cannot be told
+    pub detect_input_language: bool,
+}
+
+impl Default for I18nConfig {
+    fn default() -> Self {
+        Self {
+            locale: Locale::default(),
+            detect_input_language: true,
+        }
+    }
+}
+
+/// Default budget of one run that delegates to sub-agents
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct DelegationConfig {
+    pub max_llm_calls: u32,
+    pub max_wall_time_secs: u64,
+}
+
+impl Default for DelegationConfig {
+    fn default() -> Self {
+        Self {
+            max_llm_calls: 20,
+            max_wall_time_secs: 120,
+        }
+    }
+}
+
+/// Keyword shortcuts for the rule-based pre-classifier
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct LexiconConfig {
+    /// Classify by keyword before asking the model
+    pub enabled: bool,
+    /// Phrases per language and intent, matched ignoring case and accents
+    pub keywords: BTreeMap<Locale, BTreeMap<Intent, Vec<String>>>,
+}
+
+impl Default for LexiconConfig {
+    fn default() -> Self {
+        let lexicon = |send_email: &[&str], schedule_meeting: &[&str]| {
+            BTreeMap::from([
+                (
+                    Intent::SendEmail,
+                    send_email.iter().map(|k| k.to_string()).collect(),
+                ),
+                (
+                    Intent::ScheduleMeeting,
+                    schedule_meeting.iter().map(|k| k.to_string()).collect(),
+                ),
+            ])
+        };
+        Self {
+            enabled: false,
+            keywords: BTreeMap::from([
+                (
+                    Locale::En,
+                    lexicon(
+                        &["send an email", "send email", "email to", "write to"],
+                        &["schedule a meeting", "book a meeting", "set up a meeting"],
+                    ),
+                ),
+                (
+                    Locale::PtBr,
+                    lexicon(
+                        &[
+                            "enviar e-mail",
+                            "enviar email",
+                            "envie um email",
+                            "mande um email",
+                        ],
+                        &[
+                            "marcar reunião",
+                            "marque uma reunião",
+                            "agendar reunião",
+                            "agende uma reunião",
+                        ],
+                    ),
+                ),
+            ]),
+        }
+    }
+}
+
+/// Values the composer's variable providers fill drafts with
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct VariablesConfig {
+    /// Length of the meeting `next_free_slot` looks for
+    pub slot_minutes: u32,
+    /// Plain-text weather report; `{city}` is replaced with `city`
+    pub weather_url: Option<String>,
+    pub city: Option<String>,
+}
+
+impl Default for VariablesConfig {
+    fn default() -> Self {
+        Self {
+            slot_minutes: 30,
+            weather_url: None,
+            city: None,
+        }
+    }
+}
+
+/// Business days and hours for meeting slots and the sending policy
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Country whose national holidays apply: "BR" or "US"; defaults to
+    /// the region of `[i18n] locale`
+    pub region: Option<Region>,
+    pub work_start_hour: u32,
+    pub work_end_hour: u32,
+    /// Company holidays on top of the national ones
+    pub extra_holidays: Vec<NaiveDate>,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            region: None,
+            work_start_hour: 9,
+            work_end_hour: 18,
+            extra_holidays: Vec::new(),
+        }
+    }
+}
+
+/// When to nudge recipients who have not answered a question
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct FollowUpConfig {
+    /// Hours without a reply before a follow-up is due
+    pub wait_hours: u32,
+    /// Follow-ups per question before giving up
+    pub max_follow_ups: u32,
+}
+
+impl Default for FollowUpConfig {
+    fn default() -> Self {
+        Self {
+            wait_hours: 72,
+            max_follow_ups: 2,
+        }
+    }
+}
+
+/// Morning digest defaults; `users` overrides them per user id
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct DigestConfig {
+    pub enabled: bool,
+    /// Local hour the digest goes out
+    pub send_hour: u32,
+    pub format: DigestFormat,
+    pub users: BTreeMap<String, DigestUserConfig>,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            send_hour: 7,
+            format: DigestFormat::Email,
+            users: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct DigestUserConfig {
+    pub enabled: Option<bool>,
+    pub send_hour: Option<u32>,
+    pub locale: Option<Locale>,
+    pub format: Option<DigestFormat>,
+    pub email: Option<String>,
+}
+
+/// Config file read when `AGENT_CONFIG` is not set
+pub const CONFIG_PATH: &str = "config.toml";
+
+/// Environment variables starting with this override single settings, with
+/// `__` between the keys: `AGENT__OLLAMA__API__MODEL=llama3`
+pub const ENV_PREFIX: &str = "AGENT__";
+
+static CONFIG: Lazy<Config> = Lazy::new(|| Config::load().expect("Failed to load config.toml"));
 
 impl Config {
     pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
@@ -34,11 +769,441 @@ impl Config {
         Ok(config)
     }
 
+    /// Reads the file named by `AGENT_CONFIG` (config.toml by default) and
+    /// applies the `AGENT__*` overrides from the environment
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let path = std::env::var("AGENT_CONFIG").unwrap_or_else(|_| CONFIG_PATH.to_string());
+        Self::load_with_overrides(&path, std::env::vars())
+    }
+
+    /// Like `load_from_file`, with `vars` applied on top. Values are read
+    /// as TOML (`0.2`, `true`, `["a", "b"]`) and otherwise taken as text;
+    /// variables without the prefix are ignored.
+    pub fn load_with_overrides(
+        path: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let mut table: toml::Table = toml::from_str(&content)?;
+        for (name, value) in vars {
+            let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let keys: Vec<String> = key.split("__").map(str::to_lowercase).collect();
+            set_override(&mut table, &keys, override_value(&value))
+                .map_err(|e| format!("{}: {}", name, e))?;
+        }
+        Ok(table.try_into()?)
+    }
+
     pub fn get() -> &'static Config {
         &CONFIG
     }
 }
 
+fn set_override(
+    table: &mut toml::Table,
+    keys: &[String],
+    value: toml::Value,
+) -> Result<(), String> {
+    match keys {
+        [] => Err("no key given".to_string()),
+        [key] => {
+            table.insert(key.clone(), value);
+            Ok(())
+        }
+        [key, rest @ ..] => match table
+            .entry(key.clone())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        {
+            toml::Value::Table(inner) => set_override(inner, rest, value),
+            _ => Err(format!("'{}' is not a section", key)),
+        },
+    }
+}
+
+fn override_value(text: &str) -> toml::Value {
+    format!("value = {}", text)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(text.to_string()))
+}
+
+/// Speech-to-text hook for voice notes; `url` wins over `command`
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct TranscriptionConfig {
+    /// Command run with the audio path appended, printing the transcript
+    pub command: Option<String>,
+    /// whisper.cpp server `/inference` endpoint
+    pub url: Option<String>,
+    /// Spoken language sent to the endpoint, e.g. "pt"; whisper detects it
+    /// when unset
+    pub language: Option<String>,
+    pub timeout_secs: u64,
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            command: None,
+            url: None,
+            language: None,
+            timeout_secs: 120,
+        }
+    }
+}
+
+/// Opt-in recording of raw prompts and responses for debugging
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct PromptTraceConfig {
+    pub enabled: bool,
+    /// JSON lines file, separate from the normal logs
+    pub path: String,
+}
+
+impl Default for PromptTraceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "logs/prompt_trace.jsonl".to_string(),
+        }
+    }
+}
+
+/// Startup check of what the model supports (`/api/show`)
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ProbeConfig {
+    pub enabled: bool,
+    pub timeout_secs: u64,
+}
+
+impl Default for ProbeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_secs: 5,
+        }
+    }
+}
+
+/// Text extraction from document attachments
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct DocumentConfig {
+    /// Command per MIME type printing the document's text; `{}` stands for
+    /// the path, which is appended when absent
+    pub converters: BTreeMap<String, String>,
+    pub timeout_secs: u64,
+    /// Characters of extracted text kept per document
+    pub max_chars: usize,
+}
+
+impl Default for DocumentConfig {
+    fn default() -> Self {
+        Self {
+            converters: BTreeMap::from([
+                (
+                    "application/pdf".to_string(),
+                    "pdftotext -q -layout {} -".to_string(),
+                ),
+                (
+                    "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+                        .to_string(),
+                    "pandoc -t plain {}".to_string(),
+                ),
+            ]),
+            timeout_secs: 60,
+            max_chars: 20000,
+        }
+    }
+}
+
+/// Web UI served by the `web` binary (`server` feature)
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub bind: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1:8088".to_string(),
+        }
+    }
+}
+
+/// Mapping of model-reported confidence to the accuracy seen in reviewed
+/// classifications
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct CalibrationConfig {
+    pub enabled: bool,
+    /// JSON lines of reviewed outcomes
+    pub outcomes_path: String,
+    /// Outcomes needed before a curve is trusted
+    pub min_samples: usize,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            outcomes_path: "data/classification_outcomes.jsonl".to_string(),
+            min_samples: 30,
+        }
+    }
+}
+
+/// How the connection to the SMTP server is secured
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// TLS from the first byte, usually port 465
+    Tls,
+    /// Plain connection upgraded with STARTTLS, usually port 587
+    #[default]
+    StartTls,
+    /// Unencrypted; only for local relays and tests
+    None,
+}
+
+/// Server delivering the emails of `infra::smtp`
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Envelope and `From` address
+    pub from: Option<EmailAddress>,
+    pub timeout_secs: u64,
+    pub headers: MailHeadersConfig,
+    pub smime: SmimeConfig,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 587,
+            security: SmtpSecurity::default(),
+            username: None,
+            password: None,
+            from: None,
+            timeout_secs: 30,
+            headers: MailHeadersConfig::default(),
+            smime: SmimeConfig::default(),
+        }
+    }
+}
+
+/// Optional headers added to every sent message
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct MailHeadersConfig {
+    /// `mailto:` or `https:` targets for `List-Unsubscribe`
+    pub list_unsubscribe: Vec<String>,
+    /// Adds `List-Unsubscribe-Post` for one-click unsubscribe; needs an
+    /// `https:` target
+    pub one_click_unsubscribe: bool,
+    pub auto_submitted: Option<AutoSubmitted>,
+}
+
+/// S/MIME protection of sent mail; needs the `smime` feature
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct SmimeConfig {
+    /// Signs mail sent from an identity listed below
+    pub sign: bool,
+    /// Encrypts mail whose recipients all have a certificate in the
+    /// contact store
+    pub encrypt: bool,
+    pub identities: Vec<SmimeIdentity>,
+}
+
+/// PEM certificate and private key of one sender address
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct SmimeIdentity {
+    pub address: EmailAddress,
+    pub certificate: PathBuf,
+    pub private_key: PathBuf,
+}
+
+/// How the connection to the IMAP server is secured
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ImapSecurity {
+    /// TLS from the first byte, usually port 993
+    #[default]
+    Tls,
+    /// Plain connection upgraded with STARTTLS, usually port 143
+    StartTls,
+    /// Unencrypted; the password is only sent to a server on loopback
+    None,
+}
+
+/// Mailbox `infra::imap` reads incoming mail from
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct ImapConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: ImapSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub mailbox: String,
+    /// Flags fetched messages as read
+    pub mark_seen: bool,
+    /// Newest unread messages fetched at a time
+    pub max_messages: usize,
+    pub timeout_secs: u64,
+}
+
+impl Default for ImapConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 993,
+            security: ImapSecurity::default(),
+            username: None,
+            password: None,
+            mailbox: "INBOX".to_string(),
+            mark_seen: false,
+            max_messages: 50,
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// Ollama `format` each agent asks for, e.g. `composer = "json"` for a
+/// model that rejects schemas
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct StructuredOutputConfig {
+    pub default: OutputFormat,
+    /// By agent: `classifier`, `composer`, `entity_extractor`, `planner`,
+    /// `verifier`, `sentiment`, `toxicity`, `tone_check`, `injection_judge`
+    pub agents: BTreeMap<String, OutputFormat>,
+}
+
+impl StructuredOutputConfig {
+    pub fn format_for(&self, agent: &str) -> OutputFormat {
+        self.agents.get(agent).copied().unwrap_or(self.default)
+    }
+}
+
+/// Tokenizer counting each model's tokens: `estimate`, `cl100k`, `o200k`
+/// or the path of a Hugging Face `tokenizer.json`
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct TokenizerConfig {
+    pub default: String,
+    /// By model name prefix, e.g. `llama3` or `gpt-oss`
+    pub models: BTreeMap<String, String>,
+}
+
+impl Default for TokenizerConfig {
+    fn default() -> Self {
+        Self {
+            default: "estimate".to_string(),
+            models: BTreeMap::new(),
+        }
+    }
+}
+
+impl TokenizerConfig {
+    /// Spec of the longest prefix matching `model`
+    pub fn spec_for(&self, model: &str) -> &str {
+        self.models
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(&self.default, |(_, spec)| spec)
+    }
+}
+
+/// A mail account besides `[smtp]`, e.g. work and personal; the
+/// `AccountSelector` picks one per email
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct MailAccountConfig {
+    /// Stored with drafts and outbox items, e.g. "work"
+    pub id: String,
+    /// How the user refers to it, e.g. "work email" in "from my work email"
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Recipient domains mailed from this account
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// Intents sent from this account, e.g. schedule_meeting
+    #[serde(default)]
+    pub intents: Vec<Intent>,
+    /// Used when nothing else picks an account; `[smtp]` otherwise
+    #[serde(default)]
+    pub default: bool,
+    #[serde(default)]
+    pub smtp: SmtpConfig,
+    /// Inbox read for this account; none when unset
+    #[serde(default)]
+    pub imap: Option<ImapConfig>,
+}
+
+/// Token budgets checked before every model request
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct CostConfig {
+    pub enabled: bool,
+    /// Share of a budget at which a warning event is sent
+    pub warn_at: f32,
+    pub budgets: Vec<TokenBudgetConfig>,
+}
+
+impl Default for CostConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            warn_at: 0.8,
+            budgets: Vec::new(),
+        }
+    }
+}
+
+/// Limits for one tenant and model; leaving either out pools the usage of
+/// all of them
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct TokenBudgetConfig {
+    pub tenant: Option<String>,
+    pub model: Option<String>,
+    pub daily_tokens: Option<u64>,
+    pub monthly_tokens: Option<u64>,
+}
+
+/// Where files the user mentions ("the Q3 report") are looked up
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(default)]
+pub struct FilesConfig {
+    /// Only files under these directories are ever attached
+    pub directories: Vec<PathBuf>,
+    /// Levels of subdirectories searched below each directory
+    pub max_depth: usize,
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            directories: Vec::new(),
+            max_depth: 3,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,6 +1248,88 @@ model = "test-model"
         cleanup_test_file(test_path);
     }
 
+    #[test]
+    fn test_config_session_section_defaults_when_missing() {
+        let content = r#"
+[database]
+path = "/test/database.db"
+
+[ollama.api]
+url = "http://localhost:8080/api/chat"
+model = "test-model"
+"#;
+
+        let config: Config = toml::from_str(content).expect("Config should parse");
+        assert_eq!(config.session, SessionConfig::default());
+    }
+
+    #[test]
+    fn test_config_session_section_partial_override() {
+        let content = r#"
+[database]
+path = "/test/database.db"
+
+[ollama.api]
+url = "http://localhost:8080/api/chat"
+model = "test-model"
+
+[session]
+idle_ttl_secs = 120
+max_turns = 5
+"#;
+
+        let config: Config = toml::from_str(content).expect("Config should parse");
+        assert_eq!(config.session.idle_ttl_secs, 120);
+        assert_eq!(config.session.max_turns, 5);
+        assert_eq!(config.session.max_sessions, 1000);
+        assert_eq!(config.session.eviction_interval_secs, 60);
+    }
+
+    #[test]
+    fn test_config_load_with_overrides() {
+        let test_path = "test_config_overrides.toml";
+        let test_content = r#"
+[database]
+path = "/test/database.db"
+
+[ollama.api]
+url = "http://localhost:8080/api/chat"
+model = "test-model"
+"#;
+        create_test_config_file(test_path, test_content).expect("Failed to create test file");
+        let vars = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        let config = Config::load_with_overrides(
+            test_path,
+            vars(&[
+                ("AGENT__OLLAMA__API__MODEL", "llama3"),
+                ("AGENT__OLLAMA__API__TEMPERATURE", "0.2"),
+                ("AGENT__SESSION__MAX_TURNS", "5"),
+                ("AGENT__PROMPTS__DIRECTORY", "/etc/agent/prompts"),
+                ("PATH", "/usr/bin"),
+            ]),
+        )
+        .unwrap();
+        let invalid =
+            Config::load_with_overrides(test_path, vars(&[("AGENT__DATABASE__PATH__X", "1")]));
+        cleanup_test_file(test_path);
+
+        assert_eq!(config.ollama.api.model, "llama3");
+        assert_eq!(config.ollama.api.url, "http://localhost:8080/api/chat");
+        assert_eq!(config.ollama.api.temperature, Some(0.2));
+        assert_eq!(config.session.max_turns, 5);
+        assert_eq!(
+            config.prompts.directory.as_deref(),
+            Some("/etc/agent/prompts")
+        );
+        assert!(invalid.is_err());
+    }
+
     #[test]
     fn test_config_load_from_file_nonexistent_file() {
         let result = Config::load_from_file("nonexistent_config.toml");
@@ -132,8 +1379,11 @@ path = "/test/database.db"
                 api: ApiConfig {
                     url: "http://test.com/api".to_string(),
                     model: "test-model".to_string(),
+                    ..Default::default()
                 },
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         let serialized = toml::to_string(&original_config).expect("Serialization should succeed");
@@ -252,6 +1502,7 @@ model = "模型-test-ñoño"
         let api_config = ApiConfig {
             url: "http://test.com".to_string(),
             model: "test-model".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(api_config.url, "http://test.com");
@@ -264,7 +1515,9 @@ model = "模型-test-ñoño"
             api: ApiConfig {
                 url: "http://test.com".to_string(),
                 model: "test-model".to_string(),
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         assert_eq!(ollama_config.api.url, "http://test.com");
@@ -281,8 +1534,11 @@ model = "模型-test-ñoño"
                 api: ApiConfig {
                     url: "http://test.com".to_string(),
                     model: "test-model".to_string(),
+                    ..Default::default()
                 },
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         assert_eq!(config.database.path, "/test/db.db");
@@ -290,6 +1546,22 @@ model = "模型-test-ñoño"
         assert_eq!(config.ollama.api.model, "test-model");
     }
 
+    #[test]
+    fn test_tokenizer_spec_by_longest_prefix() {
+        let config: TokenizerConfig = toml::from_str(
+            r#"
+[models]
+"llama" = "llama.json"
+"llama3" = "llama3.json"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.spec_for("llama3.2:3b"), "llama3.json");
+        assert_eq!(config.spec_for("llama2"), "llama.json");
+        assert_eq!(config.spec_for("qwen3"), "estimate");
+    }
+
     #[test]
     fn test_config_debug_format() {
         let config = Config {
@@ -300,8 +1572,11 @@ model = "模型-test-ñoño"
                 api: ApiConfig {
                     url: "http://test.com".to_string(),
                     model: "test-model".to_string(),
+                    ..Default::default()
                 },
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         let debug_string = format!("{:?}", config);
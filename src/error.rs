@@ -0,0 +1,289 @@
+use std::fmt;
+
+use crate::action::ActionError;
+use crate::agent::AgentError;
+use crate::agent::classifier::MapperError;
+use crate::agent::pipeline::PipelineSpecError;
+use crate::agent::tools::ToolError;
+use crate::auth::AuthError;
+use crate::infra::email::EmailAddressError;
+use crate::infra::input::InputError;
+use crate::infra::resilience::BudgetExceeded;
+use crate::infra::smtp::SmtpError;
+use crate::prompt::PromptError;
+use crate::safety::{AttachmentError, BlastRadiusError};
+
+/// How callers should react to an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Transient; the same request may succeed later
+    Retryable,
+    /// The user has to change the input, credentials or approval first
+    UserFixable,
+    /// Retrying will not help
+    Fatal,
+}
+
+/// Crate-wide error wrapping the module error types
+#[derive(Debug)]
+pub enum Error {
+    Agent(AgentError),
+    Mapper(MapperError),
+    Tool(ToolError),
+    Prompt(PromptError),
+    PipelineSpec(PipelineSpecError),
+    Auth(AuthError),
+    Action(ActionError),
+    Attachment(AttachmentError),
+    BlastRadius(BlastRadiusError),
+    EmailAddress(EmailAddressError),
+    Input(InputError),
+    Budget(BudgetExceeded),
+    Smtp(SmtpError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl AgentError {
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            AgentError::NetworkError(_) => ErrorClass::Retryable,
+            // Models do not always produce valid JSON on the first try
+            AgentError::ParseError(_) => ErrorClass::Retryable,
+            AgentError::ProcessingError(_) => ErrorClass::Fatal,
+            AgentError::ValidationError(_) => ErrorClass::UserFixable,
+            // A bug for this input; retrying it would panic again
+            AgentError::StagePanicked { .. } => ErrorClass::Fatal,
+            // The same run would spiral the same way
+            AgentError::BudgetExhausted(_) => ErrorClass::Fatal,
+            // Requests go through again once the period resets
+            AgentError::BudgetExceeded(_) => ErrorClass::Retryable,
+            AgentError::HttpError { status, .. } => match status {
+                429 | 500.. => ErrorClass::Retryable,
+                _ => ErrorClass::Fatal,
+            },
+            // The caller has stopped waiting; a retry would be late too
+            AgentError::DeadlineExceeded(_) => ErrorClass::Fatal,
+            // The same input would be held again
+            AgentError::Quarantined { .. } => ErrorClass::Fatal,
+            // The model has to be pulled or configured first
+            AgentError::ModelUnavailable(_) => ErrorClass::UserFixable,
+            AgentError::MalformedModelOutput { .. } | AgentError::DeserializationError(_) => {
+                ErrorClass::Retryable
+            }
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.class() == ErrorClass::Retryable
+    }
+}
+
+impl Error {
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            Error::Agent(e) => e.class(),
+            Error::Mapper(_) => ErrorClass::Retryable,
+            Error::Tool(e) => match e {
+                ToolError::Failed(_) => ErrorClass::Retryable,
+                ToolError::InvalidArguments(_) => ErrorClass::UserFixable,
+                ToolError::UnknownTool(_) => ErrorClass::Fatal,
+            },
+            Error::Prompt(_) | Error::PipelineSpec(_) => ErrorClass::Fatal,
+            Error::Auth(_) => ErrorClass::UserFixable,
+            Error::Action(e) => match e {
+                ActionError::InvalidTransition { .. } | ActionError::UndoWindowClosed(_) => {
+                    ErrorClass::Fatal
+                }
+                ActionError::PlanNotFound(_)
+                | ActionError::ElevatedApprovalRequired(_)
+                | ActionError::ContentBlocked(_)
+                | ActionError::ContentFlagged { .. } => ErrorClass::UserFixable,
+            },
+            Error::Attachment(e) => match e {
+                AttachmentError::ScanFailed { .. } => ErrorClass::Retryable,
+                AttachmentError::Infected { .. } => ErrorClass::Fatal,
+                AttachmentError::NotFound(_)
+                | AttachmentError::TooLarge { .. }
+                | AttachmentError::TypeNotAllowed { .. } => ErrorClass::UserFixable,
+            },
+            Error::BlastRadius(e) => match e {
+                BlastRadiusError::OutsideSendingHours { .. }
+                | BlastRadiusError::NonBusinessDay { .. } => ErrorClass::Retryable,
+                BlastRadiusError::TooManyRecipients { .. }
+                | BlastRadiusError::TooManyEmails { .. } => ErrorClass::UserFixable,
+            },
+            Error::EmailAddress(_) => ErrorClass::UserFixable,
+            Error::Input(e) => match e {
+                InputError::TranscriptionFailed { .. }
+                | InputError::TimedOut { .. }
+                | InputError::ConversionFailed { .. } => ErrorClass::Retryable,
+                InputError::TranscriberNotConfigured | InputError::UnsupportedDocument { .. } => {
+                    ErrorClass::Fatal
+                }
+                InputError::NotFound(_)
+                | InputError::NotAudio(_)
+                | InputError::EmptyTranscript(_) => ErrorClass::UserFixable,
+            },
+            // Requests go through again once the period resets
+            Error::Budget(_) => ErrorClass::Retryable,
+            Error::Smtp(e) => match e {
+                SmtpError::Transport(_) | SmtpError::Deferred { .. } => ErrorClass::Retryable,
+                SmtpError::InvalidRecipient(_)
+                | SmtpError::Attachment(_)
+                | SmtpError::Undeliverable(_) => ErrorClass::UserFixable,
+                // Configuration, or a server that will refuse it again
+                SmtpError::NoSender
+                | SmtpError::UnknownAccount(_)
+                | SmtpError::Rejected { .. }
+                | SmtpError::Protection(_) => ErrorClass::Fatal,
+            },
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.class() == ErrorClass::Retryable
+    }
+
+    pub fn is_user_fixable(&self) -> bool {
+        self.class() == ErrorClass::UserFixable
+    }
+
+    pub fn is_fatal(&self) -> bool {
+        self.class() == ErrorClass::Fatal
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Agent(e) => e.fmt(f),
+            Error::Mapper(e) => e.fmt(f),
+            Error::Tool(e) => e.fmt(f),
+            Error::Prompt(e) => e.fmt(f),
+            Error::PipelineSpec(e) => e.fmt(f),
+            Error::Auth(e) => e.fmt(f),
+            Error::Action(e) => e.fmt(f),
+            Error::Attachment(e) => e.fmt(f),
+            Error::BlastRadius(e) => e.fmt(f),
+            Error::EmailAddress(e) => e.fmt(f),
+            Error::Input(e) => e.fmt(f),
+            Error::Budget(e) => e.fmt(f),
+            Error::Smtp(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(match self {
+            Error::Agent(e) => e,
+            Error::Mapper(e) => e,
+            Error::Tool(e) => e,
+            Error::Prompt(e) => e,
+            Error::PipelineSpec(e) => e,
+            Error::Auth(e) => e,
+            Error::Action(e) => e,
+            Error::Attachment(e) => e,
+            Error::BlastRadius(e) => e,
+            Error::EmailAddress(e) => e,
+            Error::Input(e) => e,
+            Error::Budget(e) => e,
+            Error::Smtp(e) => e,
+        })
+    }
+}
+
+macro_rules! impl_from {
+    ($($source:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$source> for Error {
+                fn from(e: $source) -> Self {
+                    Error::$variant(e)
+                }
+            }
+        )*
+    };
+}
+
+/// A used-up token budget is reported as such, wherever it was hit
+impl From<AgentError> for Error {
+    fn from(e: AgentError) -> Self {
+        match e {
+            AgentError::BudgetExceeded(budget) => Error::Budget(budget),
+            e => Error::Agent(e),
+        }
+    }
+}
+
+impl_from!(
+    MapperError => Mapper,
+    ToolError => Tool,
+    PromptError => Prompt,
+    PipelineSpecError => PipelineSpec,
+    AuthError => Auth,
+    ActionError => Action,
+    AttachmentError => Attachment,
+    BlastRadiusError => BlastRadius,
+    EmailAddressError => EmailAddress,
+    InputError => Input,
+    BudgetExceeded => Budget,
+    SmtpError => Smtp,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_network_errors_are_retryable() {
+        let error: Error = AgentError::NetworkError("connection refused".to_string()).into();
+
+        assert!(error.is_retryable());
+        assert_eq!(error.to_string(), "Network error: connection refused");
+    }
+
+    #[test]
+    fn test_auth_errors_are_user_fixable() {
+        let error = Error::from(AuthError::InvalidApiKey);
+
+        assert!(error.is_user_fixable());
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_infected_attachment_is_fatal() {
+        let error = Error::from(AttachmentError::Infected {
+            path: PathBuf::from("a.pdf"),
+            detail: "EICAR".to_string(),
+        });
+
+        assert!(error.is_fatal());
+    }
+
+    #[test]
+    fn test_unreachable_mail_server_is_retryable() {
+        let error = Error::from(SmtpError::Transport("connection refused".to_string()));
+
+        assert!(error.is_retryable());
+        assert!(
+            Error::from(SmtpError::Rejected {
+                code: "550".to_string(),
+                message: "No such user".to_string(),
+            })
+            .is_fatal()
+        );
+    }
+
+    #[test]
+    fn test_question_mark_conversion() {
+        fn confirm() -> Result<()> {
+            Err(ActionError::PlanNotFound(7))?
+        }
+
+        let error = confirm().unwrap_err();
+        assert_eq!(error.class(), ErrorClass::UserFixable);
+        assert!(std::error::Error::source(&error).is_some());
+    }
+}
@@ -0,0 +1,377 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::config::{CalendarConfig, Config};
+use crate::i18n::Locale;
+
+/// Days searched ahead before giving up on finding a working day
+const MAX_LOOKAHEAD_DAYS: i64 = 366;
+
+/// Country whose national holidays apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Region {
+    #[serde(rename = "BR")]
+    Br,
+    #[serde(rename = "US")]
+    Us,
+}
+
+impl Region {
+    /// Region assumed for a user writing in `locale`
+    pub fn for_locale(locale: Locale) -> Self {
+        match locale {
+            Locale::En => Region::Us,
+            Locale::PtBr => Region::Br,
+        }
+    }
+
+    /// National holidays of `year`, in date order
+    pub fn holidays(&self, year: i32) -> Vec<(NaiveDate, &'static str)> {
+        let mut holidays = match self {
+            Region::Br => br_holidays(year),
+            Region::Us => us_holidays(year),
+        };
+        holidays.sort_by_key(|(date, _)| *date);
+        holidays
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Region::Br => write!(f, "BR"),
+            Region::Us => write!(f, "US"),
+        }
+    }
+}
+
+/// Why nobody works on a given day
+#[derive(Debug, Clone, PartialEq)]
+pub enum DayOff {
+    Weekend,
+    Holiday(String),
+}
+
+impl fmt::Display for DayOff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DayOff::Weekend => write!(f, "weekend"),
+            DayOff::Holiday(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Weekends, national holidays and working hours of one region
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusinessCalendar {
+    region: Option<Region>,
+    extra_holidays: Vec<NaiveDate>,
+    work_start_hour: u32,
+    work_end_hour: u32,
+}
+
+impl BusinessCalendar {
+    /// Monday to Friday, 9:00 to 18:00; `None` observes no holidays
+    pub fn new(region: Option<Region>) -> Self {
+        Self {
+            region,
+            extra_holidays: Vec::new(),
+            work_start_hour: 9,
+            work_end_hour: 18,
+        }
+    }
+
+    /// Uses the configured region, or the one of the configured locale
+    pub fn from_config(config: &CalendarConfig, locale: Locale) -> Self {
+        Self {
+            extra_holidays: config.extra_holidays.clone(),
+            ..Self::new(Some(
+                config.region.unwrap_or_else(|| Region::for_locale(locale)),
+            ))
+        }
+        .with_working_hours(config.work_start_hour, config.work_end_hour)
+    }
+
+    /// Calendar from config.toml
+    pub fn configured() -> Self {
+        let config = Config::get();
+        Self::from_config(&config.calendar, config.i18n.locale)
+    }
+
+    /// Adds a day off on top of the national holidays, e.g. a company holiday
+    pub fn with_holiday(mut self, date: NaiveDate) -> Self {
+        self.extra_holidays.push(date);
+        self
+    }
+
+    /// Hours of the day meetings may be booked in; `end_hour` is exclusive
+    pub fn with_working_hours(mut self, start_hour: u32, end_hour: u32) -> Self {
+        self.work_start_hour = start_hour.min(23);
+        self.work_end_hour = end_hour.clamp(self.work_start_hour + 1, 24);
+        self
+    }
+
+    pub fn region(&self) -> Option<Region> {
+        self.region
+    }
+
+    /// Name of the holiday on `date`, if any
+    pub fn holiday(&self, date: NaiveDate) -> Option<String> {
+        if self.extra_holidays.contains(&date) {
+            return Some("Company holiday".to_string());
+        }
+        // An observed holiday can fall in the year before, e.g. New Year's
+        // Day on Friday, December 31st
+        let region = self.region?;
+        region
+            .holidays(date.year())
+            .into_iter()
+            .chain(region.holidays(date.year() + 1))
+            .find(|(holiday, _)| *holiday == date)
+            .map(|(_, name)| name.to_string())
+    }
+
+    /// Why `date` is not a working day; `None` if it is one
+    pub fn day_off(&self, date: NaiveDate) -> Option<DayOff> {
+        if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            return Some(DayOff::Weekend);
+        }
+        self.holiday(date).map(DayOff::Holiday)
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        self.day_off(date).is_none()
+    }
+
+    pub fn is_working_time(&self, at: NaiveDateTime) -> bool {
+        self.is_business_day(at.date()) && self.working_hours_contain(at.time())
+    }
+
+    /// First business day strictly after `date`
+    pub fn next_business_day(&self, date: NaiveDate) -> NaiveDate {
+        (1..=MAX_LOOKAHEAD_DAYS)
+            .map(|days| date + Duration::days(days))
+            .find(|day| self.is_business_day(*day))
+            .unwrap_or(date + Duration::days(1))
+    }
+
+    /// `at` itself if it is working time, else the start of the next
+    /// working period
+    pub fn next_working_time(&self, at: NaiveDateTime) -> NaiveDateTime {
+        if self.is_working_time(at) {
+            return at;
+        }
+        let date = if self.is_business_day(at.date()) && at.hour() < self.work_start_hour {
+            at.date()
+        } else {
+            self.next_business_day(at.date())
+        };
+        date.and_time(self.work_start())
+    }
+
+    /// When the working day containing `at` ends
+    pub fn end_of_working_day(&self, at: NaiveDateTime) -> NaiveDateTime {
+        at.date().and_time(NaiveTime::MIN) + Duration::hours(i64::from(self.work_end_hour))
+    }
+
+    fn work_start(&self) -> NaiveTime {
+        NaiveTime::from_hms_opt(self.work_start_hour, 0, 0).unwrap_or(NaiveTime::MIN)
+    }
+
+    fn working_hours_contain(&self, time: NaiveTime) -> bool {
+        (self.work_start_hour..self.work_end_hour).contains(&time.hour())
+    }
+}
+
+impl Default for BusinessCalendar {
+    fn default() -> Self {
+        Self::configured()
+    }
+}
+
+/// Easter Sunday (anonymous Gregorian algorithm)
+pub fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("valid Easter date")
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("valid holiday date")
+}
+
+fn br_holidays(year: i32) -> Vec<(NaiveDate, &'static str)> {
+    let easter = easter(year);
+    vec![
+        (date(year, 1, 1), "Confraternização Universal"),
+        (easter - Duration::days(48), "Carnaval"),
+        (easter - Duration::days(47), "Carnaval"),
+        (easter - Duration::days(2), "Sexta-feira Santa"),
+        (date(year, 4, 21), "Tiradentes"),
+        (date(year, 5, 1), "Dia do Trabalho"),
+        (easter + Duration::days(60), "Corpus Christi"),
+        (date(year, 9, 7), "Independência do Brasil"),
+        (date(year, 10, 12), "Nossa Senhora Aparecida"),
+        (date(year, 11, 2), "Finados"),
+        (date(year, 11, 15), "Proclamação da República"),
+        (date(year, 11, 20), "Dia da Consciência Negra"),
+        (date(year, 12, 25), "Natal"),
+    ]
+}
+
+fn us_holidays(year: i32) -> Vec<(NaiveDate, &'static str)> {
+    let nth = |month, weekday, n| {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).expect("valid weekday")
+    };
+    let last_monday_of_may = (25..=31)
+        .map(|day| date(year, 5, day))
+        .find(|day| day.weekday() == Weekday::Mon)
+        .expect("May has a Monday in its last week");
+    vec![
+        (observed(date(year, 1, 1)), "New Year's Day"),
+        (nth(1, Weekday::Mon, 3), "Martin Luther King Jr. Day"),
+        (nth(2, Weekday::Mon, 3), "Washington's Birthday"),
+        (last_monday_of_may, "Memorial Day"),
+        (observed(date(year, 6, 19)), "Juneteenth"),
+        (observed(date(year, 7, 4)), "Independence Day"),
+        (nth(9, Weekday::Mon, 1), "Labor Day"),
+        (nth(10, Weekday::Mon, 2), "Columbus Day"),
+        (observed(date(year, 11, 11)), "Veterans Day"),
+        (nth(11, Weekday::Thu, 4), "Thanksgiving Day"),
+        (observed(date(year, 12, 25)), "Christmas Day"),
+    ]
+}
+
+/// Federal holidays on a Saturday are observed on Friday, on a Sunday on
+/// Monday
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        date(year, month, day).and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_easter() {
+        assert_eq!(easter(2024), date(2024, 3, 31));
+        assert_eq!(easter(2025), date(2025, 4, 20));
+        assert_eq!(easter(2026), date(2026, 4, 5));
+    }
+
+    #[test]
+    fn test_brazilian_holidays() {
+        let calendar = BusinessCalendar::new(Some(Region::Br));
+
+        assert_eq!(
+            calendar.day_off(date(2025, 3, 4)),
+            Some(DayOff::Holiday("Carnaval".to_string()))
+        );
+        assert_eq!(
+            calendar.holiday(date(2025, 6, 19)).as_deref(),
+            Some("Corpus Christi")
+        );
+        assert!(calendar.is_business_day(date(2025, 7, 4)));
+    }
+
+    #[test]
+    fn test_us_holidays_are_observed_on_weekdays() {
+        let calendar = BusinessCalendar::new(Some(Region::Us));
+
+        // July 4th 2026 is a Saturday
+        assert_eq!(
+            calendar.holiday(date(2026, 7, 3)).as_deref(),
+            Some("Independence Day")
+        );
+        assert_eq!(
+            calendar.holiday(date(2025, 11, 27)).as_deref(),
+            Some("Thanksgiving Day")
+        );
+        assert!(calendar.is_business_day(date(2025, 4, 21)));
+    }
+
+    #[test]
+    fn test_new_years_day_observed_in_the_year_before() {
+        let calendar = BusinessCalendar::new(Some(Region::Us));
+
+        // January 1st 2022 is a Saturday
+        assert_eq!(
+            calendar.holiday(date(2021, 12, 31)).as_deref(),
+            Some("New Year's Day")
+        );
+    }
+
+    #[test]
+    fn test_weekends_and_extra_holidays() {
+        let calendar = BusinessCalendar::new(None).with_holiday(date(2025, 5, 14));
+
+        assert_eq!(calendar.day_off(date(2025, 5, 17)), Some(DayOff::Weekend));
+        assert!(!calendar.is_business_day(date(2025, 5, 14)));
+        assert!(calendar.is_business_day(date(2025, 5, 15)));
+    }
+
+    #[test]
+    fn test_next_working_time_skips_holidays() {
+        let calendar = BusinessCalendar::new(Some(Region::Us)).with_working_hours(9, 17);
+
+        // Christmas Eve evening → Christmas → the 26th at opening time
+        assert_eq!(
+            calendar.next_working_time(at(2025, 12, 24, 18)),
+            at(2025, 12, 26, 9)
+        );
+        assert_eq!(
+            calendar.next_working_time(at(2025, 12, 23, 7)),
+            at(2025, 12, 23, 9)
+        );
+        assert_eq!(
+            calendar.next_working_time(at(2025, 12, 23, 10)),
+            at(2025, 12, 23, 10)
+        );
+    }
+
+    #[test]
+    fn test_region_from_config() {
+        let config = CalendarConfig::default();
+
+        assert_eq!(
+            BusinessCalendar::from_config(&config, Locale::PtBr).region(),
+            Some(Region::Br)
+        );
+    }
+
+    #[test]
+    fn test_configured_working_hours_are_clamped() {
+        let config = CalendarConfig {
+            work_start_hour: 30,
+            work_end_hour: 5,
+            ..CalendarConfig::default()
+        };
+        let calendar = BusinessCalendar::from_config(&config, Locale::En);
+
+        assert_eq!(
+            calendar,
+            BusinessCalendar::new(Some(Region::Us)).with_working_hours(23, 24)
+        );
+    }
+}
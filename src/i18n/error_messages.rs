@@ -0,0 +1,344 @@
+use std::path::Path;
+
+use crate::action::ActionError;
+use crate::agent::AgentError;
+use crate::auth::AuthError;
+use crate::error::Error;
+use crate::i18n::{DayOff, Locale};
+use crate::infra::input::InputError;
+use crate::infra::smtp::SmtpError;
+use crate::safety::{AttachmentError, BlastRadiusError};
+
+impl Error {
+    /// Message fit to show an end user, in the given language
+    pub fn user_message(&self, locale: Locale) -> String {
+        let text = |en: &str, pt_br: &str| locale.pick(en, pt_br).to_string();
+        match self {
+            Error::Agent(e) => agent_message(e, locale),
+            Error::Mapper(_) => text(
+                "I didn't understand the model's answer. Please try again.",
+                "Não entendi a resposta do modelo. Tente novamente.",
+            ),
+            Error::Tool(_) => text(
+                "A lookup needed to answer failed. Please try again.",
+                "Uma consulta necessária para responder falhou. Tente novamente.",
+            ),
+            Error::Prompt(_) | Error::PipelineSpec(_) => text(
+                "The assistant is misconfigured. Please contact the administrator.",
+                "O assistente está mal configurado. Fale com o administrador.",
+            ),
+            Error::Auth(e) => match e {
+                AuthError::Forbidden { .. } => text(
+                    "You don't have permission to do that.",
+                    "Você não tem permissão para fazer isso.",
+                ),
+                _ => text("Please sign in again.", "Por favor, entre novamente."),
+            },
+            Error::Action(e) => match e {
+                ActionError::PlanNotFound(_) => text(
+                    "That action no longer exists.",
+                    "Essa ação não existe mais.",
+                ),
+                ActionError::InvalidTransition { .. } => text(
+                    "That action was already handled.",
+                    "Essa ação já foi tratada.",
+                ),
+                ActionError::ElevatedApprovalRequired(_) => text(
+                    "An approver needs to confirm this action.",
+                    "Um aprovador precisa confirmar esta ação.",
+                ),
+                ActionError::UndoWindowClosed(_) => text(
+                    "It's too late to undo; that action already went out.",
+                    "É tarde demais para desfazer; essa ação já foi executada.",
+                ),
+                ActionError::ContentBlocked(_) => text(
+                    "That draft breaks the content policy and can't be sent. Please reword it.",
+                    "Esse rascunho viola a política de conteúdo e não pode ser enviado. Reescreva-o.",
+                ),
+                ActionError::ContentFlagged { .. } => text(
+                    "The final draft was flagged by the content policy. Please review it and confirm again.",
+                    "O rascunho final foi sinalizado pela política de conteúdo. Revise-o e confirme novamente.",
+                ),
+            },
+            Error::Attachment(e) => attachment_message(e, locale),
+            Error::BlastRadius(e) => match e {
+                BlastRadiusError::TooManyRecipients { count, max } => match locale {
+                    Locale::En => {
+                        format!("That email has {} recipients; the limit is {}.", count, max)
+                    }
+                    Locale::PtBr => format!(
+                        "Esse e-mail tem {} destinatários; o limite é {}.",
+                        count, max
+                    ),
+                },
+                BlastRadiusError::TooManyEmails { count, max } => match locale {
+                    Locale::En => {
+                        format!("That would send {} emails; the limit is {}.", count, max)
+                    }
+                    Locale::PtBr => format!("Isso enviaria {} e-mails; o limite é {}.", count, max),
+                },
+                BlastRadiusError::OutsideSendingHours { window, .. } => match locale {
+                    Locale::En => format!("Emails can only be sent between {}.", window),
+                    Locale::PtBr => format!("E-mails só podem ser enviados entre {}.", window),
+                },
+                BlastRadiusError::NonBusinessDay {
+                    day_off, reopens, ..
+                } => {
+                    let reason = match day_off {
+                        DayOff::Weekend => locale.pick("weekend", "fim de semana").to_string(),
+                        DayOff::Holiday(name) => name.clone(),
+                    };
+                    match locale {
+                        Locale::En => format!(
+                            "Emails aren't sent on weekends or holidays ({}); they can go out from {}.",
+                            reason,
+                            locale.format_date_time(*reopens)
+                        ),
+                        Locale::PtBr => format!(
+                            "E-mails não são enviados em fins de semana ou feriados ({}); poderão sair a partir de {}.",
+                            reason,
+                            locale.format_date_time(*reopens)
+                        ),
+                    }
+                }
+            },
+            Error::EmailAddress(_) => text(
+                "I couldn't identify the recipient's email address.",
+                "Não consegui identificar o endereço de e-mail do destinatário.",
+            ),
+            Error::Input(e) => match e {
+                InputError::NotFound(_) => text(
+                    "I couldn't find that recording.",
+                    "Não encontrei essa gravação.",
+                ),
+                InputError::NotAudio(_) => text(
+                    "That file is not an audio recording.",
+                    "Esse arquivo não é uma gravação de áudio.",
+                ),
+                InputError::TranscriberNotConfigured => text(
+                    "Voice notes are not enabled. Please type your request.",
+                    "Mensagens de voz não estão habilitadas. Digite seu pedido, por favor.",
+                ),
+                InputError::TranscriptionFailed { .. } | InputError::TimedOut { .. } => text(
+                    "I couldn't transcribe your voice note. Please try again.",
+                    "Não consegui transcrever sua mensagem de voz. Tente novamente.",
+                ),
+                InputError::EmptyTranscript(_) => text(
+                    "I couldn't hear anything in that recording.",
+                    "Não consegui ouvir nada nessa gravação.",
+                ),
+                InputError::UnsupportedDocument { .. } => text(
+                    "I can't read that kind of document.",
+                    "Não consigo ler esse tipo de documento.",
+                ),
+                InputError::ConversionFailed { .. } => text(
+                    "I couldn't read the text of that document.",
+                    "Não consegui ler o texto desse documento.",
+                ),
+            },
+            Error::Budget(_) => text(
+                "The usage budget for the assistant is used up. Please try again once it resets.",
+                "O orçamento de uso do assistente acabou. Tente novamente quando ele for renovado.",
+            ),
+            Error::Smtp(e) => smtp_message(e, locale),
+        }
+    }
+
+    /// `user_message` in the configured locale
+    pub fn localized(&self) -> String {
+        self.user_message(Locale::configured())
+    }
+}
+
+fn agent_message(error: &AgentError, locale: Locale) -> String {
+    let text = |en: &str, pt_br: &str| locale.pick(en, pt_br).to_string();
+    match error {
+        AgentError::NetworkError(_) | AgentError::HttpError { .. } => text(
+            "I couldn't reach the language model. Please try again in a moment.",
+            "Não consegui falar com o modelo de linguagem. Tente novamente em instantes.",
+        ),
+        AgentError::DeadlineExceeded(_) => text(
+            "The language model did not answer in the time allowed for this request.",
+            "O modelo de linguagem não respondeu no tempo permitido para este pedido.",
+        ),
+        AgentError::Quarantined { .. } => text(
+            "Your message was held for review because it looks like instructions to the assistant.",
+            "Sua mensagem foi retida para revisão porque parece conter instruções para o assistente.",
+        ),
+        AgentError::ModelUnavailable(_) => text(
+            "The configured language model is not installed on the server.",
+            "O modelo de linguagem configurado não está instalado no servidor.",
+        ),
+        AgentError::ParseError(_)
+        | AgentError::MalformedModelOutput { .. }
+        | AgentError::DeserializationError(_) => text(
+            "I didn't understand the model's answer. Please try again.",
+            "Não entendi a resposta do modelo. Tente novamente.",
+        ),
+        AgentError::ProcessingError(_) | AgentError::StagePanicked { .. } => text(
+            "Something went wrong while processing your request.",
+            "Algo deu errado ao processar seu pedido.",
+        ),
+        AgentError::BudgetExceeded(_) => text(
+            "The usage budget for the assistant is used up. Please try again once it resets.",
+            "O orçamento de uso do assistente acabou. Tente novamente quando ele for renovado.",
+        ),
+        AgentError::BudgetExhausted(_) => text(
+            "This request needed more steps than allowed. Try splitting it into smaller requests.",
+            "Este pedido precisou de mais etapas do que o permitido. Tente dividi-lo em pedidos menores.",
+        ),
+        AgentError::ValidationError(partial) => {
+            let fields: Vec<&str> = partial.errors.iter().map(|e| e.field.as_str()).collect();
+            if fields.contains(&"recipient") {
+                text(
+                    "I couldn't identify the recipient.",
+                    "Não consegui identificar o destinatário.",
+                )
+            } else if fields.contains(&"intent") {
+                text(
+                    "I couldn't tell what you want to do.",
+                    "Não consegui entender o que você quer fazer.",
+                )
+            } else {
+                text(
+                    "Some details are missing or invalid. Please review them.",
+                    "Alguns dados estão faltando ou são inválidos. Revise-os, por favor.",
+                )
+            }
+        }
+    }
+}
+
+fn smtp_message(error: &SmtpError, locale: Locale) -> String {
+    let text = |en: &str, pt_br: &str| locale.pick(en, pt_br).to_string();
+    match error {
+        SmtpError::NoSender | SmtpError::UnknownAccount(_) => text(
+            "Sending email is not set up. Please check the mail settings.",
+            "O envio de e-mail não está configurado. Verifique as configurações de e-mail.",
+        ),
+        SmtpError::InvalidRecipient(recipient) => match locale {
+            Locale::En => format!("I couldn't find an email address for {}.", recipient),
+            Locale::PtBr => format!("Não encontrei um endereço de e-mail para {}.", recipient),
+        },
+        SmtpError::Transport(_) | SmtpError::Deferred { .. } => text(
+            "The mail server is not accepting the email right now. I'll try again.",
+            "O servidor de e-mail não está aceitando o e-mail agora. Vou tentar novamente.",
+        ),
+        SmtpError::Rejected { .. } => text(
+            "The mail server refused the email.",
+            "O servidor de e-mail recusou o e-mail.",
+        ),
+        SmtpError::Attachment(_) => text(
+            "I couldn't read one of the attachments.",
+            "Não consegui ler um dos anexos.",
+        ),
+        SmtpError::Protection(_) => text(
+            "I couldn't sign or encrypt the email.",
+            "Não consegui assinar ou criptografar o e-mail.",
+        ),
+        SmtpError::Undeliverable(_) => text(
+            "The email would likely be marked as spam, so it was not sent.",
+            "O e-mail provavelmente seria marcado como spam, então não foi enviado.",
+        ),
+    }
+}
+
+fn attachment_message(error: &AttachmentError, locale: Locale) -> String {
+    let file = |path: &Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string())
+    };
+    match (error, locale) {
+        (AttachmentError::NotFound(path), Locale::En) => {
+            format!("I couldn't find the attachment {}.", file(path))
+        }
+        (AttachmentError::NotFound(path), Locale::PtBr) => {
+            format!("Não encontrei o anexo {}.", file(path))
+        }
+        (AttachmentError::TooLarge { path, max, .. }, Locale::En) => format!(
+            "The attachment {} is too large; the limit is {} MB.",
+            file(path),
+            max / (1024 * 1024)
+        ),
+        (AttachmentError::TooLarge { path, max, .. }, Locale::PtBr) => format!(
+            "O anexo {} é grande demais; o limite é {} MB.",
+            file(path),
+            max / (1024 * 1024)
+        ),
+        (AttachmentError::TypeNotAllowed { mime_type, .. }, Locale::En) => {
+            format!("Attachments of type {} are not allowed.", mime_type)
+        }
+        (AttachmentError::TypeNotAllowed { mime_type, .. }, Locale::PtBr) => {
+            format!("Anexos do tipo {} não são permitidos.", mime_type)
+        }
+        (AttachmentError::Infected { path, .. }, Locale::En) => {
+            format!(
+                "The attachment {} was blocked by the virus scanner.",
+                file(path)
+            )
+        }
+        (AttachmentError::Infected { path, .. }, Locale::PtBr) => {
+            format!("O anexo {} foi bloqueado pelo antivírus.", file(path))
+        }
+        (AttachmentError::ScanFailed { path, .. }, Locale::En) => {
+            format!(
+                "I couldn't scan the attachment {}. Please try again.",
+                file(path)
+            )
+        }
+        (AttachmentError::ScanFailed { path, .. }, Locale::PtBr) => {
+            format!(
+                "Não consegui verificar o anexo {}. Tente novamente.",
+                file(path)
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::PartialClassification;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_unresolved_recipient_in_portuguese() {
+        let partial = PartialClassification::from_content(
+            r#"{"intent": "send_email", "params": {"recipient": "eva@@x"}}"#,
+        );
+        let error = Error::from(AgentError::ValidationError(Box::new(partial)));
+
+        assert_eq!(
+            error.user_message(Locale::PtBr),
+            "Não consegui identificar o destinatário."
+        );
+        assert_eq!(
+            error.user_message(Locale::En),
+            "I couldn't identify the recipient."
+        );
+    }
+
+    #[test]
+    fn test_messages_hide_internal_details() {
+        let error = Error::from(AgentError::NetworkError(
+            "reqwest::Error { kind: Connect }".to_string(),
+        ));
+
+        assert!(!error.user_message(Locale::En).contains("reqwest"));
+    }
+
+    #[test]
+    fn test_attachment_message_uses_file_name() {
+        let error = Error::from(AttachmentError::TooLarge {
+            path: PathBuf::from("/tmp/uploads/report.pdf"),
+            size: 30 * 1024 * 1024,
+            max: 10 * 1024 * 1024,
+        });
+
+        assert_eq!(
+            error.user_message(Locale::PtBr),
+            "O anexo report.pdf é grande demais; o limite é 10 MB."
+        );
+    }
+}
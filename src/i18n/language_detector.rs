@@ -0,0 +1,79 @@
+use crate::i18n::Locale;
+
+/// Short words that are common in one language and rare in the other
+const EN_STOPWORDS: &[&str] = &[
+    "the", "to", "and", "of", "is", "that", "for", "with", "about", "send", "tell", "please",
+    "meeting", "tomorrow", "i", "i'm", "i'll", "my", "you", "will", "be", "at", "it", "this",
+    "email", "message", "late", "schedule",
+];
+
+const PT_STOPWORDS: &[&str] = &[
+    "o", "a", "os", "as", "de", "do", "da", "que", "para", "com", "sobre", "um", "uma", "não",
+    "nao", "eu", "vou", "está", "esta", "envie", "mande", "avise", "diga", "por", "favor",
+    "reunião", "reuniao", "amanhã", "amanha", "mensagem", "atrasar", "agende", "marque", "às",
+];
+
+/// Letters that only show up in Portuguese text
+const PT_LETTERS: &[char] = &['ã', 'õ', 'ç', 'á', 'é', 'í', 'ó', 'ú', 'â', 'ê', 'ô', 'à'];
+
+/// Guesses the language of `text` among the bundled locales. `None` when
+/// there is too little signal to tell, e.g. a bare email address.
+pub fn detect_locale(text: &str) -> Option<Locale> {
+    let mut en = 0;
+    let mut pt = 0;
+    // Addresses and links say nothing about the language around them
+    let words = text
+        .split_whitespace()
+        .filter(|w| !w.contains('@') && !w.contains("://"))
+        .flat_map(|w| w.split(|c: char| !c.is_alphanumeric() && c != '\''))
+        .filter(|w| !w.is_empty());
+    for word in words {
+        let word = word.to_lowercase();
+        if EN_STOPWORDS.contains(&word.as_str()) {
+            en += 1;
+        }
+        if PT_STOPWORDS.contains(&word.as_str()) {
+            pt += 1;
+        }
+        if word.chars().any(|c| PT_LETTERS.contains(&c)) {
+            pt += 1;
+        }
+    }
+    match en.cmp(&pt) {
+        std::cmp::Ordering::Greater => Some(Locale::En),
+        std::cmp::Ordering::Less => Some(Locale::PtBr),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_portuguese() {
+        assert_eq!(
+            detect_locale("Avise a Eva que vou atrasar"),
+            Some(Locale::PtBr)
+        );
+        assert_eq!(
+            detect_locale("Marque reunião com Carlos amanhã às 15h"),
+            Some(Locale::PtBr)
+        );
+    }
+
+    #[test]
+    fn test_detects_english() {
+        assert_eq!(
+            detect_locale("Send an email to Carlos about the delay"),
+            Some(Locale::En)
+        );
+    }
+
+    #[test]
+    fn test_no_signal() {
+        assert_eq!(detect_locale("eva@company.com"), None);
+        assert_eq!(detect_locale("Eva, 15h"), None);
+        assert_eq!(detect_locale(""), None);
+    }
+}
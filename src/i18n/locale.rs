@@ -0,0 +1,168 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::config::Config;
+
+/// Language for user-facing text
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "pt-BR")]
+    PtBr,
+}
+
+impl Locale {
+    /// Reads tags like `pt-BR`, `pt_BR.UTF-8` or `en-US`; `None` for
+    /// languages without translations
+    pub fn parse(tag: &str) -> Option<Self> {
+        let language = tag
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "pt" => Some(Locale::PtBr),
+            _ => None,
+        }
+    }
+
+    /// Locale from config.toml
+    pub fn configured() -> Self {
+        Config::get().i18n.locale
+    }
+
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::PtBr => "pt-BR",
+        }
+    }
+
+    /// English name of the language, for prompts
+    pub fn language_name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::PtBr => "Brazilian Portuguese",
+        }
+    }
+
+    /// Picks the text for this locale
+    pub fn pick<'a>(&self, en: &'a str, pt_br: &'a str) -> &'a str {
+        match self {
+            Locale::En => en,
+            Locale::PtBr => pt_br,
+        }
+    }
+
+    /// `05/14/2025` in English, `14/05/2025` in Portuguese
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(self.pick("%m/%d/%Y", "%d/%m/%Y")).to_string()
+    }
+
+    /// `3:05 PM` in English, `15:05` in Portuguese
+    pub fn format_time(&self, time: NaiveTime) -> String {
+        time.format(self.pick("%-I:%M %p", "%H:%M")).to_string()
+    }
+
+    pub fn format_date_time(&self, date_time: NaiveDateTime) -> String {
+        format!(
+            "{} {} {}",
+            self.format_date(date_time.date()),
+            self.pick("at", "às"),
+            self.format_time(date_time.time())
+        )
+    }
+
+    /// Number with `decimals` places and grouped thousands: `1,234.50` in
+    /// English, `1.234,50` in Portuguese
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        let (group, point) = match self {
+            Locale::En => (',', '.'),
+            Locale::PtBr => ('.', ','),
+        };
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = formatted
+            .split_once('.')
+            .unwrap_or((formatted.as_str(), ""));
+
+        let mut result = String::new();
+        if value.is_sign_negative() && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                result.push(group);
+            }
+            result.push(digit);
+        }
+        if !fraction.is_empty() {
+            result.push(point);
+            result.push_str(fraction);
+        }
+        result
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tag())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(Locale::parse("pt-BR"), Some(Locale::PtBr));
+        assert_eq!(Locale::parse("pt_BR.UTF-8"), Some(Locale::PtBr));
+        assert_eq!(Locale::parse("EN-us"), Some(Locale::En));
+        assert_eq!(Locale::parse("fr"), None);
+    }
+
+    #[test]
+    fn test_format_date_and_time() {
+        let date_time = NaiveDate::from_ymd_opt(2025, 5, 14)
+            .unwrap()
+            .and_hms_opt(15, 5, 0)
+            .unwrap();
+
+        assert_eq!(
+            Locale::En.format_date_time(date_time),
+            "05/14/2025 at 3:05 PM"
+        );
+        assert_eq!(
+            Locale::PtBr.format_date_time(date_time),
+            "14/05/2025 às 15:05"
+        );
+        assert_eq!(
+            Locale::En.format_time(NaiveTime::from_hms_opt(0, 30, 0).unwrap()),
+            "12:30 AM"
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(Locale::En.format_number(1234.5, 2), "1,234.50");
+        assert_eq!(Locale::PtBr.format_number(1234.5, 2), "1.234,50");
+        assert_eq!(Locale::PtBr.format_number(-1234567.0, 0), "-1.234.567");
+        assert_eq!(Locale::En.format_number(999.0, 0), "999");
+        assert_eq!(Locale::En.format_number(-0.001, 2), "0.00");
+    }
+
+    #[test]
+    fn test_serde_uses_tags() {
+        assert_eq!(serde_json::to_string(&Locale::PtBr).unwrap(), "\"pt-BR\"");
+        assert_eq!(
+            serde_json::from_str::<Locale>("\"en\"").unwrap(),
+            Locale::En
+        );
+    }
+}
@@ -0,0 +1,13 @@
+pub mod business_calendar;
+pub mod error_messages;
+pub mod language_detector;
+pub mod locale;
+pub mod relative_time;
+pub mod text;
+pub mod tone;
+
+pub use business_calendar::{BusinessCalendar, DayOff, Region};
+pub use language_detector::detect_locale;
+pub use locale::Locale;
+pub use relative_time::resolve_date_time;
+pub use tone::Tone;
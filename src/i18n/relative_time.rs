@@ -0,0 +1,269 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use deunicode::deunicode;
+
+/// Hour used when only a day is given ("tomorrow", "sexta")
+const DEFAULT_HOUR: u32 = 9;
+
+const WEEKDAYS: &[(&str, Weekday)] = &[
+    ("monday", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+    ("segunda", Weekday::Mon),
+    ("terca", Weekday::Tue),
+    ("quarta", Weekday::Wed),
+    ("quinta", Weekday::Thu),
+    ("sexta", Weekday::Fri),
+    ("sabado", Weekday::Sat),
+    ("domingo", Weekday::Sun),
+];
+
+const PARTS_OF_DAY: &[(&str, u32)] = &[
+    ("morning", 9),
+    ("manha", 9),
+    ("noon", 12),
+    ("midday", 12),
+    ("afternoon", 14),
+    ("tarde", 14),
+    ("evening", 19),
+    ("tonight", 19),
+    ("night", 19),
+    ("noite", 19),
+];
+
+/// Resolves a time as users write it, in English or Portuguese, against
+/// `now`: ISO timestamps, "tomorrow morning", "sexta às 15h",
+/// "in 2 hours". `None` if no date or time could be recognized.
+pub fn resolve_date_time(text: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    if let Some(exact) = parse_iso(text) {
+        return Some(exact);
+    }
+
+    let folded = deunicode(text).to_lowercase();
+    let words: Vec<&str> = folded
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == ':'))
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    if let Some(offset) = parse_offset(&words) {
+        return Some(now + offset);
+    }
+
+    let date = parse_date(&folded, &words, now.date());
+    let time = parse_time(&words);
+    match (date, time) {
+        (None, None) => None,
+        (Some(date), time) => Some(date.and_time(time.unwrap_or(at_hour(DEFAULT_HOUR)))),
+        // A bare time already past today means tomorrow
+        (None, Some(time)) => {
+            let today = now.date().and_time(time);
+            Some(if today <= now {
+                today + Duration::days(1)
+            } else {
+                today
+            })
+        }
+    }
+}
+
+fn parse_iso(text: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(at_hour(DEFAULT_HOUR)))
+        })
+}
+
+/// "in 2 hours", "em 30 minutos", "daqui a 1 hora"
+fn parse_offset(words: &[&str]) -> Option<Duration> {
+    words.windows(3).find_map(|window| {
+        let [marker, amount, unit] = window else {
+            return None;
+        };
+        if !matches!(*marker, "in" | "em" | "a") {
+            return None;
+        }
+        let amount: i64 = amount.parse().ok()?;
+        match *unit {
+            "minute" | "minutes" | "min" | "mins" | "minuto" | "minutos" => {
+                Some(Duration::minutes(amount))
+            }
+            "hour" | "hours" | "hora" | "horas" => Some(Duration::hours(amount)),
+            "day" | "days" | "dia" | "dias" => Some(Duration::days(amount)),
+            _ => None,
+        }
+    })
+}
+
+fn parse_date(folded: &str, words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    if folded.contains("day after tomorrow") || folded.contains("depois de amanha") {
+        return Some(today + Duration::days(2));
+    }
+    if folded.contains("next week")
+        || folded.contains("semana que vem")
+        || folded.contains("proxima semana")
+    {
+        return Some(next_weekday(today, Weekday::Mon));
+    }
+    words.iter().find_map(|word| match *word {
+        "today" | "tonight" | "hoje" => Some(today),
+        "tomorrow" | "amanha" => Some(today + Duration::days(1)),
+        _ => WEEKDAYS
+            .iter()
+            .find(|(name, _)| name == word)
+            .map(|(_, weekday)| next_weekday(today, *weekday)),
+    })
+}
+
+/// First `weekday` strictly after `today`
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (7 + weekday.num_days_from_monday() as i64
+        - today.weekday().num_days_from_monday() as i64
+        - 1)
+        % 7
+        + 1;
+    today + Duration::days(days)
+}
+
+fn parse_time(words: &[&str]) -> Option<NaiveTime> {
+    let clock = words.iter().enumerate().find_map(|(i, word)| {
+        let previous = i.checked_sub(1).map(|p| words[p]);
+        let next = words.get(i + 1).copied();
+        clock_time(word, previous, next)
+    });
+    clock.or_else(|| {
+        words.iter().find_map(|word| {
+            PARTS_OF_DAY
+                .iter()
+                .find(|(name, _)| name == word)
+                .map(|(_, hour)| at_hour(*hour))
+        })
+    })
+}
+
+/// "15:30", "15h", "9h30", "3pm", "3:30pm", "3 pm", "at 9", "às 9"
+fn clock_time(word: &str, previous: Option<&str>, next: Option<&str>) -> Option<NaiveTime> {
+    let (word, meridiem) = match (word.strip_suffix("am"), word.strip_suffix("pm")) {
+        (Some(rest), _) => (rest, Some(false)),
+        (_, Some(rest)) => (rest, Some(true)),
+        _ => (
+            word,
+            match next {
+                Some("am") => Some(false),
+                Some("pm") => Some(true),
+                _ => None,
+            },
+        ),
+    };
+    let (hour, minute, marked) = if let Some((hour, minute)) = word.split_once(':') {
+        (hour, minute, true)
+    } else if let Some((hour, minute)) = word.split_once('h') {
+        (hour, minute, true)
+    } else {
+        (word, "", false)
+    };
+    let introduced = matches!(previous, Some("at" | "as"));
+    if !(marked || meridiem.is_some() || introduced) {
+        return None;
+    }
+    let mut hour: u32 = hour.parse().ok()?;
+    let minute: u32 = if minute.is_empty() {
+        0
+    } else {
+        minute.parse().ok()?
+    };
+    match meridiem {
+        Some(true) if hour < 12 => hour += 12,
+        Some(false) if hour == 12 => hour = 0,
+        _ => {}
+    }
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn at_hour(hour: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or(NaiveTime::MIN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Wednesday
+    fn now() -> NaiveDateTime {
+        at(14, 16, 30)
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 5, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_relative_days_and_parts_of_day() {
+        assert_eq!(
+            resolve_date_time("tomorrow morning", now()),
+            Some(at(15, 9, 0))
+        );
+        assert_eq!(
+            resolve_date_time("amanhã de manhã", now()),
+            Some(at(15, 9, 0))
+        );
+        assert_eq!(
+            resolve_date_time("depois de amanhã à tarde", now()),
+            Some(at(16, 14, 0))
+        );
+        assert_eq!(resolve_date_time("tonight", now()), Some(at(14, 19, 0)));
+    }
+
+    #[test]
+    fn test_weekdays_and_clock_times() {
+        assert_eq!(
+            resolve_date_time("sexta às 15h", now()),
+            Some(at(16, 15, 0))
+        );
+        assert_eq!(
+            resolve_date_time("next Wednesday at 3:30pm", now()),
+            Some(at(21, 15, 30))
+        );
+        assert_eq!(resolve_date_time("Monday 9 am", now()), Some(at(19, 9, 0)));
+        assert_eq!(
+            resolve_date_time("semana que vem", now()),
+            Some(at(19, 9, 0))
+        );
+    }
+
+    #[test]
+    fn test_bare_time_rolls_over_to_tomorrow() {
+        assert_eq!(resolve_date_time("at 9", now()), Some(at(15, 9, 0)));
+        assert_eq!(resolve_date_time("18h30", now()), Some(at(14, 18, 30)));
+    }
+
+    #[test]
+    fn test_offsets_and_iso() {
+        assert_eq!(resolve_date_time("in 2 hours", now()), Some(at(14, 18, 30)));
+        assert_eq!(
+            resolve_date_time("daqui a 30 minutos", now()),
+            Some(at(14, 17, 0))
+        );
+        assert_eq!(
+            resolve_date_time("2025-05-20T08:15", now()),
+            Some(at(20, 8, 15))
+        );
+    }
+
+    #[test]
+    fn test_unrecognized() {
+        assert_eq!(resolve_date_time("", now()), None);
+        assert_eq!(resolve_date_time("whenever you can", now()), None);
+        assert_eq!(resolve_date_time("call 3 people", now()), None);
+    }
+}
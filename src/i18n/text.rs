@@ -0,0 +1,146 @@
+use unicode_normalization::{UnicodeNormalization, is_nfc_quick};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Marks text that was cut short
+pub const ELLIPSIS: char = '…';
+
+/// Composes text to NFC, so `e` + combining acute and a precomposed `é`
+/// compare, hash and count the same
+pub fn normalize(text: &str) -> String {
+    match is_nfc_quick(text.chars()) {
+        unicode_normalization::IsNormalized::Yes => text.to_string(),
+        _ => text.nfc().collect(),
+    }
+}
+
+/// Number of user-perceived characters
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// At most `max_graphemes` user-perceived characters of `text`, never
+/// splitting an accented letter or an emoji sequence
+pub fn truncate_graphemes(text: &str, max_graphemes: usize) -> &str {
+    match text.grapheme_indices(true).nth(max_graphemes) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// Short form of `text` for previews and log lines: at most `max_graphemes`
+/// characters, ending in `…` when something was cut
+pub fn preview(text: &str, max_graphemes: usize) -> String {
+    if max_graphemes == 0 {
+        return String::new();
+    }
+    let truncated = truncate_graphemes(text, max_graphemes);
+    if truncated.len() == text.len() {
+        return text.to_string();
+    }
+    let mut preview = truncate_graphemes(truncated, max_graphemes - 1)
+        .trim_end()
+        .to_string();
+    preview.push(ELLIPSIS);
+    preview
+}
+
+/// Base writing direction of a block of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextDirection {
+    Ltr,
+    Rtl,
+}
+
+impl TextDirection {
+    /// Value for an HTML `dir` attribute
+    pub fn as_html(&self) -> &'static str {
+        match self {
+            TextDirection::Ltr => "ltr",
+            TextDirection::Rtl => "rtl",
+        }
+    }
+}
+
+/// Direction of the first strongly directional letter, as browsers do for
+/// `dir="auto"`; text with no letters is left-to-right
+pub fn direction(text: &str) -> TextDirection {
+    text.chars()
+        .find(|c| c.is_alphabetic())
+        .filter(|c| is_rtl(*c))
+        .map_or(TextDirection::Ltr, |_| TextDirection::Rtl)
+}
+
+/// Hebrew, Arabic, Syriac, Thaana, N'Ko and their presentation forms
+fn is_rtl(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}'
+    )
+}
+
+/// Columns `text` takes in a terminal: CJK characters count two
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Longest prefix of `text` that fits in `columns`, cut at a grapheme
+/// boundary so wide characters are never split
+pub fn truncate_to_width(text: &str, columns: usize) -> &str {
+    let mut width = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        width += grapheme.width();
+        if width > columns {
+            return &text[..index];
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_composes() {
+        let decomposed = "Reunia\u{0303}o";
+
+        assert_eq!(normalize(decomposed), "Reunião");
+        assert_eq!(normalize("Reunião").len(), "Reunião".len());
+    }
+
+    #[test]
+    fn test_truncation_keeps_clusters_whole() {
+        let decomposed = "cafe\u{0301} 👍🏽 ok";
+
+        assert_eq!(truncate_graphemes(decomposed, 4), "cafe\u{0301}");
+        assert_eq!(truncate_graphemes(decomposed, 6), "cafe\u{0301} 👍🏽");
+        assert_eq!(truncate_graphemes("curto", 10), "curto");
+        assert_eq!(grapheme_count("👨‍👩‍👧 e você"), 8);
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview("Avise a Eva que vou atrasar", 10), "Avise a E…");
+        assert_eq!(preview("Ação", 4), "Ação");
+        assert_eq!(preview("Ação rápida", 5), "Ação…");
+        assert_eq!(preview("x", 0), "");
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(direction("שלום, Eva"), TextDirection::Rtl);
+        assert_eq!(direction("  مرحبا"), TextDirection::Rtl);
+        assert_eq!(direction("Eva: مرحبا"), TextDirection::Ltr);
+        assert_eq!(direction("会议推迟到明天"), TextDirection::Ltr);
+        assert_eq!(direction("123"), TextDirection::Ltr);
+    }
+
+    #[test]
+    fn test_cjk_width() {
+        assert_eq!(display_width("会议"), 4);
+        assert_eq!(display_width("Eva"), 3);
+        assert_eq!(truncate_to_width("会议推迟", 5), "会议");
+        assert_eq!(truncate_to_width("ab会", 3), "ab");
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::i18n::Locale;
+
+/// Register a draft is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tone {
+    /// Business English; "o senhor/a senhora" in Portuguese
+    #[default]
+    Formal,
+    /// Casual English; "você/tu" in Portuguese
+    Informal,
+}
+
+impl Tone {
+    /// Instruction telling the model how to write in this tone in `locale`
+    pub fn guidance(&self, locale: Locale) -> &'static str {
+        match (self, locale) {
+            (Tone::Formal, Locale::En) => {
+                "Use a business tone: a polite greeting and sign-off, complete sentences, no slang or contractions."
+            }
+            (Tone::Informal, Locale::En) => {
+                "Use a casual, friendly tone; contractions and a relaxed greeting are fine."
+            }
+            (Tone::Formal, Locale::PtBr) => {
+                "Use a formal register: address the recipient as \"o senhor\" or \"a senhora\" (or a courteous \"você\"), with a polite greeting and sign-off and no slang."
+            }
+            (Tone::Informal, Locale::PtBr) => {
+                "Use an informal register: address the recipient as \"você\" or \"tu\", friendly and relaxed."
+            }
+        }
+    }
+}
+
+impl fmt::Display for Tone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tone::Formal => write!(f, "formal"),
+            Tone::Informal => write!(f, "informal"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portuguese_guidance_names_the_pronouns() {
+        assert!(Tone::Formal.guidance(Locale::PtBr).contains("o senhor"));
+        assert!(Tone::Informal.guidance(Locale::PtBr).contains("tu"));
+    }
+
+    #[test]
+    fn test_serde() {
+        assert_eq!(
+            serde_json::from_str::<Tone>("\"informal\"").unwrap(),
+            Tone::Informal
+        );
+        assert_eq!(Tone::default(), Tone::Formal);
+    }
+}
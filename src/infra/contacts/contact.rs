@@ -0,0 +1,162 @@
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::action::QuietHours;
+use crate::i18n::{Locale, Tone};
+use crate::infra::email::EmailAddress;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContactEmail {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub address: EmailAddress,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Contact {
+    pub id: String,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub last_name: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub nickname: Option<String>,
+    #[serde(default)]
+    pub company: Option<String>,
+    #[serde(default)]
+    pub emails: Vec<ContactEmail>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Language emails to this contact are written in
+    #[serde(default)]
+    pub language: Option<Locale>,
+    /// Register drafts to this contact use
+    #[serde(default)]
+    pub tone: Option<Tone>,
+    /// IANA timezone the contact lives in, e.g. "Europe/Lisbon"
+    #[serde(default)]
+    pub timezone: Option<Tz>,
+    /// Overrides the configured quiet hours for this contact
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    /// PEM S/MIME certificate; mail to this contact can be encrypted
+    #[serde(default)]
+    pub certificate: Option<PathBuf>,
+    /// Id of the `[[accounts]]` entry mail to this contact is sent from
+    #[serde(default)]
+    pub account: Option<String>,
+}
+
+impl Contact {
+    /// Primary address, falling back to the first one listed
+    pub fn primary_email(&self) -> Option<&EmailAddress> {
+        self.emails
+            .iter()
+            .find(|e| e.primary)
+            .or_else(|| self.emails.first())
+            .map(|e| &e.address)
+    }
+
+    pub fn has_email(&self, address: &EmailAddress) -> bool {
+        self.emails.iter().any(|e| &e.address == address)
+    }
+
+    /// Locale to write dates, times and numbers in for this contact: their
+    /// preferred language, else the configured one
+    pub fn locale(&self) -> Locale {
+        self.language.unwrap_or_else(Locale::configured)
+    }
+
+    /// Case-insensitive match against display, first, last and nick names
+    pub fn matches_name(&self, name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            return false;
+        }
+        [
+            Some(self.display_name.as_str()),
+            Some(self.first_name.as_str()),
+            Some(self.last_name.as_str()),
+            self.nickname.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|candidate| candidate.to_lowercase() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact() -> Contact {
+        serde_json::from_str(
+            r#"{
+                "id": "c001",
+                "firstName": "Tiger",
+                "lastName": "Brilliant",
+                "displayName": "Tiger Brilliant",
+                "nickname": "Tiggy",
+                "emails": [
+                    {"type": "work", "address": "t.brilliant@TechSolutions.com", "primary": false},
+                    {"type": "personal", "address": "tiger.brilliant@gmail.com", "primary": true}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_primary_email() {
+        assert_eq!(
+            contact().primary_email().unwrap().to_string(),
+            "tiger.brilliant@gmail.com"
+        );
+    }
+
+    #[test]
+    fn test_addresses_are_normalized_on_load() {
+        let address = EmailAddress::parse("t.brilliant@techsolutions.com").unwrap();
+
+        assert!(contact().has_email(&address));
+    }
+
+    #[test]
+    fn test_matches_name() {
+        let contact = contact();
+
+        assert!(contact.matches_name("tiger"));
+        assert!(contact.matches_name("Tiger Brilliant"));
+        assert!(contact.matches_name(" TIGGY "));
+        assert!(!contact.matches_name("Eva"));
+        assert!(!contact.matches_name(""));
+    }
+
+    #[test]
+    fn test_language_and_tone_preferences() {
+        let eva: Contact = serde_json::from_str(
+            r#"{"id": "c1", "displayName": "Eva", "language": "pt-BR", "tone": "informal"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(eva.language, Some(Locale::PtBr));
+        assert_eq!(eva.locale(), Locale::PtBr);
+        assert_eq!(eva.tone, Some(Tone::Informal));
+        assert_eq!(contact().language, None);
+        assert_eq!(contact().tone, None);
+    }
+
+    #[test]
+    fn test_invalid_address_is_rejected() {
+        let result: Result<Contact, _> = serde_json::from_str(
+            r#"{"id": "c1", "displayName": "Broken", "emails": [{"type": "work", "address": "broken"}]}"#,
+        );
+
+        assert!(result.is_err());
+    }
+}
@@ -1 +1,5 @@
+pub mod contact;
 pub mod user_contacts;
+
+pub use contact::{Contact, ContactEmail};
+pub use user_contacts::UserContacts;
@@ -1 +1,71 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
 
+use crate::infra::contacts::Contact;
+use crate::infra::email::EmailAddress;
+
+/// Address book loaded from a `contacts.json` file
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UserContacts {
+    contacts: Vec<Contact>,
+}
+
+impl UserContacts {
+    pub fn new(contacts: Vec<Contact>) -> Self {
+        Self { contacts }
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        Ok(Self::from_json_str(&content)?)
+    }
+
+    pub fn from_json_str(json_str: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json_str)
+    }
+
+    pub fn contacts(&self) -> &[Contact] {
+        &self.contacts
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&Contact> {
+        self.contacts.iter().find(|c| c.matches_name(name))
+    }
+
+    pub fn find_by_email(&self, address: &EmailAddress) -> Option<&Contact> {
+        self.contacts.iter().find(|c| c.has_email(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_spec_contacts() {
+        let contacts = UserContacts::load_from_file("spec/contacts.json").unwrap();
+
+        assert!(!contacts.contacts().is_empty());
+        let turtle = contacts.find_by_name("Turtle").unwrap();
+        assert!(turtle.primary_email().is_some());
+    }
+
+    #[test]
+    fn test_find_by_email() {
+        let contacts = UserContacts::load_from_file("spec/contacts.json").unwrap();
+        let address = EmailAddress::parse("Tiger.Brilliant@GMAIL.com").unwrap();
+
+        // Local part is case-sensitive per RFC 5321, domain is not
+        assert!(contacts.find_by_email(&address).is_none());
+        let address = EmailAddress::parse("tiger.brilliant@GMAIL.com").unwrap();
+        assert_eq!(
+            contacts.find_by_email(&address).unwrap().display_name,
+            "Tiger Brilliant"
+        );
+    }
+
+    #[test]
+    fn test_missing_file() {
+        assert!(UserContacts::load_from_file("does_not_exist.json").is_err());
+    }
+}
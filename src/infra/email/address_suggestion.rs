@@ -0,0 +1,92 @@
+use deunicode::deunicode;
+
+use crate::infra::email::EmailAddress;
+
+/// ASCII spelling of `text`: `João Conceição` becomes `Joao Conceicao`
+/// and `王伟` becomes `Wang Wei`
+pub fn transliterate(text: &str) -> String {
+    deunicode(text)
+}
+
+/// Likely addresses for a person at `domain`, most common pattern first,
+/// with the name transliterated to an ASCII local part. These are guesses
+/// to offer the user, never to send to unconfirmed. IDN domains are kept
+/// in Unicode and punycoded by `EmailAddress::to_ascii` for transport.
+pub fn suggest_addresses(name: &str, domain: &str) -> Vec<EmailAddress> {
+    let ascii = transliterate(name).to_lowercase();
+    let parts: Vec<&str> = ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect();
+    let Some(first) = parts.first() else {
+        return Vec::new();
+    };
+
+    let mut local_parts = Vec::new();
+    match parts.last().filter(|_| parts.len() > 1) {
+        Some(last) => {
+            local_parts.push(format!("{}.{}", first, last));
+            local_parts.push(first.to_string());
+            local_parts.push(format!("{}{}", &first[..1], last));
+        }
+        None => local_parts.push(first.to_string()),
+    }
+
+    let mut suggestions: Vec<EmailAddress> = Vec::new();
+    for local_part in local_parts {
+        if let Ok(address) = EmailAddress::parse(&format!("{}@{}", local_part, domain))
+            && !suggestions.contains(&address)
+        {
+            suggestions.push(address);
+        }
+    }
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(addresses: Vec<EmailAddress>) -> Vec<String> {
+        addresses.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_transliterate() {
+        assert_eq!(transliterate("João Conceição"), "Joao Conceicao");
+        assert_eq!(transliterate("Zoë Ångström"), "Zoe Angstrom");
+    }
+
+    #[test]
+    fn test_suggestions_for_accented_name() {
+        assert_eq!(
+            strings(suggest_addresses("João da Conceição", "empresa.com.br")),
+            vec![
+                "joao.conceicao@empresa.com.br",
+                "joao@empresa.com.br",
+                "jconceicao@empresa.com.br"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_name_and_cjk() {
+        assert_eq!(
+            strings(suggest_addresses("Renée", "company.com")),
+            vec!["renee@company.com"]
+        );
+        assert_eq!(
+            suggest_addresses("王伟", "company.com")[0].to_string(),
+            "wang.wei@company.com"
+        );
+        assert!(suggest_addresses("—", "company.com").is_empty());
+    }
+
+    #[test]
+    fn test_idn_domain_is_punycoded_for_transport() {
+        let suggestion = &suggest_addresses("José Muñoz", "café.com")[0];
+
+        assert_eq!(suggestion.to_string(), "jose.munoz@café.com");
+        assert_eq!(suggestion.to_ascii(), "jose.munoz@xn--caf-dma.com");
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+
+use crate::infra::email::MimeMessage;
+
+/// Something that gets a message rejected or spam-foldered
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeliverabilityIssue {
+    /// `From`, `Date` or `Message-ID` is not set
+    MissingHeader(&'static str),
+    NoRecipients,
+    EmptySubject,
+    EmptyBody,
+    /// The Message-ID is not on the sender's domain
+    ForeignMessageId,
+    /// A `List-Unsubscribe` target that is neither `mailto:` nor `https:`
+    InvalidUnsubscribe(String),
+    /// One-click unsubscribe without an `https:` target to post to
+    OneClickWithoutHttps,
+}
+
+impl DeliverabilityIssue {
+    /// Issues that only hurt the spam score; the rest stop the send
+    pub fn is_warning(&self) -> bool {
+        matches!(self, DeliverabilityIssue::EmptySubject)
+    }
+}
+
+impl fmt::Display for DeliverabilityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliverabilityIssue::MissingHeader(name) => write!(f, "no {} header", name),
+            DeliverabilityIssue::NoRecipients => write!(f, "no recipients"),
+            DeliverabilityIssue::EmptySubject => write!(f, "empty subject"),
+            DeliverabilityIssue::EmptyBody => write!(f, "empty body"),
+            DeliverabilityIssue::ForeignMessageId => {
+                write!(f, "Message-ID is not on the sender's domain")
+            }
+            DeliverabilityIssue::InvalidUnsubscribe(target) => {
+                write!(
+                    f,
+                    "List-Unsubscribe target '{}' is not mailto: or https:",
+                    target
+                )
+            }
+            DeliverabilityIssue::OneClickWithoutHttps => {
+                write!(f, "one-click unsubscribe needs an https: target")
+            }
+        }
+    }
+}
+
+/// Checks `message` against what receiving servers expect of legitimate
+/// mail, before it is handed to the transport
+pub fn deliverability_issues(message: &MimeMessage) -> Vec<DeliverabilityIssue> {
+    let mut issues = Vec::new();
+    if message.from().is_none() {
+        issues.push(DeliverabilityIssue::MissingHeader("From"));
+    }
+    if message.date().is_none() {
+        issues.push(DeliverabilityIssue::MissingHeader("Date"));
+    }
+    match (message.message_id(), message.from()) {
+        (None, _) => issues.push(DeliverabilityIssue::MissingHeader("Message-ID")),
+        (Some(message_id), Some(from))
+            if !message_id
+                .rsplit_once('@')
+                .is_some_and(|(_, domain)| domain.eq_ignore_ascii_case(from.ascii_domain())) =>
+        {
+            issues.push(DeliverabilityIssue::ForeignMessageId)
+        }
+        _ => {}
+    }
+    if message.to().is_empty() {
+        issues.push(DeliverabilityIssue::NoRecipients);
+    }
+    if message.subject().trim().is_empty() {
+        issues.push(DeliverabilityIssue::EmptySubject);
+    }
+    if message.text().trim().is_empty() {
+        issues.push(DeliverabilityIssue::EmptyBody);
+    }
+    for target in message.list_unsubscribe() {
+        if !target.starts_with("mailto:") && !target.starts_with("https://") {
+            issues.push(DeliverabilityIssue::InvalidUnsubscribe(target.clone()));
+        }
+    }
+    if message.one_click_unsubscribe()
+        && !message
+            .list_unsubscribe()
+            .iter()
+            .any(|target| target.starts_with("https://"))
+    {
+        issues.push(DeliverabilityIssue::OneClickWithoutHttps);
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::email::EmailAddress;
+    use chrono::Utc;
+
+    fn address(text: &str) -> EmailAddress {
+        EmailAddress::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_complete_message_passes() {
+        let message = MimeMessage::new(vec![address("eva@company.com")], "Budget", "Attached.")
+            .with_from(address("agent@example.com"))
+            .with_message_id("18c.0001@example.com")
+            .with_date(Utc::now())
+            .with_list_unsubscribe(
+                &[
+                    "mailto:unsubscribe@example.com".to_string(),
+                    "https://example.com/unsubscribe".to_string(),
+                ],
+                true,
+            );
+
+        assert_eq!(deliverability_issues(&message), vec![]);
+    }
+
+    #[test]
+    fn test_hand_built_message_is_flagged() {
+        let message = MimeMessage::new(vec![], " ", "Hi")
+            .with_from(address("agent@example.com"))
+            .with_message_id("1@localhost")
+            .with_list_unsubscribe(&["http://example.com/unsubscribe".to_string()], true);
+
+        let issues = deliverability_issues(&message);
+
+        assert_eq!(
+            issues,
+            vec![
+                DeliverabilityIssue::MissingHeader("Date"),
+                DeliverabilityIssue::ForeignMessageId,
+                DeliverabilityIssue::NoRecipients,
+                DeliverabilityIssue::EmptySubject,
+                DeliverabilityIssue::InvalidUnsubscribe(
+                    "http://example.com/unsubscribe".to_string()
+                ),
+                DeliverabilityIssue::OneClickWithoutHttps,
+            ]
+        );
+        assert!(issues[3].is_warning());
+    }
+}
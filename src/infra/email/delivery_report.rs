@@ -0,0 +1,362 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+
+/// Where an email stands for one recipient, from the notifications that
+/// came back for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Accepted by our server; nothing heard back yet
+    Sent,
+    /// A server on the way is still retrying
+    Delayed,
+    Delivered,
+    /// Permanently rejected
+    Bounced,
+    /// The recipient opened it and sent a read receipt
+    Read,
+}
+
+impl DeliveryStatus {
+    /// Whether a notification saying `self` replaces `current`.
+    /// Notifications arrive out of order: a late "delayed" never undoes a
+    /// delivery, and nothing undoes a read receipt.
+    pub fn supersedes(self, current: DeliveryStatus) -> bool {
+        self != current && self.rank() >= current.rank()
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            DeliveryStatus::Sent => 0,
+            DeliveryStatus::Delayed => 1,
+            DeliveryStatus::Delivered | DeliveryStatus::Bounced => 2,
+            DeliveryStatus::Read => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    /// Delivery status notification (RFC 3464): bounces, delays and
+    /// delivery confirmations
+    DeliveryStatus,
+    /// Message disposition notification (RFC 8098)
+    ReadReceipt,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecipientReport {
+    pub recipient: String,
+    pub status: DeliveryStatus,
+    /// Enhanced status code, e.g. "5.1.1"
+    pub code: Option<String>,
+    /// What the remote server said
+    pub diagnostic: Option<String>,
+}
+
+/// A bounce or read receipt found in the inbox
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeliveryReport {
+    pub kind: ReportKind,
+    /// Message-ID of the email the report is about, without angle brackets
+    pub original_message_id: String,
+    /// Empty for receipts saying the email was deleted unread
+    pub recipients: Vec<RecipientReport>,
+}
+
+impl DeliveryReport {
+    /// Reads a `multipart/report` message. `None` for anything else,
+    /// including free-form bounces some servers still send, and for
+    /// reports that do not name the original Message-ID.
+    pub fn parse(source: &str) -> Option<Self> {
+        let source = source.replace("\r\n", "\n");
+        let (headers, body) = split_message(&source);
+        let content_type = field(&headers, "content-type")?;
+        if !media_type(content_type).eq_ignore_ascii_case("multipart/report") {
+            return None;
+        }
+        let boundary = parameter(content_type, "boundary")?;
+
+        let mut notification = None;
+        let mut original = Vec::new();
+        for part in split_parts(body, &boundary) {
+            let (part_headers, part_body) = split_message(part);
+            let part_type = field(&part_headers, "content-type")
+                .map(media_type)
+                .unwrap_or("text/plain")
+                .to_ascii_lowercase();
+            let part_body = decode(&part_headers, part_body);
+            match part_type.as_str() {
+                "message/delivery-status" | "message/global-delivery-status" => {
+                    notification = Some((ReportKind::DeliveryStatus, part_body))
+                }
+                "message/disposition-notification" | "message/global-disposition-notification" => {
+                    notification = Some((ReportKind::ReadReceipt, part_body))
+                }
+                "message/rfc822"
+                | "message/global"
+                | "text/rfc822-headers"
+                | "message/global-headers" => original = split_message(&part_body).0,
+                _ => {}
+            }
+        }
+        let (kind, fields) = notification?;
+        let groups = field_groups(&fields);
+
+        let recipients = match kind {
+            ReportKind::DeliveryStatus => {
+                groups.iter().skip(1).filter_map(recipient_status).collect()
+            }
+            ReportKind::ReadReceipt => groups.iter().filter_map(recipient_disposition).collect(),
+        };
+        let original_message_id = groups
+            .iter()
+            .find_map(|group| field(group, "original-message-id"))
+            .or_else(|| field(&original, "message-id"))
+            .or_else(|| field(&headers, "in-reply-to"))
+            .map(bare_message_id)
+            .filter(|id| !id.is_empty())?;
+
+        Some(Self {
+            kind,
+            original_message_id,
+            recipients,
+        })
+    }
+}
+
+type Fields = Vec<(String, String)>;
+
+/// Unfolded header fields, and the body after the blank line
+fn split_message(source: &str) -> (Fields, &str) {
+    let (head, body) = match source.find("\n\n") {
+        Some(end) => (&source[..end], &source[end + 2..]),
+        None => (source, ""),
+    };
+    (parse_fields(head), body)
+}
+
+fn parse_fields(text: &str) -> Fields {
+    let mut fields: Fields = Vec::new();
+    for line in text.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            fields.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    fields
+}
+
+/// Blocks of fields separated by blank lines, as in a delivery status body
+fn field_groups(text: &str) -> Vec<Fields> {
+    text.split("\n\n")
+        .map(parse_fields)
+        .filter(|fields| !fields.is_empty())
+        .collect()
+}
+
+fn field<'a>(fields: &'a Fields, name: &str) -> Option<&'a str> {
+    fields
+        .iter()
+        .find(|(field, _)| field == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn media_type(content_type: &str) -> &str {
+    content_type.split(';').next().unwrap_or_default().trim()
+}
+
+fn parameter(content_type: &str, name: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|parameter| {
+        let (key, value) = parameter.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Bodies between the `--boundary` lines
+fn split_parts<'a>(body: &'a str, boundary: &str) -> Vec<&'a str> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed.starts_with(&delimiter) {
+            if let Some(start) = start {
+                parts.push(&body[start..offset]);
+            }
+            if trimmed[delimiter.len()..].starts_with("--") {
+                return parts;
+            }
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    parts
+}
+
+fn decode(headers: &Fields, body: &str) -> String {
+    let base64 = field(headers, "content-transfer-encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("base64"));
+    if !base64 {
+        return body.to_string();
+    }
+    let compact: String = body.split_whitespace().collect();
+    STANDARD
+        .decode(compact)
+        .map(|bytes| String::from_utf8_lossy(&bytes).replace("\r\n", "\n"))
+        .unwrap_or_default()
+}
+
+/// Address of `rfc822; eva@example.com`
+fn address(value: &str) -> String {
+    let address = value.split_once(';').map_or(value, |(_, address)| address);
+    address.trim().trim_matches(['<', '>']).to_string()
+}
+
+fn bare_message_id(value: &str) -> String {
+    // In-Reply-To may hold a comment after the id
+    let value = value.split_whitespace().next().unwrap_or_default();
+    value.trim_matches(['<', '>']).to_string()
+}
+
+fn recipient(fields: &Fields) -> Option<String> {
+    field(fields, "final-recipient")
+        .or_else(|| field(fields, "original-recipient"))
+        .map(address)
+}
+
+fn recipient_status(fields: &Fields) -> Option<RecipientReport> {
+    let status = match field(fields, "action")?.to_ascii_lowercase().as_str() {
+        "failed" => DeliveryStatus::Bounced,
+        "delayed" => DeliveryStatus::Delayed,
+        "delivered" | "relayed" | "expanded" => DeliveryStatus::Delivered,
+        _ => return None,
+    };
+    Some(RecipientReport {
+        recipient: recipient(fields)?,
+        status,
+        code: field(fields, "status").map(str::to_string),
+        diagnostic: field(fields, "diagnostic-code").map(address),
+    })
+}
+
+/// Only "displayed" tells anything about delivery; "deleted" and the rest
+/// are dropped
+fn recipient_disposition(fields: &Fields) -> Option<RecipientReport> {
+    let disposition = field(fields, "disposition")?;
+    let (_, kind) = disposition.split_once(';')?;
+    let kind = kind.split('/').next().unwrap_or_default().trim();
+    if !kind.eq_ignore_ascii_case("displayed") {
+        return None;
+    }
+    Some(RecipientReport {
+        recipient: recipient(fields)?,
+        status: DeliveryStatus::Read,
+        code: None,
+        diagnostic: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNCE: &str = "From: MAILER-DAEMON@mx.example.com\r\n\
+Subject: Undelivered Mail Returned to Sender\r\n\
+Content-Type: multipart/report; report-type=delivery-status;\r\n\
+\tboundary=\"B0UND\"\r\n\
+\r\n\
+--B0UND\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+Your message could not be delivered.\r\n\
+--B0UND\r\n\
+Content-Type: message/delivery-status\r\n\
+\r\n\
+Reporting-MTA: dns; mx.example.com\r\n\
+\r\n\
+Final-Recipient: rfc822; bounce@example.com\r\n\
+Action: failed\r\n\
+Status: 5.1.1\r\n\
+Diagnostic-Code: smtp; 550 5.1.1 No such user\r\n\
+\r\n\
+Final-Recipient: rfc822; eva@example.com\r\n\
+Action: delayed\r\n\
+Status: 4.4.1\r\n\
+\r\n\
+--B0UND\r\n\
+Content-Type: text/rfc822-headers\r\n\
+\r\n\
+From: agent@example.com\r\n\
+Message-ID: <18f3a.0001@example.com>\r\n\
+Subject: Lunch\r\n\
+\r\n\
+--B0UND--\r\n";
+
+    #[test]
+    fn test_parse_bounce() {
+        let report = DeliveryReport::parse(BOUNCE).unwrap();
+
+        assert_eq!(report.kind, ReportKind::DeliveryStatus);
+        assert_eq!(report.original_message_id, "18f3a.0001@example.com");
+        assert_eq!(report.recipients.len(), 2);
+        assert_eq!(report.recipients[0].recipient, "bounce@example.com");
+        assert_eq!(report.recipients[0].status, DeliveryStatus::Bounced);
+        assert_eq!(report.recipients[0].code.as_deref(), Some("5.1.1"));
+        assert_eq!(
+            report.recipients[0].diagnostic.as_deref(),
+            Some("550 5.1.1 No such user")
+        );
+        assert_eq!(report.recipients[1].status, DeliveryStatus::Delayed);
+    }
+
+    #[test]
+    fn test_parse_read_receipt() {
+        let receipt = "Content-Type: multipart/report; report-type=disposition-notification; boundary=mdn\n\
+\n\
+--mdn\n\
+Content-Type: text/plain\n\
+\n\
+Your message was displayed.\n\
+--mdn\n\
+Content-Type: message/disposition-notification\n\
+\n\
+Reporting-UA: mail.example.com; Webmail\n\
+Final-Recipient: rfc822;eva@example.com\n\
+Original-Message-ID: <18f3a.0001@example.com>\n\
+Disposition: manual-action/MDN-sent-manually; displayed\n\
+--mdn--\n";
+        let deleted = receipt.replace("; displayed", "; deleted");
+
+        let report = DeliveryReport::parse(receipt).unwrap();
+
+        assert_eq!(report.kind, ReportKind::ReadReceipt);
+        assert_eq!(report.original_message_id, "18f3a.0001@example.com");
+        assert_eq!(report.recipients[0].recipient, "eva@example.com");
+        assert_eq!(report.recipients[0].status, DeliveryStatus::Read);
+        assert!(
+            DeliveryReport::parse(&deleted)
+                .unwrap()
+                .recipients
+                .is_empty()
+        );
+        assert!(DeliveryReport::parse("Subject: Hi\n\nHello").is_none());
+    }
+
+    #[test]
+    fn test_status_order() {
+        assert!(DeliveryStatus::Delivered.supersedes(DeliveryStatus::Delayed));
+        assert!(DeliveryStatus::Read.supersedes(DeliveryStatus::Delivered));
+        assert!(!DeliveryStatus::Delayed.supersedes(DeliveryStatus::Delivered));
+        assert!(!DeliveryStatus::Bounced.supersedes(DeliveryStatus::Read));
+    }
+}
@@ -0,0 +1,390 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+const MAX_LOCAL_PART_LEN: usize = 64;
+const MAX_DOMAIN_LEN: usize = 253;
+const MAX_LABEL_LEN: usize = 63;
+const LOCAL_PART_SPECIALS: &str = "!#$%&'*+-/=?^_`{|}~";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmailAddressError {
+    Empty,
+    MissingAt,
+    InvalidLocalPart(String),
+    InvalidDomain(String),
+}
+
+impl fmt::Display for EmailAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmailAddressError::Empty => write!(f, "Email address is empty"),
+            EmailAddressError::MissingAt => write!(f, "Email address is missing '@'"),
+            EmailAddressError::InvalidLocalPart(msg) => write!(f, "Invalid local part: {}", msg),
+            EmailAddressError::InvalidDomain(msg) => write!(f, "Invalid domain: {}", msg),
+        }
+    }
+}
+
+impl Error for EmailAddressError {}
+
+/// A syntactically valid, normalized email address.
+///
+/// Accepts RFC 5321/6531 addresses (dot-atom or quoted local part, UTF-8
+/// allowed) and the `Name <addr>` / `mailto:` forms models tend to emit.
+/// The domain is lowercased and kept in Unicode form; `ascii_domain()`
+/// returns its punycode representation for transport.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct EmailAddress {
+    local_part: String,
+    domain: String,
+    ascii_domain: String,
+}
+
+impl EmailAddress {
+    pub fn parse(input: &str) -> Result<Self, EmailAddressError> {
+        let candidate = strip_decorations(input);
+        if candidate.is_empty() {
+            return Err(EmailAddressError::Empty);
+        }
+
+        let at = candidate.rfind('@').ok_or(EmailAddressError::MissingAt)?;
+        let (local_part, domain) = (&candidate[..at], &candidate[at + 1..]);
+
+        validate_local_part(local_part)?;
+        let (domain, ascii_domain) = normalize_domain(domain)?;
+
+        Ok(Self {
+            local_part: local_part.to_string(),
+            domain,
+            ascii_domain,
+        })
+    }
+
+    pub fn local_part(&self) -> &str {
+        &self.local_part
+    }
+
+    /// Lowercased domain in Unicode form
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Domain encoded as punycode (IDNA), suitable for SMTP and DNS
+    pub fn ascii_domain(&self) -> &str {
+        &self.ascii_domain
+    }
+
+    /// Full address with the punycode domain
+    pub fn to_ascii(&self) -> String {
+        format!("{}@{}", self.local_part, self.ascii_domain)
+    }
+
+    /// Returns true if the input looks like an address rather than a name
+    pub fn looks_like_address(input: &str) -> bool {
+        input.contains('@')
+    }
+}
+
+impl fmt::Display for EmailAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.local_part, self.domain)
+    }
+}
+
+impl FromStr for EmailAddress {
+    type Err = EmailAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for EmailAddress {
+    type Error = EmailAddressError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<EmailAddress> for String {
+    fn from(value: EmailAddress) -> Self {
+        value.to_string()
+    }
+}
+
+/// Trims whitespace and removes `Name <...>`, `mailto:` and trailing dots
+fn strip_decorations(input: &str) -> &str {
+    let mut candidate = input.trim();
+    if let (Some(start), Some(end)) = (candidate.rfind('<'), candidate.rfind('>'))
+        && start < end
+    {
+        candidate = candidate[start + 1..end].trim();
+    }
+    if candidate
+        .get(..7)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+    {
+        candidate = candidate[7..].trim();
+    }
+    candidate.trim_end_matches('.')
+}
+
+fn validate_local_part(local_part: &str) -> Result<(), EmailAddressError> {
+    if local_part.is_empty() {
+        return Err(EmailAddressError::InvalidLocalPart("empty".to_string()));
+    }
+    if local_part.len() > MAX_LOCAL_PART_LEN {
+        return Err(EmailAddressError::InvalidLocalPart(format!(
+            "longer than {} bytes",
+            MAX_LOCAL_PART_LEN
+        )));
+    }
+
+    if local_part.len() >= 2 && local_part.starts_with('"') && local_part.ends_with('"') {
+        return validate_quoted_local_part(&local_part[1..local_part.len() - 1]);
+    }
+
+    if local_part.starts_with('.') || local_part.ends_with('.') || local_part.contains("..") {
+        return Err(EmailAddressError::InvalidLocalPart(format!(
+            "misplaced '.' in '{}'",
+            local_part
+        )));
+    }
+
+    match local_part
+        .chars()
+        .find(|c| !(c.is_alphanumeric() || *c == '.' || LOCAL_PART_SPECIALS.contains(*c)))
+    {
+        Some(c) => Err(EmailAddressError::InvalidLocalPart(format!(
+            "character '{}' is not allowed",
+            c
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn validate_quoted_local_part(inner: &str) -> Result<(), EmailAddressError> {
+    let mut escaped = false;
+    for c in inner.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '"' => {
+                return Err(EmailAddressError::InvalidLocalPart(
+                    "unescaped quote".to_string(),
+                ));
+            }
+            c if c.is_control() => {
+                return Err(EmailAddressError::InvalidLocalPart(
+                    "control character".to_string(),
+                ));
+            }
+            _ => {}
+        }
+    }
+    if escaped {
+        return Err(EmailAddressError::InvalidLocalPart(
+            "dangling escape".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the lowercased Unicode domain and its punycode form
+fn normalize_domain(domain: &str) -> Result<(String, String), EmailAddressError> {
+    if domain.is_empty() {
+        return Err(EmailAddressError::InvalidDomain("empty".to_string()));
+    }
+
+    let ascii = idna::domain_to_ascii(domain).map_err(|_| {
+        EmailAddressError::InvalidDomain(format!("'{}' is not a valid IDN", domain))
+    })?;
+
+    if ascii.len() > MAX_DOMAIN_LEN {
+        return Err(EmailAddressError::InvalidDomain(format!(
+            "longer than {} bytes",
+            MAX_DOMAIN_LEN
+        )));
+    }
+
+    let labels: Vec<&str> = ascii.split('.').collect();
+    if labels.len() < 2 {
+        return Err(EmailAddressError::InvalidDomain(format!(
+            "'{}' has no top-level domain",
+            domain
+        )));
+    }
+    for label in &labels {
+        let valid = !label.is_empty()
+            && label.len() <= MAX_LABEL_LEN
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if !valid {
+            return Err(EmailAddressError::InvalidDomain(format!(
+                "invalid label '{}' in '{}'",
+                label, domain
+            )));
+        }
+    }
+
+    let (unicode, result) = idna::domain_to_unicode(&ascii);
+    if result.is_err() {
+        return Err(EmailAddressError::InvalidDomain(format!(
+            "'{}' is not a valid IDN",
+            domain
+        )));
+    }
+
+    Ok((unicode, ascii))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_address() {
+        let address = EmailAddress::parse("eva@company.com").unwrap();
+
+        assert_eq!(address.local_part(), "eva");
+        assert_eq!(address.domain(), "company.com");
+        assert_eq!(address.to_string(), "eva@company.com");
+    }
+
+    #[test]
+    fn test_normalization_trims_and_lowercases_domain() {
+        let address = EmailAddress::parse("  John.Doe@Example.COM. ").unwrap();
+
+        assert_eq!(address.to_string(), "John.Doe@example.com");
+    }
+
+    #[test]
+    fn test_parse_display_name_and_mailto_forms() {
+        let named = EmailAddress::parse("Eva Green <eva@company.com>").unwrap();
+        let mailto = EmailAddress::parse("mailto:eva@company.com").unwrap();
+
+        assert_eq!(named.to_string(), "eva@company.com");
+        assert_eq!(mailto, named);
+    }
+
+    #[test]
+    fn test_plus_tags_and_specials_are_allowed() {
+        assert!(EmailAddress::parse("test+tag@example.com").is_ok());
+        assert!(EmailAddress::parse("o'brien@example.com").is_ok());
+        assert!(EmailAddress::parse("\"john doe\"@example.com").is_ok());
+    }
+
+    #[test]
+    fn test_unicode_local_part() {
+        let address = EmailAddress::parse("用户@example.com").unwrap();
+
+        assert_eq!(address.local_part(), "用户");
+    }
+
+    #[test]
+    fn test_multibyte_char_at_scheme_boundary() {
+        // Byte 7 falls inside "用"
+        let address = EmailAddress::parse("ab用户@example.com").unwrap();
+
+        assert_eq!(address.local_part(), "ab用户");
+        assert_eq!(
+            EmailAddress::parse("MAILTO:ab用户@example.com").unwrap(),
+            address
+        );
+    }
+
+    #[test]
+    fn test_idn_domain_is_punycoded() {
+        let address = EmailAddress::parse("joão@Café.com.br").unwrap();
+
+        assert_eq!(address.domain(), "café.com.br");
+        assert_eq!(address.ascii_domain(), "xn--caf-dma.com.br");
+        assert_eq!(address.to_ascii(), "joão@xn--caf-dma.com.br");
+    }
+
+    #[test]
+    fn test_punycode_input_is_shown_as_unicode() {
+        let address = EmailAddress::parse("ana@xn--caf-dma.com.br").unwrap();
+
+        assert_eq!(address.domain(), "café.com.br");
+    }
+
+    #[test]
+    fn test_rejects_garbage() {
+        assert_eq!(EmailAddress::parse(""), Err(EmailAddressError::Empty));
+        assert_eq!(
+            EmailAddress::parse("Eva"),
+            Err(EmailAddressError::MissingAt)
+        );
+        assert!(matches!(
+            EmailAddress::parse("@example.com"),
+            Err(EmailAddressError::InvalidLocalPart(_))
+        ));
+        assert!(matches!(
+            EmailAddress::parse("eva@"),
+            Err(EmailAddressError::InvalidDomain(_))
+        ));
+        assert!(matches!(
+            EmailAddress::parse("eva@localhost"),
+            Err(EmailAddressError::InvalidDomain(_))
+        ));
+        assert!(matches!(
+            EmailAddress::parse("eva..green@example.com"),
+            Err(EmailAddressError::InvalidLocalPart(_))
+        ));
+        assert!(matches!(
+            EmailAddress::parse("eva green@example.com"),
+            Err(EmailAddressError::InvalidLocalPart(_))
+        ));
+        assert!(matches!(
+            EmailAddress::parse("eva@-example.com"),
+            Err(EmailAddressError::InvalidDomain(_))
+        ));
+        assert!(matches!(
+            EmailAddress::parse("eva@exa_mple.com"),
+            Err(EmailAddressError::InvalidDomain(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_overlong_local_part() {
+        let address = format!("{}@example.com", "a".repeat(65));
+
+        assert!(matches!(
+            EmailAddress::parse(&address),
+            Err(EmailAddressError::InvalidLocalPart(_))
+        ));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let address = EmailAddress::parse("eva@Company.com").unwrap();
+        let json = serde_json::to_string(&address).unwrap();
+
+        assert_eq!(json, r#""eva@company.com""#);
+        let deserialized: EmailAddress = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, address);
+    }
+
+    #[test]
+    fn test_deserialization_rejects_invalid() {
+        let result: Result<EmailAddress, _> = serde_json::from_str(r#""not an address""#);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        let address: EmailAddress = "eva@company.com".parse().unwrap();
+
+        assert_eq!(address.domain(), "company.com");
+    }
+}
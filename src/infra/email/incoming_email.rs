@@ -0,0 +1,203 @@
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use mail_parser::{Address, MessageParser};
+use serde::Serialize;
+
+use crate::infra::email::EmailAddress;
+
+/// A message received in a mailbox, reduced to what the agents read
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IncomingEmail {
+    /// IMAP UID in the mailbox it was read from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+    /// Without the angle brackets
+    pub message_id: Option<String>,
+    pub from: Option<EmailAddress>,
+    /// Display name of the sender, e.g. "Eva Souza"
+    pub from_name: Option<String>,
+    pub to: Vec<EmailAddress>,
+    pub subject: String,
+    pub date: Option<DateTime<FixedOffset>>,
+    /// Message-ID of the email this one answers, without angle brackets
+    pub in_reply_to: Option<String>,
+    /// The text/plain part, else the text/html one without its tags
+    pub text: String,
+}
+
+impl IncomingEmail {
+    /// Reads an RFC 5322 message. `None` when it has no header fields.
+    pub fn parse(source: impl AsRef<[u8]>) -> Option<Self> {
+        let message = MessageParser::default().parse(source.as_ref())?;
+        if message.headers().is_empty() {
+            return None;
+        }
+        let sender = message.from().and_then(Address::first);
+        let text = message.text_part(0).and_then(|part| {
+            let text = part.text_contents()?;
+            Some(if part.is_text_html() {
+                html_text(text)
+            } else {
+                text.replace("\r\n", "\n")
+            })
+        });
+        Some(Self {
+            uid: None,
+            message_id: message.message_id().map(str::to_string),
+            from: sender
+                .and_then(|addr| addr.address())
+                .and_then(|address| EmailAddress::parse(address).ok()),
+            from_name: sender
+                .and_then(|addr| addr.name())
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+            to: message
+                .all_to()
+                .flat_map(Address::iter)
+                .filter_map(|addr| EmailAddress::parse(addr.address()?).ok())
+                .collect(),
+            subject: message.subject().unwrap_or_default().to_string(),
+            date: message.date().and_then(|date| {
+                let seconds = i32::from(date.tz_hour) * 3600 + i32::from(date.tz_minute) * 60;
+                let offset = FixedOffset::east_opt(if date.tz_before_gmt {
+                    -seconds
+                } else {
+                    seconds
+                })?;
+                NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())?
+                    .and_hms_opt(date.hour.into(), date.minute.into(), date.second.into())?
+                    .and_local_timezone(offset)
+                    .single()
+            }),
+            in_reply_to: message
+                .in_reply_to()
+                .as_text_list()
+                .and_then(|ids| ids.first())
+                .map(|id| id.to_string()),
+            text: text.unwrap_or_default(),
+        })
+    }
+
+    pub fn with_uid(mut self, uid: u32) -> Self {
+        self.uid = Some(uid);
+        self
+    }
+
+    /// Sender, subject and text, as classified
+    pub fn classifier_input(&self) -> String {
+        let from = match (&self.from_name, &self.from) {
+            (Some(name), Some(address)) => format!("{} <{}>", name, address),
+            (None, Some(address)) => address.to_string(),
+            (Some(name), None) => name.clone(),
+            (None, None) => String::new(),
+        };
+        format!(
+            "From: {}\nSubject: {}\n\n{}",
+            from,
+            self.subject,
+            self.text.trim()
+        )
+    }
+}
+
+/// Text of an HTML body: tags and blank lines dropped, line breaks kept,
+/// common entities decoded
+fn html_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim_start_matches('/');
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if matches!(name.as_str(), "br" | "p" | "div" | "li" | "tr") {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipart_alternative_prefers_plain_text() {
+        let source = "From: =?UTF-8?Q?Eva_Gon=C3=A7alves?= <eva@company.com>\r\n\
+            To: agent@example.com, \"Souza, Bruno\" <bruno@company.com>\r\n\
+            Subject: =?UTF-8?B?UmV1bmnDo28=?= =?UTF-8?B?IGFtYW5ow6M=?=\r\n\
+            Date: Wed, 4 Jun 2025 09:00:00 -0300\r\n\
+            Message-ID: <abc.123@company.com>\r\n\
+            Content-Type: multipart/alternative; boundary=\"b1\"\r\n\
+            \r\n\
+            --b1\r\n\
+            Content-Type: text/html; charset=utf-8\r\n\
+            \r\n\
+            <p>Ignored</p>\r\n\
+            --b1\r\n\
+            Content-Type: text/plain; charset=iso-8859-1\r\n\
+            Content-Transfer-Encoding: quoted-printable\r\n\
+            \r\n\
+            Podemos marcar a reuni=E3o para amanh=E3 =\r\n\
+            =E0s 10h?\r\n\
+            --b1--\r\n";
+
+        let email = IncomingEmail::parse(source).unwrap();
+
+        assert_eq!(email.from_name.as_deref(), Some("Eva Gonçalves"));
+        assert_eq!(email.from.unwrap().to_string(), "eva@company.com");
+        assert_eq!(email.to.len(), 2);
+        assert_eq!(email.subject, "Reunião amanhã");
+        assert_eq!(email.message_id.as_deref(), Some("abc.123@company.com"));
+        assert_eq!(
+            email.date.unwrap().to_rfc3339(),
+            "2025-06-04T09:00:00-03:00"
+        );
+        assert_eq!(
+            email.text.trim(),
+            "Podemos marcar a reunião para amanhã às 10h?"
+        );
+    }
+
+    #[test]
+    fn test_html_only_message_is_stripped() {
+        let source = "From: bruno@company.com\n\
+            Subject: Lunch\n\
+            In-Reply-To: <18c.0001@example.com>\n\
+            Content-Type: text/html\n\
+            \n\
+            <div>Lunch at <b>noon</b>?</div><div>Fish &amp; chips</div>\n";
+
+        let email = IncomingEmail::parse(source).unwrap().with_uid(7);
+
+        assert_eq!(email.text, "Lunch at noon?\nFish & chips");
+        assert_eq!(email.in_reply_to.as_deref(), Some("18c.0001@example.com"));
+        assert_eq!(
+            email.classifier_input(),
+            "From: bruno@company.com\nSubject: Lunch\n\nLunch at noon?\nFish & chips"
+        );
+        assert!(IncomingEmail::parse("").is_none());
+    }
+}
@@ -0,0 +1,592 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::i18n::text::{direction, normalize};
+use crate::infra::email::EmailAddress;
+#[cfg(feature = "smime")]
+use crate::infra::email::{SmimeError, SmimeProtection};
+use crate::safety::attachment_guard::mime_type_for;
+
+/// Longest encoded word allowed by RFC 2047
+const MAX_ENCODED_WORD: usize = 75;
+/// Base64 body lines are wrapped at this length (RFC 2045)
+const BASE64_LINE: usize = 76;
+/// Longest line SMTP carries, without the CRLF (RFC 5322)
+const MAX_LINE: usize = 998;
+
+/// `Auto-Submitted` value (RFC 3834), telling mail servers and
+/// autoresponders the message was not written by hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AutoSubmitted {
+    AutoGenerated,
+    AutoReplied,
+}
+
+impl AutoSubmitted {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutoSubmitted::AutoGenerated => "auto-generated",
+            AutoSubmitted::AutoReplied => "auto-replied",
+        }
+    }
+}
+
+/// A file sent along with a message
+#[derive(Debug, Clone, PartialEq)]
+pub struct MimeAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl MimeAttachment {
+    pub fn new(filename: &str, content_type: &str, data: Vec<u8>) -> Self {
+        Self {
+            filename: filename.to_string(),
+            content_type: content_type.to_string(),
+            data,
+        }
+    }
+
+    /// Reads `path`, typed by its extension
+    pub fn from_file(path: &Path) -> std::io::Result<Self> {
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Self::new(
+            &filename,
+            mime_type_for(path),
+            std::fs::read(path)?,
+        ))
+    }
+}
+
+/// A draft rendered as an RFC 5322 message. Non-ASCII headers become
+/// RFC 2047 encoded words and non-ASCII bodies are sent as base64 UTF-8,
+/// so Arabic, Hebrew or Chinese text arrives intact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MimeMessage {
+    from: Option<EmailAddress>,
+    to: Vec<EmailAddress>,
+    cc: Vec<EmailAddress>,
+    /// In the envelope only; never rendered
+    bcc: Vec<EmailAddress>,
+    subject: String,
+    text: String,
+    html: Option<String>,
+    /// Without the angle brackets
+    message_id: Option<String>,
+    date: Option<DateTime<Utc>>,
+    list_unsubscribe: Vec<String>,
+    one_click_unsubscribe: bool,
+    auto_submitted: Option<AutoSubmitted>,
+    attachments: Vec<MimeAttachment>,
+}
+
+impl MimeMessage {
+    pub fn new(to: Vec<EmailAddress>, subject: &str, text: &str) -> Self {
+        Self {
+            from: None,
+            to,
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            subject: normalize(subject),
+            text: normalize(text),
+            html: None,
+            message_id: None,
+            date: None,
+            list_unsubscribe: Vec::new(),
+            one_click_unsubscribe: false,
+            auto_submitted: None,
+            attachments: Vec::new(),
+        }
+    }
+
+    pub fn with_cc(mut self, cc: Vec<EmailAddress>) -> Self {
+        self.cc = cc;
+        self
+    }
+
+    pub fn with_bcc(mut self, bcc: Vec<EmailAddress>) -> Self {
+        self.bcc = bcc;
+        self
+    }
+
+    /// Sends the body as the first part of a `multipart/mixed` message
+    /// with `attachment` after it
+    pub fn with_attachment(mut self, attachment: MimeAttachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    pub fn with_from(mut self, from: EmailAddress) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn with_message_id(mut self, message_id: &str) -> Self {
+        self.message_id = Some(message_id.to_string());
+        self
+    }
+
+    pub fn with_date(mut self, date: DateTime<Utc>) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    /// `mailto:` or `https:` targets for `List-Unsubscribe`; `one_click`
+    /// adds `List-Unsubscribe-Post` (RFC 8058)
+    pub fn with_list_unsubscribe(mut self, targets: &[String], one_click: bool) -> Self {
+        self.list_unsubscribe = targets.to_vec();
+        self.one_click_unsubscribe = one_click;
+        self
+    }
+
+    pub fn with_auto_submitted(mut self, auto_submitted: AutoSubmitted) -> Self {
+        self.auto_submitted = Some(auto_submitted);
+        self
+    }
+
+    /// Adds an HTML alternative generated from the text body
+    pub fn with_html(mut self) -> Self {
+        self.html = Some(text_to_html(&self.text));
+        self
+    }
+
+    pub fn to(&self) -> &[EmailAddress] {
+        &self.to
+    }
+
+    pub fn cc(&self) -> &[EmailAddress] {
+        &self.cc
+    }
+
+    pub fn bcc(&self) -> &[EmailAddress] {
+        &self.bcc
+    }
+
+    /// Everyone the message is delivered to: To, Cc and Bcc
+    pub fn recipients(&self) -> Vec<EmailAddress> {
+        self.to
+            .iter()
+            .chain(&self.cc)
+            .chain(&self.bcc)
+            .cloned()
+            .collect()
+    }
+
+    pub fn attachments(&self) -> &[MimeAttachment] {
+        &self.attachments
+    }
+
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn html(&self) -> Option<&str> {
+        self.html.as_deref()
+    }
+
+    pub fn from(&self) -> Option<&EmailAddress> {
+        self.from.as_ref()
+    }
+
+    pub fn message_id(&self) -> Option<&str> {
+        self.message_id.as_deref()
+    }
+
+    pub fn date(&self) -> Option<DateTime<Utc>> {
+        self.date
+    }
+
+    pub fn list_unsubscribe(&self) -> &[String] {
+        &self.list_unsubscribe
+    }
+
+    pub fn one_click_unsubscribe(&self) -> bool {
+        self.one_click_unsubscribe
+    }
+
+    /// Message source with CRLF line endings
+    pub fn render(&self) -> String {
+        let mut message = self.headers();
+        header(&mut message, "MIME-Version", "1.0");
+        message.push_str(&self.entity());
+        message
+    }
+
+    /// Like `render`, with the content signed and/or encrypted. The
+    /// addressing headers stay readable.
+    #[cfg(feature = "smime")]
+    pub fn render_protected(&self, protection: &SmimeProtection) -> Result<String, SmimeError> {
+        if protection.is_empty() {
+            return Ok(self.render());
+        }
+        let protected = protection.protect(self.entity().as_bytes())?;
+        Ok(format!("{}{}", self.headers(), protected))
+    }
+
+    fn headers(&self) -> String {
+        let mut message = String::new();
+        if let Some(date) = &self.date {
+            header(&mut message, "Date", &date.to_rfc2822());
+        }
+        if let Some(message_id) = &self.message_id {
+            header(&mut message, "Message-ID", &format!("<{}>", message_id));
+        }
+        if let Some(from) = &self.from {
+            header(&mut message, "From", &from.to_ascii());
+        }
+        let to: Vec<String> = self.to.iter().map(EmailAddress::to_ascii).collect();
+        header(&mut message, "To", &to.join(", "));
+        if !self.cc.is_empty() {
+            let cc: Vec<String> = self.cc.iter().map(EmailAddress::to_ascii).collect();
+            header(&mut message, "Cc", &cc.join(", "));
+        }
+        header(&mut message, "Subject", &encode_header(&self.subject));
+        if let Some(auto_submitted) = &self.auto_submitted {
+            header(&mut message, "Auto-Submitted", auto_submitted.as_str());
+        }
+        if !self.list_unsubscribe.is_empty() {
+            let targets: Vec<String> = self
+                .list_unsubscribe
+                .iter()
+                .map(|target| format!("<{}>", target))
+                .collect();
+            header(&mut message, "List-Unsubscribe", &targets.join(", "));
+            if self.one_click_unsubscribe {
+                header(
+                    &mut message,
+                    "List-Unsubscribe-Post",
+                    "List-Unsubscribe=One-Click",
+                );
+            }
+        }
+        message
+    }
+
+    /// Content headers and body: the part S/MIME signs or encrypts
+    fn entity(&self) -> String {
+        if self.attachments.is_empty() {
+            return self.body();
+        }
+        let boundary = format!("{}_mixed", boundary(&self.text));
+        let mut message = String::new();
+        header(
+            &mut message,
+            "Content-Type",
+            &format!("multipart/mixed; boundary=\"{}\"", boundary),
+        );
+        message.push_str("\r\n");
+        let _ = write!(message, "--{}\r\n", boundary);
+        message.push_str(&self.body());
+        for attachment in &self.attachments {
+            let _ = write!(message, "--{}\r\n", boundary);
+            attachment_part(&mut message, attachment);
+        }
+        let _ = write!(message, "--{}--\r\n", boundary);
+        message
+    }
+
+    /// The text, with its HTML alternative if there is one
+    fn body(&self) -> String {
+        let mut message = String::new();
+        match &self.html {
+            None => body_part(&mut message, "text/plain", &self.text),
+            Some(html) => {
+                let boundary = boundary(&self.text);
+                header(
+                    &mut message,
+                    "Content-Type",
+                    &format!("multipart/alternative; boundary=\"{}\"", boundary),
+                );
+                message.push_str("\r\n");
+                for (content_type, body) in [("text/plain", &self.text), ("text/html", html)] {
+                    let _ = write!(message, "--{}\r\n", boundary);
+                    body_part(&mut message, content_type, body);
+                }
+                let _ = write!(message, "--{}--\r\n", boundary);
+            }
+        }
+        message
+    }
+}
+
+fn header(message: &mut String, name: &str, value: &str) {
+    let _ = write!(message, "{}: {}\r\n", name, value);
+}
+
+/// Headers and body of one text part, ending with a blank line
+fn body_part(message: &mut String, content_type: &str, body: &str) {
+    header(
+        message,
+        "Content-Type",
+        &format!("{}; charset=utf-8", content_type),
+    );
+    let body = body.replace("\r\n", "\n").replace('\n', "\r\n");
+    // Lines SMTP would cut go out as base64 too
+    if body.is_ascii() && body.split("\r\n").all(|line| line.len() <= MAX_LINE) {
+        header(message, "Content-Transfer-Encoding", "7bit");
+        let _ = write!(message, "\r\n{}\r\n", body);
+        return;
+    }
+    header(message, "Content-Transfer-Encoding", "base64");
+    message.push_str("\r\n");
+    let encoded = STANDARD.encode(body.as_bytes());
+    // Base64 output is ASCII, so byte chunks are character chunks
+    for line in encoded.as_bytes().chunks(BASE64_LINE) {
+        message.push_str(std::str::from_utf8(line).unwrap_or_default());
+        message.push_str("\r\n");
+    }
+}
+
+/// Headers and base64 content of one attached file
+fn attachment_part(message: &mut String, attachment: &MimeAttachment) {
+    let filename = encode_header(&attachment.filename).replace('"', "'");
+    header(
+        message,
+        "Content-Type",
+        &format!("{}; name=\"{}\"", attachment.content_type, filename),
+    );
+    header(
+        message,
+        "Content-Disposition",
+        &format!("attachment; filename=\"{}\"", filename),
+    );
+    header(message, "Content-Transfer-Encoding", "base64");
+    message.push_str("\r\n");
+    let encoded = STANDARD.encode(&attachment.data);
+    for line in encoded.as_bytes().chunks(BASE64_LINE) {
+        message.push_str(std::str::from_utf8(line).unwrap_or_default());
+        message.push_str("\r\n");
+    }
+}
+
+/// RFC 2047 `B` encoding for non-ASCII header values. Words are split on
+/// character boundaries so no encoded word holds half a character.
+pub fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+    // "=?UTF-8?B?" + "?=" leave 63 characters, i.e. 45 bytes of input
+    let max_bytes = (MAX_ENCODED_WORD - 12) / 4 * 3;
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in value.chars() {
+        if chunk.len() + c.len_utf8() > max_bytes {
+            words.push(encoded_word(&chunk));
+            chunk.clear();
+        }
+        chunk.push(c);
+    }
+    if !chunk.is_empty() {
+        words.push(encoded_word(&chunk));
+    }
+    words.join("\r\n ")
+}
+
+fn encoded_word(chunk: &str) -> String {
+    format!("=?UTF-8?B?{}?=", STANDARD.encode(chunk.as_bytes()))
+}
+
+/// Escapes the text and keeps its line breaks. Each paragraph carries its
+/// own `dir`, so a Hebrew reply quoting English text lays out correctly.
+pub fn text_to_html(text: &str) -> String {
+    let mut html =
+        String::from("<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"></head>\n<body>\n");
+    for paragraph in text.split("\n\n").filter(|p| !p.trim().is_empty()) {
+        let lines: Vec<String> = paragraph.lines().map(escape_html).collect();
+        let _ = writeln!(
+            html,
+            "<p dir=\"{}\">{}</p>",
+            direction(paragraph).as_html(),
+            lines.join("<br>\n")
+        );
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Boundary derived from the text: `_` never appears in base64, and a
+/// 7bit body would have to contain its own hash
+fn boundary(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    format!("=_part_{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eva() -> Vec<EmailAddress> {
+        vec![EmailAddress::parse("eva@company.com").unwrap()]
+    }
+
+    /// Decodes the encoded words of a header back into text
+    fn decode_header(value: &str) -> String {
+        value
+            .split_whitespace()
+            .map(|word| {
+                let encoded = word
+                    .strip_prefix("=?UTF-8?B?")
+                    .and_then(|w| w.strip_suffix("?="))
+                    .unwrap();
+                String::from_utf8(STANDARD.decode(encoded).unwrap()).unwrap()
+            })
+            .collect()
+    }
+
+    fn decode_body(rendered: &str) -> String {
+        let (_, body) = rendered.split_once("\r\n\r\n").unwrap();
+        let encoded: String = body.split_whitespace().collect();
+        String::from_utf8(STANDARD.decode(encoded).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_ascii_draft_stays_readable() {
+        let rendered = MimeMessage::new(eva(), "Delay", "Running late").render();
+
+        assert!(rendered.contains("Subject: Delay\r\n"));
+        assert!(rendered.contains("Content-Type: text/plain; charset=utf-8\r\n"));
+        assert!(rendered.contains("Content-Transfer-Encoding: 7bit\r\n"));
+        assert!(rendered.ends_with("\r\n\r\nRunning late\r\n"));
+    }
+
+    #[test]
+    fn test_overlong_lines_are_base64() {
+        let text = "x".repeat(MAX_LINE + 1);
+
+        let rendered = MimeMessage::new(eva(), "Log", &text).render();
+
+        assert!(rendered.contains("Content-Transfer-Encoding: base64\r\n"));
+        assert_eq!(decode_body(&rendered), text);
+    }
+
+    #[test]
+    fn test_arabic_draft_round_trips() {
+        let subject = "تأجيل الاجتماع إلى يوم الخميس القادم بسبب السفر";
+        let text = "مرحبا إيفا،\nسأتأخر عشر دقائق.";
+
+        let rendered = MimeMessage::new(eva(), subject, text).render();
+
+        let subject_line = rendered
+            .split("\r\n")
+            .skip_while(|l| !l.starts_with("Subject: "))
+            .take_while(|l| l.starts_with("Subject: ") || l.starts_with(' '))
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(decode_header(&subject_line["Subject: ".len()..]), subject);
+        assert!(rendered.contains("Content-Transfer-Encoding: base64\r\n"));
+        assert_eq!(decode_body(&rendered), text.replace('\n', "\r\n"));
+    }
+
+    #[test]
+    fn test_encoded_words_stay_short_and_whole() {
+        let subject = "会议推迟到明天下午三点，请大家准时参加并带上上周的报告和预算表";
+
+        let encoded = encode_header(subject);
+
+        assert!(encoded.split("\r\n ").count() > 1);
+        assert!(
+            encoded
+                .split("\r\n ")
+                .all(|word| word.len() <= MAX_ENCODED_WORD)
+        );
+        assert_eq!(decode_header(&encoded), subject);
+    }
+
+    #[test]
+    fn test_html_sets_direction_per_paragraph() {
+        let message = MimeMessage::new(
+            eva(),
+            "שלום",
+            "שלום אווה, אני מאחר.\n\nOn Monday, Eva wrote:\n> see you <soon>",
+        )
+        .with_html();
+
+        let html = message.html().unwrap();
+        assert!(html.contains("<meta charset=\"utf-8\">"));
+        assert!(html.contains("<p dir=\"rtl\">שלום אווה, אני מאחר.</p>"));
+        assert!(
+            html.contains(
+                "<p dir=\"ltr\">On Monday, Eva wrote:<br>\n&gt; see you &lt;soon&gt;</p>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_multipart_alternative() {
+        let rendered = MimeMessage::new(eva(), "会议", "会议推迟到明天")
+            .with_html()
+            .render();
+
+        let boundary = boundary("会议推迟到明天");
+        assert!(rendered.contains(&format!(
+            "Content-Type: multipart/alternative; boundary=\"{}\"",
+            boundary
+        )));
+        assert_eq!(rendered.matches(&format!("--{}\r\n", boundary)).count(), 2);
+        assert!(rendered.contains("Content-Type: text/html; charset=utf-8\r\n"));
+        assert!(rendered.ends_with(&format!("--{}--\r\n", boundary)));
+    }
+
+    #[test]
+    fn test_cc_is_shown_and_bcc_is_not() {
+        let message = MimeMessage::new(eva(), "Notes", "Attached")
+            .with_cc(vec![EmailAddress::parse("bruno@company.com").unwrap()])
+            .with_bcc(vec![EmailAddress::parse("boss@company.com").unwrap()]);
+
+        let rendered = message.render();
+
+        assert!(rendered.contains("To: eva@company.com\r\nCc: bruno@company.com\r\n"));
+        assert!(!rendered.contains("boss@company.com"));
+        assert_eq!(message.recipients().len(), 3);
+    }
+
+    #[test]
+    fn test_attachments_make_a_mixed_message() {
+        let rendered = MimeMessage::new(eva(), "Report", "See the report")
+            .with_attachment(MimeAttachment::new(
+                "relatório.pdf",
+                "application/pdf",
+                b"%PDF-1.7".to_vec(),
+            ))
+            .render();
+
+        let boundary = format!("{}_mixed", boundary("See the report"));
+        assert!(rendered.contains(&format!(
+            "Content-Type: multipart/mixed; boundary=\"{}\"",
+            boundary
+        )));
+        assert!(rendered.contains("Content-Type: text/plain; charset=utf-8\r\n"));
+        assert!(rendered.contains("Content-Disposition: attachment; filename=\"=?UTF-8?B?"));
+        assert!(rendered.contains(&STANDARD.encode(b"%PDF-1.7")));
+        assert!(rendered.ends_with(&format!("--{}--\r\n", boundary)));
+    }
+}
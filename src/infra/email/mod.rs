@@ -1 +1,18 @@
+pub mod address_suggestion;
+pub mod deliverability;
+pub mod delivery_report;
+pub mod email_address;
 pub mod email_sender;
+pub mod incoming_email;
+pub mod mime_message;
+#[cfg(feature = "smime")]
+pub mod smime;
+
+pub use address_suggestion::{suggest_addresses, transliterate};
+pub use deliverability::{DeliverabilityIssue, deliverability_issues};
+pub use delivery_report::{DeliveryReport, DeliveryStatus, RecipientReport, ReportKind};
+pub use email_address::{EmailAddress, EmailAddressError};
+pub use incoming_email::IncomingEmail;
+pub use mime_message::{AutoSubmitted, MimeAttachment, MimeMessage};
+#[cfg(feature = "smime")]
+pub use smime::{SigningKey, SmimeError, SmimeProtection, load_certificate};
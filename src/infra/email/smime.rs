@@ -0,0 +1,268 @@
+use openssl::pkcs7::{Pkcs7, Pkcs7Flags};
+use openssl::pkey::{PKey, Private};
+use openssl::stack::Stack;
+use openssl::symm::Cipher;
+use openssl::x509::X509;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// CRLF line endings and the `application/pkcs7-*` types of RFC 8551
+/// instead of the older `x-pkcs7` ones
+const SMIME_FLAGS: Pkcs7Flags = Pkcs7Flags::CRLFEOL.union(Pkcs7Flags::NOOLDMIMETYPE);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmimeError {
+    /// A certificate or key file is missing or not PEM
+    Key { path: String, detail: String },
+    /// Signing or encryption failed
+    Crypto(String),
+}
+
+impl fmt::Display for SmimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SmimeError::Key { path, detail } => {
+                write!(f, "Cannot load S/MIME key '{}': {}", path, detail)
+            }
+            SmimeError::Crypto(detail) => write!(f, "S/MIME failed: {}", detail),
+        }
+    }
+}
+
+impl Error for SmimeError {}
+
+fn crypto_error(e: openssl::error::ErrorStack) -> SmimeError {
+    SmimeError::Crypto(e.to_string())
+}
+
+/// Reads a PEM certificate, e.g. a contact's
+pub fn load_certificate(path: &Path) -> Result<X509, SmimeError> {
+    let key_error = |detail: String| SmimeError::Key {
+        path: path.display().to_string(),
+        detail,
+    };
+    let pem = fs::read(path).map_err(|e| key_error(e.to_string()))?;
+    X509::from_pem(&pem).map_err(|e| key_error(e.to_string()))
+}
+
+/// Certificate and private key of a sender identity
+#[derive(Clone)]
+pub struct SigningKey {
+    certificate: X509,
+    key: PKey<Private>,
+}
+
+impl SigningKey {
+    pub fn new(certificate: X509, key: PKey<Private>) -> Self {
+        Self { certificate, key }
+    }
+
+    /// Reads both from PEM files
+    pub fn load(certificate: &Path, key: &Path) -> Result<Self, SmimeError> {
+        let pem = fs::read(key).map_err(|e| SmimeError::Key {
+            path: key.display().to_string(),
+            detail: e.to_string(),
+        })?;
+        let key_pair = PKey::private_key_from_pem(&pem).map_err(|e| SmimeError::Key {
+            path: key.display().to_string(),
+            detail: e.to_string(),
+        })?;
+        Ok(Self::new(load_certificate(certificate)?, key_pair))
+    }
+
+    pub fn certificate(&self) -> &X509 {
+        &self.certificate
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("certificate", &self.certificate.subject_name())
+            .finish_non_exhaustive()
+    }
+}
+
+/// What to do to a message's content before it is sent: sign it with the
+/// sender's key, encrypt it to the recipients' certificates, or both.
+/// Signed mail is encrypted after signing, so only recipients see who
+/// signed it.
+#[derive(Debug, Clone, Default)]
+pub struct SmimeProtection {
+    signer: Option<SigningKey>,
+    recipients: Vec<X509>,
+}
+
+impl SmimeProtection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_signer(mut self, signer: SigningKey) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Encrypts to `certificate` as well
+    pub fn with_recipient(mut self, certificate: X509) -> Self {
+        self.recipients.push(certificate);
+        self
+    }
+
+    pub fn signs(&self) -> bool {
+        self.signer.is_some()
+    }
+
+    pub fn encrypts(&self) -> bool {
+        !self.recipients.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.signs() && !self.encrypts()
+    }
+
+    /// `entity`, a MIME part with its content headers, as a
+    /// `multipart/signed` or `application/pkcs7-mime` part with CRLF line
+    /// endings
+    pub fn protect(&self, entity: &[u8]) -> Result<String, SmimeError> {
+        let mut content = entity.to_vec();
+        if let Some(signer) = &self.signer {
+            let flags = Pkcs7Flags::DETACHED | Pkcs7Flags::BINARY;
+            let chain = Stack::new().map_err(crypto_error)?;
+            let signed = Pkcs7::sign(&signer.certificate, &signer.key, &chain, &content, flags)
+                .map_err(crypto_error)?;
+            content = signed
+                .to_smime(&content, flags | SMIME_FLAGS)
+                .map_err(crypto_error)?;
+        }
+        if self.encrypts() {
+            let mut certificates = Stack::new().map_err(crypto_error)?;
+            for certificate in &self.recipients {
+                certificates
+                    .push(certificate.clone())
+                    .map_err(crypto_error)?;
+            }
+            let encrypted = Pkcs7::encrypt(
+                &certificates,
+                &content,
+                Cipher::aes_256_cbc(),
+                Pkcs7Flags::BINARY,
+            )
+            .map_err(crypto_error)?;
+            content = encrypted
+                .to_smime(&[], Pkcs7Flags::BINARY | SMIME_FLAGS)
+                .map_err(crypto_error)?;
+        }
+        String::from_utf8(content).map_err(|e| SmimeError::Crypto(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::email::{EmailAddress, MimeMessage};
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::{BigNum, MsbOption};
+    use openssl::hash::MessageDigest;
+    use openssl::rsa::Rsa;
+    use openssl::x509::X509NameBuilder;
+    use openssl::x509::store::X509StoreBuilder;
+
+    /// Self-signed certificate and key for `address`
+    fn signing_key(address: &str) -> SigningKey {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", address).unwrap();
+        let name = name.build();
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        let mut serial = BigNum::new().unwrap();
+        serial.rand(64, MsbOption::MAYBE_ZERO, false).unwrap();
+        builder
+            .set_serial_number(&serial.to_asn1_integer().unwrap())
+            .unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        SigningKey::new(builder.build(), key)
+    }
+
+    fn message() -> MimeMessage {
+        MimeMessage::new(
+            vec![EmailAddress::parse("eva@company.com").unwrap()],
+            "Budget",
+            "Olá Eva,\nsegue o orçamento.",
+        )
+    }
+
+    /// The protected part of a rendered message
+    fn part(rendered: &str) -> &str {
+        &rendered[rendered.find("MIME-Version").unwrap()..]
+    }
+
+    #[test]
+    fn test_signed_message_verifies() {
+        let agent = signing_key("agent@example.com");
+        let protection = SmimeProtection::new().with_signer(agent.clone());
+
+        let rendered = message().render_protected(&protection).unwrap();
+        let (signature, content) = Pkcs7::from_smime(part(&rendered).as_bytes()).unwrap();
+
+        assert!(rendered.starts_with("To: eva@company.com\r\nSubject: Budget\r\n"));
+        assert!(rendered.contains("multipart/signed"));
+        let mut certificates = Stack::new().unwrap();
+        certificates.push(agent.certificate().clone()).unwrap();
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(agent.certificate().clone()).unwrap();
+        let mut verified = Vec::new();
+        signature
+            .verify(
+                &certificates,
+                &store.build(),
+                content.as_deref(),
+                Some(&mut verified),
+                Pkcs7Flags::BINARY,
+            )
+            .unwrap();
+        assert!(String::from_utf8(verified).unwrap().contains("base64"));
+    }
+
+    #[test]
+    fn test_encrypted_message_decrypts_for_recipient() {
+        let eva = signing_key("eva@company.com");
+        let protection = SmimeProtection::new()
+            .with_signer(signing_key("agent@example.com"))
+            .with_recipient(eva.certificate().clone());
+
+        let rendered = message().render_protected(&protection).unwrap();
+        let (envelope, _) = Pkcs7::from_smime(part(&rendered).as_bytes()).unwrap();
+        let decrypted = envelope
+            .decrypt(&eva.key, eva.certificate(), Pkcs7Flags::BINARY)
+            .unwrap();
+
+        assert!(rendered.contains("application/pkcs7-mime"));
+        assert!(!rendered.contains("multipart/signed"));
+        assert!(
+            String::from_utf8(decrypted)
+                .unwrap()
+                .contains("multipart/signed")
+        );
+    }
+
+    #[test]
+    fn test_empty_protection_renders_plainly() {
+        assert_eq!(
+            message().render_protected(&SmimeProtection::new()).unwrap(),
+            message().render()
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, FilesConfig};
+
+/// Words that describe a file rather than name it
+const FILLER_WORDS: &[&str] = &[
+    "the",
+    "a",
+    "an",
+    "my",
+    "our",
+    "this",
+    "that",
+    "latest",
+    "file",
+    "document",
+    "doc",
+    "o",
+    "os",
+    "as",
+    "meu",
+    "minha",
+    "nosso",
+    "nossa",
+    "arquivo",
+    "documento",
+];
+
+/// Why a reference did not become an attachment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum UnresolvedReason {
+    NotFound,
+    /// Several files match equally well; the user has to pick one
+    Ambiguous {
+        candidates: Vec<PathBuf>,
+    },
+    /// The path exists but is not under a configured directory
+    OutsideSandbox,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedFile {
+    /// As the user wrote it
+    pub reference: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnresolvedFile {
+    pub reference: String,
+    #[serde(flatten)]
+    pub reason: UnresolvedReason,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileResolution {
+    pub resolved: Vec<ResolvedFile>,
+    pub unresolved: Vec<UnresolvedFile>,
+}
+
+/// Maps file references from utterances ("the Q3 report", "notes/q3.md")
+/// to files under the configured directories. Nothing outside them is
+/// ever returned, symlinks included.
+#[derive(Debug, Clone)]
+pub struct FileResolver {
+    roots: Vec<PathBuf>,
+    max_depth: usize,
+}
+
+impl FileResolver {
+    /// Directories that do not exist are left out
+    pub fn new(directories: &[PathBuf]) -> Self {
+        Self {
+            roots: directories
+                .iter()
+                .filter_map(|directory| directory.canonicalize().ok())
+                .filter(|directory| directory.is_dir())
+                .collect(),
+            max_depth: FilesConfig::default().max_depth,
+        }
+    }
+
+    pub fn from_config(config: &FilesConfig) -> Self {
+        Self::new(&config.directories).with_max_depth(config.max_depth)
+    }
+
+    pub fn configured() -> Self {
+        Self::from_config(&Config::get().files)
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn resolve(&self, reference: &str) -> Result<PathBuf, UnresolvedReason> {
+        let reference = reference.trim();
+        if reference.contains('/') || reference.contains('\\') {
+            return self.resolve_path(Path::new(reference));
+        }
+        self.resolve_name(reference)
+    }
+
+    pub fn resolve_all(&self, references: &[String]) -> FileResolution {
+        let mut resolution = FileResolution::default();
+        for reference in references {
+            match self.resolve(reference) {
+                Ok(path) if !resolution.resolved.iter().any(|file| file.path == path) => {
+                    resolution.resolved.push(ResolvedFile {
+                        reference: reference.clone(),
+                        path,
+                    })
+                }
+                Ok(_) => {}
+                Err(reason) => resolution.unresolved.push(UnresolvedFile {
+                    reference: reference.clone(),
+                    reason,
+                }),
+            }
+        }
+        resolution
+    }
+
+    fn resolve_path(&self, path: &Path) -> Result<PathBuf, UnresolvedReason> {
+        let candidates: Vec<PathBuf> = if path.is_absolute() {
+            vec![path.to_path_buf()]
+        } else {
+            self.roots.iter().map(|root| root.join(path)).collect()
+        };
+        let mut outside = false;
+        for candidate in candidates {
+            let Ok(canonical) = candidate.canonicalize() else {
+                continue;
+            };
+            if !canonical.is_file() {
+                continue;
+            }
+            if self.contains(&canonical) {
+                return Ok(canonical);
+            }
+            outside = true;
+        }
+        Err(if outside {
+            UnresolvedReason::OutsideSandbox
+        } else {
+            UnresolvedReason::NotFound
+        })
+    }
+
+    /// Files whose names hold every word of the reference; the one with
+    /// the fewest other words wins
+    fn resolve_name(&self, reference: &str) -> Result<PathBuf, UnresolvedReason> {
+        let wanted: Vec<String> = words(reference)
+            .into_iter()
+            .filter(|word| !FILLER_WORDS.contains(&word.as_str()))
+            .collect();
+        if wanted.is_empty() {
+            return Err(UnresolvedReason::NotFound);
+        }
+
+        let mut files = Vec::new();
+        for root in &self.roots {
+            self.collect_files(root, 0, &mut files);
+        }
+        let mut best: Option<usize> = None;
+        let mut matches: Vec<PathBuf> = Vec::new();
+        for file in files {
+            let Some(name) = file.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if name.eq_ignore_ascii_case(reference) {
+                return Ok(file);
+            }
+            let stem = file
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(name);
+            let name_words = words(name);
+            if !wanted.iter().all(|word| name_words.contains(word)) {
+                continue;
+            }
+            let extra = words(stem)
+                .iter()
+                .filter(|word| !wanted.contains(word))
+                .count();
+            match best {
+                Some(fewest) if extra > fewest => {}
+                Some(fewest) if extra == fewest => matches.push(file),
+                _ => {
+                    best = Some(extra);
+                    matches = vec![file];
+                }
+            }
+        }
+
+        match matches.len() {
+            0 => Err(UnresolvedReason::NotFound),
+            1 => Ok(matches.remove(0)),
+            _ => {
+                matches.sort();
+                Err(UnresolvedReason::Ambiguous {
+                    candidates: matches,
+                })
+            }
+        }
+    }
+
+    /// Regular files below `directory`, skipping hidden entries and
+    /// anything a symlink leads out of the roots
+    fn collect_files(&self, directory: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(directory) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if depth < self.max_depth {
+                    self.collect_files(&path, depth + 1, files);
+                }
+            } else if let Ok(canonical) = path.canonicalize()
+                && canonical.is_file()
+                && self.contains(&canonical)
+            {
+                files.push(canonical);
+            }
+        }
+    }
+
+    fn contains(&self, canonical: &Path) -> bool {
+        self.roots.iter().any(|root| canonical.starts_with(root))
+    }
+}
+
+impl Default for FileResolver {
+    fn default() -> Self {
+        Self::configured()
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reports directory with a secret file next to it
+    fn sandbox(name: &str) -> (PathBuf, FileResolver) {
+        let base = std::env::temp_dir().join(format!("file_resolver_{}", name));
+        let _ = fs::remove_dir_all(&base);
+        let reports = base.join("reports");
+        fs::create_dir_all(reports.join("2025")).unwrap();
+        fs::write(reports.join("Q3_Report.pdf"), "q3").unwrap();
+        fs::write(reports.join("2025").join("Q3 Report draft.docx"), "draft").unwrap();
+        fs::write(reports.join("budget-2025.xlsx"), "budget").unwrap();
+        fs::write(reports.join("budget-2024.xlsx"), "budget").unwrap();
+        fs::write(base.join("secrets.txt"), "secret").unwrap();
+        (base, FileResolver::new(&[reports]))
+    }
+
+    #[test]
+    fn test_resolves_descriptions_to_best_match() {
+        let (base, resolver) = sandbox("names");
+
+        let resolution = resolver.resolve_all(&[
+            "the Q3 report".to_string(),
+            "budget".to_string(),
+            "minutes".to_string(),
+        ]);
+
+        assert_eq!(resolution.resolved.len(), 1);
+        assert!(resolution.resolved[0].path.ends_with("Q3_Report.pdf"));
+        assert!(matches!(
+            &resolution.unresolved[0].reason,
+            UnresolvedReason::Ambiguous { candidates } if candidates.len() == 2
+        ));
+        assert_eq!(resolution.unresolved[1].reason, UnresolvedReason::NotFound);
+        fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    fn test_paths_stay_inside_the_sandbox() {
+        let (base, resolver) = sandbox("paths");
+
+        assert!(resolver.resolve("2025/Q3 Report draft.docx").is_ok());
+        assert_eq!(
+            resolver.resolve("../secrets.txt"),
+            Err(UnresolvedReason::OutsideSandbox)
+        );
+        assert_eq!(
+            resolver.resolve(base.join("secrets.txt").to_str().unwrap()),
+            Err(UnresolvedReason::OutsideSandbox)
+        );
+        assert_eq!(resolver.resolve("secrets"), Err(UnresolvedReason::NotFound));
+        fs::remove_dir_all(base).unwrap();
+    }
+}
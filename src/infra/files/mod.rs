@@ -0,0 +1,5 @@
+pub mod file_resolver;
+
+pub use file_resolver::{
+    FileResolution, FileResolver, ResolvedFile, UnresolvedFile, UnresolvedReason,
+};
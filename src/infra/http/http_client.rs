@@ -1,5 +1,8 @@
+use std::time::Duration;
+
 use crate::infra::http::{HttpError, HttpResponse};
 
+#[derive(Clone)]
 pub struct HttpClient {
     client: reqwest::Client,
     base_url: String,
@@ -17,18 +20,50 @@ impl HttpClient {
         &self,
         body: &str,
     ) -> Result<HttpResponse<T>, Box<dyn std::error::Error>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.send_request_with_timeout(body, None).await
+    }
+
+    /// Posts `body` and hands back the response as soon as the headers
+    /// arrive, for reading a streamed body
+    pub async fn send_streaming_request(
+        &self,
+        body: &str,
+        timeout: Option<Duration>,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let mut request = self
+            .client
+            .post(&self.base_url)
+            .header("Content-Type", "application/json")
+            .body(body.to_string());
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        request.send().await
+    }
+
+    /// Like `send_request`, giving up after `timeout` if one is set
+    pub async fn send_request_with_timeout<T>(
+        &self,
+        body: &str,
+        timeout: Option<Duration>,
+    ) -> Result<HttpResponse<T>, Box<dyn std::error::Error>>
     where
         T: serde::de::DeserializeOwned,
     {
         let url = &self.base_url;
 
-        let response = self
+        let mut request = self
             .client
             .post(url)
             .header("Content-Type", "application/json")
-            .body(body.to_string())
-            .send()
-            .await?;
+            .body(body.to_string());
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        let response = request.send().await?;
 
         if response.status().is_success() {
             let data: T = response.json().await?;
@@ -38,11 +73,13 @@ impl HttpClient {
                 error: None,
             })
         } else {
+            let status = response.status().as_u16();
             let error_text = response.text().await?;
             Ok(HttpResponse {
                 success: false,
                 data: None,
                 error: Some(HttpError {
+                    status,
                     error: "HTTP Error".to_string(),
                     message: error_text,
                 }),
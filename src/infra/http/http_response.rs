@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HttpError {
+    pub status: u16,
     pub error: String,
     pub message: String,
 }
@@ -1,5 +1,9 @@
 pub mod http_client;
 pub mod http_response;
+pub mod problem_details;
 
 pub use http_client::HttpClient;
 pub use http_response::{HttpError, HttpResponse};
+pub use problem_details::{
+    PROBLEM_CONTENT_TYPE, PROBLEM_TYPE_BASE, ProblemDetails, new_correlation_id, problem_for,
+};
@@ -0,0 +1,430 @@
+use chrono::{Local, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::action::ActionError;
+use crate::agent::AgentError;
+use crate::agent::classifier::FieldError;
+use crate::agent::tools::ToolError;
+use crate::error::Error;
+use crate::i18n::Locale;
+use crate::infra::input::InputError;
+use crate::infra::resilience::BudgetExceeded;
+use crate::infra::smtp::SmtpError;
+use crate::safety::{AttachmentError, BlastRadiusError, SendingWindow};
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Prefix of the `type` URI; the suffix names the error variant and never
+/// changes once published
+pub const PROBLEM_TYPE_BASE: &str = "urn:ollama-email-agent:problem:";
+
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+static CORRELATION_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// RFC 7807 error body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    pub correlation_id: String,
+    /// Seconds the client should wait before retrying
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+    /// Field problems for a correction form
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl ProblemDetails {
+    /// Builds the body for `error`; `detail` is the localized user message
+    pub fn from_error(error: &Error, locale: Locale, correlation_id: &str) -> Self {
+        let (slug, title, status) = classify(error);
+        let errors = match error {
+            Error::Agent(AgentError::ValidationError(partial)) => partial.errors.clone(),
+            Error::Action(
+                ActionError::ContentBlocked(violations)
+                | ActionError::ContentFlagged { violations, .. },
+            ) => violations
+                .iter()
+                .map(|v| FieldError::new("message", &v.detail))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Self {
+            problem_type: format!("{}{}", PROBLEM_TYPE_BASE, slug),
+            title: title.to_string(),
+            status,
+            detail: error.user_message(locale),
+            instance: None,
+            correlation_id: correlation_id.to_string(),
+            retry_after: retry_after(error).map(|d| d.as_secs().max(1)),
+            errors,
+        }
+    }
+
+    /// Request path the problem occurred on
+    pub fn with_instance(mut self, instance: &str) -> Self {
+        self.instance = Some(instance.to_string());
+        self
+    }
+
+    /// Response headers to send along with the body
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = vec![
+            ("Content-Type", PROBLEM_CONTENT_TYPE.to_string()),
+            ("X-Correlation-Id", self.correlation_id.clone()),
+        ];
+        if let Some(seconds) = self.retry_after {
+            headers.push(("Retry-After", seconds.to_string()));
+        }
+        headers
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+}
+
+/// Id to tie a response to server logs, unique within the process
+pub fn new_correlation_id() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let sequence = CORRELATION_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:x}-{:04x}", millis, sequence & 0xffff)
+}
+
+fn classify(error: &Error) -> (&'static str, &'static str, u16) {
+    match error {
+        Error::Agent(e) => match e {
+            AgentError::NetworkError(_) => ("model-unavailable", "Model unavailable", 502),
+            AgentError::ParseError(_) => ("model-output-invalid", "Model output invalid", 502),
+            AgentError::ProcessingError(_) => ("processing-failed", "Processing failed", 500),
+            AgentError::ValidationError(_) => ("validation-failed", "Validation failed", 422),
+            AgentError::StagePanicked { .. } => ("stage-panicked", "Internal error", 500),
+            AgentError::BudgetExhausted(_) => ("budget-exhausted", "Budget exhausted", 508),
+            AgentError::BudgetExceeded(_) => ("budget-exceeded", "Token budget exceeded", 429),
+            AgentError::HttpError { .. } => ("model-server-error", "Model server error", 502),
+            AgentError::DeadlineExceeded(_) => ("deadline-exceeded", "Deadline exceeded", 504),
+            AgentError::Quarantined { .. } => ("input-quarantined", "Input quarantined", 422),
+            AgentError::ModelUnavailable(_) => ("model-unavailable", "Model unavailable", 503),
+            AgentError::MalformedModelOutput { .. } | AgentError::DeserializationError(_) => {
+                ("model-output-invalid", "Model output invalid", 502)
+            }
+        },
+        Error::Mapper(_) => ("model-output-invalid", "Model output invalid", 502),
+        Error::Tool(e) => match e {
+            ToolError::Failed(_) => ("tool-failed", "Tool failed", 502),
+            ToolError::InvalidArguments(_) | ToolError::UnknownTool(_) => {
+                ("tool-call-invalid", "Invalid tool call", 502)
+            }
+        },
+        Error::Prompt(_) => ("prompt-misconfigured", "Prompt misconfigured", 500),
+        Error::PipelineSpec(_) => ("pipeline-misconfigured", "Pipeline misconfigured", 500),
+        Error::Auth(e) => match e.status_code() {
+            403 => ("forbidden", "Forbidden", 403),
+            _ => ("unauthenticated", "Unauthenticated", 401),
+        },
+        Error::Action(e) => match e {
+            ActionError::PlanNotFound(_) => ("action-not-found", "Action not found", 404),
+            ActionError::InvalidTransition { .. } => {
+                ("action-already-handled", "Action already handled", 409)
+            }
+            ActionError::ElevatedApprovalRequired(_) => {
+                ("approval-required", "Approval required", 403)
+            }
+            ActionError::UndoWindowClosed(_) => ("undo-window-closed", "Undo window closed", 409),
+            ActionError::ContentBlocked(_) => ("content-blocked", "Content blocked", 422),
+            ActionError::ContentFlagged { .. } => ("content-flagged", "Content flagged", 409),
+        },
+        Error::Attachment(e) => match e {
+            AttachmentError::NotFound(_) => ("attachment-not-found", "Attachment not found", 400),
+            AttachmentError::TooLarge { .. } => {
+                ("attachment-too-large", "Attachment too large", 413)
+            }
+            AttachmentError::TypeNotAllowed { .. } => (
+                "attachment-type-not-allowed",
+                "Attachment type not allowed",
+                415,
+            ),
+            AttachmentError::Infected { .. } => ("attachment-infected", "Attachment infected", 422),
+            AttachmentError::ScanFailed { .. } => {
+                ("attachment-scan-failed", "Attachment scan failed", 503)
+            }
+        },
+        Error::BlastRadius(e) => match e {
+            BlastRadiusError::TooManyRecipients { .. } => {
+                ("too-many-recipients", "Too many recipients", 422)
+            }
+            BlastRadiusError::TooManyEmails { .. } => ("too-many-emails", "Too many emails", 422),
+            BlastRadiusError::OutsideSendingHours { .. } => {
+                ("outside-sending-hours", "Outside sending hours", 422)
+            }
+            BlastRadiusError::NonBusinessDay { .. } => {
+                ("non-business-day", "Non-business day", 422)
+            }
+        },
+        Error::EmailAddress(_) => ("invalid-email-address", "Invalid email address", 422),
+        Error::Input(e) => match e {
+            InputError::NotFound(_) => ("input-not-found", "Input not found", 400),
+            InputError::NotAudio(_) => ("input-not-audio", "Input is not audio", 415),
+            InputError::TranscriberNotConfigured => (
+                "transcription-not-configured",
+                "Transcription not configured",
+                500,
+            ),
+            InputError::TranscriptionFailed { .. } => {
+                ("transcription-failed", "Transcription failed", 502)
+            }
+            InputError::TimedOut { .. } => {
+                ("transcription-timed-out", "Transcription timed out", 504)
+            }
+            InputError::EmptyTranscript(_) => ("empty-transcript", "Empty transcript", 422),
+            InputError::UnsupportedDocument { .. } => {
+                ("document-unsupported", "Document type unsupported", 415)
+            }
+            InputError::ConversionFailed { .. } => (
+                "document-conversion-failed",
+                "Document conversion failed",
+                502,
+            ),
+        },
+        Error::Budget(_) => ("budget-exceeded", "Token budget exceeded", 429),
+        Error::Smtp(e) => match e {
+            SmtpError::NoSender | SmtpError::UnknownAccount(_) => {
+                ("mail-misconfigured", "Mail sending misconfigured", 500)
+            }
+            SmtpError::InvalidRecipient(_) => ("invalid-recipient", "Invalid recipient", 422),
+            SmtpError::Transport(_) => ("mail-server-unavailable", "Mail server unavailable", 502),
+            SmtpError::Deferred { .. } => ("mail-deferred", "Mail deferred", 503),
+            SmtpError::Rejected { .. } => ("mail-rejected", "Mail rejected", 502),
+            SmtpError::Attachment(_) => ("attachment-unreadable", "Attachment unreadable", 422),
+            SmtpError::Protection(_) => ("mail-protection-failed", "Mail protection failed", 500),
+            SmtpError::Undeliverable(_) => ("mail-undeliverable", "Mail undeliverable", 422),
+        },
+    }
+}
+
+fn retry_after(error: &Error) -> Option<Duration> {
+    match error {
+        Error::BlastRadius(BlastRadiusError::OutsideSendingHours { time, window }) => {
+            Some(until_window_opens(*time, window))
+        }
+        Error::BlastRadius(BlastRadiusError::NonBusinessDay { at, reopens, .. }) => {
+            Some((*reopens - *at).to_std().unwrap_or_default())
+        }
+        Error::Attachment(AttachmentError::ScanFailed { .. }) => Some(Duration::from_secs(30)),
+        Error::Budget(BudgetExceeded { resets_at, .. })
+        | Error::Agent(AgentError::BudgetExceeded(BudgetExceeded { resets_at, .. })) => Some(
+            (*resets_at - Local::now().naive_local())
+                .to_std()
+                .unwrap_or_default(),
+        ),
+        e if e.is_retryable() => Some(DEFAULT_RETRY_AFTER),
+        _ => None,
+    }
+}
+
+fn until_window_opens(time: NaiveTime, window: &SendingWindow) -> Duration {
+    let now = time.num_seconds_from_midnight() as i64;
+    let start = i64::from(window.start_hour) * 3600;
+    let seconds = (start - now).rem_euclid(24 * 3600);
+    Duration::from_secs(seconds as u64)
+}
+
+/// Problem for `error` with a fresh correlation id, in the configured locale
+pub fn problem_for(error: &Error) -> ProblemDetails {
+    ProblemDetails::from_error(error, Locale::configured(), &new_correlation_id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::PartialClassification;
+    use crate::auth::{AuthError, Role};
+    use crate::infra::resilience::BudgetPeriod;
+    use crate::safety::{PolicyAction, PolicyRule, PolicyViolation, QuarantineReason};
+
+    #[test]
+    fn test_validation_problem_carries_field_errors() {
+        let partial = PartialClassification::from_content(
+            r#"{"intent": "send_email", "params": {"recipient": "eva@@x"}}"#,
+        );
+        let error = Error::from(AgentError::ValidationError(Box::new(partial)));
+
+        let problem =
+            ProblemDetails::from_error(&error, Locale::En, "abc-0001").with_instance("/classify");
+
+        assert_eq!(
+            problem.problem_type,
+            "urn:ollama-email-agent:problem:validation-failed"
+        );
+        assert_eq!(problem.status, 422);
+        assert_eq!(problem.errors[0].field, "recipient");
+        assert_eq!(problem.retry_after, None);
+
+        let json: serde_json::Value = serde_json::from_str(&problem.to_json().unwrap()).unwrap();
+        assert_eq!(json["type"], problem.problem_type);
+        assert_eq!(json["instance"], "/classify");
+        assert_eq!(json["correlation_id"], "abc-0001");
+    }
+
+    #[test]
+    fn test_retryable_errors_get_retry_after() {
+        let error = Error::from(AgentError::NetworkError("refused".to_string()));
+
+        let problem = ProblemDetails::from_error(&error, Locale::En, "id");
+
+        assert_eq!(problem.status, 502);
+        assert_eq!(problem.retry_after, Some(5));
+        assert!(
+            problem
+                .headers()
+                .contains(&("Retry-After", "5".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_deadline_exceeded_is_a_gateway_timeout_without_retry() {
+        let error = Error::from(AgentError::DeadlineExceeded(
+            "before calling Ollama".to_string(),
+        ));
+
+        let problem = ProblemDetails::from_error(&error, Locale::En, "id");
+
+        assert_eq!(
+            problem.problem_type,
+            "urn:ollama-email-agent:problem:deadline-exceeded"
+        );
+        assert_eq!(problem.status, 504);
+        assert!(!error.is_retryable());
+        assert_eq!(problem.retry_after, None);
+    }
+
+    #[test]
+    fn test_deferred_mail_is_retried_later() {
+        let error = Error::from(SmtpError::Deferred {
+            code: "451".to_string(),
+            message: "Greylisted".to_string(),
+        });
+
+        let problem = ProblemDetails::from_error(&error, Locale::PtBr, "id");
+
+        assert_eq!(
+            problem.problem_type,
+            "urn:ollama-email-agent:problem:mail-deferred"
+        );
+        assert_eq!(problem.status, 503);
+        assert_eq!(problem.retry_after, Some(5));
+        assert!(!problem.detail.contains("Greylisted"));
+    }
+
+    #[test]
+    fn test_quarantined_input_has_its_own_problem_type() {
+        let error = Error::from(AgentError::Quarantined {
+            id: 3,
+            reason: QuarantineReason::JudgeFlagged {
+                reason: "asks to forward mail".to_string(),
+            },
+        });
+
+        let problem = ProblemDetails::from_error(&error, Locale::En, "id");
+
+        assert_eq!(
+            problem.problem_type,
+            "urn:ollama-email-agent:problem:input-quarantined"
+        );
+        assert_eq!(problem.status, 422);
+        assert_eq!(problem.retry_after, None);
+    }
+
+    #[test]
+    fn test_blocked_content_lists_the_violations() {
+        let error = Error::from(ActionError::ContentBlocked(vec![PolicyViolation {
+            rule: PolicyRule::BannedPhrase,
+            action: PolicyAction::Block,
+            detail: "contains banned phrase 'guaranteed returns'".to_string(),
+        }]));
+
+        let problem = ProblemDetails::from_error(&error, Locale::En, "id");
+
+        assert_eq!(
+            problem.problem_type,
+            "urn:ollama-email-agent:problem:content-blocked"
+        );
+        assert_eq!(problem.status, 422);
+        assert_eq!(
+            problem.errors,
+            vec![FieldError::new(
+                "message",
+                "contains banned phrase 'guaranteed returns'"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_outside_sending_hours_waits_for_window() {
+        let error = Error::from(BlastRadiusError::OutsideSendingHours {
+            time: NaiveTime::from_hms_opt(23, 30, 0).unwrap(),
+            window: SendingWindow::new(7, 22),
+        });
+
+        let problem = ProblemDetails::from_error(&error, Locale::En, "id");
+
+        assert_eq!(problem.retry_after, Some(7 * 3600 + 30 * 60));
+    }
+
+    #[test]
+    fn test_budget_exceeded_waits_for_reset() {
+        let error = Error::from(BudgetExceeded {
+            tenant: "acme".to_string(),
+            model: "gpt-4o".to_string(),
+            period: BudgetPeriod::Daily,
+            limit: 1000,
+            used: 1200,
+            resets_at: Local::now().naive_local() + chrono::Duration::hours(2),
+        });
+
+        let problem = ProblemDetails::from_error(&error, Locale::En, "id");
+
+        assert_eq!(problem.status, 429);
+        assert!(error.is_retryable());
+        assert!((7190..=7200).contains(&problem.retry_after.unwrap()));
+    }
+
+    #[test]
+    fn test_auth_status_codes() {
+        let forbidden = Error::from(AuthError::Forbidden {
+            subject: "ops".to_string(),
+            required: Role::Admin,
+        });
+
+        assert_eq!(
+            ProblemDetails::from_error(&forbidden, Locale::En, "id").status,
+            403
+        );
+        assert_eq!(
+            ProblemDetails::from_error(
+                &Error::from(AuthError::MissingCredentials),
+                Locale::En,
+                "id"
+            )
+            .problem_type,
+            "urn:ollama-email-agent:problem:unauthenticated"
+        );
+    }
+
+    #[test]
+    fn test_correlation_ids_are_unique() {
+        assert_ne!(new_correlation_id(), new_correlation_id());
+    }
+}
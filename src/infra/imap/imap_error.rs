@@ -0,0 +1,43 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImapError {
+    /// `[imap] username` or `password` is not set
+    NoCredentials,
+    /// Connection or TLS problem, or a timeout
+    Transport(String),
+    /// The server answered `NO` or `BAD`, e.g. to a wrong password
+    Rejected { command: String, message: String },
+    /// A reply this client cannot read
+    Protocol(String),
+    /// `security = "none"` towards a host that is not on loopback; the
+    /// password would cross the network in clear text
+    InsecureLogin(String),
+    /// `security = "start_tls"` but the server does not offer STARTTLS
+    NoStartTls,
+    /// The server advertises `LOGINDISABLED` on this connection
+    LoginDisabled,
+}
+
+impl fmt::Display for ImapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImapError::NoCredentials => write!(f, "No IMAP username and password configured"),
+            ImapError::Transport(detail) => write!(f, "IMAP connection failed: {}", detail),
+            ImapError::Rejected { command, message } => {
+                write!(f, "IMAP server refused {}: {}", command, message)
+            }
+            ImapError::Protocol(detail) => write!(f, "Unexpected IMAP reply: {}", detail),
+            ImapError::InsecureLogin(host) => write!(
+                f,
+                "Refusing to send the IMAP password unencrypted to {}; use security = \"tls\" or \"start_tls\"",
+                host
+            ),
+            ImapError::NoStartTls => write!(f, "IMAP server does not offer STARTTLS"),
+            ImapError::LoginDisabled => write!(f, "IMAP server does not accept LOGIN here"),
+        }
+    }
+}
+
+impl Error for ImapError {}
@@ -0,0 +1,482 @@
+use imap_proto::{AttributeValue, Capability, MailboxDatum, Response, Status};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, lookup_host};
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+
+use crate::config::{Config, ImapConfig, ImapSecurity};
+use crate::infra::email::IncomingEmail;
+use crate::infra::imap::ImapError;
+
+/// Bytes read from the server at a time
+const READ_CHUNK: usize = 8192;
+
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+/// Tagged IMAP4rev1 commands over one connection
+struct Session {
+    stream: Box<dyn Connection>,
+    /// Received and not parsed yet
+    buffer: Vec<u8>,
+    tag: u32,
+}
+
+impl Session {
+    fn new(stream: Box<dyn Connection>) -> Self {
+        Self {
+            stream,
+            buffer: Vec::new(),
+            tag: 0,
+        }
+    }
+
+    /// Reads one response, with the literals it carries
+    async fn read_response(&mut self) -> Result<Response<'static>, ImapError> {
+        loop {
+            match Response::from_bytes(&self.buffer) {
+                Ok((rest, response)) => {
+                    let consumed = self.buffer.len() - rest.len();
+                    let response = response.into_owned();
+                    self.buffer.drain(..consumed);
+                    return Ok(response);
+                }
+                Err(e) if e.is_incomplete() => {}
+                Err(_) => return Err(ImapError::Protocol(first_line(&self.buffer))),
+            }
+            let mut chunk = [0; READ_CHUNK];
+            let read = self
+                .stream
+                .read(&mut chunk)
+                .await
+                .map_err(transport_error)?;
+            if read == 0 {
+                return Err(ImapError::Transport("connection closed".to_string()));
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    /// Sends `command` and returns the untagged responses before its
+    /// tagged `OK`
+    async fn command(&mut self, command: &str) -> Result<Vec<Response<'static>>, ImapError> {
+        self.tag += 1;
+        let tag = format!("A{}", self.tag);
+        self.stream
+            .write_all(format!("{} {}\r\n", tag, command).as_bytes())
+            .await
+            .map_err(transport_error)?;
+        self.stream.flush().await.map_err(transport_error)?;
+
+        let mut untagged = Vec::new();
+        loop {
+            match self.read_response().await? {
+                Response::Done {
+                    tag: done,
+                    status,
+                    information,
+                    ..
+                } if done.0 == tag => {
+                    return if status == Status::Ok {
+                        Ok(untagged)
+                    } else {
+                        Err(ImapError::Rejected {
+                            command: command_name(command),
+                            message: information.unwrap_or_default().to_string(),
+                        })
+                    };
+                }
+                response => untagged.push(response),
+            }
+        }
+    }
+
+    /// What the server offers on this connection
+    async fn capabilities(&mut self) -> Result<Vec<Capability<'static>>, ImapError> {
+        Ok(self
+            .command("CAPABILITY")
+            .await?
+            .into_iter()
+            .flat_map(|response| match response {
+                Response::Capabilities(capabilities) => capabilities,
+                _ => Vec::new(),
+            })
+            .collect())
+    }
+
+    /// The same connection once TLS is negotiated, after `STARTTLS`
+    async fn start_tls(self, host: &str) -> Result<Session, ImapError> {
+        // Bytes before the handshake may come from a man in the middle
+        if !self.buffer.is_empty() {
+            return Err(ImapError::Protocol(
+                "data sent along with the STARTTLS reply".to_string(),
+            ));
+        }
+        Ok(Session::new(tls(host, self.stream).await?))
+    }
+}
+
+/// Fetches unread mail from the IMAP mailbox of `[imap]`, without marking
+/// it read unless `mark_seen` is set
+#[derive(Debug, Clone)]
+pub struct InboxReader {
+    config: ImapConfig,
+}
+
+impl InboxReader {
+    pub fn from_config(config: &ImapConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Reader for the mailbox in config.toml
+    pub fn configured() -> Self {
+        Self::from_config(&Config::get().imap)
+    }
+
+    /// The newest unread messages, oldest first
+    pub async fn fetch_unread(&self) -> Result<Vec<IncomingEmail>, ImapError> {
+        let (Some(username), Some(password)) = (&self.config.username, &self.config.password)
+        else {
+            return Err(ImapError::NoCredentials);
+        };
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        tokio::time::timeout(timeout, self.fetch(username, password))
+            .await
+            .map_err(|_| ImapError::Transport(format!("timed out after {:?}", timeout)))?
+    }
+
+    async fn fetch(&self, username: &str, password: &str) -> Result<Vec<IncomingEmail>, ImapError> {
+        let mut session = self.connect().await?;
+        match session.read_response().await? {
+            Response::Data {
+                status: Status::Ok, ..
+            } => {}
+            greeting => return Err(ImapError::Protocol(format!("{:?}", greeting))),
+        }
+        let mut capabilities = session.capabilities().await?;
+        if self.config.security == ImapSecurity::StartTls {
+            if !offers(&capabilities, "STARTTLS") {
+                return Err(ImapError::NoStartTls);
+            }
+            session.command("STARTTLS").await?;
+            session = session.start_tls(&self.config.host).await?;
+            // What was offered in clear text may have been tampered with
+            capabilities = session.capabilities().await?;
+        }
+        if offers(&capabilities, "LOGINDISABLED") {
+            return Err(ImapError::LoginDisabled);
+        }
+        session
+            .command(&format!("LOGIN {} {}", quote(username), quote(password)))
+            .await?;
+        session
+            .command(&format!("SELECT {}", quote(&self.config.mailbox)))
+            .await?;
+
+        let mut uids: Vec<u32> = session
+            .command("UID SEARCH UNSEEN")
+            .await?
+            .into_iter()
+            .flat_map(|response| match response {
+                Response::MailboxData(MailboxDatum::Search(uids)) => uids,
+                _ => Vec::new(),
+            })
+            .collect();
+        uids.sort_unstable();
+        let uids = &uids[uids.len().saturating_sub(self.config.max_messages)..];
+        if uids.is_empty() {
+            let _ = session.command("LOGOUT").await;
+            return Ok(Vec::new());
+        }
+        let set = uids
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let mut emails: Vec<IncomingEmail> = session
+            .command(&format!("UID FETCH {} (UID BODY.PEEK[])", set))
+            .await?
+            .iter()
+            .filter_map(|response| {
+                let Response::Fetch(_, attributes) = response else {
+                    return None;
+                };
+                let uid = attributes.iter().find_map(|attribute| match attribute {
+                    AttributeValue::Uid(uid) => Some(*uid),
+                    _ => None,
+                })?;
+                let source = attributes.iter().find_map(|attribute| match attribute {
+                    AttributeValue::BodySection {
+                        data: Some(data), ..
+                    } => Some(data),
+                    _ => None,
+                })?;
+                IncomingEmail::parse(source).map(|email| email.with_uid(uid))
+            })
+            .collect();
+        emails.sort_by_key(|email| email.uid);
+        if self.config.mark_seen {
+            session
+                .command(&format!("UID STORE {} +FLAGS.SILENT (\\Seen)", set))
+                .await?;
+        }
+        // The messages are in hand; a failed goodbye changes nothing
+        let _ = session.command("LOGOUT").await;
+        Ok(emails)
+    }
+
+    async fn connect(&self) -> Result<Session, ImapError> {
+        let addresses: Vec<SocketAddr> = lookup_host((self.config.host.as_str(), self.config.port))
+            .await
+            .map_err(transport_error)?
+            .collect();
+        // LOGIN would carry the password in clear text
+        if self.config.security == ImapSecurity::None
+            && !addresses.iter().all(|address| address.ip().is_loopback())
+        {
+            return Err(ImapError::InsecureLogin(self.config.host.clone()));
+        }
+        let tcp = TcpStream::connect(addresses.as_slice())
+            .await
+            .map_err(transport_error)?;
+        let stream: Box<dyn Connection> = match self.config.security {
+            ImapSecurity::Tls => tls(&self.config.host, Box::new(tcp)).await?,
+            ImapSecurity::StartTls | ImapSecurity::None => Box::new(tcp),
+        };
+        Ok(Session::new(stream))
+    }
+}
+
+impl Default for InboxReader {
+    fn default() -> Self {
+        Self::configured()
+    }
+}
+
+/// `stream` after a TLS handshake with `host`, checked against the webpki
+/// roots
+async fn tls(host: &str, stream: Box<dyn Connection>) -> Result<Box<dyn Connection>, ImapError> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let provider = Arc::new(tokio_rustls::rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(transport_error)?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let name = ServerName::try_from(host.to_string()).map_err(transport_error)?;
+    Ok(Box::new(
+        TlsConnector::from(Arc::new(config))
+            .connect(name, stream)
+            .await
+            .map_err(transport_error)?,
+    ))
+}
+
+fn transport_error(e: impl std::fmt::Display) -> ImapError {
+    ImapError::Transport(e.to_string())
+}
+
+fn offers(capabilities: &[Capability], name: &str) -> bool {
+    capabilities
+        .iter()
+        .any(|capability| matches!(capability, Capability::Atom(atom) if atom.eq_ignore_ascii_case(name)))
+}
+
+/// The line a response that could not be parsed starts with
+fn first_line(buffer: &[u8]) -> String {
+    let line = buffer
+        .split(|&byte| byte == b'\n')
+        .next()
+        .unwrap_or_default();
+    String::from_utf8_lossy(line).trim_end().to_string()
+}
+
+/// A quoted string; credentials may hold spaces and quotes
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Command name for errors, without arguments such as the password
+fn command_name(command: &str) -> String {
+    let mut words = command.split_whitespace();
+    match words.next() {
+        Some(uid) if uid.eq_ignore_ascii_case("UID") => {
+            format!("UID {}", words.next().unwrap_or_default())
+        }
+        Some(name) => name.to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::TcpListener;
+
+    const FIRST: &str = "From: Eva <eva@company.com>\r\nSubject: Budget\r\nMessage-ID: <1@company.com>\r\n\r\nCan you send the budget?\r\n";
+    const SECOND: &str = "From: bruno@company.com\r\nSubject: Lunch\r\n\r\nLunch tomorrow?\r\n";
+
+    /// Minimal IMAP server for one session offering `capabilities`;
+    /// returns the commands received
+    async fn fake_server(listener: TcpListener, capabilities: &'static str) -> Vec<String> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"* OK IMAP4rev1 ready\r\n").await.unwrap();
+
+        let mut commands = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let (tag, command) = line.split_once(' ').unwrap();
+            commands.push(command.to_string());
+            let reply = if command == "CAPABILITY" {
+                format!(
+                    "* CAPABILITY IMAP4rev1 {}\r\n{} OK CAPABILITY completed\r\n",
+                    capabilities, tag
+                )
+            } else if command.starts_with("LOGIN") && command.contains("wrong") {
+                format!("{} NO [AUTHENTICATIONFAILED] Invalid credentials\r\n", tag)
+            } else if command.starts_with("SELECT") {
+                format!("* 3 EXISTS\r\n{} OK [READ-WRITE] SELECT completed\r\n", tag)
+            } else if command.starts_with("UID SEARCH") {
+                format!("* SEARCH 9 4\r\n{} OK SEARCH completed\r\n", tag)
+            } else if command.starts_with("UID FETCH") {
+                format!(
+                    "* 2 FETCH (UID 9 BODY[] {{{}}}\r\n{})\r\n* 1 FETCH (BODY[] {{{}}}\r\n{} UID 4)\r\n{} OK FETCH completed\r\n",
+                    SECOND.len(),
+                    SECOND,
+                    FIRST.len(),
+                    FIRST,
+                    tag
+                )
+            } else if command.starts_with("LOGOUT") {
+                writer
+                    .write_all(format!("* BYE\r\n{} OK LOGOUT completed\r\n", tag).as_bytes())
+                    .await
+                    .unwrap();
+                break;
+            } else {
+                format!("{} OK done\r\n", tag)
+            };
+            writer.write_all(reply.as_bytes()).await.unwrap();
+        }
+        commands
+    }
+
+    async fn config(password: &str) -> (ImapConfig, TcpListener) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = ImapConfig {
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            security: ImapSecurity::None,
+            username: Some("agent@example.com".to_string()),
+            password: Some(password.to_string()),
+            timeout_secs: 5,
+            ..ImapConfig::default()
+        };
+        (config, listener)
+    }
+
+    async fn reader(password: &str, mark_seen: bool) -> (InboxReader, TcpListener) {
+        let (config, listener) = config(password).await;
+        let config = ImapConfig {
+            mark_seen,
+            ..config
+        };
+        (InboxReader::from_config(&config), listener)
+    }
+
+    #[tokio::test]
+    async fn test_fetches_unread_messages_oldest_first() {
+        let (reader, listener) = reader("secret", true).await;
+        let server = tokio::spawn(fake_server(listener, ""));
+
+        let emails = reader.fetch_unread().await.unwrap();
+        let commands = server.await.unwrap();
+
+        assert_eq!(emails.len(), 2);
+        assert_eq!(emails[0].uid, Some(4));
+        assert_eq!(emails[0].subject, "Budget");
+        assert_eq!(emails[0].text.trim(), "Can you send the budget?");
+        assert_eq!(
+            emails[1].from.as_ref().unwrap().to_string(),
+            "bruno@company.com"
+        );
+        assert!(commands.contains(&"UID FETCH 4,9 (UID BODY.PEEK[])".to_string()));
+        assert!(commands.contains(&"UID STORE 4,9 +FLAGS.SILENT (\\Seen)".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_login() {
+        let (reader, listener) = reader("wrong \"pass\"", false).await;
+        tokio::spawn(fake_server(listener, ""));
+
+        let error = reader.fetch_unread().await.unwrap_err();
+
+        assert_eq!(
+            error,
+            ImapError::Rejected {
+                command: "LOGIN".to_string(),
+                message: "[AUTHENTICATIONFAILED] Invalid credentials".to_string(),
+            }
+        );
+        assert_eq!(
+            InboxReader::from_config(&ImapConfig::default())
+                .fetch_unread()
+                .await
+                .unwrap_err(),
+            ImapError::NoCredentials
+        );
+    }
+
+    #[tokio::test]
+    async fn test_login_disabled_sends_no_password() {
+        let (reader, listener) = reader("secret", false).await;
+        let server = tokio::spawn(fake_server(listener, "STARTTLS LOGINDISABLED"));
+
+        let error = reader.fetch_unread().await.unwrap_err();
+
+        assert_eq!(error, ImapError::LoginDisabled);
+        let commands = server.await.unwrap();
+        assert!(commands.iter().all(|command| !command.starts_with("LOGIN")));
+    }
+
+    #[tokio::test]
+    async fn test_no_password_in_clear_text_off_loopback() {
+        let (config, listener) = config("secret").await;
+        let remote = ImapConfig {
+            host: "192.0.2.1".to_string(),
+            ..config.clone()
+        };
+        assert_eq!(
+            InboxReader::from_config(&remote)
+                .fetch_unread()
+                .await
+                .unwrap_err(),
+            ImapError::InsecureLogin("192.0.2.1".to_string())
+        );
+
+        let server = tokio::spawn(fake_server(listener, "IDLE"));
+        let starttls = ImapConfig {
+            security: ImapSecurity::StartTls,
+            ..config
+        };
+        assert_eq!(
+            InboxReader::from_config(&starttls)
+                .fetch_unread()
+                .await
+                .unwrap_err(),
+            ImapError::NoStartTls
+        );
+        assert_eq!(server.await.unwrap(), vec!["CAPABILITY".to_string()]);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod imap_error;
+pub mod inbox_reader;
+
+pub use crate::infra::email::IncomingEmail;
+pub use imap_error::ImapError;
+pub use inbox_reader::InboxReader;
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::{Config, DocumentConfig};
+use crate::i18n::text::truncate_graphemes;
+use crate::infra::input::InputError;
+use crate::safety::attachment_guard::mime_type_for;
+
+/// Placeholder for the document path in a converter command line
+const PATH_PLACEHOLDER: &str = "{}";
+
+/// Extracts the text of document attachments. Plain-text formats are read
+/// as they are; other types go through the converter command configured
+/// for their MIME type, e.g. `pdftotext -q {} -` for PDF.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentConverter {
+    converters: BTreeMap<String, Vec<String>>,
+    timeout: Duration,
+    max_chars: usize,
+}
+
+impl DocumentConverter {
+    /// Reads plain text only, until converters are added
+    pub fn new() -> Self {
+        let defaults = DocumentConfig::default();
+        Self {
+            converters: BTreeMap::new(),
+            timeout: Duration::from_secs(defaults.timeout_secs),
+            max_chars: defaults.max_chars,
+        }
+    }
+
+    pub fn from_config(config: &DocumentConfig) -> Self {
+        config
+            .converters
+            .iter()
+            .fold(Self::new(), |converter, (mime_type, command)| {
+                converter.with_converter(mime_type, command)
+            })
+            .with_timeout(Duration::from_secs(config.timeout_secs))
+            .with_max_chars(config.max_chars)
+    }
+
+    /// Converter with the commands from config.toml
+    pub fn configured() -> Self {
+        Self::from_config(&Config::get().documents)
+    }
+
+    /// Command printing the text of `mime_type` documents to stdout; `{}`
+    /// stands for the path, which is appended when it is missing. A blank
+    /// command line is ignored.
+    pub fn with_converter(mut self, mime_type: &str, command_line: &str) -> Self {
+        let mut parts: Vec<String> = command_line
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        if parts.is_empty() {
+            return self;
+        }
+        if !parts[1..].iter().any(|part| part == PATH_PLACEHOLDER) {
+            parts.push(PATH_PLACEHOLDER.to_string());
+        }
+        self.converters.insert(mime_type.to_lowercase(), parts);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Longest text kept per document; the rest is dropped
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars.max(1);
+        self
+    }
+
+    /// Whether the text of `path` can be extracted, judging by its extension
+    pub fn supports(&self, path: &Path) -> bool {
+        let mime_type = mime_type_for(path);
+        is_plain_text(mime_type) || self.converters.contains_key(mime_type)
+    }
+
+    pub async fn extract_text(&self, path: &Path) -> Result<String, InputError> {
+        if !tokio::fs::try_exists(path).await.unwrap_or(false) {
+            return Err(InputError::NotFound(path.to_path_buf()));
+        }
+        let mime_type = mime_type_for(path);
+        let raw = if is_plain_text(mime_type) {
+            let bytes = tokio::fs::read(path)
+                .await
+                .map_err(|e| failed(path, e.to_string()))?;
+            String::from_utf8_lossy(&bytes).to_string()
+        } else {
+            let command =
+                self.converters
+                    .get(mime_type)
+                    .ok_or_else(|| InputError::UnsupportedDocument {
+                        path: path.to_path_buf(),
+                        mime_type: mime_type.to_string(),
+                    })?;
+            tokio::time::timeout(self.timeout, run(command, path))
+                .await
+                .map_err(|_| failed(path, format!("timed out after {:?}", self.timeout)))??
+        };
+
+        let text = clean_text(&raw);
+        if text.is_empty() {
+            return Err(failed(path, "no text found".to_string()));
+        }
+        Ok(truncate_graphemes(&text, self.max_chars).to_string())
+    }
+}
+
+impl Default for DocumentConverter {
+    fn default() -> Self {
+        Self::configured()
+    }
+}
+
+fn is_plain_text(mime_type: &str) -> bool {
+    matches!(mime_type, "text/plain" | "text/markdown" | "text/csv")
+}
+
+async fn run(command: &[String], path: &Path) -> Result<String, InputError> {
+    let path_arg = path.to_string_lossy();
+    let args = command[1..].iter().map(|arg| {
+        if arg == PATH_PLACEHOLDER {
+            path_arg.as_ref()
+        } else {
+            arg.as_str()
+        }
+    });
+    let output = tokio::process::Command::new(&command[0])
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| failed(path, e.to_string()))?;
+    if !output.status.success() {
+        return Err(failed(
+            path,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn failed(path: &Path, detail: String) -> InputError {
+    InputError::ConversionFailed {
+        path: path.to_path_buf(),
+        detail,
+    }
+}
+
+/// Drops the blank lines and trailing spaces converters leave around
+/// layout, keeping paragraphs apart
+fn clean_text(raw: &str) -> String {
+    let mut text = String::new();
+    let mut blank = false;
+    for line in raw.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            blank = !text.is_empty();
+            continue;
+        }
+        if blank {
+            text.push('\n');
+            blank = false;
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(line);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_document(name: &str, content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("document_converter_tests");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_plain_text_is_read_directly() {
+        let path = temp_document("notes.md", "# Budget\n\n\n\nQ3 total: 12k   \n");
+
+        let text = DocumentConverter::new().extract_text(&path).await.unwrap();
+
+        assert_eq!(text, "# Budget\n\nQ3 total: 12k");
+    }
+
+    #[tokio::test]
+    async fn test_converter_command_gets_the_path() {
+        let path = temp_document("report.pdf", "Revenue grew 8%");
+        let converter = DocumentConverter::new().with_converter("application/pdf", "cat {}");
+
+        assert!(converter.supports(&path));
+        assert_eq!(
+            converter.extract_text(&path).await.unwrap(),
+            "Revenue grew 8%"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_path_is_appended_without_placeholder() {
+        let path = temp_document("minutes.docx", "Decision: ship on Friday");
+        let converter = DocumentConverter::new()
+            .with_converter(
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                "cat",
+            )
+            .with_max_chars(8);
+
+        assert_eq!(converter.extract_text(&path).await.unwrap(), "Decision");
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_and_failing_documents() {
+        let path = temp_document("slides.pptx", "binary");
+        let failing = temp_document("broken.pdf", "");
+
+        assert!(matches!(
+            DocumentConverter::new().extract_text(&path).await,
+            Err(InputError::UnsupportedDocument { mime_type, .. })
+                if mime_type.ends_with("presentationml.presentation")
+        ));
+        assert!(matches!(
+            DocumentConverter::new()
+                .with_converter("application/pdf", "cat")
+                .extract_text(&failing)
+                .await,
+            Err(InputError::ConversionFailed { detail, .. }) if detail == "no text found"
+        ));
+    }
+}
@@ -0,0 +1,90 @@
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputError {
+    NotFound(PathBuf),
+    NotAudio(PathBuf),
+    /// Neither a transcription command nor an endpoint is configured
+    TranscriberNotConfigured,
+    TranscriptionFailed {
+        path: PathBuf,
+        detail: String,
+    },
+    TimedOut {
+        path: PathBuf,
+        after: Duration,
+    },
+    /// The recording contained no recognizable speech
+    EmptyTranscript(PathBuf),
+    /// No converter is configured for the document's type
+    UnsupportedDocument {
+        path: PathBuf,
+        mime_type: String,
+    },
+    /// The converter failed, timed out or printed no text
+    ConversionFailed {
+        path: PathBuf,
+        detail: String,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::NotFound(path) => write!(f, "Input {} not found", path.display()),
+            InputError::NotAudio(path) => {
+                write!(f, "Input {} is not an audio file", path.display())
+            }
+            InputError::TranscriberNotConfigured => {
+                write!(f, "No transcription command or endpoint configured")
+            }
+            InputError::TranscriptionFailed { path, detail } => {
+                write!(f, "Transcription of {} failed: {}", path.display(), detail)
+            }
+            InputError::TimedOut { path, after } => write!(
+                f,
+                "Transcription of {} timed out after {:?}",
+                path.display(),
+                after
+            ),
+            InputError::EmptyTranscript(path) => {
+                write!(f, "Transcript of {} is empty", path.display())
+            }
+            InputError::UnsupportedDocument { path, mime_type } => write!(
+                f,
+                "No text converter for {} ({})",
+                path.display(),
+                mime_type
+            ),
+            InputError::ConversionFailed { path, detail } => {
+                write!(
+                    f,
+                    "Text extraction from {} failed: {}",
+                    path.display(),
+                    detail
+                )
+            }
+        }
+    }
+}
+
+impl Error for InputError {}
+
+/// Where a user request comes from; yields the text handed to the classifier
+pub trait InputSource {
+    fn read(&self) -> impl Future<Output = Result<String, InputError>> + Send;
+}
+
+/// A request typed by the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextInput(pub String);
+
+impl InputSource for TextInput {
+    async fn read(&self) -> Result<String, InputError> {
+        Ok(self.0.clone())
+    }
+}
@@ -0,0 +1,9 @@
+pub mod document;
+pub mod input_source;
+pub mod transcriber;
+pub mod voice_note;
+
+pub use document::DocumentConverter;
+pub use input_source::{InputError, InputSource, TextInput};
+pub use transcriber::Transcriber;
+pub use voice_note::{VoiceNoteInput, is_audio};
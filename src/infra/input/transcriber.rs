@@ -0,0 +1,221 @@
+use serde::Deserialize;
+use std::path::Path;
+
+use crate::config::TranscriptionConfig;
+use crate::infra::input::InputError;
+use crate::safety::attachment_guard::mime_type_for;
+
+const BOUNDARY: &str = "----ollama-email-agent-audio";
+
+/// Speech-to-text hook run on voice notes before classification
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transcriber {
+    /// Runs a command with the audio path appended and reads the transcript
+    /// from stdout, e.g. `whisper-cli -m ggml-base.bin -nt -np -f`
+    Command { program: String, args: Vec<String> },
+    /// POSTs the file to a whisper.cpp server `/inference` endpoint and
+    /// expects `{"text": "..."}`
+    Http {
+        url: String,
+        language: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+impl Transcriber {
+    /// Parses a command line such as `whisper-cli -m model.bin -nt -f`
+    pub fn command(command_line: &str) -> Option<Self> {
+        let mut parts = command_line.split_whitespace().map(str::to_string);
+        let program = parts.next()?;
+        Some(Transcriber::Command {
+            program,
+            args: parts.collect(),
+        })
+    }
+
+    pub fn http(url: &str) -> Self {
+        Transcriber::Http {
+            url: url.to_string(),
+            language: None,
+        }
+    }
+
+    /// The endpoint wins when both a command and an endpoint are set
+    pub fn from_config(config: &TranscriptionConfig) -> Option<Self> {
+        match (&config.url, &config.command) {
+            (Some(url), _) => Some(Transcriber::Http {
+                url: url.clone(),
+                language: config.language.clone(),
+            }),
+            (None, Some(command)) => Self::command(command),
+            (None, None) => None,
+        }
+    }
+
+    pub async fn transcribe(&self, path: &Path) -> Result<String, InputError> {
+        let transcript = match self {
+            Transcriber::Command { program, args } => {
+                let output = tokio::process::Command::new(program)
+                    .args(args)
+                    .arg(path)
+                    .kill_on_drop(true)
+                    .output()
+                    .await
+                    .map_err(|e| failed(path, e.to_string()))?;
+                if !output.status.success() {
+                    return Err(failed(
+                        path,
+                        String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                    ));
+                }
+                String::from_utf8_lossy(&output.stdout).to_string()
+            }
+            Transcriber::Http { url, language } => {
+                let bytes = tokio::fs::read(path)
+                    .await
+                    .map_err(|e| failed(path, e.to_string()))?;
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "audio".to_string());
+                let mut fields = vec![("response_format", "json")];
+                if let Some(language) = language {
+                    fields.push(("language", language.as_str()));
+                }
+                let body = multipart_body(&file_name, mime_type_for(path), &bytes, &fields);
+                reqwest::Client::new()
+                    .post(url)
+                    .header(
+                        "Content-Type",
+                        format!("multipart/form-data; boundary={}", BOUNDARY),
+                    )
+                    .body(body)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| failed(path, e.to_string()))?
+                    .json::<TranscriptionResponse>()
+                    .await
+                    .map_err(|e| failed(path, e.to_string()))?
+                    .text
+            }
+        };
+        Ok(clean_transcript(&transcript))
+    }
+}
+
+fn failed(path: &Path, detail: String) -> InputError {
+    InputError::TranscriptionFailed {
+        path: path.to_path_buf(),
+        detail,
+    }
+}
+
+/// Joins the segments whisper prints one per line
+fn clean_transcript(raw: &str) -> String {
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `multipart/form-data` body with the audio as the `file` part
+fn multipart_body(
+    file_name: &str,
+    mime_type: &str,
+    bytes: &[u8],
+    fields: &[(&str, &str)],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(bytes.len() + 512);
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            BOUNDARY,
+            file_name.replace('"', ""),
+            mime_type
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(b"\r\n");
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                BOUNDARY, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_wins_over_command() {
+        let config = TranscriptionConfig {
+            command: Some("whisper-cli -nt -f".to_string()),
+            url: Some("http://localhost:8080/inference".to_string()),
+            language: Some("pt".to_string()),
+            ..TranscriptionConfig::default()
+        };
+
+        assert_eq!(
+            Transcriber::from_config(&config),
+            Some(Transcriber::Http {
+                url: "http://localhost:8080/inference".to_string(),
+                language: Some("pt".to_string()),
+            })
+        );
+        assert_eq!(
+            Transcriber::from_config(&TranscriptionConfig::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_multipart_body() {
+        let body = multipart_body("note.wav", "audio/wav", b"RIFF", &[("language", "pt")]);
+        let body = String::from_utf8(body).unwrap();
+
+        assert!(body.starts_with(&format!("--{}\r\n", BOUNDARY)));
+        assert!(body.contains("filename=\"note.wav\"\r\nContent-Type: audio/wav\r\n\r\nRIFF\r\n"));
+        assert!(body.contains("name=\"language\"\r\n\r\npt\r\n"));
+        assert!(body.ends_with(&format!("--{}--\r\n", BOUNDARY)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_transcript_is_joined() {
+        // `cat` stands in for whisper and prints the "audio" back
+        let path = std::env::temp_dir().join("transcriber_test_note.wav");
+        std::fs::write(&path, "  Send an email to Eva\n\n saying I'm late \n").unwrap();
+
+        let transcript = Transcriber::command("cat")
+            .unwrap()
+            .transcribe(&path)
+            .await
+            .unwrap();
+
+        assert_eq!(transcript, "Send an email to Eva saying I'm late");
+    }
+
+    #[tokio::test]
+    async fn test_missing_program_fails() {
+        let transcriber = Transcriber::command("definitely-not-whisper").unwrap();
+
+        assert!(matches!(
+            transcriber.transcribe(Path::new("note.wav")).await,
+            Err(InputError::TranscriptionFailed { .. })
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::{Config, TranscriptionConfig};
+use crate::infra::input::{InputError, InputSource, Transcriber};
+use crate::safety::attachment_guard::mime_type_for;
+
+pub fn is_audio(path: &Path) -> bool {
+    mime_type_for(path).starts_with("audio/")
+}
+
+/// A dictated request, transcribed before it is classified
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoiceNoteInput {
+    path: PathBuf,
+    transcriber: Transcriber,
+    timeout: Duration,
+}
+
+impl VoiceNoteInput {
+    pub fn new(path: impl Into<PathBuf>, transcriber: Transcriber) -> Self {
+        Self {
+            path: path.into(),
+            transcriber,
+            timeout: Duration::from_secs(TranscriptionConfig::default().timeout_secs),
+        }
+    }
+
+    pub fn from_config(
+        path: impl Into<PathBuf>,
+        config: &TranscriptionConfig,
+    ) -> Result<Self, InputError> {
+        let transcriber =
+            Transcriber::from_config(config).ok_or(InputError::TranscriberNotConfigured)?;
+        Ok(Self::new(path, transcriber).with_timeout(Duration::from_secs(config.timeout_secs)))
+    }
+
+    /// Voice note transcribed with the hook from config.toml
+    pub fn configured(path: impl Into<PathBuf>) -> Result<Self, InputError> {
+        Self::from_config(path, &Config::get().transcription)
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl InputSource for VoiceNoteInput {
+    async fn read(&self) -> Result<String, InputError> {
+        if !is_audio(&self.path) {
+            return Err(InputError::NotAudio(self.path.clone()));
+        }
+        if !tokio::fs::try_exists(&self.path).await.unwrap_or(false) {
+            return Err(InputError::NotFound(self.path.clone()));
+        }
+        let transcript =
+            tokio::time::timeout(self.timeout, self.transcriber.transcribe(&self.path))
+                .await
+                .map_err(|_| InputError::TimedOut {
+                    path: self.path.clone(),
+                    after: self.timeout,
+                })??;
+        if transcript.is_empty() {
+            return Err(InputError::EmptyTranscript(self.path.clone()));
+        }
+        Ok(transcript)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_note(name: &str, content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("voice_note_tests");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_is_audio() {
+        assert!(is_audio(Path::new("memo.M4A")));
+        assert!(is_audio(Path::new("note.ogg")));
+        assert!(!is_audio(Path::new("report.pdf")));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_audio_and_missing_files() {
+        let transcriber = Transcriber::command("cat").unwrap();
+
+        assert_eq!(
+            VoiceNoteInput::new("notes.txt", transcriber.clone())
+                .read()
+                .await,
+            Err(InputError::NotAudio(PathBuf::from("notes.txt")))
+        );
+        assert_eq!(
+            VoiceNoteInput::new("missing.wav", transcriber).read().await,
+            Err(InputError::NotFound(PathBuf::from("missing.wav")))
+        );
+    }
+
+    #[test]
+    fn test_unconfigured_transcriber() {
+        assert_eq!(
+            VoiceNoteInput::from_config("note.wav", &TranscriptionConfig::default()),
+            Err(InputError::TranscriberNotConfigured)
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_silent_recording() {
+        let path = temp_note("silence.wav", "\n\n");
+
+        let result = VoiceNoteInput::new(&path, Transcriber::command("cat").unwrap())
+            .read()
+            .await;
+
+        assert_eq!(result, Err(InputError::EmptyTranscript(path)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_slow_transcriber_times_out() {
+        let path = temp_note("long.wav", "");
+        let transcriber = Transcriber::Command {
+            program: "sh".to_string(),
+            args: vec!["-c".to_string(), "sleep 5".to_string()],
+        };
+
+        let result = VoiceNoteInput::new(&path, transcriber)
+            .with_timeout(Duration::from_millis(50))
+            .read()
+            .await;
+
+        assert!(matches!(result, Err(InputError::TimedOut { .. })));
+    }
+}
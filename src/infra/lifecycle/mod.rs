@@ -0,0 +1,3 @@
+pub mod shutdown;
+
+pub use shutdown::{InFlightGuard, Shutdown, ShutdownReport};
@@ -0,0 +1,227 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{Notify, watch};
+
+use crate::config::Config;
+
+type DrainHook = Box<dyn FnOnce() -> Result<(), String> + Send>;
+
+/// What happened during shutdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShutdownReport {
+    /// False when the drain timeout passed with work still running
+    pub drained: bool,
+    pub abandoned: usize,
+    pub hook_errors: Vec<(String, String)>,
+}
+
+/// Coordinates a graceful stop: refuse new work, wait (bounded) for
+/// in-flight work, run persistence hooks, then let the process exit
+#[derive(Clone)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    shutting_down: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+    signal: watch::Sender<bool>,
+    hooks: Mutex<Vec<(String, DrainHook)>>,
+    drain_timeout: Duration,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(
+            Config::get().shutdown.drain_timeout_secs,
+        ))
+    }
+}
+
+impl Shutdown {
+    pub fn new(drain_timeout: Duration) -> Self {
+        let (signal, _) = watch::channel(false);
+        Self {
+            inner: Arc::new(Inner {
+                shutting_down: AtomicBool::new(false),
+                in_flight: AtomicUsize::new(0),
+                idle: Notify::new(),
+                signal,
+                hooks: Mutex::new(Vec::new()),
+                drain_timeout,
+            }),
+        }
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.shutting_down.load(Ordering::SeqCst)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Registers a unit of work (a classification, a delivery). Returns
+    /// `None` once shutdown has started; the caller should reject the
+    /// request. The work counts as finished when the guard is dropped.
+    pub fn track(&self) -> Option<InFlightGuard> {
+        if self.is_shutting_down() {
+            return None;
+        }
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        // Shutdown may have started between the check and the increment
+        let guard = InFlightGuard {
+            inner: self.inner.clone(),
+        };
+        if self.is_shutting_down() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Runs after in-flight work has drained, e.g. to persist the outbox
+    pub fn on_drained<F>(&self, name: &str, hook: F)
+    where
+        F: FnOnce() -> Result<(), String> + Send + 'static,
+    {
+        self.inner
+            .hooks
+            .lock()
+            .unwrap()
+            .push((name.to_string(), Box::new(hook)));
+    }
+
+    /// Resolves once shutdown has started; for accept loops and workers
+    pub async fn started(&self) {
+        let mut signal = self.inner.signal.subscribe();
+        let _ = signal.wait_for(|started| *started).await;
+    }
+
+    pub async fn shutdown(&self) -> ShutdownReport {
+        self.inner.shutting_down.store(true, Ordering::SeqCst);
+        self.inner.signal.send_replace(true);
+
+        let drained = tokio::time::timeout(self.inner.drain_timeout, self.wait_idle())
+            .await
+            .is_ok();
+        let abandoned = self.in_flight();
+
+        let hooks = std::mem::take(&mut *self.inner.hooks.lock().unwrap());
+        let hook_errors = hooks
+            .into_iter()
+            .filter_map(|(name, hook)| hook().err().map(|e| (name, e)))
+            .collect();
+
+        ShutdownReport {
+            drained,
+            abandoned,
+            hook_errors,
+        }
+    }
+
+    /// Waits for Ctrl+C, then shuts down
+    pub async fn on_ctrl_c(&self) -> ShutdownReport {
+        let _ = tokio::signal::ctrl_c().await;
+        self.shutdown().await
+    }
+
+    async fn wait_idle(&self) {
+        loop {
+            let idle = self.inner.idle.notified();
+            if self.in_flight() == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+}
+
+pub struct InFlightGuard {
+    inner: Arc<Inner>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.inner.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rejects_new_work_after_shutdown() {
+        let shutdown = Shutdown::new(Duration::from_millis(50));
+
+        shutdown.shutdown().await;
+
+        assert!(shutdown.is_shutting_down());
+        assert!(shutdown.track().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_in_flight_work() {
+        let shutdown = Shutdown::new(Duration::from_secs(5));
+        let guard = shutdown.track().unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(guard);
+        });
+
+        let report = shutdown.shutdown().await;
+
+        assert!(report.drained);
+        assert_eq!(report.abandoned, 0);
+    }
+
+    #[tokio::test]
+    async fn test_drain_is_bounded() {
+        let shutdown = Shutdown::new(Duration::from_millis(20));
+        let _guard = shutdown.track().unwrap();
+
+        let report = shutdown.shutdown().await;
+
+        assert!(!report.drained);
+        assert_eq!(report.abandoned, 1);
+    }
+
+    #[tokio::test]
+    async fn test_hooks_run_after_drain() {
+        let shutdown = Shutdown::new(Duration::from_millis(50));
+        let persisted = Arc::new(AtomicBool::new(false));
+        let flag = persisted.clone();
+        shutdown.on_drained("outbox", move || {
+            flag.store(true, Ordering::SeqCst);
+            Ok(())
+        });
+        shutdown.on_drained("sessions", || Err("disk full".to_string()));
+
+        let report = shutdown.shutdown().await;
+
+        assert!(persisted.load(Ordering::SeqCst));
+        assert_eq!(
+            report.hook_errors,
+            vec![("sessions".to_string(), "disk full".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_started_resolves_on_shutdown() {
+        let shutdown = Shutdown::new(Duration::from_millis(50));
+        let waiter = shutdown.clone();
+        let handle = tokio::spawn(async move { waiter.started().await });
+
+        shutdown.shutdown().await;
+
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -1,4 +1,11 @@
 pub mod contacts;
 pub mod email;
+pub mod files;
 pub mod http;
+#[cfg(feature = "imap")]
+pub mod imap;
+pub mod input;
+pub mod lifecycle;
 pub mod ollama;
+pub mod resilience;
+pub mod smtp;
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::infra::ollama::OllamaChat;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+impl fmt::Display for ChatRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChatRole::System => write!(f, "system"),
+            ChatRole::User => write!(f, "user"),
+            ChatRole::Assistant => write!(f, "assistant"),
+            ChatRole::Tool => write!(f, "tool"),
+        }
+    }
+}
+
+/// Ordered chat messages for an Ollama `/api/chat` request.
+///
+/// System messages are kept ahead of the conversation regardless of the
+/// order they were added in, so instructions always come first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChatMessages {
+    system: Vec<OllamaChat>,
+    conversation: Vec<OllamaChat>,
+}
+
+impl ChatMessages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn system(mut self, content: &str) -> Self {
+        self.system.push(OllamaChat::system(content.to_string()));
+        self
+    }
+
+    pub fn user(mut self, content: &str) -> Self {
+        self.conversation
+            .push(OllamaChat::user(content.to_string()));
+        self
+    }
+
+    pub fn assistant(mut self, content: &str) -> Self {
+        self.conversation
+            .push(OllamaChat::assistant(content.to_string()));
+        self
+    }
+
+    /// Result of a tool call, attributed to the tool that produced it
+    pub fn tool(mut self, tool_name: &str, content: &str) -> Self {
+        self.conversation
+            .push(OllamaChat::tool(tool_name.to_string(), content.to_string()));
+        self
+    }
+
+    /// Few-shot example as a user turn followed by the expected answer
+    pub fn example(self, input: &str, output: &str) -> Self {
+        self.user(input).assistant(output)
+    }
+
+    /// Appends earlier turns, e.g. from a session
+    pub fn history<I>(mut self, turns: I) -> Self
+    where
+        I: IntoIterator<Item = OllamaChat>,
+    {
+        for turn in turns {
+            if turn.role == ChatRole::System.to_string() {
+                self.system.push(turn);
+            } else {
+                self.conversation.push(turn);
+            }
+        }
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.system.len() + self.conversation.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn into_vec(self) -> Vec<OllamaChat> {
+        let mut messages = self.system;
+        messages.extend(self.conversation);
+        messages
+    }
+}
+
+impl From<ChatMessages> for Vec<OllamaChat> {
+    fn from(messages: ChatMessages) -> Self {
+        messages.into_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_order() {
+        let messages = ChatMessages::new()
+            .system("Classify intents")
+            .example("Send an email to Carlos", r#"{"intent":"send_email"}"#)
+            .user("Avise a Eva")
+            .into_vec();
+
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "user"]);
+        assert_eq!(messages[3].content, "Avise a Eva");
+    }
+
+    #[test]
+    fn test_system_messages_come_first() {
+        let messages = ChatMessages::new().user("hi").system("be brief").into_vec();
+
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(messages[1].role, "user");
+    }
+
+    #[test]
+    fn test_tool_message_serialization() {
+        let messages = ChatMessages::new().tool("lookup_contact", "eva@company.com");
+
+        let json = serde_json::to_string(&messages.into_vec()).unwrap();
+
+        assert_eq!(
+            json,
+            r#"[{"role":"tool","content":"eva@company.com","tool_name":"lookup_contact"}]"#
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let history = vec![
+            OllamaChat::system("old instructions".to_string()),
+            OllamaChat::user("first".to_string()),
+            OllamaChat::assistant("ok".to_string()),
+        ];
+
+        let messages = ChatMessages::new().history(history).user("second");
+
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages.into_vec()[0].content, "old instructions");
+    }
+}
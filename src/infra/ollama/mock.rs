@@ -0,0 +1,149 @@
+use futures_core::future::BoxFuture;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::agent::{AgentError, ChatModel};
+use crate::infra::ollama::{
+    ChatMessages, Modelfile, OllamaCreateResponse, OllamaCreateStatusMessage,
+    OllamaResponseMessage, OutputConstraint,
+};
+
+/// Stands in for `OllamaClient` wherever a `ChatModel` is taken, e.g.
+/// `IntentClassifierAgent::with_chat_model`: answers with canned messages
+/// in order and records every request and created model, so agents can be
+/// tested without an Ollama server
+#[derive(Debug, Default)]
+pub struct MockOllamaClient {
+    replies: Mutex<VecDeque<Result<String, AgentError>>>,
+    /// Given once the queued replies run out
+    fallback: Option<String>,
+    requests: Mutex<Vec<ChatMessages>>,
+    constraints: Mutex<Vec<OutputConstraint>>,
+    created: Mutex<Vec<(String, Modelfile)>>,
+}
+
+impl MockOllamaClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues an answer with `content`
+    pub fn with_reply(self, content: &str) -> Self {
+        self.push(Ok(content.to_string()))
+    }
+
+    /// Queues a canned response message
+    pub fn with_message(self, message: OllamaResponseMessage) -> Self {
+        self.push(Ok(message.raw_content().to_string()))
+    }
+
+    /// Queues a failed request, e.g. `AgentError::NetworkError`
+    pub fn with_error(self, error: AgentError) -> Self {
+        self.push(Err(error))
+    }
+
+    /// Answer repeated after the queued ones; without it an exhausted mock
+    /// fails the request
+    pub fn with_fallback(mut self, content: &str) -> Self {
+        self.fallback = Some(content.to_string());
+        self
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<ChatMessages> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Constraint of each request, `Unconstrained` for plain ones
+    pub fn constraints(&self) -> Vec<OutputConstraint> {
+        self.constraints.lock().unwrap().clone()
+    }
+
+    /// Models created so far with their Modelfiles, oldest first
+    pub fn created_models(&self) -> Vec<(String, Modelfile)> {
+        self.created.lock().unwrap().clone()
+    }
+
+    /// Content of the last message of the last request
+    pub fn last_prompt(&self) -> Option<String> {
+        self.requests
+            .lock()
+            .unwrap()
+            .last()
+            .and_then(|messages| messages.clone().into_vec().pop())
+            .map(|message| message.content)
+    }
+
+    fn push(self, reply: Result<String, AgentError>) -> Self {
+        self.replies.lock().unwrap().push_back(reply);
+        self
+    }
+}
+
+impl ChatModel for MockOllamaClient {
+    fn chat(&self, messages: ChatMessages) -> BoxFuture<'_, Result<String, AgentError>> {
+        self.chat_constrained(messages, &OutputConstraint::Unconstrained)
+    }
+
+    fn chat_constrained<'a>(
+        &'a self,
+        messages: ChatMessages,
+        constraint: &'a OutputConstraint,
+    ) -> BoxFuture<'a, Result<String, AgentError>> {
+        self.requests.lock().unwrap().push(messages);
+        self.constraints.lock().unwrap().push(constraint.clone());
+        let reply = match self.replies.lock().unwrap().pop_front() {
+            Some(reply) => reply,
+            None => self.fallback.clone().ok_or_else(|| {
+                AgentError::ProcessingError("MockOllamaClient has no reply left".to_string())
+            }),
+        };
+        Box::pin(std::future::ready(reply))
+    }
+
+    /// Records the model and reports success
+    fn create_model<'a>(
+        &'a self,
+        name: &'a str,
+        modelfile: &'a Modelfile,
+    ) -> BoxFuture<'a, Result<OllamaCreateResponse, AgentError>> {
+        self.created
+            .lock()
+            .unwrap()
+            .push((name.to_string(), modelfile.clone()));
+        Box::pin(std::future::ready(Ok(
+            OllamaCreateResponse::new_with_status_messages(vec![OllamaCreateStatusMessage {
+                status: "success".to_string(),
+            }]),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_replies_in_order_then_fallback() {
+        let mock = MockOllamaClient::new()
+            .with_message(OllamaResponseMessage::assistant(
+                r#"{"intent": "no_action"}"#,
+            ))
+            .with_error(AgentError::NetworkError("connection refused".to_string()))
+            .with_fallback("ok");
+        let ask = |prompt: &str| ChatMessages::new().system("Be brief").user(prompt);
+
+        assert_eq!(
+            mock.chat(ask("Classify")).await.unwrap(),
+            r#"{"intent": "no_action"}"#
+        );
+        assert!(matches!(
+            mock.chat(ask("Again")).await,
+            Err(AgentError::NetworkError(_))
+        ));
+        assert_eq!(mock.chat(ask("Once more")).await.unwrap(), "ok");
+        assert_eq!(mock.requests().len(), 3);
+        assert_eq!(mock.last_prompt().as_deref(), Some("Once more"));
+        assert!(MockOllamaClient::new().chat(ask("Hi")).await.is_err());
+    }
+}
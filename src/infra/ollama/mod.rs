@@ -1,17 +1,41 @@
+pub mod chat_messages;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+pub mod model_capabilities;
+pub mod modelfile;
 pub mod ollama_chat;
 pub mod ollama_chat_request;
+pub mod ollama_chunk;
 pub mod ollama_client;
 pub mod ollama_create_reponse;
 pub mod ollama_create_request;
 pub mod ollama_intent_response_content;
+pub mod ollama_json_content;
 pub mod ollama_response;
 pub mod ollama_response_message;
+pub mod output_constraint;
+pub mod prompt_trace;
+pub mod response_cache;
 
+pub use chat_messages::{ChatMessages, ChatRole};
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::MockOllamaClient;
+pub use model_capabilities::{
+    ModelCapabilities, OutputMode, PromptStrategy, ToolMode, api_url, show_url,
+};
+pub use modelfile::Modelfile;
 pub use ollama_chat::OllamaChat;
 pub use ollama_chat_request::OllamaChatRequest;
+pub use ollama_chunk::{ChunkDecoder, ChunkStream, OllamaChunk, OllamaChunkMessage};
 pub use ollama_client::OllamaClient;
-pub use ollama_create_reponse::OllamaCreateResponse;
+pub use ollama_create_reponse::{OllamaCreateResponse, OllamaCreateStatusMessage};
 pub use ollama_create_request::OllamaCreateRequest;
 pub use ollama_intent_response_content::OllamaIntentResponseContent;
+pub use ollama_json_content::{
+    FINAL_ANSWER_MARKER, extract_json, isolate_final_answer, parse_json_content,
+};
 pub use ollama_response::OllamaResponse;
 pub use ollama_response_message::OllamaResponseMessage;
+pub use output_constraint::{OutputConstraint, OutputFormat};
+pub use prompt_trace::{PromptTraceEntry, PromptTracer};
+pub use response_cache::ResponseCache;
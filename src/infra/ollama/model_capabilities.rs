@@ -0,0 +1,293 @@
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::infra::ollama::OutputConstraint;
+
+/// How long a failed probe keeps the model at `unknown` before the
+/// server is asked again
+pub const FAILED_PROBE_TTL: Duration = Duration::from_secs(30);
+
+/// Probe results per (`/api/show` url, model). Answers are kept for the
+/// process, failures only for `FAILED_PROBE_TTL`.
+static PROBED: Lazy<Mutex<HashMap<(String, String), Probe>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone)]
+enum Probe {
+    Answered(ModelCapabilities),
+    Failed { at: Instant },
+}
+
+/// What a model supports, as reported by Ollama's `/api/show`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    pub model: String,
+    /// Context window in tokens; `None` when not reported
+    pub context_length: Option<u64>,
+    /// Takes a JSON schema as `format`. Servers that report capabilities
+    /// at all are recent enough for structured outputs.
+    pub structured_output: bool,
+    /// Takes native tool definitions
+    pub tools: bool,
+}
+
+impl ModelCapabilities {
+    /// Assumed when probing is off or fails: schema output, which the
+    /// client already falls back from, and no tools
+    pub fn unknown(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            context_length: None,
+            structured_output: true,
+            tools: false,
+        }
+    }
+
+    pub fn from_show_response(model: &str, show: &Value) -> Self {
+        let capabilities = show.get("capabilities").and_then(Value::as_array);
+        let has = |name: &str| {
+            capabilities.is_some_and(|list| list.iter().any(|c| c.as_str() == Some(name)))
+        };
+        let context_length = show
+            .get("model_info")
+            .and_then(Value::as_object)
+            .and_then(|info| {
+                info.iter()
+                    .find(|(key, _)| key.ends_with(".context_length"))
+                    .and_then(|(_, value)| value.as_u64())
+            });
+        Self {
+            model: model.to_string(),
+            context_length,
+            structured_output: has("completion"),
+            tools: has("tools"),
+        }
+    }
+
+    pub fn strategy(&self) -> PromptStrategy {
+        PromptStrategy {
+            output: if self.structured_output {
+                OutputMode::Schema
+            } else {
+                OutputMode::FencedJson
+            },
+            tools: if self.tools {
+                ToolMode::ToolLoop
+            } else {
+                ToolMode::Extraction
+            },
+            context_length: self.context_length,
+        }
+    }
+}
+
+/// How to get structured answers out of the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    /// `format` carries the JSON schema of the answer
+    Schema,
+    /// No schema; the prompt asks for a ```json block
+    FencedJson,
+}
+
+impl OutputMode {
+    /// Constraint to send for an agent that would like `preferred`
+    pub fn constrain(&self, preferred: OutputConstraint) -> OutputConstraint {
+        match (self, preferred) {
+            (OutputMode::FencedJson, OutputConstraint::Schema(_)) => {
+                OutputConstraint::Unconstrained
+            }
+            (_, preferred) => preferred,
+        }
+    }
+}
+
+/// How agents that need lookups get them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolMode {
+    /// The model calls tools itself (`agent::tools::ToolLoop`)
+    ToolLoop,
+    /// The model only extracts fields and the code does the lookups
+    Extraction,
+}
+
+/// Prompting strategy chosen from the model's capabilities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PromptStrategy {
+    pub output: OutputMode,
+    pub tools: ToolMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_length: Option<u64>,
+}
+
+impl fmt::Display for PromptStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = match self.output {
+            OutputMode::Schema => "schema-constrained output",
+            OutputMode::FencedJson => "fenced JSON output",
+        };
+        let tools = match self.tools {
+            ToolMode::ToolLoop => "tool loop",
+            ToolMode::Extraction => "extraction",
+        };
+        write!(f, "{}, {}", output, tools)?;
+        if let Some(context_length) = self.context_length {
+            write!(f, ", {} token context", context_length)?;
+        }
+        Ok(())
+    }
+}
+
+/// `/api/show` on the same host as the configured `/api/chat` url
+pub fn show_url(chat_url: &str) -> String {
+    api_url(chat_url, "show")
+}
+
+/// Another Ollama endpoint (`show`, `embed`, ...) on the host of `chat_url`
+pub fn api_url(chat_url: &str, endpoint: &str) -> String {
+    match chat_url.strip_suffix("/api/chat") {
+        Some(base) => format!("{}/api/{}", base, endpoint),
+        None => format!("{}/api/{}", chat_url.trim_end_matches('/'), endpoint),
+    }
+}
+
+pub(crate) fn probed(show_url: &str, model: &str) -> Option<ModelCapabilities> {
+    let mut probed = PROBED.lock().unwrap();
+    let key = (show_url.to_string(), model.to_string());
+    match probed.get(&key)? {
+        Probe::Answered(capabilities) => Some(capabilities.clone()),
+        Probe::Failed { at } if at.elapsed() < FAILED_PROBE_TTL => {
+            Some(ModelCapabilities::unknown(model))
+        }
+        Probe::Failed { .. } => {
+            probed.remove(&key);
+            None
+        }
+    }
+}
+
+pub(crate) fn remember(show_url: &str, capabilities: &ModelCapabilities) {
+    PROBED.lock().unwrap().insert(
+        (show_url.to_string(), capabilities.model.clone()),
+        Probe::Answered(capabilities.clone()),
+    );
+}
+
+/// Keeps a down server from being probed on every request, for a while
+pub(crate) fn remember_failure(show_url: &str, model: &str) {
+    PROBED.lock().unwrap().insert(
+        (show_url.to_string(), model.to_string()),
+        Probe::Failed { at: Instant::now() },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_show_response() {
+        let show = json!({
+            "capabilities": ["completion", "tools"],
+            "model_info": {
+                "general.architecture": "qwen3",
+                "qwen3.context_length": 40960
+            }
+        });
+
+        let capabilities = ModelCapabilities::from_show_response("qwen3", &show);
+
+        assert_eq!(capabilities.context_length, Some(40960));
+        assert!(capabilities.structured_output);
+        assert_eq!(
+            capabilities.strategy(),
+            PromptStrategy {
+                output: OutputMode::Schema,
+                tools: ToolMode::ToolLoop,
+                context_length: Some(40960),
+            }
+        );
+    }
+
+    #[test]
+    fn test_old_servers_get_fenced_json() {
+        let show = json!({"modelfile": "FROM llama2", "details": {"format": "gguf"}});
+
+        let strategy = ModelCapabilities::from_show_response("llama2", &show).strategy();
+
+        assert_eq!(strategy.output, OutputMode::FencedJson);
+        assert_eq!(strategy.tools, ToolMode::Extraction);
+        assert_eq!(strategy.to_string(), "fenced JSON output, extraction");
+    }
+
+    #[test]
+    fn test_fenced_json_drops_schema_only() {
+        let schema = OutputConstraint::Schema(json!({"type": "object"}));
+
+        assert_eq!(
+            OutputMode::FencedJson.constrain(schema.clone()),
+            OutputConstraint::Unconstrained
+        );
+        assert_eq!(
+            OutputMode::FencedJson.constrain(OutputConstraint::Json),
+            OutputConstraint::Json
+        );
+        assert_eq!(OutputMode::Schema.constrain(schema.clone()), schema);
+    }
+
+    #[test]
+    fn test_show_url() {
+        assert_eq!(
+            show_url("http://localhost:11434/api/chat"),
+            "http://localhost:11434/api/show"
+        );
+        assert_eq!(
+            show_url("http://gpu-box:11434/"),
+            "http://gpu-box:11434/api/show"
+        );
+        assert_eq!(
+            api_url("http://localhost:11434/api/chat", "embed"),
+            "http://localhost:11434/api/embed"
+        );
+    }
+
+    #[test]
+    fn test_probes_are_remembered_per_model() {
+        let url = "http://probe-test:11434/api/show";
+        remember(url, &ModelCapabilities::unknown("phi4"));
+
+        assert_eq!(
+            probed(url, "phi4"),
+            Some(ModelCapabilities::unknown("phi4"))
+        );
+        assert_eq!(probed(url, "gemma3"), None);
+    }
+
+    #[test]
+    fn test_failed_probes_expire() {
+        let url = "http://failed-probe-test:11434/api/show";
+        remember_failure(url, "phi4");
+        remember_failure(url, "gemma3");
+        PROBED.lock().unwrap().insert(
+            (url.to_string(), "gemma3".to_string()),
+            Probe::Failed {
+                at: Instant::now() - FAILED_PROBE_TTL,
+            },
+        );
+
+        assert_eq!(
+            probed(url, "phi4"),
+            Some(ModelCapabilities::unknown("phi4"))
+        );
+        assert_eq!(probed(url, "gemma3"), None);
+    }
+}
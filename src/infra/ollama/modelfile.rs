@@ -0,0 +1,109 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::infra::ollama::OllamaCreateRequest;
+
+/// A custom model: a base model with a system prompt and parameters baked
+/// in, registered on the server through `/api/create`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Modelfile {
+    from: String,
+    system: Option<String>,
+    parameters: BTreeMap<String, Value>,
+}
+
+impl Modelfile {
+    pub fn new(from: &str) -> Self {
+        Self {
+            from: from.to_string(),
+            system: None,
+            parameters: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_system(mut self, system: &str) -> Self {
+        self.system = Some(system.to_string());
+        self
+    }
+
+    /// Sets a `PARAMETER`, e.g. `temperature` or `num_ctx`
+    pub fn with_parameter(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.parameters.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Base model this one is created from
+    pub fn from_model(&self) -> &str {
+        &self.from
+    }
+
+    pub fn system(&self) -> Option<&str> {
+        self.system.as_deref()
+    }
+
+    pub fn parameters(&self) -> &BTreeMap<String, Value> {
+        &self.parameters
+    }
+
+    /// Request creating this model as `name`, answered with a single status
+    pub fn create_request(&self, name: &str) -> OllamaCreateRequest {
+        let mut request = OllamaCreateRequest::new(
+            name.to_string(),
+            self.from.clone(),
+            self.system.clone().unwrap_or_default(),
+            name.to_string(),
+        );
+        request.parameters = self.parameters.clone();
+        request.stream = Some(false);
+        request
+    }
+}
+
+/// Renders the Modelfile as `ollama create -f` reads it
+impl fmt::Display for Modelfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "FROM {}", self.from)?;
+        for (name, value) in &self.parameters {
+            match value {
+                Value::String(text) => writeln!(f, "PARAMETER {} {}", name, text)?,
+                value => writeln!(f, "PARAMETER {} {}", name, value)?,
+            }
+        }
+        if let Some(system) = &self.system {
+            writeln!(f, "SYSTEM \"\"\"{}\"\"\"", system)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modelfile() -> Modelfile {
+        Modelfile::new("gemma3")
+            .with_system("Classify intent.")
+            .with_parameter("temperature", 0)
+            .with_parameter("stop", "```")
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            modelfile().to_string(),
+            "FROM gemma3\nPARAMETER stop ```\nPARAMETER temperature 0\nSYSTEM \"\"\"Classify intent.\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_create_request() {
+        let json = serde_json::to_value(modelfile().create_request("classifier")).unwrap();
+
+        assert_eq!(json["model"], "classifier");
+        assert_eq!(json["from"], "gemma3");
+        assert_eq!(json["system"], "Classify intent.");
+        assert_eq!(json["parameters"]["temperature"], 0);
+        assert_eq!(json["stream"], false);
+    }
+}
@@ -4,11 +4,21 @@ use serde::{Deserialize, Serialize};
 pub struct OllamaChat {
     pub role: String,
     pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
 }
 
 impl OllamaChat {
     pub fn new(role: String, content: String) -> Self {
-        Self { role, content }
+        Self {
+            role,
+            content,
+            tool_name: None,
+        }
+    }
+
+    pub fn system(content: String) -> Self {
+        Self::new("system".to_string(), content)
     }
 
     pub fn user(content: String) -> Self {
@@ -18,6 +28,13 @@ impl OllamaChat {
     pub fn assistant(content: String) -> Self {
         Self::new("assistant".to_string(), content)
     }
+
+    pub fn tool(tool_name: String, content: String) -> Self {
+        Self {
+            tool_name: Some(tool_name),
+            ..Self::new("tool".to_string(), content)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::infra::ollama::OllamaChat;
 
@@ -9,18 +10,23 @@ pub struct OllamaChatRequest {
     pub stream: bool,
     #[serde(default)]
     pub think: bool,
+    /// `"json"` or a JSON schema the output must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<Value>,
+    /// Sampling options such as `temperature`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Value>,
 }
 
 impl OllamaChatRequest {
     pub fn new(model: String, content: String) -> Self {
         Self {
             model,
-            messages: vec![OllamaChat {
-                role: "user".to_string(),
-                content,
-            }],
+            messages: vec![OllamaChat::user(content)],
             stream: false,
             think: false,
+            format: None,
+            options: None,
         }
     }
 
@@ -30,8 +36,20 @@ impl OllamaChatRequest {
             messages,
             stream: false,
             think: false,
+            format: None,
+            options: None,
         }
     }
+
+    pub fn with_format(mut self, format: Option<Value>) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_options(mut self, options: Option<Value>) -> Self {
+        self.options = options;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -47,8 +65,8 @@ mod tests {
         assert_eq!(request.messages.len(), 1);
         assert_eq!(request.messages[0].role, "user");
         assert_eq!(request.messages[0].content, "Hello world");
-        assert_eq!(request.stream, false);
-        assert_eq!(request.think, false);
+        assert!(!request.stream);
+        assert!(!request.think);
     }
 
     #[test]
@@ -61,8 +79,8 @@ mod tests {
 
         assert_eq!(request.model, "llama2");
         assert_eq!(request.messages, messages);
-        assert_eq!(request.stream, false);
-        assert_eq!(request.think, false);
+        assert!(!request.stream);
+        assert!(!request.think);
     }
 
     #[test]
@@ -98,8 +116,8 @@ mod tests {
         assert_eq!(request.messages.len(), 1);
         assert_eq!(request.messages[0].role, "user");
         assert_eq!(request.messages[0].content, "Test message");
-        assert_eq!(request.stream, false);
-        assert_eq!(request.think, false); // Default value when not present
+        assert!(!request.stream);
+        assert!(!request.think); // Default value when not present
     }
 
     #[test]
@@ -116,8 +134,8 @@ mod tests {
         assert_eq!(request.messages[1].content, "Hi there!");
         assert_eq!(request.messages[2].role, "user");
         assert_eq!(request.messages[2].content, "How are you?");
-        assert_eq!(request.stream, true);
-        assert_eq!(request.think, false); // Default value when not present
+        assert!(request.stream);
+        assert!(!request.think); // Default value when not present
     }
 
     #[test]
@@ -193,7 +211,26 @@ JSON special: {"key": "value"}"#;
 
         assert_eq!(request, deserialized);
         assert_eq!(deserialized.messages.len(), 0);
-        assert_eq!(deserialized.think, false);
+        assert!(!deserialized.think);
+    }
+
+    #[test]
+    fn test_ollama_request_format_is_omitted_by_default() {
+        let request = OllamaChatRequest::new("llama2".to_string(), "Hi".to_string());
+
+        assert!(!serde_json::to_string(&request).unwrap().contains("format"));
+    }
+
+    #[test]
+    fn test_ollama_request_with_format() {
+        let request = OllamaChatRequest::new("llama2".to_string(), "Hi".to_string())
+            .with_format(Some(Value::String("json".to_string())));
+
+        assert!(
+            serde_json::to_string(&request)
+                .unwrap()
+                .ends_with(r#""format":"json"}"#)
+        );
     }
 
     #[test]
@@ -208,7 +245,7 @@ JSON special: {"key": "value"}"#;
 
         let deserialized: OllamaChatRequest =
             serde_json::from_str(&json).expect("Deserialization should succeed");
-        assert_eq!(deserialized.think, true);
+        assert!(deserialized.think);
     }
 
     #[test]
@@ -221,7 +258,7 @@ JSON special: {"key": "value"}"#;
         assert_eq!(request.messages.len(), 1);
         assert_eq!(request.messages[0].role, "user");
         assert_eq!(request.messages[0].content, "Hello");
-        assert_eq!(request.stream, false);
-        assert_eq!(request.think, true);
+        assert!(!request.stream);
+        assert!(request.think);
     }
 }
@@ -0,0 +1,174 @@
+use futures_core::Stream;
+use serde::Deserialize;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+
+use crate::agent::AgentError;
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+pub struct OllamaChunkMessage {
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+/// One line of a streamed `/api/chat` response. Only the last chunk, with
+/// `done` set, carries the timings and token counts.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct OllamaChunk {
+    pub model: String,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub message: OllamaChunkMessage,
+    pub done: bool,
+    #[serde(default)]
+    pub done_reason: Option<String>,
+    #[serde(default)]
+    pub total_duration: u64,
+    #[serde(default)]
+    pub prompt_eval_count: u32,
+    #[serde(default)]
+    pub eval_count: u32,
+}
+
+impl OllamaChunk {
+    /// Text added by this chunk
+    pub fn content(&self) -> &str {
+        &self.message.content
+    }
+
+    /// Prompt and completion tokens; zero before the last chunk
+    pub fn tokens(&self) -> u64 {
+        u64::from(self.prompt_eval_count) + u64::from(self.eval_count)
+    }
+}
+
+#[derive(Deserialize)]
+struct StreamFailure {
+    error: String,
+}
+
+/// Splits newline-delimited JSON into chunks as bytes arrive, keeping
+/// partial lines until the rest shows up
+#[derive(Debug, Default)]
+pub struct ChunkDecoder {
+    buffer: Vec<u8>,
+}
+
+impl ChunkDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Chunks completed by `bytes`
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Result<OllamaChunk, AgentError>> {
+        self.buffer.extend_from_slice(bytes);
+        let mut chunks = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            if let Some(chunk) = decode_line(&line) {
+                chunks.push(chunk);
+            }
+        }
+        chunks
+    }
+
+    /// The last chunk if the response did not end with a newline
+    pub fn finish(&mut self) -> Option<Result<OllamaChunk, AgentError>> {
+        let line = std::mem::take(&mut self.buffer);
+        decode_line(&line)
+    }
+}
+
+fn decode_line(line: &[u8]) -> Option<Result<OllamaChunk, AgentError>> {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    if let Ok(failure) = serde_json::from_str::<StreamFailure>(line) {
+        return Some(Err(AgentError::NetworkError(failure.error)));
+    }
+    Some(
+        serde_json::from_str(line)
+            .map_err(|e| AgentError::ParseError(format!("Invalid stream chunk: {}", e))),
+    )
+}
+
+/// Chunks of a streamed reply as they arrive. Ends after the `done` chunk
+/// or the first error; dropping it stops reading the response.
+pub struct ChunkStream {
+    receiver: mpsc::Receiver<Result<OllamaChunk, AgentError>>,
+}
+
+impl ChunkStream {
+    pub(crate) fn new(receiver: mpsc::Receiver<Result<OllamaChunk, AgentError>>) -> Self {
+        Self { receiver }
+    }
+
+    pub async fn next_chunk(&mut self) -> Option<Result<OllamaChunk, AgentError>> {
+        self.receiver.recv().await
+    }
+
+    /// Reads the stream to its end, returning the whole text and the last
+    /// chunk
+    pub async fn collect_text(mut self) -> Result<(String, Option<OllamaChunk>), AgentError> {
+        let mut text = String::new();
+        let mut last = None;
+        while let Some(chunk) = self.next_chunk().await {
+            let chunk = chunk?;
+            text.push_str(chunk.content());
+            last = Some(chunk);
+        }
+        Ok((text, last))
+    }
+}
+
+impl Stream for ChunkStream {
+    type Item = Result<OllamaChunk, AgentError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_handles_split_lines() {
+        let mut decoder = ChunkDecoder::new();
+
+        let first = decoder.push(
+            br#"{"model":"llama3","message":{"role":"assistant","content":"Hel"},"done":false}
+{"model":"llama3","message":{"role":"assistant","con"#,
+        );
+        let second = decoder.push(
+            br#"tent":"lo"},"done":false}
+{"model":"llama3","message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":12,"eval_count":3}"#,
+        );
+        let last = decoder.finish().unwrap().unwrap();
+
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].as_ref().unwrap().content(), "Hel");
+        assert_eq!(second[0].as_ref().unwrap().content(), "lo");
+        assert!(last.done);
+        assert_eq!(last.tokens(), 15);
+        assert!(decoder.finish().is_none());
+    }
+
+    #[test]
+    fn test_decoder_reports_errors() {
+        let mut decoder = ChunkDecoder::new();
+
+        let chunks = decoder.push(b"{\"error\":\"model not found\"}\nnot json\n\n");
+
+        assert!(matches!(&chunks[0], Err(AgentError::NetworkError(e)) if e == "model not found"));
+        assert!(matches!(chunks[1], Err(AgentError::ParseError(_))));
+        assert_eq!(chunks.len(), 2);
+    }
+}
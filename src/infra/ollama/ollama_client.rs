@@ -1,69 +1,976 @@
-use crate::config::Config;
-use crate::infra::http::HttpClient;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use crate::agent::AgentError;
+use crate::agent::delegation::charge_llm_call;
+use crate::config::{ApiConfig, Config};
+use crate::infra::http::{HttpClient, HttpError};
+use crate::infra::ollama::model_capabilities::{probed, remember, remember_failure};
 use crate::infra::ollama::{
-    OllamaChatRequest, OllamaCreateRequest, OllamaCreateResponse, OllamaResponse,
+    ChatMessages, ChunkDecoder, ChunkStream, ModelCapabilities, Modelfile, OllamaChat,
+    OllamaChatRequest, OllamaChunk, OllamaCreateResponse, OllamaCreateStatusMessage,
+    OllamaResponse, OutputConstraint, PromptTracer, ResponseCache, api_url, show_url,
+};
+use crate::infra::resilience::{
+    CircuitError, CostGuard, DEFAULT_TENANT, Deadline, cost_guard, hedge_budget, hedged,
+    ollama_breaker,
 };
+use crate::prompt::tokenizer_for;
 
+/// Chunks buffered between the reading task and a slow consumer
+const STREAM_BUFFER: usize = 32;
+
+#[derive(Clone)]
 pub struct OllamaClient {
     http_client: HttpClient,
+    model: String,
+    deadline: Option<Deadline>,
+    hedge: Option<Hedge>,
+    tracer: Option<Arc<PromptTracer>>,
+    cache: Option<Arc<ResponseCache>>,
+    show_url: String,
+    embed_url: String,
+    create_url: String,
+    probe_timeout: Option<Duration>,
+    tenant: Option<String>,
+    /// Checked and charged in place of the process-wide guard
+    cost_guard: Option<Arc<CostGuard>>,
+    temperature: Option<f32>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// Second host/model that slow requests are duplicated to
+#[derive(Clone)]
+struct Hedge {
+    http_client: HttpClient,
+    model: String,
+    delay: Duration,
 }
 
 impl OllamaClient {
     pub fn new() -> Self {
+        Self::from_config(Config::get())
+    }
+
+    /// Client set up from `config` rather than the global config.toml: its
+    /// server and model, hedging, capability probe, tracing and cache
+    pub fn from_config(config: &Config) -> Self {
+        let api = &config.ollama.api;
+        let hedging = &config.resilience.hedging;
+        let probe = &config.ollama.probe;
+        let hedge = hedging.enabled.then(|| Hedge {
+            http_client: HttpClient::new(hedging.url.clone().unwrap_or_else(|| api.url.clone())),
+            model: hedging.model.clone().unwrap_or_else(|| api.model.clone()),
+            delay: Duration::from_millis(hedging.delay_ms),
+        });
         Self {
-            http_client: HttpClient::new(Config::get().ollama.api.url.clone()),
+            http_client: HttpClient::new(api.url.clone()),
+            model: api.model.clone(),
+            deadline: None,
+            hedge,
+            tracer: PromptTracer::from_config(&config.prompt_tracing, &config.safety.redaction),
+            cache: ResponseCache::from_config(&config.ollama.cache),
+            show_url: show_url(&api.url),
+            embed_url: api_url(&api.url, "embed"),
+            create_url: api_url(&api.url, "create"),
+            probe_timeout: probe
+                .enabled
+                .then(|| Duration::from_secs(probe.timeout_secs)),
+            tenant: None,
+            cost_guard: None,
+            temperature: api.temperature,
+            timeout: api.timeout_secs.map(Duration::from_secs),
         }
     }
 
-    pub async fn send_chat_request(
-        &self,
-        body: &str,
-    ) -> Result<OllamaResponse, Box<dyn std::error::Error>> {
-        let ollama_request =
-            OllamaChatRequest::new(Config::get().ollama.api.model.clone(), body.to_string());
-
-        let json_request = serde_json::to_string(&ollama_request);
-
-        match json_request {
-            Ok(request_body) => {
-                let response = self
-                    .http_client
-                    .send_request::<OllamaResponse>(request_body.as_str())
-                    .await?;
-
-                if response.success {
-                    response
-                        .data
-                        .ok_or_else(|| "No data received from Ollama API".into())
-                } else {
-                    let error_msg = response
-                        .error
-                        .map(|e| format!("{}: {}", e.error, e.message))
-                        .unwrap_or_else(|| "Unknown error occurred".to_string());
-                    Err(error_msg.into())
+    /// Targets the server, model and sampling settings of `api` instead of
+    /// the configured ones
+    pub fn with_api(mut self, api: &ApiConfig) -> Self {
+        self.http_client = HttpClient::new(api.url.clone());
+        self.model = api.model.clone();
+        self.show_url = show_url(&api.url);
+        self.embed_url = api_url(&api.url, "embed");
+        self.create_url = api_url(&api.url, "create");
+        self.temperature = api.temperature;
+        self.timeout = api.timeout_secs.map(Duration::from_secs);
+        self
+    }
+
+    /// Bounds every request (and retry) by the caller's deadline
+    pub fn with_deadline(mut self, deadline: Option<Deadline>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn deadline(&self) -> Option<Deadline> {
+        self.deadline
+    }
+
+    /// Targets a model other than the configured default
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Tenant whose token budgets requests are charged to
+    pub fn with_tenant(mut self, tenant: &str) -> Self {
+        self.tenant = Some(tenant.to_string());
+        self
+    }
+
+    pub fn tenant(&self) -> &str {
+        self.tenant.as_deref().unwrap_or(DEFAULT_TENANT)
+    }
+
+    /// Checks and charges token budgets against `cost_guard` instead of
+    /// the process-wide one
+    pub fn with_cost_guard(mut self, cost_guard: Arc<CostGuard>) -> Self {
+        self.cost_guard = Some(cost_guard);
+        self
+    }
+
+    fn cost_guard(&self) -> &CostGuard {
+        match &self.cost_guard {
+            Some(guard) => guard,
+            None => cost_guard(),
+        }
+    }
+
+    /// The configured timeout, cut short by the deadline if there is one
+    fn request_timeout(&self) -> Option<Duration> {
+        let remaining = self.deadline.map(|d| d.remaining());
+        match (remaining, self.timeout) {
+            (Some(remaining), Some(timeout)) => Some(remaining.min(timeout)),
+            (remaining, timeout) => remaining.or(timeout),
+        }
+    }
+
+    /// Chat request to the client's model with its sampling options
+    fn chat_request(&self, messages: Vec<OllamaChat>) -> OllamaChatRequest {
+        OllamaChatRequest::with_messages(self.model.clone(), messages).with_options(
+            self.temperature
+                .map(|temperature| serde_json::json!({ "temperature": temperature })),
+        )
+    }
+
+    /// Records every prompt and response sent by this client
+    pub fn with_prompt_tracer(mut self, tracer: Option<Arc<PromptTracer>>) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
+    /// Answers repeated requests from `cache` instead of the model
+    pub fn with_response_cache(mut self, cache: Option<Arc<ResponseCache>>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Turns `/api/show` probing on (giving up after `timeout`) or off
+    pub fn with_capability_probe(mut self, timeout: Option<Duration>) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// What the model supports. Probed once per host and model; a failed
+    /// probe counts as unknown for `FAILED_PROBE_TTL` so a down server is
+    /// not asked on every request. A probe cut short by this caller's
+    /// deadline says nothing about the server and is not remembered.
+    pub async fn capabilities(&self) -> ModelCapabilities {
+        let Some(timeout) = self.probe_timeout else {
+            return ModelCapabilities::unknown(&self.model);
+        };
+        if let Some(capabilities) = probed(&self.show_url, &self.model) {
+            return capabilities;
+        }
+        if self.deadline.is_some_and(|d| d.is_expired()) {
+            return ModelCapabilities::unknown(&self.model);
+        }
+        let timeout = match self.deadline {
+            Some(deadline) => timeout.min(deadline.remaining()),
+            None => timeout,
+        };
+        match self.probe_capabilities(timeout).await {
+            Ok(capabilities) => {
+                remember(&self.show_url, &capabilities);
+                capabilities
+            }
+            Err(_) => {
+                if !self.deadline.is_some_and(|d| d.is_expired()) {
+                    remember_failure(&self.show_url, &self.model);
                 }
+                ModelCapabilities::unknown(&self.model)
             }
-            Err(e) => Err(e.to_string().into()),
         }
     }
 
-    pub async fn send_message(
+    /// Asks `/api/show` about the model, bypassing the cache
+    pub async fn probe_capabilities(
+        &self,
+        timeout: Duration,
+    ) -> Result<ModelCapabilities, AgentError> {
+        let body = serde_json::json!({ "model": self.model }).to_string();
+        let response = HttpClient::new(self.show_url.clone())
+            .send_request_with_timeout::<serde_json::Value>(&body, Some(timeout))
+            .await
+            .map_err(network_error)?;
+        match (response.data, response.error) {
+            (Some(show), _) => Ok(ModelCapabilities::from_show_response(&self.model, &show)),
+            (None, Some(e)) => Err(self.status_error(e)),
+            (None, None) => Err(no_data()),
+        }
+    }
+
+    /// Embedding of `text` from `/api/embed`; the client's model must be
+    /// an embedding model
+    pub async fn send_embedding(&self, text: &str) -> Result<Vec<f32>, AgentError> {
+        self.check_deadline()?;
+        let body = serde_json::json!({ "model": self.model, "input": text }).to_string();
+        let response = HttpClient::new(self.embed_url.clone())
+            .send_request_with_timeout::<EmbedResponse>(&body, self.request_timeout())
+            .await
+            .map_err(network_error)?;
+        match (response.data, response.error) {
+            (Some(data), _) => data.embeddings.into_iter().next().ok_or_else(|| {
+                AgentError::DeserializationError(
+                    "No embedding received from Ollama API".to_string(),
+                )
+            }),
+            (None, Some(e)) => Err(self.status_error(e)),
+            (None, None) => Err(no_data()),
+        }
+    }
+
+    pub async fn send_chat_request(&self, body: &str) -> Result<OllamaResponse, AgentError> {
+        let ollama_request = self.chat_request(vec![OllamaChat::user(body.to_string())]);
+        self.send(&ollama_request).await
+    }
+
+    /// Sends a prompt whose answer must satisfy `constraint`. If the server
+    /// rejects the constraint (older Ollama versions don't take schemas) the
+    /// request is retried with the weaker fallback.
+    pub async fn send_message_constrained(
         &self,
         prompt: &str,
-    ) -> Result<OllamaResponse, Box<dyn std::error::Error>> {
-        let request_body = format!(r#"{}"#, prompt.replace('"', "\\\""));
-        self.send_chat_request(&request_body).await
+        constraint: &OutputConstraint,
+    ) -> Result<OllamaResponse, AgentError> {
+        self.send_messages_constrained(ChatMessages::new().user(prompt), constraint)
+            .await
+    }
+
+    /// Like `send_message_constrained`, for structured messages
+    pub async fn send_messages_constrained(
+        &self,
+        messages: ChatMessages,
+        constraint: &OutputConstraint,
+    ) -> Result<OllamaResponse, AgentError> {
+        let request = self.chat_request(messages.into_vec());
+        let error = match self
+            .send(&request.clone().with_format(constraint.format()))
+            .await
+        {
+            Ok(response) => return Ok(response),
+            Err(e) => e,
+        };
+        // No point retrying once the caller has stopped waiting
+        if self.deadline.is_some_and(|d| d.is_expired()) {
+            return Err(error);
+        }
+        match constraint.fallback() {
+            Some(fallback) => self.send(&request.with_format(fallback.format())).await,
+            None => Err(error),
+        }
+    }
+
+    /// Sends structured system/user/assistant/tool messages
+    pub async fn send_messages(
+        &self,
+        messages: ChatMessages,
+    ) -> Result<OllamaResponse, AgentError> {
+        let ollama_request = self.chat_request(messages.into_vec());
+        self.send(&ollama_request).await
+    }
+
+    /// Sends the request unless the response cache already has the answer
+    async fn send(&self, ollama_request: &OllamaChatRequest) -> Result<OllamaResponse, AgentError> {
+        if let Some(response) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get(ollama_request))
+        {
+            return Ok(response);
+        }
+        let result = self.send_traced(ollama_request).await;
+        if let (Some(cache), Ok(response)) = (&self.cache, &result) {
+            // The answer is still good; the next run just asks again
+            if let Err(e) = cache.put(ollama_request, response) {
+                tracing::warn!("Response cache: could not store answer: {}", e);
+            }
+        }
+        result
+    }
+
+    /// Sends the request, recording it when prompt tracing is on
+    async fn send_traced(
+        &self,
+        ollama_request: &OllamaChatRequest,
+    ) -> Result<OllamaResponse, AgentError> {
+        let Some(tracer) = &self.tracer else {
+            return self.send_guarded(ollama_request).await;
+        };
+        let started = Instant::now();
+        let result = self.send_guarded(ollama_request).await;
+        let outcome = match &result {
+            Ok(response) => Ok(response.message.raw_content().to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        tracer.record(
+            ollama_request,
+            outcome.as_deref().map_err(String::as_str),
+            started.elapsed(),
+        );
+        result
+    }
+
+    /// Refuses the request once a token budget is used up; otherwise sends
+    /// it and charges the tokens spent
+    async fn send_guarded(
+        &self,
+        ollama_request: &OllamaChatRequest,
+    ) -> Result<OllamaResponse, AgentError> {
+        // An expired deadline is the caller's problem, not a server failure
+        self.check_deadline()?;
+        charge_llm_call()?;
+        self.cost_guard()
+            .check(self.tenant(), &ollama_request.model)
+            .map_err(AgentError::BudgetExceeded)?;
+        let response = self.send_hedged(ollama_request).await?;
+        let prompt_tokens = match response.prompt_eval_count {
+            // Left out when Ollama reused a cached prompt
+            0 => prompt_tokens(ollama_request),
+            count => u64::from(count),
+        };
+        self.cost_guard().record(
+            self.tenant(),
+            &response.model,
+            prompt_tokens + u64::from(response.eval_count),
+        );
+        Ok(response)
+    }
+
+    /// Sends through the shared circuit breaker so a down server fails
+    /// fast; only transport errors and 5xx answers count against it, hedging to the second host when one is configured
+    async fn send_hedged(
+        &self,
+        ollama_request: &OllamaChatRequest,
+    ) -> Result<OllamaResponse, AgentError> {
+        let primary = async {
+            ollama_breaker()
+                .call_counting(
+                    self.send_unguarded(&self.http_client, ollama_request),
+                    is_server_failure,
+                )
+                .await
+                .map_err(|e| match e {
+                    CircuitError::Open { .. } => AgentError::NetworkError(e.to_string()),
+                    CircuitError::Inner(e) => e,
+                })
+        };
+        match &self.hedge {
+            None => primary.await,
+            Some(hedge) => {
+                let hedge_request = OllamaChatRequest {
+                    model: hedge.model.clone(),
+                    ..ollama_request.clone()
+                };
+                hedged(
+                    primary,
+                    || self.send_unguarded(&hedge.http_client, &hedge_request),
+                    hedge.delay,
+                    hedge_budget(),
+                )
+                .await
+            }
+        }
+    }
+
+    async fn send_unguarded(
+        &self,
+        http_client: &HttpClient,
+        ollama_request: &OllamaChatRequest,
+    ) -> Result<OllamaResponse, AgentError> {
+        let request_body = serde_json::to_string(ollama_request)
+            .map_err(|e| AgentError::ProcessingError(e.to_string()))?;
+        let response = http_client
+            .send_request_with_timeout::<OllamaResponse>(
+                request_body.as_str(),
+                self.request_timeout(),
+            )
+            .await
+            .map_err(|e| match self.deadline {
+                // The timeout was cut short to the deadline
+                Some(deadline) if deadline.is_expired() => {
+                    AgentError::DeadlineExceeded("while waiting for Ollama".to_string())
+                }
+                _ => network_error(e),
+            })?;
+
+        match (response.data, response.error) {
+            (Some(data), _) => Ok(data),
+            (None, Some(e)) => Err(self.status_error(e)),
+            (None, None) => Err(no_data()),
+        }
+    }
+
+    pub fn stream_message(&self, prompt: &str) -> ChunkStream {
+        self.stream_messages(ChatMessages::new().user(prompt))
+    }
+
+    /// Sends `messages` with `stream: true` so the reply arrives chunk by
+    /// chunk. Token budgets apply as usual; the circuit breaker, hedging
+    /// and prompt tracing do not.
+    pub fn stream_messages(&self, messages: ChatMessages) -> ChunkStream {
+        let mut request = self.chat_request(messages.into_vec());
+        request.stream = true;
+
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        let allowed = self.stream_allowed(&request.model);
+        let http_client = self.http_client.clone();
+        let timeout = self.request_timeout();
+        let tenant = self.tenant().to_string();
+        let guard = self.cost_guard.clone();
+        tokio::spawn(async move {
+            if let Err(e) = allowed {
+                let _ = sender.send(Err(e)).await;
+                return;
+            }
+            let body = match serde_json::to_string(&request) {
+                Ok(body) => body,
+                Err(e) => {
+                    let _ = sender
+                        .send(Err(AgentError::ProcessingError(e.to_string())))
+                        .await;
+                    return;
+                }
+            };
+            let mut response = match http_client.send_streaming_request(&body, timeout).await {
+                Ok(response) if response.status().is_success() => response,
+                Ok(response) => {
+                    let error = HttpError {
+                        status: response.status().as_u16(),
+                        error: "HTTP Error".to_string(),
+                        message: response.text().await.unwrap_or_default(),
+                    };
+                    let _ = sender.send(Err(status_error(&request.model, error))).await;
+                    return;
+                }
+                Err(e) => {
+                    let _ = sender.send(Err(network_error(e))).await;
+                    return;
+                }
+            };
+            let mut decoder = ChunkDecoder::new();
+            loop {
+                let bytes = match response.chunk().await {
+                    Ok(Some(bytes)) => bytes,
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = sender.send(Err(network_error(e))).await;
+                        return;
+                    }
+                };
+                for chunk in decoder.push(&bytes) {
+                    if !forward(&sender, guard.as_deref(), &tenant, chunk).await {
+                        return;
+                    }
+                }
+            }
+            if let Some(chunk) = decoder.finish() {
+                forward(&sender, guard.as_deref(), &tenant, chunk).await;
+            }
+        });
+        ChunkStream::new(receiver)
+    }
+
+    /// Checks run before a streamed request, while still in the caller's
+    /// task so its run budget is charged
+    fn stream_allowed(&self, model: &str) -> Result<(), AgentError> {
+        self.check_deadline()?;
+        charge_llm_call()?;
+        self.cost_guard()
+            .check(self.tenant(), model)
+            .map_err(AgentError::BudgetExceeded)
+    }
+
+    pub async fn send_message(&self, prompt: &str) -> Result<OllamaResponse, AgentError> {
+        self.send_chat_request(prompt).await
+    }
+
+    fn check_deadline(&self) -> Result<(), AgentError> {
+        match self.deadline {
+            Some(deadline) if deadline.is_expired() => Err(AgentError::DeadlineExceeded(
+                "before calling Ollama".to_string(),
+            )),
+            _ => Ok(()),
+        }
     }
 
+    fn status_error(&self, error: HttpError) -> AgentError {
+        status_error(&self.model, error)
+    }
+
+    /// Asks for an assistant with its own system prompt, built on this
+    /// client's model
     pub async fn create_assistant(
         &self,
-        system: &String, 
-        name: &String,
-    ) -> Result<OllamaCreateResponse, Box<dyn std::error::Error>> {
-        // TODO: Implement create assistant functionality
-        Ok(OllamaCreateResponse::new(vec![
-            format!("Assistant '{}' created successfully", name),
-            format!("Assistant '{}' created successfully", system),
-        ]))
+        system: &str,
+        name: &str,
+    ) -> Result<OllamaCreateResponse, AgentError> {
+        self.create_model(name, &Modelfile::new(&self.model).with_system(system))
+            .await
+    }
+
+    /// Registers `modelfile` on the server as `name`; replaces a model of
+    /// the same name. Target it afterwards with `with_model(name)`.
+    pub async fn create_model(
+        &self,
+        name: &str,
+        modelfile: &Modelfile,
+    ) -> Result<OllamaCreateResponse, AgentError> {
+        let body = serde_json::to_string(&modelfile.create_request(name))
+            .map_err(|e| AgentError::ProcessingError(e.to_string()))?;
+        let response = HttpClient::new(self.create_url.clone())
+            .send_request_with_timeout::<OllamaCreateStatusMessage>(&body, self.request_timeout())
+            .await
+            .map_err(network_error)?;
+        match (response.data, response.error) {
+            (Some(status), _) => Ok(OllamaCreateResponse::new_with_status_messages(vec![status])),
+            (None, Some(e)) => Err(status_error(modelfile.from_model(), e)),
+            (None, None) => Err(no_data()),
+        }
+    }
+}
+
+/// A 404 from Ollama means the model has not been pulled
+fn status_error(model: &str, error: HttpError) -> AgentError {
+    match error.status {
+        404 => AgentError::ModelUnavailable(model.to_string()),
+        status => AgentError::HttpError {
+            status,
+            message: error.message,
+        },
+    }
+}
+
+/// Whether `e` says the server is unwell; a caller's short deadline or a
+/// bad request must not open the breaker for everyone else
+fn is_server_failure(e: &AgentError) -> bool {
+    match e {
+        AgentError::NetworkError(_) => true,
+        AgentError::HttpError { status, .. } => *status >= 500,
+        _ => false,
+    }
+}
+
+fn network_error(e: impl std::fmt::Display) -> AgentError {
+    AgentError::NetworkError(e.to_string())
+}
+
+/// Tokens of the request's messages by the model's tokenizer
+fn prompt_tokens(request: &OllamaChatRequest) -> u64 {
+    let tokenizer = tokenizer_for(&request.model);
+    request
+        .messages
+        .iter()
+        .map(|message| tokenizer.count(&message.content) as u64)
+        .sum()
+}
+
+fn no_data() -> AgentError {
+    AgentError::DeserializationError("No data received from Ollama API".to_string())
+}
+
+/// Passes a chunk on, charging the token counts of the last one; false once
+/// the stream is over or nobody is listening
+async fn forward(
+    sender: &mpsc::Sender<Result<OllamaChunk, AgentError>>,
+    guard: Option<&CostGuard>,
+    tenant: &str,
+    chunk: Result<OllamaChunk, AgentError>,
+) -> bool {
+    let more = matches!(&chunk, Ok(chunk) if !chunk.done);
+    if let Ok(chunk) = &chunk
+        && chunk.done
+    {
+        guard
+            .unwrap_or(cost_guard())
+            .record(tenant, &chunk.model, chunk.tokens());
+    }
+    sender.send(chunk).await.is_ok() && more
+}
+
+impl Default for OllamaClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::safety::Redactor;
+
+    #[tokio::test]
+    async fn test_failed_calls_are_traced() {
+        let path = std::env::temp_dir().join("ollama_client_trace.jsonl");
+        let _ = std::fs::remove_file(&path);
+        let client = OllamaClient::new()
+            .with_deadline(Some(Deadline::after(Duration::ZERO)))
+            .with_prompt_tracer(Some(Arc::new(PromptTracer::new(
+                &path,
+                Redactor::default(),
+            ))));
+
+        assert!(client.send_message("Email eva@company.com").await.is_err());
+
+        let trace = std::fs::read_to_string(&path).unwrap();
+        assert!(trace.contains("Deadline exceeded"));
+        assert!(trace.contains("[REDACTED_EMAIL]"));
+        assert!(!trace.contains("eva@company.com"));
+    }
+
+    #[test]
+    fn test_only_server_failures_count_against_the_breaker() {
+        let http = |status| AgentError::HttpError {
+            status,
+            message: String::new(),
+        };
+
+        assert!(is_server_failure(&network_error("connection refused")));
+        assert!(is_server_failure(&http(503)));
+        assert!(!is_server_failure(&http(400)));
+        assert!(!is_server_failure(&AgentError::ModelUnavailable(
+            "llama3".to_string()
+        )));
+        assert!(!is_server_failure(&AgentError::DeadlineExceeded(
+            "while waiting for Ollama".to_string()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_request_is_answered_from_cache() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        // Accepts a single request; a second one would fail to connect
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let _ = stream.read(&mut request).await.unwrap();
+            let body = r#"{"model":"llama3","created_at":"2025-06-04T09:00:00Z","message":{"role":"assistant","content":"no_action"},"done_reason":"stop","done":true,"total_duration":1,"load_duration":1,"prompt_eval_count":10,"prompt_eval_duration":1,"eval_count":5,"eval_duration":1}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        let cache = ResponseCache::new(&std::env::temp_dir().join("ollama_client_cache"));
+        cache.clear().unwrap();
+        let mut client = OllamaClient::new()
+            .with_model("llama3")
+            .with_response_cache(Some(Arc::new(cache)));
+        client.http_client = HttpClient::new(format!("http://{}/api/chat", address));
+
+        let first = client.send_message("Classify: thanks!").await.unwrap();
+        let second = client.send_message("Classify: thanks!").await.unwrap();
+
+        assert_eq!(first.message.raw_content(), "no_action");
+        assert_eq!(second.message.raw_content(), "no_action");
+    }
+
+    #[tokio::test]
+    async fn test_prompt_is_sent_verbatim() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = vec![0; 4096];
+            // Until the whole JSON body has arrived
+            let body = loop {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((_, body)) = text.split_once("\r\n\r\n")
+                    && let Ok(body) = serde_json::from_str::<serde_json::Value>(body)
+                {
+                    break body;
+                }
+            };
+            let answer = r#"{"model":"llama3","created_at":"2025-06-04T09:00:00Z","message":{"role":"assistant","content":"{}"},"done_reason":"stop","done":true,"total_duration":1,"load_duration":1,"prompt_eval_count":10,"prompt_eval_duration":1,"eval_count":5,"eval_duration":1}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                answer.len(),
+                answer
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            body
+        });
+        let mut client = OllamaClient::new().with_model("llama3");
+        client.http_client = HttpClient::new(format!("http://{}/api/chat", address));
+        let prompt = "Reply to \"Eva\" with {\"intent\": \"no_action\"} and a \\ backslash";
+
+        client
+            .send_message_constrained(prompt, &OutputConstraint::Json)
+            .await
+            .unwrap();
+        let body = server.await.unwrap();
+
+        assert_eq!(body["messages"][0]["content"], prompt);
+        assert_eq!(body["format"], "json");
+    }
+
+    #[test]
+    fn test_status_errors_are_typed() {
+        let error = |status| HttpError {
+            status,
+            error: "HTTP Error".to_string(),
+            message: "model \"llama9\" not found".to_string(),
+        };
+
+        assert!(matches!(
+            status_error("llama9", error(404)),
+            AgentError::ModelUnavailable(model) if model == "llama9"
+        ));
+        assert!(matches!(
+            status_error("llama9", error(503)),
+            AgentError::HttpError { status: 503, .. }
+        ));
+        assert!(status_error("llama9", error(503)).is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_used_up_budget_becomes_a_429_problem() {
+        use crate::config::TokenBudgetConfig;
+        use crate::error::Error;
+        use crate::i18n::Locale;
+        use crate::infra::http::ProblemDetails;
+
+        let guard = CostGuard::new().with_budget(&TokenBudgetConfig {
+            tenant: Some("acme".to_string()),
+            daily_tokens: Some(0),
+            ..TokenBudgetConfig::default()
+        });
+        let client = OllamaClient::new()
+            .with_model("llama3")
+            .with_tenant("acme")
+            .with_cost_guard(Arc::new(guard));
+
+        let error = client
+            .send_message("Summarize the budget review")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            &error,
+            AgentError::BudgetExceeded(exceeded) if exceeded.tenant == "acme"
+        ));
+
+        let error = Error::from(error);
+        let problem = ProblemDetails::from_error(&error, Locale::En, "id");
+
+        assert!(matches!(error, Error::Budget(_)));
+        assert_eq!(problem.status, 429);
+        assert!(problem.retry_after.unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_chunks_arrive_in_order() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let read = stream.read(&mut request).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            for content in ["Dear", " Eva", ","] {
+                let line = format!(
+                    "{{\"model\":\"llama3\",\"message\":{{\"role\":\"assistant\",\"content\":\"{}\"}},\"done\":false}}\n",
+                    content
+                );
+                stream.write_all(line.as_bytes()).await.unwrap();
+                stream.flush().await.unwrap();
+            }
+            stream
+                .write_all(b"{\"model\":\"llama3\",\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"eval_count\":3}\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&request[..read]).to_string()
+        });
+        let mut client = OllamaClient::new().with_model("llama3");
+        client.http_client = HttpClient::new(format!("http://{}/api/chat", address));
+
+        let mut stream = client.stream_message("Write to Eva");
+        let first = stream.next_chunk().await.unwrap().unwrap();
+        let (rest, last) = stream.collect_text().await.unwrap();
+
+        assert_eq!(first.content(), "Dear");
+        assert_eq!(rest, " Eva,");
+        assert!(last.unwrap().done);
+        assert!(server.await.unwrap().contains("\"stream\":true"));
+    }
+
+    #[tokio::test]
+    async fn test_create_model_posts_modelfile() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let read = stream.read(&mut request).await.unwrap();
+            let body = "{\"status\":\"success\"}";
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request[..read]).to_string()
+        });
+        let mut client = OllamaClient::new();
+        client.create_url = format!("http://{}/api/create", address);
+
+        let response = client
+            .create_model(
+                "classifier",
+                &Modelfile::new("gemma3")
+                    .with_system("Classify intent.")
+                    .with_parameter("temperature", 0),
+            )
+            .await
+            .unwrap();
+        let request = server.await.unwrap();
+
+        assert_eq!(response.messages[0].status, "success");
+        assert!(request.starts_with("POST /api/create"));
+        assert!(request.contains("\"model\":\"classifier\""));
+        assert!(request.contains("\"parameters\":{\"temperature\":0}"));
+        assert!(request.contains("\"stream\":false"));
+    }
+
+    #[test]
+    fn test_api_settings_apply_to_requests() {
+        let client = OllamaClient::new().with_api(&ApiConfig {
+            url: "http://ollama.internal:11434/api/chat".to_string(),
+            model: "llama3".to_string(),
+            temperature: Some(0.5),
+            timeout_secs: Some(30),
+        });
+
+        let request = serde_json::to_value(client.chat_request(Vec::new())).unwrap();
+        let bounded = client.with_deadline(Some(Deadline::after(Duration::from_secs(5))));
+
+        assert_eq!(request["model"], "llama3");
+        assert_eq!(request["options"]["temperature"], 0.5);
+        assert_eq!(
+            bounded.create_url,
+            "http://ollama.internal:11434/api/create"
+        );
+        assert!(bounded.request_timeout().unwrap() <= Duration::from_secs(5));
+        assert_eq!(
+            bounded.with_deadline(None).request_timeout(),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn test_uncounted_prompt_is_estimated() {
+        let client = OllamaClient::new().with_model("unconfigured-model");
+        let request = client.chat_request(
+            ChatMessages::new()
+                .system("Be brief.")
+                .user("Classify: thanks!")
+                .into(),
+        );
+
+        // "Be brief." and "Classify: thanks!" at four characters per token
+        assert_eq!(prompt_tokens(&request), 3 + 5);
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_without_probe_are_unknown() {
+        let client = OllamaClient::new()
+            .with_model("unprobed-model")
+            .with_capability_probe(None);
+
+        assert_eq!(
+            client.capabilities().await,
+            ModelCapabilities::unknown("unprobed-model")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probe_cut_short_by_deadline_is_not_remembered() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        // Keeps the first probe waiting, answers the second
+        tokio::spawn(async move {
+            let (stuck, _) = listener.accept().await.unwrap();
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let _ = stream.read(&mut request).await.unwrap();
+            let body = r#"{"capabilities":["completion","tools"]}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            drop(stuck);
+        });
+        let client = OllamaClient::new()
+            .with_api(&ApiConfig {
+                url: format!("http://{}/api/chat", address),
+                model: "deadline-probe-model".to_string(),
+                ..ApiConfig::default()
+            })
+            .with_capability_probe(Some(Duration::from_secs(5)));
+
+        let hurried = client
+            .clone()
+            .with_deadline(Some(Deadline::after(Duration::from_millis(100))))
+            .capabilities()
+            .await;
+        let patient = client.capabilities().await;
+
+        assert!(!hurried.tools);
+        assert!(patient.tools);
+        assert_eq!(
+            probed(&client.show_url, "deadline-probe-model"),
+            Some(patient)
+        );
     }
 }
@@ -20,7 +20,7 @@ impl OllamaCreateResponse {
     pub fn new(messages: Vec<String>) -> Self {
         let status_messages = messages
             .into_iter()
-            .map(|msg| OllamaCreateStatusMessage::new(msg))
+            .map(OllamaCreateStatusMessage::new)
             .collect();
 
         Self {
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct OllamaCreateRequest {
@@ -6,6 +8,12 @@ pub struct OllamaCreateRequest {
     pub from: String,
     pub system: String,
     pub name: String,
+    /// Sampling parameters baked into the model, e.g. `temperature`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, Value>,
+    /// Ollama streams progress unless this is `false`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
 }
 
 impl OllamaCreateRequest {
@@ -15,6 +23,8 @@ impl OllamaCreateRequest {
             from,
             system,
             name,
+            parameters: BTreeMap::new(),
+            stream: None,
         }
     }
 }
@@ -181,7 +191,7 @@ Line 2"#;
     fn test_ollama_create_request_realistic_model_names() {
         let models = [
             ("llama3.1:8b", "meta-llama/llama-3.1-8b"),
-            ("qwen2.5:14b", "alibaba-cloud/qwen-2.5-14b"), 
+            ("qwen2.5:14b", "alibaba-cloud/qwen-2.5-14b"),
             ("mistral:7b", "mistralai/mistral-7b"),
             ("codellama:13b", "meta-llama/codellama-13b"),
         ];
@@ -193,14 +203,14 @@ Line 2"#;
                 "You are a helpful AI assistant".to_string(),
                 model_name.to_string(),
             );
-            
+
             assert_eq!(request.model, *model_name);
             assert_eq!(request.from, *base_model);
-            
+
             let json = serde_json::to_string(&request).expect("Serialization should succeed");
-            let deserialized: OllamaCreateRequest = 
+            let deserialized: OllamaCreateRequest =
                 serde_json::from_str(&json).expect("Deserialization should succeed");
-                
+
             assert_eq!(request, deserialized);
         }
     }
@@ -209,7 +219,7 @@ Line 2"#;
     fn test_ollama_create_request_consistency() {
         let models = vec![
             "personal-assistant-pro",
-            "senior-code-reviewer", 
+            "senior-code-reviewer",
             "technical-documentation-expert",
             "senior-financial-analyst",
         ];
@@ -267,7 +277,7 @@ Line 2"#;
         // Test creating multiple models with layer references
         let models = vec![
             ("model-v1", "using existing layer sha256:abc123"),
-            ("model-v2", "creating new layer sha256:def456"), 
+            ("model-v2", "creating new layer sha256:def456"),
             ("model-v3", "writing manifest"),
         ];
 
@@ -296,10 +306,10 @@ Line 2"#;
         // Verify serialization format matches expected status response format
         let json = serde_json::to_string(&request).expect("Serialization should succeed");
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
-        
+
         assert!(parsed["model"].is_string());
-        assert!(parsed["from"].is_string()); 
+        assert!(parsed["from"].is_string());
         assert!(parsed["system"].is_string());
         assert!(parsed["name"].is_string());
     }
-}
\ No newline at end of file
+}
@@ -1,38 +1,48 @@
-use crate::agent::Intent;
 use crate::agent::classifier::Params;
+use crate::agent::classifier::response_schema::{self, legacy_schema_version};
+use crate::agent::{AgentError, Intent};
+use crate::infra::ollama::ollama_json_content::extract_json;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct OllamaIntentResponseContent {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub intent: Intent,
     pub params: Params,
+    /// How sure the model says it is, from 0 to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
 }
 
 impl OllamaIntentResponseContent {
-    /// Extracts JSON from ```json ... ``` markdown format and parses it
-    pub fn from_markdown_json(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Extracts JSON from ```json ... ``` markdown format and parses it,
+    /// upgrading answers to older prompts to the current schema
+    pub fn from_markdown_json(content: &str) -> Result<Self, AgentError> {
         let json_content = Self::extract_json_from_markdown(content)?;
-        let parsed: OllamaIntentResponseContent = serde_json::from_str(&json_content)?;
+        let parsed: OllamaIntentResponseContent =
+            serde_json::from_value(response_schema::migrate_str(&json_content)?)?;
         Ok(parsed)
     }
 
+    /// Like `from_markdown_json`, for answers listing several requests in a
+    /// JSON array. A single object counts as a list of one.
+    pub fn all_from_markdown_json(content: &str) -> Result<Vec<Self>, AgentError> {
+        let json_content = Self::extract_json_from_markdown(content)?;
+        let values = match serde_json::from_str(&json_content)? {
+            serde_json::Value::Array(values) => values,
+            value => vec![value],
+        };
+        values
+            .into_iter()
+            .map(|value| Ok(serde_json::from_value(response_schema::migrate(value))?))
+            .collect()
+    }
+
     /// Extracts JSON content from markdown code block
-    fn extract_json_from_markdown(content: &str) -> Result<String, Box<dyn std::error::Error>> {
-        // Find the start and end of the JSON code block
-        if let Some(start) = content.find("```json") {
-            let after_start = &content[start + 7..]; // Skip "```json"
-            if let Some(end) = after_start.find("```") {
-                let json_content = &after_start[..end].trim();
-                return Ok(json_content.to_string());
-            }
-        }
-
-        // Fallback: try to find JSON without markdown markers
-        if content.trim().starts_with('{') && content.trim().ends_with('}') {
-            return Ok(content.trim().to_string());
-        }
-
-        Err(format!("Could not extract JSON from content: {}", content).into())
+    fn extract_json_from_markdown(content: &str) -> Result<String, AgentError> {
+        extract_json(content)
     }
 
     pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
@@ -44,6 +54,23 @@ impl OllamaIntentResponseContent {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_all_from_array() {
+        let content = r#"[{"intent": "send_email", "params": {"recipient": "Turtle", "message": "Hi"}},
+{"schema_version": 2, "intent": "schedule_meeting", "params": {"recipient": "Turtle"}}]"#;
+
+        let all = OllamaIntentResponseContent::all_from_markdown_json(content).unwrap();
+        let single = OllamaIntentResponseContent::all_from_markdown_json(
+            r#"{"intent": "no_action", "params": {}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].intent, Intent::ScheduleMeeting);
+        assert_eq!(all[0].schema_version, 2);
+        assert_eq!(single.len(), 1);
+    }
+
     #[test]
     fn test_extract_json_from_markdown() {
         let markdown_content = r#"```json
@@ -83,6 +110,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_v1_response_is_migrated() {
+        let markdown_content = r#"```json
+{"action": "send_message", "params": {"recipient": "Sofia", "message": "I'll arrive in 10 min"}}
+```"#;
+
+        let result = OllamaIntentResponseContent::from_markdown_json(markdown_content).unwrap();
+        assert_eq!(result.schema_version, 2);
+        assert_eq!(result.intent, Intent::SendEmail);
+        assert_eq!(result.params.recipient(), Some("Sofia"));
+    }
+
     #[test]
     fn test_fallback_plain_json() {
         let plain_json = r#"{
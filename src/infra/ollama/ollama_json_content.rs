@@ -0,0 +1,105 @@
+use serde::de::DeserializeOwned;
+
+use crate::agent::AgentError;
+
+/// Line that separates free-form reasoning from the final JSON answer
+pub const FINAL_ANSWER_MARKER: &str = "FINAL ANSWER:";
+
+/// Returns the text after the last final-answer marker, or all of it when
+/// the model did not reason first
+pub fn isolate_final_answer(content: &str) -> &str {
+    match content.rfind(FINAL_ANSWER_MARKER) {
+        Some(index) => &content[index + FINAL_ANSWER_MARKER.len()..],
+        None => content,
+    }
+}
+
+/// Extracts JSON content from a ```json markdown block or a bare JSON object
+/// or array. Reasoning before a final-answer marker is ignored.
+pub fn extract_json(content: &str) -> Result<String, AgentError> {
+    let content = isolate_final_answer(content);
+    // Find the start and end of the JSON code block
+    if let Some(start) = content.find("```json") {
+        let after_start = &content[start + 7..]; // Skip "```json"
+        if let Some(end) = after_start.find("```") {
+            let json_content = &after_start[..end].trim();
+            return Ok(json_content.to_string());
+        }
+    }
+
+    // Fallback: try to find JSON without markdown markers
+    let trimmed = content.trim();
+    if (trimmed.starts_with('{') && trimmed.ends_with('}'))
+        || (trimmed.starts_with('[') && trimmed.ends_with(']'))
+    {
+        return Ok(trimmed.to_string());
+    }
+
+    Err(AgentError::malformed(content))
+}
+
+/// Extracts and deserializes the JSON payload of a model response
+pub fn parse_json_content<T: DeserializeOwned>(content: &str) -> Result<T, AgentError> {
+    let json_content = extract_json(content)?;
+    Ok(serde_json::from_str(&json_content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Flag {
+        flag: bool,
+    }
+
+    #[test]
+    fn test_extract_from_markdown_block() {
+        let content = "Sure!\n```json\n{\"flag\": true}\n```\nDone.";
+
+        assert_eq!(extract_json(content).unwrap(), "{\"flag\": true}");
+    }
+
+    #[test]
+    fn test_extract_plain_json() {
+        assert_eq!(
+            extract_json("  {\"flag\": false} ").unwrap(),
+            "{\"flag\": false}"
+        );
+    }
+
+    #[test]
+    fn test_extract_fails_without_json() {
+        assert!(matches!(
+            extract_json("no json here"),
+            Err(AgentError::MalformedModelOutput { raw }) if raw == "no json here"
+        ));
+        assert!(matches!(
+            parse_json_content::<Flag>("{\"flag\": \"yes\"}"),
+            Err(AgentError::DeserializationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_reasoning_is_ignored_after_final_answer_marker() {
+        let content = "The user mentions Eva, so maybe:\n```json\n{\"flag\": false}\n```\nOn reflection the flag should be set.\nFINAL ANSWER:\n```json\n{\"flag\": true}\n```";
+
+        assert_eq!(extract_json(content).unwrap(), "{\"flag\": true}");
+    }
+
+    #[test]
+    fn test_last_marker_wins() {
+        let content = "FINAL ANSWER: draft\nFINAL ANSWER: {\"flag\": true}";
+
+        assert_eq!(isolate_final_answer(content), " {\"flag\": true}");
+        assert_eq!(extract_json(content).unwrap(), "{\"flag\": true}");
+    }
+
+    #[test]
+    fn test_parse_json_content() {
+        let parsed: Flag = parse_json_content("```json\n{\"flag\": true}\n```").unwrap();
+
+        assert_eq!(parsed, Flag { flag: true });
+    }
+}
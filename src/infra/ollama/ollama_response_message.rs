@@ -1,7 +1,8 @@
 use super::ollama_intent_response_content::OllamaIntentResponseContent;
-use serde::Deserialize;
+use crate::agent::AgentError;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OllamaResponseMessage {
     pub role: String,
     #[serde(rename = "content")]
@@ -9,18 +10,29 @@ pub struct OllamaResponseMessage {
 }
 
 impl OllamaResponseMessage {
+    /// Message with `content` as an assistant's answer
+    pub fn assistant(content: &str) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            raw_content: content.to_string(),
+        }
+    }
+
     /// Returns the raw content string as received from Ollama
     pub fn raw_content(&self) -> &str {
         &self.raw_content
     }
 
     /// Parses the content to extract structured JSON data
-    pub fn parsed_content(
-        &self,
-    ) -> Result<OllamaIntentResponseContent, Box<dyn std::error::Error>> {
+    pub fn parsed_content(&self) -> Result<OllamaIntentResponseContent, AgentError> {
         OllamaIntentResponseContent::from_markdown_json(&self.raw_content)
     }
 
+    /// Every request of a multi-intent answer
+    pub fn parsed_contents(&self) -> Result<Vec<OllamaIntentResponseContent>, AgentError> {
+        OllamaIntentResponseContent::all_from_markdown_json(&self.raw_content)
+    }
+
     /// Convenience method to get content, trying parsed first, fallback to raw
     pub fn content(&self) -> String {
         match self.parsed_content() {
@@ -0,0 +1,136 @@
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How far an agent lets Ollama constrain its output; configured per agent
+/// in `[structured_output]` since some models handle schemas poorly
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// A JSON schema of the expected answer
+    #[default]
+    Schema,
+    /// `"format": "json"`
+    Json,
+    /// Free text; JSON is dug out of markdown fences
+    None,
+}
+
+impl OutputFormat {
+    pub fn constraint_for<T: JsonSchema>(self) -> OutputConstraint {
+        OutputConstraint::schema_for::<T>().limit(self)
+    }
+}
+
+/// Constraint on the shape of the model output, sent as Ollama's `format`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum OutputConstraint {
+    #[default]
+    Unconstrained,
+    /// Any syntactically valid JSON
+    Json,
+    /// JSON matching a schema (Ollama structured outputs)
+    Schema(Value),
+}
+
+impl OutputConstraint {
+    /// Schema derived from the type, with subschemas inlined since not
+    /// every Ollama version resolves `$ref`
+    pub fn schema_for<T: JsonSchema>() -> Self {
+        let mut schema = SchemaSettings::draft07()
+            .with(|settings| settings.inline_subschemas = true)
+            .into_generator()
+            .into_root_schema_for::<T>()
+            .to_value();
+        if let Some(object) = schema.as_object_mut() {
+            object.remove("$schema");
+            object.remove("title");
+        }
+        OutputConstraint::Schema(schema)
+    }
+
+    /// Value for the request's `format` field
+    pub fn format(&self) -> Option<Value> {
+        match self {
+            OutputConstraint::Unconstrained => None,
+            OutputConstraint::Json => Some(Value::String("json".to_string())),
+            OutputConstraint::Schema(schema) => Some(schema.clone()),
+        }
+    }
+
+    /// This constraint, weakened to what `format` allows
+    pub fn limit(self, format: OutputFormat) -> OutputConstraint {
+        match (format, self) {
+            (OutputFormat::None, _) => OutputConstraint::Unconstrained,
+            (OutputFormat::Json, OutputConstraint::Schema(_)) => OutputConstraint::Json,
+            (_, constraint) => constraint,
+        }
+    }
+
+    /// Weaker constraint to retry with when the server rejects this one
+    pub fn fallback(&self) -> Option<OutputConstraint> {
+        match self {
+            OutputConstraint::Schema(_) => Some(OutputConstraint::Json),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::ollama::OllamaIntentResponseContent;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_values() {
+        assert_eq!(OutputConstraint::Unconstrained.format(), None);
+        assert_eq!(OutputConstraint::Json.format(), Some(json!("json")));
+    }
+
+    #[test]
+    fn test_classification_schema() {
+        let OutputConstraint::Schema(schema) =
+            OutputConstraint::schema_for::<OllamaIntentResponseContent>()
+        else {
+            panic!("Expected a schema");
+        };
+
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["intent", "params"]));
+        assert_eq!(
+            schema["properties"]["intent"]["enum"],
+            json!(["send_email", "schedule_meeting", "quick_reply", "no_action"])
+        );
+        assert!(schema["properties"]["params"]["properties"]["recipient"].is_object());
+        assert!(!schema.to_string().contains("$ref"));
+    }
+
+    #[test]
+    fn test_schema_falls_back_to_json() {
+        let schema = OutputConstraint::schema_for::<OllamaIntentResponseContent>();
+
+        assert_eq!(schema.fallback(), Some(OutputConstraint::Json));
+        assert_eq!(OutputConstraint::Json.fallback(), None);
+    }
+
+    #[test]
+    fn test_format_limits_constraint() {
+        let schema = OutputConstraint::schema_for::<OllamaIntentResponseContent>();
+
+        assert_eq!(schema.clone().limit(OutputFormat::Schema), schema);
+        assert_eq!(
+            OutputFormat::Json.constraint_for::<OllamaIntentResponseContent>(),
+            OutputConstraint::Json
+        );
+        assert_eq!(
+            OutputConstraint::Json.limit(OutputFormat::None),
+            OutputConstraint::Unconstrained
+        );
+        assert_eq!(
+            OutputConstraint::Unconstrained.limit(OutputFormat::Json),
+            OutputConstraint::Unconstrained
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::{Config, PromptTraceConfig, RedactionConfig};
+use crate::infra::ollama::{OllamaChat, OllamaChatRequest};
+use crate::safety::{RedactionSink, Redactor};
+
+/// One model call: the exact messages sent and what came back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptTraceEntry {
+    pub at: DateTime<Utc>,
+    pub model: String,
+    pub messages: Vec<OllamaChat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Appends every rendered prompt and raw response as a JSON line to a
+/// dedicated file, kept apart from normal logs and redacted for the
+/// `prompt_trace` sink. Off unless `[prompt_tracing] enabled = true`.
+#[derive(Debug)]
+pub struct PromptTracer {
+    path: PathBuf,
+    redactor: Redactor,
+    lock: Mutex<()>,
+}
+
+impl PromptTracer {
+    pub fn new(path: &Path, redactor: Redactor) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            redactor,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Tracer from config.toml, `None` while tracing is disabled
+    pub fn configured() -> Option<Arc<Self>> {
+        let config = Config::get();
+        Self::from_config(&config.prompt_tracing, &config.safety.redaction)
+    }
+
+    pub fn from_config(
+        config: &PromptTraceConfig,
+        redaction: &RedactionConfig,
+    ) -> Option<Arc<Self>> {
+        config.enabled.then(|| {
+            Arc::new(Self::new(
+                Path::new(&config.path),
+                Redactor::for_sink(redaction, RedactionSink::PromptTrace),
+            ))
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records a finished call. Tracing must never fail the call it
+    /// observes, so write errors are ignored.
+    pub fn record(
+        &self,
+        request: &OllamaChatRequest,
+        outcome: Result<&str, &str>,
+        elapsed: Duration,
+    ) {
+        let (response, error) = match outcome {
+            Ok(response) => (Some(self.redactor.redact(response)), None),
+            Err(error) => (None, Some(self.redactor.redact(error))),
+        };
+        let entry = PromptTraceEntry {
+            at: Utc::now(),
+            model: request.model.clone(),
+            messages: request
+                .messages
+                .iter()
+                .map(|message| OllamaChat {
+                    content: self.redactor.redact(&message.content),
+                    ..message.clone()
+                })
+                .collect(),
+            format: request.format.clone(),
+            response,
+            error,
+            elapsed_ms: elapsed.as_millis() as u64,
+        };
+        let Ok(line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        let _guard = self.lock.lock().unwrap();
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(mut file) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+        {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::safety::PiiKind;
+
+    fn trace_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join("prompt_trace_tests").join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn entries(path: &Path) -> Vec<PromptTraceEntry> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_records_redacted_prompt_and_response() {
+        let path = trace_path("redacted.jsonl");
+        let tracer = PromptTracer::new(&path, Redactor::new(vec![PiiKind::Email]));
+        let request = OllamaChatRequest::new(
+            "llama3".to_string(),
+            "Email eva@company.com about the delay".to_string(),
+        );
+
+        tracer.record(
+            &request,
+            Ok(r#"{"recipient":"eva@company.com"}"#),
+            Duration::from_millis(42),
+        );
+        tracer.record(&request, Err("connection refused"), Duration::ZERO);
+
+        let entries = entries(&path);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].model, "llama3");
+        assert_eq!(
+            entries[0].messages[0].content,
+            "Email [REDACTED_EMAIL] about the delay"
+        );
+        assert_eq!(
+            entries[0].response.as_deref(),
+            Some(r#"{"recipient":"[REDACTED_EMAIL]"}"#)
+        );
+        assert_eq!(entries[0].elapsed_ms, 42);
+        assert_eq!(entries[1].error.as_deref(), Some("connection refused"));
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let path = trace_path("configured.jsonl");
+        let enabled = PromptTraceConfig {
+            enabled: true,
+            path: path.display().to_string(),
+        };
+
+        assert!(
+            PromptTracer::from_config(&PromptTraceConfig::default(), &RedactionConfig::default())
+                .is_none()
+        );
+        assert_eq!(
+            PromptTracer::from_config(&enabled, &RedactionConfig::default())
+                .unwrap()
+                .path(),
+            path
+        );
+    }
+}
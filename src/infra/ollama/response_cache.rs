@@ -0,0 +1,391 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "sqlite")]
+use std::sync::Mutex;
+
+use crate::config::{CacheBackend, Config, ResponseCacheConfig};
+use crate::infra::ollama::{OllamaChatRequest, OllamaResponse};
+
+/// Database file of the `sqlite` backend, inside the cache directory
+#[cfg(feature = "sqlite")]
+pub const SQLITE_FILE: &str = "responses.sqlite3";
+
+/// One stored answer; `request` guards against hash collisions
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    request: String,
+    stored_at: DateTime<Utc>,
+    response: OllamaResponse,
+}
+
+#[derive(Debug)]
+enum Store {
+    /// One JSON file per request
+    Files,
+    /// Rows keyed by model and prompt hash
+    #[cfg(feature = "sqlite")]
+    Sqlite(Mutex<rusqlite::Connection>),
+}
+
+/// Keeps model answers on disk so repeated runs during development skip
+/// the model: one JSON file per request, or with the `sqlite` feature a
+/// single database that several processes can share. A request is the
+/// same when its model, messages, format and options are. Off unless
+/// `[ollama.cache] enabled = true`.
+#[derive(Debug)]
+pub struct ResponseCache {
+    directory: PathBuf,
+    store: Store,
+    ttl: Option<Duration>,
+}
+
+impl ResponseCache {
+    pub fn new(directory: &Path) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+            store: Store::Files,
+            ttl: None,
+        }
+    }
+
+    /// Cache kept in `SQLITE_FILE` under `directory`, created if missing
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(directory: &Path) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+        let connection =
+            rusqlite::Connection::open(directory.join(SQLITE_FILE)).map_err(io::Error::other)?;
+        // Another run may be writing; wait for it rather than fail
+        connection
+            .busy_timeout(std::time::Duration::from_secs(5))
+            .map_err(io::Error::other)?;
+        connection
+            .execute(
+                "CREATE TABLE IF NOT EXISTS responses (
+                    model TEXT NOT NULL,
+                    prompt_hash TEXT NOT NULL,
+                    entry TEXT NOT NULL,
+                    PRIMARY KEY (model, prompt_hash)
+                )",
+                (),
+            )
+            .map_err(io::Error::other)?;
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            store: Store::Sqlite(Mutex::new(connection)),
+            ttl: None,
+        })
+    }
+
+    /// Answers older than `ttl` are asked for again
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Cache from config.toml, `None` while caching is disabled
+    pub fn configured() -> Option<Arc<Self>> {
+        Self::from_config(&Config::get().ollama.cache)
+    }
+
+    /// `None` while caching is disabled, or when the cache cannot be
+    /// opened; requests then go to the model as usual
+    pub fn from_config(config: &ResponseCacheConfig) -> Option<Arc<Self>> {
+        if !config.enabled {
+            return None;
+        }
+        let directory = Path::new(&config.directory);
+        let cache = match config.backend {
+            CacheBackend::Files => Self::new(directory),
+            #[cfg(feature = "sqlite")]
+            CacheBackend::Sqlite => match Self::sqlite(directory) {
+                Ok(cache) => cache,
+                Err(e) => {
+                    tracing::warn!("Response cache disabled: {}", e);
+                    return None;
+                }
+            },
+            #[cfg(not(feature = "sqlite"))]
+            CacheBackend::Sqlite => {
+                tracing::warn!("Response cache: built without the sqlite feature, using files");
+                Self::new(directory)
+            }
+        };
+        Some(Arc::new(match config.ttl_secs {
+            Some(secs) => cache.with_ttl(Duration::seconds(secs as i64)),
+            None => cache,
+        }))
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn get(&self, request: &OllamaChatRequest) -> Option<OllamaResponse> {
+        self.get_at(request, Utc::now())
+    }
+
+    /// Like `get`, judging the age of the answer against `now`
+    pub fn get_at(
+        &self,
+        request: &OllamaChatRequest,
+        now: DateTime<Utc>,
+    ) -> Option<OllamaResponse> {
+        let key = serde_json::to_string(request).ok()?;
+        let text = match &self.store {
+            Store::Files => fs::read_to_string(self.path_for(&prompt_hash(&key))).ok()?,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(connection) => {
+                use rusqlite::OptionalExtension;
+                connection
+                    .lock()
+                    .ok()?
+                    .query_row(
+                        "SELECT entry FROM responses WHERE model = ?1 AND prompt_hash = ?2",
+                        (&request.model, prompt_hash(&key)),
+                        |row| row.get::<_, String>(0),
+                    )
+                    .optional()
+                    .ok()??
+            }
+        };
+        let entry: CacheEntry = serde_json::from_str(&text).ok()?;
+        let fresh = self.ttl.is_none_or(|ttl| now - entry.stored_at < ttl);
+        (entry.request == key && fresh).then_some(entry.response)
+    }
+
+    /// Stores an answer. Each entry is replaced whole, so a reader never
+    /// sees half of one; callers decide whether a failed write matters.
+    pub fn put(&self, request: &OllamaChatRequest, response: &OllamaResponse) -> io::Result<()> {
+        let key = serde_json::to_string(request)?;
+        let hash = prompt_hash(&key);
+        let text = serde_json::to_string(&CacheEntry {
+            request: key,
+            stored_at: Utc::now(),
+            response: response.clone(),
+        })?;
+        match &self.store {
+            Store::Files => {
+                let path = self.path_for(&hash);
+                fs::create_dir_all(&self.directory)?;
+                // Renaming is atomic, so concurrent runs never read a
+                // partly written file
+                let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+                fs::write(&partial, text)?;
+                fs::rename(&partial, &path).inspect_err(|_| {
+                    let _ = fs::remove_file(&partial);
+                })
+            }
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(connection) => connection
+                .lock()
+                .map_err(|_| io::Error::other("response cache lock poisoned"))?
+                .execute(
+                    "INSERT OR REPLACE INTO responses (model, prompt_hash, entry)
+                     VALUES (?1, ?2, ?3)",
+                    (&request.model, hash, text),
+                )
+                .map(|_| ())
+                .map_err(io::Error::other),
+        }
+    }
+
+    /// Removes every stored answer; returns how many there were
+    pub fn clear(&self) -> io::Result<usize> {
+        #[cfg(feature = "sqlite")]
+        if let Store::Sqlite(connection) = &self.store {
+            return connection
+                .lock()
+                .map_err(|_| io::Error::other("response cache lock poisoned"))?
+                .execute("DELETE FROM responses", ())
+                .map_err(io::Error::other);
+        }
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                fs::remove_file(path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn path_for(&self, hash: &str) -> PathBuf {
+        self.directory.join(format!("{}.json", hash))
+    }
+}
+
+fn prompt_hash(key: &str) -> String {
+    format!("{:016x}", fnv1a(key))
+}
+
+/// FNV-1a; unlike `DefaultHasher` it stays the same across Rust releases,
+/// so files written by one build are found by the next
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(model: &str, prompt: &str) -> OllamaChatRequest {
+        OllamaChatRequest::new(model.to_string(), prompt.to_string())
+    }
+
+    fn response(content: &str) -> OllamaResponse {
+        serde_json::from_value(serde_json::json!({
+            "model": "llama3",
+            "created_at": "2025-06-04T09:00:00Z",
+            "message": {"role": "assistant", "content": content},
+            "done_reason": "stop",
+            "done": true,
+            "total_duration": 1,
+            "load_duration": 1,
+            "prompt_eval_count": 10,
+            "prompt_eval_duration": 1,
+            "eval_count": 5,
+            "eval_duration": 1
+        }))
+        .unwrap()
+    }
+
+    fn cache(name: &str) -> ResponseCache {
+        let cache = ResponseCache::new(&std::env::temp_dir().join(name));
+        cache.clear().unwrap();
+        cache
+    }
+
+    #[test]
+    fn test_answer_is_found_by_model_and_prompt() {
+        let cache = cache("response_cache_hit");
+        cache
+            .put(&request("llama3", "Classify"), &response("no_action"))
+            .unwrap();
+
+        let hit = cache.get(&request("llama3", "Classify")).unwrap();
+
+        assert_eq!(hit.message.raw_content(), "no_action");
+        assert!(cache.get(&request("mistral", "Classify")).is_none());
+        assert!(cache.get(&request("llama3", "Classify again")).is_none());
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.get(&request("llama3", "Classify")).is_none());
+    }
+
+    #[test]
+    fn test_stale_answer_is_ignored() {
+        let cache = cache("response_cache_ttl").with_ttl(Duration::hours(1));
+        cache
+            .put(&request("llama3", "Classify"), &response("no_action"))
+            .unwrap();
+
+        let later = Utc::now() + Duration::hours(2);
+
+        assert!(cache.get(&request("llama3", "Classify")).is_some());
+        assert!(
+            cache
+                .get_at(&request("llama3", "Classify"), later)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_failed_write_is_reported() {
+        let blocker = std::env::temp_dir().join("response_cache_blocker");
+        fs::write(&blocker, "not a directory").unwrap();
+        let cache = ResponseCache::new(&blocker.join("cache"));
+
+        assert!(
+            cache
+                .put(&request("llama3", "Classify"), &response("no_action"))
+                .is_err()
+        );
+        assert!(cache.get(&request("llama3", "Classify")).is_none());
+    }
+
+    #[test]
+    fn test_write_leaves_no_partial_files() {
+        let cache = cache("response_cache_atomic");
+        cache
+            .put(&request("llama3", "Classify"), &response("no_action"))
+            .unwrap();
+        cache
+            .put(&request("llama3", "Classify"), &response("send_email"))
+            .unwrap();
+
+        let names: Vec<_> = fs::read_dir(cache.directory())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+
+        assert_eq!(names.len(), 1);
+        assert_eq!(
+            cache
+                .get(&request("llama3", "Classify"))
+                .unwrap()
+                .message
+                .raw_content(),
+            "send_email"
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn sqlite_cache(name: &str) -> ResponseCache {
+        let cache = ResponseCache::sqlite(&std::env::temp_dir().join(name)).unwrap();
+        cache.clear().unwrap();
+        cache
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_answer_is_found_by_model_and_prompt() {
+        let cache = sqlite_cache("response_cache_sqlite_hit");
+        cache
+            .put(&request("llama3", "Classify"), &response("no_action"))
+            .unwrap();
+
+        let hit = cache.get(&request("llama3", "Classify")).unwrap();
+
+        assert_eq!(hit.message.raw_content(), "no_action");
+        assert!(cache.get(&request("mistral", "Classify")).is_none());
+        assert!(cache.get(&request("llama3", "Classify again")).is_none());
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.get(&request("llama3", "Classify")).is_none());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_answers_outlive_the_connection() {
+        let directory = std::env::temp_dir().join("response_cache_sqlite_reopen");
+        sqlite_cache("response_cache_sqlite_reopen")
+            .put(&request("llama3", "Classify"), &response("no_action"))
+            .unwrap();
+
+        let reopened = ResponseCache::sqlite(&directory).unwrap();
+        let config = ResponseCacheConfig {
+            enabled: true,
+            directory: directory.to_string_lossy().into_owned(),
+            backend: CacheBackend::Sqlite,
+            ttl_secs: Some(3600),
+        };
+        let configured = ResponseCache::from_config(&config).unwrap();
+
+        assert!(reopened.get(&request("llama3", "Classify")).is_some());
+        assert!(configured.get(&request("llama3", "Classify")).is_some());
+        assert!(directory.join(SQLITE_FILE).exists());
+    }
+}
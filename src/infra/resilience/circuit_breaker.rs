@@ -0,0 +1,457 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+
+use crate::config::{CircuitBreakerConfig, Config};
+use crate::infra::resilience::CircuitEvent;
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+static OLLAMA_BREAKER: Lazy<CircuitBreaker> =
+    Lazy::new(|| CircuitBreaker::from_config("ollama", &Config::get().resilience.circuit_breaker));
+
+static EMAIL_TRANSPORT_BREAKER: Lazy<CircuitBreaker> = Lazy::new(|| {
+    CircuitBreaker::from_config("email_transport", &Config::get().resilience.circuit_breaker)
+});
+
+/// Process-wide breaker guarding the Ollama endpoint
+pub fn ollama_breaker() -> &'static CircuitBreaker {
+    &OLLAMA_BREAKER
+}
+
+/// Process-wide breaker guarding the outgoing email transport
+pub fn email_transport_breaker() -> &'static CircuitBreaker {
+    &EMAIL_TRANSPORT_BREAKER
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+    /// Calls are rejected until the open period ends
+    Open,
+    /// A limited number of probe calls decide whether to close again
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half_open"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CircuitError<E> {
+    Open { service: String },
+    Inner(E),
+}
+
+impl<E: fmt::Display> fmt::Display for CircuitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::Open { service } => {
+                write!(f, "Circuit for {} is open; not calling it", service)
+            }
+            CircuitError::Inner(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for CircuitError<E> {}
+
+/// Counters for dashboards and health checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitMetrics {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    pub rejected_calls: u64,
+    pub times_opened: u64,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probes_in_flight: u32,
+    /// Bumped on every move to half-open, so a late permit from an earlier
+    /// half-open period does not free a slot of the current one
+    half_open_period: u64,
+    total_failures: u64,
+    rejected_calls: u64,
+    times_opened: u64,
+}
+
+/// Stops calling a failing dependency for a while instead of letting every
+/// request wait for its timeout
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    service: String,
+    failure_threshold: u32,
+    open_duration: Duration,
+    half_open_probes: u32,
+    inner: Mutex<Inner>,
+    events: broadcast::Sender<CircuitEvent>,
+}
+
+impl CircuitBreaker {
+    pub fn new(service: &str, failure_threshold: u32, open_duration: Duration) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            service: service.to_string(),
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            half_open_probes: 1,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probes_in_flight: 0,
+                half_open_period: 0,
+                total_failures: 0,
+                rejected_calls: 0,
+                times_opened: 0,
+            }),
+            events,
+        }
+    }
+
+    pub fn from_config(service: &str, config: &CircuitBreakerConfig) -> Self {
+        Self::new(
+            service,
+            config.failure_threshold,
+            Duration::from_secs(config.open_secs),
+        )
+        .with_half_open_probes(config.half_open_probes)
+    }
+
+    /// Number of concurrent probe calls allowed while half-open
+    pub fn with_half_open_probes(mut self, probes: u32) -> Self {
+        self.half_open_probes = probes.max(1);
+        self
+    }
+
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CircuitEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.inner.lock().unwrap().state
+    }
+
+    pub fn metrics(&self) -> CircuitMetrics {
+        let inner = self.inner.lock().unwrap();
+        CircuitMetrics {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            total_failures: inner.total_failures,
+            rejected_calls: inner.rejected_calls,
+            times_opened: inner.times_opened,
+        }
+    }
+
+    /// Asks to make a call; on `Ok` the outcome is reported through the
+    /// permit. A permit dropped without an outcome counts as neither, and
+    /// frees its half-open probe slot.
+    pub fn try_acquire(&self) -> Result<CircuitPermit<'_>, CircuitError<()>> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    pub fn try_acquire_at(&self, now: Instant) -> Result<CircuitPermit<'_>, CircuitError<()>> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state == CircuitState::Open
+            && inner
+                .opened_at
+                .is_some_and(|opened| now.duration_since(opened) >= self.open_duration)
+        {
+            self.transition(&mut inner, CircuitState::HalfOpen);
+        }
+
+        match inner.state {
+            CircuitState::Closed => Ok(CircuitPermit {
+                breaker: self,
+                probe_period: None,
+            }),
+            CircuitState::HalfOpen if inner.probes_in_flight < self.half_open_probes => {
+                inner.probes_in_flight += 1;
+                Ok(CircuitPermit {
+                    breaker: self,
+                    probe_period: Some(inner.half_open_period),
+                })
+            }
+            _ => {
+                inner.rejected_calls += 1;
+                Err(CircuitError::Open {
+                    service: self.service.clone(),
+                })
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = 0;
+        if inner.state == CircuitState::HalfOpen {
+            inner.probes_in_flight = 0;
+            self.transition(&mut inner, CircuitState::Closed);
+        }
+    }
+
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now());
+    }
+
+    pub fn record_failure_at(&self, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        inner.total_failures += 1;
+        let should_open = match inner.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            CircuitState::Open => false,
+        };
+        if should_open {
+            inner.opened_at = Some(now);
+            inner.probes_in_flight = 0;
+            inner.times_opened += 1;
+            self.transition(&mut inner, CircuitState::Open);
+        }
+    }
+
+    /// Runs `call` through the breaker, recording its outcome
+    pub async fn call<T, E, F>(&self, call: F) -> Result<T, CircuitError<E>>
+    where
+        F: Future<Output = Result<T, E>>,
+    {
+        self.call_counting(call, |_| true).await
+    }
+
+    /// Like `call`, but only errors for which `is_failure` holds count
+    /// against the service; the others leave the breaker as it was
+    pub async fn call_counting<T, E, F>(
+        &self,
+        call: F,
+        is_failure: impl FnOnce(&E) -> bool,
+    ) -> Result<T, CircuitError<E>>
+    where
+        F: Future<Output = Result<T, E>>,
+    {
+        let permit = self.try_acquire().map_err(|_| CircuitError::Open {
+            service: self.service.clone(),
+        })?;
+        match call.await {
+            Ok(value) => {
+                permit.success();
+                Ok(value)
+            }
+            Err(e) => {
+                if is_failure(&e) {
+                    permit.failure();
+                }
+                Err(CircuitError::Inner(e))
+            }
+        }
+    }
+
+    /// Gives back a probe slot whose call ended without an outcome
+    fn release_probe(&self, period: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state == CircuitState::HalfOpen && inner.half_open_period == period {
+            inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+        }
+    }
+
+    fn transition(&self, inner: &mut Inner, to: CircuitState) {
+        if inner.state == to {
+            return;
+        }
+        let from = inner.state;
+        inner.state = to;
+        if to == CircuitState::HalfOpen {
+            inner.half_open_period += 1;
+        }
+        let _ = self.events.send(CircuitEvent::StateChanged {
+            service: self.service.clone(),
+            from,
+            to,
+        });
+    }
+}
+
+/// Leave to make one call through a `CircuitBreaker`
+#[derive(Debug)]
+#[must_use = "dropping the permit reports no outcome"]
+pub struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    /// Half-open period of the probe slot this permit holds, if any
+    probe_period: Option<u64>,
+}
+
+impl CircuitPermit<'_> {
+    pub fn success(mut self) {
+        self.probe_period = None;
+        self.breaker.record_success();
+    }
+
+    pub fn failure(mut self) {
+        self.probe_period = None;
+        self.breaker.record_failure();
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if let Some(period) = self.probe_period {
+            self.breaker.release_probe(period);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new("ollama", 3, Duration::from_secs(30))
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = breaker();
+
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(
+            breaker.try_acquire(),
+            Err(CircuitError::Open { .. })
+        ));
+        assert_eq!(breaker.metrics().rejected_calls, 1);
+    }
+
+    #[test]
+    fn test_half_open_probe_closes_on_success() {
+        let breaker = breaker();
+        let start = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(start);
+        }
+
+        let later = start + Duration::from_secs(31);
+        let probe = breaker.try_acquire_at(later).unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        // Only one probe at a time
+        assert!(breaker.try_acquire_at(later).is_err());
+
+        probe.success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_probe_failure_reopens() {
+        let breaker = breaker();
+        let start = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(start);
+        }
+
+        let later = start + Duration::from_secs(31);
+        let _probe = breaker.try_acquire_at(later).unwrap();
+        breaker.record_failure_at(later);
+
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(
+            breaker
+                .try_acquire_at(later + Duration::from_secs(1))
+                .is_err()
+        );
+        assert_eq!(breaker.metrics().times_opened, 2);
+    }
+
+    #[test]
+    fn test_state_changes_are_broadcast() {
+        let breaker = CircuitBreaker::new("email_transport", 1, Duration::from_secs(30));
+        let mut events = breaker.subscribe();
+
+        breaker.record_failure();
+
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CircuitEvent::StateChanged {
+                service: "email_transport".to_string(),
+                from: CircuitState::Closed,
+                to: CircuitState::Open,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_records_outcome() {
+        let breaker = CircuitBreaker::new("ollama", 1, Duration::from_secs(30));
+
+        let result: Result<(), _> = breaker.call(async { Err("timeout") }).await;
+        assert_eq!(result, Err(CircuitError::Inner("timeout")));
+
+        let result = breaker.call(async { Ok::<_, &str>(1) }).await;
+        assert_eq!(
+            result,
+            Err(CircuitError::Open {
+                service: "ollama".to_string()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dropped_probe_frees_its_slot() {
+        let breaker = CircuitBreaker::new("ollama", 1, Duration::ZERO);
+        breaker.record_failure();
+
+        {
+            let probe = breaker.call(std::future::pending::<Result<(), &str>>());
+            tokio::pin!(probe);
+            // Polls the call once so it takes the probe slot, then drops it
+            let _ = tokio::time::timeout(Duration::from_millis(1), &mut probe).await;
+            assert_eq!(breaker.state(), CircuitState::HalfOpen);
+            assert!(breaker.try_acquire().is_err());
+        }
+
+        let result = breaker.call(async { Ok::<_, &str>(1) }).await;
+        assert_eq!(result, Ok(1));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_uncounted_errors_leave_the_breaker_closed() {
+        let breaker = CircuitBreaker::new("ollama", 1, Duration::from_secs(30));
+
+        let result: Result<(), _> = breaker
+            .call_counting(async { Err("bad request") }, |_| false)
+            .await;
+
+        assert_eq!(result, Err(CircuitError::Inner("bad request")));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.metrics().total_failures, 0);
+    }
+}
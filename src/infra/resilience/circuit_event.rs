@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+use crate::infra::resilience::CircuitState;
+
+/// Emitted by a circuit breaker whenever its state changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum CircuitEvent {
+    StateChanged {
+        service: String,
+        from: CircuitState,
+        to: CircuitState,
+    },
+}
@@ -0,0 +1,389 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use crate::config::{Config, CostConfig, TokenBudgetConfig};
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Tenant of clients that were not given one
+pub const DEFAULT_TENANT: &str = "default";
+
+static COST_GUARD: Lazy<CostGuard> = Lazy::new(|| CostGuard::from_config(&Config::get().cost));
+
+/// Process-wide guard every Ollama request is checked against
+pub fn cost_guard() -> &'static CostGuard {
+    &COST_GUARD
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    /// Start of the next period after `now`
+    pub fn resets_at(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let date = match self {
+            BudgetPeriod::Daily => now.date() + Duration::days(1),
+            BudgetPeriod::Monthly => first_of_next_month(now.date()),
+        };
+        date.and_time(NaiveTime::MIN)
+    }
+}
+
+impl fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetPeriod::Daily => write!(f, "daily"),
+            BudgetPeriod::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+/// A token budget was used up; requests are refused until it resets
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetExceeded {
+    pub tenant: String,
+    pub model: String,
+    pub period: BudgetPeriod,
+    pub limit: u64,
+    pub used: u64,
+    pub resets_at: NaiveDateTime,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} token budget of {} for '{}' on {} used up ({} used); resets at {}",
+            self.period,
+            self.limit,
+            self.tenant,
+            self.model,
+            self.used,
+            self.resets_at.format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+
+impl Error for BudgetExceeded {}
+
+/// Emitted when usage of a budget crosses the warning threshold or the
+/// limit itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum UsageEvent {
+    ThresholdReached {
+        tenant: String,
+        model: String,
+        period: BudgetPeriod,
+        used: u64,
+        limit: u64,
+    },
+    BudgetExceeded {
+        tenant: String,
+        model: String,
+        period: BudgetPeriod,
+        used: u64,
+        limit: u64,
+    },
+}
+
+/// Tokens spent against one limit in its current period
+#[derive(Debug, Clone)]
+struct Spend {
+    period_start: NaiveDate,
+    used: u64,
+}
+
+#[derive(Debug)]
+struct Limit {
+    tenant: Option<String>,
+    model: Option<String>,
+    period: BudgetPeriod,
+    tokens: u64,
+    spend: Spend,
+}
+
+impl Limit {
+    fn applies_to(&self, tenant: &str, model: &str) -> bool {
+        self.tenant.as_deref().is_none_or(|t| t == tenant)
+            && self.model.as_deref().is_none_or(|m| m == model)
+    }
+
+    /// Starts a new period once the current one is over
+    fn roll(&mut self, now: NaiveDateTime) {
+        let start = period_start(self.period, now.date());
+        if start != self.spend.period_start {
+            self.spend = Spend {
+                period_start: start,
+                used: 0,
+            };
+        }
+    }
+}
+
+/// Daily and monthly token budgets per model and tenant. A limit without a
+/// tenant or model pools the usage of all of them.
+#[derive(Debug)]
+pub struct CostGuard {
+    enabled: bool,
+    warn_at: f32,
+    limits: Mutex<Vec<Limit>>,
+    events: broadcast::Sender<UsageEvent>,
+}
+
+impl CostGuard {
+    /// Guard without limits; add them with `with_budget`
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            enabled: true,
+            warn_at: CostConfig::default().warn_at,
+            limits: Mutex::new(Vec::new()),
+            events,
+        }
+    }
+
+    pub fn from_config(config: &CostConfig) -> Self {
+        let guard = Self {
+            enabled: config.enabled,
+            ..Self::new()
+        }
+        .with_warning_at(config.warn_at);
+        config
+            .budgets
+            .iter()
+            .fold(guard, |guard, budget| guard.with_budget(budget))
+    }
+
+    /// Share of a budget at which `ThresholdReached` is sent
+    pub fn with_warning_at(mut self, warn_at: f32) -> Self {
+        self.warn_at = warn_at.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_budget(self, budget: &TokenBudgetConfig) -> Self {
+        let now = Local::now().naive_local();
+        let periods = [
+            (BudgetPeriod::Daily, budget.daily_tokens),
+            (BudgetPeriod::Monthly, budget.monthly_tokens),
+        ];
+        {
+            let mut limits = self.limits.lock().unwrap();
+            for (period, tokens) in periods {
+                let Some(tokens) = tokens else { continue };
+                limits.push(Limit {
+                    tenant: budget.tenant.clone(),
+                    model: budget.model.clone(),
+                    period,
+                    tokens,
+                    spend: Spend {
+                        period_start: period_start(period, now.date()),
+                        used: 0,
+                    },
+                });
+            }
+        }
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<UsageEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn check(&self, tenant: &str, model: &str) -> Result<(), BudgetExceeded> {
+        self.check_at(tenant, model, Local::now().naive_local())
+    }
+
+    /// Refuses a request once any budget it falls under is used up
+    pub fn check_at(
+        &self,
+        tenant: &str,
+        model: &str,
+        now: NaiveDateTime,
+    ) -> Result<(), BudgetExceeded> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let mut limits = self.limits.lock().unwrap();
+        for limit in limits.iter_mut() {
+            if !limit.applies_to(tenant, model) {
+                continue;
+            }
+            limit.roll(now);
+            if limit.spend.used >= limit.tokens {
+                return Err(BudgetExceeded {
+                    tenant: tenant.to_string(),
+                    model: model.to_string(),
+                    period: limit.period,
+                    limit: limit.tokens,
+                    used: limit.spend.used,
+                    resets_at: limit.period.resets_at(now),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn record(&self, tenant: &str, model: &str, tokens: u64) {
+        self.record_at(tenant, model, tokens, Local::now().naive_local())
+    }
+
+    /// Adds the tokens of a finished request to every budget it falls under
+    pub fn record_at(&self, tenant: &str, model: &str, tokens: u64, now: NaiveDateTime) {
+        if !self.enabled {
+            return;
+        }
+        let mut limits = self.limits.lock().unwrap();
+        for limit in limits.iter_mut() {
+            if !limit.applies_to(tenant, model) {
+                continue;
+            }
+            limit.roll(now);
+            let before = limit.spend.used;
+            limit.spend.used += tokens;
+            let warning = (limit.tokens as f64 * f64::from(self.warn_at)).round() as u64;
+            let event = if before < limit.tokens && limit.spend.used >= limit.tokens {
+                UsageEvent::BudgetExceeded {
+                    tenant: tenant.to_string(),
+                    model: model.to_string(),
+                    period: limit.period,
+                    used: limit.spend.used,
+                    limit: limit.tokens,
+                }
+            } else if before < warning && limit.spend.used >= warning {
+                UsageEvent::ThresholdReached {
+                    tenant: tenant.to_string(),
+                    model: model.to_string(),
+                    period: limit.period,
+                    used: limit.spend.used,
+                    limit: limit.tokens,
+                }
+            } else {
+                continue;
+            };
+            let _ = self.events.send(event);
+        }
+    }
+
+    /// Tokens used in the current period of each budget `tenant` and
+    /// `model` fall under
+    pub fn usage(&self, tenant: &str, model: &str) -> Vec<(BudgetPeriod, u64, u64)> {
+        let now = Local::now().naive_local();
+        let mut limits = self.limits.lock().unwrap();
+        limits
+            .iter_mut()
+            .filter(|limit| limit.applies_to(tenant, model))
+            .map(|limit| {
+                limit.roll(now);
+                (limit.period, limit.spend.used, limit.tokens)
+            })
+            .collect()
+    }
+}
+
+impl Default for CostGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn period_start(period: BudgetPeriod, date: NaiveDate) -> NaiveDate {
+    match period {
+        BudgetPeriod::Daily => date,
+        BudgetPeriod::Monthly => date.with_day(1).unwrap_or(date),
+    }
+}
+
+fn first_of_next_month(date: NaiveDate) -> NaiveDate {
+    let (year, month) = match date.month() {
+        12 => (date.year() + 1, 1),
+        month => (date.year(), month + 1),
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, month, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn budget(model: Option<&str>, daily: Option<u64>, monthly: Option<u64>) -> TokenBudgetConfig {
+        TokenBudgetConfig {
+            tenant: None,
+            model: model.map(str::to_string),
+            daily_tokens: daily,
+            monthly_tokens: monthly,
+        }
+    }
+
+    #[test]
+    fn test_daily_budget_is_enforced_and_resets() {
+        let guard = CostGuard::new().with_budget(&budget(Some("gpt-4o"), Some(1000), None));
+
+        guard.record_at("acme", "gpt-4o", 1200, at(5, 14, 10));
+        let error = guard.check_at("acme", "gpt-4o", at(5, 14, 11)).unwrap_err();
+
+        assert_eq!(error.period, BudgetPeriod::Daily);
+        assert_eq!(error.used, 1200);
+        assert_eq!(error.resets_at, at(5, 15, 0));
+        assert!(guard.check_at("acme", "llama3", at(5, 14, 11)).is_ok());
+        assert!(guard.check_at("acme", "gpt-4o", at(5, 15, 0)).is_ok());
+    }
+
+    #[test]
+    fn test_monthly_budget_per_tenant() {
+        let guard = CostGuard::new().with_budget(&TokenBudgetConfig {
+            tenant: Some("acme".to_string()),
+            ..budget(None, None, Some(500))
+        });
+
+        guard.record_at("acme", "llama3", 300, at(12, 1, 9));
+        guard.record_at("acme", "gpt-4o", 300, at(12, 20, 9));
+        guard.record_at("globex", "gpt-4o", 10_000, at(12, 20, 9));
+
+        let error = guard
+            .check_at("acme", "llama3", at(12, 31, 23))
+            .unwrap_err();
+        assert_eq!(error.resets_at, at(1, 1, 0).with_year(2026).unwrap());
+        assert!(guard.check_at("globex", "llama3", at(12, 31, 23)).is_ok());
+    }
+
+    #[test]
+    fn test_threshold_and_exceeded_events() {
+        let guard =
+            CostGuard::new()
+                .with_warning_at(0.8)
+                .with_budget(&budget(None, Some(100), None));
+        let mut events = guard.subscribe();
+
+        guard.record_at("acme", "llama3", 50, at(5, 14, 9));
+        guard.record_at("acme", "llama3", 30, at(5, 14, 9));
+        guard.record_at("acme", "llama3", 30, at(5, 14, 9));
+        guard.record_at("acme", "llama3", 30, at(5, 14, 9));
+
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            UsageEvent::ThresholdReached { used: 80, .. }
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            UsageEvent::BudgetExceeded { used: 110, .. }
+        ));
+        assert!(events.try_recv().is_err());
+    }
+}
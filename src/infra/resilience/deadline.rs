@@ -0,0 +1,107 @@
+use std::time::{Duration, Instant};
+
+/// Header carrying the caller's remaining time budget, e.g. `2500ms`, `3s`
+/// or a bare number of milliseconds
+pub const DEADLINE_HEADER: &str = "x-request-timeout";
+
+/// Point in time after which the caller no longer wants an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline {
+    expires_at: Instant,
+}
+
+impl Deadline {
+    pub fn after(budget: Duration) -> Self {
+        Self::after_at(budget, Instant::now())
+    }
+
+    pub fn after_at(budget: Duration, now: Instant) -> Self {
+        Self {
+            expires_at: now + budget,
+        }
+    }
+
+    /// Parses the value of `DEADLINE_HEADER`; `None` if it is malformed
+    pub fn from_header(value: &str) -> Option<Self> {
+        parse_budget(value).map(Self::after)
+    }
+
+    pub fn expires_at(&self) -> Instant {
+        self.expires_at
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.remaining_at(Instant::now())
+    }
+
+    pub fn remaining_at(&self, now: Instant) -> Duration {
+        self.expires_at.saturating_duration_since(now)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Whether an attempt expected to take `expected` still fits
+    pub fn allows(&self, expected: Duration) -> bool {
+        self.remaining() > expected
+    }
+}
+
+fn parse_budget(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Some(ms) = value.strip_suffix("ms") {
+        return ms.trim().parse().ok().map(Duration::from_millis);
+    }
+    if let Some(secs) = value.strip_suffix('s') {
+        return secs
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .map(Duration::from_secs_f64);
+    }
+    value.parse().ok().map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_budget() {
+        assert_eq!(parse_budget("2500ms"), Some(Duration::from_millis(2500)));
+        assert_eq!(parse_budget("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_budget(" 800 "), Some(Duration::from_millis(800)));
+        assert_eq!(parse_budget("-1s"), None);
+        assert_eq!(parse_budget("soon"), None);
+    }
+
+    #[test]
+    fn test_remaining_budget() {
+        let now = Instant::now();
+        let deadline = Deadline::after_at(Duration::from_secs(2), now);
+
+        assert_eq!(
+            deadline.remaining_at(now + Duration::from_millis(500)),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            deadline.remaining_at(now + Duration::from_secs(3)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_expired_deadline_allows_nothing() {
+        let deadline = Deadline::after(Duration::ZERO);
+
+        assert!(deadline.is_expired());
+        assert!(!deadline.allows(Duration::from_millis(1)));
+        assert!(
+            Deadline::from_header("10s")
+                .unwrap()
+                .allows(Duration::from_secs(1))
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::infra::resilience::RetryBudget;
+
+/// Runs `primary`; if it has not finished after `delay` and the budget
+/// allows, starts `hedge` as well and returns the first success. When one
+/// attempt fails the other is awaited, so an error is only returned when
+/// both fail (the later error wins).
+pub async fn hedged<T, E, P, H, F>(
+    primary: P,
+    hedge: F,
+    delay: Duration,
+    budget: &RetryBudget,
+) -> Result<T, E>
+where
+    P: Future<Output = Result<T, E>>,
+    H: Future<Output = Result<T, E>>,
+    F: FnOnce() -> H,
+{
+    budget.record_request();
+    tokio::pin!(primary);
+
+    tokio::select! {
+        result = &mut primary => return result,
+        _ = tokio::time::sleep(delay) => {}
+    }
+    if !budget.try_withdraw() {
+        return primary.await;
+    }
+
+    let hedge = hedge();
+    tokio::pin!(hedge);
+    let primary_failed = tokio::select! {
+        result = &mut primary => match result {
+            Ok(value) => return Ok(value),
+            Err(_) => true,
+        },
+        result = &mut hedge => match result {
+            Ok(value) => return Ok(value),
+            Err(_) => false,
+        },
+    };
+    if primary_failed {
+        hedge.await
+    } else {
+        primary.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn answer(value: &'static str, after_ms: u64, ok: bool) -> Result<&'static str, String> {
+        tokio::time::sleep(Duration::from_millis(after_ms)).await;
+        if ok {
+            Ok(value)
+        } else {
+            Err(value.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fast_primary_is_not_hedged() {
+        let budget = RetryBudget::new(0.0, 1);
+
+        let result = hedged(
+            answer("primary", 1, true),
+            || answer("hedge", 1, true),
+            Duration::from_millis(50),
+            &budget,
+        )
+        .await;
+
+        assert_eq!(result, Ok("primary"));
+        assert_eq!(budget.available(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_primary_loses_to_hedge() {
+        let budget = RetryBudget::new(0.0, 1);
+
+        let result = hedged(
+            answer("primary", 500, true),
+            || answer("hedge", 1, true),
+            Duration::from_millis(10),
+            &budget,
+        )
+        .await;
+
+        assert_eq!(result, Ok("hedge"));
+        assert_eq!(budget.available(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_hedge_waits_for_primary() {
+        let budget = RetryBudget::new(0.0, 1);
+
+        let result = hedged(
+            answer("primary", 60, true),
+            || answer("hedge", 1, false),
+            Duration::from_millis(10),
+            &budget,
+        )
+        .await;
+
+        assert_eq!(result, Ok("primary"));
+    }
+
+    #[tokio::test]
+    async fn test_empty_budget_disables_hedging() {
+        let budget = RetryBudget::new(0.0, 0);
+
+        let result = hedged(
+            answer("primary", 40, true),
+            || answer("hedge", 1, true),
+            Duration::from_millis(5),
+            &budget,
+        )
+        .await;
+
+        assert_eq!(result, Ok("primary"));
+    }
+}
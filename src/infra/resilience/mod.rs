@@ -0,0 +1,18 @@
+pub mod circuit_breaker;
+pub mod circuit_event;
+pub mod cost_guard;
+pub mod deadline;
+pub mod hedge;
+pub mod retry_budget;
+
+pub use circuit_breaker::{
+    CircuitBreaker, CircuitError, CircuitMetrics, CircuitPermit, CircuitState,
+    email_transport_breaker, ollama_breaker,
+};
+pub use circuit_event::CircuitEvent;
+pub use cost_guard::{
+    BudgetExceeded, BudgetPeriod, CostGuard, DEFAULT_TENANT, UsageEvent, cost_guard,
+};
+pub use deadline::{DEADLINE_HEADER, Deadline};
+pub use hedge::hedged;
+pub use retry_budget::{RetryBudget, hedge_budget};
@@ -0,0 +1,88 @@
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+use crate::config::{Config, HedgingConfig};
+
+static HEDGE_BUDGET: Lazy<RetryBudget> =
+    Lazy::new(|| RetryBudget::from_config(&Config::get().resilience.hedging));
+
+/// Process-wide budget shared by all hedged Ollama requests
+pub fn hedge_budget() -> &'static RetryBudget {
+    &HEDGE_BUDGET
+}
+
+/// Token bucket limiting extra attempts to a fraction of regular traffic.
+/// Each request deposits `ratio` tokens and each extra attempt spends one,
+/// so a slow dependency cannot make us double our load indefinitely.
+#[derive(Debug)]
+pub struct RetryBudget {
+    ratio: f64,
+    max_tokens: f64,
+    tokens: Mutex<f64>,
+}
+
+impl RetryBudget {
+    /// `reserve` tokens are available up front so a cold process can still
+    /// hedge its first requests
+    pub fn new(ratio: f64, reserve: u32) -> Self {
+        let reserve = f64::from(reserve);
+        Self {
+            ratio: ratio.max(0.0),
+            max_tokens: reserve.max(1.0),
+            tokens: Mutex::new(reserve),
+        }
+    }
+
+    pub fn from_config(config: &HedgingConfig) -> Self {
+        Self::new(config.budget_ratio, config.budget_reserve)
+    }
+
+    pub fn record_request(&self) {
+        let mut tokens = self.tokens.lock().unwrap();
+        *tokens = (*tokens + self.ratio).min(self.max_tokens);
+    }
+
+    /// Spends a token for one extra attempt, if there is one
+    pub fn try_withdraw(&self) -> bool {
+        let mut tokens = self.tokens.lock().unwrap();
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn available(&self) -> f64 {
+        *self.tokens.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_is_spent_then_refilled_by_requests() {
+        let budget = RetryBudget::new(0.5, 1);
+
+        assert!(budget.try_withdraw());
+        assert!(!budget.try_withdraw());
+
+        budget.record_request();
+        assert!(!budget.try_withdraw());
+        budget.record_request();
+        assert!(budget.try_withdraw());
+    }
+
+    #[test]
+    fn test_tokens_are_capped() {
+        let budget = RetryBudget::new(1.0, 2);
+
+        for _ in 0..10 {
+            budget.record_request();
+        }
+
+        assert_eq!(budget.available(), 2.0);
+    }
+}
@@ -0,0 +1,127 @@
+use futures_core::future::BoxFuture;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::agent::ComposedEmail;
+use crate::config::{Config, MailAccountConfig, SmtpConfig};
+use crate::infra::contacts::UserContacts;
+use crate::infra::smtp::{DeliveryReceipt, EmailSender, MailTransport, SmtpError};
+
+/// One `EmailSender` per `[[accounts]]` entry, plus the `[smtp]` one for
+/// emails without an account
+pub struct AccountSenders {
+    default: Option<EmailSender>,
+    accounts: BTreeMap<String, EmailSender>,
+}
+
+impl AccountSenders {
+    /// `[smtp]` is left out when it has no `from` address
+    pub fn from_config(
+        smtp: &SmtpConfig,
+        accounts: &[MailAccountConfig],
+    ) -> Result<Self, SmtpError> {
+        let default = match EmailSender::from_config(smtp) {
+            Ok(sender) => Some(sender),
+            Err(SmtpError::NoSender) => None,
+            Err(e) => return Err(e),
+        };
+        let accounts = accounts
+            .iter()
+            .map(|account| Ok((account.id.clone(), EmailSender::from_config(&account.smtp)?)))
+            .collect::<Result<_, SmtpError>>()?;
+        Ok(Self { default, accounts })
+    }
+
+    pub fn configured() -> Result<Self, SmtpError> {
+        let config = Config::get();
+        Self::from_config(&config.smtp, &config.accounts)
+    }
+
+    /// Resolves recipients given by contact name, for every account
+    pub fn with_contacts(mut self, contacts: Arc<UserContacts>) -> Self {
+        self.default = self
+            .default
+            .map(|sender| sender.with_contacts(contacts.clone()));
+        self.accounts = self
+            .accounts
+            .into_iter()
+            .map(|(id, sender)| (id, sender.with_contacts(contacts.clone())))
+            .collect();
+        self
+    }
+
+    /// Sender of `account_id`; `[smtp]`'s for `None`
+    pub fn sender(&self, account_id: Option<&str>) -> Result<&EmailSender, SmtpError> {
+        match account_id {
+            Some(id) => self
+                .accounts
+                .get(id)
+                .ok_or_else(|| SmtpError::UnknownAccount(id.to_string())),
+            None => self.default.as_ref().ok_or(SmtpError::NoSender),
+        }
+    }
+
+    /// Sends `email` from its account
+    pub async fn send(&self, email: ComposedEmail) -> Result<DeliveryReceipt, SmtpError> {
+        self.sender(email.account_id.as_deref())?.send(email).await
+    }
+}
+
+impl MailTransport for AccountSenders {
+    fn send(&self, email: ComposedEmail) -> BoxFuture<'_, Result<DeliveryReceipt, SmtpError>> {
+        Box::pin(AccountSenders::send(self, email))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::email::EmailAddress;
+
+    fn smtp(from: Option<&str>) -> SmtpConfig {
+        SmtpConfig {
+            from: from.map(|from| EmailAddress::parse(from).unwrap()),
+            ..SmtpConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_sender_per_account() {
+        let work = MailAccountConfig {
+            id: "work".to_string(),
+            names: Vec::new(),
+            domains: Vec::new(),
+            intents: Vec::new(),
+            default: false,
+            smtp: smtp(Some("me@company.com")),
+            imap: None,
+        };
+
+        let senders =
+            AccountSenders::from_config(&smtp(None), std::slice::from_ref(&work)).unwrap();
+
+        assert_eq!(
+            senders
+                .sender(Some("work"))
+                .unwrap()
+                .from_address()
+                .to_string(),
+            "me@company.com"
+        );
+        assert!(matches!(senders.sender(None), Err(SmtpError::NoSender)));
+        assert!(matches!(
+            senders.sender(Some("personal")),
+            Err(SmtpError::UnknownAccount(id)) if id == "personal"
+        ));
+        assert!(matches!(
+            AccountSenders::from_config(
+                &smtp(None),
+                &[MailAccountConfig {
+                    smtp: smtp(None),
+                    ..work
+                }]
+            ),
+            Err(SmtpError::NoSender)
+        ));
+    }
+}
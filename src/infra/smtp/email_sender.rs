@@ -0,0 +1,485 @@
+use chrono::{DateTime, Utc};
+use futures_core::future::BoxFuture;
+use lettre::address::Envelope;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::agent::ComposedEmail;
+#[cfg(feature = "smime")]
+use crate::config::SmimeConfig;
+use crate::config::{Config, MailHeadersConfig, SmtpConfig, SmtpSecurity};
+use crate::infra::contacts::UserContacts;
+use crate::infra::email::{EmailAddress, MimeAttachment, MimeMessage, deliverability_issues};
+#[cfg(feature = "smime")]
+use crate::infra::email::{SigningKey, SmimeError, SmimeProtection, load_certificate};
+use crate::infra::resilience::{CircuitError, email_transport_breaker};
+use crate::infra::smtp::{DeliveryReceipt, MailTransport, SmtpError};
+
+static MESSAGE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Delivers composed emails through the SMTP server of `[smtp]`
+pub struct EmailSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: EmailAddress,
+    contacts: Arc<UserContacts>,
+    headers: MailHeadersConfig,
+    #[cfg(feature = "smime")]
+    smime: SmimeConfig,
+}
+
+impl EmailSender {
+    pub fn from_config(config: &SmtpConfig) -> Result<Self, SmtpError> {
+        let from = config.from.clone().ok_or(SmtpError::NoSender)?;
+        let builder = match config.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+                .map_err(|e| SmtpError::Transport(e.to_string()))?,
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                    .map_err(|e| SmtpError::Transport(e.to_string()))?
+            }
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            }
+        };
+        let mut builder = builder
+            .port(config.port)
+            .timeout(Some(Duration::from_secs(config.timeout_secs)));
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            contacts: Arc::new(UserContacts::default()),
+            headers: config.headers.clone(),
+            #[cfg(feature = "smime")]
+            smime: config.smime.clone(),
+        })
+    }
+
+    /// Sender for the server in config.toml
+    pub fn configured() -> Result<Self, SmtpError> {
+        Self::from_config(&Config::get().smtp)
+    }
+
+    /// Resolves recipients given by contact name
+    pub fn with_contacts(mut self, contacts: Arc<UserContacts>) -> Self {
+        self.contacts = contacts;
+        self
+    }
+
+    pub fn from_address(&self) -> &EmailAddress {
+        &self.from
+    }
+
+    /// Message for `email`, addressed to the resolved recipient, cc and
+    /// bcc, with its files attached
+    pub fn message(&self, email: &ComposedEmail) -> Result<MimeMessage, SmtpError> {
+        let to = self.resolve(&email.recipient)?;
+        let mut message = MimeMessage::new(vec![to], &email.subject, &email.body)
+            .with_cc(self.resolve_all(&email.cc)?)
+            .with_bcc(self.resolve_all(&email.bcc)?);
+        for path in &email.attachments {
+            let attachment = MimeAttachment::from_file(path)
+                .map_err(|e| SmtpError::Attachment(format!("{}: {}", path.display(), e)))?;
+            message = message.with_attachment(attachment);
+        }
+        Ok(message)
+    }
+
+    pub async fn send(&self, email: ComposedEmail) -> Result<DeliveryReceipt, SmtpError> {
+        self.send_message(self.message(&email)?).await
+    }
+
+    /// Sends an already built message; its `From` becomes the configured
+    /// sender so it matches the envelope. Messages failing the
+    /// deliverability checklist are not sent.
+    pub async fn send_message(&self, message: MimeMessage) -> Result<DeliveryReceipt, SmtpError> {
+        let message_id = self.new_message_id();
+        let sent_at = Utc::now();
+        let message = self.with_headers(message, &message_id, sent_at);
+        let issues: Vec<_> = deliverability_issues(&message)
+            .into_iter()
+            .filter(|issue| !issue.is_warning())
+            .collect();
+        if !issues.is_empty() {
+            return Err(SmtpError::Undeliverable(issues));
+        }
+        let envelope = Envelope::new(
+            Some(lettre_address(&self.from)?),
+            message
+                .recipients()
+                .iter()
+                .map(lettre_address)
+                .collect::<Result<_, _>>()?,
+        )
+        .map_err(|e| SmtpError::InvalidRecipient(e.to_string()))?;
+
+        let source = self.render(&message)?;
+        // Only an unreachable server counts against the breaker; a reply
+        // refusing this message says nothing about the others
+        let response = email_transport_breaker()
+            .call_counting(
+                async {
+                    self.transport
+                        .send_raw(&envelope, source.as_bytes())
+                        .await
+                        .map_err(smtp_error)
+                },
+                |e| matches!(e, SmtpError::Transport(_)),
+            )
+            .await
+            .map_err(|e| match e {
+                CircuitError::Open { .. } => SmtpError::Transport(e.to_string()),
+                CircuitError::Inner(e) => e,
+            })?;
+
+        Ok(DeliveryReceipt {
+            message_id,
+            recipients: message.recipients(),
+            response: format!(
+                "{} {}",
+                response.code(),
+                response.message().collect::<Vec<_>>().join(" ")
+            ),
+            sent_at,
+        })
+    }
+
+    /// Sender, Date, Message-ID and the configured optional headers
+    fn with_headers(
+        &self,
+        message: MimeMessage,
+        message_id: &str,
+        sent_at: DateTime<Utc>,
+    ) -> MimeMessage {
+        let message = message
+            .with_from(self.from.clone())
+            .with_message_id(message_id)
+            .with_date(sent_at)
+            .with_list_unsubscribe(
+                &self.headers.list_unsubscribe,
+                self.headers.one_click_unsubscribe,
+            );
+        match self.headers.auto_submitted {
+            Some(auto_submitted) => message.with_auto_submitted(auto_submitted),
+            None => message,
+        }
+    }
+
+    #[cfg(not(feature = "smime"))]
+    fn render(&self, message: &MimeMessage) -> Result<String, SmtpError> {
+        Ok(message.render())
+    }
+
+    #[cfg(feature = "smime")]
+    fn render(&self, message: &MimeMessage) -> Result<String, SmtpError> {
+        self.protection(&message.recipients())
+            .and_then(|protection| message.render_protected(&protection))
+            .map_err(|e| SmtpError::Protection(e.to_string()))
+    }
+
+    /// Signs with the sender's identity, if it has one, and encrypts when
+    /// every recipient's contact has a certificate. The sender's own
+    /// certificate is added so the sent copy stays readable.
+    #[cfg(feature = "smime")]
+    fn protection(&self, to: &[EmailAddress]) -> Result<SmimeProtection, SmimeError> {
+        let identity = self
+            .smime
+            .identities
+            .iter()
+            .find(|identity| identity.address == self.from);
+        let signer = identity
+            .map(|identity| SigningKey::load(&identity.certificate, &identity.private_key))
+            .transpose()?;
+        let mut protection = SmimeProtection::new();
+        if self.smime.encrypt
+            && let Some(certificates) = to
+                .iter()
+                .map(|address| {
+                    self.contacts
+                        .find_by_email(address)
+                        .and_then(|contact| contact.certificate.clone())
+                })
+                .collect::<Option<Vec<_>>>()
+        {
+            for path in certificates {
+                protection = protection.with_recipient(load_certificate(&path)?);
+            }
+            if let Some(signer) = &signer {
+                protection = protection.with_recipient(signer.certificate().clone());
+            }
+        }
+        match signer {
+            Some(signer) if self.smime.sign => Ok(protection.with_signer(signer)),
+            _ => Ok(protection),
+        }
+    }
+
+    fn resolve(&self, recipient: &str) -> Result<EmailAddress, SmtpError> {
+        if !EmailAddress::looks_like_address(recipient)
+            && let Some(address) = self
+                .contacts
+                .find_by_name(recipient)
+                .and_then(|contact| contact.primary_email())
+        {
+            return Ok(address.clone());
+        }
+        EmailAddress::parse(recipient)
+            .map_err(|_| SmtpError::InvalidRecipient(recipient.to_string()))
+    }
+
+    fn resolve_all(&self, recipients: &[String]) -> Result<Vec<EmailAddress>, SmtpError> {
+        recipients
+            .iter()
+            .map(|recipient| self.resolve(recipient))
+            .collect()
+    }
+
+    fn new_message_id(&self) -> String {
+        let sequence = MESSAGE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        format!(
+            "{:x}.{:04x}@{}",
+            Utc::now().timestamp_millis(),
+            sequence & 0xffff,
+            self.from.ascii_domain()
+        )
+    }
+}
+
+impl MailTransport for EmailSender {
+    fn send(&self, email: ComposedEmail) -> BoxFuture<'_, Result<DeliveryReceipt, SmtpError>> {
+        Box::pin(EmailSender::send(self, email))
+    }
+}
+
+fn lettre_address(address: &EmailAddress) -> Result<Address, SmtpError> {
+    address
+        .to_ascii()
+        .parse()
+        .map_err(|_| SmtpError::InvalidRecipient(address.to_string()))
+}
+
+fn smtp_error(e: lettre::transport::smtp::Error) -> SmtpError {
+    let code = e.status().map(|code| code.to_string()).unwrap_or_default();
+    if e.is_permanent() {
+        SmtpError::Rejected {
+            code,
+            message: e.to_string(),
+        }
+    } else if e.is_transient() {
+        SmtpError::Deferred {
+            code,
+            message: e.to_string(),
+        }
+    } else {
+        SmtpError::Transport(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::email::AutoSubmitted;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Minimal SMTP server accepting one session; returns the message data
+    async fn fake_server(listener: TcpListener) -> String {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"220 test ESMTP\r\n").await.unwrap();
+
+        let mut data = String::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let command = line.to_ascii_uppercase();
+            let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                b"250 test\r\n"
+            } else if command.starts_with("RCPT") && command.contains("BOUNCE") {
+                b"550 5.1.1 No such user\r\n"
+            } else if command.starts_with("DATA") {
+                writer.write_all(b"354 Go ahead\r\n").await.unwrap();
+                while let Some(line) = lines.next_line().await.unwrap() {
+                    if line == "." {
+                        break;
+                    }
+                    data.push_str(&line);
+                    data.push('\n');
+                }
+                b"250 2.0.0 queued as 4F2A\r\n"
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 Bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 OK\r\n"
+            };
+            writer.write_all(reply).await.unwrap();
+        }
+        data
+    }
+
+    async fn sender() -> (EmailSender, TcpListener) {
+        sender_with(MailHeadersConfig::default()).await
+    }
+
+    async fn sender_with(headers: MailHeadersConfig) -> (EmailSender, TcpListener) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = SmtpConfig {
+            headers,
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            security: SmtpSecurity::None,
+            from: Some(EmailAddress::parse("agent@example.com").unwrap()),
+            timeout_secs: 5,
+            ..SmtpConfig::default()
+        };
+        let contacts = UserContacts::load_from_file("spec/contacts.json").unwrap();
+        let sender = EmailSender::from_config(&config)
+            .unwrap()
+            .with_contacts(Arc::new(contacts));
+        (sender, listener)
+    }
+
+    #[tokio::test]
+    async fn test_send_delivers_to_resolved_contact() {
+        let (sender, listener) = sender().await;
+        let server = tokio::spawn(fake_server(listener));
+
+        let receipt = sender
+            .send(ComposedEmail::new(
+                "Tiger Brilliant",
+                "Lunch",
+                "Hi Tiger,\nlunch?",
+            ))
+            .await
+            .unwrap();
+        let data = server.await.unwrap();
+
+        assert_eq!(receipt.response, "250 2.0.0 queued as 4F2A");
+        assert_eq!(
+            receipt.recipients[0].to_string(),
+            "tiger.brilliant@gmail.com"
+        );
+        assert!(receipt.message_id.ends_with("@example.com"));
+        assert!(data.contains(&format!("Message-ID: <{}>", receipt.message_id)));
+        assert!(data.contains("From: agent@example.com"));
+        assert!(data.contains("Subject: Lunch"));
+    }
+
+    #[tokio::test]
+    async fn test_cc_bcc_and_attachments_are_sent() {
+        let (sender, listener) = sender().await;
+        let server = tokio::spawn(fake_server(listener));
+        let path = std::env::temp_dir().join(format!("notes-{}.txt", std::process::id()));
+        std::fs::write(&path, "Q3 numbers").unwrap();
+        let mut email = ComposedEmail::new("Tiger Brilliant", "Notes", "Notes attached")
+            .with_copies(
+                vec!["bruno@example.com".to_string()],
+                vec!["boss@example.com".to_string()],
+            );
+        email.attachments = vec![path.clone()];
+
+        let receipt = sender.send(email).await.unwrap();
+        let data = server.await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            receipt
+                .recipients
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "tiger.brilliant@gmail.com",
+                "bruno@example.com",
+                "boss@example.com"
+            ]
+        );
+        assert!(data.contains("Cc: bruno@example.com\n"));
+        assert!(!data.contains("boss@example.com"));
+        assert!(data.contains("Content-Type: multipart/mixed;"));
+        assert!(data.contains("Content-Disposition: attachment; filename=\"notes-"));
+    }
+
+    #[test]
+    fn test_unreadable_attachment_is_an_error() {
+        let config = SmtpConfig {
+            from: Some(EmailAddress::parse("agent@example.com").unwrap()),
+            security: SmtpSecurity::None,
+            ..SmtpConfig::default()
+        };
+        let mut email = ComposedEmail::new("eva@example.com", "Notes", "Attached");
+        email.attachments = vec!["/nonexistent/notes.txt".into()];
+
+        let message = EmailSender::from_config(&config).unwrap().message(&email);
+
+        assert!(matches!(message, Err(SmtpError::Attachment(_))));
+    }
+
+    #[tokio::test]
+    async fn test_configured_headers_are_added() {
+        let (sender, listener) = sender_with(MailHeadersConfig {
+            list_unsubscribe: vec!["https://example.com/unsubscribe".to_string()],
+            one_click_unsubscribe: true,
+            auto_submitted: Some(AutoSubmitted::AutoGenerated),
+        })
+        .await;
+        let server = tokio::spawn(fake_server(listener));
+
+        sender
+            .send(ComposedEmail::new("eva@company.com", "Lunch", "Lunch?"))
+            .await
+            .unwrap();
+        let data = server.await.unwrap();
+
+        assert!(data.starts_with("Date: "));
+        assert!(data.contains("Auto-Submitted: auto-generated\n"));
+        assert!(data.contains("List-Unsubscribe: <https://example.com/unsubscribe>\n"));
+        assert!(data.contains("List-Unsubscribe-Post: List-Unsubscribe=One-Click\n"));
+    }
+
+    #[tokio::test]
+    async fn test_undeliverable_message_is_not_sent() {
+        let (sender, _listener) = sender_with(MailHeadersConfig {
+            list_unsubscribe: vec!["http://example.com/unsubscribe".to_string()],
+            ..MailHeadersConfig::default()
+        })
+        .await;
+
+        let error = sender
+            .send(ComposedEmail::new("eva@company.com", "Lunch", " "))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            SmtpError::Undeliverable(issues) if issues.len() == 2
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_recipient() {
+        let (sender, listener) = sender().await;
+        tokio::spawn(fake_server(listener));
+
+        let error = sender
+            .send(ComposedEmail::new("bounce@example.com", "Hi", "Hello"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(&error, SmtpError::Rejected { code, .. } if code == "550"));
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_sender_address_is_required() {
+        assert_eq!(
+            EmailSender::from_config(&SmtpConfig::default()).err(),
+            Some(SmtpError::NoSender)
+        );
+    }
+}
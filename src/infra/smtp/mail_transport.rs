@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use futures_core::future::BoxFuture;
+use serde::Serialize;
+
+use crate::agent::ComposedEmail;
+use crate::infra::email::EmailAddress;
+use crate::infra::smtp::SmtpError;
+
+/// What the server said when it accepted a message
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeliveryReceipt {
+    /// Without the angle brackets
+    pub message_id: String,
+    pub recipients: Vec<EmailAddress>,
+    /// Final reply to the message data, e.g. "250 2.0.0 queued as 4F2A"
+    pub response: String,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Anything that delivers composed emails. The outbox dispatcher holds it
+/// as `Arc<dyn MailTransport>`, so tests can record emails instead.
+pub trait MailTransport: Send + Sync {
+    fn send(&self, email: ComposedEmail) -> BoxFuture<'_, Result<DeliveryReceipt, SmtpError>>;
+}
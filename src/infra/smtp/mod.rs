@@ -0,0 +1,13 @@
+#[cfg(feature = "smtp")]
+pub mod account_senders;
+#[cfg(feature = "smtp")]
+pub mod email_sender;
+pub mod mail_transport;
+pub mod smtp_error;
+
+#[cfg(feature = "smtp")]
+pub use account_senders::AccountSenders;
+#[cfg(feature = "smtp")]
+pub use email_sender::EmailSender;
+pub use mail_transport::{DeliveryReceipt, MailTransport};
+pub use smtp_error::SmtpError;
@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::infra::email::DeliverabilityIssue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmtpError {
+    /// `[smtp] from` is not set
+    NoSender,
+    /// No `[[accounts]]` entry has this id
+    UnknownAccount(String),
+    /// Neither an address nor a known contact
+    InvalidRecipient(String),
+    /// Connection, TLS or authentication problem, or a timeout
+    Transport(String),
+    /// 4xx reply; the server may accept the message later
+    Deferred { code: String, message: String },
+    /// 5xx reply; sending the same message again will fail again
+    Rejected { code: String, message: String },
+    /// An attached file could not be read
+    Attachment(String),
+    /// The message could not be signed or encrypted
+    Protection(String),
+    /// The message fails the deliverability checklist
+    Undeliverable(Vec<DeliverabilityIssue>),
+}
+
+impl SmtpError {
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, SmtpError::Transport(_) | SmtpError::Deferred { .. })
+    }
+}
+
+impl fmt::Display for SmtpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SmtpError::NoSender => write!(f, "No sender address configured for SMTP"),
+            SmtpError::UnknownAccount(id) => write!(f, "No mail account '{}' configured", id),
+            SmtpError::InvalidRecipient(recipient) => {
+                write!(
+                    f,
+                    "Cannot send to '{}': not an address or contact",
+                    recipient
+                )
+            }
+            SmtpError::Transport(detail) => write!(f, "SMTP connection failed: {}", detail),
+            SmtpError::Deferred { code, message } => {
+                write!(f, "SMTP server deferred the message: {} {}", code, message)
+            }
+            SmtpError::Rejected { code, message } => {
+                write!(f, "SMTP server rejected the message: {} {}", code, message)
+            }
+            SmtpError::Attachment(detail) => write!(f, "Cannot attach file: {}", detail),
+            SmtpError::Protection(detail) => write!(f, "Cannot protect the message: {}", detail),
+            SmtpError::Undeliverable(issues) => {
+                let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+                write!(f, "Message would not be delivered: {}", issues.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for SmtpError {}
@@ -1,4 +1,16 @@
+pub mod action;
 pub mod agent;
 pub mod assistant;
+pub mod auth;
+pub mod codec;
 pub mod config;
+pub mod error;
+pub mod i18n;
 pub mod infra;
+pub mod playground;
+pub mod prelude;
+pub mod prompt;
+pub mod safety;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod session;
@@ -1,33 +1,536 @@
-mod config;
-use ollama_ai_agents_playground::agent::{classifier::{IntentClassifierAgent, IntentParam}, Agent};
+//! Command line for the playground.
+//!
+//! Usage: ollama-ai-agents-playground [--json | --format <name>] [--contacts <file>] <command>
+//!
+//! `classify`, `compose` and `send` take the request as text or, for
+//! `classify`, as the path of a voice note. `send` asks before an email
+//! that needs confirmation is queued, and can be undone with Ctrl+C until
+//! it leaves the outbox; `send --dry-run` shows what would be sent without
+//! sending it. `compare --model <name>` classifies
+//! a request with the configured model and with another one and shows what
+//! differs. `inbox` reads the unread mail of `[imap]` and classifies
+//! each message as untrusted input.
+
+use clap::{Parser, Subcommand};
+use ollama_ai_agents_playground::action::{
+    ActionError, ActionPlan, OutboxStatus, Proposal, SimulatedEffect, Simulation,
+};
+use ollama_ai_agents_playground::agent::composer::ComposedEmail;
+use ollama_ai_agents_playground::agent::{
+    Agent, ClassificationResult,
+    classifier::{ClassificationDiff, IntentClassifierAgent, IntentDetails, IntentParam},
+};
+use ollama_ai_agents_playground::codec::{self, CodecError, Format};
+use ollama_ai_agents_playground::config::Config;
+use ollama_ai_agents_playground::error::Error;
+use ollama_ai_agents_playground::infra::contacts::UserContacts;
+use ollama_ai_agents_playground::infra::input::{TextInput, VoiceNoteInput, is_audio};
+use ollama_ai_agents_playground::infra::ollama::OllamaClient;
+use ollama_ai_agents_playground::infra::smtp::{DeliveryReceipt, MailTransport};
+use ollama_ai_agents_playground::playground::Playground;
+use serde::Serialize;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often `send` looks whether its email can leave the outbox
+const SEND_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Classify, draft and send email requests with a local Ollama model"
+)]
+struct Cli {
+    /// Print results as JSON for other tools
+    #[arg(long, global = true, conflicts_with = "format")]
+    json: bool,
+    /// Print results as json, yaml or msgpack
+    #[arg(long, global = true, value_parser = parse_format)]
+    format: Option<Format>,
+    /// Address book used to resolve recipients given by name
+    #[arg(long, global = true)]
+    contacts: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Classify a request, given as text or as the path of a voice note
+    Classify {
+        #[arg(required = true)]
+        request: Vec<String>,
+    },
+    /// Classify a request with the configured model and another one, and
+    /// show what differs
+    Compare {
+        /// Model to compare against
+        #[arg(long)]
+        model: String,
+        #[arg(required = true)]
+        request: Vec<String>,
+    },
+    /// Draft the email a request asks for
+    Compose {
+        #[arg(required = true)]
+        request: Vec<String>,
+    },
+    /// Draft the email a request asks for and send it, asking first when
+    /// the action gate wants a confirmation
+    Send {
+        #[arg(required = true)]
+        request: Vec<String>,
+        /// Show the drafts, events and handler calls it would make, and stop
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Classify the unread messages of the `[imap]` mailbox (needs the
+    /// imap feature)
+    Inbox,
+    /// Serve the web UI (needs the server feature)
+    Serve {
+        /// Address to listen on; `[server] bind` when unset
+        bind: Option<String>,
+    },
+}
+
+/// Human-readable lines on stdout, or the encoded result when a format
+/// was asked for; progress then goes to stderr
+struct Output {
+    format: Option<Format>,
+}
+
+impl Output {
+    fn status(&self, line: &str) {
+        match self.format {
+            Some(_) => eprintln!("{}", line),
+            None => println!("{}", line),
+        }
+    }
+
+    fn print<T: Serialize>(&self, value: &T, human: impl FnOnce(&T)) -> CliResult {
+        let Some(format) = self.format else {
+            human(value);
+            return Ok(());
+        };
+        let mut stdout = std::io::stdout();
+        stdout.write_all(&codec::encode(value, format)?)?;
+        if !format.is_binary() {
+            writeln!(stdout)?;
+        }
+        Ok(())
+    }
+}
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Create an assistante model customized for the user
+async fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+    let cli = Cli::parse();
+    let format = if cli.json {
+        Some(Format::Json)
+    } else {
+        cli.format
+    };
+    if let Some(format) = format.filter(|format| !format.is_enabled()) {
+        eprintln!("{}", CodecError::Disabled(format));
+        return ExitCode::from(2);
+    }
+    let output = Output { format };
+    let contacts = match &cli.contacts {
+        Some(path) => match UserContacts::load_from_file(path) {
+            Ok(contacts) => Arc::new(contacts),
+            Err(e) => {
+                eprintln!("Invalid contacts {}: {}", path, e);
+                return ExitCode::from(2);
+            }
+        },
+        None => Arc::new(UserContacts::new(Vec::new())),
+    };
+    let playground = Playground::builder().contacts(contacts.clone()).build();
 
-    // Create a tokio runtime for the async example
-    println!();
-    println!("🚀 Starting asynchronous processing...");
-    println!("🚀 Starting classifier...");
-    println!();
-    let input = "Envie um e-mail para Eva informando que não vou poder comparecer à reunião e que peço desculpas por avisar tão em cima da hora.";
-    let intent_classifier_agent = IntentClassifierAgent::new();
-    let result = intent_classifier_agent.process(IntentParam::new(input.to_string())).await;
-    match result {
-        Ok(classification_result) => {
-            println!();
-            println!("🚀 Classification done!");
-            println!("User intent: {}", classification_result.intent);
-            println!(
-                "User recipient: {}",
-                classification_result.params.recipient().unwrap()
-            );
-            println!();
+    let result = match cli.command {
+        Command::Classify { request } => classify(&output, request.join(" ")).await,
+        Command::Compare { model, request } => compare(&output, &model, request.join(" ")).await,
+        Command::Compose { request } => compose(&output, &playground, &request.join(" ")).await,
+        Command::Send {
+            request,
+            dry_run: true,
+        } => simulate(&output, &playground, &request.join(" ")).await,
+        Command::Send { request, .. } => {
+            send(&output, &playground, contacts, &request.join(" ")).await
         }
+        Command::Inbox => inbox(&output, &playground).await,
+        Command::Serve { bind } => serve(playground, contacts, bind).await,
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
-            println!("Failed: {}", e);
+            let message = match e.downcast_ref::<Error>() {
+                Some(e) => e.localized(),
+                None => e.to_string(),
+            };
+            eprintln!("Failed: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_format(name: &str) -> Result<Format, CodecError> {
+    name.parse()
+}
+
+async fn classify(output: &Output, request: String) -> CliResult {
+    let client = OllamaClient::new();
+    let capabilities = client.capabilities().await;
+    output.status(&format!(
+        "🔎 Model {}: {}",
+        client.model(),
+        capabilities.strategy()
+    ));
+    // A dictated request can be passed as an audio file path instead
+    let param = if is_audio(Path::new(&request)) {
+        output.status(&format!("🎙️ Transcribing {}...", request));
+        let voice_note = VoiceNoteInput::configured(request).map_err(Error::from)?;
+        IntentParam::from_source(&voice_note)
+            .await
+            .map_err(Error::from)?
+    } else {
+        IntentParam::from_source(&TextInput(request))
+            .await
+            .map_err(Error::from)?
+    };
+    let result = IntentClassifierAgent::from_config(Config::get())?
+        .process(param)
+        .await
+        .map_err(Error::from)?;
+    output.print(&result, |result: &ClassificationResult| {
+        println!("Intent: {}", result.intent);
+        if let Some(recipient) = result.params.recipient() {
+            println!("Recipient: {}", recipient);
+        }
+        if let Some(message) = result.params.message() {
+            println!("Message: {}", message);
+        }
+    })
+}
+
+async fn compare(output: &Output, model: &str, request: String) -> CliResult {
+    let config = Config::get();
+    let mut api = config.ollama.api.clone();
+    api.model = model.to_string();
+    output.status(&format!(
+        "🔎 Comparing {} with {}",
+        config.ollama.api.model, model
+    ));
+    let configured = IntentClassifierAgent::from_config(config)?
+        .process(IntentParam::trusted(request.clone()))
+        .await
+        .map_err(Error::from)?;
+    let other = IntentClassifierAgent::from_config(config)?
+        .with_api(&api)
+        .process(IntentParam::trusted(request))
+        .await
+        .map_err(Error::from)?;
+    output.print(&configured.diff(&other), |diff: &ClassificationDiff| {
+        print!("{}", diff)
+    })
+}
+
+async fn compose(output: &Output, playground: &Playground, request: &str) -> CliResult {
+    let result = playground.classify(request).await?;
+    let email = playground.draft(&result).await?;
+    output.print(&email, print_email)
+}
+
+async fn simulate(output: &Output, playground: &Playground, request: &str) -> CliResult {
+    let simulation = playground.simulate(request).await?;
+    output.print(&simulation, |simulation: &Simulation| {
+        println!("Plan: {}", simulation.plan.summary());
+        if simulation.needs_confirmation() {
+            println!("Would ask for confirmation first");
+        }
+        if simulation.effects.is_empty() {
+            println!("Nothing would be sent or created");
+        }
+        for effect in &simulation.effects {
+            match effect {
+                SimulatedEffect::Draft {
+                    to, subject, body, ..
+                } => println!("✉️ Email to {}: {}\n{}", to.join(", "), subject, body),
+                SimulatedEffect::CalendarEvent {
+                    title,
+                    attendees,
+                    start,
+                    ..
+                } => println!("📅 {} with {} at {}", title, attendees.join(", "), start),
+                SimulatedEffect::Webhook { intent, .. } => {
+                    println!("🔔 Handler for {}", intent)
+                }
+            }
+        }
+    })
+}
+
+/// Proposes the drafted email to the action gate, asks the user when the
+/// gate wants a confirmation, and sends it through the outbox once its
+/// undo window has passed
+async fn send(
+    output: &Output,
+    playground: &Playground,
+    contacts: Arc<UserContacts>,
+    request: &str,
+) -> CliResult {
+    let transport = mail_transport(contacts)?;
+    let result = playground.classify(request).await?;
+    let email = playground.draft(&result).await?;
+    let draft_id = match playground.send(&with_draft(result, &email)).await? {
+        Proposal::AutoConfirmed(confirmed) => confirmed.id(),
+        Proposal::NeedsConfirmation(plan) => {
+            let item = match approval_of_user(&plan, &email)? {
+                Approval::Declined => {
+                    playground.gate().reject(plan.id).map_err(Error::from)?;
+                    output.status("Not sent");
+                    return Ok(());
+                }
+                Approval::Confirmed => playground.confirm(plan.id).await?,
+                Approval::Elevated => playground.confirm_elevated(plan.id).await?,
+            };
+            item.draft_id
+        }
+    };
+    deliver(output, playground, transport.as_ref(), draft_id).await
+}
+
+/// `result` carrying the drafted subject and body, so the plan sends them
+fn with_draft(mut result: ClassificationResult, email: &ComposedEmail) -> ClassificationResult {
+    result.params = result
+        .params
+        .with_subject(&email.subject)
+        .with_message(&email.body);
+    if let Some(IntentDetails::SendEmail(details)) = &mut result.details {
+        details.subject = email.subject.clone();
+        details.message = email.body.clone();
+    }
+    result
+}
+
+/// What the user answered to a plan the gate wants confirmed
+enum Approval {
+    Declined,
+    Confirmed,
+    /// Accepted the anomalous recipients by typing the address
+    Elevated,
+}
+
+/// Shows the plan, the draft and what the gate found, and asks. A plan
+/// with anomalous recipients is approved as such only if the user also
+/// types the recipient's address.
+fn approval_of_user(plan: &ActionPlan, email: &ComposedEmail) -> std::io::Result<Approval> {
+    println!("Plan: {}", plan.summary());
+    for anomaly in &plan.anomalies {
+        println!("⚠️ {}", anomaly);
+    }
+    for violation in &plan.policy_flags {
+        println!("⚠️ {}", violation.detail);
+    }
+    println!();
+    print_email(email);
+    eprint!("Send this email? [y/N] ");
+    if !matches!(read_answer()?.as_str(), "y" | "Y" | "yes") {
+        return Ok(Approval::Declined);
+    }
+    if !plan.requires_elevated_approval() {
+        return Ok(Approval::Confirmed);
+    }
+    eprint!(
+        "The recipients were flagged above; type {} to send anyway: ",
+        email.recipient
+    );
+    Ok(if read_answer()?.eq_ignore_ascii_case(&email.recipient) {
+        Approval::Elevated
+    } else {
+        Approval::Confirmed
+    })
+}
+
+fn read_answer() -> std::io::Result<String> {
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+/// Waits for the email to leave the outbox and sends it through the
+/// dispatcher; Ctrl+C undoes it while it is still sending soon
+async fn deliver(
+    output: &Output,
+    playground: &Playground,
+    transport: &dyn MailTransport,
+    draft_id: u64,
+) -> CliResult {
+    let item = playground
+        .outbox()
+        .get(draft_id)
+        .ok_or(Error::from(ActionError::PlanNotFound(draft_id)))?;
+    output.status(&format!(
+        "⏳ Sending at {}; Ctrl+C to undo",
+        item.send_at.format("%Y-%m-%d %H:%M:%S")
+    ));
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                if playground.outbox().get(draft_id).map(|item| item.status)
+                    == Some(OutboxStatus::SendingSoon)
+                {
+                    playground.cancel(draft_id)?;
+                    output.status("Not sent");
+                    return Ok(());
+                }
+            }
+            _ = tokio::time::sleep(SEND_POLL_INTERVAL) => {}
+        }
+        for outcome in playground.dispatch_due(transport).await {
+            match outcome {
+                Ok(receipt) => {
+                    return output.print(&receipt, |receipt: &DeliveryReceipt| {
+                        println!("📤 Sent as <{}>: {}", receipt.message_id, receipt.response);
+                    });
+                }
+                // Queued again by the dispatcher; keep waiting
+                Err(e) if e.is_retryable() => output.status(&format!("⏳ {}", e.localized())),
+                Err(e) => return Err(e.into()),
+            }
         }
     }
+}
+
+#[cfg(feature = "server")]
+async fn serve(
+    playground: Playground,
+    contacts: Arc<UserContacts>,
+    bind: Option<String>,
+) -> CliResult {
+    let bind = bind.unwrap_or_else(|| Config::get().server.bind.clone());
+    println!("🌐 Playground UI on http://{}", bind);
+    use ollama_ai_agents_playground::server::{Server, serve};
 
+    let playground = Arc::new(playground);
+    let dispatcher = match mail_transport(contacts) {
+        Ok(transport) => Some(playground.spawn_dispatcher(transport)),
+        Err(e) => {
+            eprintln!("⚠️ Confirmed emails stay in the outbox: {}", e);
+            None
+        }
+    };
+    let server = Arc::new(Server::new(playground.clone()));
+    let shutdown = server.shutdown().clone();
+    if let Some(path) = &Config::get().actions.outbox_path {
+        let restored = playground.keep_outbox(Path::new(path), &shutdown)?;
+        if !restored.is_empty() {
+            println!("📬 {} emails back in the outbox", restored.len());
+        }
+    }
+    let stopping = tokio::spawn(async move { shutdown.on_ctrl_c().await });
+    serve(server, &bind).await?;
+    // Serving stopped on Ctrl+C; wait for the shutdown to finish its hooks
+    let _ = stopping.await;
+    if let Some(dispatcher) = dispatcher {
+        dispatcher.abort();
+    }
     Ok(())
 }
+
+#[cfg(not(feature = "server"))]
+async fn serve(_: Playground, _: Arc<UserContacts>, _: Option<String>) -> CliResult {
+    Err("serving needs the server feature".into())
+}
+
+/// An unread message and what it was classified as
+#[cfg(feature = "imap")]
+#[derive(Serialize)]
+struct ClassifiedEmail {
+    email: ollama_ai_agents_playground::infra::email::IncomingEmail,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<ClassificationResult>,
+    /// Why it was not classified, e.g. quarantined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Classifies the unread mail; a message that fails is reported and the
+/// others are still classified
+#[cfg(feature = "imap")]
+async fn inbox(output: &Output, playground: &Playground) -> CliResult {
+    use ollama_ai_agents_playground::infra::imap::InboxReader;
+
+    let emails = InboxReader::configured().fetch_unread().await?;
+    output.status(&format!("📥 {} unread", emails.len()));
+    let mut classified = Vec::new();
+    for email in emails {
+        let (result, error) = match playground.classify_email(&email).await {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e.localized())),
+        };
+        classified.push(ClassifiedEmail {
+            email,
+            result,
+            error,
+        });
+    }
+    output.print(&classified, |classified: &Vec<ClassifiedEmail>| {
+        for entry in classified {
+            let from = entry
+                .email
+                .from
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            match (&entry.result, &entry.error) {
+                (Some(result), _) => {
+                    println!("{} — {}: {}", from, entry.email.subject, result.intent)
+                }
+                (None, error) => println!(
+                    "{} — {}: ⚠️ {}",
+                    from,
+                    entry.email.subject,
+                    error.as_deref().unwrap_or_default()
+                ),
+            }
+        }
+    })
+}
+
+#[cfg(not(feature = "imap"))]
+async fn inbox(_: &Output, _: &Playground) -> CliResult {
+    Err("reading the inbox needs the imap feature".into())
+}
+
+/// Where the outbox dispatcher sends confirmed emails
+#[cfg(feature = "smtp")]
+fn mail_transport(contacts: Arc<UserContacts>) -> Result<Arc<dyn MailTransport>, Error> {
+    use ollama_ai_agents_playground::infra::smtp::AccountSenders;
+
+    Ok(Arc::new(
+        AccountSenders::configured()
+            .map_err(Error::from)?
+            .with_contacts(contacts),
+    ))
+}
+
+#[cfg(not(feature = "smtp"))]
+fn mail_transport(_: Arc<UserContacts>) -> Result<Arc<dyn MailTransport>, &'static str> {
+    Err("sending needs the smtp feature; --dry-run shows what would be sent")
+}
+
+fn print_email(email: &ComposedEmail) {
+    println!("To: {}", email.recipient);
+    println!("Subject: {}", email.subject);
+    println!();
+    println!("{}", email.body);
+}
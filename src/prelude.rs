@@ -0,0 +1,12 @@
+//! The types most embedders need, importable with
+//! `use ollama_ai_agents_playground::prelude::*;`
+
+pub use crate::action::{ActionGate, ActionPlan, ConfirmedPlan, Outbox, Proposal};
+pub use crate::agent::classifier::{IntentClassifierAgent, IntentParam, Params};
+pub use crate::agent::{Agent, AgentError, ClassificationResult, Intent};
+pub use crate::error::{Error, ErrorClass, Result};
+pub use crate::i18n::Locale;
+pub use crate::infra::contacts::{Contact, UserContacts};
+pub use crate::infra::email::{EmailAddress, MimeMessage};
+pub use crate::infra::input::{InputSource, TextInput, VoiceNoteInput};
+pub use crate::playground::{Playground, PlaygroundBuilder};
@@ -0,0 +1,237 @@
+//! Dependency-free renderer for the template subset described in `engine`.
+
+use serde_json::Value;
+
+use crate::prompt::PromptError;
+
+pub fn render(source: &str, context: &Value) -> Result<String, PromptError> {
+    let tokens = tokenize(source)?;
+    let mut pos = 0;
+    let (nodes, terminator) = parse(&tokens, &mut pos)?;
+    if let Some(tag) = terminator {
+        return Err(PromptError::Syntax(format!("unexpected '{{% {} %}}'", tag)));
+    }
+    let mut scope = Scope {
+        root: context,
+        locals: Vec::new(),
+    };
+    let mut output = String::new();
+    render_nodes(&nodes, &mut scope, &mut output)?;
+    Ok(output)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Var(String),
+    Tag(String),
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Var(String),
+    If {
+        condition: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        variable: String,
+        iterable: String,
+        body: Vec<Node>,
+    },
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, PromptError> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = [rest.find("{{"), rest.find("{%")]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+        let is_var = rest[start..].starts_with("{{");
+        let close = if is_var { "}}" } else { "%}" };
+        let body_start = start + 2;
+        let end = rest[body_start..]
+            .find(close)
+            .map(|offset| body_start + offset)
+            .ok_or_else(|| {
+                PromptError::Syntax(format!("unclosed '{}'", &rest[start..body_start]))
+            })?;
+        let inner = rest[body_start..end].trim().to_string();
+        rest = &rest[end + 2..];
+
+        if is_var {
+            tokens.push(Token::Var(inner));
+        } else {
+            tokens.push(Token::Tag(inner));
+            rest = rest
+                .strip_prefix("\r\n")
+                .or_else(|| rest.strip_prefix('\n'))
+                .unwrap_or(rest);
+        }
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+/// Parses until the end of input or a closing tag, which is returned
+fn parse(tokens: &[Token], pos: &mut usize) -> Result<(Vec<Node>, Option<String>), PromptError> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.get(*pos) {
+        *pos += 1;
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.clone())),
+            Token::Var(path) => nodes.push(Node::Var(path.clone())),
+            Token::Tag(tag) => {
+                let words: Vec<&str> = tag.split_whitespace().collect();
+                match words.as_slice() {
+                    ["if", condition] | ["if", "not", condition] => {
+                        let negate = words.len() == 3;
+                        let (then, end) = parse(tokens, pos)?;
+                        let otherwise = match end.as_deref() {
+                            Some("endif") => Vec::new(),
+                            Some("else") => {
+                                let (otherwise, end) = parse(tokens, pos)?;
+                                expect_end(end, "endif")?;
+                                otherwise
+                            }
+                            _ => return Err(PromptError::Syntax(format!("unclosed '{}'", tag))),
+                        };
+                        nodes.push(Node::If {
+                            condition: condition.to_string(),
+                            negate,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    ["for", variable, "in", iterable] => {
+                        let (body, end) = parse(tokens, pos)?;
+                        expect_end(end, "endfor")?;
+                        nodes.push(Node::For {
+                            variable: variable.to_string(),
+                            iterable: iterable.to_string(),
+                            body,
+                        });
+                    }
+                    ["else"] | ["endif"] | ["endfor"] => return Ok((nodes, Some(tag.clone()))),
+                    _ => return Err(PromptError::Syntax(format!("unsupported tag '{}'", tag))),
+                }
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn expect_end(end: Option<String>, expected: &str) -> Result<(), PromptError> {
+    match end {
+        Some(tag) if tag == expected => Ok(()),
+        _ => Err(PromptError::Syntax(format!(
+            "missing '{{% {} %}}'",
+            expected
+        ))),
+    }
+}
+
+struct Scope<'a> {
+    root: &'a Value,
+    locals: Vec<(String, Value)>,
+}
+
+impl Scope<'_> {
+    fn lookup(&self, path: &str) -> Result<&Value, PromptError> {
+        let mut segments = path.split('.');
+        let first = segments.next().unwrap_or_default();
+        let mut value = self
+            .locals
+            .iter()
+            .rev()
+            .find(|(name, _)| name == first)
+            .map(|(_, value)| value)
+            .or_else(|| self.root.get(first))
+            .ok_or_else(|| PromptError::UndefinedVariable(path.to_string()))?;
+        for segment in segments {
+            value = value
+                .get(segment)
+                .ok_or_else(|| PromptError::UndefinedVariable(path.to_string()))?;
+        }
+        Ok(value)
+    }
+}
+
+fn render_nodes(nodes: &[Node], scope: &mut Scope, output: &mut String) -> Result<(), PromptError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Var(path) => output.push_str(&display(scope.lookup(path)?)),
+            Node::If {
+                condition,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let branch = if is_truthy(scope.lookup(condition)?) != *negate {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, scope, output)?;
+            }
+            Node::For {
+                variable,
+                iterable,
+                body,
+            } => {
+                let items = match scope.lookup(iterable)? {
+                    Value::Array(items) => items.clone(),
+                    _ => {
+                        return Err(PromptError::Render(format!("'{}' is not a list", iterable)));
+                    }
+                };
+                let len = items.len();
+                for (index, item) in items.into_iter().enumerate() {
+                    let meta = serde_json::json!({
+                        "index": index + 1,
+                        "first": index == 0,
+                        "last": index + 1 == len,
+                    });
+                    scope.locals.push(("loop".to_string(), meta));
+                    scope.locals.push((variable.clone(), item));
+                    let rendered = render_nodes(body, scope, output);
+                    scope.locals.truncate(scope.locals.len() - 2);
+                    rendered?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
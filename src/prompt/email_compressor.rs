@@ -0,0 +1,228 @@
+use crate::config::CompressionConfig;
+
+/// Result of compressing an email body
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressedEmail {
+    pub text: String,
+    pub original_chars: usize,
+}
+
+impl CompressedEmail {
+    pub fn removed_chars(&self) -> usize {
+        self.original_chars
+            .saturating_sub(self.text.chars().count())
+    }
+}
+
+/// Strips quoted reply chains, signatures and legal footers from email
+/// text before it is placed into a prompt.
+///
+/// Marker rules are matched case-insensitively against whole trimmed lines
+/// and may contain `*` wildcards, e.g. `On * wrote:`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailCompressor {
+    strip_quoted: bool,
+    reply_headers: Vec<String>,
+    signature_markers: Vec<String>,
+    footer_markers: Vec<String>,
+    drop_lines: Vec<String>,
+}
+
+impl EmailCompressor {
+    pub fn from_config(config: &CompressionConfig) -> Self {
+        Self {
+            strip_quoted: config.strip_quoted,
+            reply_headers: config.reply_headers.clone(),
+            signature_markers: config.signature_markers.clone(),
+            footer_markers: config.footer_markers.clone(),
+            drop_lines: config.drop_lines.clone(),
+        }
+    }
+
+    pub fn compress(&self, body: &str) -> CompressedEmail {
+        let mut kept: Vec<&str> = Vec::new();
+
+        for line in body.lines() {
+            let trimmed = line.trim();
+            // Everything after a reply header, signature delimiter or legal
+            // footer is older thread content or boilerplate. A reply header
+            // before any content is a forwarded message and is kept.
+            let has_content = kept.iter().any(|l| !l.trim().is_empty());
+            if (has_content && self.matches_any(&self.reply_headers, trimmed))
+                || self.matches_any(&self.footer_markers, trimmed)
+                || self.is_signature_marker(line)
+            {
+                break;
+            }
+            if self.strip_quoted && trimmed.starts_with('>') {
+                continue;
+            }
+            if self.matches_any(&self.drop_lines, trimmed) {
+                continue;
+            }
+            kept.push(line.trim_end());
+        }
+
+        CompressedEmail {
+            text: collapse_blank_lines(&kept),
+            original_chars: body.chars().count(),
+        }
+    }
+
+    fn is_signature_marker(&self, line: &str) -> bool {
+        // RFC 3676 delimiter is exactly "-- "; many clients drop the space
+        let line = line.trim_end_matches(['\r', '\n']);
+        line == "-- " || line == "--" || self.matches_any(&self.signature_markers, line.trim())
+    }
+
+    fn matches_any(&self, patterns: &[String], line: &str) -> bool {
+        !line.is_empty() && patterns.iter().any(|p| glob_match(p, line))
+    }
+}
+
+impl Default for EmailCompressor {
+    fn default() -> Self {
+        Self::from_config(&CompressionConfig::default())
+    }
+}
+
+/// Case-insensitive match where `*` stands for any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return text == pattern;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || !text[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn collapse_blank_lines(lines: &[&str]) -> String {
+    let mut text = String::new();
+    let mut previous_blank = true;
+    for line in lines {
+        let blank = line.trim().is_empty();
+        if blank && previous_blank {
+            continue;
+        }
+        text.push_str(line);
+        text.push('\n');
+        previous_blank = blank;
+    }
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(
+            "On * wrote:",
+            "On Mon, 3 Jun 2024, Eva <eva@company.com> wrote:"
+        ));
+        assert!(glob_match(
+            "em * escreveu:",
+            "Em seg., 3 de jun. de 2024, Eva escreveu:"
+        ));
+        assert!(glob_match("sent from my *", "Sent from my iPhone"));
+        assert!(!glob_match("On * wrote:", "On second thought, call me"));
+        assert!(glob_match(
+            "--*original message*--",
+            "-----Original Message-----"
+        ));
+    }
+
+    #[test]
+    fn test_strips_quoted_reply_chain() {
+        let body = "Sure, Thursday works.\n\nOn Mon, 3 Jun 2024, Eva <eva@company.com> wrote:\n> Can we meet Thursday?\n> Thanks";
+
+        let compressed = EmailCompressor::default().compress(body);
+
+        assert_eq!(compressed.text, "Sure, Thursday works.");
+        assert!(compressed.removed_chars() > 0);
+    }
+
+    #[test]
+    fn test_strips_inline_quotes() {
+        let body = "> Can we meet?\nYes, at 3pm.\n> Where?\nRoom 2.";
+
+        let compressed = EmailCompressor::default().compress(body);
+
+        assert_eq!(compressed.text, "Yes, at 3pm.\nRoom 2.");
+    }
+
+    #[test]
+    fn test_strips_signature_and_mobile_footer() {
+        let body = "Vou me atrasar 10 minutos.\n\nEnviado do meu iPhone";
+        assert_eq!(
+            EmailCompressor::default().compress(body).text,
+            "Vou me atrasar 10 minutos."
+        );
+
+        let body = "See you then.\n-- \nJohn Doe\nSales Director\n+1 555 0100";
+        assert_eq!(
+            EmailCompressor::default().compress(body).text,
+            "See you then."
+        );
+    }
+
+    #[test]
+    fn test_strips_legal_footer() {
+        let body = "Please review the attached contract.\n\nCONFIDENTIALITY NOTICE: This e-mail is intended only for the addressee.\nIf you received it by mistake, delete it.";
+
+        assert_eq!(
+            EmailCompressor::default().compress(body).text,
+            "Please review the attached contract."
+        );
+    }
+
+    #[test]
+    fn test_outlook_headers() {
+        let body =
+            "Approved.\n\n-----Original Message-----\nFrom: Eva\nSent: Monday\nSubject: Budget";
+
+        assert_eq!(EmailCompressor::default().compress(body).text, "Approved.");
+    }
+
+    #[test]
+    fn test_leading_header_is_kept() {
+        let body = "From: Eva\nSubject: Budget\nPlease approve.";
+
+        assert_eq!(EmailCompressor::default().compress(body).text, body);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let config = CompressionConfig {
+            strip_quoted: false,
+            footer_markers: vec!["ACME Corp *".to_string()],
+            ..Default::default()
+        };
+        let compressor = EmailCompressor::from_config(&config);
+
+        let compressed = compressor.compress("> kept quote\nBody\nACME Corp - all rights reserved");
+
+        assert_eq!(compressed.text, "> kept quote\nBody");
+    }
+
+    #[test]
+    fn test_plain_text_is_unchanged() {
+        let body = "Avise a Eva que vou me atrasar";
+
+        assert_eq!(EmailCompressor::default().compress(body).text, body);
+    }
+}
@@ -0,0 +1,115 @@
+//! Rendering backend for prompt templates.
+//!
+//! Templates use a Jinja subset: `{{ a.b }}`, `{% if [not] x %}`/`{% else %}`/
+//! `{% endif %}` and `{% for x in xs %}`/`{% endfor %}` with `loop.index`,
+//! `loop.first` and `loop.last`. A newline right after a block tag is
+//! dropped and undefined variables are errors. With the `minijinja` feature
+//! the same templates are rendered by minijinja, configured to match.
+
+use serde_json::Value;
+
+use crate::prompt::PromptError;
+
+#[cfg(feature = "minijinja")]
+pub fn render(name: &str, source: &str, context: &Value) -> Result<String, PromptError> {
+    let mut env = minijinja::Environment::new();
+    env.set_trim_blocks(true);
+    env.set_keep_trailing_newline(true);
+    env.set_undefined_behavior(minijinja::UndefinedBehavior::Strict);
+    env.set_auto_escape_callback(|_| minijinja::AutoEscape::None);
+    env.add_template(name, source)
+        .map_err(|e| PromptError::Syntax(e.to_string()))?;
+    let template = env
+        .get_template(name)
+        .map_err(|e| PromptError::Syntax(e.to_string()))?;
+    template.render(context).map_err(|e| match e.kind() {
+        minijinja::ErrorKind::UndefinedError => PromptError::UndefinedVariable(e.to_string()),
+        _ => PromptError::Render(e.to_string()),
+    })
+}
+
+#[cfg(not(feature = "minijinja"))]
+pub fn render(_name: &str, source: &str, context: &Value) -> Result<String, PromptError> {
+    crate::prompt::builtin_engine::render(source, context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_variables_and_paths() {
+        let context = json!({"user": {"name": "Eva", "age": 30}});
+
+        assert_eq!(
+            render("t", "{{ user.name }} is {{user.age}}", &context).unwrap(),
+            "Eva is 30"
+        );
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let source = "{% if urgent %}URGENT {% endif %}{% if not cc %}no cc{% else %}cc{% endif %}";
+
+        assert_eq!(
+            render("t", source, &json!({"urgent": true, "cc": []})).unwrap(),
+            "URGENT no cc"
+        );
+        assert_eq!(
+            render("t", source, &json!({"urgent": false, "cc": ["a"]})).unwrap(),
+            "cc"
+        );
+    }
+
+    #[test]
+    fn test_loops_with_loop_metadata() {
+        let source = "{% for i in items %}{{ loop.index }}:{{ i }}{% if not loop.last %}, {% endif %}{% endfor %}";
+
+        assert_eq!(
+            render("t", source, &json!({"items": ["a", "b", "c"]})).unwrap(),
+            "1:a, 2:b, 3:c"
+        );
+    }
+
+    #[test]
+    fn test_newline_after_block_tag_is_trimmed() {
+        let source = "{% for i in items %}\n{{ i }}\n{% endfor %}\n";
+
+        assert_eq!(
+            render("t", source, &json!({"items": [1, 2]})).unwrap(),
+            "1\n2\n"
+        );
+    }
+
+    #[test]
+    fn test_json_braces_are_literal() {
+        let source = "Output: {\"intent\":\"\",\"params\":{\"message\":\"\"}}";
+
+        assert_eq!(render("t", source, &json!({})).unwrap(), source);
+    }
+
+    #[test]
+    fn test_undefined_variable_is_an_error() {
+        assert!(matches!(
+            render("t", "{{ missing }}", &json!({})),
+            Err(PromptError::UndefinedVariable(_))
+        ));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert!(matches!(
+            render("t", "{% if x %}open", &json!({"x": true})),
+            Err(PromptError::Syntax(_))
+        ));
+        assert!(matches!(
+            render("t", "{{ x", &json!({"x": 1})),
+            Err(PromptError::Syntax(_))
+        ));
+        assert!(matches!(
+            render("t", "{% endfor %}", &json!({})),
+            Err(PromptError::Syntax(_))
+        ));
+    }
+}
@@ -0,0 +1,367 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::Path;
+
+use crate::agent::{
+    Agent, AgentError, ClassificationResult, Intent,
+    classifier::{ClassificationDiff, IntentParam},
+};
+use crate::i18n::{Locale, detect_locale};
+use crate::prompt::PromptError;
+
+/// Breakdown key for samples whose language is neither tagged nor detected
+pub const UNKNOWN_LANGUAGE: &str = "unknown";
+
+/// One utterance with the classification we expect for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalSample {
+    pub input: String,
+    pub intent: Intent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+    /// Language of the input; detected from the text when not tagged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Locale>,
+}
+
+impl EvalSample {
+    pub fn new(input: &str, intent: Intent) -> Self {
+        Self {
+            input: input.to_string(),
+            intent,
+            recipient: None,
+            language: None,
+        }
+    }
+
+    pub fn with_recipient(mut self, recipient: &str) -> Self {
+        self.recipient = Some(recipient.to_string());
+        self
+    }
+
+    pub fn with_language(mut self, language: Locale) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Tagged language, else the detected one
+    pub fn language(&self) -> Option<Locale> {
+        self.language.or_else(|| detect_locale(&self.input))
+    }
+
+    /// True if the result has the expected intent and, when one is
+    /// expected, the same recipient (ignoring case)
+    pub fn is_matched_by(&self, result: &ClassificationResult) -> bool {
+        result.intent == self.intent
+            && self.recipient.as_deref().is_none_or(|expected| {
+                result
+                    .params
+                    .recipient()
+                    .is_some_and(|actual| actual.trim().eq_ignore_ascii_case(expected.trim()))
+            })
+    }
+}
+
+/// Labelled samples stored as JSON lines
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EvalSet {
+    samples: Vec<EvalSample>,
+}
+
+impl EvalSet {
+    pub fn new(samples: Vec<EvalSample>) -> Self {
+        Self { samples }
+    }
+
+    pub fn load_jsonl(path: &Path) -> Result<Self, PromptError> {
+        let content = fs::read_to_string(path).map_err(|e| PromptError::Io(e.to_string()))?;
+        Self::from_jsonl(&content)
+    }
+
+    pub fn from_jsonl(content: &str) -> Result<Self, PromptError> {
+        let samples = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|e| PromptError::InvalidContext(e.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { samples })
+    }
+
+    pub fn samples(&self) -> &[EvalSample] {
+        &self.samples
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Classifies every sample in order and scores the answers
+    pub async fn run<A>(&self, agent: &A) -> EvalReport
+    where
+        A: Agent<IntentParam, ClassificationResult>,
+    {
+        let mut report = EvalReport::default();
+        for sample in &self.samples {
+            let result = agent
+                .process(IntentParam::trusted(sample.input.clone()))
+                .await;
+            report.record(sample, &result);
+        }
+        report
+    }
+}
+
+/// Counts for one language
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LanguageStats {
+    pub total: usize,
+    pub correct: usize,
+    /// Samples where the agent failed instead of answering
+    pub errors: usize,
+}
+
+impl LanguageStats {
+    pub fn accuracy(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.total as f64
+    }
+}
+
+/// A miss, kept so it can be inspected
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalMiss {
+    pub input: String,
+    pub language: String,
+    pub expected: Intent,
+    /// The intent given, or the error when the agent failed
+    pub actual: String,
+}
+
+/// What the agent answered for one sample; `None` when it failed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalAnswer {
+    pub input: String,
+    pub result: Option<ClassificationResult>,
+}
+
+/// A sample whose answer differs from the baseline run's
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalChange {
+    pub input: String,
+    pub diff: ClassificationDiff,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub overall: LanguageStats,
+    /// Keyed by language tag (`en`, `pt-BR`) or `unknown`
+    pub by_language: BTreeMap<String, LanguageStats>,
+    pub misses: Vec<EvalMiss>,
+    /// Every answer in sample order, to compare runs with `changes_since`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<EvalAnswer>,
+}
+
+impl EvalReport {
+    pub fn record(
+        &mut self,
+        sample: &EvalSample,
+        result: &Result<ClassificationResult, AgentError>,
+    ) {
+        let language = sample
+            .language()
+            .map_or(UNKNOWN_LANGUAGE, |locale| locale.tag())
+            .to_string();
+        let correct = matches!(result, Ok(r) if sample.is_matched_by(r));
+
+        for stats in [
+            &mut self.overall,
+            self.by_language.entry(language.clone()).or_default(),
+        ] {
+            stats.total += 1;
+            stats.correct += usize::from(correct);
+            stats.errors += usize::from(result.is_err());
+        }
+        if !correct {
+            self.misses.push(EvalMiss {
+                input: sample.input.clone(),
+                language,
+                expected: sample.intent.clone(),
+                actual: match result {
+                    Ok(r) => r.intent.to_string(),
+                    Err(e) => e.to_string(),
+                },
+            });
+        }
+        self.answers.push(EvalAnswer {
+            input: sample.input.clone(),
+            result: result.as_ref().ok().cloned(),
+        });
+    }
+
+    /// Answers that differ from `baseline`'s for the same input. Samples
+    /// one of the runs failed on, or that only one run has, are skipped;
+    /// the accuracy counts already show those.
+    pub fn changes_since(&self, baseline: &EvalReport) -> Vec<EvalChange> {
+        let before: BTreeMap<&str, &ClassificationResult> = baseline
+            .answers
+            .iter()
+            .filter_map(|answer| Some((answer.input.as_str(), answer.result.as_ref()?)))
+            .collect();
+        self.answers
+            .iter()
+            .filter_map(|answer| {
+                let diff = before
+                    .get(answer.input.as_str())?
+                    .diff(answer.result.as_ref()?);
+                (!diff.is_empty()).then(|| EvalChange {
+                    input: answer.input.clone(),
+                    diff,
+                })
+            })
+            .collect()
+    }
+
+    pub fn accuracy(&self, language: Locale) -> Option<f64> {
+        self.by_language
+            .get(language.tag())
+            .map(LanguageStats::accuracy)
+    }
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = format!(
+            "{:<10} {:>6} {:>8} {:>7} {:>9}\n",
+            "language", "total", "correct", "errors", "accuracy"
+        );
+        let rows = self
+            .by_language
+            .iter()
+            .map(|(language, stats)| (language.as_str(), stats))
+            .chain([("all", &self.overall)]);
+        for (language, stats) in rows {
+            let _ = writeln!(
+                table,
+                "{:<10} {:>6} {:>8} {:>7} {:>8.1}%",
+                language,
+                stats.total,
+                stats.correct,
+                stats.errors,
+                stats.accuracy() * 100.0
+            );
+        }
+        write!(f, "{}", table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::Params;
+
+    fn result(intent: Intent, recipient: &str) -> Result<ClassificationResult, AgentError> {
+        Ok(ClassificationResult::new(
+            intent,
+            Params::with_values(recipient.to_string(), String::new()),
+        ))
+    }
+
+    #[test]
+    fn test_load_bundled_dataset() {
+        let set = EvalSet::load_jsonl(Path::new("spec/eval/classify_intent.jsonl")).unwrap();
+
+        assert!(
+            set.samples()
+                .iter()
+                .any(|s| s.language() == Some(Locale::En))
+        );
+        assert!(
+            set.samples()
+                .iter()
+                .any(|s| s.language() == Some(Locale::PtBr))
+        );
+    }
+
+    #[test]
+    fn test_language_is_tagged_or_detected() {
+        let tagged = EvalSample::new("Eva, 15h", Intent::NoAction).with_language(Locale::PtBr);
+        let detected = EvalSample::new("Avise a Eva que vou atrasar", Intent::SendEmail);
+
+        assert_eq!(tagged.language(), Some(Locale::PtBr));
+        assert_eq!(detected.language(), Some(Locale::PtBr));
+        assert_eq!(EvalSample::new("ok", Intent::NoAction).language(), None);
+    }
+
+    #[test]
+    fn test_per_language_breakdown() {
+        let mut report = EvalReport::default();
+        let english = EvalSample::new("Send an email to Carlos about the delay", Intent::SendEmail)
+            .with_recipient("Carlos");
+        let portuguese =
+            EvalSample::new("Avise a Eva que vou atrasar", Intent::SendEmail).with_recipient("Eva");
+
+        report.record(&english, &result(Intent::SendEmail, "carlos"));
+        report.record(&portuguese, &result(Intent::NoAction, "Eva"));
+        report.record(&portuguese, &result(Intent::SendEmail, "Sofia"));
+        report.record(
+            &portuguese,
+            &Err(AgentError::NetworkError("refused".to_string())),
+        );
+
+        assert_eq!(report.accuracy(Locale::En), Some(1.0));
+        assert_eq!(report.accuracy(Locale::PtBr), Some(0.0));
+        assert_eq!(report.by_language["pt-BR"].errors, 1);
+        assert_eq!(report.overall.total, 4);
+        assert_eq!(report.misses.len(), 3);
+        assert_eq!(report.misses[0].actual, "no_action");
+    }
+
+    #[test]
+    fn test_report_table() {
+        let mut report = EvalReport::default();
+        report.record(
+            &EvalSample::new("Send an email to Carlos", Intent::SendEmail),
+            &result(Intent::SendEmail, "Carlos"),
+        );
+
+        let table = report.to_string();
+
+        assert!(table.starts_with("language"));
+        assert!(table.contains("en              1        1       0    100.0%"));
+        assert!(table.lines().last().unwrap().starts_with("all"));
+    }
+
+    #[test]
+    fn test_changes_since_baseline() {
+        let sample = EvalSample::new("Tell Eva I'm late", Intent::SendEmail);
+        let unchanged = EvalSample::new("Tell Bruno I'm late", Intent::SendEmail);
+        let mut baseline = EvalReport::default();
+        baseline.record(&sample, &result(Intent::SendEmail, "Eva"));
+        baseline.record(&unchanged, &result(Intent::SendEmail, "Bruno"));
+        let baseline: EvalReport =
+            serde_json::from_str(&serde_json::to_string(&baseline).unwrap()).unwrap();
+        let mut report = EvalReport::default();
+        report.record(&sample, &result(Intent::ScheduleMeeting, "Eva"));
+        report.record(&unchanged, &result(Intent::SendEmail, "Bruno"));
+
+        let changes = report.changes_since(&baseline);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].input, "Tell Eva I'm late");
+        assert_eq!(
+            changes[0].diff.to_string(),
+            "intent: send_email -> schedule_meeting\n"
+        );
+    }
+}
@@ -0,0 +1,28 @@
+#[cfg(not(feature = "minijinja"))]
+mod builtin_engine;
+pub mod email_compressor;
+pub mod engine;
+pub mod eval_set;
+pub mod prompt_error;
+pub mod prompt_lint;
+pub mod prompt_registry;
+pub mod prompt_template;
+pub mod reasoning_mode;
+pub mod tokenizer;
+pub mod training_export;
+
+pub use email_compressor::{CompressedEmail, EmailCompressor};
+pub use eval_set::{
+    EvalAnswer, EvalChange, EvalMiss, EvalReport, EvalSample, EvalSet, LanguageStats,
+};
+pub use prompt_error::PromptError;
+pub use prompt_lint::{LintIssue, LintReport, PromptLinter, estimate_tokens, truncate_to_tokens};
+pub use prompt_registry::{PromptRegistry, PromptSelection, PromptVersion, localized_name};
+pub use prompt_template::PromptTemplate;
+pub use reasoning_mode::ReasoningMode;
+#[cfg(feature = "hf-tokenizers")]
+pub use tokenizer::HfTokenizer;
+#[cfg(feature = "tiktoken")]
+pub use tokenizer::TiktokenTokenizer;
+pub use tokenizer::{CharEstimate, Tokenizer, TokenizerError, tokenizer_for, tokenizer_from_spec};
+pub use training_export::{ExportFormat, TrainingPair, TrainingSet};
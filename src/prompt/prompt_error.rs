@@ -0,0 +1,29 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromptError {
+    Io(String),
+    Syntax(String),
+    UndefinedVariable(String),
+    InvalidContext(String),
+    Render(String),
+    UnknownPrompt(String),
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromptError::Io(msg) => write!(f, "Failed to read template: {}", msg),
+            PromptError::Syntax(msg) => write!(f, "Template syntax error: {}", msg),
+            PromptError::UndefinedVariable(name) => {
+                write!(f, "Undefined template variable '{}'", name)
+            }
+            PromptError::InvalidContext(msg) => write!(f, "Invalid template context: {}", msg),
+            PromptError::Render(msg) => write!(f, "Template render error: {}", msg),
+            PromptError::UnknownPrompt(id) => write!(f, "Unknown prompt '{}'", id),
+        }
+    }
+}
+
+impl Error for PromptError {}
@@ -0,0 +1,314 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::prompt::{CharEstimate, PromptTemplate, Tokenizer};
+
+/// Chat-format control tokens that must never appear in a template
+const CONTROL_TOKENS: &[&str] = &[
+    "<|im_start|>",
+    "<|im_end|>",
+    "<|endoftext|>",
+    "<|eot_id|>",
+    "[INST]",
+    "[/INST]",
+    "<<SYS>>",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintIssue {
+    RenderFailed(String),
+    MissingVariable(String),
+    UnusedVariable(String),
+    ForbiddenContent(String),
+    TooManyTokens { tokens: usize, max: usize },
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintIssue::RenderFailed(msg) => write!(f, "render failed: {}", msg),
+            LintIssue::MissingVariable(name) => {
+                write!(f, "placeholder '{}' has no value in the sample", name)
+            }
+            LintIssue::UnusedVariable(name) => {
+                write!(f, "variable '{}' is never used by the template", name)
+            }
+            LintIssue::ForbiddenContent(content) => {
+                write!(f, "forbidden content '{}'", content)
+            }
+            LintIssue::TooManyTokens { tokens, max } => {
+                write!(f, "~{} tokens, limit is {}", tokens, max)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintReport {
+    /// The dry-rendered prompt, if rendering succeeded
+    pub rendered: Option<String>,
+    pub token_estimate: usize,
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PromptLinter {
+    forbidden: Vec<String>,
+    max_tokens: Option<usize>,
+    tokenizer: Arc<dyn Tokenizer>,
+}
+
+impl PromptLinter {
+    pub fn new(forbidden: Vec<String>, max_tokens: Option<usize>) -> Self {
+        Self {
+            forbidden,
+            max_tokens,
+            tokenizer: Arc::new(CharEstimate),
+        }
+    }
+
+    pub fn with_forbidden(mut self, content: &str) -> Self {
+        self.forbidden.push(content.to_string());
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Counts tokens like the target model, e.g. `tokenizer_for(model)`
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Renders the template with sample data and reports every problem found
+    pub fn lint<C: Serialize>(&self, template: &PromptTemplate, sample: &C) -> LintReport {
+        let mut issues = Vec::new();
+        let sample = serde_json::to_value(sample).unwrap_or(Value::Null);
+
+        let referenced = referenced_variables(template.source());
+        let provided: BTreeSet<String> = match &sample {
+            Value::Object(map) => map.keys().cloned().collect(),
+            _ => BTreeSet::new(),
+        };
+        issues.extend(
+            referenced
+                .difference(&provided)
+                .map(|name| LintIssue::MissingVariable(name.clone())),
+        );
+        issues.extend(
+            provided
+                .difference(&referenced)
+                .map(|name| LintIssue::UnusedVariable(name.clone())),
+        );
+
+        for content in &self.forbidden {
+            if template
+                .source()
+                .to_lowercase()
+                .contains(&content.to_lowercase())
+            {
+                issues.push(LintIssue::ForbiddenContent(content.clone()));
+            }
+        }
+
+        let rendered = match template.render(&sample) {
+            Ok(rendered) => Some(rendered),
+            Err(e) => {
+                // Missing variables are already reported individually
+                if referenced.is_subset(&provided) {
+                    issues.push(LintIssue::RenderFailed(e.to_string()));
+                }
+                None
+            }
+        };
+
+        let token_estimate = self
+            .tokenizer
+            .count(rendered.as_deref().unwrap_or(template.source()));
+        if let Some(max) = self.max_tokens
+            && token_estimate > max
+        {
+            issues.push(LintIssue::TooManyTokens {
+                tokens: token_estimate,
+                max,
+            });
+        }
+
+        LintReport {
+            rendered,
+            token_estimate,
+            issues,
+        }
+    }
+}
+
+impl Default for PromptLinter {
+    fn default() -> Self {
+        Self::new(CONTROL_TOKENS.iter().map(|t| t.to_string()).collect(), None)
+    }
+}
+
+impl PromptTemplate {
+    /// Lints with the default rules (chat control tokens are forbidden)
+    pub fn lint<C: Serialize>(&self, sample: &C) -> LintReport {
+        PromptLinter::default().lint(self, sample)
+    }
+}
+
+/// Tokens in `text` by `CharEstimate`
+pub fn estimate_tokens(text: &str) -> usize {
+    CharEstimate.count(text)
+}
+
+/// Longest prefix of `text` within `max_tokens` by `estimate_tokens`, cut
+/// at a grapheme boundary so accents and emoji stay whole
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> &str {
+    CharEstimate.truncate(text, max_tokens)
+}
+
+/// Top-level context variables referenced by `{{ }}` and `{% %}` tags,
+/// excluding loop variables and `loop` itself
+fn referenced_variables(source: &str) -> BTreeSet<String> {
+    let mut referenced = BTreeSet::new();
+    let mut locals = BTreeSet::from(["loop".to_string()]);
+    let mut rest = source;
+
+    while let Some(start) = [rest.find("{{"), rest.find("{%")]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        let close = if rest[start..].starts_with("{{") {
+            "}}"
+        } else {
+            "%}"
+        };
+        let Some(end) = rest[start + 2..].find(close) else {
+            break;
+        };
+        let inner = rest[start + 2..start + 2 + end].trim();
+        rest = &rest[start + 2 + end + 2..];
+
+        let paths: Vec<&str> = if close == "}}" {
+            vec![inner]
+        } else {
+            match inner.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["if", path] | ["if", "not", path] => vec![*path],
+                ["for", variable, "in", path] => {
+                    locals.insert(variable.to_string());
+                    vec![*path]
+                }
+                _ => Vec::new(),
+            }
+        };
+        for path in paths {
+            let root = path.split('.').next().unwrap_or_default().to_string();
+            if !root.is_empty() && !locals.contains(&root) {
+                referenced.insert(root);
+            }
+        }
+    }
+    referenced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_clean_template() {
+        let template = PromptTemplate::new(
+            "t",
+            "{% for e in examples %}{{ loop.index }} {{ e.input }}\n{% endfor %}{{ input }}",
+        );
+
+        let report = template.lint(&json!({"input": "hi", "examples": [{"input": "a"}]}));
+
+        assert!(report.is_clean(), "{:?}", report.issues);
+        assert_eq!(report.rendered.as_deref(), Some("1 a\nhi"));
+    }
+
+    #[test]
+    fn test_missing_and_unused_variables() {
+        let template = PromptTemplate::new("t", "{% if urgent %}!{% endif %}{{ input }}");
+
+        let report = template.lint(&json!({"input": "hi", "tone": "formal"}));
+
+        assert_eq!(
+            report.issues,
+            vec![
+                LintIssue::MissingVariable("urgent".to_string()),
+                LintIssue::UnusedVariable("tone".to_string()),
+            ]
+        );
+        assert!(report.rendered.is_none());
+    }
+
+    #[test]
+    fn test_forbidden_content() {
+        let template = PromptTemplate::new("t", "[INST] {{ input }} [/INST]");
+
+        let report = template.lint(&json!({"input": "hi"}));
+
+        assert!(
+            report
+                .issues
+                .contains(&LintIssue::ForbiddenContent("[INST]".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_token_limit() {
+        let template = PromptTemplate::new("t", "{{ input }}");
+        let linter = PromptLinter::default().with_max_tokens(2);
+
+        let report = linter.lint(&template, &json!({"input": "a fairly long input"}));
+
+        assert_eq!(report.token_estimate, 5);
+        assert_eq!(
+            report.issues,
+            vec![LintIssue::TooManyTokens { tokens: 5, max: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_syntax_error_is_reported() {
+        let template = PromptTemplate::new("t", "{% if input %}open");
+
+        let report = template.lint(&json!({"input": "hi"}));
+
+        assert!(matches!(report.issues[..], [LintIssue::RenderFailed(_)]));
+    }
+
+    #[test]
+    fn test_builtin_classifier_template_is_clean() {
+        use crate::agent::classifier::{ClassifierContext, default_classifier_template};
+
+        let report = default_classifier_template().lint(&ClassifierContext::new("Avise a Eva"));
+
+        assert!(report.is_clean(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn test_truncate_to_tokens_keeps_graphemes_whole() {
+        // 8 chars of budget; the decomposed "ã" is two chars
+        let text = "Reuna\u{0303}o amanhã";
+
+        assert_eq!(truncate_to_tokens(text, 2), "Reuna\u{0303}o ");
+        assert_eq!(truncate_to_tokens("Reuna\u{0303}", 1), "Reun");
+        assert_eq!(truncate_to_tokens("ok", 5), "ok");
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use crate::config::PromptsConfig;
+use crate::i18n::Locale;
+use crate::prompt::{PromptError, PromptTemplate};
+
+/// One stored version of a named prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptVersion {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub template: PromptTemplate,
+}
+
+impl PromptVersion {
+    pub fn new(name: &str, version: &str, template: PromptTemplate) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: None,
+            template,
+        }
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// `name@version`, recorded on results produced with this prompt
+    pub fn id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// How a version is picked when a prompt is requested
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromptSelection {
+    Fixed(String),
+    /// Versions with relative weights; the assignment key picks a bucket
+    Weighted(Vec<(String, u32)>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PromptRegistry {
+    versions: HashMap<String, Vec<PromptVersion>>,
+    selections: HashMap<String, PromptSelection>,
+}
+
+impl PromptRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads `<name>@<version>.txt` files from the configured directory and
+    /// applies the configured experiments
+    pub fn from_config(config: &PromptsConfig) -> Result<Self, PromptError> {
+        let mut registry = Self::new();
+        if let Some(directory) = &config.directory {
+            registry.load_dir(Path::new(directory))?;
+        }
+        registry.apply_experiments(&config.experiments)?;
+        Ok(registry)
+    }
+
+    pub fn apply_experiments(
+        &mut self,
+        experiments: &BTreeMap<String, BTreeMap<String, u32>>,
+    ) -> Result<(), PromptError> {
+        for (name, weights) in experiments {
+            let selection = match weights.len() {
+                1 => PromptSelection::Fixed(weights.keys().next().cloned().unwrap_or_default()),
+                _ => PromptSelection::Weighted(
+                    weights.iter().map(|(v, w)| (v.clone(), *w)).collect(),
+                ),
+            };
+            self.set_selection(name, selection)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of versions loaded
+    pub fn load_dir(&mut self, directory: &Path) -> Result<usize, PromptError> {
+        let entries = fs::read_dir(directory).map_err(|e| PromptError::Io(e.to_string()))?;
+        let mut loaded = 0;
+        for entry in entries {
+            let path = entry.map_err(|e| PromptError::Io(e.to_string()))?.path();
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let Some((name, version)) = stem.split_once('@') else {
+                continue;
+            };
+            let template = PromptTemplate::load_from_file(&path)?;
+            self.register(PromptVersion::new(name, version, template));
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Adds a version, replacing any existing one with the same id
+    pub fn register(&mut self, version: PromptVersion) {
+        let versions = self.versions.entry(version.name.clone()).or_default();
+        versions.retain(|v| v.version != version.version);
+        versions.push(version);
+    }
+
+    pub fn get(&self, name: &str, version: &str) -> Option<&PromptVersion> {
+        self.versions(name).iter().find(|v| v.version == version)
+    }
+
+    pub fn versions(&self, name: &str) -> &[PromptVersion] {
+        self.versions
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn set_selection(
+        &mut self,
+        name: &str,
+        selection: PromptSelection,
+    ) -> Result<(), PromptError> {
+        let referenced: Vec<&String> = match &selection {
+            PromptSelection::Fixed(version) => vec![version],
+            PromptSelection::Weighted(weights) => weights.iter().map(|(v, _)| v).collect(),
+        };
+        if let Some(missing) = referenced.iter().find(|v| self.get(name, v).is_none()) {
+            return Err(PromptError::UnknownPrompt(format!("{}@{}", name, missing)));
+        }
+        self.selections.insert(name.to_string(), selection);
+        Ok(())
+    }
+
+    /// Picks the version of `name` to use. Weighted selections are sticky:
+    /// the same key always lands on the same version. Without a selection
+    /// the most recently registered version wins.
+    pub fn select(&self, name: &str, key: &str) -> Option<&PromptVersion> {
+        match self.selections.get(name) {
+            Some(PromptSelection::Fixed(version)) => self.get(name, version),
+            Some(PromptSelection::Weighted(weights)) => {
+                let total: u64 = weights.iter().map(|(_, w)| u64::from(*w)).sum();
+                if total == 0 {
+                    return self.versions(name).last();
+                }
+                let mut bucket = fnv1a(&format!("{}:{}", name, key)) % total;
+                weights
+                    .iter()
+                    .find(|(_, weight)| {
+                        let hit = bucket < u64::from(*weight);
+                        bucket = bucket.saturating_sub(u64::from(*weight));
+                        hit
+                    })
+                    .and_then(|(version, _)| self.get(name, version))
+            }
+            None => self.versions(name).last(),
+        }
+    }
+
+    /// Like `select`, but prefers the variant of `name` for `locale` when
+    /// one is registered. A selection pinned on the plain name (a custom
+    /// template or an experiment) wins over an unpinned variant.
+    pub fn select_localized(
+        &self,
+        name: &str,
+        locale: Locale,
+        key: &str,
+    ) -> Option<&PromptVersion> {
+        let localized = localized_name(name, locale);
+        let pinned_elsewhere =
+            self.selections.contains_key(name) && !self.selections.contains_key(&localized);
+        if localized != name && !pinned_elsewhere && !self.versions(&localized).is_empty() {
+            return self.select(&localized, key);
+        }
+        self.select(name, key)
+    }
+}
+
+/// Name of the `locale` variant of a prompt, e.g. `classify_intent.pt-BR`.
+/// English prompts keep the plain name.
+pub fn localized_name(name: &str, locale: Locale) -> String {
+    match locale {
+        Locale::En => name.to_string(),
+        _ => format!("{}.{}", name, locale.tag()),
+    }
+}
+
+/// Stable hash so assignments survive restarts and compiler upgrades
+fn fnv1a(input: &str) -> u64 {
+    input.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> PromptRegistry {
+        let mut registry = PromptRegistry::new();
+        registry.register(PromptVersion::new(
+            "greet",
+            "v1",
+            PromptTemplate::new("greet", "Hello {{ name }}"),
+        ));
+        registry.register(
+            PromptVersion::new("greet", "v2", PromptTemplate::new("greet", "Hi {{ name }}"))
+                .with_description("shorter"),
+        );
+        registry
+    }
+
+    #[test]
+    fn test_latest_version_by_default() {
+        let registry = registry();
+
+        assert_eq!(registry.select("greet", "user-1").unwrap().id(), "greet@v2");
+        assert!(registry.select("missing", "user-1").is_none());
+    }
+
+    #[test]
+    fn test_fixed_selection() {
+        let mut registry = registry();
+
+        registry
+            .set_selection("greet", PromptSelection::Fixed("v1".to_string()))
+            .unwrap();
+
+        assert_eq!(registry.select("greet", "anyone").unwrap().version, "v1");
+    }
+
+    #[test]
+    fn test_unknown_version_is_rejected() {
+        let mut registry = registry();
+
+        assert_eq!(
+            registry.set_selection("greet", PromptSelection::Fixed("v9".to_string())),
+            Err(PromptError::UnknownPrompt("greet@v9".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_weighted_selection_is_sticky_and_split() {
+        let mut registry = registry();
+        registry
+            .set_selection(
+                "greet",
+                PromptSelection::Weighted(vec![("v1".to_string(), 50), ("v2".to_string(), 50)]),
+            )
+            .unwrap();
+
+        let first = registry.select("greet", "user-42").unwrap().version.clone();
+        assert_eq!(registry.select("greet", "user-42").unwrap().version, first);
+
+        let v1 = (0..1000)
+            .filter(|i| {
+                registry
+                    .select("greet", &format!("user-{}", i))
+                    .unwrap()
+                    .version
+                    == "v1"
+            })
+            .count();
+        assert!((350..650).contains(&v1), "v1 got {} of 1000", v1);
+    }
+
+    #[test]
+    fn test_zero_weight_is_never_selected() {
+        let mut registry = registry();
+        registry
+            .set_selection(
+                "greet",
+                PromptSelection::Weighted(vec![("v1".to_string(), 0), ("v2".to_string(), 1)]),
+            )
+            .unwrap();
+
+        assert!(
+            (0..100).all(|i| registry.select("greet", &i.to_string()).unwrap().version == "v2")
+        );
+    }
+
+    #[test]
+    fn test_localized_variant_is_preferred() {
+        let mut registry = registry();
+        registry.register(PromptVersion::new(
+            "greet.pt-BR",
+            "v1",
+            PromptTemplate::new("greet.pt-BR", "Olá {{ name }}"),
+        ));
+
+        assert_eq!(
+            registry
+                .select_localized("greet", Locale::PtBr, "x")
+                .unwrap()
+                .id(),
+            "greet.pt-BR@v1"
+        );
+        assert_eq!(
+            registry
+                .select_localized("greet", Locale::En, "x")
+                .unwrap()
+                .id(),
+            "greet@v2"
+        );
+
+        registry
+            .set_selection("greet", PromptSelection::Fixed("v1".to_string()))
+            .unwrap();
+        assert_eq!(
+            registry
+                .select_localized("greet", Locale::PtBr, "x")
+                .unwrap()
+                .id(),
+            "greet@v1"
+        );
+    }
+
+    #[test]
+    fn test_missing_variant_falls_back() {
+        assert_eq!(
+            registry()
+                .select_localized("greet", Locale::PtBr, "x")
+                .unwrap()
+                .id(),
+            "greet@v2"
+        );
+    }
+
+    #[test]
+    fn test_load_dir_and_config_experiments() {
+        let mut experiments = BTreeMap::new();
+        experiments.insert(
+            "classify_intent".to_string(),
+            BTreeMap::from([("v1".to_string(), 100)]),
+        );
+        let config = PromptsConfig {
+            directory: Some("prompts".to_string()),
+            experiments,
+            ..Default::default()
+        };
+
+        let registry = PromptRegistry::from_config(&config).unwrap();
+
+        assert_eq!(
+            registry.select("classify_intent", "x").unwrap().id(),
+            "classify_intent@v1"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::prompt::{PromptError, engine};
+
+/// A named prompt template rendered with a typed, serializable context
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    name: String,
+    source: String,
+}
+
+impl PromptTemplate {
+    pub fn new(name: &str, source: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            source: source.to_string(),
+        }
+    }
+
+    /// Loads a template, named after the file stem
+    pub fn load_from_file(path: &Path) -> Result<Self, PromptError> {
+        let source = fs::read_to_string(path).map_err(|e| PromptError::Io(e.to_string()))?;
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("prompt");
+        Ok(Self::new(name, &source))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn render<C: Serialize>(&self, context: &C) -> Result<String, PromptError> {
+        let context = serde_json::to_value(context)
+            .map_err(|e| PromptError::InvalidContext(e.to_string()))?;
+        engine::render(&self.name, &self.source, &context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Greeting {
+        name: String,
+        friends: Vec<String>,
+        formal: bool,
+    }
+
+    #[test]
+    fn test_render_typed_context() {
+        let template = PromptTemplate::new(
+            "greeting",
+            "{% if formal %}Dear{% else %}Hi{% endif %} {{ name }}\n{% for f in friends %}\n- {{ f }}\n{% endfor %}\n",
+        );
+        let context = Greeting {
+            name: "Eva".to_string(),
+            friends: vec!["Ana".to_string(), "John".to_string()],
+            formal: false,
+        };
+
+        assert_eq!(
+            template.render(&context).unwrap(),
+            "Hi Eva\n- Ana\n- John\n"
+        );
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let template =
+            PromptTemplate::load_from_file(Path::new("prompts/classify_intent@v1.txt")).unwrap();
+
+        assert_eq!(template.name(), "classify_intent@v1");
+        assert!(template.source().contains("{{ input }}"));
+    }
+
+    #[test]
+    fn test_missing_file() {
+        assert!(matches!(
+            PromptTemplate::load_from_file(Path::new("prompts/missing.txt")),
+            Err(PromptError::Io(_))
+        ));
+    }
+}
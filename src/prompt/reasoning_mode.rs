@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// Whether a prompt asks for the answer directly or lets the model reason
+/// first. With chain of thought the model ends with a final-answer marker
+/// and only the JSON after it is parsed (see `isolate_final_answer`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningMode {
+    #[default]
+    Direct,
+    ChainOfThought,
+}
+
+impl ReasoningMode {
+    pub fn is_chain_of_thought(&self) -> bool {
+        *self == ReasoningMode::ChainOfThought
+    }
+}
@@ -0,0 +1,276 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::config::Config;
+
+/// Characters per token assumed when no tokenizer is configured
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Tokenizers already loaded, by spec; a tokenizer.json takes a while to
+/// parse
+static LOADED: Lazy<Mutex<HashMap<String, Arc<dyn Tokenizer>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Counts tokens the way a model family does, for context budgets and for
+/// usage Ollama does not report
+pub trait Tokenizer: fmt::Debug + Send + Sync {
+    /// The spec it was loaded from, e.g. `cl100k`
+    fn name(&self) -> &str;
+
+    fn count(&self, text: &str) -> usize;
+
+    /// Longest prefix of `text` within `max_tokens`, cut at a grapheme
+    /// boundary so accents and emoji stay whole
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        if self.count(text) <= max_tokens {
+            return text;
+        }
+        let starts: Vec<usize> = text.grapheme_indices(true).map(|(i, _)| i).collect();
+        // The prefix of `low` graphemes fits and the whole text does not
+        let (mut low, mut high) = (0, starts.len());
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if self.count(&text[..starts[middle]]) <= max_tokens {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        &text[..starts[low]]
+    }
+}
+
+/// Four characters per token; close enough for English prose and the
+/// fallback for models without a configured tokenizer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CharEstimate;
+
+impl Tokenizer for CharEstimate {
+    fn name(&self) -> &str {
+        "estimate"
+    }
+
+    fn count(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(CHARS_PER_TOKEN)
+    }
+
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        let budget = max_tokens * CHARS_PER_TOKEN;
+        let mut chars = 0;
+        for (index, grapheme) in text.grapheme_indices(true) {
+            chars += grapheme.chars().count();
+            if chars > budget {
+                return &text[..index];
+            }
+        }
+        text
+    }
+}
+
+/// OpenAI BPE encodings, also used by gpt-oss
+#[cfg(feature = "tiktoken")]
+pub struct TiktokenTokenizer {
+    name: &'static str,
+    bpe: &'static tiktoken_rs::CoreBPE,
+}
+
+#[cfg(feature = "tiktoken")]
+impl TiktokenTokenizer {
+    pub fn cl100k() -> Self {
+        Self {
+            name: "cl100k",
+            bpe: tiktoken_rs::cl100k_base_singleton(),
+        }
+    }
+
+    pub fn o200k() -> Self {
+        Self {
+            name: "o200k",
+            bpe: tiktoken_rs::o200k_base_singleton(),
+        }
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl fmt::Debug for TiktokenTokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TiktokenTokenizer")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl Tokenizer for TiktokenTokenizer {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn count(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len()
+    }
+}
+
+/// A Hugging Face `tokenizer.json`, as shipped with Llama, Qwen, Mistral
+/// and Gemma weights
+#[cfg(feature = "hf-tokenizers")]
+#[derive(Debug)]
+pub struct HfTokenizer {
+    name: String,
+    inner: tokenizers::Tokenizer,
+}
+
+#[cfg(feature = "hf-tokenizers")]
+impl HfTokenizer {
+    pub fn from_file(path: &str) -> Result<Self, TokenizerError> {
+        let inner = tokenizers::Tokenizer::from_file(path).map_err(|e| TokenizerError::Load {
+            spec: path.to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(Self {
+            name: path.to_string(),
+            inner,
+        })
+    }
+}
+
+#[cfg(feature = "hf-tokenizers")]
+impl Tokenizer for HfTokenizer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn count(&self, text: &str) -> usize {
+        self.inner
+            .encode(text, false)
+            .map(|encoding| encoding.len())
+            .unwrap_or_else(|_| CharEstimate.count(text))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenizerError {
+    /// Not `estimate`, `cl100k`, `o200k` or a `.json` path
+    Unknown(String),
+    /// The spec needs a cargo feature this build lacks
+    Unavailable {
+        spec: String,
+        feature: &'static str,
+    },
+    Load {
+        spec: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for TokenizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenizerError::Unknown(spec) => write!(f, "Unknown tokenizer '{}'", spec),
+            TokenizerError::Unavailable { spec, feature } => {
+                write!(f, "Tokenizer '{}' needs the '{}' feature", spec, feature)
+            }
+            TokenizerError::Load { spec, message } => {
+                write!(f, "Failed to load tokenizer '{}': {}", spec, message)
+            }
+        }
+    }
+}
+
+impl Error for TokenizerError {}
+
+/// Tokenizer named by a `[tokenizer]` spec: `estimate`, `cl100k`, `o200k`
+/// or the path of a `tokenizer.json`
+pub fn tokenizer_from_spec(spec: &str) -> Result<Arc<dyn Tokenizer>, TokenizerError> {
+    match spec.trim() {
+        "" | "estimate" => Ok(Arc::new(CharEstimate)),
+        #[cfg(feature = "tiktoken")]
+        "cl100k" => Ok(Arc::new(TiktokenTokenizer::cl100k())),
+        #[cfg(feature = "tiktoken")]
+        "o200k" => Ok(Arc::new(TiktokenTokenizer::o200k())),
+        #[cfg(not(feature = "tiktoken"))]
+        spec @ ("cl100k" | "o200k") => Err(TokenizerError::Unavailable {
+            spec: spec.to_string(),
+            feature: "tiktoken",
+        }),
+        #[cfg(feature = "hf-tokenizers")]
+        path if path.ends_with(".json") => Ok(Arc::new(HfTokenizer::from_file(path)?)),
+        #[cfg(not(feature = "hf-tokenizers"))]
+        path if path.ends_with(".json") => Err(TokenizerError::Unavailable {
+            spec: path.to_string(),
+            feature: "hf-tokenizers",
+        }),
+        spec => Err(TokenizerError::Unknown(spec.to_string())),
+    }
+}
+
+/// Tokenizer configured for `model`. A spec that cannot be loaded falls
+/// back to `CharEstimate` rather than failing the request.
+pub fn tokenizer_for(model: &str) -> Arc<dyn Tokenizer> {
+    let spec = Config::get().tokenizer.spec_for(model).to_string();
+    let mut loaded = LOADED.lock().unwrap();
+    loaded
+        .entry(spec)
+        .or_insert_with_key(|spec| {
+            tokenizer_from_spec(spec).unwrap_or_else(|_| Arc::new(CharEstimate))
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One token per word, to check the generic truncation
+    #[derive(Debug)]
+    struct Words;
+
+    impl Tokenizer for Words {
+        fn name(&self) -> &str {
+            "words"
+        }
+
+        fn count(&self, text: &str) -> usize {
+            text.split_whitespace().count()
+        }
+    }
+
+    #[test]
+    fn test_truncate_by_count() {
+        assert_eq!(Words.truncate("one two three four", 2), "one two ");
+        assert_eq!(Words.truncate("one two", 5), "one two");
+        assert_eq!(Words.truncate("one", 0), "");
+    }
+
+    #[test]
+    fn test_specs() {
+        assert_eq!(tokenizer_from_spec("estimate").unwrap().name(), "estimate");
+        assert_eq!(
+            tokenizer_from_spec("sentencepiece").unwrap_err(),
+            TokenizerError::Unknown("sentencepiece".to_string())
+        );
+        #[cfg(not(feature = "tiktoken"))]
+        assert!(matches!(
+            tokenizer_from_spec("o200k"),
+            Err(TokenizerError::Unavailable { .. })
+        ));
+        #[cfg(feature = "hf-tokenizers")]
+        assert!(matches!(
+            tokenizer_from_spec("missing/tokenizer.json"),
+            Err(TokenizerError::Load { .. })
+        ));
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn test_tiktoken_counts_bpe_tokens() {
+        let tokenizer = tokenizer_from_spec("cl100k").unwrap();
+
+        assert_eq!(tokenizer.count("hello world"), 2);
+        assert_eq!(tokenizer.truncate("hello world", 1), "hello");
+    }
+}
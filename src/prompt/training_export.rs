@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::prompt::PromptError;
+
+/// A prompt with the answer we want the model to give
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrainingPair {
+    pub prompt: String,
+    pub response: String,
+    /// Prompt version that produced the pair, e.g. "classify_intent@v1"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+    /// What the model answered before a human corrected it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_response: Option<String>,
+}
+
+impl TrainingPair {
+    pub fn new(prompt: &str, response: &str) -> Self {
+        Self {
+            prompt: prompt.to_string(),
+            response: response.to_string(),
+            prompt_version: None,
+            original_response: None,
+        }
+    }
+
+    /// A pair where a human replaced the model's answer
+    pub fn corrected(prompt: &str, model_response: &str, corrected_response: &str) -> Self {
+        Self {
+            original_response: Some(model_response.to_string()),
+            ..Self::new(prompt, corrected_response)
+        }
+    }
+
+    pub fn with_prompt_version(mut self, prompt_version: &str) -> Self {
+        self.prompt_version = Some(prompt_version.to_string());
+        self
+    }
+
+    pub fn is_corrected(&self) -> bool {
+        self.original_response.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Ollama Modelfile with the pairs as MESSAGE few-shot turns
+    Modelfile,
+    /// One `{"messages": [...]}` conversation per line, the usual LoRA
+    /// chat fine-tuning input
+    ChatJsonl,
+    /// One `{"prompt": ..., "completion": ...}` object per line
+    CompletionJsonl,
+}
+
+/// Pairs collected from playground usage, stored as JSON lines
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrainingSet {
+    pairs: Vec<TrainingPair>,
+}
+
+impl TrainingSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load_jsonl(path: &Path) -> Result<Self, PromptError> {
+        let content = fs::read_to_string(path).map_err(|e| PromptError::Io(e.to_string()))?;
+        Self::from_jsonl(&content)
+    }
+
+    pub fn from_jsonl(content: &str) -> Result<Self, PromptError> {
+        let pairs = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|e| PromptError::InvalidContext(e.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { pairs })
+    }
+
+    /// Appends a pair to a JSONL store, creating the file if needed
+    pub fn append_to(path: &Path, pair: &TrainingPair) -> Result<(), PromptError> {
+        let line =
+            serde_json::to_string(pair).map_err(|e| PromptError::InvalidContext(e.to_string()))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| PromptError::Io(e.to_string()))?;
+        writeln!(file, "{}", line).map_err(|e| PromptError::Io(e.to_string()))
+    }
+
+    pub fn push(&mut self, pair: TrainingPair) {
+        self.pairs.push(pair);
+    }
+
+    pub fn pairs(&self) -> &[TrainingPair] {
+        &self.pairs
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Keeps only pairs produced by the given prompt version
+    pub fn for_prompt_version(&self, prompt_version: &str) -> Self {
+        Self {
+            pairs: self
+                .pairs
+                .iter()
+                .filter(|p| p.prompt_version.as_deref() == Some(prompt_version))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Human-corrected pairs only; they carry the most signal
+    pub fn corrections(&self) -> Self {
+        Self {
+            pairs: self
+                .pairs
+                .iter()
+                .filter(|p| p.is_corrected())
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn export(&self, format: ExportFormat, base_model: &str, system: Option<&str>) -> String {
+        match format {
+            ExportFormat::Modelfile => self.to_modelfile(base_model, system, None),
+            ExportFormat::ChatJsonl => self.to_chat_jsonl(system),
+            ExportFormat::CompletionJsonl => self.to_completion_jsonl(),
+        }
+    }
+
+    /// Modelfile for `ollama create`. An adapter path adds an ADAPTER line
+    /// for a LoRA trained on the JSONL export.
+    pub fn to_modelfile(
+        &self,
+        base_model: &str,
+        system: Option<&str>,
+        adapter: Option<&str>,
+    ) -> String {
+        let mut modelfile = format!("FROM {}\n", base_model);
+        if let Some(adapter) = adapter {
+            modelfile.push_str(&format!("ADAPTER {}\n", adapter));
+        }
+        if let Some(system) = system {
+            modelfile.push_str(&format!("SYSTEM {}\n", quote(system)));
+        }
+        for pair in &self.pairs {
+            modelfile.push_str(&format!("MESSAGE user {}\n", quote(&pair.prompt)));
+            modelfile.push_str(&format!("MESSAGE assistant {}\n", quote(&pair.response)));
+        }
+        modelfile
+    }
+
+    pub fn to_chat_jsonl(&self, system: Option<&str>) -> String {
+        self.pairs
+            .iter()
+            .map(|pair| {
+                let mut messages = Vec::new();
+                if let Some(system) = system {
+                    messages.push(json!({"role": "system", "content": system}));
+                }
+                messages.push(json!({"role": "user", "content": pair.prompt}));
+                messages.push(json!({"role": "assistant", "content": pair.response}));
+                format!("{}\n", json!({ "messages": messages }))
+            })
+            .collect()
+    }
+
+    pub fn to_completion_jsonl(&self) -> String {
+        self.pairs
+            .iter()
+            .map(|pair| {
+                format!(
+                    "{}\n",
+                    json!({"prompt": pair.prompt, "completion": pair.response})
+                )
+            })
+            .collect()
+    }
+}
+
+/// Modelfile values go in triple quotes so they may span lines
+fn quote(value: &str) -> String {
+    format!("\"\"\"{}\"\"\"", value.replace("\"\"\"", "\\\"\\\"\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn training_set() -> TrainingSet {
+        let mut set = TrainingSet::new();
+        set.push(
+            TrainingPair::new("Email Eva", r#"{"intent":"send_email"}"#)
+                .with_prompt_version("classify_intent@v1"),
+        );
+        set.push(
+            TrainingPair::corrected(
+                "Meet Bob at 3",
+                r#"{"intent":"send_email"}"#,
+                r#"{"intent":"schedule_meeting"}"#,
+            )
+            .with_prompt_version("classify_intent@v2"),
+        );
+        set
+    }
+
+    #[test]
+    fn test_modelfile_export() {
+        let modelfile =
+            training_set().to_modelfile("llama3.1:8b", Some("Classify intents."), Some("./lora"));
+
+        assert!(modelfile.starts_with("FROM llama3.1:8b\nADAPTER ./lora\n"));
+        assert!(modelfile.contains("SYSTEM \"\"\"Classify intents.\"\"\"\n"));
+        assert!(modelfile.contains("MESSAGE user \"\"\"Meet Bob at 3\"\"\"\n"));
+        assert!(
+            modelfile.contains("MESSAGE assistant \"\"\"{\"intent\":\"schedule_meeting\"}\"\"\"\n")
+        );
+    }
+
+    #[test]
+    fn test_chat_jsonl_export() {
+        let jsonl = training_set().to_chat_jsonl(Some("Classify intents."));
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["messages"][0]["role"], "system");
+        assert_eq!(
+            lines[1]["messages"][2]["content"],
+            r#"{"intent":"schedule_meeting"}"#
+        );
+    }
+
+    #[test]
+    fn test_completion_jsonl_export() {
+        let jsonl = training_set().export(ExportFormat::CompletionJsonl, "unused", None);
+
+        assert_eq!(
+            jsonl.lines().next().unwrap(),
+            r#"{"completion":"{\"intent\":\"send_email\"}","prompt":"Email Eva"}"#
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        let set = training_set();
+
+        assert_eq!(set.corrections().len(), 1);
+        assert_eq!(set.for_prompt_version("classify_intent@v1").len(), 1);
+        assert!(set.for_prompt_version("classify_intent@v9").is_empty());
+    }
+
+    #[test]
+    fn test_jsonl_store_roundtrip() {
+        let path = std::env::temp_dir().join(format!("training-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        for pair in training_set().pairs() {
+            TrainingSet::append_to(&path, pair).unwrap();
+        }
+        let loaded = TrainingSet::load_jsonl(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, training_set());
+    }
+
+    #[test]
+    fn test_triple_quotes_are_escaped() {
+        assert_eq!(quote("a\"\"\"b"), "\"\"\"a\\\"\\\"\\\"b\"\"\"");
+    }
+}
@@ -0,0 +1,424 @@
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::config::AttachmentConfig;
+
+/// Extension → MIME type table used to classify local files
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("pdf", "application/pdf"),
+    ("doc", "application/msword"),
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    ("xls", "application/vnd.ms-excel"),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    ("odt", "application/vnd.oasis.opendocument.text"),
+    ("txt", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("html", "text/html"),
+    ("ics", "text/calendar"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("wav", "audio/wav"),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("oga", "audio/ogg"),
+    ("opus", "audio/opus"),
+    ("m4a", "audio/mp4"),
+    ("flac", "audio/flac"),
+    ("webm", "audio/webm"),
+    ("zip", "application/zip"),
+    ("exe", "application/vnd.microsoft.portable-executable"),
+    ("sh", "application/x-sh"),
+    ("js", "text/javascript"),
+];
+
+const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttachmentError {
+    NotFound(PathBuf),
+    TooLarge { path: PathBuf, size: u64, max: u64 },
+    TypeNotAllowed { path: PathBuf, mime_type: String },
+    Infected { path: PathBuf, detail: String },
+    ScanFailed { path: PathBuf, detail: String },
+}
+
+impl fmt::Display for AttachmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttachmentError::NotFound(path) => {
+                write!(f, "Attachment {} not found", path.display())
+            }
+            AttachmentError::TooLarge { path, size, max } => write!(
+                f,
+                "Attachment {} is {} bytes, limit is {}",
+                path.display(),
+                size,
+                max
+            ),
+            AttachmentError::TypeNotAllowed { path, mime_type } => write!(
+                f,
+                "Attachment {} has disallowed type {}",
+                path.display(),
+                mime_type
+            ),
+            AttachmentError::Infected { path, detail } => {
+                write!(
+                    f,
+                    "Attachment {} failed scanning: {}",
+                    path.display(),
+                    detail
+                )
+            }
+            AttachmentError::ScanFailed { path, detail } => {
+                write!(
+                    f,
+                    "Attachment {} could not be scanned: {}",
+                    path.display(),
+                    detail
+                )
+            }
+        }
+    }
+}
+
+impl Error for AttachmentError {}
+
+/// Metadata of an attachment that passed every check
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckedAttachment {
+    pub path: PathBuf,
+    pub mime_type: String,
+    pub size: u64,
+}
+
+/// External scanning hook run on every attachment
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttachmentScanner {
+    /// Runs a command with the file path appended. Exit code 0 means clean,
+    /// 1 means infected (clamscan convention), anything else is a failure.
+    Command { program: String, args: Vec<String> },
+    /// POSTs the file bytes and expects `{"clean": bool, "detail": "..."}`
+    Http { url: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct ScanResponse {
+    clean: bool,
+    #[serde(default)]
+    detail: String,
+}
+
+impl AttachmentScanner {
+    /// Parses a command line such as `clamscan --no-summary`
+    pub fn command(command_line: &str) -> Option<Self> {
+        let mut parts = command_line.split_whitespace().map(str::to_string);
+        let program = parts.next()?;
+        Some(AttachmentScanner::Command {
+            program,
+            args: parts.collect(),
+        })
+    }
+
+    pub async fn scan(&self, path: &Path) -> Result<(), AttachmentError> {
+        match self {
+            AttachmentScanner::Command { program, args } => {
+                let output = tokio::process::Command::new(program)
+                    .args(args)
+                    .arg(path)
+                    .output()
+                    .await
+                    .map_err(|e| scan_failed(path, e.to_string()))?;
+                let detail = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                match output.status.code() {
+                    Some(0) => Ok(()),
+                    Some(1) => Err(AttachmentError::Infected {
+                        path: path.to_path_buf(),
+                        detail,
+                    }),
+                    _ => Err(scan_failed(
+                        path,
+                        String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                    )),
+                }
+            }
+            AttachmentScanner::Http { url } => {
+                let bytes = tokio::fs::read(path)
+                    .await
+                    .map_err(|e| scan_failed(path, e.to_string()))?;
+                let response = reqwest::Client::new()
+                    .post(url)
+                    .header("Content-Type", "application/octet-stream")
+                    .body(bytes)
+                    .send()
+                    .await
+                    .map_err(|e| scan_failed(path, e.to_string()))?
+                    .json::<ScanResponse>()
+                    .await
+                    .map_err(|e| scan_failed(path, e.to_string()))?;
+                if response.clean {
+                    Ok(())
+                } else {
+                    Err(AttachmentError::Infected {
+                        path: path.to_path_buf(),
+                        detail: response.detail,
+                    })
+                }
+            }
+        }
+    }
+}
+
+fn scan_failed(path: &Path, detail: String) -> AttachmentError {
+    AttachmentError::ScanFailed {
+        path: path.to_path_buf(),
+        detail,
+    }
+}
+
+/// Guesses a MIME type from the file extension
+pub fn mime_type_for(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    extension
+        .and_then(|ext| {
+            MIME_TYPES
+                .iter()
+                .find(|(known, _)| *known == ext)
+                .map(|(_, mime)| *mime)
+        })
+        .unwrap_or(DEFAULT_MIME_TYPE)
+}
+
+/// Size, type and scanning checks run before a draft may be queued
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachmentGuard {
+    max_size_bytes: u64,
+    allowed_mime_types: Vec<String>,
+    scanner: Option<AttachmentScanner>,
+}
+
+impl AttachmentGuard {
+    pub fn new(max_size_bytes: u64, allowed_mime_types: Vec<String>) -> Self {
+        Self {
+            max_size_bytes,
+            allowed_mime_types,
+            scanner: None,
+        }
+    }
+
+    pub fn from_config(config: &AttachmentConfig) -> Self {
+        let scanner = match (&config.scan_command, &config.scan_url) {
+            (Some(command), _) => AttachmentScanner::command(command),
+            (None, Some(url)) => Some(AttachmentScanner::Http { url: url.clone() }),
+            (None, None) => None,
+        };
+        Self {
+            max_size_bytes: config.max_size_bytes,
+            allowed_mime_types: config.allowed_mime_types.clone(),
+            scanner,
+        }
+    }
+
+    pub fn with_scanner(mut self, scanner: AttachmentScanner) -> Self {
+        self.scanner = Some(scanner);
+        self
+    }
+
+    /// Allowlist entries may be exact (`application/pdf`) or wildcards (`image/*`)
+    pub fn is_type_allowed(&self, mime_type: &str) -> bool {
+        self.allowed_mime_types
+            .iter()
+            .any(|allowed| match allowed.strip_suffix("/*") {
+                Some(prefix) => mime_type
+                    .split('/')
+                    .next()
+                    .is_some_and(|top| top.eq_ignore_ascii_case(prefix)),
+                None => allowed.eq_ignore_ascii_case(mime_type),
+            })
+    }
+
+    pub async fn check(&self, path: &Path) -> Result<CheckedAttachment, AttachmentError> {
+        let metadata = tokio::fs::metadata(path)
+            .await
+            .map_err(|_| AttachmentError::NotFound(path.to_path_buf()))?;
+        if !metadata.is_file() {
+            return Err(AttachmentError::NotFound(path.to_path_buf()));
+        }
+
+        let size = metadata.len();
+        if size > self.max_size_bytes {
+            return Err(AttachmentError::TooLarge {
+                path: path.to_path_buf(),
+                size,
+                max: self.max_size_bytes,
+            });
+        }
+
+        let mime_type = mime_type_for(path);
+        if !self.is_type_allowed(mime_type) {
+            return Err(AttachmentError::TypeNotAllowed {
+                path: path.to_path_buf(),
+                mime_type: mime_type.to_string(),
+            });
+        }
+
+        if let Some(scanner) = &self.scanner {
+            scanner.scan(path).await?;
+        }
+
+        Ok(CheckedAttachment {
+            path: path.to_path_buf(),
+            mime_type: mime_type.to_string(),
+            size,
+        })
+    }
+
+    /// Checks every attachment, stopping at the first failure
+    pub async fn check_all(
+        &self,
+        paths: &[PathBuf],
+    ) -> Result<Vec<CheckedAttachment>, AttachmentError> {
+        let mut checked = Vec::with_capacity(paths.len());
+        for path in paths {
+            checked.push(self.check(path).await?);
+        }
+        Ok(checked)
+    }
+}
+
+impl Default for AttachmentGuard {
+    fn default() -> Self {
+        Self::from_config(&AttachmentConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_file(name: &str, size: usize) -> PathBuf {
+        let dir = std::env::temp_dir().join("attachment_guard_tests");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, vec![b'a'; size]).unwrap();
+        path
+    }
+
+    fn guard() -> AttachmentGuard {
+        AttachmentGuard::new(
+            100,
+            vec!["application/pdf".to_string(), "image/*".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_mime_type_for() {
+        assert_eq!(mime_type_for(Path::new("report.PDF")), "application/pdf");
+        assert_eq!(mime_type_for(Path::new("photo.jpeg")), "image/jpeg");
+        assert_eq!(mime_type_for(Path::new("noext")), DEFAULT_MIME_TYPE);
+    }
+
+    #[test]
+    fn test_type_allowlist_with_wildcards() {
+        let guard = guard();
+
+        assert!(guard.is_type_allowed("application/pdf"));
+        assert!(guard.is_type_allowed("image/png"));
+        assert!(!guard.is_type_allowed("application/zip"));
+    }
+
+    #[tokio::test]
+    async fn test_allowed_attachment() {
+        let path = temp_file("ok.pdf", 10);
+
+        let checked = guard().check(&path).await.unwrap();
+
+        assert_eq!(checked.mime_type, "application/pdf");
+        assert_eq!(checked.size, 10);
+    }
+
+    #[tokio::test]
+    async fn test_too_large() {
+        let path = temp_file("big.pdf", 101);
+
+        assert!(matches!(
+            guard().check(&path).await,
+            Err(AttachmentError::TooLarge {
+                size: 101,
+                max: 100,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_type() {
+        let path = temp_file("tool.exe", 10);
+
+        assert!(matches!(
+            guard().check(&path).await,
+            Err(AttachmentError::TypeNotAllowed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        assert!(matches!(
+            guard().check(Path::new("does/not/exist.pdf")).await,
+            Err(AttachmentError::NotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_scanner_exit_codes() {
+        let path = temp_file("scanned.pdf", 10);
+        let clean = guard().with_scanner(AttachmentScanner::command("true").unwrap());
+        let infected = guard().with_scanner(AttachmentScanner::command("false").unwrap());
+
+        assert!(clean.check(&path).await.is_ok());
+        assert!(matches!(
+            infected.check(&path).await,
+            Err(AttachmentError::Infected { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_missing_scanner_program_fails_closed() {
+        let path = temp_file("unscanned.pdf", 10);
+        let guard = guard()
+            .with_scanner(AttachmentScanner::command("definitely-not-a-real-scanner").unwrap());
+
+        assert!(matches!(
+            guard.check(&path).await,
+            Err(AttachmentError::ScanFailed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_all_stops_at_first_failure() {
+        let ok = temp_file("first.pdf", 10);
+        let bad = temp_file("second.zip", 10);
+
+        assert!(guard().check_all(&[ok, bad]).await.is_err());
+    }
+}
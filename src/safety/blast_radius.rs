@@ -0,0 +1,411 @@
+use chrono::{Duration, Local, NaiveDateTime, NaiveTime, Timelike};
+use std::error::Error;
+use std::fmt;
+
+use crate::action::ActionPlan;
+use crate::config::BlastRadiusConfig;
+use crate::i18n::{BusinessCalendar, DayOff};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlastRadiusError {
+    TooManyRecipients {
+        count: usize,
+        max: usize,
+    },
+    TooManyEmails {
+        count: usize,
+        max: usize,
+    },
+    OutsideSendingHours {
+        time: NaiveTime,
+        window: SendingWindow,
+    },
+    /// Weekend or holiday while sending is limited to business days
+    NonBusinessDay {
+        at: NaiveDateTime,
+        day_off: DayOff,
+        /// When the next business day's sending window opens
+        reopens: NaiveDateTime,
+    },
+}
+
+impl fmt::Display for BlastRadiusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlastRadiusError::TooManyRecipients { count, max } => write!(
+                f,
+                "{} recipients exceeds the limit of {} per email",
+                count, max
+            ),
+            BlastRadiusError::TooManyEmails { count, max } => write!(
+                f,
+                "{} emails exceeds the limit of {} per request",
+                count, max
+            ),
+            BlastRadiusError::OutsideSendingHours { time, window } => write!(
+                f,
+                "Sending at {} is outside the allowed window {}",
+                time.format("%H:%M"),
+                window
+            ),
+            BlastRadiusError::NonBusinessDay { at, day_off, .. } => write!(
+                f,
+                "Sending on {} is not allowed ({})",
+                at.format("%Y-%m-%d"),
+                day_off
+            ),
+        }
+    }
+}
+
+impl Error for BlastRadiusError {}
+
+/// Hours of the day during which sending is allowed. A window whose start
+/// is after its end wraps around midnight (e.g. 22 → 6); equal hours leave
+/// it open all day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SendingWindow {
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl SendingWindow {
+    pub fn new(start_hour: u32, end_hour: u32) -> Self {
+        Self {
+            start_hour,
+            end_hour,
+        }
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let hour = time.hour();
+        if self.start_hour == self.end_hour {
+            true
+        } else if self.start_hour < self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+impl fmt::Display for SendingWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:00-{:02}:00", self.start_hour, self.end_hour)
+    }
+}
+
+/// Hard limits on what a single agent action may do
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlastRadiusLimits {
+    max_recipients_per_email: usize,
+    max_emails_per_utterance: usize,
+    sending_window: Option<SendingWindow>,
+    business_calendar: Option<BusinessCalendar>,
+}
+
+impl BlastRadiusLimits {
+    pub fn new(
+        max_recipients_per_email: usize,
+        max_emails_per_utterance: usize,
+        sending_window: Option<SendingWindow>,
+    ) -> Self {
+        Self {
+            max_recipients_per_email,
+            max_emails_per_utterance,
+            sending_window,
+            business_calendar: None,
+        }
+    }
+
+    /// Holds emails on the weekends and holidays of `calendar`
+    pub fn with_business_calendar(mut self, calendar: BusinessCalendar) -> Self {
+        self.business_calendar = Some(calendar);
+        self
+    }
+
+    pub fn from_config(config: &BlastRadiusConfig) -> Self {
+        let sending_window = match (config.sending_start_hour, config.sending_end_hour) {
+            (Some(start), Some(end)) => Some(SendingWindow::new(start, end)),
+            _ => None,
+        };
+        let limits = Self::new(
+            config.max_recipients_per_email,
+            config.max_emails_per_utterance,
+            sending_window,
+        );
+        if config.business_days_only {
+            limits.with_business_calendar(BusinessCalendar::configured())
+        } else {
+            limits
+        }
+    }
+
+    pub fn check_recipients(&self, count: usize) -> Result<(), BlastRadiusError> {
+        if count > self.max_recipients_per_email {
+            return Err(BlastRadiusError::TooManyRecipients {
+                count,
+                max: self.max_recipients_per_email,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_email_count(&self, count: usize) -> Result<(), BlastRadiusError> {
+        if count > self.max_emails_per_utterance {
+            return Err(BlastRadiusError::TooManyEmails {
+                count,
+                max: self.max_emails_per_utterance,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_sending_time(&self, time: NaiveTime) -> Result<(), BlastRadiusError> {
+        match self.sending_window {
+            Some(window) if !window.contains(time) => {
+                Err(BlastRadiusError::OutsideSendingHours { time, window })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_sending_day(&self, at: NaiveDateTime) -> Result<(), BlastRadiusError> {
+        let Some(calendar) = &self.business_calendar else {
+            return Ok(());
+        };
+        let Some(day_off) = calendar.day_off(at.date()) else {
+            return Ok(());
+        };
+        let opens = self.sending_window.map_or(0, |window| window.start_hour);
+        let reopens = calendar
+            .next_business_day(at.date())
+            .and_hms_opt(opens, 0, 0)
+            .unwrap_or_default();
+        Err(BlastRadiusError::NonBusinessDay {
+            at,
+            day_off,
+            reopens,
+        })
+    }
+
+    /// Checks the sending hours and days at `at`
+    pub fn check_sending_at(&self, at: NaiveDateTime) -> Result<(), BlastRadiusError> {
+        self.check_sending_time(at.time())?;
+        self.check_sending_day(at)
+    }
+
+    /// When sending is next allowed after `at`, or `None` if it is
+    /// allowed at `at`
+    pub fn reopens(&self, at: NaiveDateTime) -> Option<NaiveDateTime> {
+        if let Err(BlastRadiusError::NonBusinessDay { reopens, .. }) = self.check_sending_day(at) {
+            return Some(reopens);
+        }
+        let window = self
+            .sending_window
+            .filter(|window| !window.contains(at.time()))?;
+        let opens = at.date().and_hms_opt(window.start_hour, 0, 0)?;
+        let opens = if opens > at {
+            opens
+        } else {
+            opens + Duration::days(1)
+        };
+        Some(self.reopens(opens).unwrap_or(opens))
+    }
+
+    /// Checks a single plan against the recipient and time limits
+    pub fn check_plan(&self, plan: &ActionPlan, time: NaiveTime) -> Result<(), BlastRadiusError> {
+        self.check_recipients(plan.all_recipients().len())?;
+        self.check_sending_time(time)
+    }
+
+    /// Checks every plan produced from one utterance, using local time
+    pub fn check_utterance(&self, plans: &[ActionPlan]) -> Result<(), BlastRadiusError> {
+        self.check_utterance_on(plans, Local::now().naive_local())
+    }
+
+    /// Like `check_utterance_at`, also holding emails on days off
+    pub fn check_utterance_on(
+        &self,
+        plans: &[ActionPlan],
+        at: NaiveDateTime,
+    ) -> Result<(), BlastRadiusError> {
+        self.check_utterance_at(plans, at.time())?;
+        self.check_sending_day(at)
+    }
+
+    pub fn check_utterance_at(
+        &self,
+        plans: &[ActionPlan],
+        time: NaiveTime,
+    ) -> Result<(), BlastRadiusError> {
+        self.check_email_count(plans.len())?;
+        plans
+            .iter()
+            .try_for_each(|plan| self.check_plan(plan, time))
+    }
+}
+
+impl Default for BlastRadiusLimits {
+    fn default() -> Self {
+        Self::from_config(&BlastRadiusConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::Params;
+    use crate::agent::{ClassificationResult, Intent};
+    use crate::i18n::Region;
+    use chrono::NaiveDate;
+
+    fn plan(recipients: usize) -> ActionPlan {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva@company.com".to_string(), "Hi".to_string()),
+        );
+        let mut plan = ActionPlan::from_classification(1, &result);
+        plan.recipients = (0..recipients)
+            .map(|i| format!("user{}@company.com", i))
+            .collect();
+        plan
+    }
+
+    fn at(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 30, 0).unwrap()
+    }
+
+    fn limits() -> BlastRadiusLimits {
+        BlastRadiusLimits::new(3, 2, Some(SendingWindow::new(8, 20)))
+    }
+
+    #[test]
+    fn test_within_limits() {
+        assert!(
+            limits()
+                .check_utterance_at(&[plan(3), plan(1)], at(9))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_too_many_recipients() {
+        assert_eq!(
+            limits().check_plan(&plan(4), at(9)),
+            Err(BlastRadiusError::TooManyRecipients { count: 4, max: 3 })
+        );
+    }
+
+    #[test]
+    fn test_too_many_emails() {
+        assert_eq!(
+            limits().check_utterance_at(&[plan(1), plan(1), plan(1)], at(9)),
+            Err(BlastRadiusError::TooManyEmails { count: 3, max: 2 })
+        );
+    }
+
+    #[test]
+    fn test_outside_sending_hours() {
+        let error = limits().check_plan(&plan(1), at(21)).unwrap_err();
+
+        assert!(matches!(
+            error,
+            BlastRadiusError::OutsideSendingHours { .. }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Sending at 21:30 is outside the allowed window 08:00-20:00"
+        );
+    }
+
+    #[test]
+    fn test_window_wrapping_midnight() {
+        let window = SendingWindow::new(22, 6);
+
+        assert!(window.contains(at(23)));
+        assert!(window.contains(at(2)));
+        assert!(!window.contains(at(12)));
+    }
+
+    #[test]
+    fn test_equal_hours_are_always_open() {
+        let limits = BlastRadiusLimits::new(10, 5, Some(SendingWindow::new(9, 9)));
+
+        assert!(SendingWindow::new(0, 0).contains(at(12)));
+        assert!(limits.check_plan(&plan(1), at(3)).is_ok());
+        assert!(limits.check_plan(&plan(1), at(9)).is_ok());
+    }
+
+    #[test]
+    fn test_held_on_holidays() {
+        let limits = limits().with_business_calendar(BusinessCalendar::new(Some(Region::Br)));
+        // Tiradentes, a Monday
+        let holiday = NaiveDate::from_ymd_opt(2025, 4, 21)
+            .unwrap()
+            .and_time(at(9));
+
+        let error = limits.check_utterance_on(&[plan(1)], holiday).unwrap_err();
+
+        assert_eq!(
+            error,
+            BlastRadiusError::NonBusinessDay {
+                at: holiday,
+                day_off: DayOff::Holiday("Tiradentes".to_string()),
+                reopens: NaiveDate::from_ymd_opt(2025, 4, 22)
+                    .unwrap()
+                    .and_hms_opt(8, 0, 0)
+                    .unwrap(),
+            }
+        );
+        assert!(
+            limits
+                .check_utterance_on(&[plan(1)], holiday + chrono::Duration::days(1))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_reopens_with_the_next_window() {
+        let limits = limits().with_business_calendar(BusinessCalendar::new(Some(Region::Br)));
+        let day = |day: u32| NaiveDate::from_ymd_opt(2025, 4, day).unwrap();
+
+        assert_eq!(limits.reopens(day(22).and_time(at(9))), None);
+        assert_eq!(
+            limits.reopens(day(22).and_time(at(21))),
+            day(23).and_hms_opt(8, 0, 0)
+        );
+        assert_eq!(
+            limits.reopens(day(22).and_time(at(6))),
+            day(22).and_hms_opt(8, 0, 0)
+        );
+        // Sunday, then Tiradentes on Monday
+        assert_eq!(
+            limits.reopens(day(20).and_time(at(9))),
+            day(22).and_hms_opt(8, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_no_window_allows_any_time() {
+        let limits = BlastRadiusLimits::new(3, 2, None);
+
+        assert!(limits.check_sending_time(at(3)).is_ok());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let limits = BlastRadiusLimits::from_config(&BlastRadiusConfig {
+            max_recipients_per_email: 5,
+            max_emails_per_utterance: 1,
+            sending_start_hour: Some(9),
+            sending_end_hour: Some(17),
+            business_days_only: false,
+        });
+
+        assert_eq!(
+            limits,
+            BlastRadiusLimits::new(5, 1, Some(SendingWindow::new(9, 17)))
+        );
+    }
+}
@@ -0,0 +1,347 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::agent::Agent;
+use crate::agent::toxicity::{ToxicityAssessment, ToxicityParam};
+use crate::config::ContentPolicyConfig;
+
+const URL_PREFIXES: &[&str] = &["http://", "https://", "www."];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyRule {
+    BannedPhrase,
+    MaxLength,
+    RequiredDisclaimer,
+    UrlNotAllowed,
+    Toxicity,
+}
+
+impl fmt::Display for PolicyRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyRule::BannedPhrase => write!(f, "banned_phrase"),
+            PolicyRule::MaxLength => write!(f, "max_length"),
+            PolicyRule::RequiredDisclaimer => write!(f, "required_disclaimer"),
+            PolicyRule::UrlNotAllowed => write!(f, "url_not_allowed"),
+            PolicyRule::Toxicity => write!(f, "toxicity"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    Block,
+    Flag,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PolicyViolation {
+    pub rule: PolicyRule,
+    pub action: PolicyAction,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct PolicyReport {
+    pub violations: Vec<PolicyViolation>,
+}
+
+impl PolicyReport {
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// A blocked draft must not reach the outbox
+    pub fn is_blocked(&self) -> bool {
+        self.violations
+            .iter()
+            .any(|v| v.action == PolicyAction::Block)
+    }
+
+    /// A flagged draft may be sent only after human review
+    pub fn is_flagged(&self) -> bool {
+        self.violations
+            .iter()
+            .any(|v| v.action == PolicyAction::Flag)
+    }
+}
+
+/// Configurable checks run on generated email bodies before sending
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentPolicy {
+    config: ContentPolicyConfig,
+}
+
+impl ContentPolicy {
+    pub fn new(config: ContentPolicyConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &ContentPolicyConfig {
+        &self.config
+    }
+
+    /// Runs the rule-based checks
+    pub fn check(&self, body: &str) -> PolicyReport {
+        let mut report = PolicyReport::default();
+        let lowered = body.to_lowercase();
+
+        for phrase in &self.config.banned_phrases {
+            if lowered.contains(&phrase.to_lowercase()) {
+                self.push(
+                    &mut report,
+                    PolicyRule::BannedPhrase,
+                    format!("contains banned phrase '{}'", phrase),
+                );
+            }
+        }
+
+        if let Some(max_length) = self.config.max_length {
+            let length = body.chars().count();
+            if length > max_length {
+                self.push(
+                    &mut report,
+                    PolicyRule::MaxLength,
+                    format!("{} characters exceeds the limit of {}", length, max_length),
+                );
+            }
+        }
+
+        if let Some(disclaimer) = &self.config.required_disclaimer
+            && !body.contains(disclaimer.as_str())
+        {
+            self.push(
+                &mut report,
+                PolicyRule::RequiredDisclaimer,
+                "required disclaimer is missing".to_string(),
+            );
+        }
+
+        for url in find_urls(body) {
+            if !self.is_url_allowed(&url) {
+                self.push(
+                    &mut report,
+                    PolicyRule::UrlNotAllowed,
+                    format!("URL '{}' is not whitelisted", url),
+                );
+            }
+        }
+
+        report
+    }
+
+    /// Runs the rule-based checks and, if enabled, the model toxicity check.
+    /// A failing toxicity check flags the draft instead of silently passing.
+    pub async fn check_with_toxicity<A>(&self, body: &str, agent: &A) -> PolicyReport
+    where
+        A: Agent<ToxicityParam, ToxicityAssessment>,
+    {
+        let mut report = self.check(body);
+        if !self.config.toxicity_check {
+            return report;
+        }
+
+        match agent.process(ToxicityParam::new(body.to_string())).await {
+            Ok(assessment) if assessment.toxic => {
+                self.push(&mut report, PolicyRule::Toxicity, assessment.reason)
+            }
+            Ok(_) => {}
+            Err(e) => report.violations.push(PolicyViolation {
+                rule: PolicyRule::Toxicity,
+                action: PolicyAction::Flag,
+                detail: format!("toxicity check unavailable: {}", e),
+            }),
+        }
+        report
+    }
+
+    fn push(&self, report: &mut PolicyReport, rule: PolicyRule, detail: String) {
+        report.violations.push(PolicyViolation {
+            rule,
+            action: self.action_for(rule),
+            detail,
+        });
+    }
+
+    fn action_for(&self, rule: PolicyRule) -> PolicyAction {
+        if self.config.flag_only.contains(&rule) {
+            PolicyAction::Flag
+        } else {
+            PolicyAction::Block
+        }
+    }
+
+    fn is_url_allowed(&self, url: &str) -> bool {
+        let host = url_host(url).to_lowercase();
+        self.config.allowed_url_domains.iter().any(|domain| {
+            let domain = domain.to_lowercase();
+            host == domain || host.ends_with(&format!(".{}", domain))
+        })
+    }
+}
+
+impl Default for ContentPolicy {
+    fn default() -> Self {
+        Self::new(ContentPolicyConfig::default())
+    }
+}
+
+fn find_urls(body: &str) -> Vec<String> {
+    body.split_whitespace()
+        .filter_map(|word| {
+            // Compared in place: lowercasing can change byte offsets
+            let (start, _) = word.char_indices().find(|(i, _)| {
+                URL_PREFIXES.iter().any(|prefix| {
+                    word.get(*i..*i + prefix.len())
+                        .is_some_and(|candidate| candidate.eq_ignore_ascii_case(prefix))
+                })
+            })?;
+            Some(
+                word[start..]
+                    .trim_end_matches(|c: char| ".,;:!?)]}>\"'".contains(c))
+                    .to_string(),
+            )
+        })
+        .collect()
+}
+
+fn url_host(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let authority = without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or(without_scheme);
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    host.split(':').next().unwrap_or(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentError;
+
+    struct FixedToxicity(bool);
+
+    impl Agent<ToxicityParam, ToxicityAssessment> for FixedToxicity {
+        async fn process(&self, _input: ToxicityParam) -> Result<ToxicityAssessment, AgentError> {
+            Ok(ToxicityAssessment::new(
+                self.0,
+                "insulting tone".to_string(),
+            ))
+        }
+    }
+
+    fn policy() -> ContentPolicy {
+        ContentPolicy::new(ContentPolicyConfig {
+            banned_phrases: vec!["guaranteed returns".to_string()],
+            max_length: Some(80),
+            required_disclaimer: Some("Sent by my assistant".to_string()),
+            allowed_url_domains: vec!["company.com".to_string()],
+            flag_only: vec![PolicyRule::MaxLength],
+            toxicity_check: true,
+        })
+    }
+
+    #[test]
+    fn test_clean_body() {
+        let report =
+            policy().check("Hi Eva, see https://docs.company.com/q3. Sent by my assistant");
+
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_banned_phrase_blocks() {
+        let report = policy().check("GUARANTEED RETURNS inside! Sent by my assistant");
+
+        assert!(report.is_blocked());
+        assert_eq!(report.violations[0].rule, PolicyRule::BannedPhrase);
+    }
+
+    #[test]
+    fn test_max_length_only_flags() {
+        let body = format!("{} Sent by my assistant", "a".repeat(100));
+        let report = policy().check(&body);
+
+        assert!(!report.is_blocked());
+        assert!(report.is_flagged());
+        assert_eq!(report.violations[0].rule, PolicyRule::MaxLength);
+    }
+
+    #[test]
+    fn test_missing_disclaimer() {
+        let report = policy().check("Hi Eva");
+
+        assert_eq!(report.violations[0].rule, PolicyRule::RequiredDisclaimer);
+        assert!(report.is_blocked());
+    }
+
+    #[test]
+    fn test_url_whitelist() {
+        let report = policy()
+            .check("Click www.evil.com/login or http://company.com.evil.io. Sent by my assistant");
+
+        let urls: Vec<&PolicyViolation> = report
+            .violations
+            .iter()
+            .filter(|v| v.rule == PolicyRule::UrlNotAllowed)
+            .collect();
+        assert_eq!(urls.len(), 2);
+    }
+
+    #[test]
+    fn test_default_policy_blocks_any_url() {
+        let report = ContentPolicy::default().check("See https://example.com");
+
+        assert!(report.is_blocked());
+    }
+
+    #[test]
+    fn test_urls_after_non_ascii_chars() {
+        // Both lowercase to a different byte length
+        assert_eq!(
+            find_urls("see ẞ€https://evil.example/x"),
+            vec!["https://evil.example/x"]
+        );
+        assert_eq!(
+            find_urls("İhttps://evil.example"),
+            vec!["https://evil.example"]
+        );
+        assert_eq!(find_urls("WWW.evil.example"), vec!["WWW.evil.example"]);
+        assert!(
+            ContentPolicy::default()
+                .check("see ẞ€https://evil.example/x")
+                .is_blocked()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_toxicity_check() {
+        let report = policy()
+            .check_with_toxicity("You idiot. Sent by my assistant", &FixedToxicity(true))
+            .await;
+
+        assert_eq!(report.violations[0].rule, PolicyRule::Toxicity);
+        assert!(report.is_blocked());
+    }
+
+    #[tokio::test]
+    async fn test_toxicity_check_disabled() {
+        let report = ContentPolicy::default()
+            .check_with_toxicity("You idiot.", &FixedToxicity(true))
+            .await;
+
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://user@docs.company.com:8080/x?y"),
+            "docs.company.com"
+        );
+        assert_eq!(url_host("www.company.com/path"), "www.company.com");
+    }
+}
@@ -0,0 +1,21 @@
+pub mod attachment_guard;
+pub mod blast_radius;
+pub mod content_policy;
+pub mod injection_detector;
+pub mod injection_guard;
+pub mod poison_tracker;
+pub mod quarantine;
+pub mod recipient_anomaly;
+pub mod redaction;
+
+pub use attachment_guard::{
+    AttachmentError, AttachmentGuard, AttachmentScanner, CheckedAttachment,
+};
+pub use blast_radius::{BlastRadiusError, BlastRadiusLimits, SendingWindow};
+pub use content_policy::{ContentPolicy, PolicyAction, PolicyReport, PolicyRule, PolicyViolation};
+pub use injection_detector::{InjectionDetector, InjectionRisk, InjectionVerdict};
+pub use injection_guard::InjectionGuard;
+pub use poison_tracker::{PoisonError, PoisonTracker};
+pub use quarantine::{Quarantine, QuarantineReason, QuarantinedInput};
+pub use recipient_anomaly::{RecipientAnomaly, RecipientAnomalyDetector, RecipientHistory};
+pub use redaction::{PiiKind, PiiMatch, RedactionSink, Redactor};
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::PoisonConfig;
+use crate::safety::{Quarantine, QuarantineReason};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoisonError<E> {
+    /// The input was quarantined earlier (or just now) and is not retried
+    Quarantined(u64),
+    TimedOut(Duration),
+    Failed(E),
+}
+
+impl<E: fmt::Display> fmt::Display for PoisonError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoisonError::Quarantined(id) => {
+                write!(f, "Input quarantined (#{}) after repeated failures", id)
+            }
+            PoisonError::TimedOut(after) => write!(f, "Timed out after {:?}", after),
+            PoisonError::Failed(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for PoisonError<E> {}
+
+#[derive(Debug, Default)]
+struct Attempts {
+    failures: HashMap<u64, u32>,
+    quarantined: HashMap<u64, u64>,
+}
+
+/// Counts failed attempts per input and quarantines inputs that keep
+/// crashing or timing out, so a queue is not blocked retrying them forever
+pub struct PoisonTracker {
+    max_attempts: u32,
+    attempt_timeout: Duration,
+    quarantine: Arc<Quarantine>,
+    attempts: Mutex<Attempts>,
+}
+
+impl PoisonTracker {
+    pub fn new(max_attempts: u32, attempt_timeout: Duration, quarantine: Arc<Quarantine>) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            attempt_timeout,
+            quarantine,
+            attempts: Mutex::new(Attempts::default()),
+        }
+    }
+
+    pub fn from_config(config: &PoisonConfig, quarantine: Arc<Quarantine>) -> Self {
+        Self::new(
+            config.max_attempts,
+            Duration::from_secs(config.attempt_timeout_secs),
+            quarantine,
+        )
+    }
+
+    pub fn quarantine(&self) -> &Arc<Quarantine> {
+        &self.quarantine
+    }
+
+    /// Quarantine id if the input has been given up on
+    pub fn quarantined_id(&self, input: &str) -> Option<u64> {
+        let attempts = self.attempts.lock().unwrap();
+        attempts.quarantined.get(&fingerprint(input)).copied()
+    }
+
+    pub fn failures(&self, input: &str) -> u32 {
+        let attempts = self.attempts.lock().unwrap();
+        attempts
+            .failures
+            .get(&fingerprint(input))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Records a failed attempt; returns the quarantine id once the input
+    /// has failed `max_attempts` times
+    pub fn record_failure(&self, input: &str, error: &str) -> Option<u64> {
+        let key = fingerprint(input);
+        let mut attempts = self.attempts.lock().unwrap();
+        if let Some(id) = attempts.quarantined.get(&key) {
+            return Some(*id);
+        }
+        let failures = attempts.failures.entry(key).or_insert(0);
+        *failures += 1;
+        if *failures < self.max_attempts {
+            return None;
+        }
+
+        let reason = QuarantineReason::PoisonInput {
+            attempts: *failures,
+            last_error: error.to_string(),
+        };
+        attempts.failures.remove(&key);
+        let id = self.quarantine.add(input, reason);
+        attempts.quarantined.insert(key, id);
+        Some(id)
+    }
+
+    pub fn record_success(&self, input: &str) {
+        self.attempts
+            .lock()
+            .unwrap()
+            .failures
+            .remove(&fingerprint(input));
+    }
+
+    /// Forgets a quarantined input, e.g. after it was released for review
+    pub fn forgive(&self, input: &str) {
+        let key = fingerprint(input);
+        let mut attempts = self.attempts.lock().unwrap();
+        attempts.failures.remove(&key);
+        attempts.quarantined.remove(&key);
+    }
+
+    /// Runs one attempt for `input` under the attempt timeout, recording
+    /// the outcome. Quarantined inputs are rejected without running.
+    pub async fn run<T, E, F>(&self, input: &str, attempt: F) -> Result<T, PoisonError<E>>
+    where
+        E: fmt::Display,
+        F: Future<Output = Result<T, E>>,
+    {
+        if let Some(id) = self.quarantined_id(input) {
+            return Err(PoisonError::Quarantined(id));
+        }
+
+        let error = match tokio::time::timeout(self.attempt_timeout, attempt).await {
+            Ok(Ok(value)) => {
+                self.record_success(input);
+                return Ok(value);
+            }
+            Ok(Err(e)) => PoisonError::Failed(e),
+            Err(_) => PoisonError::TimedOut(self.attempt_timeout),
+        };
+        match self.record_failure(input, &error.to_string()) {
+            Some(id) => Err(PoisonError::Quarantined(id)),
+            None => Err(error),
+        }
+    }
+}
+
+fn fingerprint(input: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    input.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> PoisonTracker {
+        PoisonTracker::new(3, Duration::from_millis(20), Arc::new(Quarantine::new()))
+    }
+
+    #[test]
+    fn test_quarantined_after_max_attempts() {
+        let tracker = tracker();
+
+        assert_eq!(tracker.record_failure("\u{0}\u{0}", "parse error"), None);
+        assert_eq!(tracker.record_failure("\u{0}\u{0}", "parse error"), None);
+        let id = tracker.record_failure("\u{0}\u{0}", "timeout").unwrap();
+
+        let entry = tracker.quarantine().get(id).unwrap();
+        assert_eq!(
+            entry.reason,
+            QuarantineReason::PoisonInput {
+                attempts: 3,
+                last_error: "timeout".to_string()
+            }
+        );
+        assert_eq!(tracker.quarantined_id("\u{0}\u{0}"), Some(id));
+    }
+
+    #[test]
+    fn test_success_resets_count() {
+        let tracker = tracker();
+
+        tracker.record_failure("hello", "boom");
+        tracker.record_failure("hello", "boom");
+        tracker.record_success("hello");
+
+        assert_eq!(tracker.failures("hello"), 0);
+        assert_eq!(tracker.record_failure("hello", "boom"), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_quarantines_inputs_that_time_out() {
+        let tracker = tracker();
+        let slow = || async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok::<_, String>(())
+        };
+
+        assert!(matches!(
+            tracker.run("huge", slow()).await,
+            Err(PoisonError::TimedOut(_))
+        ));
+        tracker.run("huge", slow()).await.unwrap_err();
+        assert!(matches!(
+            tracker.run("huge", slow()).await,
+            Err(PoisonError::Quarantined(_))
+        ));
+
+        // Not attempted again
+        let result = tracker.run("huge", async { Ok::<_, String>(()) }).await;
+        assert!(matches!(result, Err(PoisonError::Quarantined(_))));
+    }
+
+    #[tokio::test]
+    async fn test_forgive() {
+        let tracker = PoisonTracker::new(1, Duration::from_secs(1), Arc::new(Quarantine::new()));
+        tracker
+            .run("x", async { Err::<(), _>("bad".to_string()) })
+            .await
+            .unwrap_err();
+
+        tracker.forgive("x");
+
+        assert_eq!(tracker.run("x", async { Ok::<_, String>(1) }).await, Ok(1));
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::i18n::text::preview;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuarantineReason {
+    PromptInjection {
+        score: f32,
+        matches: Vec<String>,
+    },
+    JudgeFlagged {
+        reason: String,
+    },
+    /// Repeatedly crashed or timed out the pipeline
+    PoisonInput {
+        attempts: u32,
+        last_error: String,
+    },
+}
+
+impl fmt::Display for QuarantineReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuarantineReason::PromptInjection { score, matches } => write!(
+                f,
+                "Prompt injection suspected (score {:.2}): {}",
+                score,
+                matches.join(", ")
+            ),
+            QuarantineReason::JudgeFlagged { reason } => {
+                write!(f, "Flagged by injection judge: {}", reason)
+            }
+            QuarantineReason::PoisonInput {
+                attempts,
+                last_error,
+            } => write!(f, "Failed {} attempts, last with: {}", attempts, last_error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuarantinedInput {
+    pub id: u64,
+    pub input: String,
+    pub reason: QuarantineReason,
+}
+
+impl QuarantinedInput {
+    /// First line-sized part of the input for review lists
+    pub fn preview(&self) -> String {
+        preview(&self.input, 80)
+    }
+}
+
+/// Holding area for inputs that must not reach an agent prompt
+#[derive(Debug, Default)]
+pub struct Quarantine {
+    entries: Mutex<Vec<QuarantinedInput>>,
+    /// Last id handed out; never reused, so a stale id cannot reach a
+    /// later entry
+    last_id: AtomicU64,
+}
+
+impl Quarantine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the input and returns its quarantine id
+    pub fn add(&self, input: &str, reason: QuarantineReason) -> u64 {
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.entries.lock().unwrap().push(QuarantinedInput {
+            id,
+            input: input.to_string(),
+            reason,
+        });
+        id
+    }
+
+    pub fn get(&self, id: u64) -> Option<QuarantinedInput> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|e| e.id == id)
+            .cloned()
+    }
+
+    /// Removes an entry, e.g. after a human has reviewed it
+    pub fn release(&self, id: u64) -> Option<QuarantinedInput> {
+        let mut entries = self.entries.lock().unwrap();
+        let position = entries.iter().position(|e| e.id == id)?;
+        Some(entries.remove(position))
+    }
+
+    pub fn entries(&self) -> Vec<QuarantinedInput> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reason() -> QuarantineReason {
+        QuarantineReason::PromptInjection {
+            score: 0.9,
+            matches: vec!["ignore previous instructions".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_add_and_get() {
+        let quarantine = Quarantine::new();
+
+        let id = quarantine.add("bad input", reason());
+
+        assert_eq!(quarantine.len(), 1);
+        let entry = quarantine.get(id).unwrap();
+        assert_eq!(entry.input, "bad input");
+        assert_eq!(entry.preview(), "bad input");
+        assert_eq!(entry.reason, reason());
+    }
+
+    #[test]
+    fn test_ids_are_sequential() {
+        let quarantine = Quarantine::new();
+
+        assert_eq!(quarantine.add("a", reason()), 1);
+        assert_eq!(quarantine.add("b", reason()), 2);
+    }
+
+    #[test]
+    fn test_released_ids_are_not_reused() {
+        let quarantine = Quarantine::new();
+        quarantine.add("a", reason());
+        let released = quarantine.add("b", reason());
+        quarantine.release(released);
+
+        let next = quarantine.add("c", reason());
+
+        assert_ne!(next, released);
+        assert!(quarantine.get(released).is_none());
+        assert!(quarantine.release(released).is_none());
+    }
+
+    #[test]
+    fn test_release() {
+        let quarantine = Quarantine::new();
+        let id = quarantine.add("bad input", reason());
+
+        assert!(quarantine.release(id).is_some());
+        assert!(quarantine.is_empty());
+        assert!(quarantine.release(id).is_none());
+    }
+
+    #[test]
+    fn test_reason_display() {
+        assert_eq!(
+            reason().to_string(),
+            "Prompt injection suspected (score 0.90): ignore previous instructions"
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::config::RecipientAnomalyConfig;
+use crate::infra::email::EmailAddress;
+
+/// Outbound history the anomaly checks compare against
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecipientHistory {
+    /// Keyed by the lowercased address; local parts are compared
+    /// case-insensitively like virtually every mail server does
+    contacted: HashMap<String, usize>,
+    recipient_counts: Vec<usize>,
+}
+
+impl RecipientHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one sent email and its recipients
+    pub fn record(&mut self, recipients: &[EmailAddress]) {
+        for recipient in recipients {
+            *self.contacted.entry(history_key(recipient)).or_insert(0) += 1;
+        }
+        self.recipient_counts.push(recipients.len());
+    }
+
+    /// Counts `address` as contacted without recording an email, as for
+    /// an address saved in the contacts
+    pub fn know(&mut self, address: &EmailAddress) {
+        self.contacted.entry(history_key(address)).or_insert(0);
+    }
+
+    pub fn has_contacted(&self, recipient: &EmailAddress) -> bool {
+        self.contacted.contains_key(&history_key(recipient))
+    }
+
+    pub fn emails_sent(&self) -> usize {
+        self.recipient_counts.len()
+    }
+
+    /// Largest recipient count seen so far
+    pub fn usual_recipient_count(&self) -> usize {
+        self.recipient_counts.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn domains(&self) -> impl Iterator<Item = &str> {
+        self.contacted
+            .keys()
+            .filter_map(|address| address.rsplit_once('@'))
+            .map(|(_, domain)| domain)
+    }
+}
+
+fn history_key(address: &EmailAddress) -> String {
+    address.to_string().to_lowercase()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecipientAnomaly {
+    FirstContact { recipient: String },
+    FreeMailDomain { recipient: String },
+    UnusualRecipientCount { count: usize, usual: usize },
+}
+
+impl fmt::Display for RecipientAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecipientAnomaly::FirstContact { recipient } => {
+                write!(f, "{} has never been contacted before", recipient)
+            }
+            RecipientAnomaly::FreeMailDomain { recipient } => write!(
+                f,
+                "{} uses a free-mail domain while history is corporate",
+                recipient
+            ),
+            RecipientAnomaly::UnusualRecipientCount { count, usual } => {
+                write!(f, "{} recipients where at most {} is usual", count, usual)
+            }
+        }
+    }
+}
+
+/// Flags outbound recipients that deserve an elevated approval
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipientAnomalyDetector {
+    free_mail_domains: Vec<String>,
+    /// History smaller than this is too thin to judge recipient counts
+    min_history: usize,
+}
+
+impl RecipientAnomalyDetector {
+    pub fn new(free_mail_domains: Vec<String>, min_history: usize) -> Self {
+        Self {
+            free_mail_domains: free_mail_domains
+                .into_iter()
+                .map(|d| d.to_lowercase())
+                .collect(),
+            min_history,
+        }
+    }
+
+    pub fn from_config(config: &RecipientAnomalyConfig) -> Self {
+        Self::new(config.free_mail_domains.clone(), config.min_history)
+    }
+
+    pub fn is_free_mail(&self, domain: &str) -> bool {
+        self.free_mail_domains.iter().any(|d| d == domain)
+    }
+
+    pub fn inspect(
+        &self,
+        history: &RecipientHistory,
+        recipients: &[EmailAddress],
+    ) -> Vec<RecipientAnomaly> {
+        let mut anomalies = Vec::new();
+
+        for recipient in recipients {
+            if !history.has_contacted(recipient) {
+                anomalies.push(RecipientAnomaly::FirstContact {
+                    recipient: recipient.to_string(),
+                });
+            }
+        }
+
+        let corporate_history =
+            history.emails_sent() > 0 && history.domains().all(|domain| !self.is_free_mail(domain));
+        if corporate_history {
+            for recipient in recipients {
+                if self.is_free_mail(recipient.domain()) {
+                    anomalies.push(RecipientAnomaly::FreeMailDomain {
+                        recipient: recipient.to_string(),
+                    });
+                }
+            }
+        }
+
+        let usual = history.usual_recipient_count();
+        if history.emails_sent() >= self.min_history && recipients.len() > usual {
+            anomalies.push(RecipientAnomaly::UnusualRecipientCount {
+                count: recipients.len(),
+                usual,
+            });
+        }
+
+        anomalies
+    }
+}
+
+impl Default for RecipientAnomalyDetector {
+    fn default() -> Self {
+        Self::from_config(&RecipientAnomalyConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> EmailAddress {
+        EmailAddress::parse(s).unwrap()
+    }
+
+    fn corporate_history() -> RecipientHistory {
+        let mut history = RecipientHistory::new();
+        for _ in 0..5 {
+            history.record(&[addr("eva@company.com"), addr("john@company.com")]);
+        }
+        history
+    }
+
+    #[test]
+    fn test_known_recipient_is_clean() {
+        let detector = RecipientAnomalyDetector::default();
+
+        let anomalies = detector.inspect(&corporate_history(), &[addr("Eva@Company.com")]);
+
+        assert!(anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_known_address_has_no_emails() {
+        let mut history = RecipientHistory::new();
+
+        history.know(&addr("eva@company.com"));
+
+        assert!(history.has_contacted(&addr("EVA@company.com")));
+        assert_eq!(history.emails_sent(), 0);
+    }
+
+    #[test]
+    fn test_first_contact() {
+        let detector = RecipientAnomalyDetector::default();
+
+        let anomalies = detector.inspect(&corporate_history(), &[addr("ana@company.com")]);
+
+        assert_eq!(
+            anomalies,
+            vec![RecipientAnomaly::FirstContact {
+                recipient: "ana@company.com".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_free_mail_when_history_is_corporate() {
+        let detector = RecipientAnomalyDetector::default();
+
+        let anomalies = detector.inspect(&corporate_history(), &[addr("eva@gmail.com")]);
+
+        assert!(anomalies.contains(&RecipientAnomaly::FreeMailDomain {
+            recipient: "eva@gmail.com".to_string()
+        }));
+    }
+
+    #[test]
+    fn test_free_mail_is_fine_when_history_has_free_mail() {
+        let detector = RecipientAnomalyDetector::default();
+        let mut history = corporate_history();
+        history.record(&[addr("friend@gmail.com")]);
+        history.record(&[addr("eva@gmail.com")]);
+
+        assert!(
+            detector
+                .inspect(&history, &[addr("eva@gmail.com")])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_unusual_recipient_count() {
+        let detector = RecipientAnomalyDetector::default();
+        let recipients = [
+            addr("eva@company.com"),
+            addr("john@company.com"),
+            addr("eva@company.com"),
+        ];
+
+        let anomalies = detector.inspect(&corporate_history(), &recipients);
+
+        assert_eq!(
+            anomalies,
+            vec![RecipientAnomaly::UnusualRecipientCount { count: 3, usual: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_thin_history_skips_count_check() {
+        let detector = RecipientAnomalyDetector::new(Vec::new(), 5);
+        let mut history = RecipientHistory::new();
+        history.record(&[addr("eva@company.com")]);
+
+        let anomalies = detector.inspect(
+            &history,
+            &[addr("eva@company.com"), addr("eva@company.com")],
+        );
+
+        assert!(anomalies.is_empty());
+    }
+}
@@ -0,0 +1,339 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::config::RedactionConfig;
+use crate::infra::email::EmailAddress;
+
+const MIN_PHONE_DIGITS: usize = 8;
+const MAX_PHONE_DIGITS: usize = 15;
+const EMAIL_CHARS: &str = ".!#$%&'*+-/=?^_`{|}~";
+const NUMBER_SEPARATORS: &str = " -.()";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    Phone,
+    NationalId,
+}
+
+impl PiiKind {
+    pub fn all() -> Vec<PiiKind> {
+        vec![PiiKind::Email, PiiKind::Phone, PiiKind::NationalId]
+    }
+
+    fn placeholder(&self) -> &'static str {
+        match self {
+            PiiKind::Email => "[REDACTED_EMAIL]",
+            PiiKind::Phone => "[REDACTED_PHONE]",
+            PiiKind::NationalId => "[REDACTED_ID]",
+        }
+    }
+}
+
+impl fmt::Display for PiiKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PiiKind::Email => write!(f, "email"),
+            PiiKind::Phone => write!(f, "phone"),
+            PiiKind::NationalId => write!(f, "national_id"),
+        }
+    }
+}
+
+/// Destinations that may receive text derived from user data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionSink {
+    /// Log lines, including the stage errors the `Redaction` middleware
+    /// masks
+    Logs,
+    /// Raw prompts and responses recorded by prompt tracing
+    PromptTrace,
+}
+
+/// A detected PII span, as byte offsets into the scanned text
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiiMatch {
+    pub kind: PiiKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Masks emails, phone numbers and national IDs (CPF, SSN) in free text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redactor {
+    kinds: Vec<PiiKind>,
+}
+
+impl Redactor {
+    pub fn new(kinds: Vec<PiiKind>) -> Self {
+        Self { kinds }
+    }
+
+    pub fn for_sink(config: &RedactionConfig, sink: RedactionSink) -> Self {
+        let kinds = match sink {
+            RedactionSink::Logs => &config.logs,
+            RedactionSink::PromptTrace => &config.prompt_trace,
+        };
+        Self::new(kinds.clone())
+    }
+
+    pub fn kinds(&self) -> &[PiiKind] {
+        &self.kinds
+    }
+
+    pub fn find(&self, text: &str) -> Vec<PiiMatch> {
+        let mut matches = Vec::new();
+        if self.kinds.contains(&PiiKind::Email) {
+            matches.extend(find_emails(text));
+        }
+        if self.kinds.contains(&PiiKind::Phone) || self.kinds.contains(&PiiKind::NationalId) {
+            let numbers: Vec<PiiMatch> = find_numbers(text)
+                .into_iter()
+                .filter(|m| self.kinds.contains(&m.kind))
+                .filter(|m| !overlaps_any(m, &matches))
+                .collect();
+            matches.extend(numbers);
+        }
+        matches.sort_by_key(|m| m.start);
+        matches
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut redacted = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for m in self.find(text) {
+            redacted.push_str(&text[cursor..m.start]);
+            redacted.push_str(m.kind.placeholder());
+            cursor = m.end;
+        }
+        redacted.push_str(&text[cursor..]);
+        redacted
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(PiiKind::all())
+    }
+}
+
+fn overlaps_any(candidate: &PiiMatch, matches: &[PiiMatch]) -> bool {
+    matches
+        .iter()
+        .any(|m| candidate.start < m.end && m.start < candidate.end)
+}
+
+fn is_email_char(c: char) -> bool {
+    c.is_alphanumeric() || EMAIL_CHARS.contains(c)
+}
+
+fn find_emails(text: &str) -> Vec<PiiMatch> {
+    let mut matches = Vec::new();
+    for (at, _) in text.match_indices('@') {
+        let start = text[..at]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_email_char(*c))
+            .last()
+            .map(|(i, _)| i)
+            .unwrap_or(at);
+        let end = text[at + 1..]
+            .char_indices()
+            .take_while(|(_, c)| c.is_alphanumeric() || *c == '.' || *c == '-')
+            .last()
+            .map(|(i, c)| at + 1 + i + c.len_utf8())
+            .unwrap_or(at + 1);
+        let end = start + text[start..end].trim_end_matches('.').len();
+
+        if start < at && EmailAddress::parse(&text[start..end]).is_ok() {
+            matches.push(PiiMatch {
+                kind: PiiKind::Email,
+                start,
+                end,
+            });
+        }
+    }
+    matches
+}
+
+fn find_numbers(text: &str) -> Vec<PiiMatch> {
+    let mut matches = Vec::new();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let starts_number = c.is_ascii_digit()
+            || ((c == '+' || c == '(')
+                && chars.get(i + 1).is_some_and(|(_, n)| n.is_ascii_digit()));
+        let preceded_by_word = i > 0 && chars[i - 1].1.is_alphanumeric();
+        if !starts_number || preceded_by_word {
+            i += 1;
+            continue;
+        }
+
+        let mut j = i + 1;
+        while j < chars.len()
+            && (chars[j].1.is_ascii_digit() || NUMBER_SEPARATORS.contains(chars[j].1))
+        {
+            j += 1;
+        }
+        // Trailing separators are not part of the number
+        while j > i + 1 && !chars[j - 1].1.is_ascii_digit() && chars[j - 1].1 != ')' {
+            j -= 1;
+        }
+        let end = chars.get(j).map(|(idx, _)| *idx).unwrap_or(text.len());
+        let followed_by_word = chars.get(j).is_some_and(|(_, n)| n.is_alphanumeric());
+
+        if !followed_by_word && let Some(kind) = classify_number(&text[start..end]) {
+            matches.push(PiiMatch { kind, start, end });
+        }
+        i = j.max(i + 1);
+    }
+    matches
+}
+
+fn classify_number(raw: &str) -> Option<PiiKind> {
+    let digits: String = raw.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    if matches_shape(raw, "###-##-####") || matches_shape(raw, "###.###.###-##") {
+        return Some(PiiKind::NationalId);
+    }
+    if raw == digits && is_valid_cpf(&digits) {
+        return Some(PiiKind::NationalId);
+    }
+    if matches_shape(raw, "####-##-##") || matches_shape(raw, "##.##.####") {
+        // Looks like a date, not a phone number
+        return None;
+    }
+    if (MIN_PHONE_DIGITS..=MAX_PHONE_DIGITS).contains(&digits.len()) {
+        return Some(PiiKind::Phone);
+    }
+    None
+}
+
+/// Checks `raw` against a shape where `#` stands for any ASCII digit
+fn matches_shape(raw: &str, shape: &str) -> bool {
+    raw.len() == shape.len()
+        && raw
+            .chars()
+            .zip(shape.chars())
+            .all(|(c, s)| if s == '#' { c.is_ascii_digit() } else { c == s })
+}
+
+/// Brazilian CPF check-digit validation
+fn is_valid_cpf(digits: &str) -> bool {
+    let numbers: Vec<u32> = digits.chars().filter_map(|c| c.to_digit(10)).collect();
+    if numbers.len() != 11 || numbers.iter().all(|n| *n == numbers[0]) {
+        return false;
+    }
+    let check = |len: usize| {
+        let sum: u32 = numbers[..len]
+            .iter()
+            .enumerate()
+            .map(|(i, n)| n * (len as u32 + 1 - i as u32))
+            .sum();
+        let rest = (sum * 10) % 11;
+        if rest == 10 { 0 } else { rest }
+    };
+    check(9) == numbers[9] && check(10) == numbers[10]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_email() {
+        let redactor = Redactor::default();
+
+        assert_eq!(
+            redactor.redact("Send it to eva.green@company.com."),
+            "Send it to [REDACTED_EMAIL]."
+        );
+    }
+
+    #[test]
+    fn test_redacts_phone_numbers() {
+        let redactor = Redactor::default();
+
+        assert_eq!(
+            redactor.redact("Call +1-555-123-4567 or (11) 98765-4321 now"),
+            "Call [REDACTED_PHONE] or [REDACTED_PHONE] now"
+        );
+    }
+
+    #[test]
+    fn test_redacts_national_ids() {
+        let redactor = Redactor::default();
+
+        assert_eq!(
+            redactor.redact("SSN 123-45-6789, CPF 529.982.247-25 / 52998224725"),
+            "SSN [REDACTED_ID], CPF [REDACTED_ID] / [REDACTED_ID]"
+        );
+    }
+
+    #[test]
+    fn test_leaves_dates_and_short_numbers() {
+        let redactor = Redactor::default();
+        let text = "Meeting on 2025-08-28 at 14:00 in room 42, order A1234567890";
+
+        assert_eq!(redactor.redact(text), text);
+    }
+
+    #[test]
+    fn test_respects_enabled_kinds() {
+        let redactor = Redactor::new(vec![PiiKind::Email]);
+
+        assert_eq!(
+            redactor.redact("eva@company.com +1-555-123-4567"),
+            "[REDACTED_EMAIL] +1-555-123-4567"
+        );
+    }
+
+    #[test]
+    fn test_for_sink_uses_sink_configuration() {
+        let config = RedactionConfig {
+            logs: vec![PiiKind::Phone],
+            prompt_trace: vec![],
+        };
+
+        let logs = Redactor::for_sink(&config, RedactionSink::Logs);
+        let trace = Redactor::for_sink(&config, RedactionSink::PromptTrace);
+
+        assert_eq!(logs.kinds(), &[PiiKind::Phone]);
+        assert_eq!(trace.redact("eva@company.com"), "eva@company.com");
+    }
+
+    #[test]
+    fn test_find_reports_offsets() {
+        let redactor = Redactor::default();
+        let matches = redactor.find("mail eva@company.com");
+
+        assert_eq!(
+            matches,
+            vec![PiiMatch {
+                kind: PiiKind::Email,
+                start: 5,
+                end: 20
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unicode_text_is_preserved() {
+        let redactor = Redactor::default();
+
+        assert_eq!(
+            redactor.redact("Olá, João! Meu e-mail é joão@café.com.br 🌍"),
+            "Olá, João! Meu e-mail é [REDACTED_EMAIL] 🌍"
+        );
+    }
+
+    #[test]
+    fn test_cpf_validation() {
+        assert!(is_valid_cpf("52998224725"));
+        assert!(!is_valid_cpf("52998224726"));
+        assert!(!is_valid_cpf("11111111111"));
+    }
+}
@@ -0,0 +1,206 @@
+// Talks to the playground API: classification arrives as a server-sent
+// event stream, sending goes through the approval gate and the outbox.
+const $ = (id) => document.getElementById(id);
+
+let classification = null;
+let planId = null;
+
+function showError(problem) {
+  $("error").textContent = problem.detail || problem.title || String(problem);
+  $("error").hidden = false;
+}
+
+// API calls need a key from [auth] api_keys; asked for once per tab and
+// again after the server turns it down
+function apiHeaders() {
+  let key = sessionStorage.getItem("apiKey");
+  if (!key) {
+    key = window.prompt("API key") || "";
+    sessionStorage.setItem("apiKey", key);
+  }
+  return { "Content-Type": "application/json", "X-Api-Key": key };
+}
+
+function forgetRejectedKey(response) {
+  if (response.status === 401) {
+    sessionStorage.removeItem("apiKey");
+  }
+}
+
+async function post(path, body) {
+  const response = await fetch(path, {
+    method: "POST",
+    headers: apiHeaders(),
+    body: JSON.stringify(body || {}),
+  });
+  forgetRejectedKey(response);
+  const json = await response.json();
+  if (!response.ok) {
+    throw json;
+  }
+  return json;
+}
+
+function onEvent(name, data) {
+  const item = document.createElement("li");
+  item.textContent = name === "status" ? data.stage : name;
+  $("events").appendChild(item);
+
+  if (name === "classification") {
+    classification = data;
+    $("result").textContent = JSON.stringify(data, null, 2);
+    $("classification").hidden = false;
+  } else if (name === "draft") {
+    $("recipient").value = data.recipient;
+    $("cc").value = (data.cc || []).join(", ");
+    $("bcc").value = (data.bcc || []).join(", ");
+    $("subject").value = data.subject;
+    $("message").value = data.message;
+    $("draft").hidden = false;
+  } else if (name === "error") {
+    showError(data);
+  }
+}
+
+// EventSource only does GET, so the stream is read from a POST response
+async function classify(input) {
+  const response = await fetch("/api/classify", {
+    method: "POST",
+    headers: apiHeaders(),
+    body: JSON.stringify({ input }),
+  });
+  forgetRejectedKey(response);
+  if (!response.ok) {
+    throw await response.json();
+  }
+  const reader = response.body.getReader();
+  const decoder = new TextDecoder();
+  let buffer = "";
+  for (;;) {
+    const { value, done } = await reader.read();
+    if (done) {
+      break;
+    }
+    buffer += decoder.decode(value, { stream: true });
+    let end;
+    while ((end = buffer.indexOf("\n\n")) >= 0) {
+      const block = buffer.slice(0, end);
+      buffer = buffer.slice(end + 2);
+      const name = block.match(/^event: (.*)$/m);
+      const data = block.match(/^data: (.*)$/m);
+      if (name && data) {
+        onEvent(name[1], JSON.parse(data[1]));
+      }
+    }
+  }
+}
+
+$("request-form").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  for (const id of ["classification", "draft", "proposal", "outbox", "error"]) {
+    $(id).hidden = true;
+  }
+  $("events").replaceChildren();
+  $("progress").hidden = false;
+  $("classify").disabled = true;
+  try {
+    await classify($("request").value);
+  } catch (problem) {
+    showError(problem);
+  } finally {
+    $("classify").disabled = false;
+  }
+});
+
+function addresses(text) {
+  return text
+    .split(",")
+    .map((address) => address.trim())
+    .filter((address) => address);
+}
+
+function showQueued(queued) {
+  $("proposal").hidden = true;
+  $("outbox-status").textContent = `Sending at ${queued.send_at} (${queued.status})`;
+  $("outbox").hidden = false;
+  $("undo").disabled = false;
+}
+
+// A plan the gate holds back is shown with what it found; nothing is
+// queued until the user approves it
+function showProposal(sent) {
+  $("proposal-summary").textContent = sent.summary;
+  $("warnings").replaceChildren(
+    ...sent.warnings.map((warning) => {
+      const item = document.createElement("li");
+      item.textContent = warning;
+      return item;
+    }),
+  );
+  $("elevated").checked = false;
+  $("elevated-label").hidden = !sent.elevated;
+  $("approve").dataset.elevated = sent.elevated ? "true" : "";
+  $("proposal").hidden = false;
+}
+
+$("send").addEventListener("click", async () => {
+  $("send").disabled = true;
+  $("error").hidden = true;
+  try {
+    const draft = {
+      recipient: $("recipient").value,
+      cc: addresses($("cc").value),
+      bcc: addresses($("bcc").value),
+      subject: $("subject").value,
+      message: $("message").value,
+    };
+    const sent = await post("/api/send", { result: classification, draft });
+    planId = sent.plan.id;
+    if (sent.outbox) {
+      showQueued(sent.outbox);
+    } else {
+      showProposal(sent);
+    }
+  } catch (problem) {
+    showError(problem);
+  } finally {
+    $("send").disabled = false;
+  }
+});
+
+$("approve").addEventListener("click", async () => {
+  const elevated = $("approve").dataset.elevated === "true";
+  if (elevated && !$("elevated").checked) {
+    showError("Tick the box to confirm the flagged recipients first");
+    return;
+  }
+  $("approve").disabled = true;
+  $("error").hidden = true;
+  try {
+    const action = elevated ? "confirm-elevated" : "confirm";
+    showQueued((await post(`/api/plans/${planId}/${action}`)).outbox);
+  } catch (problem) {
+    showError(problem);
+  } finally {
+    $("approve").disabled = false;
+  }
+});
+
+$("reject").addEventListener("click", async () => {
+  try {
+    await post(`/api/plans/${planId}/reject`);
+    $("proposal").hidden = true;
+  } catch (problem) {
+    showError(problem);
+  }
+});
+
+$("undo").addEventListener("click", async () => {
+  $("undo").disabled = true;
+  try {
+    await post(`/api/plans/${planId}/cancel`);
+    $("outbox-status").textContent = "Cancelled";
+  } catch (problem) {
+    showError(problem);
+  }
+});
@@ -0,0 +1,416 @@
+use axum::body::{Body as HttpBody, BodyDataStream, HttpBody as _};
+use axum::extract::FromRequest;
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use axum::response::IntoResponse;
+use futures_core::Stream;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::infra::http::ProblemDetails;
+use crate::infra::lifecycle::InFlightGuard;
+
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+/// Time a client gets to send the body, so a slow or idle one cannot hold
+/// a connection open
+pub const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+const TEXT: &str = "text/plain; charset=utf-8";
+
+/// A request as the routes see it, its body read in full
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn new(method: &str, path: &str) -> Self {
+        Self {
+            method: method.to_uppercase(),
+            path: path.split('?').next().unwrap_or_default().to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn with_body(mut self, body: &str) -> Self {
+        self.body = body.as_bytes().to_vec();
+        self
+    }
+
+    /// Header value; names are case-insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Reads the body of a request axum received; the error is the
+    /// response to answer with instead
+    pub async fn from_http(request: axum::extract::Request) -> Result<Self, Response> {
+        Self::from_http_within(request, READ_TIMEOUT).await
+    }
+
+    /// Like `from_http`, answering 408 when the body takes longer than
+    /// `timeout` to arrive
+    pub async fn from_http_within(
+        request: axum::extract::Request,
+        timeout: Duration,
+    ) -> Result<Self, Response> {
+        let (parts, body) = request.into_parts();
+        let mut request = Request::new(parts.method.as_str(), parts.uri.path());
+        // Headers that are not text are of no use to the routes
+        request.headers = parts
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let announced = request
+            .header("Content-Length")
+            .and_then(|length| length.parse::<usize>().ok());
+        if announced.is_some_and(|length| length > MAX_BODY_BYTES) {
+            return Err(Response::new(413, TEXT, "body too large"));
+        }
+        request.body = tokio::time::timeout(timeout, read_body(body))
+            .await
+            .map_err(|_| Response::new(408, TEXT, "client too slow"))??;
+        Ok(request)
+    }
+
+    /// The request as axum would receive it
+    #[cfg(test)]
+    pub(crate) fn into_http(self) -> axum::extract::Request {
+        let mut request = axum::http::Request::builder()
+            .method(self.method.as_str())
+            .uri(self.path.as_str());
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request.body(HttpBody::from(self.body)).unwrap()
+    }
+}
+
+impl<S: Send + Sync> FromRequest<S> for Request {
+    type Rejection = Response;
+
+    async fn from_request(request: axum::extract::Request, _: &S) -> Result<Self, Response> {
+        Self::from_http(request).await
+    }
+}
+
+/// Reads a body in full, refusing one over `MAX_BODY_BYTES` whatever its
+/// `Content-Length` said
+async fn read_body(body: HttpBody) -> Result<Vec<u8>, Response> {
+    let mut chunks = body.into_data_stream();
+    let mut body = Vec::new();
+    while let Some(chunk) = poll_fn(|cx| Pin::new(&mut chunks).poll_next(cx)).await {
+        let chunk = chunk.map_err(|e| Response::new(400, TEXT, e.to_string()))?;
+        if body.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(Response::new(413, TEXT, "body too large"));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// One server-sent event; `data` is a single line of JSON
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub data: String,
+}
+
+impl Event {
+    pub fn json<T: Serialize>(name: &str, value: &T) -> Self {
+        Self {
+            name: name.to_string(),
+            data: serde_json::to_string(value).unwrap_or_else(|_| "null".to_string()),
+        }
+    }
+
+    fn render(&self) -> String {
+        format!("event: {}\ndata: {}\n\n", self.name, self.data)
+    }
+}
+
+#[derive(Debug)]
+pub enum Body {
+    Full(Vec<u8>),
+    /// Written as `text/event-stream` while the sender is alive
+    Events(mpsc::Receiver<Event>),
+}
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Body,
+}
+
+impl Response {
+    pub fn new(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body: Body::Full(body.into()),
+        }
+    }
+
+    pub fn json<T: Serialize>(status: u16, value: &T) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(body) => Self::new(status, "application/json", body),
+            Err(e) => Self::new(500, "text/plain; charset=utf-8", e.to_string()),
+        }
+    }
+
+    /// RFC 7807 body with the problem's own headers
+    pub fn problem(problem: &ProblemDetails) -> Self {
+        Self {
+            status: problem.status,
+            headers: problem
+                .headers()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+            body: Body::Full(problem.to_json().unwrap_or_default().into_bytes()),
+        }
+    }
+
+    pub fn events(events: mpsc::Receiver<Event>) -> Self {
+        Self {
+            status: 200,
+            headers: vec![
+                ("Content-Type".to_string(), "text/event-stream".to_string()),
+                ("Cache-Control".to_string(), "no-cache".to_string()),
+            ],
+            body: Body::Events(events),
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Body of a non-streaming response as text
+    pub fn text(&self) -> Option<&str> {
+        match &self.body {
+            Body::Full(bytes) => std::str::from_utf8(bytes).ok(),
+            Body::Events(_) => None,
+        }
+    }
+
+    /// A response axum sent, its body read in full
+    #[cfg(test)]
+    pub(crate) async fn from_http(response: axum::response::Response) -> Self {
+        let (parts, body) = response.into_parts();
+        Self {
+            status: parts.status.as_u16(),
+            headers: parts
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            body: Body::Full(
+                axum::body::to_bytes(body, usize::MAX)
+                    .await
+                    .unwrap()
+                    .to_vec(),
+            ),
+        }
+    }
+
+    /// The response for axum to send
+    pub fn into_http(self) -> axum::response::Response {
+        let body = match self.body {
+            Body::Full(bytes) => HttpBody::from(bytes),
+            Body::Events(events) => HttpBody::from_stream(EventStream { events }),
+        };
+        let mut response = axum::response::Response::new(body);
+        *response.status_mut() =
+            StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) =
+                (HeaderName::try_from(name), HeaderValue::try_from(value))
+            {
+                response.headers_mut().append(name, value);
+            }
+        }
+        response
+    }
+}
+
+impl IntoResponse for Response {
+    fn into_response(self) -> axum::response::Response {
+        self.into_http()
+    }
+}
+
+/// `response` holding `in_flight` until its body is written, so a stream
+/// counts as running work while it lasts
+pub(crate) fn tracked(
+    response: axum::response::Response,
+    in_flight: InFlightGuard,
+) -> axum::response::Response {
+    if response.body().size_hint().exact().is_some() {
+        return response;
+    }
+    response.map(|body| {
+        HttpBody::from_stream(Tracked {
+            body: body.into_data_stream(),
+            _in_flight: in_flight,
+        })
+    })
+}
+
+/// Events as written to the client, ending when their sender is dropped
+struct EventStream {
+    events: mpsc::Receiver<Event>,
+}
+
+impl Stream for EventStream {
+    type Item = Result<String, Infallible>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events
+            .poll_recv(cx)
+            .map(|event| event.map(|event| Ok(event.render())))
+    }
+}
+
+/// A body being written while its request counts as in flight
+struct Tracked {
+    body: BodyDataStream,
+    _in_flight: InFlightGuard,
+}
+
+impl Stream for Tracked {
+    type Item = Result<axum::body::Bytes, axum::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.body).poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::lifecycle::Shutdown;
+    use axum::body::to_bytes;
+
+    fn received(method: &str, uri: &str, body: HttpBody) -> axum::extract::Request {
+        axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(body)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_request_with_body() {
+        let mut received = received("POST", "/api/send?x=1", HttpBody::from("{\"input\":\"a\"}"));
+        received
+            .headers_mut()
+            .insert("content-length", HeaderValue::from_static("13"));
+
+        let request = Request::from_http(received).await.unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/send");
+        assert_eq!(request.header("Content-Length"), Some("13"));
+        assert_eq!(request.body, b"{\"input\":\"a\"}");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_oversized_body() {
+        let mut announced = received("POST", "/", HttpBody::empty());
+        announced
+            .headers_mut()
+            .insert("content-length", HeaderValue::from(MAX_BODY_BYTES + 1));
+        let streamed = received("POST", "/", HttpBody::from(vec![b'a'; MAX_BODY_BYTES + 1]));
+
+        for request in [announced, streamed] {
+            let response = Request::from_http(request).await.unwrap_err();
+
+            assert_eq!(response.status, 413);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_client_times_out() {
+        let (sender, events) = mpsc::channel(1);
+        let body = HttpBody::from_stream(EventStream { events });
+
+        let response =
+            Request::from_http_within(received("POST", "/", body), Duration::from_millis(20))
+                .await
+                .unwrap_err();
+
+        assert_eq!(response.status, 408);
+        drop(sender);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_is_written_until_sender_drops() {
+        let (sender, receiver) = mpsc::channel(4);
+        sender
+            .send(Event::json(
+                "status",
+                &serde_json::json!({"stage": "classifying"}),
+            ))
+            .await
+            .unwrap();
+        drop(sender);
+
+        let response = Response::events(receiver).into_http();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            HeaderValue::from_static("text/event-stream")
+        );
+        let written = to_bytes(response.into_body(), MAX_BODY_BYTES)
+            .await
+            .unwrap();
+        assert_eq!(
+            written,
+            "event: status\ndata: {\"stage\":\"classifying\"}\n\n".as_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_is_in_flight_until_written() {
+        let shutdown = Shutdown::new(Duration::from_secs(1));
+        let (sender, receiver) = mpsc::channel(4);
+
+        let full = tracked(
+            Response::new(200, TEXT, "done").into_http(),
+            shutdown.track().unwrap(),
+        );
+        let streamed = tracked(
+            Response::events(receiver).into_http(),
+            shutdown.track().unwrap(),
+        );
+
+        assert_eq!(shutdown.in_flight(), 1);
+        drop(full);
+        drop(sender);
+        to_bytes(streamed.into_body(), MAX_BODY_BYTES)
+            .await
+            .unwrap();
+        assert_eq!(shutdown.in_flight(), 0);
+    }
+}
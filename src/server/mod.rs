@@ -0,0 +1,350 @@
+//! HTTP server for the bundled web UI and the JSON API other services
+//! call, on axum behind the `server` feature
+
+pub mod http;
+pub mod routes;
+pub mod web_ui;
+
+pub use http::{Body, Event, Request, Response};
+pub use routes::DraftView;
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::Method;
+use axum::middleware::{Next, from_fn_with_state};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::auth::{Authenticator, Credentials};
+use crate::config::Config;
+use crate::error::Error;
+use crate::infra::lifecycle::Shutdown;
+use crate::playground::Playground;
+use routes::Session;
+
+/// The playground as served over HTTP: every API request is
+/// authenticated and checked against the endpoint policy first
+pub struct Server {
+    playground: Arc<Playground>,
+    authenticator: Authenticator,
+    shutdown: Shutdown,
+}
+
+impl Server {
+    /// Server with the `[auth]` credentials from config.toml
+    pub fn new(playground: Arc<Playground>) -> Self {
+        Self {
+            playground,
+            authenticator: Authenticator::from_config(&Config::get().auth),
+            shutdown: Shutdown::default(),
+        }
+    }
+
+    pub fn with_authenticator(mut self, authenticator: Authenticator) -> Self {
+        self.authenticator = authenticator;
+        self
+    }
+
+    /// Stops `serve` from accepting once `shutdown` starts; requests still
+    /// running are tracked so it can wait for them
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    pub fn shutdown(&self) -> &Shutdown {
+        &self.shutdown
+    }
+}
+
+/// The routes behind authentication, with every request tracked by the
+/// server's shutdown
+fn app(server: Arc<Server>) -> Router {
+    routes::router(server.playground.clone())
+        .layer(from_fn_with_state(server.clone(), authenticate))
+        .layer(from_fn_with_state(server, track))
+}
+
+/// Serves the web UI and its API on `bind` until the server's shutdown
+/// starts
+pub async fn serve(server: Arc<Server>, bind: &str) -> std::io::Result<()> {
+    serve_on(server, TcpListener::bind(bind).await?).await
+}
+
+/// Like `serve`, on a listener that is already bound. Idle sessions are
+/// evicted while it serves. Once shutdown starts no connection is
+/// accepted, and it returns when the open ones have finished.
+pub async fn serve_on(server: Arc<Server>, listener: TcpListener) -> std::io::Result<()> {
+    let session_tasks = server.playground.spawn_session_tasks();
+    let shutdown = server.shutdown.clone();
+    let served = axum::serve(listener, app(server))
+        .with_graceful_shutdown(async move { shutdown.started().await })
+        .await;
+    session_tasks.iter().for_each(JoinHandle::abort);
+    served
+}
+
+/// Checks the caller's credentials against the endpoint policy. Only the
+/// web UI's own files are served without them; they carry no data. The
+/// caller's subject names their session.
+async fn authenticate(
+    State(server): State<Arc<Server>>,
+    mut request: axum::extract::Request,
+    next: Next,
+) -> axum::response::Response {
+    let (method, path) = (request.method().clone(), request.uri().path().to_string());
+    if method == Method::GET && web_ui::asset(&path).is_some() {
+        return next.run(request).await;
+    }
+    let credentials = {
+        let header = |name| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        Credentials::from_headers(header("Authorization"), header("X-Api-Key"))
+    };
+    match server
+        .authenticator
+        .check(credentials.as_ref(), method.as_str(), &path)
+    {
+        Ok(principal) => {
+            request.extensions_mut().insert(Session(principal.subject));
+            next.run(request).await
+        }
+        Err(e) => routes::error(&Error::from(e), &Request::new(method.as_str(), &path)).into_http(),
+    }
+}
+
+/// Counts the request as in flight until its response is written, or
+/// turns it away once shutdown has started
+async fn track(
+    State(server): State<Arc<Server>>,
+    request: axum::extract::Request,
+    next: Next,
+) -> axum::response::Response {
+    match server.shutdown.track() {
+        Some(in_flight) => http::tracked(next.run(request).await, in_flight),
+        None => routes::shutting_down(&Request::new(
+            request.method().as_str(),
+            request.uri().path(),
+        ))
+        .into_http(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::{IntentClassifierAgent, Params};
+    use crate::agent::{ClassificationResult, Intent};
+    use crate::auth::{EndpointPolicy, Role};
+    use crate::infra::contacts::UserContacts;
+    use crate::safety::BlastRadiusLimits;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tower::ServiceExt;
+
+    impl Server {
+        /// Answer to `request` as served
+        async fn handle(self: &Arc<Self>, request: Request) -> Response {
+            let response = app(self.clone()).oneshot(request.into_http()).await;
+            Response::from_http(response.unwrap()).await
+        }
+    }
+
+    fn server(playground: Playground) -> Arc<Server> {
+        let server = Server::new(Arc::new(playground))
+            .with_authenticator(
+                Authenticator::new(EndpointPolicy::default())
+                    .with_api_key("reader-key", "dashboard", &[Role::Reader])
+                    .with_api_key("sender-key", "eva", &[Role::Sender])
+                    .with_api_key("approver-key", "ops", &[Role::Approver]),
+            )
+            .with_shutdown(Shutdown::new(Duration::from_secs(1)));
+        Arc::new(server)
+    }
+
+    fn playground() -> Playground {
+        Playground::builder()
+            .blast_radius(BlastRadiusLimits::new(10, 3, None))
+            .contacts(Arc::new(
+                UserContacts::load_from_file("spec/contacts.json").unwrap(),
+            ))
+            .build()
+    }
+
+    fn with_key(mut request: Request, key: &str) -> Request {
+        request
+            .headers
+            .push(("X-Api-Key".to_string(), key.to_string()));
+        request
+    }
+
+    fn send_request() -> Request {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("bob@example.com".to_string(), "Running late".to_string()),
+        );
+        Request::new("POST", "/api/send")
+            .with_body(&serde_json::json!({ "result": result }).to_string())
+    }
+
+    #[tokio::test]
+    async fn test_api_needs_credentials_but_the_page_does_not() {
+        let server = server(playground());
+
+        let page = server.handle(Request::new("GET", "/")).await;
+        let anonymous = server.handle(Request::new("GET", "/api/outbox")).await;
+        let forged = server
+            .handle(with_key(Request::new("GET", "/api/outbox"), "guess"))
+            .await;
+        let reader = server
+            .handle(with_key(Request::new("GET", "/api/outbox"), "reader-key"))
+            .await;
+
+        assert_eq!(page.status, 200);
+        assert_eq!(anonymous.status, 401);
+        assert_eq!(forged.status, 401);
+        assert_eq!(reader.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_only_senders_send_and_only_approvers_confirm() {
+        let server = server(playground());
+
+        let anonymous = server.handle(send_request()).await;
+        let reader = server.handle(with_key(send_request(), "reader-key")).await;
+        let sent = server.handle(with_key(send_request(), "sender-key")).await;
+        let body: serde_json::Value = serde_json::from_str(sent.text().unwrap()).unwrap();
+        let confirm = format!("/api/plans/{}/confirm", body["plan"]["id"]);
+        let unconfirmed = [
+            server.handle(Request::new("POST", &confirm)).await,
+            server
+                .handle(with_key(Request::new("POST", &confirm), "reader-key"))
+                .await,
+            server
+                .handle(with_key(Request::new("POST", &confirm), "sender-key"))
+                .await,
+        ];
+        // bob@example.com was never contacted, so a plain confirm is not
+        // enough and the approver has to ask for elevated approval
+        let plain = server
+            .handle(with_key(Request::new("POST", &confirm), "approver-key"))
+            .await;
+        let elevate = format!("{}-elevated", confirm);
+        let sender_elevated = server
+            .handle(with_key(Request::new("POST", &elevate), "sender-key"))
+            .await;
+        let confirmed = server
+            .handle(with_key(Request::new("POST", &elevate), "approver-key"))
+            .await;
+
+        assert_eq!(anonymous.status, 401);
+        assert_eq!(reader.status, 403);
+        assert_eq!(sent.status, 201);
+        assert_eq!(body["elevated"], true);
+        assert!(
+            body["warnings"][0]
+                .as_str()
+                .unwrap()
+                .contains("bob@example.com")
+        );
+        assert_eq!(unconfirmed.map(|response| response.status), [401, 403, 403]);
+        assert_eq!(plain.status, 403);
+        assert_eq!(sender_elevated.status, 403);
+        assert_eq!(confirmed.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_sends_of_one_caller_share_a_session() {
+        let server = server(playground());
+
+        let first = server.handle(with_key(send_request(), "sender-key")).await;
+        let repeat = server.handle(with_key(send_request(), "sender-key")).await;
+        let first: serde_json::Value = serde_json::from_str(first.text().unwrap()).unwrap();
+        let repeat: serde_json::Value = serde_json::from_str(repeat.text().unwrap()).unwrap();
+
+        assert_eq!(repeat["plan"]["duplicate_of"], first["plan"]["id"]);
+        assert_eq!(
+            server
+                .playground
+                .sessions()
+                .get("eva")
+                .unwrap()
+                .turn_count(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_header_bounds_the_model_call() {
+        use crate::config::ApiConfig;
+
+        // Accepts connections and never answers, like a stuck Ollama
+        let ollama = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = ollama.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = ollama.accept().await {
+                open.push(stream);
+            }
+        });
+        let classifier = IntentClassifierAgent::default()
+            .with_specialized_extraction(false)
+            .with_api(&ApiConfig {
+                url: format!("http://{}/api/chat", address),
+                model: "llama3".to_string(),
+                ..ApiConfig::default()
+            });
+        let server = server(
+            Playground::builder()
+                .blast_radius(BlastRadiusLimits::new(10, 3, None))
+                .classifier(classifier)
+                .build(),
+        );
+        let mut request = with_key(
+            Request::new("POST", "/classify").with_body(r#"{"text": "Email Eva"}"#),
+            "reader-key",
+        );
+        request
+            .headers
+            .push(("x-request-timeout".to_string(), "100ms".to_string()));
+
+        let response = tokio::time::timeout(Duration::from_secs(5), server.handle(request))
+            .await
+            .expect("the deadline should cut the call short");
+
+        assert_eq!(response.status, 504);
+    }
+
+    #[tokio::test]
+    async fn test_serve_stops_accepting_once_shutdown_starts() {
+        let server = server(playground());
+        let shutdown = server.shutdown().clone();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let serving = tokio::spawn(serve_on(server, listener));
+
+        let mut client = tokio::net::TcpStream::connect(address).await.unwrap();
+        client
+            .write_all(b"GET /api/outbox HTTP/1.1\r\nHost: localhost\r\nX-Api-Key: reader-key\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        let report = shutdown.shutdown().await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(report.drained);
+        assert_eq!(shutdown.in_flight(), 0);
+        tokio::time::timeout(Duration::from_secs(1), serving)
+            .await
+            .expect("serve should return after shutdown")
+            .unwrap()
+            .unwrap();
+        assert!(shutdown.track().is_none());
+    }
+}
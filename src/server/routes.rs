@@ -0,0 +1,936 @@
+use axum::Router;
+use axum::extract::{Extension, Path, State};
+use axum::routing::{get, post};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::action::{
+    ActionError, ActionPlan, OutboxItem, OutboxStatus, Proposal, RecipientDelivery,
+};
+use crate::agent::classifier::{IntentDetails, SendEmailDetails};
+use crate::agent::{ClassificationResult, Intent};
+use crate::error::Error;
+use crate::infra::email::{DeliveryReport, MimeMessage};
+use crate::infra::http::{PROBLEM_TYPE_BASE, ProblemDetails, new_correlation_id, problem_for};
+use crate::infra::resilience::{DEADLINE_HEADER, Deadline};
+use crate::playground::Playground;
+use crate::server::http::{Event, Request, Response};
+use crate::server::web_ui;
+
+/// Events buffered while the browser reads the stream
+const EVENT_BUFFER: usize = 8;
+/// Seconds a client is told to wait while the server shuts down
+const RETRY_AFTER_SECS: u64 = 5;
+
+/// The composed email as shown, and edited, in the UI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DraftView {
+    pub recipient: String,
+    pub subject: String,
+    pub message: String,
+    /// Left as extracted when not sent back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cc: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bcc: Option<Vec<String>>,
+}
+
+impl DraftView {
+    pub fn from_email(email: &MimeMessage) -> Self {
+        Self {
+            recipient: email
+                .to()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            subject: email.subject().to_string(),
+            message: email.text().to_string(),
+            cc: Some(email.cc().iter().map(ToString::to_string).collect()),
+            bcc: Some(email.bcc().iter().map(ToString::to_string).collect()),
+        }
+    }
+
+    /// `result` with the user's edits in place of what was extracted;
+    /// attachments and the send time are kept
+    pub fn apply(&self, result: &ClassificationResult) -> ClassificationResult {
+        let mut edited = result.clone();
+        let mut params = result
+            .params
+            .clone()
+            .with_recipient(&self.recipient)
+            .with_subject(&self.subject)
+            .with_message(&self.message);
+        if let Some(cc) = &self.cc {
+            params = params.with_cc(&cc.iter().map(String::as_str).collect::<Vec<_>>());
+        }
+        if let Some(bcc) = &self.bcc {
+            params = params.with_bcc(&bcc.iter().map(String::as_str).collect::<Vec<_>>());
+        }
+        edited.params = params;
+        if edited.intent == Intent::SendEmail {
+            edited.details = Some(IntentDetails::SendEmail(SendEmailDetails {
+                recipient: self.recipient.clone(),
+                subject: self.subject.clone(),
+                message: self.message.clone(),
+            }));
+        }
+        edited
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClassifyRequest {
+    input: String,
+}
+
+/// Body of `POST /classify`
+#[derive(Debug, Deserialize)]
+struct TextRequest {
+    text: String,
+}
+
+/// Body of `POST /compose`: a request to classify first, or a result
+/// classified earlier
+#[derive(Debug, Deserialize)]
+struct ComposeRequest {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    result: Option<ClassificationResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendRequest {
+    result: ClassificationResult,
+    #[serde(default)]
+    draft: Option<DraftView>,
+}
+
+#[derive(Debug, Serialize)]
+struct OutboxView {
+    draft_id: u64,
+    send_at: NaiveDateTime,
+    status: OutboxStatus,
+    plan: ActionPlan,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    deliveries: Vec<RecipientDelivery>,
+}
+
+impl From<OutboxItem> for OutboxView {
+    fn from(item: OutboxItem) -> Self {
+        Self {
+            draft_id: item.draft_id,
+            send_at: item.send_at,
+            status: item.status,
+            plan: item.plan.plan().clone(),
+            message_id: item.message_id,
+            deliveries: item.deliveries,
+        }
+    }
+}
+
+/// Caller a request was authenticated as; their requests share a session
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Session(pub String);
+
+/// Routes for already authenticated requests, to the web UI assets or the
+/// JSON API:
+///
+/// - `POST /classify` `{"text"}`: the `ClassificationResult`
+/// - `POST /compose` `{"text"}` or `{"result"}`: the `ComposedEmail`
+/// - `POST /api/classify` `{"input"}`: event stream of `status`,
+///   `classification`, `draft` (emails only), `error` and `done`
+/// - `POST /api/send` `{"result", "draft"?}`: proposes the action; one
+///   that needs confirmation comes with its `summary` and `warnings`
+/// - `POST /api/plans/{id}/confirm|confirm-elevated|reject|cancel`;
+///   `confirm-elevated` also accepts anomalous recipients
+/// - `GET /api/outbox`: emails still in their undo window
+/// - `GET /api/outbox/{id}`: one email, with its delivery status per
+///   recipient once sent
+/// - `POST /api/delivery-reports` (raw message): applies a bounce or read
+///   receipt from the inbox to the email it is about
+///
+/// Requests made with a `Session` extension count as its turns, and a send
+/// repeating a recent one of the session waits for confirmation.
+pub(crate) fn router(playground: Arc<Playground>) -> Router {
+    let mut router = Router::new()
+        .route("/classify", post(classify_text))
+        .route("/compose", post(compose_text))
+        .route("/api/classify", post(classify_events))
+        .route("/api/send", post(send_result))
+        .route("/api/delivery-reports", post(delivery_report))
+        .route("/api/outbox", get(outbox))
+        .route("/api/outbox/{id}", get(outbox_item))
+        .route("/api/plans/{id}/confirm", post(confirm))
+        .route("/api/plans/{id}/confirm-elevated", post(confirm_elevated))
+        .route("/api/plans/{id}/reject", post(reject))
+        .route("/api/plans/{id}/cancel", post(cancel));
+    for path in web_ui::paths() {
+        if let Some(asset) = web_ui::asset(path) {
+            router = router.route(
+                path,
+                get(move || async move { Response::new(200, asset.content_type, asset.content) }),
+            );
+        }
+    }
+    // Axum names the allowed methods in the `Allow` header
+    router
+        .fallback(not_found)
+        .method_not_allowed_fallback(method_not_allowed)
+        .with_state(playground)
+}
+
+/// Subject of the caller, when the request was authenticated
+fn subject(session: Option<Extension<Session>>) -> Option<String> {
+    session.map(|Extension(Session(subject))| subject)
+}
+
+fn deadline(request: &Request) -> Option<Deadline> {
+    request
+        .header(DEADLINE_HEADER)
+        .and_then(Deadline::from_header)
+}
+
+async fn outbox(State(playground): State<Arc<Playground>>) -> Response {
+    let items: Vec<OutboxView> = playground
+        .outbox()
+        .sending_soon()
+        .into_iter()
+        .map(OutboxView::from)
+        .collect();
+    Response::json(200, &items)
+}
+
+async fn outbox_item(
+    State(playground): State<Arc<Playground>>,
+    Path(id): Path<String>,
+    request: Request,
+) -> Response {
+    let Ok(id) = id.parse::<u64>() else {
+        return not_found(request).await;
+    };
+    match playground.outbox().get(id) {
+        Some(item) => Response::json(200, &OutboxView::from(item)),
+        None => error(&Error::from(ActionError::PlanNotFound(id)), &request),
+    }
+}
+
+async fn classify_text(
+    State(playground): State<Arc<Playground>>,
+    session: Option<Extension<Session>>,
+    request: Request,
+) -> Response {
+    let body = match parse::<TextRequest>(&request) {
+        Ok(body) => body,
+        Err(response) => return response,
+    };
+    let session = subject(session);
+    match classify_in(
+        &playground,
+        session.as_deref(),
+        &body.text,
+        deadline(&request),
+    )
+    .await
+    {
+        Ok(result) => Response::json(200, &result),
+        Err(e) => error(&e, &request),
+    }
+}
+
+async fn compose_text(
+    State(playground): State<Arc<Playground>>,
+    session: Option<Extension<Session>>,
+    request: Request,
+) -> Response {
+    match parse::<ComposeRequest>(&request) {
+        Ok(body) => {
+            let session = subject(session);
+            compose(
+                &playground,
+                session.as_deref(),
+                body,
+                deadline(&request),
+                &request,
+            )
+            .await
+        }
+        Err(response) => response,
+    }
+}
+
+async fn classify_events(
+    State(playground): State<Arc<Playground>>,
+    session: Option<Extension<Session>>,
+    request: Request,
+) -> Response {
+    match parse::<ClassifyRequest>(&request) {
+        Ok(body) => classify(playground, subject(session), body.input, deadline(&request)),
+        Err(response) => response,
+    }
+}
+
+async fn send_result(
+    State(playground): State<Arc<Playground>>,
+    session: Option<Extension<Session>>,
+    request: Request,
+) -> Response {
+    match parse::<SendRequest>(&request) {
+        Ok(body) => {
+            let session = subject(session);
+            send(&playground, session.as_deref(), body, &request).await
+        }
+        Err(response) => response,
+    }
+}
+
+async fn confirm(
+    State(playground): State<Arc<Playground>>,
+    Path(id): Path<String>,
+    request: Request,
+) -> Response {
+    plan_action(&playground, &id, "confirm", request).await
+}
+
+/// Asked for once the anomalies were shown; the endpoint policy keeps it
+/// to roles granted elevated approval
+async fn confirm_elevated(
+    State(playground): State<Arc<Playground>>,
+    Path(id): Path<String>,
+    request: Request,
+) -> Response {
+    plan_action(&playground, &id, "confirm-elevated", request).await
+}
+
+async fn reject(
+    State(playground): State<Arc<Playground>>,
+    Path(id): Path<String>,
+    request: Request,
+) -> Response {
+    plan_action(&playground, &id, "reject", request).await
+}
+
+async fn cancel(
+    State(playground): State<Arc<Playground>>,
+    Path(id): Path<String>,
+    request: Request,
+) -> Response {
+    plan_action(&playground, &id, "cancel", request).await
+}
+
+/// Classifies `input`, as a turn of `session` if there is one
+async fn classify_in(
+    playground: &Playground,
+    session: Option<&str>,
+    input: &str,
+    deadline: Option<Deadline>,
+) -> crate::error::Result<ClassificationResult> {
+    match session {
+        Some(session) => {
+            playground
+                .classify_in_session(session, input, deadline)
+                .await
+        }
+        None => playground.classify_within(input, deadline).await,
+    }
+}
+
+fn classify(
+    playground: Arc<Playground>,
+    session: Option<String>,
+    input: String,
+    deadline: Option<Deadline>,
+) -> Response {
+    let (sender, receiver) = mpsc::channel(EVENT_BUFFER);
+    tokio::spawn(async move {
+        let stage = |stage: &str| Event::json("status", &serde_json::json!({ "stage": stage }));
+        let _ = sender.send(stage("classifying")).await;
+        let result = match classify_in(&playground, session.as_deref(), &input, deadline).await {
+            Ok(result) => result,
+            Err(e) => {
+                let _ = sender.send(Event::json("error", &problem_for(&e))).await;
+                return;
+            }
+        };
+        let _ = sender.send(Event::json("classification", &result)).await;
+
+        if result.intent == Intent::SendEmail {
+            let _ = sender.send(stage("composing")).await;
+            let event = match playground.compose(&result) {
+                Ok(email) => Event::json("draft", &DraftView::from_email(&email)),
+                Err(e) => Event::json("error", &problem_for(&e)),
+            };
+            let _ = sender.send(event).await;
+        }
+        let _ = sender
+            .send(Event::json("done", &serde_json::json!({})))
+            .await;
+    });
+    Response::events(receiver)
+}
+
+async fn compose(
+    playground: &Playground,
+    session: Option<&str>,
+    body: ComposeRequest,
+    deadline: Option<Deadline>,
+    request: &Request,
+) -> Response {
+    let result = match (body.result, body.text) {
+        (Some(result), _) => result,
+        (None, Some(text)) => match classify_in(playground, session, &text, deadline).await {
+            Ok(result) => result,
+            Err(e) => return error(&e, request),
+        },
+        (None, None) => {
+            return problem(
+                400,
+                "bad-request",
+                "Bad request",
+                "Expected \"text\" or \"result\"".to_string(),
+                request,
+            );
+        }
+    };
+    match playground.draft_within(&result, deadline).await {
+        Ok(email) => Response::json(200, &email),
+        Err(e) => error(&e, request),
+    }
+}
+
+async fn send(
+    playground: &Playground,
+    session: Option<&str>,
+    body: SendRequest,
+    request: &Request,
+) -> Response {
+    let result = match &body.draft {
+        Some(draft) => draft.apply(&body.result),
+        None => body.result,
+    };
+    let proposal = match session {
+        Some(session) => playground.send_in_session(session, &result).await,
+        None => playground.send(&result).await,
+    };
+    match proposal {
+        Ok(Proposal::NeedsConfirmation(plan)) => Response::json(
+            201,
+            &serde_json::json!({
+                "proposal": "needs_confirmation",
+                "summary": plan.summary(),
+                "warnings": warnings(&plan),
+                "elevated": plan.requires_elevated_approval(),
+                "plan": plan,
+            }),
+        ),
+        Ok(Proposal::AutoConfirmed(confirmed)) => {
+            let plan = confirmed.plan().clone();
+            let outbox = playground.outbox().get(plan.id).map(OutboxView::from);
+            Response::json(
+                201,
+                &serde_json::json!({ "proposal": "auto_confirmed", "plan": plan, "outbox": outbox }),
+            )
+        }
+        Err(e) => error(&e, request),
+    }
+}
+
+/// What the gate found about a plan, as the user should read it before
+/// approving
+fn warnings(plan: &ActionPlan) -> Vec<String> {
+    let anomalies = plan.anomalies.iter().map(ToString::to_string);
+    let flags = plan.policy_flags.iter().map(|flag| flag.detail.clone());
+    let duplicate = plan
+        .duplicate_of
+        .map(|id| format!("Repeats plan {} sent earlier in this session", id));
+    anomalies.chain(flags).chain(duplicate).collect()
+}
+
+async fn plan_action(
+    playground: &Playground,
+    id: &str,
+    action: &str,
+    request: Request,
+) -> Response {
+    let Ok(id) = id.parse::<u64>() else {
+        return not_found(request).await;
+    };
+    let outcome = match action {
+        "confirm" => playground
+            .confirm(id)
+            .await
+            .map(|item| serde_json::json!({ "outbox": OutboxView::from(item) })),
+        "confirm-elevated" => playground
+            .confirm_elevated(id)
+            .await
+            .map(|item| serde_json::json!({ "outbox": OutboxView::from(item) })),
+        "reject" => playground
+            .gate()
+            .reject(id)
+            .map(|plan| serde_json::json!({ "plan": plan }))
+            .map_err(Error::from),
+        "cancel" => playground
+            .cancel(id)
+            .map(|_| serde_json::json!({ "cancelled": id })),
+        _ => return not_found(request).await,
+    };
+    match outcome {
+        Ok(body) => Response::json(200, &body),
+        Err(e) => error(&e, &request),
+    }
+}
+
+async fn delivery_report(State(playground): State<Arc<Playground>>, request: Request) -> Response {
+    let source = String::from_utf8_lossy(&request.body);
+    let Some(report) = DeliveryReport::parse(&source) else {
+        return problem(
+            422,
+            "not-a-delivery-report",
+            "Not a delivery report",
+            "Expected a multipart/report bounce or read receipt".to_string(),
+            &request,
+        );
+    };
+    // Reports about emails this outbox never sent are accepted and ignored
+    let events = playground.outbox().apply_report(&report);
+    let draft_id = playground
+        .outbox()
+        .find_by_message_id(&report.original_message_id)
+        .map(|item| item.draft_id);
+    Response::json(
+        200,
+        &serde_json::json!({
+            "report": report,
+            "draft_id": draft_id,
+            "events": events.unwrap_or_default(),
+        }),
+    )
+}
+
+fn parse<T: DeserializeOwned>(request: &Request) -> Result<T, Response> {
+    serde_json::from_slice(&request.body)
+        .map_err(|e| problem(400, "bad-request", "Bad request", e.to_string(), request))
+}
+
+pub(super) fn error(error: &Error, request: &Request) -> Response {
+    Response::problem(&problem_for(error).with_instance(&request.path))
+}
+
+/// Answer to requests arriving after shutdown has started
+pub(super) fn shutting_down(request: &Request) -> Response {
+    let mut response = problem(
+        503,
+        "shutting-down",
+        "Shutting down",
+        "The server is shutting down; try another instance or again shortly".to_string(),
+        request,
+    );
+    response
+        .headers
+        .push(("Retry-After".to_string(), RETRY_AFTER_SECS.to_string()));
+    response
+}
+
+async fn not_found(request: Request) -> Response {
+    problem(
+        404,
+        "not-found",
+        "Not found",
+        format!("No route for {} {}", request.method, request.path),
+        &request,
+    )
+}
+
+async fn method_not_allowed(request: Request) -> Response {
+    problem(
+        405,
+        "method-not-allowed",
+        "Method not allowed",
+        format!("{} does not accept {}", request.path, request.method),
+        &request,
+    )
+}
+
+fn problem(status: u16, slug: &str, title: &str, detail: String, request: &Request) -> Response {
+    Response::problem(&ProblemDetails {
+        problem_type: format!("{}{}", PROBLEM_TYPE_BASE, slug),
+        title: title.to_string(),
+        status,
+        detail,
+        instance: Some(request.path.clone()),
+        correlation_id: new_correlation_id(),
+        retry_after: None,
+        errors: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::classifier::Params;
+    use crate::infra::contacts::UserContacts;
+    use crate::safety::BlastRadiusLimits;
+    use futures_core::Stream;
+    use std::future::poll_fn;
+    use std::pin::Pin;
+    use tower::ServiceExt;
+
+    fn playground() -> Arc<Playground> {
+        Arc::new(
+            Playground::builder()
+                .blast_radius(BlastRadiusLimits::new(10, 3, None))
+                .contacts(Arc::new(
+                    UserContacts::load_from_file("spec/contacts.json").unwrap(),
+                ))
+                .build(),
+        )
+    }
+
+    /// Answer of the router to `request`, made by `session`'s caller
+    async fn route(
+        playground: Arc<Playground>,
+        session: Option<&str>,
+        request: Request,
+    ) -> Response {
+        let mut request = request.into_http();
+        if let Some(session) = session {
+            request
+                .extensions_mut()
+                .insert(Session(session.to_string()));
+        }
+        Response::from_http(router(playground).oneshot(request).await.unwrap()).await
+    }
+
+    fn json(response: &Response) -> serde_json::Value {
+        serde_json::from_str(response.text().unwrap()).unwrap()
+    }
+
+    fn send_body(recipient: &str) -> String {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values(recipient.to_string(), "Running late".to_string()),
+        );
+        serde_json::json!({
+            "result": result,
+            "draft": {"recipient": "Tiggy", "subject": "Late", "message": "Ten minutes late"}
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_serves_the_page() {
+        let response = route(playground(), None, Request::new("GET", "/")).await;
+
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.header("Content-Type"),
+            Some("text/html; charset=utf-8")
+        );
+        assert!(
+            response
+                .text()
+                .unwrap()
+                .contains("<textarea id=\"request\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_routes_and_bad_bodies_are_problems() {
+        let missing = route(playground(), None, Request::new("GET", "/nothing")).await;
+        let bad = route(
+            playground(),
+            None,
+            Request::new("POST", "/api/send").with_body("{not json"),
+        )
+        .await;
+
+        assert_eq!(missing.status, 404);
+        assert_eq!(bad.status, 400);
+        assert_eq!(
+            json(&bad)["type"],
+            "urn:ollama-email-agent:problem:bad-request"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wrong_methods_are_not_allowed() {
+        let deleted = route(playground(), None, Request::new("DELETE", "/api/outbox/1")).await;
+        let fetched = route(playground(), None, Request::new("GET", "/classify")).await;
+        let posted = route(playground(), None, Request::new("POST", "/api/outbox")).await;
+
+        assert_eq!(deleted.status, 405);
+        assert_eq!(deleted.header("Allow"), Some("GET,HEAD"));
+        assert_eq!(
+            json(&deleted)["type"],
+            "urn:ollama-email-agent:problem:method-not-allowed"
+        );
+        assert_eq!(fetched.status, 405);
+        assert_eq!(fetched.header("Allow"), Some("POST"));
+        assert_eq!(posted.status, 405);
+        assert_eq!(posted.header("Allow"), Some("GET,HEAD"));
+    }
+
+    #[tokio::test]
+    async fn test_classify_and_compose_endpoints() {
+        use crate::agent::classifier::{IntentClassifierAgent, KeywordClassifier};
+        use crate::i18n::Locale;
+
+        let playground = Arc::new(
+            Playground::builder()
+                .blast_radius(BlastRadiusLimits::new(10, 3, None))
+                .classifier(IntentClassifierAgent::default().with_keyword_classifier(
+                    KeywordClassifier::new().with_keyword(
+                        Locale::En,
+                        Intent::NoAction,
+                        "never mind",
+                    ),
+                ))
+                .build(),
+        );
+
+        let classified = route(
+            playground.clone(),
+            None,
+            Request::new("POST", "/classify").with_body(r#"{"text": "Oh, never mind"}"#),
+        )
+        .await;
+        let nothing_to_compose = route(
+            playground.clone(),
+            None,
+            Request::new("POST", "/compose").with_body(r#"{"text": "Oh, never mind"}"#),
+        )
+        .await;
+        let empty = route(
+            playground,
+            None,
+            Request::new("POST", "/compose").with_body("{}"),
+        )
+        .await;
+
+        assert_eq!(classified.status, 200);
+        assert_eq!(json(&classified)["intent"], "no_action");
+        assert!(nothing_to_compose.status >= 400);
+        assert_eq!(empty.status, 400);
+    }
+
+    #[tokio::test]
+    async fn test_edited_draft_is_sent_confirmed_and_undone() {
+        let playground = playground();
+
+        let sent = route(
+            playground.clone(),
+            None,
+            Request::new("POST", "/api/send").with_body(&send_body("bob@example.com")),
+        )
+        .await;
+        let plan = json(&sent)["plan"].clone();
+        let id = plan["id"].as_u64().unwrap();
+        let confirmed = route(
+            playground.clone(),
+            None,
+            Request::new("POST", &format!("/api/plans/{}/confirm", id)),
+        )
+        .await;
+        let outbox = route(playground.clone(), None, Request::new("GET", "/api/outbox")).await;
+        let cancelled = route(
+            playground.clone(),
+            None,
+            Request::new("POST", &format!("/api/plans/{}/cancel", id)),
+        )
+        .await;
+
+        assert_eq!(sent.status, 201);
+        assert_eq!(json(&sent)["proposal"], "needs_confirmation");
+        assert_eq!(plan["content"], "Ten minutes late");
+        assert_eq!(confirmed.status, 200);
+        assert_eq!(json(&confirmed)["outbox"]["status"], "sending_soon");
+        assert_eq!(json(&outbox)[0]["draft_id"], id);
+        assert_eq!(cancelled.status, 200);
+        assert_eq!(
+            route(
+                playground,
+                None,
+                Request::new("POST", &format!("/api/plans/{}/confirm", id))
+            )
+            .await
+            .status,
+            409
+        );
+    }
+
+    #[tokio::test]
+    async fn test_plan_over_the_limits_is_rejected_and_not_queued() {
+        use crate::safety::SendingWindow;
+
+        let playground = Arc::new(
+            Playground::builder()
+                .blast_radius(BlastRadiusLimits::new(
+                    10,
+                    3,
+                    Some(SendingWindow::new(7, 22)),
+                ))
+                .auto_confirm(vec![Intent::SendEmail])
+                .build(),
+        );
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("bob@example.com".to_string(), "Running late".to_string())
+                .with_send_at("2099-01-05T03:00:00"),
+        );
+
+        let sent = route(
+            playground.clone(),
+            None,
+            Request::new("POST", "/api/send")
+                .with_body(&serde_json::json!({ "result": result }).to_string()),
+        )
+        .await;
+        let outbox = route(playground.clone(), None, Request::new("GET", "/api/outbox")).await;
+
+        assert_eq!(sent.status, 422);
+        assert_eq!(
+            json(&sent)["type"],
+            "urn:ollama-email-agent:problem:outside-sending-hours"
+        );
+        assert_eq!(json(&outbox), serde_json::json!([]));
+        assert!(playground.gate().plans().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_report_updates_sent_email() {
+        let playground = playground();
+        let sent = route(
+            playground.clone(),
+            None,
+            Request::new("POST", "/api/send").with_body(&send_body("bob@example.com")),
+        )
+        .await;
+        let id = json(&sent)["plan"]["id"].as_u64().unwrap();
+        playground.confirm(id).await.unwrap();
+        playground
+            .outbox()
+            .record_sent(
+                id,
+                "18f3a.0001@example.com",
+                &[crate::infra::email::EmailAddress::parse("bob@example.com").unwrap()],
+            )
+            .unwrap();
+        let bounce = "Content-Type: multipart/report; report-type=delivery-status; boundary=b\n\n\
+--b\nContent-Type: message/delivery-status\n\nReporting-MTA: dns; mx.example.com\n\n\
+Final-Recipient: rfc822; bob@example.com\nAction: failed\nStatus: 5.1.1\n\
+--b\nContent-Type: text/rfc822-headers\n\nMessage-ID: <18f3a.0001@example.com>\n--b--\n";
+
+        let applied = route(
+            playground.clone(),
+            None,
+            Request::new("POST", "/api/delivery-reports").with_body(bounce),
+        )
+        .await;
+        let item = route(
+            playground.clone(),
+            None,
+            Request::new("GET", &format!("/api/outbox/{}", id)),
+        )
+        .await;
+        let rejected = route(
+            playground,
+            None,
+            Request::new("POST", "/api/delivery-reports").with_body("Subject: Hi\n\nHello"),
+        )
+        .await;
+
+        assert_eq!(applied.status, 200);
+        assert_eq!(json(&applied)["draft_id"], id);
+        assert_eq!(json(&applied)["events"][0]["status"], "bounced");
+        assert_eq!(json(&item)["deliveries"][0]["status"], "bounced");
+        assert_eq!(json(&item)["deliveries"][0]["code"], "5.1.1");
+        assert_eq!(rejected.status, 422);
+    }
+
+    #[tokio::test]
+    async fn test_classify_streams_events() {
+        let response = router(playground())
+            .oneshot(
+                Request::new("POST", "/api/classify")
+                    .with_body(r#"{"input": "Email Eva"}"#)
+                    .into_http(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let mut body = response.into_body().into_data_stream();
+        let first = poll_fn(|cx| Pin::new(&mut body).poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            first,
+            "event: status\ndata: {\"stage\":\"classifying\"}\n\n".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_draft_edits_replace_extracted_fields() {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva".to_string(), "Late".to_string()).with_send_at("tomorrow"),
+        );
+        let draft = DraftView {
+            recipient: "eva@company.com".to_string(),
+            subject: "Running late".to_string(),
+            message: "I'll be ten minutes late".to_string(),
+            cc: None,
+            bcc: None,
+        };
+
+        let edited = draft.apply(&result);
+
+        assert_eq!(edited.params.recipient(), Some("eva@company.com"));
+        assert_eq!(edited.params.subject(), Some("Running late"));
+        assert_eq!(edited.params.send_at(), Some("tomorrow"));
+        assert!(matches!(
+            edited.details,
+            Some(IntentDetails::SendEmail(details)) if details.subject == "Running late"
+        ));
+    }
+
+    #[test]
+    fn test_draft_edits_keep_copies_and_attachments() {
+        let result = ClassificationResult::new(
+            Intent::SendEmail,
+            Params::with_values("eva".to_string(), "Report attached".to_string())
+                .with_cc(&["bruno@company.com"])
+                .with_bcc(&["archive@company.com"])
+                .with_attachments(&["the Q3 report"]),
+        );
+        let untouched = DraftView {
+            recipient: "eva@company.com".to_string(),
+            subject: "Q3".to_string(),
+            message: "Here is the Q3 report".to_string(),
+            cc: None,
+            bcc: None,
+        };
+        let recopied = DraftView {
+            cc: Some(vec!["carla@company.com".to_string()]),
+            bcc: Some(Vec::new()),
+            ..untouched.clone()
+        };
+
+        let edited = untouched.apply(&result);
+        let changed = recopied.apply(&result);
+
+        assert_eq!(edited.params.message(), Some("Here is the Q3 report"));
+        assert_eq!(edited.params.cc(), ["bruno@company.com"]);
+        assert_eq!(edited.params.bcc(), ["archive@company.com"]);
+        assert_eq!(edited.params.attachments(), ["the Q3 report"]);
+        assert_eq!(changed.params.cc(), ["carla@company.com"]);
+        assert!(changed.params.bcc().is_empty());
+        assert_eq!(changed.params.attachments(), ["the Q3 report"]);
+    }
+}
@@ -0,0 +1,53 @@
+/// A file of the bundled web UI, compiled into the binary
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Asset {
+    pub path: &'static str,
+    pub content_type: &'static str,
+    pub content: &'static str,
+}
+
+const ASSETS: &[Asset] = &[
+    Asset {
+        path: "/",
+        content_type: "text/html; charset=utf-8",
+        content: include_str!("assets/index.html"),
+    },
+    Asset {
+        path: "/app.js",
+        content_type: "text/javascript; charset=utf-8",
+        content: include_str!("assets/app.js"),
+    },
+    Asset {
+        path: "/style.css",
+        content_type: "text/css; charset=utf-8",
+        content: include_str!("assets/style.css"),
+    },
+];
+
+/// Asset served at `path`; `/index.html` is the page itself
+pub fn asset(path: &str) -> Option<&'static Asset> {
+    let path = if path == "/index.html" { "/" } else { path };
+    ASSETS.iter().find(|asset| asset.path == path)
+}
+
+/// Every path an asset is served at
+pub fn paths() -> impl Iterator<Item = &'static str> {
+    ASSETS.iter().map(|asset| asset.path).chain(["/index.html"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_references_bundled_assets() {
+        let page = asset("/index.html").unwrap();
+
+        assert_eq!(page, asset("/").unwrap());
+        for path in ["/app.js", "/style.css"] {
+            assert!(page.content.contains(path.trim_start_matches('/')));
+            assert!(asset(path).is_some());
+        }
+        assert_eq!(asset("/../config.toml"), None);
+    }
+}
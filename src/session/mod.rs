@@ -0,0 +1,10 @@
+#[allow(clippy::module_inception)]
+pub mod session;
+pub mod session_event;
+pub mod session_policy;
+pub mod session_store;
+
+pub use session::Session;
+pub use session_event::{EvictionReason, SessionEvent};
+pub use session_policy::SessionPolicy;
+pub use session_store::{SessionStore, spawn_eviction_task};
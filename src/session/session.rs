@@ -0,0 +1,80 @@
+use std::time::Instant;
+
+/// Per-user conversation state kept between turns
+#[derive(Debug, Clone)]
+pub struct Session {
+    user_id: String,
+    created_at: Instant,
+    last_active: Instant,
+    turns: Vec<String>,
+}
+
+impl Session {
+    pub fn new(user_id: String) -> Self {
+        Self::started_at(user_id, Instant::now())
+    }
+
+    pub fn started_at(user_id: String, now: Instant) -> Self {
+        Self {
+            user_id,
+            created_at: now,
+            last_active: now,
+            turns: Vec::new(),
+        }
+    }
+
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    pub fn created_at(&self) -> Instant {
+        self.created_at
+    }
+
+    pub fn last_active(&self) -> Instant {
+        self.last_active
+    }
+
+    pub fn turns(&self) -> &[String] {
+        &self.turns
+    }
+
+    pub fn turn_count(&self) -> usize {
+        self.turns.len()
+    }
+
+    /// Appends a user turn and refreshes the activity timestamp
+    pub fn push_turn(&mut self, input: String, now: Instant) {
+        self.turns.push(input);
+        self.last_active = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_new_session_is_empty() {
+        let session = Session::new("user-1".to_string());
+
+        assert_eq!(session.user_id(), "user-1");
+        assert_eq!(session.turn_count(), 0);
+        assert_eq!(session.created_at(), session.last_active());
+    }
+
+    #[test]
+    fn test_push_turn_updates_last_active() {
+        let start = Instant::now();
+        let mut session = Session::started_at("user-1".to_string(), start);
+        let later = start + Duration::from_secs(5);
+
+        session.push_turn("Envie um e-mail para Eva".to_string(), later);
+
+        assert_eq!(session.turn_count(), 1);
+        assert_eq!(session.turns()[0], "Envie um e-mail para Eva");
+        assert_eq!(session.last_active(), later);
+        assert_eq!(session.created_at(), start);
+    }
+}
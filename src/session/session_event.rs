@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionReason {
+    IdleTimeout,
+    MaxTurns,
+    Capacity,
+}
+
+impl fmt::Display for EvictionReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvictionReason::IdleTimeout => write!(f, "idle_timeout"),
+            EvictionReason::MaxTurns => write!(f, "max_turns"),
+            EvictionReason::Capacity => write!(f, "capacity"),
+        }
+    }
+}
+
+/// Emitted by the session store whenever a session leaves it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    Expired {
+        user_id: String,
+        reason: EvictionReason,
+        turns: usize,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eviction_reason_display() {
+        assert_eq!(EvictionReason::IdleTimeout.to_string(), "idle_timeout");
+        assert_eq!(EvictionReason::MaxTurns.to_string(), "max_turns");
+        assert_eq!(EvictionReason::Capacity.to_string(), "capacity");
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use crate::config::SessionConfig;
+
+/// Shortest eviction interval; a zero interval would make the eviction
+/// task panic
+pub const MIN_EVICTION_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Limits applied by the session store to keep per-user state bounded
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionPolicy {
+    pub idle_ttl: Duration,
+    pub max_sessions: usize,
+    pub max_turns: usize,
+    pub eviction_interval: Duration,
+}
+
+impl SessionPolicy {
+    pub fn new(
+        idle_ttl: Duration,
+        max_sessions: usize,
+        max_turns: usize,
+        eviction_interval: Duration,
+    ) -> Self {
+        Self {
+            idle_ttl,
+            max_sessions,
+            max_turns,
+            eviction_interval: eviction_interval.max(MIN_EVICTION_INTERVAL),
+        }
+    }
+
+    pub fn from_config(config: &SessionConfig) -> Self {
+        // Whole seconds in config, so 0 becomes 1s rather than a busy loop
+        Self::new(
+            Duration::from_secs(config.idle_ttl_secs),
+            config.max_sessions,
+            config.max_turns,
+            Duration::from_secs(config.eviction_interval_secs.max(1)),
+        )
+    }
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        Self::from_config(&SessionConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config() {
+        let config = SessionConfig {
+            idle_ttl_secs: 10,
+            max_sessions: 2,
+            max_turns: 3,
+            eviction_interval_secs: 1,
+        };
+        let policy = SessionPolicy::from_config(&config);
+
+        assert_eq!(policy.idle_ttl, Duration::from_secs(10));
+        assert_eq!(policy.max_sessions, 2);
+        assert_eq!(policy.max_turns, 3);
+        assert_eq!(policy.eviction_interval, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_zero_eviction_interval_is_clamped() {
+        let config = SessionConfig {
+            eviction_interval_secs: 0,
+            ..SessionConfig::default()
+        };
+        let policy = SessionPolicy::new(Duration::from_secs(1), 1, 1, Duration::ZERO);
+
+        assert_eq!(
+            SessionPolicy::from_config(&config).eviction_interval,
+            Duration::from_secs(1)
+        );
+        assert_eq!(policy.eviction_interval, MIN_EVICTION_INTERVAL);
+    }
+
+    #[test]
+    fn test_default_matches_default_config() {
+        let policy = SessionPolicy::default();
+
+        assert_eq!(policy.idle_ttl, Duration::from_secs(1800));
+        assert_eq!(policy.max_sessions, 1000);
+        assert_eq!(policy.max_turns, 50);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::session::{
+    EvictionReason, Session, SessionEvent, SessionPolicy, session_policy::MIN_EVICTION_INTERVAL,
+};
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// In-memory store of per-user sessions enforcing a `SessionPolicy`
+pub struct SessionStore {
+    policy: SessionPolicy,
+    sessions: Mutex<HashMap<String, Session>>,
+    events: broadcast::Sender<SessionEvent>,
+}
+
+impl SessionStore {
+    pub fn new(policy: SessionPolicy) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            policy,
+            sessions: Mutex::new(HashMap::new()),
+            events,
+        }
+    }
+
+    pub fn policy(&self) -> &SessionPolicy {
+        &self.policy
+    }
+
+    /// Subscribes to expiry events emitted by this store
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, user_id: &str) -> Option<Session> {
+        self.sessions.lock().unwrap().get(user_id).cloned()
+    }
+
+    /// Records a user turn, creating the session if needed, and returns the
+    /// session state after the turn.
+    pub fn record_turn(&self, user_id: &str, input: &str) -> Session {
+        self.record_turn_at(user_id, input, Instant::now())
+    }
+
+    pub fn record_turn_at(&self, user_id: &str, input: &str, now: Instant) -> Session {
+        let mut expired = Vec::new();
+        let session = {
+            let mut sessions = self.sessions.lock().unwrap();
+
+            if !sessions.contains_key(user_id) {
+                while self.policy.max_sessions > 0 && sessions.len() >= self.policy.max_sessions {
+                    match least_recently_active(&sessions) {
+                        Some(oldest) => {
+                            let session = sessions.remove(&oldest).unwrap();
+                            expired.push((session, EvictionReason::Capacity));
+                        }
+                        None => break,
+                    }
+                }
+                sessions.insert(
+                    user_id.to_string(),
+                    Session::started_at(user_id.to_string(), now),
+                );
+            }
+
+            let session = sessions.get_mut(user_id).unwrap();
+            session.push_turn(input.to_string(), now);
+            let snapshot = session.clone();
+
+            if self.policy.max_turns > 0 && snapshot.turn_count() >= self.policy.max_turns {
+                let session = sessions.remove(user_id).unwrap();
+                expired.push((session, EvictionReason::MaxTurns));
+            }
+            snapshot
+        };
+
+        self.emit_expired(expired);
+        session
+    }
+
+    /// Removes every session idle for longer than the policy TTL
+    pub fn evict_expired(&self) -> Vec<String> {
+        self.evict_expired_at(Instant::now())
+    }
+
+    pub fn evict_expired_at(&self, now: Instant) -> Vec<String> {
+        let expired: Vec<(Session, EvictionReason)> = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let idle: Vec<String> = sessions
+                .values()
+                .filter(|s| now.saturating_duration_since(s.last_active()) >= self.policy.idle_ttl)
+                .map(|s| s.user_id().to_string())
+                .collect();
+            idle.iter()
+                .filter_map(|id| sessions.remove(id))
+                .map(|s| (s, EvictionReason::IdleTimeout))
+                .collect()
+        };
+
+        let ids = expired
+            .iter()
+            .map(|(s, _)| s.user_id().to_string())
+            .collect();
+        self.emit_expired(expired);
+        ids
+    }
+
+    fn emit_expired(&self, expired: Vec<(Session, EvictionReason)>) {
+        for (session, reason) in expired {
+            // No subscribers is not an error; the event is simply dropped.
+            let _ = self.events.send(SessionEvent::Expired {
+                user_id: session.user_id().to_string(),
+                reason,
+                turns: session.turn_count(),
+            });
+        }
+    }
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new(SessionPolicy::default())
+    }
+}
+
+fn least_recently_active(sessions: &HashMap<String, Session>) -> Option<String> {
+    sessions
+        .values()
+        .min_by_key(|s| s.last_active())
+        .map(|s| s.user_id().to_string())
+}
+
+/// Spawns a background task evicting idle sessions every `eviction_interval`
+pub fn spawn_eviction_task(store: Arc<SessionStore>) -> JoinHandle<()> {
+    // The fields are public, so the policy may not have gone through `new`
+    let interval = store.policy().eviction_interval.max(MIN_EVICTION_INTERVAL);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            store.evict_expired();
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn policy(idle_secs: u64, max_sessions: usize, max_turns: usize) -> SessionPolicy {
+        SessionPolicy::new(
+            Duration::from_secs(idle_secs),
+            max_sessions,
+            max_turns,
+            Duration::from_millis(10),
+        )
+    }
+
+    #[test]
+    fn test_record_turn_creates_session() {
+        let store = SessionStore::new(policy(60, 10, 10));
+
+        let session = store.record_turn("alice", "hello");
+
+        assert_eq!(session.turn_count(), 1);
+        assert_eq!(store.len(), 1);
+        assert!(store.get("alice").is_some());
+    }
+
+    #[test]
+    fn test_idle_sessions_are_evicted() {
+        let store = SessionStore::new(policy(60, 10, 10));
+        let start = Instant::now();
+        store.record_turn_at("alice", "hello", start);
+        store.record_turn_at("bob", "hi", start + Duration::from_secs(50));
+        let mut events = store.subscribe();
+
+        let evicted = store.evict_expired_at(start + Duration::from_secs(61));
+
+        assert_eq!(evicted, vec!["alice".to_string()]);
+        assert_eq!(store.len(), 1);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SessionEvent::Expired {
+                user_id: "alice".to_string(),
+                reason: EvictionReason::IdleTimeout,
+                turns: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_max_turns_ends_session() {
+        let store = SessionStore::new(policy(60, 10, 2));
+        let mut events = store.subscribe();
+
+        store.record_turn("alice", "one");
+        let session = store.record_turn("alice", "two");
+
+        assert_eq!(session.turn_count(), 2);
+        assert!(store.is_empty());
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SessionEvent::Expired {
+                user_id: "alice".to_string(),
+                reason: EvictionReason::MaxTurns,
+                turns: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_capacity_evicts_least_recently_active() {
+        let store = SessionStore::new(policy(60, 2, 10));
+        let start = Instant::now();
+        let mut events = store.subscribe();
+
+        store.record_turn_at("alice", "a", start);
+        store.record_turn_at("bob", "b", start + Duration::from_secs(1));
+        store.record_turn_at("alice", "a2", start + Duration::from_secs(2));
+        store.record_turn_at("carol", "c", start + Duration::from_secs(3));
+
+        assert_eq!(store.len(), 2);
+        assert!(store.get("bob").is_none());
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            SessionEvent::Expired {
+                reason: EvictionReason::Capacity,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_eviction_task_removes_idle_sessions() {
+        let store = Arc::new(SessionStore::new(SessionPolicy::new(
+            Duration::from_millis(1),
+            10,
+            10,
+            Duration::from_millis(5),
+        )));
+        store.record_turn("alice", "hello");
+
+        let handle = spawn_eviction_task(store.clone());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.abort();
+
+        assert!(store.is_empty());
+    }
+}